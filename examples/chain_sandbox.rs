@@ -0,0 +1,41 @@
+//! Skips the menus and drops straight into the sandbox, to try out chains
+//! without a level in the way.
+//!
+//! ```sh
+//! cargo run --example chain_sandbox
+//! ```
+
+use bevy::prelude::*;
+use hooked::{game::sandbox::Sandbox, screens::Screen};
+
+const INSTRUCTIONS: &str = "Aim with the mouse, left click fires a chain and right click lets go.\n\
+    Mouse back and forward reel in and out. The panel on the side sets up the arena.";
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins(hooked::AppPlugin)
+        .add_systems(OnEnter(Screen::Title), enter_sandbox)
+        .add_systems(OnEnter(Screen::Gameplay), spawn_instructions)
+        .run()
+}
+
+/// Go past the title screen as soon as it's reached.
+fn enter_sandbox(mut sandbox: ResMut<Sandbox>, mut next_screen: ResMut<NextState<Screen>>) {
+    sandbox.active = true;
+    next_screen.set(Screen::Gameplay);
+}
+
+fn spawn_instructions(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Instructions"),
+        Text::new(INSTRUCTIONS),
+        TextFont::from_font_size(16.0),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.0),
+            bottom: Val::Px(12.0),
+            ..default()
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
@@ -0,0 +1,341 @@
+//! Golden images of the bundled levels, for catching layout regressions.
+//!
+//! Every level in `assets/levels` is loaded and spawned the way the game does
+//! it, rendered offscreen with Bevy and compared to the golden stored in
+//! `goldens/<level>.png`. Levels that don't match get a
+//! `goldens/<level>.diff.png` with the differing pixels in magenta, and the
+//! example exits with an error. `--update` overwrites the goldens with the
+//! current renders instead, once a change is known to be right.
+//!
+//! ```sh
+//! cargo run --example level_goldens
+//! cargo run --example level_goldens -- --update
+//! ```
+//!
+//! No window is opened. Without a GPU, Mesa's software renderer does the job:
+//!
+//! ```sh
+//! WGPU_BACKEND=gl LIBGL_ALWAYS_SOFTWARE=1 cargo run --example level_goldens
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::{RenderAssetUsages, io::file::FileAssetReader},
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        view::screenshot::{Screenshot, ScreenshotCaptured},
+    },
+};
+use hooked::{
+    AppConfig,
+    game::{
+        level::{LevelBounds, MAIN_LEVEL_PATH, spawn_level},
+        level_data::{LEVEL_EXTENSIONS, LevelData},
+    },
+    screens::Screen,
+};
+
+const GOLDEN_DIR: &str = "goldens";
+const GOLDEN_SIZE: UVec2 = UVec2::new(640, 360);
+/// Empty space around the level, in world units.
+const GOLDEN_MARGIN: f32 = 40.0;
+const BACKGROUND: Color = Color::srgb(0.1, 0.1, 0.12);
+/// Frames to let a spawned level settle before it's captured.
+const SETTLE_FRAMES: u32 = 5;
+/// How far a channel may be off before a pixel counts as different, to allow
+/// for rounding.
+const TOLERANCE: u8 = 2;
+const DIFF_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+fn main() -> AppExit {
+    let update = std::env::args().any(|arg| arg == "--update");
+    App::new()
+        .add_plugins(
+            AppConfig::from_env()
+                .audio(false)
+                .dev_tools(false)
+                .net(false)
+                .headless(true)
+                .offscreen(true),
+        )
+        .insert_resource(Goldens {
+            update,
+            ..default()
+        })
+        .add_systems(OnEnter(Screen::Title), start)
+        .add_systems(Update, step.run_if(in_state(Screen::Title)))
+        .run()
+}
+
+#[derive(Resource, Default)]
+struct Goldens {
+    update: bool,
+    /// Level file names still to go, last first.
+    queue: Vec<String>,
+    /// The level file being rendered, and how far along it is.
+    current: Option<(String, Stage)>,
+    failures: usize,
+    /// What the levels are rendered into.
+    image: Handle<Image>,
+}
+
+enum Stage {
+    Loading(Handle<LevelData>),
+    /// Spawned, with the frames left until it's captured.
+    Settling(u32),
+    Capturing,
+}
+
+#[derive(Component)]
+struct GoldenCamera;
+
+/// Once everything's loaded, freeze time so each level is captured as it
+/// spawns, and set up the camera that renders them.
+fn start(
+    mut commands: Commands,
+    mut goldens: ResMut<Goldens>,
+    mut time: ResMut<Time<Virtual>>,
+    mut images: ResMut<Assets<Image>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    time.pause();
+
+    let levels = FileAssetReader::get_base_path().join("assets/levels");
+    let mut queue: Vec<_> = std::fs::read_dir(&levels)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| LEVEL_EXTENSIONS.iter().any(|ext| name.ends_with(ext)))
+        .collect();
+    if queue.is_empty() {
+        error!("There are no levels in {}", levels.display());
+        app_exit.write(AppExit::error());
+        return;
+    }
+    queue.sort();
+    queue.reverse();
+    goldens.queue = queue;
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: GOLDEN_SIZE.x,
+            height: GOLDEN_SIZE.y,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::RENDER_ATTACHMENT;
+    goldens.image = images.add(image);
+    commands.spawn((
+        Name::new("Golden Camera"),
+        GoldenCamera,
+        Camera2d,
+        Camera {
+            target: RenderTarget::Image(goldens.image.clone().into()),
+            clear_color: ClearColorConfig::Custom(BACKGROUND),
+            ..default()
+        },
+    ));
+}
+
+/// Take the levels one at a time: load, spawn, frame, capture.
+fn step(
+    mut commands: Commands,
+    mut goldens: ResMut<Goldens>,
+    asset_server: Res<AssetServer>,
+    mut levels: ResMut<Assets<LevelData>>,
+    bounds: Res<LevelBounds>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<GoldenCamera>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let goldens = &mut *goldens;
+    let Some((name, stage)) = &mut goldens.current else {
+        if let Some(name) = goldens.queue.pop() {
+            let handle = asset_server.load(format!("levels/{name}"));
+            goldens.current = Some((name, Stage::Loading(handle)));
+        } else if goldens.failures > 0 {
+            error!("Levels not matching their goldens: {}", goldens.failures);
+            app_exit.write(AppExit::error());
+        } else {
+            app_exit.write(AppExit::Success);
+        }
+        return;
+    };
+
+    match stage {
+        Stage::Loading(handle) => {
+            if asset_server.load_state(&*handle).is_failed() {
+                error!("{name}: could not be loaded");
+                goldens.failures += 1;
+                goldens.current = None;
+                return;
+            }
+            let Some(level) = levels.get(&*handle).cloned() else {
+                return;
+            };
+            // `spawn_level` spawns the main level, so this one stands in for it.
+            let main_level = asset_server.load::<LevelData>(MAIN_LEVEL_PATH);
+            levels.insert(main_level.id(), level);
+            commands.run_system_cached(spawn_level);
+            *stage = Stage::Settling(SETTLE_FRAMES);
+        }
+        Stage::Settling(SETTLE_FRAMES) => {
+            // `spawn_level` has run by now, and has set the bounds.
+            if let Ok((mut transform, mut projection)) = camera_query.single_mut() {
+                let (center, viewport) = frame(bounds.0.inflate(GOLDEN_MARGIN));
+                transform.translation = center.extend(0.0);
+                *projection = Projection::from(OrthographicProjection {
+                    scaling_mode: ScalingMode::Fixed {
+                        width: viewport.x,
+                        height: viewport.y,
+                    },
+                    ..OrthographicProjection::default_2d()
+                });
+            }
+            *stage = Stage::Settling(SETTLE_FRAMES - 1);
+        }
+        Stage::Settling(0) => {
+            let path = golden_path(name);
+            let update = goldens.update;
+            commands
+                .spawn(Screenshot::image(goldens.image.clone()))
+                .observe(
+                    move |trigger: Trigger<ScreenshotCaptured>,
+                          mut commands: Commands,
+                          mut goldens: ResMut<Goldens>,
+                          scoped_query: Query<(Entity, &StateScoped<Screen>)>| {
+                        let Some((name, _)) = goldens.current.take() else {
+                            return;
+                        };
+                        match check(&trigger.event().0, &path, update) {
+                            Ok(message) => info!("{name}: {message}"),
+                            Err(message) => {
+                                error!("{name}: {message}");
+                                goldens.failures += 1;
+                            }
+                        }
+                        // Clear the level away for the next one.
+                        for (entity, scoped) in &scoped_query {
+                            if scoped.0 == Screen::Gameplay {
+                                commands.entity(entity).despawn();
+                            }
+                        }
+                    },
+                );
+            *stage = Stage::Capturing;
+        }
+        Stage::Settling(frames) => *frames -= 1,
+        Stage::Capturing => {}
+    }
+}
+
+/// The center and size of a view of `bounds` with the image's aspect ratio,
+/// the same way level thumbnails are framed.
+fn frame(bounds: Rect) -> (Vec2, Vec2) {
+    let aspect = GOLDEN_SIZE.x as f32 / GOLDEN_SIZE.y as f32;
+    let size = bounds.size();
+    let viewport = if size.x / size.y > aspect {
+        Vec2::new(size.x, size.x / aspect)
+    } else {
+        Vec2::new(size.y * aspect, size.y)
+    };
+    (bounds.center(), viewport)
+}
+
+/// Where the golden for the level file `name` is kept.
+fn golden_path(name: &str) -> PathBuf {
+    let stem = LEVEL_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name);
+    FileAssetReader::get_base_path()
+        .join(GOLDEN_DIR)
+        .join(format!("{stem}.png"))
+}
+
+/// Save the render as the golden at `path`, or compare it to it.
+fn check(render: &Image, path: &Path, update: bool) -> Result<String, String> {
+    let render = render
+        .clone()
+        .try_into_dynamic()
+        .map_err(|error| error.to_string())?
+        .to_rgba8();
+    let mut render = Image::from_dynamic(render.into(), true, RenderAssetUsages::default());
+    if update {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+        }
+        save_png(render, path)?;
+        return Ok(format!("saved {}", path.display()));
+    }
+
+    let bytes = std::fs::read(path).map_err(|_| {
+        format!(
+            "there is no golden at {}, run with --update to make one",
+            path.display()
+        )
+    })?;
+    let golden = Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )
+    .map_err(|error| format!("the golden is corrupt: {error}"))?;
+    if golden.size() != render.size() {
+        return Err(format!(
+            "the golden is {} but the render is {}",
+            golden.size(),
+            render.size()
+        ));
+    }
+    let (Some(expected), Some(actual)) = (golden.data.as_ref(), render.data.as_mut()) else {
+        return Err("an image has no pixels".to_string());
+    };
+
+    let mut different = 0;
+    for (expected, actual) in expected.chunks_exact(4).zip(actual.chunks_exact_mut(4)) {
+        let same = expected
+            .iter()
+            .zip(actual.iter())
+            .all(|(a, b)| a.abs_diff(*b) <= TOLERANCE);
+        if same {
+            // Dim matching pixels, so the differences stand out.
+            for channel in &mut actual[..3] {
+                *channel /= 3;
+            }
+        } else {
+            different += 1;
+            actual.copy_from_slice(&DIFF_COLOR);
+        }
+    }
+    if different == 0 {
+        return Ok("matches its golden".to_string());
+    }
+    let diff_path = path.with_extension("diff.png");
+    save_png(render, &diff_path)?;
+    Err(format!(
+        "{different} pixels differ from the golden, see {}",
+        diff_path.display()
+    ))
+}
+
+fn save_png(image: Image, path: &Path) -> Result<(), String> {
+    image
+        .try_into_dynamic()
+        .map_err(|error| error.to_string())?
+        .save(path)
+        .map_err(|error| format!("could not save {}: {error}", path.display()))
+}
@@ -0,0 +1,133 @@
+//! Loads a level file on its own, plain or packed, and draws its layout,
+//! with any problems validation finds printed to the log. Handy for checking
+//! a level made by hand without starting the game.
+//!
+//! ```sh
+//! cargo run --example level_loader -- assets/levels/level1.level.ron
+//! ```
+
+use bevy::{color::palettes::css, input::mouse::MouseWheel, prelude::*};
+use hooked::game::prelude::*;
+
+const DEFAULT_LEVEL: &str = "assets/levels/level1.level.ron";
+
+fn main() -> AppExit {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_LEVEL.to_string());
+    let level = match std::fs::read(&path)
+        .map_err(LevelLoadError::Io)
+        .and_then(|bytes| LevelData::from_bytes(&bytes))
+    {
+        Ok(level) => level,
+        Err(error) => {
+            eprintln!("Couldn't load {path}: {error}");
+            return AppExit::error();
+        }
+    };
+
+    let issues = level.validate();
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity() == Severity::Error)
+        .count();
+    for issue in &issues {
+        eprintln!("{:?}: {issue}", issue.severity());
+    }
+
+    let summary = format!(
+        "{path}: \"{}\", {} obstacles, {} anchors, {} hazards\n\
+        {} issues, {errors} of them errors. Scroll to zoom, Escape quits.",
+        level.name,
+        level.obstacles.len(),
+        level.anchors.len(),
+        level.hazards.len(),
+        issues.len(),
+    );
+
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .insert_resource(LoadedLevel(level))
+        .insert_resource(Summary(summary))
+        .add_systems(Startup, (spawn_camera, spawn_summary))
+        .add_systems(Update, (draw_level, zoom, quit_on_escape))
+        .run()
+}
+
+#[derive(Resource)]
+struct LoadedLevel(LevelData);
+
+#[derive(Resource)]
+struct Summary(String);
+
+/// Center the camera on the level, zoomed out far enough to see all of it.
+fn spawn_camera(mut commands: Commands, level: Res<LoadedLevel>, window: Single<&Window>) {
+    let bounds = level.0.bounds();
+    let scale = (bounds.size() / window.size()).max_element().max(1.0) * 1.1;
+    commands.spawn((
+        Camera2d,
+        Projection::Orthographic(OrthographicProjection {
+            scale,
+            ..OrthographicProjection::default_2d()
+        }),
+        Transform::from_translation(bounds.center().extend(0.0)),
+    ));
+}
+
+fn spawn_summary(mut commands: Commands, summary: Res<Summary>) {
+    commands.spawn((
+        Text::new(summary.0.clone()),
+        TextFont::from_font_size(16.0),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.0),
+            top: Val::Px(12.0),
+            ..default()
+        },
+    ));
+}
+
+fn draw_level(mut gizmos: Gizmos, level: Res<LoadedLevel>) {
+    let level = &level.0;
+    gizmos.rect_2d(
+        level.bounds().center(),
+        level.bounds().size(),
+        css::DIM_GRAY,
+    );
+    for obstacle in &level.obstacles {
+        gizmos.rect_2d(obstacle.position, obstacle.size, css::WHITE);
+    }
+    for prop in &level.props {
+        gizmos.rect_2d(prop.position, prop.size, css::SANDY_BROWN);
+    }
+    for hazard in &level.hazards {
+        gizmos.rect_2d(hazard.position, hazard.size, css::RED);
+    }
+    for &anchor in &level.anchors {
+        gizmos.circle_2d(anchor, 6.0, css::GOLD);
+    }
+    for &checkpoint in &level.checkpoints {
+        gizmos.circle_2d(checkpoint, 10.0, css::DEEP_SKY_BLUE);
+    }
+    if let Some(spawn) = level.spawn {
+        gizmos.circle_2d(spawn, 12.0, css::LIME);
+    }
+    if let Some(exit) = level.exit {
+        gizmos.circle_2d(exit, 12.0, css::MAGENTA);
+    }
+}
+
+fn zoom(mut wheel: EventReader<MouseWheel>, mut projection: Single<&mut Projection>) {
+    let Projection::Orthographic(projection) = &mut **projection else {
+        return;
+    };
+    for event in wheel.read() {
+        projection.scale = (projection.scale * (1.0 - event.y * 0.1)).clamp(0.1, 20.0);
+    }
+}
+
+fn quit_on_escape(keys: Res<ButtonInput<KeyCode>>, mut exit: EventWriter<AppExit>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        exit.write(AppExit::Success);
+    }
+}
@@ -0,0 +1,63 @@
+//! Plays a recorded attempt back, either the file given as the first argument
+//! or the last attempt saved by the game.
+//!
+//! ```sh
+//! cargo run --example replay_player -- path/to/attempt.replay
+//! ```
+
+use bevy::prelude::*;
+use hooked::{
+    platform::ActivePlatform,
+    replay::{Replay, read_last_replay, start_playback},
+    screens::Screen,
+};
+
+const INSTRUCTIONS: &str = "Playing back the replay. Escape stops it.";
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins(hooked::AppPlugin)
+        .add_systems(OnEnter(Screen::Title), play_replay)
+        .add_systems(OnEnter(Screen::Gameplay), spawn_instructions)
+        .run()
+}
+
+/// Start playback once the title screen is reached, so that everything's
+/// loaded.
+fn play_replay(mut commands: Commands, platform: Res<ActivePlatform>) {
+    let replay = match std::env::args().nth(1) {
+        Some(path) => match std::fs::read(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| Replay::from_bytes(&bytes))
+        {
+            Ok(replay) => replay,
+            Err(error) => {
+                error!("Couldn't read {path}: {error}");
+                return;
+            }
+        },
+        None => {
+            let Some(replay) = read_last_replay(&platform) else {
+                warn!("No replay saved yet, play the level first or pass a file");
+                return;
+            };
+            replay
+        }
+    };
+    commands.queue(move |world: &mut World| start_playback(world, replay));
+}
+
+fn spawn_instructions(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Instructions"),
+        Text::new(INSTRUCTIONS),
+        TextFont::from_font_size(16.0),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.0),
+            bottom: Val::Px(12.0),
+            ..default()
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
@@ -1,4 +1,6 @@
-use bevy::prelude::*;
+use bevy::{audio::Volume, prelude::*};
+
+use crate::persistence::Settings;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Music>();
@@ -6,7 +8,12 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        apply_global_volume.run_if(resource_changed::<GlobalVolume>),
+        (
+            apply_master_volume.run_if(resource_changed::<Settings>),
+            apply_volume.run_if(resource_changed::<GlobalVolume>.or(resource_changed::<Settings>)),
+            apply_volume_to_new_sounds,
+        )
+            .chain(),
     );
 }
 
@@ -36,12 +43,44 @@ pub fn sound_effect(handle: Handle<AudioSource>) -> impl Bundle {
     (AudioPlayer(handle), PlaybackSettings::DESPAWN, SoundEffect)
 }
 
+/// The volume the player has set for music, or for everything else.
+pub fn category_volume(settings: &Settings, is_music: bool) -> Volume {
+    Volume::Linear(if is_music {
+        settings.music_volume
+    } else {
+        settings.sfx_volume
+    })
+}
+
+/// The master volume is [`GlobalVolume`], which is what Bevy applies to new sounds.
+fn apply_master_volume(settings: Res<Settings>, mut global_volume: ResMut<GlobalVolume>) {
+    if global_volume.volume.to_linear() != settings.master_volume {
+        global_volume.volume = Volume::Linear(settings.master_volume);
+    }
+}
+
 /// [`GlobalVolume`] doesn't apply to already-running audio entities, so this system will update them.
-fn apply_global_volume(
+fn apply_volume(
+    global_volume: Res<GlobalVolume>,
+    settings: Res<Settings>,
+    mut audio_query: Query<(&PlaybackSettings, &mut AudioSink, Has<Music>)>,
+) {
+    for (playback, mut sink, is_music) in &mut audio_query {
+        sink.set_volume(
+            global_volume.volume * playback.volume * category_volume(&settings, is_music),
+        );
+    }
+}
+
+/// Bevy starts sounds at the global volume, which leaves out their category's.
+fn apply_volume_to_new_sounds(
     global_volume: Res<GlobalVolume>,
-    mut audio_query: Query<(&PlaybackSettings, &mut AudioSink)>,
+    settings: Res<Settings>,
+    mut audio_query: Query<(&PlaybackSettings, &mut AudioSink, Has<Music>), Added<AudioSink>>,
 ) {
-    for (playback, mut sink) in &mut audio_query {
-        sink.set_volume(global_volume.volume * playback.volume);
+    for (playback, mut sink, is_music) in &mut audio_query {
+        sink.set_volume(
+            global_volume.volume * playback.volume * category_volume(&settings, is_music),
+        );
     }
 }
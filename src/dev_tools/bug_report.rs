@@ -0,0 +1,411 @@
+//! Bug reports for playtesters, saved with F12.
+//!
+//! A report is a zip in `reports/` holding a screenshot, the latest lines of
+//! the log, the bodies in gameplay exported as a scene (see `scene_load` for
+//! bringing them back), and what the game is running on. Shift+F12 also
+//! opens the page for filing an issue, to attach the report to. Native only.
+//!
+//! The log lines are kept by [`recent_log_layer`], which the app adds to the
+//! log plugin in dev builds.
+
+use std::{collections::VecDeque, fmt::Write as _, sync::Mutex};
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    log::{
+        BoxedLayer,
+        tracing::{
+            Event, Subscriber,
+            field::{Field, Visit},
+        },
+        tracing_subscriber::{Layer, layer::Context},
+    },
+    prelude::*,
+};
+#[cfg(not(target_family = "wasm"))]
+use bevy::{
+    render::{
+        renderer::RenderAdapterInfo,
+        view::screenshot::{Screenshot, ScreenshotCaptured},
+    },
+    tasks::IoTaskPool,
+    window::PrimaryWindow,
+};
+#[cfg(not(target_family = "wasm"))]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(not(target_family = "wasm"))]
+use crate::{
+    dev_tools::scene_export::export_scene, game::game_rng::GameRng, persistence::Settings,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        save_bug_report.run_if(input_just_pressed(REPORT_KEY)),
+    );
+}
+
+const REPORT_KEY: KeyCode = KeyCode::F12;
+/// How many lines of the log a report has.
+const MAX_LOG_LINES: usize = 200;
+#[cfg(not(target_family = "wasm"))]
+const REPORT_DIR: &str = "reports";
+#[cfg(not(target_family = "wasm"))]
+const ISSUES_URL: &str = "https://github.com/geirsagberg/hooked/issues/new";
+
+/// The latest lines of the log, oldest first.
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// A log layer keeping the latest lines for bug reports, to go in
+/// [`LogPlugin::custom_layer`](bevy::log::LogPlugin::custom_layer).
+pub fn recent_log_layer(_: &mut App) -> Option<BoxedLayer> {
+    Some(Box::new(RecentLogLayer))
+}
+
+struct RecentLogLayer;
+
+impl<S: Subscriber> Layer<S> for RecentLogLayer {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!("{:>5} {}: ", metadata.level(), metadata.target());
+        event.record(&mut LineVisitor(&mut line));
+        let Ok(mut logs) = RECENT_LOGS.lock() else {
+            return;
+        };
+        if logs.len() == MAX_LOG_LINES {
+            logs.pop_front();
+        }
+        logs.push_back(line);
+    }
+}
+
+/// Writes the message of a log event, then its other fields.
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let _ = if field.name() == "message" {
+            write!(self.0, "{value:?}")
+        } else {
+            write!(self.0, " {}={value:?}", field.name())
+        };
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_bug_report(world: &mut World) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let name = format!("report-{secs}");
+    let open_issue = world
+        .resource::<ButtonInput<KeyCode>>()
+        .any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    let mut files = vec![("system.txt".to_string(), system_info(world).into_bytes())];
+    match export_scene(world) {
+        Ok((ron, _)) => files.push(("scene.scn.ron".to_string(), ron.into_bytes())),
+        Err(reason) => info!("Leaving the scene out of the bug report: {reason}"),
+    }
+    let log: String = RECENT_LOGS
+        .lock()
+        .map(|logs| logs.iter().map(|line| format!("{line}\n")).collect())
+        .unwrap_or_default();
+    files.push(("log.txt".to_string(), log.into_bytes()));
+
+    // The screenshot comes a frame or so later, and the report is written
+    // once it's in.
+    let mut files = Some(files);
+    world.spawn(Screenshot::primary_window()).observe(
+        move |trigger: Trigger<ScreenshotCaptured>| {
+            let Some(mut files) = files.take() else {
+                return;
+            };
+            let screenshot = trigger.event().0.clone();
+            let name = name.clone();
+            IoTaskPool::get()
+                .spawn(async move {
+                    match png_bytes(screenshot) {
+                        Ok(png) => files.insert(0, ("screenshot.png".to_string(), png)),
+                        Err(error) => {
+                            warn!("Leaving the screenshot out of the bug report: {error}")
+                        }
+                    }
+                    match write_report(&name, &files) {
+                        Ok(path) => info!("Saved a bug report to {}", path.display()),
+                        Err(error) => error!("Couldn't save the bug report: {error}"),
+                    }
+                })
+                .detach();
+            if open_issue {
+                open_url(ISSUES_URL);
+            }
+        },
+    );
+}
+
+#[cfg(target_family = "wasm")]
+fn save_bug_report() {
+    warn!("Bug reports are not supported on the web");
+}
+
+/// What the game is, and what it's running on.
+#[cfg(not(target_family = "wasm"))]
+fn system_info(world: &mut World) -> String {
+    let mut info = String::new();
+    let _ = writeln!(info, "Hooked {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        info,
+        "OS: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let threads = std::thread::available_parallelism().map_or(0, |threads| threads.get());
+    let _ = writeln!(info, "CPU threads: {threads}");
+    if let Some(adapter) = world.get_resource::<RenderAdapterInfo>() {
+        let _ = writeln!(
+            info,
+            "GPU: {} on {:?}, driver {} {}",
+            adapter.name, adapter.backend, adapter.driver, adapter.driver_info
+        );
+    }
+    if let Ok(window) = world
+        .query_filtered::<&Window, With<PrimaryWindow>>()
+        .single(world)
+    {
+        let _ = writeln!(
+            info,
+            "Window: {}x{} at scale {}",
+            window.physical_width(),
+            window.physical_height(),
+            window.scale_factor()
+        );
+    }
+    let _ = writeln!(
+        info,
+        "Screen: {:?}",
+        world.resource::<State<Screen>>().get()
+    );
+    let _ = writeln!(info, "Seed: {}", world.resource::<GameRng>().seed());
+    let _ = writeln!(
+        info,
+        "Physics quality: {:?}",
+        world.resource::<Settings>().physics_quality
+    );
+    info
+}
+
+/// Encode an image as a PNG.
+#[cfg(not(target_family = "wasm"))]
+fn png_bytes(image: Image) -> Result<Vec<u8>, String> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image
+        .try_into_dynamic()
+        .map_err(|error| error.to_string())?
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .map_err(|error| error.to_string())?;
+    Ok(bytes.into_inner())
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write_report(name: &str, files: &[(String, Vec<u8>)]) -> Result<std::path::PathBuf, String> {
+    let dir = bevy::asset::io::file::FileAssetReader::get_base_path().join(REPORT_DIR);
+    std::fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+    let path = dir.join(format!("{name}.zip"));
+    std::fs::write(&path, zip(files))
+        .map_err(|error| format!("could not save {}: {error}", path.display()))?;
+    Ok(path)
+}
+
+/// The date every file in a report claims, 1980-01-01 in MS-DOS format.
+#[cfg(not(target_family = "wasm"))]
+const ZIP_DATE: u16 = 0x21;
+
+/// Pack files into a zip, stored without compression: the screenshot is
+/// compressed already, and the rest is small.
+#[cfg(not(target_family = "wasm"))]
+fn zip(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    // The fields local headers and the central directory share.
+    let fields = |out: &mut Vec<u8>, name: &str, data: &[u8]| {
+        out.extend(20u16.to_le_bytes()); // Version needed to extract.
+        out.extend(0u16.to_le_bytes()); // Flags.
+        out.extend(0u16.to_le_bytes()); // Stored.
+        out.extend(0u16.to_le_bytes()); // Time.
+        out.extend(ZIP_DATE.to_le_bytes());
+        out.extend(crc32(data).to_le_bytes());
+        out.extend((data.len() as u32).to_le_bytes()); // Compressed size.
+        out.extend((data.len() as u32).to_le_bytes());
+        out.extend((name.len() as u16).to_le_bytes());
+        out.extend(0u16.to_le_bytes()); // Extra field length.
+    };
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in files {
+        let offset = archive.len() as u32;
+        archive.extend(0x0403_4b50u32.to_le_bytes());
+        fields(&mut archive, name, data);
+        archive.extend(name.as_bytes());
+        archive.extend(data);
+
+        directory.extend(0x0201_4b50u32.to_le_bytes());
+        directory.extend(20u16.to_le_bytes()); // Version made by.
+        fields(&mut directory, name, data);
+        directory.extend(0u16.to_le_bytes()); // Comment length.
+        directory.extend(0u16.to_le_bytes()); // Disk.
+        directory.extend(0u16.to_le_bytes()); // Internal attributes.
+        directory.extend(0u32.to_le_bytes()); // External attributes.
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+    }
+
+    let offset = archive.len() as u32;
+    let count = files.len() as u16;
+    archive.extend(&directory);
+    archive.extend(0x0605_4b50u32.to_le_bytes());
+    archive.extend(0u16.to_le_bytes()); // Disk.
+    archive.extend(0u16.to_le_bytes()); // Disk the directory starts on.
+    archive.extend(count.to_le_bytes()); // Entries on this disk.
+    archive.extend(count.to_le_bytes());
+    archive.extend((directory.len() as u32).to_le_bytes());
+    archive.extend(offset.to_le_bytes());
+    archive.extend(0u16.to_le_bytes()); // Comment length.
+    archive
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Open a page in the default browser.
+#[cfg(not(target_family = "wasm"))]
+fn open_url(url: &str) {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    if let Err(error) = command.arg(url).spawn() {
+        warn!("Couldn't open {url}: {error}");
+    }
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use bevy::{
+        asset::RenderAssetUsages,
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    use super::*;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    }
+
+    /// Read the files back out of a zip, going by its central directory the
+    /// way unzip tools do.
+    fn unzip(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+        // Without a comment, the end record is the last 22 bytes.
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(archive, end), 0x0605_4b50);
+        let count = u16_at(archive, end + 10) as usize;
+        let mut entry = u32_at(archive, end + 16) as usize;
+        assert_eq!(entry + u32_at(archive, end + 12) as usize, end);
+
+        let mut files = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(archive, entry), 0x0201_4b50);
+            let crc = u32_at(archive, entry + 16);
+            let size = u32_at(archive, entry + 24) as usize;
+            let name_len = u16_at(archive, entry + 28) as usize;
+            let extra_len = u16_at(archive, entry + 30) as usize;
+            let comment_len = u16_at(archive, entry + 32) as usize;
+            let local = u32_at(archive, entry + 42) as usize;
+            let name = &archive[entry + 46..entry + 46 + name_len];
+
+            assert_eq!(u32_at(archive, local), 0x0403_4b50);
+            assert_eq!(u16_at(archive, local + 8), 0, "stored");
+            assert_eq!(u32_at(archive, local + 14), crc);
+            let local_name_len = u16_at(archive, local + 26) as usize;
+            let local_extra_len = u16_at(archive, local + 28) as usize;
+            assert_eq!(&archive[local + 30..local + 30 + local_name_len], name);
+            let start = local + 30 + local_name_len + local_extra_len;
+            let data = archive[start..start + size].to_vec();
+            assert_eq!(crc32(&data), crc);
+
+            files.push((String::from_utf8(name.to_vec()).unwrap(), data));
+            entry += 46 + name_len + extra_len + comment_len;
+        }
+        files
+    }
+
+    #[test]
+    fn crc32_matches_known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"a"), 0xE8B7_BE43);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+
+    #[test]
+    fn zip_round_trips() {
+        let files = vec![
+            ("system.txt".to_string(), b"Hooked 0.1.0\n".to_vec()),
+            ("log.txt".to_string(), Vec::new()),
+            (
+                "screenshot.png".to_string(),
+                (0..=255).cycle().take(5000).collect(),
+            ),
+        ];
+        assert_eq!(unzip(&zip(&files)), files);
+        assert!(unzip(&zip(&[])).is_empty());
+    }
+
+    #[test]
+    fn png_bytes_are_a_png() {
+        let image = Image::new_fill(
+            Extent3d {
+                width: 3,
+                height: 2,
+                ..default()
+            },
+            TextureDimension::D2,
+            &[10, 20, 30, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let png = png_bytes(image).unwrap();
+        let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded.dimensions(), (3, 2));
+        assert!(decoded.pixels().all(|pixel| pixel.0 == [10, 20, 30, 255]));
+    }
+}
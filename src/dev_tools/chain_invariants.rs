@@ -0,0 +1,215 @@
+//! Checking that the bookkeeping in [`ChainState`] matches the world.
+//!
+//! While `chain_checks` has it turned on, each chain is checked every frame
+//! of gameplay for:
+//!
+//! - links that are gone, or in more than one chain or place,
+//! - links whose index isn't their place along the chain,
+//! - a joint missing between two neighboring links, or joining the wrong ones,
+//! - chain joints and links that no chain knows about.
+//!
+//! Each different problem is logged once. The checks are off by default, as
+//! they walk every chain and joint. `chain_fuzz <steps> [seed]` turns them on
+//! and throws random work at the chains to shake problems out: firing chains
+//! of random lengths, releasing and snapping random chains, and despawning
+//! random links out from under them, the way anything else in the game might.
+
+use std::collections::{HashMap, HashSet};
+
+use avian2d::prelude::*;
+use bevy::{ecs::system::SystemState, prelude::*};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    dev_tools::console::{ConsoleAppExt, parse_arg},
+    game::{
+        chain::{ChainConfig, ChainLink, ChainPool, ChainState, LINK_SIZE},
+        hook_types::HookRegistry,
+        player::Player,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ChainChecks>();
+    app.add_console_command(
+        "chain_checks",
+        "chain_checks: toggle checking chain bookkeeping every frame",
+        toggle_chain_checks,
+    );
+    app.add_console_command(
+        "chain_fuzz",
+        "chain_fuzz <steps> [seed]: fire, release, snap and despawn chains at random",
+        fuzz_chains,
+    );
+
+    app.add_systems(
+        PostUpdate,
+        check_chain_invariants.run_if(in_state(Screen::Gameplay).and(checking_chains)),
+    );
+}
+
+/// Whether [`check_chain_invariants`] runs.
+#[derive(Resource, Default)]
+struct ChainChecks(bool);
+
+fn checking_chains(checks: Res<ChainChecks>) -> bool {
+    checks.0
+}
+
+fn toggle_chain_checks(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let mut checks = world.resource_mut::<ChainChecks>();
+    checks.0 = !checks.0;
+    Ok(format!(
+        "chain checks {}, broken invariants go to the log",
+        if checks.0 { "on" } else { "off" }
+    ))
+}
+
+fn check_chain_invariants(
+    chain_state: Res<ChainState>,
+    link_query: Query<(Entity, &ChainLink)>,
+    joint_query: Query<(Entity, &RevoluteJoint)>,
+    mut reported: Local<HashSet<String>>,
+) {
+    let mut problems = Vec::new();
+    // Where each link is, as (chain, place along it).
+    let mut places: HashMap<Entity, (usize, usize)> = HashMap::new();
+
+    for (c, chain) in chain_state.chains.iter().enumerate() {
+        for (i, &link) in chain.links.iter().enumerate() {
+            if let Some((other, j)) = places.insert(link, (c, i)) {
+                problems.push(format!(
+                    "link {link} is both link {j} of chain {other} and link {i} of chain {c}"
+                ));
+            }
+            match link_query.get(link) {
+                Ok((_, chain_link)) if chain_link.link_index != i => problems.push(format!(
+                    "link {i} of chain {c} thinks it's link {}",
+                    chain_link.link_index
+                )),
+                Ok(_) => {}
+                Err(_) => problems.push(format!("link {i} of chain {c} is gone")),
+            }
+        }
+
+        if chain.joints.len() + 1 != chain.links.len() {
+            problems.push(format!(
+                "chain {c} has {} links but {} joints between them",
+                chain.links.len(),
+                chain.joints.len()
+            ));
+        }
+        for (i, (&joint, pair)) in chain.joints.iter().zip(chain.links.windows(2)).enumerate() {
+            match joint_query.get(joint) {
+                Ok((_, joint))
+                    if [joint.entity1, joint.entity2].contains(&pair[0])
+                        && [joint.entity1, joint.entity2].contains(&pair[1]) => {}
+                Ok(_) => problems.push(format!(
+                    "joint {i} of chain {c} doesn't join links {i} and {}",
+                    i + 1
+                )),
+                Err(_) => problems.push(format!("joint {i} of chain {c} is gone")),
+            }
+        }
+    }
+
+    for (link, chain_link) in &link_query {
+        if !places.contains_key(&link) {
+            problems.push(format!(
+                "link {link} (index {}) is in no chain",
+                chain_link.link_index
+            ));
+        }
+    }
+    let known_joints: HashSet<Entity> = chain_state
+        .chains
+        .iter()
+        .flat_map(|chain| {
+            chain
+                .joints
+                .iter()
+                .chain(&chain.hook)
+                .chain(&chain.rappel)
+                .chain(chain.frozen.as_ref().map(|frozen| &frozen.joint))
+                .copied()
+        })
+        .collect();
+    for (entity, joint) in &joint_query {
+        let joins_links = link_query.contains(joint.entity1) || link_query.contains(joint.entity2);
+        if joins_links && !known_joints.contains(&entity) {
+            problems.push(format!(
+                "joint {entity} joins chain links but is in no chain"
+            ));
+        }
+    }
+
+    for problem in problems {
+        if reported.insert(problem.clone()) {
+            warn!("Chain invariant broken: {problem}");
+        }
+    }
+}
+
+fn fuzz_chains(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if *world.resource::<State<Screen>>().get() != Screen::Gameplay {
+        return Err("chains only exist during gameplay".to_string());
+    }
+    let steps = parse_arg(args, 0, "steps")? as usize;
+    let seed = match args.get(1) {
+        Some(_) => parse_arg(args, 1, "seed")? as u64,
+        None => 0,
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    world.resource_mut::<ChainChecks>().0 = true;
+
+    let mut state: SystemState<(
+        Commands,
+        Res<ChainConfig>,
+        Res<HookRegistry>,
+        ResMut<ChainState>,
+        ResMut<ChainPool>,
+        Query<&GlobalTransform, With<Player>>,
+    )> = SystemState::new(world);
+    let mut despawned = 0;
+    for _ in 0..steps {
+        let (mut commands, config, hooks, mut chain_state, mut pool, player) = state.get_mut(world);
+        let Ok(player) = player.single() else {
+            return Err("there is no player to fire from".to_string());
+        };
+        let from = player.translation().truncate();
+        let count = chain_state.chains.len();
+        match rng.random_range(0..4) {
+            0 => {
+                let hook_type = hooks.active(&config);
+                let links = rng.random_range(1..=hook_type.max_links(&config));
+                let direction = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU));
+                let target = from + direction * links as f32 * LINK_SIZE;
+                let chain =
+                    pool.fire_chain(&mut commands, &config, hook_type, from, target, Vec2::ZERO);
+                chain_state.chains.push(chain);
+            }
+            1 if count > 0 => {
+                let chain = chain_state.chains.remove(rng.random_range(0..count));
+                chain.release(&mut commands, &mut pool);
+            }
+            2 if count > 0 => {
+                let chain = chain_state.chains.remove(rng.random_range(0..count));
+                chain.snap(&mut commands, &mut pool);
+            }
+            3 if count > 0 => {
+                let chain = &chain_state.chains[rng.random_range(0..count)];
+                let link = chain.links[rng.random_range(0..chain.links.len())];
+                commands.entity(link).try_despawn();
+                despawned += 1;
+            }
+            _ => {}
+        }
+        state.apply(world);
+    }
+    let chains = world.resource::<ChainState>().chains.len();
+    Ok(format!(
+        "ran {steps} steps, {chains} chains left and {despawned} links despawned, \
+         broken invariants go to the log"
+    ))
+}
@@ -0,0 +1,281 @@
+//! A panel for tuning [`ChainConfig`] while playing.
+//!
+//! Toggle it with F4. Every field of the config gets a row with buttons to
+//! lower or raise it, found through reflection so new fields show up on their
+//! own. "Respawn test chain" releases all chains and fires a fresh one, so a
+//! change can be tried right away. Settings worth keeping are saved as named
+//! presets in `assets/chain_presets` with the `chain_save <name>` and
+//! `chain_load <name>` console commands.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, reflect::Struct, ui::Val::*};
+
+use crate::{
+    dev_tools::console::ConsoleAppExt,
+    game::{
+        aim::PlayerAim,
+        chain::{ChainConfig, ChainPool, ChainState, RootAttachment},
+        hook_types::HookRegistry,
+        player::Player,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChainTuningUi>();
+    app.register_type::<ChainTuningLabel>();
+
+    app.add_console_command(
+        "chain_save",
+        "chain_save <name>: save the chain tuning as a preset",
+        save_preset,
+    );
+    app.add_console_command(
+        "chain_load",
+        "chain_load <name>: load a chain tuning preset",
+        load_preset,
+    );
+
+    app.add_systems(Startup, spawn_tuning_panel);
+    app.add_systems(
+        Update,
+        (
+            toggle_tuning_panel.run_if(input_just_pressed(TOGGLE_KEY)),
+            update_tuning_labels.run_if(resource_changed::<ChainConfig>),
+        ),
+    );
+}
+
+const TOGGLE_KEY: KeyCode = KeyCode::F4;
+#[cfg(not(target_family = "wasm"))]
+const PRESET_DIR: &str = "chain_presets";
+/// How much one click changes a decimal field, as a factor.
+const FLOAT_STEP: f32 = 1.1;
+const BUTTON_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ChainTuningUi;
+
+/// The label showing the value of the config field at this index.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ChainTuningLabel(usize);
+
+fn spawn_tuning_panel(mut commands: Commands, config: Res<ChainConfig>) {
+    let panel = commands
+        .spawn((
+            Name::new("Chain Tuning"),
+            ChainTuningUi,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Px(0.0),
+                bottom: Px(0.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Px(4.0),
+                padding: UiRect::all(Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(10),
+            Visibility::Hidden,
+        ))
+        .id();
+
+    for index in 0..config.field_len() {
+        let row = commands
+            .spawn((
+                Name::new("Chain Tuning Row"),
+                Node {
+                    column_gap: Px(10.0),
+                    ..default()
+                },
+                ChildOf(panel),
+            ))
+            .id();
+        commands.spawn((
+            Name::new("Chain Tuning Label"),
+            ChainTuningLabel(index),
+            Text::default(),
+            TextFont::from_font_size(14.0),
+            Node {
+                width: Px(220.0),
+                ..default()
+            },
+            ChildOf(row),
+        ));
+        for (text, raise) in [("[-]", false), ("[+]", true)] {
+            commands.spawn(tuning_button(text, row)).observe(
+                move |_: Trigger<Pointer<Click>>,
+                      mut config: ResMut<ChainConfig>,
+                      hooks: Res<HookRegistry>| {
+                    step_field(&mut config, &hooks, index, raise);
+                },
+            );
+        }
+    }
+    commands
+        .spawn(tuning_button("[Respawn test chain]", panel))
+        .observe(|_: Trigger<Pointer<Click>>, mut commands: Commands| {
+            commands.queue(respawn_test_chain);
+        });
+    commands.spawn((
+        Name::new("Chain Tuning Hint"),
+        Text::new("chain_save / chain_load <name> in the console (F1)"),
+        TextFont::from_font_size(12.0),
+        ChildOf(panel),
+    ));
+}
+
+fn tuning_button(text: &str, parent: Entity) -> impl Bundle {
+    (
+        Name::new("Chain Tuning Button"),
+        Text::new(text),
+        TextFont::from_font_size(14.0),
+        TextColor(BUTTON_COLOR),
+        ChildOf(parent),
+    )
+}
+
+fn toggle_tuning_panel(
+    mut panel_query: Query<&mut Visibility, (With<ChainTuningUi>, Without<ChildOf>)>,
+) {
+    for mut visibility in &mut panel_query {
+        visibility.toggle_visible_hidden();
+    }
+}
+
+/// Lower or raise a field of the config. Counts change by one and never go
+/// below one, decimals change by [`FLOAT_STEP`], and the root attachment and
+/// hook type cycle through what there is either way.
+fn step_field(config: &mut ChainConfig, hooks: &HookRegistry, index: usize, raise: bool) {
+    let Some(field) = config.field_at_mut(index) else {
+        return;
+    };
+    if let Some(value) = field.try_downcast_mut::<f32>() {
+        *value = if raise {
+            *value * FLOAT_STEP
+        } else {
+            *value / FLOAT_STEP
+        };
+    } else if let Some(value) = field.try_downcast_mut::<usize>() {
+        *value = if raise {
+            *value + 1
+        } else {
+            value.saturating_sub(1).max(1)
+        };
+    } else if let Some(value) = field.try_downcast_mut::<RootAttachment>() {
+        *value = value.next();
+    } else if let Some(value) = field.try_downcast_mut::<String>() {
+        let ids: Vec<&str> = hooks.ids().collect();
+        let i = ids.iter().position(|&id| id == value.as_str());
+        if let Some(next) = ids.get(i.map_or(0, |i| (i + 1) % ids.len())) {
+            *value = next.to_string();
+        }
+    }
+}
+
+fn update_tuning_labels(
+    config: Res<ChainConfig>,
+    mut label_query: Query<(&ChainTuningLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut label_query {
+        let (Some(name), Some(field)) = (config.name_at(label.0), config.field_at(label.0)) else {
+            continue;
+        };
+        let value = if let Some(value) = field.try_downcast_ref::<f32>() {
+            format!("{value:.2}")
+        } else {
+            format!("{field:?}")
+        };
+        text.0 = format!("{}: {value}", name.replace('_', " "));
+    }
+}
+
+/// Release every chain and fire a new one toward the aim, or straight up.
+fn respawn_test_chain(world: &mut World) {
+    let Ok(from) = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .map(|transform| transform.translation.truncate())
+    else {
+        return;
+    };
+    let config = world.resource::<ChainConfig>().clone();
+    let hook_type = world.resource::<HookRegistry>().active(&config).clone();
+    let target = world
+        .resource::<PlayerAim>()
+        .target
+        .unwrap_or(from + Vec2::Y * config.max_length());
+
+    world.resource_scope(|world, mut chain_state: Mut<ChainState>| {
+        world.resource_scope(|world, mut pool: Mut<ChainPool>| {
+            let mut commands = world.commands();
+            while !chain_state.chains.is_empty() {
+                chain_state.release_oldest(&mut commands, &mut pool);
+            }
+            let chain =
+                pool.fire_chain(&mut commands, &config, &hook_type, from, target, Vec2::ZERO);
+            chain_state.chains.push(chain);
+        });
+    });
+    world.flush();
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn preset_path(name: &str) -> Result<std::path::PathBuf, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "`{name}` is not a valid preset name, use letters, digits, - and _"
+        ));
+    }
+    Ok(bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join("assets")
+        .join(PRESET_DIR)
+        .join(format!("{name}.chain.ron")))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_preset(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: chain_save <name>".to_string());
+    };
+    let path = preset_path(name)?;
+    let ron = ron::ser::to_string_pretty(
+        world.resource::<ChainConfig>(),
+        ron::ser::PrettyConfig::default(),
+    )
+    .map_err(|error| error.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    std::fs::write(&path, ron)
+        .map_err(|error| format!("could not save {}: {error}", path.display()))?;
+    Ok(format!("saved {}", path.display()))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn load_preset(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: chain_load <name>".to_string());
+    };
+    let path = preset_path(name)?;
+    let bytes = std::fs::read(&path)
+        .map_err(|error| format!("could not read {}: {error}", path.display()))?;
+    let config: ChainConfig = ron::de::from_bytes(&bytes)
+        .map_err(|error| format!("{} is corrupt: {error}", path.display()))?;
+    world.insert_resource(config);
+    Ok(format!("loaded {name}"))
+}
+
+#[cfg(target_family = "wasm")]
+fn save_preset(_: &mut World, _: &[&str]) -> Result<String, String> {
+    Err("presets are not supported on the web".to_string())
+}
+
+#[cfg(target_family = "wasm")]
+fn load_preset(_: &mut World, _: &[&str]) -> Result<String, String> {
+    Err("presets are not supported on the web".to_string())
+}
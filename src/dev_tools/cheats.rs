@@ -0,0 +1,56 @@
+//! Console commands for the cheats in [`Cheats`].
+
+use bevy::prelude::*;
+
+use crate::{
+    dev_tools::console::ConsoleAppExt,
+    game::{cheats::Cheats, player::Player},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_console_command("god", "god: toggle god mode", toggle_god_mode);
+    app.add_console_command(
+        "hooks",
+        "hooks: toggle infinite hooks (chains never expire)",
+        toggle_infinite_hooks,
+    );
+    app.add_console_command("warp", "warp <x> <y>: move the player", warp);
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+fn toggle_god_mode(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let mut cheats = world.resource_mut::<Cheats>();
+    cheats.god_mode = !cheats.god_mode;
+    cheats.used = true;
+    Ok(format!("god mode {}", on_off(cheats.god_mode)))
+}
+
+fn toggle_infinite_hooks(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let mut cheats = world.resource_mut::<Cheats>();
+    cheats.infinite_hooks = !cheats.infinite_hooks;
+    cheats.used = true;
+    Ok(format!("infinite hooks {}", on_off(cheats.infinite_hooks)))
+}
+
+/// Jump anywhere, not just to checkpoints.
+fn warp(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [x, y] = args else {
+        return Err("usage: warp <x> <y>".to_string());
+    };
+    let position = Vec2::new(
+        x.parse()
+            .map_err(|_| format!("<x> must be a number, got `{x}`"))?,
+        y.parse()
+            .map_err(|_| format!("<y> must be a number, got `{y}`"))?,
+    );
+    let mut player_query = world.query_filtered::<&mut Transform, With<Player>>();
+    let mut transform = player_query
+        .single_mut(world)
+        .map_err(|_| "no player to warp".to_string())?;
+    transform.translation = position.extend(transform.translation.z);
+    world.resource_mut::<Cheats>().used = true;
+    Ok(format!("warped to {position}"))
+}
@@ -0,0 +1,272 @@
+//! A debug console for running commands while the game is running.
+//!
+//! Toggle it with F1. Other modules add their own commands with
+//! [`ConsoleAppExt::add_console_command`]. Commands that change the world
+//! should go through [`history::execute`] so they can be undone.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use avian2d::prelude::*;
+use bevy::{
+    input::{
+        ButtonState,
+        common_conditions::input_just_pressed,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::history::{self, CommandHistory, EditCommand};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Console>();
+    app.init_resource::<ConsoleCommands>();
+    app.register_type::<ConsoleRoot>();
+    app.register_type::<ConsoleText>();
+
+    app.add_console_command("help", "help: list all commands", help);
+    app.add_console_command("undo", "undo: revert the last change", undo);
+    app.add_console_command("redo", "redo: re-apply the last undone change", redo);
+    app.add_console_command("history", "history: list undoable changes", list_history);
+    app.add_console_command("gravity", "gravity <x> <y>: set gravity", set_gravity);
+
+    app.add_systems(Startup, spawn_console);
+    app.add_systems(
+        Update,
+        (
+            toggle_console.run_if(input_just_pressed(TOGGLE_KEY)),
+            (read_console_input, update_console_text)
+                .chain()
+                .run_if(console_open),
+        )
+            .chain(),
+    );
+}
+
+const TOGGLE_KEY: KeyCode = KeyCode::F1;
+const MAX_LOG_LINES: usize = 12;
+
+/// A console command: gets the world and the arguments after the command
+/// name, returns a message to print.
+pub type ConsoleCommandFn = fn(&mut World, &[&str]) -> Result<String, String>;
+
+/// All registered console commands by name, with their help text.
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, (&'static str, ConsoleCommandFn)>);
+
+pub trait ConsoleAppExt {
+    /// Register a console command. `help` should start with the usage, e.g.
+    /// `"gravity <x> <y>: set gravity"`.
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        run: ConsoleCommandFn,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        run: ConsoleCommandFn,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<ConsoleCommands>()
+            .0
+            .insert(name, (help, run));
+        self
+    }
+}
+
+/// Whether the console is open, what's typed into it and what it printed.
+#[derive(Resource, Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    log: VecDeque<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        if self.log.len() == MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(line.into());
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ConsoleRoot;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ConsoleText;
+
+/// Run condition for systems that shouldn't react to keys typed into the console.
+pub fn console_open(console: Res<Console>) -> bool {
+    console.open
+}
+
+fn spawn_console(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Console"),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Px(0.0),
+            right: Px(0.0),
+            bottom: Px(0.0),
+            padding: UiRect::all(Px(10.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        GlobalZIndex(10),
+        Visibility::Hidden,
+        ConsoleRoot,
+        children![(
+            Name::new("Console Text"),
+            Text::default(),
+            TextFont::from_font_size(16.0),
+            ConsoleText,
+        )],
+    ));
+}
+
+fn toggle_console(
+    mut console: ResMut<Console>,
+    mut console_query: Query<&mut Visibility, With<ConsoleRoot>>,
+) {
+    console.open = !console.open;
+    for mut visibility in &mut console_query {
+        *visibility = if console.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn read_console_input(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(text) => console.input.push_str(text),
+            Key::Space => console.input.push(' '),
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                console.print(format!("> {line}"));
+                commands.queue(move |world: &mut World| run_line(world, &line));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run_line(world: &mut World, line: &str) {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return;
+    };
+    let args: Vec<&str> = words.collect();
+
+    let command = world
+        .resource::<ConsoleCommands>()
+        .0
+        .get(name)
+        .map(|&(_, run)| run);
+    let output = match command {
+        Some(run) => run(world, &args).unwrap_or_else(|error| format!("error: {error}")),
+        None => format!("unknown command `{name}`, try `help`"),
+    };
+
+    let mut console = world.resource_mut::<Console>();
+    for line in output.lines() {
+        console.print(line);
+    }
+}
+
+fn update_console_text(console: Res<Console>, mut text: Single<&mut Text, With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+    let mut lines: Vec<String> = console.log.iter().cloned().collect();
+    lines.push(format!("> {}_", console.input));
+    text.0 = lines.join("\n");
+}
+
+fn help(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let commands = world.resource::<ConsoleCommands>();
+    Ok(commands
+        .0
+        .values()
+        .map(|(help, _)| *help)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn undo(world: &mut World, _: &[&str]) -> Result<String, String> {
+    history::undo(world)
+        .map(|description| format!("undid: {description}"))
+        .ok_or_else(|| "nothing to undo".to_string())
+}
+
+fn redo(world: &mut World, _: &[&str]) -> Result<String, String> {
+    history::redo(world)
+        .map(|description| format!("redid: {description}"))
+        .ok_or_else(|| "nothing to redo".to_string())
+}
+
+fn list_history(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let descriptions: Vec<String> = world.resource::<CommandHistory>().descriptions().collect();
+    if descriptions.is_empty() {
+        return Ok("history is empty".to_string());
+    }
+    Ok(descriptions.join("\n"))
+}
+
+/// Parse a float argument for a console command.
+pub fn parse_arg(args: &[&str], index: usize, name: &str) -> Result<f32, String> {
+    let arg = args
+        .get(index)
+        .ok_or_else(|| format!("missing argument <{name}>"))?;
+    arg.parse()
+        .map_err(|_| format!("<{name}> must be a number, got `{arg}`"))
+}
+
+fn set_gravity(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let to = Vec2::new(parse_arg(args, 0, "x")?, parse_arg(args, 1, "y")?);
+    let from = world.resource::<Gravity>().0;
+    history::execute(world, SetGravity { from, to });
+    Ok(format!("gravity set to {to}"))
+}
+
+struct SetGravity {
+    from: Vec2,
+    to: Vec2,
+}
+
+impl EditCommand for SetGravity {
+    fn apply(&mut self, world: &mut World) {
+        world.resource_mut::<Gravity>().0 = self.to;
+    }
+
+    fn revert(&mut self, world: &mut World) {
+        world.resource_mut::<Gravity>().0 = self.from;
+    }
+
+    fn describe(&self) -> String {
+        format!("Set gravity to {}", self.to)
+    }
+}
@@ -0,0 +1,376 @@
+//! Checking that a replay plays out the same way twice.
+//!
+//! `determinism_audit [file]` plays the last replay, or the replay file given,
+//! twice in a row. On every fixed tick of the first pass it keeps each
+//! physics body's position, rotation and velocities, the player's health and
+//! the score, and a hash of it all. The second pass hashes the same things
+//! on every tick and compares. The first tick that differs is reported with
+//! the values that differ, which points at whatever stopped being
+//! deterministic. Results go to the log.
+//!
+//! Starting the game with `--determinism-audit <file>` runs an audit right
+//! away and exits once it's done, with an error if the passes diverged, so it
+//! can run headless in CI.
+//!
+//! Entity ids aren't the same from one pass to the next, so bodies are told
+//! apart by name, and by their order among bodies with the same name. Seeking
+//! in the replay viewer starts the level over, which throws the tick count
+//! off, so leave it alone while an audit runs.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    dev_tools::console::ConsoleAppExt,
+    game::{chain_impact::PlayerHealth, objectives::Score},
+    platform::ActivePlatform,
+    replay::{Replay, playing, read_last_replay, start_playback},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Audit>();
+    app.add_console_command(
+        "determinism_audit",
+        "determinism_audit [file]: play a replay twice and compare every tick",
+        start_audit,
+    );
+
+    #[cfg(not(target_family = "wasm"))]
+    app.add_systems(Startup, read_audit_argument);
+    app.add_systems(OnEnter(Screen::Gameplay), reset_tick);
+    app.add_systems(
+        FixedLast,
+        check_tick.run_if(in_state(Screen::Gameplay).and(playing).and(auditing)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), finish_pass.run_if(auditing));
+    app.add_systems(
+        Update,
+        start_second_pass.run_if(not(in_state(Screen::Gameplay)).and(between_passes)),
+    );
+}
+
+/// How many differing values to list at the first divergent tick.
+const MAX_REPORTED: usize = 12;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+enum AuditPass {
+    #[default]
+    Idle,
+    First,
+    /// The first pass is over, the second starts once the screen it ended on
+    /// has been entered.
+    Between,
+    Second,
+}
+
+/// One physics body's state on one tick.
+#[derive(Clone, PartialEq, Debug)]
+struct BodyState {
+    /// Its name, and its place among bodies with the same name.
+    key: (String, usize),
+    position: Vec2,
+    rotation: f32,
+    linear: Vec2,
+    angular: f32,
+}
+
+/// Everything compared on one tick.
+#[derive(Clone, Debug)]
+struct TickState {
+    hash: u64,
+    bodies: Vec<BodyState>,
+    health: f32,
+    points: u32,
+}
+
+#[derive(Resource, Default)]
+struct Audit {
+    pass: AuditPass,
+    replay: Option<Replay>,
+    /// Fixed ticks since the level started.
+    tick: usize,
+    /// What the first pass saw on each tick.
+    first: Vec<TickState>,
+    /// What went wrong, once something has.
+    divergence: Option<String>,
+    /// Whether to exit once done, for audits started from the command line.
+    exit_when_done: bool,
+}
+
+fn auditing(audit: Res<Audit>) -> bool {
+    matches!(audit.pass, AuditPass::First | AuditPass::Second)
+}
+
+fn between_passes(audit: Res<Audit>) -> bool {
+    audit.pass == AuditPass::Between
+}
+
+fn start_audit(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if world.resource::<Audit>().pass != AuditPass::Idle {
+        return Err("an audit is already running".to_string());
+    }
+    let replay = match args {
+        [] => read_last_replay(world.resource::<ActivePlatform>())
+            .ok_or("there is no last replay to audit")?,
+        #[cfg(not(target_family = "wasm"))]
+        [path] => std::fs::read(path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| Replay::from_bytes(&bytes))
+            .map_err(|error| format!("couldn't read {path}: {error}"))?,
+        _ => return Err("usage: determinism_audit [file]".to_string()),
+    };
+    begin(world, replay, false);
+    Ok("playing the replay twice, results go to the log".to_string())
+}
+
+fn begin(world: &mut World, replay: Replay, exit_when_done: bool) {
+    *world.resource_mut::<Audit>() = Audit {
+        pass: AuditPass::First,
+        replay: Some(replay.clone()),
+        exit_when_done,
+        ..default()
+    };
+    start_playback(world, replay);
+}
+
+/// Audit the replay passed with `--determinism-audit <file>` right away.
+#[cfg(not(target_family = "wasm"))]
+fn read_audit_argument(mut commands: Commands) {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(path) = args
+        .iter()
+        .position(|arg| arg == "--determinism-audit")
+        .and_then(|i| args.get(i + 1))
+    else {
+        return;
+    };
+    let replay = std::fs::read(path)
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| Replay::from_bytes(&bytes));
+    match replay {
+        Ok(replay) => {
+            info!("Auditing {path}");
+            commands.queue(move |world: &mut World| begin(world, replay, true));
+        }
+        Err(error) => {
+            error!("Couldn't audit {path}: {error}");
+            commands.queue(|world: &mut World| {
+                world.send_event(AppExit::error());
+            });
+        }
+    }
+}
+
+fn reset_tick(mut audit: ResMut<Audit>) {
+    audit.tick = 0;
+}
+
+fn check_tick(
+    body_query: Query<
+        (
+            Option<&Name>,
+            &Position,
+            &Rotation,
+            &LinearVelocity,
+            &AngularVelocity,
+        ),
+        With<RigidBody>,
+    >,
+    health: Res<PlayerHealth>,
+    score: Res<Score>,
+    mut audit: ResMut<Audit>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    // Only the first divergent tick is of interest.
+    if audit.divergence.is_some() {
+        return;
+    }
+    let state = capture(&body_query, &health, &score);
+    let tick = audit.tick;
+    audit.tick += 1;
+    if audit.pass == AuditPass::First {
+        audit.first.push(state);
+        return;
+    }
+
+    let report = match audit.first.get(tick) {
+        None => Some(format!(
+            "the second pass ran past the {} ticks of the first",
+            audit.first.len()
+        )),
+        Some(first) if first.hash != state.hash => Some(differences(first, &state)),
+        Some(_) => None,
+    };
+    if let Some(report) = report {
+        audit.divergence = Some(format!("diverged on tick {tick}: {report}"));
+        next_screen.set(Screen::Title);
+    }
+}
+
+/// The state of every body, in a stable order, and its hash.
+fn capture(
+    body_query: &Query<
+        (
+            Option<&Name>,
+            &Position,
+            &Rotation,
+            &LinearVelocity,
+            &AngularVelocity,
+        ),
+        With<RigidBody>,
+    >,
+    health: &PlayerHealth,
+    score: &Score,
+) -> TickState {
+    let mut bodies: Vec<_> = body_query
+        .iter()
+        .map(|(name, position, rotation, linear, angular)| BodyState {
+            key: (name.map_or("unnamed", Name::as_str).to_string(), 0),
+            position: position.0,
+            rotation: rotation.as_radians(),
+            linear: linear.0,
+            angular: angular.0,
+        })
+        .collect();
+    // Sorting is stable, so bodies with the same name keep the order they
+    // were spawned in.
+    bodies.sort_by(|a, b| a.key.0.cmp(&b.key.0));
+    for i in 1..bodies.len() {
+        if bodies[i].key.0 == bodies[i - 1].key.0 {
+            bodies[i].key.1 = bodies[i - 1].key.1 + 1;
+        }
+    }
+
+    let mut hasher = DefaultHasher::new();
+    for body in &bodies {
+        body.key.hash(&mut hasher);
+        for value in [
+            body.position.x,
+            body.position.y,
+            body.rotation,
+            body.linear.x,
+            body.linear.y,
+            body.angular,
+        ] {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+    health.current.to_bits().hash(&mut hasher);
+    score.points.hash(&mut hasher);
+    TickState {
+        hash: hasher.finish(),
+        bodies,
+        health: health.current,
+        points: score.points,
+    }
+}
+
+/// What differs between two ticks' states, one value per line.
+fn differences(first: &TickState, second: &TickState) -> String {
+    let mut lines = Vec::new();
+    if first.health != second.health {
+        lines.push(format!("health {} vs {}", first.health, second.health));
+    }
+    if first.points != second.points {
+        lines.push(format!("points {} vs {}", first.points, second.points));
+    }
+    if first.bodies.len() != second.bodies.len() {
+        lines.push(format!(
+            "{} bodies vs {}",
+            first.bodies.len(),
+            second.bodies.len()
+        ));
+    }
+    for body in &first.bodies {
+        let (name, index) = &body.key;
+        let Some(other) = second.bodies.iter().find(|other| other.key == body.key) else {
+            lines.push(format!("{name} #{index} only in the first pass"));
+            continue;
+        };
+        let values = [
+            (
+                "position",
+                body.position.to_string(),
+                other.position.to_string(),
+            ),
+            (
+                "rotation",
+                body.rotation.to_string(),
+                other.rotation.to_string(),
+            ),
+            (
+                "velocity",
+                body.linear.to_string(),
+                other.linear.to_string(),
+            ),
+            ("spin", body.angular.to_string(), other.angular.to_string()),
+        ];
+        for (what, a, b) in values {
+            if a != b {
+                lines.push(format!("{name} #{index} {what} {a} vs {b}"));
+            }
+        }
+    }
+    for body in &second.bodies {
+        if !first.bodies.iter().any(|other| other.key == body.key) {
+            let (name, index) = &body.key;
+            lines.push(format!("{name} #{index} only in the second pass"));
+        }
+    }
+
+    let hidden = lines.len().saturating_sub(MAX_REPORTED);
+    lines.truncate(MAX_REPORTED);
+    if hidden > 0 {
+        lines.push(format!("and {hidden} more"));
+    }
+    format!("\n- {}", lines.join("\n- "))
+}
+
+fn finish_pass(mut audit: ResMut<Audit>, mut exit: EventWriter<AppExit>) {
+    if audit.pass == AuditPass::First && audit.divergence.is_none() {
+        info!(
+            "First audit pass done after {} ticks, playing it again",
+            audit.first.len()
+        );
+        audit.pass = AuditPass::Between;
+        return;
+    }
+
+    if audit.divergence.is_none() && audit.tick != audit.first.len() {
+        audit.divergence = Some(format!(
+            "ran {} ticks the first time and {} the second",
+            audit.first.len(),
+            audit.tick
+        ));
+    }
+    let diverged = audit.divergence.is_some();
+    match &audit.divergence {
+        Some(divergence) => error!("Determinism audit failed, the replay {divergence}"),
+        None => info!(
+            "Determinism audit passed, all {} ticks matched",
+            audit.first.len()
+        ),
+    }
+    let exit_when_done = audit.exit_when_done;
+    *audit = Audit::default();
+    if exit_when_done {
+        exit.write(if diverged {
+            AppExit::error()
+        } else {
+            AppExit::Success
+        });
+    }
+}
+
+fn start_second_pass(world: &mut World) {
+    let mut audit = world.resource_mut::<Audit>();
+    let Some(replay) = audit.replay.clone() else {
+        audit.pass = AuditPass::Idle;
+        return;
+    };
+    audit.pass = AuditPass::Second;
+    start_playback(world, replay);
+}
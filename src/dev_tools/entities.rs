@@ -0,0 +1,365 @@
+//! An entity browser for tracking down leaked or misbehaving entities.
+//!
+//! Toggle it with F3. Tab cycles the component filter, V toggles showing only
+//! visible entities, and the `find <text>` console command filters by name.
+//! Click an entity to select it and list its components, then press Delete
+//! (or click "Despawn selected") to despawn it.
+
+use std::time::Duration;
+
+use avian2d::prelude::*;
+use bevy::{
+    ecs::world::EntityRef, input::common_conditions::input_just_pressed, prelude::*,
+    time::common_conditions::on_timer, ui::Val::*,
+};
+
+use crate::{
+    dev_tools::console::{ConsoleAppExt, console_open},
+    game::{
+        chain::{ChainLink, ChainRoot},
+        gameplay_id::{GameplayId, GameplayIds},
+        player::Player,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<EntityBrowser>();
+    app.register_type::<EntityBrowserUi>();
+    app.register_type::<EntityBrowserList>();
+    app.register_type::<EntityBrowserText>();
+
+    app.add_console_command(
+        "find",
+        "find [text]: filter the entity browser by name",
+        set_name_filter,
+    );
+    app.add_console_command(
+        "select",
+        "select <gameplay id>: select an entity in the entity browser",
+        select_by_gameplay_id,
+    );
+
+    app.add_systems(Startup, spawn_browser);
+    app.add_systems(
+        Update,
+        (
+            toggle_browser.run_if(input_just_pressed(TOGGLE_KEY)),
+            (
+                handle_browser_keys.run_if(not(console_open)),
+                refresh_browser
+                    .run_if(resource_changed::<EntityBrowser>.or(on_timer(REFRESH_INTERVAL))),
+            )
+                .chain()
+                .run_if(browser_open),
+        )
+            .chain(),
+    );
+}
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_ROWS: usize = 25;
+const ROW_COLOR: Color = Color::srgb(0.85, 0.85, 0.85);
+const SELECTED_ROW_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+#[derive(Resource, Default)]
+pub struct EntityBrowser {
+    open: bool,
+    filter: ComponentFilter,
+    name: String,
+    visible_only: bool,
+    /// The entity whose components are listed below the results.
+    pub selected: Option<Entity>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+enum ComponentFilter {
+    #[default]
+    All,
+    Player,
+    ChainLink,
+    ChainRoot,
+    RigidBody,
+    Ui,
+    /// Top-level entities that aren't scoped to a screen and will survive
+    /// screen changes. Leaks usually show up here.
+    Unscoped,
+}
+
+impl ComponentFilter {
+    const ALL: [ComponentFilter; 7] = [
+        ComponentFilter::All,
+        ComponentFilter::Player,
+        ComponentFilter::ChainLink,
+        ComponentFilter::ChainRoot,
+        ComponentFilter::RigidBody,
+        ComponentFilter::Ui,
+        ComponentFilter::Unscoped,
+    ];
+
+    fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&filter| filter == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn matches(self, entity: &EntityRef) -> bool {
+        match self {
+            ComponentFilter::All => true,
+            ComponentFilter::Player => entity.contains::<Player>(),
+            ComponentFilter::ChainLink => entity.contains::<ChainLink>(),
+            ComponentFilter::ChainRoot => entity.contains::<ChainRoot>(),
+            ComponentFilter::RigidBody => entity.contains::<RigidBody>(),
+            ComponentFilter::Ui => entity.contains::<Node>(),
+            ComponentFilter::Unscoped => {
+                !entity.contains::<ChildOf>()
+                    && !entity.contains::<StateScoped<Screen>>()
+                    && !entity.contains::<Window>()
+                    && !entity.contains::<Camera>()
+            }
+        }
+    }
+}
+
+/// Marks everything spawned by the browser, so it doesn't list itself.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct EntityBrowserUi;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct EntityBrowserList;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct EntityBrowserText;
+
+fn browser_open(browser: Res<EntityBrowser>) -> bool {
+    browser.open
+}
+
+fn spawn_browser(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Entity Browser"),
+        EntityBrowserUi,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Px(0.0),
+            top: Px(0.0),
+            width: Px(360.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Px(10.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        GlobalZIndex(10),
+        Visibility::Hidden,
+        children![
+            (
+                Name::new("Entity Browser Text"),
+                EntityBrowserUi,
+                EntityBrowserText,
+                Text::default(),
+                TextFont::from_font_size(14.0),
+            ),
+            (
+                Name::new("Entity Browser List"),
+                EntityBrowserUi,
+                EntityBrowserList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+            ),
+        ],
+    ));
+}
+
+fn toggle_browser(
+    mut browser: ResMut<EntityBrowser>,
+    mut root_query: Query<&mut Visibility, (With<EntityBrowserUi>, Without<ChildOf>)>,
+) {
+    browser.open = !browser.open;
+    for mut visibility in &mut root_query {
+        *visibility = if browser.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn handle_browser_keys(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut browser: ResMut<EntityBrowser>,
+) {
+    if input.just_pressed(KeyCode::Tab) {
+        browser.filter = browser.filter.next();
+    }
+    if input.just_pressed(KeyCode::KeyV) {
+        browser.visible_only = !browser.visible_only;
+    }
+    if input.just_pressed(KeyCode::Delete) {
+        commands.queue(despawn_selected);
+    }
+}
+
+fn despawn_selected(world: &mut World) {
+    let Some(entity) = world.resource_mut::<EntityBrowser>().selected.take() else {
+        return;
+    };
+    if world.get_entity(entity).is_ok() {
+        info!("Despawning {entity} from the entity browser");
+        world.despawn(entity);
+    }
+}
+
+/// A readable label for an entity: its name if it has one, plus its ID.
+fn entity_label(entity: &EntityRef) -> String {
+    match entity.get::<Name>() {
+        Some(name) => format!("{name} ({})", entity.id()),
+        None => format!("Entity {}", entity.id()),
+    }
+}
+
+/// `bevy_transform::components::transform::Transform` -> `Transform`.
+pub(super) fn short_type_name(name: &str) -> &str {
+    let path = name.split('<').next().unwrap_or(name);
+    let start = path.rfind("::").map_or(0, |i| i + 2);
+    &name[start..]
+}
+
+/// Rebuild the result rows and the selection details. Rows are only respawned
+/// when the results change, so clicks aren't lost to a refresh.
+fn refresh_browser(world: &mut World, mut shown: Local<Vec<(Entity, String, bool)>>) {
+    let browser = world.resource::<EntityBrowser>();
+    let (filter, visible_only, selected) = (browser.filter, browser.visible_only, browser.selected);
+    let name = browser.name.clone();
+    let lowercase_name = name.to_lowercase();
+
+    let mut matches: Vec<(Entity, String)> = world
+        .iter_entities()
+        // Observers are their own entities, but only add noise here.
+        .filter(|entity| !entity.contains::<EntityBrowserUi>() && !entity.contains::<Observer>())
+        .filter(|entity| filter.matches(entity))
+        .filter(|entity| !visible_only || entity.get::<ViewVisibility>().is_some_and(|v| v.get()))
+        .map(|entity| (entity.id(), entity_label(&entity)))
+        .filter(|(_, label)| label.to_lowercase().contains(&lowercase_name))
+        .collect();
+    matches.sort_by_key(|&(entity, _)| entity.index());
+    let total = matches.len();
+    matches.truncate(MAX_ROWS);
+
+    let selected = selected.filter(|&entity| world.get_entity(entity).is_ok());
+    let mut lines = vec![
+        format!(
+            "Filter (Tab): {filter:?}  Visible only (V): {}",
+            if visible_only { "yes" } else { "no" }
+        ),
+        format!("Name (`find` in console): \"{name}\""),
+        format!("{total} match(es), showing {}", matches.len()),
+    ];
+    if let Some(entity) = selected {
+        let mut components: Vec<&str> = world
+            .inspect_entity(entity)
+            .into_iter()
+            .flatten()
+            .map(|info| short_type_name(info.name()))
+            .collect();
+        components.sort_unstable();
+        let gameplay_id = world
+            .resource::<GameplayIds>()
+            .id(entity)
+            .map_or(String::new(), |id| format!(" (gameplay ID {})", id.0));
+        lines.push(format!(
+            "Selected {entity}{gameplay_id}: {}",
+            components.join(", ")
+        ));
+    }
+    if let Ok(mut text) = world
+        .query_filtered::<&mut Text, With<EntityBrowserText>>()
+        .single_mut(world)
+    {
+        text.0 = lines.join("\n");
+    }
+
+    let rows: Vec<(Entity, String, bool)> = matches
+        .into_iter()
+        .map(|(entity, label)| (entity, label, Some(entity) == selected))
+        .collect();
+    if *shown == rows {
+        return;
+    }
+    let Ok(list) = world
+        .query_filtered::<Entity, With<EntityBrowserList>>()
+        .single(world)
+    else {
+        return;
+    };
+    world.entity_mut(list).despawn_related::<Children>();
+    for (entity, label, is_selected) in &rows {
+        let entity = *entity;
+        world
+            .spawn((
+                Name::new("Entity Browser Row"),
+                EntityBrowserUi,
+                Text(label.clone()),
+                TextFont::from_font_size(14.0),
+                TextColor(if *is_selected {
+                    SELECTED_ROW_COLOR
+                } else {
+                    ROW_COLOR
+                }),
+                ChildOf(list),
+            ))
+            .observe(
+                move |_: Trigger<Pointer<Click>>, mut browser: ResMut<EntityBrowser>| {
+                    browser.selected = Some(entity);
+                },
+            );
+    }
+    if selected.is_some() {
+        world
+            .spawn((
+                Name::new("Despawn Selected"),
+                EntityBrowserUi,
+                Text("[Despawn selected]".to_string()),
+                TextFont::from_font_size(14.0),
+                TextColor(SELECTED_ROW_COLOR),
+                ChildOf(list),
+            ))
+            .observe(|_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                commands.queue(despawn_selected);
+            });
+    }
+    *shown = rows;
+}
+
+fn set_name_filter(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = args.join(" ");
+    let message = if name.is_empty() {
+        "entity browser name filter cleared".to_string()
+    } else {
+        format!("entity browser showing names containing \"{name}\"")
+    };
+    world.resource_mut::<EntityBrowser>().name = name;
+    Ok(message)
+}
+
+fn select_by_gameplay_id(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let arg = args.first().ok_or("missing argument <gameplay id>")?;
+    let id = GameplayId(
+        arg.parse()
+            .map_err(|_| format!("<gameplay id> must be a whole number, got `{arg}`"))?,
+    );
+    let entity = world
+        .resource::<GameplayIds>()
+        .entity(id)
+        .ok_or_else(|| format!("no entity has gameplay ID {}", id.0))?;
+    world.resource_mut::<EntityBrowser>().selected = Some(entity);
+    Ok(format!("selected {entity}"))
+}
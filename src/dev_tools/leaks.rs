@@ -0,0 +1,127 @@
+//! An entity-leak detector.
+//!
+//! Every time a level starts, including restarts, the number of entities in
+//! each archetype is sampled. Whatever the previous level left behind is
+//! still around at that point, so an archetype that grows on every one of
+//! the last few starts is most likely leaking, e.g. joints or audio entities
+//! that nothing cleans up. Growing archetypes are logged and listed in the
+//! top left corner.
+
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{dev_tools::entities::short_type_name, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<EntitySamples>();
+    app.register_type::<LeakReportText>();
+
+    app.add_systems(Startup, spawn_leak_report);
+    app.add_systems(OnEnter(Screen::Gameplay), sample_entities);
+}
+
+/// How many level starts in a row an archetype has to grow on to be
+/// reported.
+const GROWING_SAMPLES: usize = 3;
+const MAX_REPORTED: usize = 10;
+
+/// Entities per archetype at each of the latest level starts, oldest first.
+/// Archetypes are named by their components.
+#[derive(Resource, Default)]
+struct EntitySamples(Vec<BTreeMap<String, usize>>);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct LeakReportText;
+
+fn spawn_leak_report(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Leak Report"),
+        LeakReportText,
+        Text::default(),
+        TextFont::from_font_size(14.0),
+        TextColor(Color::srgb(1.0, 0.45, 0.35)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Px(10.0),
+            top: Px(10.0),
+            max_width: Px(600.0),
+            ..default()
+        },
+        GlobalZIndex(10),
+        Pickable::IGNORE,
+    ));
+}
+
+fn sample_entities(world: &mut World) {
+    let mut sample = BTreeMap::new();
+    for archetype in world.archetypes().iter().filter(|a| !a.is_empty()) {
+        let mut components: Vec<&str> = archetype
+            .components()
+            .filter_map(|id| world.components().get_info(id))
+            .map(|info| short_type_name(info.name()))
+            .collect();
+        components.sort_unstable();
+        *sample.entry(components.join(", ")).or_default() += archetype.len();
+    }
+
+    let mut samples = world.resource_mut::<EntitySamples>();
+    samples.0.push(sample);
+    if samples.0.len() > GROWING_SAMPLES + 1 {
+        samples.0.remove(0);
+    }
+    let report = leak_report(&samples.0);
+
+    for line in &report {
+        warn!("Possible entity leak: {line}");
+    }
+    if let Ok(mut text) = world
+        .query_filtered::<&mut Text, With<LeakReportText>>()
+        .single_mut(world)
+    {
+        text.0 = if report.is_empty() {
+            String::new()
+        } else {
+            format!("Possible entity leaks:\n{}", report.join("\n"))
+        };
+    }
+}
+
+/// The archetypes that grew between every pair of consecutive samples, with
+/// how much they grew by last time.
+fn leak_report(samples: &[BTreeMap<String, usize>]) -> Vec<String> {
+    if samples.len() <= GROWING_SAMPLES {
+        return Vec::new();
+    }
+    let count = |sample: &BTreeMap<String, usize>, archetype: &str| {
+        sample.get(archetype).copied().unwrap_or_default()
+    };
+    let (previous, latest) = (&samples[samples.len() - 2], &samples[samples.len() - 1]);
+    let mut growing: Vec<(usize, &String)> = latest
+        .keys()
+        .filter(|archetype| {
+            samples
+                .windows(2)
+                .all(|pair| count(&pair[1], archetype) > count(&pair[0], archetype))
+        })
+        .map(|archetype| (count(latest, archetype), archetype))
+        .collect();
+    // Biggest first.
+    growing.sort_unstable();
+    growing.reverse();
+
+    let total = growing.len();
+    let mut lines: Vec<String> = growing
+        .into_iter()
+        .take(MAX_REPORTED)
+        .map(|(latest_count, archetype)| {
+            let grown = latest_count - count(previous, archetype);
+            format!("+{grown} (now {latest_count}) [{archetype}]")
+        })
+        .collect();
+    if total > MAX_REPORTED {
+        lines.push(format!("...and {} more", total - MAX_REPORTED));
+    }
+    lines
+}
@@ -1,5 +1,18 @@
 //! Development tools for the game. This plugin is only enabled in dev builds.
 
+pub mod bug_report;
+mod chain_invariants;
+mod chain_tuning;
+mod cheats;
+pub mod console;
+mod determinism_audit;
+mod entities;
+mod leaks;
+mod physics_debug;
+mod scene_export;
+#[cfg(feature = "tas")]
+mod tas;
+
 use bevy::{
     dev_tools::states::log_transitions, input::common_conditions::input_just_pressed, prelude::*,
     ui::UiDebugOptions,
@@ -8,6 +21,21 @@ use bevy::{
 use crate::screens::Screen;
 
 pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((
+        bug_report::plugin,
+        chain_invariants::plugin,
+        chain_tuning::plugin,
+        cheats::plugin,
+        console::plugin,
+        determinism_audit::plugin,
+        entities::plugin,
+        leaks::plugin,
+        physics_debug::plugin,
+        scene_export::plugin,
+        #[cfg(feature = "tas")]
+        tas::plugin,
+    ));
+
     // Log `Screen` state transitions.
     app.add_systems(Update, log_transitions::<Screen>);
 
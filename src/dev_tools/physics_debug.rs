@@ -0,0 +1,143 @@
+//! A physics overlay for tuning chains.
+//!
+//! Toggle it with F5. It turns on Avian's debug rendering of colliders and
+//! joints, draws every chain joint colored by how far it's stretched, and
+//! lists how many chains, links and joints are active. The chain tuning panel
+//! (F4) changes [`ChainConfig`] live, so the two work well together.
+
+use avian2d::prelude::*;
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+
+use crate::game::chain::{ChainConfig, ChainLink, ChainState, LINK_SIZE};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(PhysicsDebugPlugin::default());
+    app.init_resource::<PhysicsOverlay>();
+    app.register_type::<PhysicsOverlayText>();
+
+    app.add_systems(Startup, (hide_physics_gizmos, spawn_overlay_text));
+    app.add_systems(
+        Update,
+        (
+            toggle_overlay.run_if(input_just_pressed(TOGGLE_KEY)),
+            (draw_joint_tension, update_overlay_text).run_if(overlay_open),
+        )
+            .chain(),
+    );
+}
+
+const TOGGLE_KEY: KeyCode = KeyCode::F5;
+const RELAXED_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+const STRETCHED_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+/// Drawn across the joint, so it shows up on top of the link colliders.
+const TENSION_MARK_WIDTH: f32 = 8.0;
+
+#[derive(Resource, Default)]
+struct PhysicsOverlay {
+    open: bool,
+}
+
+fn overlay_open(overlay: Res<PhysicsOverlay>) -> bool {
+    overlay.open
+}
+
+/// Avian draws its gizmos as soon as the plugin is added.
+fn hide_physics_gizmos(mut config_store: ResMut<GizmoConfigStore>) {
+    config_store.config_mut::<PhysicsGizmos>().0.enabled = false;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct PhysicsOverlayText;
+
+fn spawn_overlay_text(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Physics Overlay"),
+        PhysicsOverlayText,
+        Text::default(),
+        TextFont::from_font_size(14.0),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Px(10.0),
+            bottom: Px(10.0),
+            ..default()
+        },
+        GlobalZIndex(10),
+        Pickable::IGNORE,
+        Visibility::Hidden,
+    ));
+}
+
+fn toggle_overlay(
+    mut overlay: ResMut<PhysicsOverlay>,
+    mut config_store: ResMut<GizmoConfigStore>,
+    mut text: Single<&mut Visibility, With<PhysicsOverlayText>>,
+) {
+    overlay.open = !overlay.open;
+    config_store.config_mut::<PhysicsGizmos>().0.enabled = overlay.open;
+    **text = if overlay.open {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+}
+
+/// Mark every joint between two links, from green while it holds together to
+/// red once it's stretched far enough to build up stress.
+fn draw_joint_tension(
+    mut gizmos: Gizmos,
+    config: Res<ChainConfig>,
+    chain_state: Res<ChainState>,
+    joint_query: Query<&RevoluteJoint>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    for chain in &chain_state.chains {
+        for joint in joint_query.iter_many(&chain.joints) {
+            let Ok([previous, next]) = transform_query.get_many([joint.entity1, joint.entity2])
+            else {
+                continue;
+            };
+            let anchor1 = previous
+                .transform_point(joint.local_anchor1.extend(0.0))
+                .truncate();
+            let anchor2 = next
+                .transform_point(joint.local_anchor2.extend(0.0))
+                .truncate();
+            let stretch = anchor1.distance(anchor2) / LINK_SIZE;
+            let tension = (stretch / config.stress_tolerance.max(f32::EPSILON)).min(1.0);
+            let color = RELAXED_COLOR.mix(&STRETCHED_COLOR, tension);
+
+            let midpoint = anchor1.midpoint(anchor2);
+            let across =
+                (anchor2 - anchor1).normalize_or(Vec2::X).perp() * TENSION_MARK_WIDTH / 2.0;
+            gizmos.line_2d(anchor1, anchor2, color);
+            gizmos.line_2d(midpoint - across, midpoint + across, color);
+        }
+    }
+}
+
+fn update_overlay_text(
+    chain_state: Res<ChainState>,
+    substeps: Res<SubstepCount>,
+    link_query: Query<(), With<ChainLink>>,
+    joint_query: Query<(), With<RevoluteJoint>>,
+    mut text: Single<&mut Text, With<PhysicsOverlayText>>,
+) {
+    let chain_joints: usize = chain_state
+        .chains
+        .iter()
+        .map(|chain| {
+            chain.joints.len()
+                + usize::from(chain.rappel.is_some())
+                + usize::from(chain.hook.is_some())
+                + usize::from(chain.frozen.is_some())
+        })
+        .sum();
+    text.0 = format!(
+        "Chains: {}\nLinks: {}\nChain joints: {chain_joints}\nRevolute joints: {}\nSubsteps: {}\n(F4 to tune chains)",
+        chain_state.chains.len(),
+        link_query.iter().count(),
+        joint_query.iter().count(),
+        substeps.0,
+    );
+}
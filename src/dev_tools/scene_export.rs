@@ -0,0 +1,254 @@
+//! Exporting what's going on in gameplay as a Bevy scene, for bug reports.
+//!
+//! `scene_save <name>` writes every physics body and the joints between them
+//! to `assets/scenes/<name>.scn.ron`: where they are, how they're moving, and
+//! what they collide with. Colliders and sprites can't be serialized, so each
+//! body's shape and color go in an [`ExportedBody`] instead, and the rest of
+//! their components are left out. `scene_load <name>` restores a scene into
+//! the sandbox, rebuilding each body from its [`ExportedBody`]. The player
+//! goes where they were, and chains come back as loose links.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    dev_tools::console::ConsoleAppExt,
+    game::{
+        chain::JointCollisionDisabled, highlight::HookTarget, player::Player, sandbox::Sandbox,
+    },
+    screens::{Screen, gameplay::restart_level},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ExportedBody>();
+
+    app.add_console_command(
+        "scene_save",
+        "scene_save <name>: export the bodies in gameplay as a scene",
+        save_scene,
+    );
+    app.add_console_command(
+        "scene_load",
+        "scene_load <name>: restore an exported scene into the sandbox",
+        load_scene,
+    );
+
+    app.add_systems(Update, restore_bodies.run_if(in_state(Screen::Gameplay)));
+}
+
+const SCENE_DIR: &str = "scenes";
+
+/// What a body looked like when it was exported.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component, Default)]
+struct ExportedBody {
+    shape: ExportedShape,
+    color: Color,
+    /// Whether this was the player, who takes its place when restored.
+    player: bool,
+}
+
+#[derive(Reflect, Default, Debug)]
+enum ExportedShape {
+    /// Anything other than the shapes below, restored without a collider.
+    #[default]
+    Other,
+    Rectangle(Vec2),
+    Circle(f32),
+    Capsule {
+        radius: f32,
+        length: f32,
+    },
+}
+
+impl ExportedShape {
+    #[cfg(not(target_family = "wasm"))]
+    fn of(collider: &Collider) -> Self {
+        let shape = collider.shape_scaled();
+        if let Some(cuboid) = shape.as_cuboid() {
+            Self::Rectangle(Vec2::new(cuboid.half_extents.x, cuboid.half_extents.y) * 2.0)
+        } else if let Some(ball) = shape.as_ball() {
+            Self::Circle(ball.radius)
+        } else if let Some(capsule) = shape.as_capsule() {
+            Self::Capsule {
+                radius: capsule.radius,
+                length: capsule.half_height() * 2.0,
+            }
+        } else {
+            Self::Other
+        }
+    }
+
+    fn collider(&self) -> Option<Collider> {
+        match *self {
+            Self::Other => None,
+            Self::Rectangle(size) => Some(Collider::rectangle(size.x, size.y)),
+            Self::Circle(radius) => Some(Collider::circle(radius)),
+            Self::Capsule { radius, length } => Some(Collider::capsule(radius, length)),
+        }
+    }
+
+    /// The size of a sprite covering the shape.
+    fn size(&self) -> Vec2 {
+        match *self {
+            Self::Other => Vec2::ZERO,
+            Self::Rectangle(size) => size,
+            Self::Circle(radius) => Vec2::splat(radius * 2.0),
+            Self::Capsule { radius, length } => Vec2::new(radius * 2.0, length + radius * 2.0),
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_scene(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: scene_save <name>".to_string());
+    };
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "`{name}` is not a valid scene name, use letters, digits, - and _"
+        ));
+    }
+    let (ron, count) = export_scene(world)?;
+    let path = bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join("assets")
+        .join(SCENE_DIR)
+        .join(format!("{name}.scn.ron"));
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    std::fs::write(&path, ron)
+        .map_err(|error| format!("could not save {}: {error}", path.display()))?;
+    Ok(format!("exported {count} bodies to {}", path.display()))
+}
+
+/// Serialize the bodies in gameplay and the joints between them as a scene,
+/// returning it with how many bodies are in it.
+#[cfg(not(target_family = "wasm"))]
+pub(super) fn export_scene(world: &mut World) -> Result<(String, usize), String> {
+    if *world.resource::<State<Screen>>().get() != Screen::Gameplay {
+        return Err("there is no gameplay to export".to_string());
+    }
+
+    let bodies: Vec<(Entity, ExportedBody)> = world
+        .query_filtered::<(Entity, &Collider, Option<&Sprite>, Has<Player>), With<RigidBody>>()
+        .iter(world)
+        .map(|(entity, collider, sprite, player)| {
+            let body = ExportedBody {
+                shape: ExportedShape::of(collider),
+                color: sprite.map_or(Color::WHITE, |sprite| sprite.color),
+                player,
+            };
+            (entity, body)
+        })
+        .collect();
+    // Joints to anything left out would come back joined to nothing, and so
+    // would joints to the player, who is only a stand-in in the scene.
+    let exported = |entity: Entity| {
+        bodies
+            .iter()
+            .any(|(body, exported)| *body == entity && !exported.player)
+    };
+    let mut joints: Vec<Entity> = world
+        .query::<(Entity, &RevoluteJoint)>()
+        .iter(world)
+        .filter(|(_, joint)| exported(joint.entity1) && exported(joint.entity2))
+        .map(|(entity, _)| entity)
+        .collect();
+    joints.extend(
+        world
+            .query::<(Entity, &DistanceJoint)>()
+            .iter(world)
+            .filter(|(_, joint)| exported(joint.entity1) && exported(joint.entity2))
+            .map(|(entity, _)| entity),
+    );
+
+    let entities: Vec<Entity> = bodies.iter().map(|(entity, _)| *entity).collect();
+    for (entity, body) in bodies {
+        world.entity_mut(entity).insert(body);
+    }
+    let scene = DynamicSceneBuilder::from_world(world)
+        .deny_all()
+        .allow_component::<Name>()
+        .allow_component::<Transform>()
+        .allow_component::<RigidBody>()
+        .allow_component::<LinearVelocity>()
+        .allow_component::<AngularVelocity>()
+        .allow_component::<Mass>()
+        .allow_component::<Friction>()
+        .allow_component::<Restitution>()
+        .allow_component::<CollisionLayers>()
+        .allow_component::<HookTarget>()
+        .allow_component::<RevoluteJoint>()
+        .allow_component::<DistanceJoint>()
+        .allow_component::<JointCollisionDisabled>()
+        .allow_component::<ExportedBody>()
+        .extract_entities(entities.iter().copied().chain(joints))
+        .build();
+    for &entity in &entities {
+        world.entity_mut(entity).remove::<ExportedBody>();
+    }
+
+    let ron = scene
+        .serialize(&world.resource::<AppTypeRegistry>().read())
+        .map_err(|error| error.to_string())?;
+    Ok((ron, entities.len()))
+}
+
+#[cfg(target_family = "wasm")]
+fn save_scene(_: &mut World, _: &[&str]) -> Result<String, String> {
+    Err("exporting scenes is not supported on the web".to_string())
+}
+
+fn load_scene(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: scene_load <name>".to_string());
+    };
+    let scene = world
+        .resource::<AssetServer>()
+        .load(format!("{SCENE_DIR}/{name}.scn.ron"));
+    let mut sandbox = world.resource_mut::<Sandbox>();
+    sandbox.active = true;
+    sandbox.scene = Some(scene);
+
+    if *world.resource::<State<Screen>>().get() == Screen::Gameplay {
+        restart_level(world);
+    } else if world.resource::<ResourceHandles>().is_all_done() {
+        world
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Gameplay);
+    } else {
+        world
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Loading);
+    }
+    Ok(format!("loading {name} into the sandbox"))
+}
+
+/// Give restored bodies their colliders and sprites back, and put the player
+/// where they were.
+fn restore_bodies(
+    mut commands: Commands,
+    body_query: Query<(Entity, &ExportedBody, &Transform), Added<ExportedBody>>,
+    mut player_query: Query<&mut Transform, (With<Player>, Without<ExportedBody>)>,
+) {
+    for (entity, body, transform) in &body_query {
+        if body.player {
+            for mut player in &mut player_query {
+                player.translation = transform.translation;
+            }
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let mut restored = commands.entity(entity);
+        restored.insert(Sprite::from_color(body.color, body.shape.size()));
+        if let Some(collider) = body.shape.collider() {
+            // So jointed bodies still pass through each other
+            restored.insert((collider, ActiveCollisionHooks::FILTER_PAIRS));
+        }
+    }
+}
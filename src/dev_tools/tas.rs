@@ -0,0 +1,511 @@
+//! Tool-assisted runs: recording, editing and replaying input frame by frame.
+//!
+//! Only built with the `tas` feature. While it's on, every frame lasts exactly
+//! [`FRAME`] of game time however long it really took, and runs exactly one
+//! physics step, so the game runs at the display's frame rate and the same
+//! inputs from the same start always play out the same way. The feature also
+//! turns on Avian's `enhanced-determinism`, which makes that hold across
+//! machines. That makes scripts good for routing and for physics regression
+//! tests alike.
+//!
+//! - `tas_record <name>` restarts the level and records every frame's actions
+//!   and aim. `tas_stop` saves the recording to `tas/<name>.tas.ron`.
+//! - `tas_play <name>` restarts the level and replays a script, then pauses
+//!   where it ends.
+//! - F6 pauses and resumes gameplay, F7 advances a single frame while paused,
+//!   and F8 cycles through slower playback speeds.
+//! - F9 saves a state and F10 loads it. There are no real snapshots of the
+//!   world: a state is a frame number, and loading one restarts the level and
+//!   fast-forwards through the inputs up to it. Loading while recording drops
+//!   everything recorded after the state, so a section can be redone.
+//!
+//! Scripts are RON, as a list of segments that hold a set of actions and an
+//! aim point for some number of frames, so they can be edited by hand.
+
+use std::time::Duration;
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    dev_tools::console::ConsoleAppExt,
+    game::aim::{PlayerAim, update_player_aim},
+    input::{ActionInput, InputAction, ScriptedInput},
+    replay::replaying,
+    screens::{Screen, gameplay::restart_level},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<TasClock>();
+    app.init_resource::<TasMode>();
+    app.init_resource::<Savestate>();
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+    app.insert_resource(Time::<Fixed>::from_duration(FRAME));
+    app.configure_sets(Update, PausableSystems.run_if(frame_runs));
+
+    app.add_console_command(
+        "tas_record",
+        "tas_record <name>: restart the level and record a TAS script",
+        start_recording,
+    );
+    app.add_console_command(
+        "tas_play",
+        "tas_play <name>: restart the level and replay a TAS script",
+        start_playback,
+    );
+    app.add_console_command(
+        "tas_stop",
+        "tas_stop: stop recording or replaying, saving any recording",
+        stop,
+    );
+
+    app.add_systems(
+        First,
+        (
+            (
+                toggle_pause.run_if(input_just_pressed(PAUSE_KEY)),
+                advance_frame.run_if(input_just_pressed(ADVANCE_KEY)),
+                cycle_speed.run_if(input_just_pressed(SPEED_KEY)),
+                save_state.run_if(input_just_pressed(SAVE_STATE_KEY)),
+            )
+                .run_if(in_state(Screen::Gameplay)),
+            step_clock,
+        )
+            .chain()
+            .before(TimeSystem),
+    );
+    app.add_systems(
+        Update,
+        (
+            // Replays feed their own input.
+            feed_script_input
+                .before(AppSystems::RecordInput)
+                .run_if(not(replaying)),
+            aim_from_script
+                .in_set(AppSystems::RecordInput)
+                .after(update_player_aim),
+            record_frame
+                .after(AppSystems::RecordInput)
+                .before(AppSystems::Update),
+        )
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        load_state.run_if(in_state(Screen::Gameplay).and(input_just_pressed(LOAD_STATE_KEY))),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), resume_clock);
+}
+
+/// How long every frame lasts, in game time.
+const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+const PAUSE_KEY: KeyCode = KeyCode::F6;
+const ADVANCE_KEY: KeyCode = KeyCode::F7;
+const SPEED_KEY: KeyCode = KeyCode::F8;
+const SAVE_STATE_KEY: KeyCode = KeyCode::F9;
+const LOAD_STATE_KEY: KeyCode = KeyCode::F10;
+/// Slow playback runs a game frame every this many app frames.
+const SLOWDOWNS: [u32; 4] = [1, 2, 4, 8];
+#[cfg(not(target_family = "wasm"))]
+const SCRIPT_DIR: &str = "tas";
+
+/// Whether, and how fast, gameplay moves on.
+#[derive(Resource, Default, Debug)]
+struct TasClock {
+    paused: bool,
+    /// Run just one frame, then stay paused.
+    advance: bool,
+    /// Index into [`SLOWDOWNS`].
+    slowdown: usize,
+    /// App frames since the last game frame.
+    waited: u32,
+    /// Whether gameplay runs this frame.
+    running: bool,
+}
+
+fn frame_runs(clock: Res<TasClock>) -> bool {
+    clock.running
+}
+
+/// The actions held and where the player aimed, for one frame.
+#[derive(Clone, Debug, Default)]
+struct TasFrame {
+    press: Vec<InputAction>,
+    aim: Option<Vec2>,
+}
+
+/// The same input for a number of frames in a row.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct TasSegment {
+    frames: u32,
+    press: Vec<InputAction>,
+    aim: Option<Vec2>,
+}
+
+/// A TAS script as it's saved.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct TasScript {
+    segments: Vec<TasSegment>,
+}
+
+impl TasScript {
+    fn from_frames(frames: &[TasFrame]) -> Self {
+        let mut segments: Vec<TasSegment> = Vec::new();
+        for frame in frames {
+            match segments.last_mut() {
+                Some(segment) if segment.press == frame.press && segment.aim == frame.aim => {
+                    segment.frames += 1;
+                }
+                _ => segments.push(TasSegment {
+                    frames: 1,
+                    press: frame.press.clone(),
+                    aim: frame.aim,
+                }),
+            }
+        }
+        Self { segments }
+    }
+
+    fn frames(&self) -> Vec<TasFrame> {
+        self.segments
+            .iter()
+            .flat_map(|segment| {
+                let frame = TasFrame {
+                    press: segment.press.clone(),
+                    aim: segment.aim,
+                };
+                std::iter::repeat_n(frame, segment.frames as usize)
+            })
+            .collect()
+    }
+}
+
+/// What the TAS tools are doing.
+#[derive(Resource, Default, Debug)]
+enum TasMode {
+    #[default]
+    Idle,
+    Recording {
+        name: String,
+        frames: Vec<TasFrame>,
+    },
+    Playing {
+        name: String,
+        frames: Vec<TasFrame>,
+        /// The next frame to play.
+        cursor: usize,
+        /// Fast-forwarding to a loaded state, so play at full speed and pause
+        /// once there.
+        fast_forward_to: Option<usize>,
+        /// Pick up recording where the frames end.
+        then_record: bool,
+    },
+}
+
+impl TasMode {
+    /// How many frames have been recorded or played.
+    fn frame(&self) -> usize {
+        match self {
+            Self::Idle => 0,
+            Self::Recording { frames, .. } => frames.len(),
+            Self::Playing { cursor, .. } => *cursor,
+        }
+    }
+}
+
+/// The frame the state was saved at, if any.
+#[derive(Resource, Default, Debug)]
+struct Savestate(Option<usize>);
+
+fn toggle_pause(mut clock: ResMut<TasClock>) {
+    clock.paused = !clock.paused;
+}
+
+fn advance_frame(mut clock: ResMut<TasClock>) {
+    if clock.paused {
+        clock.advance = true;
+    }
+}
+
+fn cycle_speed(mut clock: ResMut<TasClock>) {
+    clock.slowdown = (clock.slowdown + 1) % SLOWDOWNS.len();
+    info!("TAS playback at 1/{} speed", SLOWDOWNS[clock.slowdown]);
+}
+
+/// Decide whether gameplay runs this frame, and stop game time if it doesn't.
+fn step_clock(mut clock: ResMut<TasClock>, mode: Res<TasMode>, mut time: ResMut<Time<Virtual>>) {
+    let fast_forward = matches!(
+        *mode,
+        TasMode::Playing {
+            fast_forward_to: Some(_),
+            ..
+        }
+    );
+    clock.running = if fast_forward {
+        true
+    } else if clock.paused {
+        std::mem::take(&mut clock.advance)
+    } else {
+        clock.waited += 1;
+        if clock.waited >= SLOWDOWNS[clock.slowdown] {
+            clock.waited = 0;
+            true
+        } else {
+            false
+        }
+    };
+    if clock.running {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+}
+
+/// Leaving gameplay mid-pause shouldn't freeze the menus. Restarting the
+/// level runs this too, which the TAS commands account for by setting up
+/// after restarting.
+fn resume_clock(mut clock: ResMut<TasClock>, mut mode: ResMut<TasMode>) {
+    clock.paused = false;
+    clock.advance = false;
+    if !matches!(*mode, TasMode::Idle) {
+        warn!("The level ended or restarted, so the TAS recording or playback was dropped");
+        *mode = TasMode::Idle;
+    }
+}
+
+fn feed_script_input(
+    mut commands: Commands,
+    mode: Res<TasMode>,
+    scripted: Option<ResMut<ScriptedInput>>,
+) {
+    let TasMode::Playing { frames, cursor, .. } = &*mode else {
+        if scripted.is_some() {
+            commands.remove_resource::<ScriptedInput>();
+        }
+        return;
+    };
+    let pressed = frames
+        .get(*cursor)
+        .map(|frame| frame.press.clone())
+        .unwrap_or_default();
+    match scripted {
+        Some(mut scripted) => scripted.advance(pressed),
+        None => commands.insert_resource(ScriptedInput {
+            pressed,
+            previous: Vec::new(),
+        }),
+    }
+}
+
+fn aim_from_script(mode: Res<TasMode>, mut aim: ResMut<PlayerAim>) {
+    let TasMode::Playing { frames, cursor, .. } = &*mode else {
+        return;
+    };
+    if let Some(frame) = frames.get(*cursor) {
+        aim.target = frame.aim;
+    }
+}
+
+/// Record the frame's input, or move playback on past it.
+fn record_frame(
+    mut mode: ResMut<TasMode>,
+    mut clock: ResMut<TasClock>,
+    input: ActionInput,
+    aim: Res<PlayerAim>,
+) {
+    match &mut *mode {
+        TasMode::Idle => {}
+        TasMode::Recording { frames, .. } => frames.push(TasFrame {
+            press: InputAction::ALL
+                .into_iter()
+                .filter(|&action| input.pressed(action))
+                .collect(),
+            aim: aim.target,
+        }),
+        TasMode::Playing {
+            name,
+            frames,
+            cursor,
+            fast_forward_to,
+            then_record,
+        } => {
+            *cursor += 1;
+            if fast_forward_to.is_some_and(|frame| *cursor >= frame) {
+                *fast_forward_to = None;
+                clock.paused = true;
+            }
+            if *cursor < frames.len() {
+                return;
+            }
+            clock.paused = true;
+            *mode = if *then_record {
+                TasMode::Recording {
+                    name: std::mem::take(name),
+                    frames: std::mem::take(frames),
+                }
+            } else {
+                info!("TAS script {name} finished after {cursor} frames");
+                TasMode::Idle
+            };
+        }
+    }
+}
+
+fn save_state(mode: Res<TasMode>, mut savestate: ResMut<Savestate>) {
+    if matches!(*mode, TasMode::Idle) {
+        return;
+    }
+    savestate.0 = Some(mode.frame());
+    info!("TAS state saved at frame {}", mode.frame());
+}
+
+fn load_state(world: &mut World) {
+    let Some(frame) = world.resource::<Savestate>().0 else {
+        return;
+    };
+    let mode = std::mem::take(&mut *world.resource_mut::<TasMode>());
+    let (name, mut frames, then_record) = match mode {
+        TasMode::Idle => return,
+        TasMode::Recording { name, frames } => (name, frames, true),
+        TasMode::Playing {
+            name,
+            frames,
+            then_record,
+            ..
+        } => (name, frames, then_record),
+    };
+    if then_record {
+        frames.truncate(frame);
+    }
+    restart_level(world);
+    world.remove_resource::<ScriptedInput>();
+    *world.resource_mut::<TasMode>() = if then_record && frames.is_empty() {
+        TasMode::Recording { name, frames }
+    } else {
+        TasMode::Playing {
+            name,
+            frames,
+            cursor: 0,
+            fast_forward_to: (frame > 0).then_some(frame),
+            then_record,
+        }
+    };
+    world.resource_mut::<TasClock>().paused = frame == 0;
+}
+
+fn in_gameplay(world: &World) -> Result<(), String> {
+    if world.resource::<State<Screen>>().get() == &Screen::Gameplay {
+        Ok(())
+    } else {
+        Err("start a level first".to_string())
+    }
+}
+
+fn start_recording(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: tas_record <name>".to_string());
+    };
+    in_gameplay(world)?;
+    script_path(name)?;
+    restart_level(world);
+    world.remove_resource::<ScriptedInput>();
+    world.insert_resource(Savestate::default());
+    world.insert_resource(TasMode::Recording {
+        name: name.to_string(),
+        frames: Vec::new(),
+    });
+    Ok(format!("recording {name}, tas_stop to save"))
+}
+
+fn start_playback(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: tas_play <name>".to_string());
+    };
+    in_gameplay(world)?;
+    let frames = read_script(name)?.frames();
+    if frames.is_empty() {
+        return Err(format!("{name} has no frames"));
+    }
+    let count = frames.len();
+    restart_level(world);
+    world.remove_resource::<ScriptedInput>();
+    world.insert_resource(Savestate::default());
+    world.insert_resource(TasMode::Playing {
+        name: name.to_string(),
+        frames,
+        cursor: 0,
+        fast_forward_to: None,
+        then_record: false,
+    });
+    Ok(format!("playing {name}, {count} frames"))
+}
+
+fn stop(world: &mut World, _: &[&str]) -> Result<String, String> {
+    world.remove_resource::<ScriptedInput>();
+    match std::mem::take(&mut *world.resource_mut::<TasMode>()) {
+        TasMode::Idle => Err("nothing is recording or playing".to_string()),
+        TasMode::Recording { name, frames } => {
+            write_script(&name, &TasScript::from_frames(&frames))?;
+            Ok(format!("saved {name}, {} frames", frames.len()))
+        }
+        TasMode::Playing { name, cursor, .. } => Ok(format!("stopped {name} at frame {cursor}")),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn script_path(name: &str) -> Result<std::path::PathBuf, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "`{name}` is not a valid script name, use letters, digits, - and _"
+        ));
+    }
+    Ok(bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join(SCRIPT_DIR)
+        .join(format!("{name}.tas.ron")))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read_script(name: &str) -> Result<TasScript, String> {
+    let path = script_path(name)?;
+    let bytes = std::fs::read(&path)
+        .map_err(|error| format!("could not read {}: {error}", path.display()))?;
+    ron::de::from_bytes(&bytes).map_err(|error| format!("{} is corrupt: {error}", path.display()))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write_script(name: &str, script: &TasScript) -> Result<(), String> {
+    let path = script_path(name)?;
+    let ron = ron::ser::to_string_pretty(script, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    }
+    std::fs::write(&path, ron)
+        .map_err(|error| format!("could not save {}: {error}", path.display()))
+}
+
+#[cfg(target_family = "wasm")]
+fn script_path(_: &str) -> Result<(), String> {
+    Err("TAS scripts are not supported on the web".to_string())
+}
+
+#[cfg(target_family = "wasm")]
+fn read_script(_: &str) -> Result<TasScript, String> {
+    Err("TAS scripts are not supported on the web".to_string())
+}
+
+#[cfg(target_family = "wasm")]
+fn write_script(_: &str, _: &TasScript) -> Result<(), String> {
+    Err("TAS scripts are not supported on the web".to_string())
+}
@@ -0,0 +1,90 @@
+//! Undoable edits to the level in the editor.
+
+use bevy::prelude::*;
+
+use crate::{
+    editor::{EditorItem, EditorLevel, set_item_position},
+    game::prelude::*,
+    history::EditCommand,
+};
+
+type LevelEditFn = Box<dyn FnOnce(&mut LevelData) + Send + Sync>;
+
+/// An arbitrary edit to the level, undone by restoring a snapshot.
+pub struct LevelEdit {
+    description: String,
+    edit: Option<LevelEditFn>,
+    before: Option<LevelData>,
+    after: Option<LevelData>,
+}
+
+impl LevelEdit {
+    pub fn new(
+        description: impl Into<String>,
+        edit: impl FnOnce(&mut LevelData) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            description: description.into(),
+            edit: Some(Box::new(edit)),
+            before: None,
+            after: None,
+        }
+    }
+}
+
+impl EditCommand for LevelEdit {
+    fn apply(&mut self, world: &mut World) {
+        let mut level = world.resource_mut::<EditorLevel>();
+        match self.edit.take() {
+            Some(edit) => {
+                self.before = Some(level.0.clone());
+                edit(&mut level.0);
+                self.after = Some(level.0.clone());
+            }
+            None => {
+                if let Some(after) = &self.after {
+                    level.0 = after.clone();
+                }
+            }
+        }
+    }
+
+    fn revert(&mut self, world: &mut World) {
+        if let Some(before) = &self.before {
+            world.resource_mut::<EditorLevel>().0 = before.clone();
+        }
+    }
+
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+}
+
+/// Moving a single item. Cheap enough to record every frame of a drag.
+pub struct MoveItem {
+    pub item: EditorItem,
+    pub from: Vec2,
+    pub to: Vec2,
+}
+
+impl EditCommand for MoveItem {
+    fn apply(&mut self, world: &mut World) {
+        set_item_position(
+            &mut world.resource_mut::<EditorLevel>().0,
+            self.item,
+            self.to,
+        );
+    }
+
+    fn revert(&mut self, world: &mut World) {
+        set_item_position(
+            &mut world.resource_mut::<EditorLevel>().0,
+            self.item,
+            self.from,
+        );
+    }
+
+    fn describe(&self) -> String {
+        format!("Move {:?}", self.item)
+    }
+}
@@ -0,0 +1,1235 @@
+//! A minimal level editor for `.level.ron` files. Only enabled in dev builds.
+//!
+//! Press F2 on the title screen to open it. Validation runs on every edit and
+//! its results are listed in the side panel, so broken levels are caught
+//! before they are saved.
+
+mod edits;
+mod thumbnail;
+
+use std::path::PathBuf;
+
+use bevy::{
+    asset::LoadState, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    editor::edits::{LevelEdit, MoveItem},
+    game::prelude::*,
+    history::{CommandHistory, HistoryCommandsExt},
+    persistence::Settings,
+    screens::Screen,
+    theme::layout::view_cursor,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(thumbnail::plugin);
+
+    app.init_resource::<EditorLevel>();
+    app.init_resource::<EditorStatus>();
+    app.init_resource::<EditorDrag>();
+    app.init_resource::<EditorPrefabs>();
+    app.init_resource::<EditorWiring>();
+    app.add_event::<LevelSaved>();
+    app.register_type::<EditorPreview>();
+    app.register_type::<EditorPanelText>();
+
+    app.add_systems(
+        Update,
+        enter_editor.run_if(in_state(Screen::Title).and(input_just_pressed(KeyCode::F2))),
+    );
+    app.add_systems(
+        OnEnter(Screen::Editor),
+        (spawn_editor_panel, start_loading_level),
+    );
+    app.add_systems(OnExit(Screen::Editor), stop_loading_level);
+    app.add_systems(
+        Update,
+        (
+            receive_loaded_level.run_if(resource_exists::<PendingLevel>),
+            (handle_pointer, handle_hotkeys),
+            (draw_wires, draw_paths),
+            (rebuild_preview, update_panel_text).run_if(
+                resource_changed::<EditorLevel>
+                    .or(resource_changed::<EditorStatus>)
+                    .or(resource_changed::<EditorPrefabs>)
+                    .or(resource_changed::<EditorWiring>)
+                    .or(on_event::<AssetEvent<PrefabLibrary>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Editor)),
+    );
+    app.add_systems(
+        Update,
+        exit_editor.run_if(in_state(Screen::Editor).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+/// The level file the editor opens and saves, relative to the assets folder.
+const EDITOR_LEVEL_PATH: &str = "levels/level1.level.ron";
+const GRID_SIZE: f32 = 10.0;
+const NEW_OBSTACLE_SIZE: Vec2 = Vec2::splat(40.0);
+const NEW_REVERB_ZONE_SIZE: Vec2 = Vec2::new(300.0, 200.0);
+const NEW_SECRET_SIZE: Vec2 = Vec2::new(200.0, 120.0);
+const NEW_HAZARD_SIZE: Vec2 = Vec2::new(100.0, 20.0);
+/// How far past the end of a path a new stop is added.
+const NEW_PATH_STEP: Vec2 = Vec2::new(80.0, 0.0);
+/// Where a new camera anchor's focus goes, relative to the anchor.
+const NEW_FOCUS_OFFSET: Vec2 = Vec2::new(300.0, 100.0);
+/// What a camera anchor frames at a zoom of 1, in a default-sized window.
+const FOCUS_FRAME: Vec2 = Vec2::new(1280.0, 720.0);
+const MARKER_SIZE: f32 = 16.0;
+/// Path control points are smaller than other markers, so they don't hide
+/// the curve.
+const PATH_HANDLE_SIZE: f32 = 10.0;
+const WIRE_COLOR: Color = Color::srgb(1.0, 0.4, 0.9);
+const PATH_COLOR: Color = Color::srgb(1.0, 0.6, 0.2);
+const CAMERA_RAIL_COLOR: Color = Color::srgb(0.4, 0.9, 1.0);
+const BOSS_COLOR: Color = Color::srgba(0.55, 0.12, 0.15, 0.6);
+
+/// The level being edited.
+#[derive(Resource, Default)]
+pub struct EditorLevel(pub LevelData);
+
+/// The latest load/save message and validation results, shown in the panel.
+#[derive(Resource, Default)]
+struct EditorStatus {
+    message: String,
+    issues: Vec<LevelIssue>,
+}
+
+/// The level file handle while it is still loading.
+#[derive(Resource)]
+struct PendingLevel(Handle<LevelData>);
+
+/// Sent after the editor has written a level file to disk.
+#[derive(Event, Debug, Clone)]
+pub struct LevelSaved {
+    /// The file the level was written to.
+    pub path: PathBuf,
+    pub level: LevelData,
+}
+
+/// Something in the level that can be picked and dragged in the editor.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+pub enum EditorItem {
+    Spawn,
+    Exit,
+    Boss,
+    Obstacle(usize),
+    Prop(usize),
+    Anchor(usize),
+    Prefab(usize),
+    Trigger(usize),
+    Action(usize),
+    ReverbZone(usize),
+    Hazard(usize),
+    /// A point of a hazard's path, by hazard and by index into its `path`.
+    PathPoint(usize, usize),
+    CameraRailPoint(usize),
+    CameraAnchor(usize),
+    /// The point a camera anchor pulls the camera to.
+    CameraAnchorFocus(usize),
+    Secret(usize),
+    Signpost(usize),
+    /// A point of a signpost's route, by signpost and by index into its
+    /// `route`.
+    RoutePoint(usize, usize),
+}
+
+impl EditorItem {
+    /// The hazard this is, or is a path point of.
+    fn hazard(self) -> Option<usize> {
+        match self {
+            Self::Hazard(i) | Self::PathPoint(i, _) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+/// Wiring mode: clicking a trigger and then an action connects them.
+#[derive(Resource, Default)]
+struct EditorWiring {
+    active: bool,
+    /// The trigger a wire is being drawn from.
+    from: Option<usize>,
+}
+
+/// The prefab library, and which prefab the `G` key places.
+#[derive(Resource, Default)]
+struct EditorPrefabs {
+    handle: Handle<PrefabLibrary>,
+    selected: usize,
+}
+
+#[derive(Resource, Default)]
+struct EditorDrag {
+    item: Option<EditorItem>,
+    offset: Vec2,
+    /// Where the dragged item was last moved to.
+    position: Vec2,
+}
+
+/// Marks the sprites drawn for the level being edited.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct EditorPreview;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct EditorPanelText;
+
+fn enter_editor(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Editor);
+}
+
+fn exit_editor(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn spawn_editor_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Editor Panel"),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Px(10.0),
+            top: Px(10.0),
+            max_width: Px(420.0),
+            padding: UiRect::all(Px(10.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        StateScoped(Screen::Editor),
+        children![(
+            Name::new("Editor Panel Text"),
+            Text::default(),
+            TextFont::from_font_size(14.0),
+            EditorPanelText,
+        )],
+    ));
+}
+
+fn start_loading_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut prefabs: ResMut<EditorPrefabs>,
+) {
+    commands.insert_resource(PendingLevel(asset_server.load(EDITOR_LEVEL_PATH)));
+    prefabs.handle = asset_server.load(PREFAB_LIBRARY_PATH);
+}
+
+fn stop_loading_level(mut commands: Commands) {
+    commands.remove_resource::<PendingLevel>();
+}
+
+/// Copy the level into the editor once it has loaded, or start from an empty
+/// level if the file is missing or broken.
+fn receive_loaded_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    pending: Res<PendingLevel>,
+    levels: Res<Assets<LevelData>>,
+    mut level: ResMut<EditorLevel>,
+    mut status: ResMut<EditorStatus>,
+    mut history: ResMut<CommandHistory>,
+) {
+    match asset_server.load_state(&pending.0) {
+        LoadState::Loaded => {
+            if let Some(loaded) = levels.get(&pending.0) {
+                level.0 = loaded.clone();
+                status.message = format!("Loaded {EDITOR_LEVEL_PATH}");
+            }
+        }
+        LoadState::Failed(error) => {
+            level.0 = LevelData::default();
+            status.message =
+                format!("Could not load {EDITOR_LEVEL_PATH}, starting empty.\n{error}");
+        }
+        _ => return,
+    }
+    history.clear();
+    commands.remove_resource::<PendingLevel>();
+}
+
+fn cursor_world_position(
+    window: &Window,
+    settings: &Settings,
+    (camera, camera_transform): (&Camera, &GlobalTransform),
+) -> Option<Vec2> {
+    let cursor = view_cursor(window, settings)?;
+    camera.viewport_to_world_2d(camera_transform, cursor).ok()
+}
+
+fn snap(position: Vec2) -> Vec2 {
+    (position / GRID_SIZE).round() * GRID_SIZE
+}
+
+/// Find the topmost item under a point. Markers win over path handles,
+/// handles win over boxes, boxes win over prefabs, and secrets and reverb
+/// zones come last.
+fn item_at(level: &LevelData, library: &PrefabLibrary, point: Vec2) -> Option<(EditorItem, Vec2)> {
+    let marker = Rect::from_center_size(Vec2::ZERO, Vec2::splat(MARKER_SIZE));
+    let hit_marker = |position: Vec2| marker.contains(point - position);
+    let handle = Rect::from_center_size(Vec2::ZERO, Vec2::splat(PATH_HANDLE_SIZE));
+    let hit_handle = |position: Vec2| handle.contains(point - position);
+
+    if let Some(spawn) = level.spawn.filter(|&spawn| hit_marker(spawn)) {
+        return Some((EditorItem::Spawn, spawn));
+    }
+    if let Some(exit) = level.exit.filter(|&exit| hit_marker(exit)) {
+        return Some((EditorItem::Exit, exit));
+    }
+    if let Some(boss) = level.boss.filter(|boss| hit_marker(boss.position)) {
+        return Some((EditorItem::Boss, boss.position));
+    }
+    if let Some(i) = level.anchors.iter().position(|&anchor| hit_marker(anchor)) {
+        return Some((EditorItem::Anchor(i), level.anchors[i]));
+    }
+    if let Some(i) = level
+        .signposts
+        .iter()
+        .position(|sign| hit_marker(sign.position))
+    {
+        return Some((EditorItem::Signpost(i), level.signposts[i].position));
+    }
+    if let Some(i) = level
+        .camera_anchors
+        .iter()
+        .position(|anchor| hit_marker(anchor.position))
+    {
+        return Some((
+            EditorItem::CameraAnchor(i),
+            level.camera_anchors[i].position,
+        ));
+    }
+    if let Some(i) = level
+        .triggers
+        .iter()
+        .position(|trigger| hit_marker(trigger.position))
+    {
+        return Some((EditorItem::Trigger(i), level.triggers[i].position));
+    }
+    if let Some(i) = level
+        .actions
+        .iter()
+        .position(|action| hit_marker(action.position))
+    {
+        return Some((EditorItem::Action(i), level.actions[i].position));
+    }
+    for (i, hazard) in level.hazards.iter().enumerate().rev() {
+        if let Some(j) = hazard.path.iter().rposition(|&point| hit_handle(point)) {
+            return Some((EditorItem::PathPoint(i, j), hazard.path[j]));
+        }
+    }
+    for (i, sign) in level.signposts.iter().enumerate().rev() {
+        if let Some(j) = sign.route.iter().rposition(|&point| hit_handle(point)) {
+            return Some((EditorItem::RoutePoint(i, j), sign.route[j]));
+        }
+    }
+    let rail_points = level
+        .camera_rail
+        .as_ref()
+        .map_or(&[][..], |rail| rail.points.as_slice());
+    if let Some(i) = rail_points.iter().rposition(|&point| hit_handle(point)) {
+        return Some((EditorItem::CameraRailPoint(i), rail_points[i]));
+    }
+    if let Some(i) = level
+        .camera_anchors
+        .iter()
+        .rposition(|anchor| hit_handle(anchor.focus))
+    {
+        return Some((
+            EditorItem::CameraAnchorFocus(i),
+            level.camera_anchors[i].focus,
+        ));
+    }
+    if let Some(i) = level
+        .props
+        .iter()
+        .rposition(|prop| prop.rect().contains(point))
+    {
+        return Some((EditorItem::Prop(i), level.props[i].position));
+    }
+    if let Some(i) = level
+        .hazards
+        .iter()
+        .rposition(|hazard| hazard.rect().contains(point))
+    {
+        return Some((EditorItem::Hazard(i), level.hazards[i].position));
+    }
+    if let Some(i) = level
+        .obstacles
+        .iter()
+        .rposition(|obstacle| obstacle.rect().contains(point))
+    {
+        return Some((EditorItem::Obstacle(i), level.obstacles[i].position));
+    }
+    if let Some(i) = level.prefabs.iter().rposition(|instance| {
+        library.0.get(&instance.prefab).is_some_and(|prefab| {
+            let bounds = prefab.bounds();
+            Rect::from_center_size(bounds.center() + instance.position, bounds.size())
+                .contains(point)
+        })
+    }) {
+        return Some((EditorItem::Prefab(i), level.prefabs[i].position));
+    }
+    if let Some(i) = level
+        .secrets
+        .iter()
+        .rposition(|secret| secret.rect().contains(point))
+    {
+        return Some((EditorItem::Secret(i), level.secrets[i].position));
+    }
+    if let Some(i) = level
+        .reverb_zones
+        .iter()
+        .rposition(|zone| zone.rect().contains(point))
+    {
+        return Some((EditorItem::ReverbZone(i), level.reverb_zones[i].position));
+    }
+    None
+}
+
+/// Move an item to a new position.
+pub fn set_item_position(level: &mut LevelData, item: EditorItem, position: Vec2) {
+    match item {
+        EditorItem::Spawn => level.spawn = Some(position),
+        EditorItem::Exit => level.exit = Some(position),
+        EditorItem::Boss => {
+            if let Some(boss) = &mut level.boss {
+                boss.position = position;
+            }
+        }
+        EditorItem::Obstacle(i) => level.obstacles[i].position = position,
+        EditorItem::Prop(i) => level.props[i].position = position,
+        EditorItem::Anchor(i) => level.anchors[i] = position,
+        EditorItem::Prefab(i) => level.prefabs[i].position = position,
+        EditorItem::Trigger(i) => level.triggers[i].position = position,
+        EditorItem::Action(i) => level.actions[i].position = position,
+        EditorItem::ReverbZone(i) => level.reverb_zones[i].position = position,
+        EditorItem::Hazard(i) => level.hazards[i].position = position,
+        EditorItem::PathPoint(i, j) => level.hazards[i].path[j] = position,
+        EditorItem::CameraRailPoint(i) => {
+            if let Some(rail) = &mut level.camera_rail {
+                rail.points[i] = position;
+            }
+        }
+        EditorItem::CameraAnchor(i) => level.camera_anchors[i].position = position,
+        EditorItem::CameraAnchorFocus(i) => level.camera_anchors[i].focus = position,
+        EditorItem::Secret(i) => level.secrets[i].position = position,
+        EditorItem::Signpost(i) => level.signposts[i].position = position,
+        EditorItem::RoutePoint(i, j) => level.signposts[i].route[j] = position,
+    }
+}
+
+/// Left click places an obstacle or drags the item under the cursor, right
+/// click removes it. A whole drag is recorded as a single undo step. In
+/// wiring mode, left clicks connect triggers to actions instead.
+fn handle_pointer(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    camera: Single<
+        (&Camera, &GlobalTransform),
+        (With<Camera2d>, Without<thumbnail::ThumbnailCamera>),
+    >,
+    level: Res<EditorLevel>,
+    prefabs: Res<EditorPrefabs>,
+    libraries: Res<Assets<PrefabLibrary>>,
+    mut drag: ResMut<EditorDrag>,
+    mut wiring: ResMut<EditorWiring>,
+) {
+    let Some(cursor) = cursor_world_position(&window, &settings, *camera) else {
+        return;
+    };
+    let empty_library = PrefabLibrary::default();
+    let library = libraries.get(&prefabs.handle).unwrap_or(&empty_library);
+
+    if wiring.active {
+        if mouse_input.just_pressed(MouseButton::Left) {
+            wiring.from = match (item_at(&level.0, library, cursor), wiring.from) {
+                (Some((EditorItem::Trigger(trigger), _)), _) => Some(trigger),
+                (Some((EditorItem::Action(action), _)), Some(trigger)) => {
+                    commands.execute(LevelEdit::new(
+                        format!("Toggle wire trigger #{trigger} -> action #{action}"),
+                        move |level| level.toggle_wire(trigger, action),
+                    ));
+                    None
+                }
+                _ => None,
+            };
+        }
+        if mouse_input.just_pressed(MouseButton::Right) {
+            wiring.from = None;
+        }
+        return;
+    }
+
+    if mouse_input.just_pressed(MouseButton::Left) {
+        match item_at(&level.0, library, cursor) {
+            Some((item, position)) => {
+                drag.item = Some(item);
+                drag.offset = position - cursor;
+                drag.position = position;
+                commands.begin_group();
+            }
+            None => {
+                let obstacle = ObstacleData {
+                    position: snap(cursor),
+                    size: NEW_OBSTACLE_SIZE,
+                };
+                commands.execute(LevelEdit::new("Add obstacle", move |level| {
+                    level.obstacles.push(obstacle)
+                }));
+            }
+        }
+    }
+
+    if let Some(item) = drag.item {
+        if mouse_input.pressed(MouseButton::Left) {
+            let position = snap(cursor + drag.offset);
+            if position != drag.position {
+                commands.execute(MoveItem {
+                    item,
+                    from: drag.position,
+                    to: position,
+                });
+                drag.position = position;
+            }
+        } else {
+            drag.item = None;
+            commands.end_group();
+        }
+    }
+
+    let deleted = mouse_input
+        .just_pressed(MouseButton::Right)
+        .then(|| item_at(&level.0, library, cursor))
+        .flatten();
+    if let Some((item, _)) = deleted {
+        commands.execute(LevelEdit::new(
+            format!("Delete {item:?}"),
+            move |level| match item {
+                EditorItem::Obstacle(i) => {
+                    level.obstacles.remove(i);
+                }
+                EditorItem::Prop(i) => {
+                    level.props.remove(i);
+                }
+                EditorItem::Anchor(i) => {
+                    level.anchors.remove(i);
+                }
+                EditorItem::Prefab(i) => {
+                    level.prefabs.remove(i);
+                }
+                EditorItem::Trigger(i) => level.remove_trigger(i),
+                EditorItem::Action(i) => level.remove_action(i),
+                EditorItem::ReverbZone(i) => {
+                    level.reverb_zones.remove(i);
+                }
+                EditorItem::Hazard(i) => {
+                    level.hazards.remove(i);
+                }
+                EditorItem::PathPoint(i, j) => {
+                    level.hazards[i].path.remove(j);
+                }
+                EditorItem::CameraRailPoint(i) => {
+                    if let Some(rail) = &mut level.camera_rail {
+                        rail.points.remove(i);
+                        if rail.points.is_empty() {
+                            level.camera_rail = None;
+                        }
+                    }
+                }
+                // The focus is part of its anchor, so both go together.
+                EditorItem::CameraAnchor(i) | EditorItem::CameraAnchorFocus(i) => {
+                    level.camera_anchors.remove(i);
+                }
+                EditorItem::Secret(i) => {
+                    level.secrets.remove(i);
+                }
+                EditorItem::Signpost(i) => {
+                    level.signposts.remove(i);
+                }
+                EditorItem::RoutePoint(i, j) => {
+                    level.signposts[i].route.remove(j);
+                }
+                EditorItem::Exit => level.exit = None,
+                EditorItem::Boss => level.boss = None,
+                EditorItem::Spawn => level.spawn = None,
+            },
+        ));
+    }
+}
+
+/// Keyboard shortcuts for placing markers, undo/redo and saving.
+fn handle_hotkeys(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    camera: Single<
+        (&Camera, &GlobalTransform),
+        (With<Camera2d>, Without<thumbnail::ThumbnailCamera>),
+    >,
+    level: Res<EditorLevel>,
+    mut prefabs: ResMut<EditorPrefabs>,
+    libraries: Res<Assets<PrefabLibrary>>,
+    mut wiring: ResMut<EditorWiring>,
+    mut status: ResMut<EditorStatus>,
+    mut saved_events: EventWriter<LevelSaved>,
+) {
+    let raw_cursor = cursor_world_position(&window, &settings, *camera);
+    let cursor = raw_cursor.map(snap);
+    let ctrl = input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    if ctrl {
+        if input.just_pressed(KeyCode::KeyS) {
+            let library = libraries.get(&prefabs.handle).cloned().unwrap_or_default();
+            match save_level(&level.0, &library) {
+                Ok(path) => {
+                    status.message = format!("Saved {}", path.display());
+                    saved_events.write(LevelSaved {
+                        path,
+                        level: level.0.clone(),
+                    });
+                }
+                Err(message) => status.message = message,
+            }
+        } else if input.just_pressed(KeyCode::KeyY) || (shift && input.just_pressed(KeyCode::KeyZ))
+        {
+            commands.redo();
+        } else if input.just_pressed(KeyCode::KeyZ) {
+            commands.undo();
+        }
+        return;
+    }
+
+    if input.just_pressed(KeyCode::KeyW) {
+        wiring.active = !wiring.active;
+        wiring.from = None;
+    }
+
+    let prefab_names: Vec<String> = libraries
+        .get(&prefabs.handle)
+        .map(|library| library.0.keys().cloned().collect())
+        .unwrap_or_default();
+    if !prefab_names.is_empty() {
+        if input.just_pressed(KeyCode::BracketRight) {
+            prefabs.selected = (prefabs.selected + 1) % prefab_names.len();
+        }
+        if input.just_pressed(KeyCode::BracketLeft) {
+            prefabs.selected = (prefabs.selected + prefab_names.len() - 1) % prefab_names.len();
+        }
+    }
+
+    let (Some(raw_cursor), Some(cursor)) = (raw_cursor, cursor) else {
+        return;
+    };
+    let empty_library = PrefabLibrary::default();
+    let library = libraries.get(&prefabs.handle).unwrap_or(&empty_library);
+    let hovered = item_at(&level.0, library, raw_cursor).map(|(item, _)| item);
+
+    // Pressing T or O over an existing trigger or action cycles its kind.
+    if input.just_pressed(KeyCode::KeyT) {
+        match hovered {
+            Some(EditorItem::Trigger(i)) => {
+                let kind = next_trigger_kind(level.0.triggers[i].kind);
+                commands.execute(LevelEdit::new(
+                    format!("Make trigger #{i} a {}", kind.label()),
+                    move |level| level.triggers[i].kind = kind,
+                ));
+            }
+            _ => {
+                let kind = TriggerKind::presets()[0];
+                commands.execute(LevelEdit::new("Add trigger", move |level| {
+                    level.triggers.push(TriggerData {
+                        position: cursor,
+                        kind,
+                    })
+                }));
+            }
+        }
+    }
+    if input.just_pressed(KeyCode::KeyO) {
+        match hovered {
+            Some(EditorItem::Action(i)) => {
+                let kind = next_action_kind(&level.0.actions[i].kind);
+                commands.execute(LevelEdit::new(
+                    format!("Make action #{i} {}", kind.label()),
+                    move |level| level.actions[i].kind = kind,
+                ));
+            }
+            _ => {
+                let [kind, ..] = ActionKind::presets();
+                commands.execute(LevelEdit::new("Add action", move |level| {
+                    level.actions.push(ActionData {
+                        position: cursor,
+                        kind,
+                    })
+                }));
+            }
+        }
+    }
+
+    // Pressing V over a reverb zone cycles its preset.
+    if input.just_pressed(KeyCode::KeyV) {
+        match hovered {
+            Some(EditorItem::ReverbZone(i)) => {
+                let preset = level.0.reverb_zones[i].preset.next();
+                commands.execute(LevelEdit::new(
+                    format!("Make reverb zone #{i} a {}", preset.label()),
+                    move |level| level.reverb_zones[i].preset = preset,
+                ));
+            }
+            _ => {
+                commands.execute(LevelEdit::new("Add reverb zone", move |level| {
+                    level.reverb_zones.push(ReverbZoneData {
+                        position: cursor,
+                        size: NEW_REVERB_ZONE_SIZE,
+                        preset: ReverbPreset::default(),
+                    })
+                }));
+            }
+        }
+    }
+
+    // Pressing H over a hazard cycles its kind.
+    if input.just_pressed(KeyCode::KeyH) {
+        match hovered {
+            Some(EditorItem::Hazard(i)) => {
+                let kind = level.0.hazards[i].kind.next();
+                commands.execute(LevelEdit::new(
+                    format!("Make hazard #{i} a {}", kind.label()),
+                    move |level| level.hazards[i].kind = kind,
+                ));
+            }
+            _ => {
+                commands.execute(LevelEdit::new("Add hazard", move |level| {
+                    level.hazards.push(HazardData {
+                        kind: HazardKind::Platform,
+                        position: cursor,
+                        size: NEW_HAZARD_SIZE,
+                        path: vec![cursor + NEW_PATH_STEP],
+                        curve: PathCurve::default(),
+                        easing: PathEasing::default(),
+                        loop_mode: LoopMode::default(),
+                        speed: 100.0,
+                    })
+                }));
+            }
+        }
+    }
+
+    // Pressing I over a signpost cycles its icon, and N extends its route.
+    if input.just_pressed(KeyCode::KeyI) {
+        match hovered {
+            Some(EditorItem::Signpost(i)) => {
+                let icon = level.0.signposts[i].icon.next();
+                commands.execute(LevelEdit::new(
+                    format!("Give signpost #{i} the {} icon", icon.label()),
+                    move |level| level.signposts[i].icon = icon,
+                ));
+            }
+            _ => {
+                commands.execute(LevelEdit::new("Add signpost", move |level| {
+                    level.signposts.push(SignpostData {
+                        position: cursor,
+                        icon: SignIcon::default(),
+                        text: String::new(),
+                        route: Vec::new(),
+                    })
+                }));
+            }
+        }
+    }
+    if let Some(EditorItem::Signpost(i) | EditorItem::RoutePoint(i, _)) =
+        hovered.filter(|_| input.just_pressed(KeyCode::KeyN))
+    {
+        let sign = &level.0.signposts[i];
+        let end = sign.route.last().copied().unwrap_or(sign.position) + NEW_PATH_STEP;
+        commands.execute(LevelEdit::new(
+            format!("Extend the route of signpost #{i}"),
+            move |level| level.signposts[i].route.push(end),
+        ));
+    }
+
+    // N, C, K and L work on the path of the hazard under the cursor, or of
+    // the path point under it.
+    if let Some(i) = hovered.and_then(EditorItem::hazard) {
+        let hazard = &level.0.hazards[i];
+        if input.just_pressed(KeyCode::KeyN) {
+            let last = hazard.path.last().copied().unwrap_or(hazard.position);
+            let end = last + NEW_PATH_STEP;
+            // Bézier curves get their two handles along with the new stop.
+            let points = match hazard.curve {
+                PathCurve::Bezier => {
+                    vec![last.lerp(end, 1.0 / 3.0), last.lerp(end, 2.0 / 3.0), end]
+                }
+                PathCurve::Linear | PathCurve::CatmullRom => vec![end],
+            };
+            commands.execute(LevelEdit::new(
+                format!("Extend the path of hazard #{i}"),
+                move |level| level.hazards[i].path.extend(points),
+            ));
+        }
+        if input.just_pressed(KeyCode::KeyC) {
+            let curve = hazard.curve.next();
+            commands.execute(LevelEdit::new(
+                format!("Make the path of hazard #{i} {}", curve.label()),
+                move |level| level.hazards[i].curve = curve,
+            ));
+        }
+        if input.just_pressed(KeyCode::KeyK) {
+            let easing = hazard.easing.next();
+            commands.execute(LevelEdit::new(
+                format!("Make hazard #{i} {}", easing.label()),
+                move |level| level.hazards[i].easing = easing,
+            ));
+        }
+        if input.just_pressed(KeyCode::KeyL) {
+            let loop_mode = hazard.loop_mode.next();
+            commands.execute(LevelEdit::new(
+                format!("Make hazard #{i} {}", loop_mode.label()),
+                move |level| level.hazards[i].loop_mode = loop_mode,
+            ));
+        }
+    }
+    if let (Some(EditorItem::CameraRailPoint(_)), Some(rail), true) = (
+        hovered,
+        &level.0.camera_rail,
+        input.just_pressed(KeyCode::KeyC),
+    ) {
+        let curve = rail.curve.next();
+        commands.execute(LevelEdit::new(
+            format!("Make the camera rail {}", curve.label()),
+            move |level| {
+                if let Some(rail) = &mut level.camera_rail {
+                    rail.curve = curve;
+                }
+            },
+        ));
+    }
+    if input.just_pressed(KeyCode::KeyR) {
+        commands.execute(LevelEdit::new("Add camera rail point", move |level| {
+            level
+                .camera_rail
+                .get_or_insert_with(CameraRailData::default)
+                .points
+                .push(cursor)
+        }));
+    }
+    if input.just_pressed(KeyCode::KeyS) {
+        commands.execute(LevelEdit::new("Add secret", move |level| {
+            level.secrets.push(SecretData {
+                position: cursor,
+                size: NEW_SECRET_SIZE,
+            })
+        }));
+    }
+    if input.just_pressed(KeyCode::KeyM) {
+        commands.execute(LevelEdit::new("Add camera anchor", move |level| {
+            level.camera_anchors.push(CameraAnchorData {
+                position: cursor,
+                focus: cursor + NEW_FOCUS_OFFSET,
+                via: Vec::new(),
+                hold: 3.0,
+                zoom: 1.0,
+            })
+        }));
+    }
+
+    let selected_prefab = prefab_names.get(prefabs.selected).cloned();
+    if let Some(name) = selected_prefab.filter(|_| input.just_pressed(KeyCode::KeyG)) {
+        commands.execute(LevelEdit::new(format!("Place {name}"), move |level| {
+            level.prefabs.push(PrefabInstance {
+                prefab: name,
+                position: cursor,
+                overrides: Vec::new(),
+            })
+        }));
+    }
+    if input.just_pressed(KeyCode::KeyP) {
+        commands.execute(LevelEdit::new("Set spawn", move |level| {
+            level.spawn = Some(cursor)
+        }));
+    }
+    if input.just_pressed(KeyCode::KeyE) {
+        commands.execute(LevelEdit::new("Set exit", move |level| {
+            level.exit = Some(cursor)
+        }));
+    }
+    if input.just_pressed(KeyCode::KeyX) {
+        commands.execute(LevelEdit::new("Place boss", move |level| {
+            level.boss = Some(match level.boss {
+                Some(boss) => BossData {
+                    position: cursor,
+                    ..boss
+                },
+                None => BossData::new(cursor),
+            })
+        }));
+    }
+    if input.just_pressed(KeyCode::KeyA) {
+        commands.execute(LevelEdit::new("Add anchor", move |level| {
+            level.anchors.push(cursor)
+        }));
+    }
+    if input.just_pressed(KeyCode::KeyB) {
+        commands.execute(LevelEdit::new("Add prop", move |level| {
+            level.props.push(PropData {
+                position: cursor,
+                size: Vec2::splat(30.0),
+                mass: 0.5,
+            })
+        }));
+    }
+}
+
+fn next_trigger_kind(kind: TriggerKind) -> TriggerKind {
+    let presets = TriggerKind::presets();
+    let i = presets
+        .iter()
+        .position(|preset| preset.label() == kind.label())
+        .unwrap_or_default();
+    presets[(i + 1) % presets.len()]
+}
+
+fn next_action_kind(kind: &ActionKind) -> ActionKind {
+    let presets = ActionKind::presets();
+    let i = presets
+        .iter()
+        .position(|preset| preset.label() == kind.label())
+        .unwrap_or_default();
+    presets[(i + 1) % presets.len()].clone()
+}
+
+/// Draw a line for every wire, and for the wire being drawn in wiring mode.
+fn draw_wires(
+    mut gizmos: Gizmos,
+    level: Res<EditorLevel>,
+    wiring: Res<EditorWiring>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    camera: Single<
+        (&Camera, &GlobalTransform),
+        (With<Camera2d>, Without<thumbnail::ThumbnailCamera>),
+    >,
+) {
+    let level = &level.0;
+    for &Wire(trigger, action) in &level.wires {
+        if let (Some(trigger), Some(action)) =
+            (level.triggers.get(trigger), level.actions.get(action))
+        {
+            gizmos.arrow_2d(trigger.position, action.position, WIRE_COLOR);
+        }
+    }
+
+    let pending = wiring.from.and_then(|i| level.triggers.get(i));
+    if let (Some(trigger), Some(cursor)) =
+        (pending, cursor_world_position(&window, &settings, *camera))
+    {
+        gizmos.line_2d(trigger.position, cursor, WIRE_COLOR.with_alpha(0.5));
+    }
+}
+
+/// Draw the paths of hazards and the camera rail, with lines from each
+/// Bézier handle to the stop it belongs to, and the routes camera anchors
+/// pull the camera along.
+fn draw_paths(mut gizmos: Gizmos, level: Res<EditorLevel>) {
+    for hazard in &level.0.hazards {
+        for leg in hazard.shape().legs() {
+            gizmos.linestrip_2d(leg.iter().copied(), PATH_COLOR);
+        }
+        if hazard.curve == PathCurve::Bezier {
+            let points = hazard.path_points();
+            for (i, &point) in points.iter().enumerate() {
+                // The first handle of each curve pulls away from the stop
+                // before it, the second toward the stop after it.
+                let stop = match i % 3 {
+                    1 => points.get(i - 1),
+                    2 => points.get(i + 1),
+                    _ => None,
+                };
+                if let Some(&stop) = stop {
+                    gizmos.line_2d(stop, point, PATH_COLOR.with_alpha(0.4));
+                }
+            }
+        }
+    }
+    if let Some(rail) = &level.0.camera_rail {
+        for leg in rail.shape().legs() {
+            gizmos.linestrip_2d(leg.iter().copied(), CAMERA_RAIL_COLOR);
+        }
+    }
+    for anchor in &level.0.camera_anchors {
+        let route = std::iter::once(anchor.position)
+            .chain(anchor.via.iter().copied())
+            .chain(std::iter::once(anchor.focus));
+        gizmos.linestrip_2d(route, CAMERA_RAIL_COLOR.with_alpha(0.5));
+        gizmos.rect_2d(anchor.focus, FOCUS_FRAME * anchor.zoom, CAMERA_RAIL_COLOR);
+    }
+}
+
+/// Write the level to disk, refusing levels that would fail to load.
+fn save_level(level: &LevelData, library: &PrefabLibrary) -> Result<PathBuf, String> {
+    let errors = level
+        .validate_with_prefabs(library)
+        .into_iter()
+        .filter(|issue| issue.severity() == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(format!("Not saved: fix the {errors} error(s) below first."));
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let path = bevy::asset::io::file::FileAssetReader::get_base_path()
+            .join("assets")
+            .join(EDITOR_LEVEL_PATH);
+        level
+            .to_ron()
+            .map_err(|error| error.to_string())
+            .and_then(|ron| std::fs::write(&path, ron).map_err(|error| error.to_string()))
+            .map_err(|error| format!("Could not save {}: {error}", path.display()))?;
+        Ok(path)
+    }
+
+    #[cfg(target_family = "wasm")]
+    {
+        Err("Saving is not supported on the web.".to_string())
+    }
+}
+
+fn rebuild_preview(
+    mut commands: Commands,
+    level: Res<EditorLevel>,
+    prefabs: Res<EditorPrefabs>,
+    libraries: Res<Assets<PrefabLibrary>>,
+    preview_query: Query<Entity, With<EditorPreview>>,
+) {
+    for entity in &preview_query {
+        commands.entity(entity).despawn();
+    }
+
+    // Prefabs are previewed as the parts they expand to.
+    let empty_library = PrefabLibrary::default();
+    let library = libraries.get(&prefabs.handle).unwrap_or(&empty_library);
+    let (level, _) = level.0.flatten(library);
+    let level = &level;
+    let mut spawn_box = |name: &str, position: Vec2, size: Vec2, color: Color, z: f32| {
+        commands.spawn((
+            Name::new(name.to_string()),
+            EditorPreview,
+            Sprite::from_color(color, size),
+            Transform::from_translation(position.extend(z)),
+            StateScoped(Screen::Editor),
+        ));
+    };
+
+    for zone in &level.reverb_zones {
+        let color = match zone.preset {
+            ReverbPreset::Cave => Color::srgba(0.5, 0.4, 0.9, 0.15),
+            ReverbPreset::Hall => Color::srgba(0.9, 0.8, 0.4, 0.15),
+            ReverbPreset::Outdoors => Color::srgba(0.4, 0.9, 0.5, 0.15),
+        };
+        spawn_box("Reverb Zone", zone.position, zone.size, color, -0.1);
+    }
+    // Facades are see-through here, so what's behind them can be edited.
+    for secret in &level.secrets {
+        spawn_box(
+            "Secret",
+            secret.position,
+            secret.size,
+            Color::srgba(0.8, 0.8, 0.8, 0.3),
+            0.28,
+        );
+    }
+    for obstacle in &level.obstacles {
+        spawn_box(
+            "Obstacle",
+            obstacle.position,
+            obstacle.size,
+            Color::srgb(0.8, 0.8, 0.8),
+            0.0,
+        );
+    }
+    for prop in &level.props {
+        spawn_box(
+            "Prop",
+            prop.position,
+            prop.size,
+            Color::srgb(1.0, 0.5, 0.5),
+            0.1,
+        );
+    }
+    for &anchor in &level.anchors {
+        spawn_box(
+            "Anchor",
+            anchor,
+            Vec2::splat(MARKER_SIZE * 0.75),
+            Color::srgb(0.4, 0.6, 1.0),
+            0.2,
+        );
+    }
+    for trigger in &level.triggers {
+        let (size, color) = match trigger.kind {
+            TriggerKind::Zone { size } => (size, Color::srgba(0.9, 0.3, 0.9, 0.25)),
+            TriggerKind::Switch | TriggerKind::Timer { .. } => {
+                (Vec2::splat(MARKER_SIZE), Color::srgb(0.9, 0.3, 0.9))
+            }
+        };
+        spawn_box("Trigger", trigger.position, size, color, 0.25);
+    }
+    for hazard in &level.hazards {
+        spawn_box(
+            "Hazard",
+            hazard.position,
+            hazard.size,
+            hazard.kind.color(),
+            0.05,
+        );
+        for (i, &point) in hazard.path.iter().enumerate() {
+            // Stops are solid, Bézier handles faded.
+            let color = if hazard.curve.is_stop(i + 1) {
+                PATH_COLOR
+            } else {
+                PATH_COLOR.with_alpha(0.5)
+            };
+            spawn_box(
+                "Path Point",
+                point,
+                Vec2::splat(PATH_HANDLE_SIZE),
+                color,
+                0.22,
+            );
+        }
+    }
+    for &point in level.camera_rail.iter().flat_map(|rail| &rail.points) {
+        spawn_box(
+            "Camera Rail Point",
+            point,
+            Vec2::splat(PATH_HANDLE_SIZE),
+            CAMERA_RAIL_COLOR,
+            0.22,
+        );
+    }
+    for sign in &level.signposts {
+        spawn_box(
+            "Signpost",
+            sign.position,
+            Vec2::splat(MARKER_SIZE * 0.75),
+            sign.icon.color(),
+            0.2,
+        );
+        for &point in &sign.route {
+            spawn_box(
+                "Route Point",
+                point,
+                Vec2::splat(PATH_HANDLE_SIZE),
+                sign.icon.color().with_alpha(0.6),
+                0.22,
+            );
+        }
+    }
+    for anchor in &level.camera_anchors {
+        spawn_box(
+            "Camera Anchor",
+            anchor.position,
+            Vec2::splat(MARKER_SIZE * 0.75),
+            CAMERA_RAIL_COLOR,
+            0.2,
+        );
+        spawn_box(
+            "Camera Anchor Focus",
+            anchor.focus,
+            Vec2::splat(PATH_HANDLE_SIZE),
+            CAMERA_RAIL_COLOR,
+            0.22,
+        );
+    }
+    for action in &level.actions {
+        spawn_box(
+            "Action",
+            action.position,
+            Vec2::splat(MARKER_SIZE),
+            Color::srgb(0.3, 0.9, 0.9),
+            0.25,
+        );
+    }
+    if let Some(spawn) = level.spawn {
+        spawn_box(
+            "Spawn",
+            spawn,
+            Vec2::splat(MARKER_SIZE),
+            Color::srgb(0.3, 0.9, 0.3),
+            0.3,
+        );
+    }
+    if let Some(boss) = level.boss {
+        spawn_box("Boss", boss.position, boss.size, BOSS_COLOR, 0.1);
+    }
+    if let Some(exit) = level.exit {
+        spawn_box(
+            "Exit",
+            exit,
+            Vec2::splat(MARKER_SIZE),
+            Color::srgb(1.0, 0.85, 0.2),
+            0.3,
+        );
+    }
+}
+
+fn update_panel_text(
+    level: Res<EditorLevel>,
+    prefabs: Res<EditorPrefabs>,
+    libraries: Res<Assets<PrefabLibrary>>,
+    wiring: Res<EditorWiring>,
+    mut status: ResMut<EditorStatus>,
+    mut text: Single<&mut Text, With<EditorPanelText>>,
+) {
+    let library = libraries.get(&prefabs.handle);
+    status.bypass_change_detection().issues = match library {
+        Some(library) => level.0.validate_with_prefabs(library),
+        None => level.0.validate(),
+    };
+    let selected_prefab = library
+        .and_then(|library| library.0.keys().nth(prefabs.selected))
+        .map_or("none", String::as_str);
+
+    let mut lines = vec![
+        "Click: place obstacle / drag  Right click: delete  Ctrl+Z/Y: undo/redo".to_string(),
+        "P: spawn  E: exit  A: anchor  B: prop  X: boss  Ctrl+S: save  Esc: quit".to_string(),
+        format!("[ ]: choose prefab ({selected_prefab})  G: place prefab"),
+        "T: add trigger / change kind  O: add action / change kind".to_string(),
+        "V: add reverb zone / change preset".to_string(),
+        "H: add hazard / change kind  R: add camera rail point".to_string(),
+        "M: add camera anchor (drag its focus to aim it)  S: add secret".to_string(),
+        "I: add signpost / change icon (N over it: extend its route)".to_string(),
+        "Over a path: N: extend  C: curve  K: easing  L: loop mode".to_string(),
+        match (wiring.active, wiring.from) {
+            (false, _) => "W: wiring mode (off)".to_string(),
+            (true, None) => "W: wiring mode (on) - click a trigger".to_string(),
+            (true, Some(i)) => {
+                format!("W: wiring mode (on) - click an action to wire trigger #{i} to it")
+            }
+        },
+        String::new(),
+        status.message.clone(),
+    ];
+    if status.issues.is_empty() {
+        lines.push("No problems found.".to_string());
+    }
+    for issue in &status.issues {
+        let prefix = match issue.severity() {
+            Severity::Error => "ERROR",
+            Severity::Warning => "warning",
+        };
+        lines.push(format!("{prefix}: {issue}"));
+    }
+    text.0 = lines.join("\n");
+}
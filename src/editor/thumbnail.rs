@@ -0,0 +1,115 @@
+//! Render a thumbnail of each level when it is saved from the editor.
+//!
+//! A temporary camera renders the level preview into an offscreen image,
+//! which is then captured and written next to the level file.
+
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        view::screenshot::{Screenshot, save_to_disk},
+    },
+};
+
+use crate::{editor::LevelSaved, game::prelude::*, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ThumbnailCamera>();
+    app.add_systems(
+        Update,
+        (capture_thumbnail, spawn_thumbnail_camera)
+            .chain()
+            .run_if(in_state(Screen::Editor)),
+    );
+}
+
+const THUMBNAIL_SIZE: UVec2 = UVec2::new(320, 180);
+/// Empty space around the level in the thumbnail, in world units.
+const THUMBNAIL_MARGIN: f32 = 40.0;
+const THUMBNAIL_BACKGROUND: Color = Color::srgb(0.1, 0.1, 0.12);
+
+/// A camera that renders the editor preview into an offscreen image for a
+/// thumbnail. It lives for a single frame before it is captured.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ThumbnailCamera {
+    #[reflect(ignore)]
+    path: PathBuf,
+    image: Handle<Image>,
+}
+
+fn spawn_thumbnail_camera(
+    mut commands: Commands,
+    mut saved_events: EventReader<LevelSaved>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Some(saved) = saved_events.read().last() else {
+        return;
+    };
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: THUMBNAIL_SIZE.x,
+            height: THUMBNAIL_SIZE.y,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+
+    // Frame the whole level while keeping the thumbnail's aspect ratio.
+    let bounds = saved.level.bounds().inflate(THUMBNAIL_MARGIN);
+    let aspect = THUMBNAIL_SIZE.x as f32 / THUMBNAIL_SIZE.y as f32;
+    let size = bounds.size();
+    let viewport = if size.x / size.y > aspect {
+        Vec2::new(size.x, size.x / aspect)
+    } else {
+        Vec2::new(size.y * aspect, size.y)
+    };
+
+    commands.spawn((
+        Name::new("Thumbnail Camera"),
+        ThumbnailCamera {
+            path: thumbnail_path(&saved.path),
+            image: image.clone(),
+        },
+        Camera2d,
+        Camera {
+            target: RenderTarget::Image(image.into()),
+            order: -1,
+            clear_color: ClearColorConfig::Custom(THUMBNAIL_BACKGROUND),
+            ..default()
+        },
+        Projection::from(OrthographicProjection {
+            scaling_mode: ScalingMode::Fixed {
+                width: viewport.x,
+                height: viewport.y,
+            },
+            ..OrthographicProjection::default_2d()
+        }),
+        Transform::from_translation(bounds.center().extend(0.0)),
+        StateScoped(Screen::Editor),
+    ));
+}
+
+/// Capture thumbnail cameras that rendered last frame, then remove them.
+fn capture_thumbnail(mut commands: Commands, camera_query: Query<(Entity, &ThumbnailCamera)>) {
+    for (entity, camera) in &camera_query {
+        info!("Saving level thumbnail to {}", camera.path.display());
+        commands
+            .spawn(Screenshot::image(camera.image.clone()))
+            .observe(save_to_disk(camera.path.clone()));
+        commands.entity(entity).despawn();
+    }
+}
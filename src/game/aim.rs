@@ -0,0 +1,220 @@
+//! Aiming chains with the mouse, a gamepad stick or the keyboard.
+//!
+//! The mouse aims straight at the cursor. Sticks and keys only give a
+//! direction, so chains aimed with them are thrown a fixed distance, and aim
+//! assist bends them toward anything nearby that a chain can catch on.
+//!
+//! Keyboard aim snaps to the eight compass directions of the held aim keys.
+//! Holding fine aim turns it slowly instead, for angles in between.
+
+use std::f32::consts::FRAC_PI_4;
+
+use avian2d::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        gameplay_error::{GameplayError, OrReport},
+        mutators::{Mutator, RunMutators},
+        player::Player,
+    },
+    input::{ActionInput, InputAction},
+    persistence::Settings,
+    screens::Screen,
+    theme::layout::view_cursor,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PlayerAim>();
+    app.init_resource::<KeyboardAim>();
+    app.init_resource::<AimAssist>();
+
+    app.add_systems(
+        Update,
+        (
+            update_player_aim
+                .in_set(AppSystems::RecordInput)
+                .in_set(PausableSystems),
+            draw_aim_reticle.in_set(AppSystems::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How far chains aimed without the mouse are thrown, in pixels.
+const AIM_DISTANCE: f32 = 300.0;
+/// How fast fine aim turns, in radians per second.
+const FINE_AIM_SPEED: f32 = FRAC_PI_4;
+const RETICLE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum AimSource {
+    #[default]
+    Mouse,
+    Stick,
+    Keyboard,
+}
+
+/// Where the player is aiming this frame.
+#[derive(Resource, Default, Debug)]
+pub struct PlayerAim {
+    pub origin: Vec2,
+    /// The point in the world being aimed at, if any.
+    pub target: Option<Vec2>,
+    pub source: AimSource,
+}
+
+/// The keyboard aim direction, relative to the screen.
+#[derive(Resource, Debug)]
+pub struct KeyboardAim(Vec2);
+
+impl Default for KeyboardAim {
+    fn default() -> Self {
+        Self(Vec2::X)
+    }
+}
+
+/// Bends stick and keyboard aim toward static bodies. Mouse aim is never
+/// assisted.
+#[derive(Resource, Debug)]
+pub struct AimAssist {
+    pub enabled: bool,
+    /// How far off the aim can be for a target to still be picked, in radians.
+    pub max_angle: f32,
+    /// How far away targets can be, in pixels.
+    pub range: f32,
+}
+
+impl Default for AimAssist {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_angle: 12f32.to_radians(),
+            range: AIM_DISTANCE * 1.5,
+        }
+    }
+}
+
+impl AimAssist {
+    /// The target closest to the aim `direction` from `origin`, if any are
+    /// close enough.
+    pub fn pick(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        targets: impl IntoIterator<Item = Vec2>,
+    ) -> Option<Vec2> {
+        targets
+            .into_iter()
+            .filter(|target| {
+                let distance = target.distance(origin);
+                distance > 0.0 && distance <= self.range
+            })
+            .map(|target| (target, direction.angle_to(target - origin).abs()))
+            .filter(|&(_, angle)| angle <= self.max_angle)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(target, _)| target)
+    }
+}
+
+pub fn update_player_aim(
+    time: Res<Time>,
+    input: ActionInput,
+    mutators: Res<RunMutators>,
+    assist: Res<AimAssist>,
+    mut keyboard_aim: ResMut<KeyboardAim>,
+    mut aim: ResMut<PlayerAim>,
+    player_query: Query<&Transform, With<Player>>,
+    target_query: Query<(&GlobalTransform, &RigidBody)>,
+    settings: Res<Settings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player_transform) =
+        player_query
+            .single()
+            .or_report(&mut errors, "update_player_aim", "player")
+    else {
+        aim.target = None;
+        return;
+    };
+    let origin = player_transform.translation.truncate();
+    aim.origin = origin;
+
+    let aims_with_keys = input.aims_with_keys();
+    if aims_with_keys {
+        if input.pressed(InputAction::FineAim) {
+            let turn = input.axis(InputAction::AimLeft, InputAction::AimRight);
+            let rotation = Vec2::from_angle(turn * FINE_AIM_SPEED * time.delta_secs());
+            keyboard_aim.0 = rotation.rotate(keyboard_aim.0).normalize();
+        } else {
+            let snapped = Vec2::new(
+                input.axis(InputAction::AimRight, InputAction::AimLeft),
+                input.axis(InputAction::AimUp, InputAction::AimDown),
+            );
+            if snapped != Vec2::ZERO {
+                keyboard_aim.0 = snapped.normalize();
+            }
+        }
+    }
+
+    let (mut direction, source) = if let Some(direction) = input.stick_aim() {
+        (direction, AimSource::Stick)
+    } else if aims_with_keys {
+        (keyboard_aim.0, AimSource::Keyboard)
+    } else {
+        aim.source = AimSource::Mouse;
+        aim.target = get_cursor_world_position(&settings, &windows, &camera_query, &mut errors);
+        return;
+    };
+
+    // Keep aim relative to the screen when the view is mirrored.
+    if mutators.contains(Mutator::Mirror) {
+        direction.x = -direction.x;
+    }
+
+    let assisted = if assist.enabled {
+        let targets = target_query
+            .iter()
+            .filter(|(_, body)| **body == RigidBody::Static)
+            .map(|(transform, _)| transform.translation().truncate());
+        assist.pick(origin, direction, targets)
+    } else {
+        None
+    };
+    aim.source = source;
+    aim.target = Some(assisted.unwrap_or(origin + direction * AIM_DISTANCE));
+}
+
+fn get_cursor_world_position(
+    settings: &Settings,
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    errors: &mut EventWriter<GameplayError>,
+) -> Option<Vec2> {
+    const SYSTEM: &str = "update_player_aim";
+    let window = windows
+        .single()
+        .or_report(errors, SYSTEM, "primary window")?;
+    // Off the window is somewhere to not aim, not an error.
+    let cursor_pos = view_cursor(window, settings)?;
+    let (camera, camera_transform) = camera_query.single().or_report(errors, SYSTEM, "camera")?;
+
+    camera
+        .viewport_to_world_2d(camera_transform, cursor_pos)
+        .ok()
+}
+
+/// Without a cursor, players need to see where they're aiming.
+fn draw_aim_reticle(aim: Res<PlayerAim>, mut gizmos: Gizmos) {
+    if aim.source == AimSource::Mouse {
+        return;
+    }
+    let Some(target) = aim.target else {
+        return;
+    };
+    gizmos.line_2d(aim.origin, target, RETICLE_COLOR.with_alpha(0.2));
+    gizmos.circle_2d(Isometry2d::from_translation(target), 10.0, RETICLE_COLOR);
+}
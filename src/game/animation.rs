@@ -3,15 +3,26 @@
 //! - [Sprite flipping](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_flipping.rs)
 //! - [Sprite animation](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_animation.rs)
 //! - [Timers](https://github.com/bevyengine/bevy/blob/latest/examples/time/timers.rs)
+//!
+//! Animation comes in two layers. [`PlayerAnimation`] is the base, and picks
+//! the atlas frame from what the player is doing. A [`TauntLayer`] plays over
+//! it, moving and squashing the sprite without touching the frame.
 
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::Anchor};
 use rand::prelude::*;
 use std::time::Duration;
 
 use crate::{
     AppSystems, PausableSystems,
     audio::sound_effect,
-    demo::{movement::MovementController, player::PlayerAssets},
+    game::{
+        cleanup::{GameplaySpawned, SpawnKind},
+        game_rng::{GameRng, RngStream},
+        movement::MovementController,
+        player::PlayerAssets,
+        taunt::TauntLayer,
+    },
+    screens::Screen,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -24,6 +35,7 @@ pub(super) fn plugin(app: &mut App) {
             (
                 update_animation_movement,
                 update_animation_atlas,
+                update_animation_taunt,
                 trigger_step_sound_effect,
             )
                 .chain()
@@ -72,11 +84,32 @@ fn update_animation_atlas(mut query: Query<(&PlayerAnimation, &mut Sprite)>) {
     }
 }
 
+/// Pose the sprite for the taunt playing over it, if any, and put it back
+/// once the taunt is over.
+fn update_animation_taunt(
+    mut commands: Commands,
+    mut query: Query<(Entity, &TauntLayer, &mut Sprite)>,
+) {
+    for (entity, taunt, mut sprite) in &mut query {
+        if taunt.timer.finished() {
+            sprite.anchor = Anchor::Center;
+            sprite.custom_size = None;
+            commands.entity(entity).remove::<TauntLayer>();
+            continue;
+        }
+        let (anchor, scale) = taunt.pose();
+        sprite.anchor = anchor;
+        sprite.custom_size = Some(PlayerAnimation::FRAME_SIZE * scale);
+    }
+}
+
 /// If the player is moving, play a step sound effect synchronized with the
 /// animation.
 fn trigger_step_sound_effect(
     mut commands: Commands,
     player_assets: Res<PlayerAssets>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
     mut step_query: Query<&PlayerAnimation>,
 ) {
     for animation in &mut step_query {
@@ -84,9 +117,13 @@ fn trigger_step_sound_effect(
             && animation.changed()
             && (animation.frame == 2 || animation.frame == 5)
         {
-            let rng = &mut rand::rng();
+            let rng = stream.get(&game_rng, "steps");
             let random_step = player_assets.steps.choose(rng).unwrap().clone();
-            commands.spawn(sound_effect(random_step));
+            commands.spawn((
+                sound_effect(random_step),
+                GameplaySpawned(SpawnKind::Sound),
+                StateScoped(Screen::Gameplay),
+            ));
         }
     }
 }
@@ -107,7 +144,15 @@ pub enum PlayerAnimationState {
     Walking,
 }
 
+impl Default for PlayerAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PlayerAnimation {
+    /// The size of a frame in the atlas, in pixels.
+    const FRAME_SIZE: Vec2 = Vec2::splat(32.0);
     /// The number of idle frames.
     const IDLE_FRAMES: usize = 2;
     /// The duration of each idle frame.
@@ -0,0 +1,75 @@
+//! Assists that make the level easier to get through, picked in their own
+//! menu rather than as a difficulty.
+//!
+//! Each assist is applied where the thing it changes is run: hazards move at
+//! [`Assists::hazard_speed`] in `hazards` and `path`, auto-reel takes in a
+//! hooked chain in `chain`, showing the way draws route arrows in
+//! `signpost`, and chain damage scales how much links hurt in
+//! `chain_impact`. The game has no jumping or stamina, so there's no
+//! coyote time, jump buffer or stamina to assist with. A run with any assist
+//! on is flagged in its [`Score`], which the results screen shows and the
+//! level's best time remembers. Replays keep the assists they were recorded
+//! with.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppSystems, game::objectives::Score, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Assists>();
+    app.init_resource::<Assists>();
+
+    app.add_systems(
+        Update,
+        flag_assisted_run
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The assists that are on.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[reflect(Resource)]
+pub struct Assists {
+    /// How fast hazards move, as a fraction of their usual speed.
+    pub hazard_speed: f32,
+    /// Whether a hooked chain reels itself in while no reel is held.
+    pub auto_reel: bool,
+    /// Whether arrows show the way on from signposts.
+    pub show_the_way: bool,
+    /// How much fast chain links hurt the player, as a fraction of the usual
+    /// damage. None at all when 0.
+    pub chain_damage: f32,
+}
+
+impl Default for Assists {
+    fn default() -> Self {
+        Self {
+            hazard_speed: 1.0,
+            auto_reel: false,
+            show_the_way: false,
+            chain_damage: 1.0,
+        }
+    }
+}
+
+impl Assists {
+    /// The hazard speeds to pick from, the usual one first.
+    pub const HAZARD_SPEEDS: [f32; 4] = [1.0, 0.75, 0.5, 0.25];
+    /// The chain damage levels to pick from, the usual one first.
+    pub const CHAIN_DAMAGES: [f32; 3] = [1.0, 0.5, 0.0];
+
+    /// Whether any assist is on.
+    pub fn any(&self) -> bool {
+        self.hazard_speed < 1.0 || self.auto_reel || self.show_the_way || self.chain_damage < 1.0
+    }
+}
+
+/// Once an assist has been on, the run counts as assisted, even if it's
+/// turned off again.
+fn flag_assisted_run(assists: Res<Assists>, mut score: ResMut<Score>) {
+    if assists.any() && !score.assisted {
+        score.assisted = true;
+    }
+}
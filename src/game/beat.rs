@@ -0,0 +1,168 @@
+//! Keeping time with the music.
+//!
+//! Music tracks spawned with a [`MusicTempo`] drive the [`MusicClock`], which
+//! follows the track's playback position in beats. Anything that should
+//! happen in time with the music reads the clock, e.g. stingers starting on
+//! the next beat, or level elements with a [`BeatPulse`] that flash on every
+//! beat or bar.
+
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems, audio::Music, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<MusicTempo>();
+    app.register_type::<BeatPulse>();
+    app.init_resource::<MusicClock>();
+
+    app.add_systems(
+        Update,
+        (
+            tick_music_clock.in_set(AppSystems::TickTimers),
+            pulse_on_beat
+                .in_set(AppSystems::Update)
+                .in_set(PausableSystems)
+                .run_if(in_state(Screen::Gameplay)),
+        ),
+    );
+}
+
+/// How quickly pulses die down, per second.
+const PULSE_DECAY: f32 = 6.0;
+
+/// The beat of a music track. Spawn it with the track's [`AudioPlayer`].
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct MusicTempo {
+    pub bpm: f32,
+    pub beats_per_bar: u32,
+    /// Seconds into the track where the first beat falls.
+    pub first_beat: f32,
+}
+
+impl MusicTempo {
+    pub fn beat_length(&self) -> f32 {
+        60.0 / self.bpm
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+pub enum BeatDivision {
+    Beat,
+    Bar,
+}
+
+/// Where the music with a [`MusicTempo`] is at, in beats.
+#[derive(Resource, Default, Debug)]
+pub struct MusicClock {
+    /// The tempo of the music playing, if any.
+    pub tempo: Option<MusicTempo>,
+    /// Beats since the first beat of the track.
+    pub beat: f32,
+    previous_beat: f32,
+    /// The track being followed.
+    track: Option<Entity>,
+    /// Seconds the track has played for. Audio sinks don't say where they
+    /// are, so this adds up the time they weren't paused.
+    played: f32,
+}
+
+impl MusicClock {
+    /// Whether a beat or bar started since the last frame.
+    pub fn just_started(&self, division: BeatDivision) -> bool {
+        let Some(tempo) = self.tempo else {
+            return false;
+        };
+        let length = match division {
+            BeatDivision::Beat => 1.0,
+            BeatDivision::Bar => tempo.beats_per_bar as f32,
+        };
+        // Looping back to the start of the track counts too.
+        (self.beat / length).floor() != (self.previous_beat / length).floor()
+    }
+
+    /// Seconds until the next beat, or `None` without music to follow.
+    pub fn until_next_beat(&self) -> Option<f32> {
+        let tempo = self.tempo?;
+        if self.beat < 0.0 {
+            return Some(-self.beat * tempo.beat_length());
+        }
+        let since_beat = self.beat.fract();
+        Some(if since_beat == 0.0 {
+            0.0
+        } else {
+            (1.0 - since_beat) * tempo.beat_length()
+        })
+    }
+}
+
+fn tick_music_clock(
+    time: Res<Time<Real>>,
+    mut clock: ResMut<MusicClock>,
+    music_query: Query<(Entity, &MusicTempo, &AudioSink), With<Music>>,
+) {
+    let Some((track, &tempo, sink)) = music_query.iter().next() else {
+        if clock.tempo.is_some() {
+            *clock = MusicClock::default();
+        }
+        return;
+    };
+    let same_track = clock.track == Some(track);
+    if !same_track {
+        clock.track = Some(track);
+        clock.played = 0.0;
+    }
+    if !sink.is_paused() {
+        clock.played += time.delta_secs() * sink.speed();
+    }
+    let beat = (clock.played - tempo.first_beat) / tempo.beat_length();
+    clock.previous_beat = if same_track { clock.beat } else { beat };
+    clock.tempo = Some(tempo);
+    clock.beat = beat;
+}
+
+/// Makes a sprite flash on every beat or bar of the music. Only the color
+/// changes, since scaling would resize colliders too.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct BeatPulse {
+    pub on: BeatDivision,
+    /// How close to white the sprite gets at the start of a flash.
+    pub amount: f32,
+    /// How far into the current flash it is, from 1 at the start to 0.
+    strength: f32,
+    /// The sprite's own color, taken when the first flash starts.
+    base: Option<Color>,
+}
+
+impl BeatPulse {
+    pub fn new(on: BeatDivision, amount: f32) -> Self {
+        Self {
+            on,
+            amount,
+            strength: 0.0,
+            base: None,
+        }
+    }
+}
+
+fn pulse_on_beat(
+    time: Res<Time>,
+    clock: Res<MusicClock>,
+    mut pulse_query: Query<(&mut BeatPulse, &mut Sprite)>,
+) {
+    for (mut pulse, mut sprite) in &mut pulse_query {
+        if clock.just_started(pulse.on) {
+            pulse.strength = 1.0;
+        } else if pulse.strength > 0.0 {
+            pulse.strength *= (-PULSE_DECAY * time.delta_secs()).exp();
+            if pulse.strength < 0.01 {
+                pulse.strength = 0.0;
+            }
+        } else {
+            continue;
+        }
+        let base = *pulse.base.get_or_insert(sprite.color);
+        sprite.color = base.mix(&Color::WHITE, pulse.amount * pulse.strength);
+    }
+}
@@ -0,0 +1,90 @@
+//! Big switches on the floor that only the heavy partner can hold down.
+//!
+//! Each switch holds its door open for as long as the heavy partner stands
+//! on it, whether they're being played or were left there. Stepping off
+//! closes the door again.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        level::obstacle,
+        level_data::BigSwitchData,
+        player::Character,
+        trigger_volume::{DetectTriggers, TriggerVolume, trigger_volume},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<BigSwitch>();
+
+    app.add_systems(
+        Update,
+        press_big_switches
+            .after(DetectTriggers)
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+const SWITCH_SIZE: Vec2 = Vec2::new(80.0, 12.0);
+const SWITCH_COLOR: Color = Color::srgb(0.8, 0.35, 0.3);
+const PRESSED_COLOR: Color = Color::srgb(0.35, 0.8, 0.4);
+
+/// A switch holding `door` open while it's pressed.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BigSwitch {
+    door: Entity,
+    pressed: bool,
+}
+
+/// Spawn the `index`th big switch of the level, and its door.
+pub fn spawn_big_switch(commands: &mut Commands, index: usize, data: &BigSwitchData) {
+    let door = commands
+        .spawn((
+            Name::new(format!("Big Switch Door {index}")),
+            obstacle(&data.door),
+        ))
+        .id();
+    commands.spawn((
+        Name::new(format!("Big Switch {index}")),
+        BigSwitch {
+            door,
+            pressed: false,
+        },
+        // Tall enough to catch the partner standing on it.
+        trigger_volume(Collider::rectangle(SWITCH_SIZE.x, SWITCH_SIZE.y * 4.0)),
+        Sprite::from_color(SWITCH_COLOR, SWITCH_SIZE),
+        Transform::from_translation(data.position.extend(0.0)),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn press_big_switches(
+    mut commands: Commands,
+    mut switch_query: Query<(&mut BigSwitch, &TriggerVolume, &mut Sprite)>,
+    character_query: Query<(Entity, &Character)>,
+) {
+    for (mut switch, volume, mut sprite) in &mut switch_query {
+        let pressed = character_query
+            .iter()
+            .any(|(entity, &character)| character == Character::Heavy && volume.contains(entity));
+        if pressed == switch.pressed {
+            continue;
+        }
+        switch.pressed = pressed;
+        sprite.color = if pressed { PRESSED_COLOR } else { SWITCH_COLOR };
+        let mut door = commands.entity(switch.door);
+        if pressed {
+            door.insert((ColliderDisabled, Visibility::Hidden));
+        } else {
+            door.remove::<ColliderDisabled>()
+                .insert(Visibility::Inherited);
+        }
+    }
+}
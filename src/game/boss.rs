@@ -0,0 +1,440 @@
+//! The boss, and the tug of war it starts with its chain.
+//!
+//! A boss waits until the player comes within [`FIRE_RANGE`], then fires a
+//! chain of its own at them. While it's flying the player can dodge it, or
+//! hook it with their own chain to yank it off the boss. Swinging a yanked
+//! chain back into the boss beats it outright, for [`DEFLECT_POINTS`] on top.
+//! Otherwise the far end latches on, and the two pull against each other: the boss steadily, the player by mashing the
+//! interaction button or holding reel-in. Whoever is pulling harder moves the
+//! [`BossPhase::Tug`] meter their way, and a boss winning drags the player
+//! in. Pulling the meter all the way over snaps the boss's chain and beats
+//! it. Being pulled all the way over, or into the boss, loses the run.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems, Pause,
+    game::{
+        chain::{ChainConfig, ChainOwner, ChainPool, ChainState, LINK_SIZE, Layer, OwnedChains},
+        gameplay_error::{GameplayError, OrReport},
+        hook_types::HookRegistry,
+        interaction::{Interactable, Interacted},
+        level_data::BossData,
+        objectives::{Defeat, DefeatCause, Score},
+        player::Player,
+    },
+    input::{ActionInput, InputAction},
+    menus::Menu,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Boss>();
+    app.register_type::<TugPrompt>();
+
+    app.add_systems(
+        Update,
+        (
+            fire_at_player,
+            latch_onto_player,
+            tug_of_war,
+            take_deflected_hits,
+            draw_tug_meter,
+        )
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The hook type the boss fires, from the
+/// [`HookRegistry`](crate::game::hook_types::HookRegistry).
+const BOSS_HOOK_TYPE: &str = "boss";
+/// How close the player has to get before the boss fires, in pixels.
+const FIRE_RANGE: f32 = 400.0;
+/// How long the boss waits before firing again when its chain misses or is
+/// lost, in seconds.
+const FIRE_COOLDOWN: f32 = 2.0;
+/// How long the chain flies before it latches on, which is how long the
+/// player has to get out of the way or hook it, in seconds.
+const FLIGHT_TIME: f32 = 0.6;
+/// How close the far end has to be to the player when it lands to latch on,
+/// in pixels.
+const LATCH_DISTANCE: f32 = 40.0;
+/// How fast a yanked chain has to hit the boss to beat it, in pixels per
+/// second.
+const DEFLECT_SPEED: f32 = 250.0;
+/// Points on top of [`BOSS_POINTS`] for beating a boss with its own chain.
+const DEFLECT_POINTS: u32 = 500;
+/// How hard the boss pulls, in meter per second.
+const BOSS_PULL: f32 = 0.35;
+/// How far each press of the interaction button pulls the meter.
+const MASH_PULL: f32 = 0.08;
+/// How hard holding reel-in pulls, in meter per second. Less than the boss,
+/// so holding alone only slows the boss down.
+const HOLD_PULL: f32 = 0.25;
+/// How fast a boss that's winning drags the player in, in pixels per second
+/// at the full difference in pull.
+const DRAG_SPEED: f32 = 240.0;
+/// How close to the boss the player can be dragged before the run is lost,
+/// past the edge of the boss, in pixels.
+const CAUGHT_DISTANCE: f32 = 10.0;
+/// Points for beating a boss.
+const BOSS_POINTS: u32 = 1000;
+const BOSS_COLOR: Color = Color::srgb(0.55, 0.12, 0.15);
+const BEATEN_COLOR: Color = Color::srgb(0.3, 0.25, 0.25);
+const METER_WIDTH: f32 = 80.0;
+/// How far above the player the meter is drawn, in pixels.
+const METER_OFFSET: f32 = 48.0;
+const METER_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.5);
+const PLAYER_SIDE_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+const BOSS_SIDE_COLOR: Color = Color::srgb(0.9, 0.25, 0.2);
+
+/// What the boss is doing.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub enum BossPhase {
+    /// Waiting for the player to come in range.
+    Waiting {
+        cooldown: f32,
+    },
+    /// Its chain is flying at the player.
+    Firing {
+        elapsed: f32,
+    },
+    /// Pulling on its chain, hooked onto the player. The meter goes from -1,
+    /// where the boss has won, to 1, where the player has.
+    Tug {
+        meter: f32,
+        prompt: Entity,
+    },
+    Beaten,
+}
+
+/// A boss, placed by a level's [`BossData`].
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Boss {
+    pub phase: BossPhase,
+    pub size: Vec2,
+}
+
+/// The interaction prompt the player mashes to pull against `boss`. It goes
+/// along with the player, so it's always in range.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct TugPrompt {
+    boss: Entity,
+}
+
+pub fn boss(data: &BossData) -> impl Bundle {
+    (
+        Name::new("Boss"),
+        Boss {
+            phase: BossPhase::Waiting { cooldown: 0.0 },
+            size: data.size,
+        },
+        RigidBody::Static,
+        Collider::rectangle(data.size.x, data.size.y),
+        CollisionLayers::new(
+            [Layer::StaticObstacle],
+            [Layer::ChainLink, Layer::Player, Layer::DynamicProp],
+        ),
+        Sprite {
+            color: BOSS_COLOR,
+            custom_size: Some(data.size),
+            ..default()
+        },
+        Transform::from_translation(data.position.extend(0.0)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn fire_at_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<ChainConfig>,
+    hooks: Res<HookRegistry>,
+    mut pool: ResMut<ChainPool>,
+    mut owned_chains: ResMut<OwnedChains>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut boss_query: Query<(Entity, &GlobalTransform, &mut Boss)>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "fire_at_player", "player")
+    else {
+        return;
+    };
+    let target = player.translation().truncate();
+    for (entity, transform, mut boss) in &mut boss_query {
+        let BossPhase::Waiting { cooldown } = &mut boss.phase else {
+            continue;
+        };
+        *cooldown = (*cooldown - time.delta_secs()).max(0.0);
+        let position = transform.translation().truncate();
+        if *cooldown > 0.0 || position.distance(target) > FIRE_RANGE {
+            continue;
+        }
+
+        // The chain leaves from the side of the boss facing the player.
+        let direction = (target - position).normalize_or(Vec2::X);
+        let edge = direction * (boss.size.length() / 2.0 + LINK_SIZE);
+        let mut chain = pool.fire_chain(
+            &mut commands,
+            &config,
+            hooks.get(BOSS_HOOK_TYPE),
+            position + edge,
+            target,
+            Vec2::ZERO,
+        );
+        chain.hand_to(&mut commands, entity);
+        if let Some(&root) = chain.links.first() {
+            chain.hook = Some(
+                commands
+                    .spawn((
+                        Name::new("Boss Chain Hold"),
+                        RevoluteJoint::new(entity, root).with_local_anchor_1(edge),
+                        StateScoped(Screen::Gameplay),
+                    ))
+                    .id(),
+            );
+        }
+        owned_chains.0.push((entity, chain));
+        boss.phase = BossPhase::Firing { elapsed: 0.0 };
+    }
+}
+
+/// Latch a boss's chain onto the player once it's done flying, if they're
+/// still where it was aimed, or give up on it if they got away.
+fn latch_onto_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut owned_chains: ResMut<OwnedChains>,
+    mut pool: ResMut<ChainPool>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    mut boss_query: Query<(Entity, &mut Boss)>,
+    tip_query: Query<&GlobalTransform>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some((player, player_transform)) =
+        player_query
+            .single()
+            .or_report(&mut errors, "latch_onto_player", "player")
+    else {
+        return;
+    };
+    let target = player_transform.translation().truncate();
+    for (entity, mut boss) in &mut boss_query {
+        let BossPhase::Firing { elapsed } = &mut boss.phase else {
+            continue;
+        };
+        *elapsed += time.delta_secs();
+        // Yanked away by the player
+        let Some(&tip) = owned_chains
+            .get(entity)
+            .and_then(|chain| chain.links.last())
+        else {
+            boss.phase = BossPhase::Waiting {
+                cooldown: FIRE_COOLDOWN,
+            };
+            continue;
+        };
+        if *elapsed < FLIGHT_TIME {
+            continue;
+        }
+        let reached = tip_query.get(tip).is_ok_and(|transform| {
+            transform.translation().truncate().distance(target) <= LATCH_DISTANCE
+        });
+        if !reached {
+            if let Some(chain) = owned_chains.take(entity) {
+                chain.release(&mut commands, &mut pool);
+            }
+            boss.phase = BossPhase::Waiting {
+                cooldown: FIRE_COOLDOWN,
+            };
+            continue;
+        }
+
+        let prompt = commands
+            .spawn((
+                Name::new("Tug Prompt"),
+                TugPrompt { boss: entity },
+                Interactable {
+                    prompt: "Pull!".to_string(),
+                    icon: None,
+                    priority: i32::MAX,
+                    range: f32::INFINITY,
+                },
+                Transform::default(),
+                ChildOf(player),
+            ))
+            .id();
+        boss.phase = BossPhase::Tug { meter: 0.0, prompt };
+    }
+}
+
+fn tug_of_war(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: ActionInput,
+    mut interacted: EventReader<Interacted>,
+    chain_state: Res<ChainState>,
+    mut owned_chains: ResMut<OwnedChains>,
+    mut pool: ResMut<ChainPool>,
+    mut score: ResMut<Score>,
+    mut defeat: ResMut<Defeat>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    prompt_query: Query<&TugPrompt>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    mut boss_query: Query<(Entity, &GlobalTransform, &mut Boss, &mut Sprite)>,
+    mut tip_query: Query<(&mut Position, &mut LinearVelocity), Without<Player>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(mut player) = player_query
+        .single_mut()
+        .or_report(&mut errors, "tug_of_war", "player")
+    else {
+        return;
+    };
+    let mashes: Vec<Entity> = interacted
+        .read()
+        .filter_map(|event| prompt_query.get(event.target).ok())
+        .map(|prompt| prompt.boss)
+        .collect();
+    let dt = time.delta_secs();
+    // Reeling in pulls on the boss only while the player has no chain of
+    // their own to reel.
+    let holding = input.pressed(InputAction::ReelIn) && chain_state.chains.is_empty();
+
+    for (entity, transform, mut boss, mut sprite) in &mut boss_query {
+        let size = boss.size;
+        let BossPhase::Tug { meter, prompt } = &mut boss.phase else {
+            continue;
+        };
+        let Some(&tip) = owned_chains
+            .get(entity)
+            .and_then(|chain| chain.links.last())
+        else {
+            commands.entity(*prompt).despawn();
+            boss.phase = BossPhase::Waiting {
+                cooldown: FIRE_COOLDOWN,
+            };
+            continue;
+        };
+
+        let player_pull = mashes.iter().filter(|&&mashed| mashed == entity).count() as f32
+            * MASH_PULL
+            + if holding { HOLD_PULL * dt } else { 0.0 };
+        let net = player_pull - BOSS_PULL * dt;
+        *meter = (*meter + net).clamp(-1.0, 1.0);
+
+        let position = transform.translation().truncate();
+        let mut target = player.translation.truncate();
+        if net < 0.0 {
+            // How much of the boss's pull the player isn't matching.
+            let deficit = -net / (BOSS_PULL * dt);
+            let toward = (position - target).normalize_or_zero();
+            target += toward * DRAG_SPEED * deficit * dt;
+            player.translation = target.extend(player.translation.z);
+        }
+        // The far end of the chain stays latched onto the player.
+        if let Ok((mut tip_position, mut velocity)) = tip_query.get_mut(tip) {
+            tip_position.0 = target;
+            velocity.0 = Vec2::ZERO;
+        }
+
+        let caught = (target - position)
+            .abs()
+            .cmple(size / 2.0 + CAUGHT_DISTANCE)
+            .all();
+        if *meter >= 1.0 {
+            commands.entity(*prompt).despawn();
+            if let Some(chain) = owned_chains.take(entity) {
+                chain.snap(&mut commands, &mut pool);
+            }
+            score.points += BOSS_POINTS;
+            sprite.color = BEATEN_COLOR;
+            boss.phase = BossPhase::Beaten;
+        } else if *meter <= -1.0 || caught {
+            defeat.0 = Some(DefeatCause::DraggedIn);
+            next_pause.set(Pause(true));
+            next_menu.set(Menu::Retry);
+        }
+    }
+}
+
+/// Beat any boss hit hard enough by a chain the player yanked away.
+fn take_deflected_hits(
+    mut commands: Commands,
+    collisions: Collisions,
+    mut score: ResMut<Score>,
+    player_query: Query<Entity, With<Player>>,
+    mut boss_query: Query<(Entity, &mut Boss, &mut Sprite)>,
+    link_query: Query<(&ChainOwner, &LinearVelocity)>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) =
+        player_query
+            .single()
+            .or_report(&mut errors, "take_deflected_hits", "player")
+    else {
+        return;
+    };
+    for (entity, mut boss, mut sprite) in &mut boss_query {
+        if boss.phase == BossPhase::Beaten {
+            continue;
+        }
+        let deflected = collisions.collisions_with(entity).any(|pair| {
+            let other = if pair.collider1 == entity {
+                pair.collider2
+            } else {
+                pair.collider1
+            };
+            link_query.get(other).is_ok_and(|(owner, velocity)| {
+                owner.0 == player && velocity.length() >= DEFLECT_SPEED
+            })
+        });
+        if !deflected {
+            continue;
+        }
+        if let BossPhase::Tug { prompt, .. } = boss.phase {
+            commands.entity(prompt).despawn();
+        }
+        score.points += BOSS_POINTS + DEFLECT_POINTS;
+        sprite.color = BEATEN_COLOR;
+        boss.phase = BossPhase::Beaten;
+    }
+}
+
+/// Draw the tug meter above the player: the player's side fills from the
+/// left, the boss's from the right, and they meet where the meter is.
+fn draw_tug_meter(
+    mut gizmos: Gizmos,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    boss_query: Query<&Boss>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "draw_tug_meter", "player")
+    else {
+        return;
+    };
+    let center = player.translation().truncate() + Vec2::Y * METER_OFFSET;
+    let left = center - Vec2::X * METER_WIDTH / 2.0;
+    let right = center + Vec2::X * METER_WIDTH / 2.0;
+    for boss in &boss_query {
+        let BossPhase::Tug { meter, .. } = boss.phase else {
+            continue;
+        };
+        let split = left.lerp(right, (meter + 1.0) / 2.0);
+        gizmos.line_2d(left, split, PLAYER_SIDE_COLOR);
+        gizmos.line_2d(split, right, BOSS_SIDE_COLOR);
+        gizmos.line_2d(split - Vec2::Y * 6.0, split + Vec2::Y * 6.0, METER_COLOR);
+    }
+}
@@ -0,0 +1,335 @@
+//! A short course measuring how quickly and accurately the player hooks, to
+//! suggest assists. It's offered on the title screen, first of all to new
+//! players.
+//!
+//! Anchors along the ceiling light up one at a time, after a random wait.
+//! Each trial times how long the player takes to fire once one lights up,
+//! and how far from it their hook lands. After [`TRIALS`] trials the course
+//! suggests assists to go with the results: slower hazards for slow
+//! reactions, and auto-reel for hooks that land wide. The player can take
+//! the suggestion or not, and either way the results are kept in the
+//! [`Progress`].
+//!
+//! The game has no difficulty presets, so assists are all there is to
+//! suggest.
+
+use bevy::{prelude::*, ui::Val::*};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        assists::Assists,
+        chain::{ChainFired, ChainHooked},
+        game_rng::{GameRng, RngStream},
+        level_data::{LevelData, ObstacleData},
+    },
+    persistence::Progress,
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CalibrationTarget>();
+    app.init_resource::<Calibration>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        start_calibration.run_if(in_calibration),
+    );
+    app.add_systems(
+        Update,
+        (light_target, time_trial)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay).and(in_calibration)),
+    );
+    app.add_systems(OnEnter(Screen::Title), leave_calibration);
+}
+
+/// How many anchors light up over the course.
+pub const TRIALS: usize = 6;
+/// How many anchors there are to light up.
+const TARGETS: usize = 7;
+const COURSE_HALF_WIDTH: f32 = 900.0;
+const COURSE_HEIGHT: f32 = 700.0;
+const WALL_THICKNESS: f32 = 100.0;
+/// How far below the ceiling the anchors hang.
+const TARGET_DROP: f32 = 250.0;
+/// How long before an anchor lights up, in seconds.
+const WAIT: std::ops::Range<f32> = 1.0..2.5;
+/// How long the player has to hook a lit anchor before the trial counts as
+/// missed, in seconds.
+const TRIAL_TIMEOUT: f32 = 4.0;
+/// How far off a missed trial counts as, in pixels.
+const MISS_DISTANCE: f32 = 200.0;
+/// Reactions slower than these, in seconds, suggest slowing hazards down to
+/// 75% and 50%.
+const SLOW_REACTION: f32 = 0.6;
+const VERY_SLOW_REACTION: f32 = 0.9;
+/// Hooks landing further off than this on average, in pixels, suggest
+/// auto-reel.
+const WIDE_HOOKS: f32 = 60.0;
+const TARGET_SIZE: Vec2 = Vec2::splat(36.0);
+const TARGET_COLOR: Color = Color::srgba(1.0, 0.85, 0.2, 0.6);
+
+/// Whether the player is on the calibration course, and how far they got.
+#[derive(Resource, Default, Debug)]
+pub struct Calibration {
+    pub active: bool,
+    trials: Vec<Trial>,
+    state: TrialState,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Trial {
+    reaction_time: f32,
+    miss_distance: f32,
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+enum TrialState {
+    /// Waiting this much longer to light the next anchor.
+    Waiting(f32),
+    /// The anchor at `target` has been lit for `elapsed` seconds, and the
+    /// player fired after `fired` seconds if they have.
+    Lit {
+        target: Vec2,
+        elapsed: f32,
+        fired: Option<f32>,
+    },
+    /// All trials are done.
+    #[default]
+    Done,
+}
+
+/// Run condition for things that only happen on the calibration course.
+pub fn in_calibration(calibration: Res<Calibration>) -> bool {
+    calibration.active
+}
+
+/// How the player did on the calibration course, and the assists that suit
+/// them.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct CalibrationResult {
+    /// The average time from an anchor lighting up to firing, in seconds.
+    pub reaction_time: f32,
+    /// How far from the anchors hooks landed on average, in pixels.
+    pub miss_distance: f32,
+    pub hazard_speed: f32,
+    pub auto_reel: bool,
+}
+
+impl CalibrationResult {
+    fn from_trials(trials: &[Trial]) -> Self {
+        let count = trials.len().max(1) as f32;
+        let reaction_time = trials.iter().map(|trial| trial.reaction_time).sum::<f32>() / count;
+        let miss_distance = trials.iter().map(|trial| trial.miss_distance).sum::<f32>() / count;
+        let hazard_speed = if reaction_time > VERY_SLOW_REACTION {
+            0.5
+        } else if reaction_time > SLOW_REACTION {
+            0.75
+        } else {
+            1.0
+        };
+        Self {
+            reaction_time,
+            miss_distance,
+            hazard_speed,
+            auto_reel: miss_distance > WIDE_HOOKS,
+        }
+    }
+}
+
+/// The anchors to light up, spread along under the ceiling. All of them are
+/// in range of the spawn point.
+fn target_positions() -> impl Iterator<Item = Vec2> {
+    let spacing = COURSE_HALF_WIDTH * 2.0 / (TARGETS + 1) as f32;
+    (1..=TARGETS).map(move |i| {
+        Vec2::new(
+            -COURSE_HALF_WIDTH + spacing * i as f32,
+            COURSE_HEIGHT - TARGET_DROP,
+        )
+    })
+}
+
+/// The level the course plays in: a closed room with a row of anchors.
+pub fn calibration_course() -> LevelData {
+    let wall = |position: Vec2, size: Vec2| ObstacleData { position, size };
+    let width = COURSE_HALF_WIDTH * 2.0;
+    LevelData {
+        name: "Calibration".to_string(),
+        spawn: Some(Vec2::new(0.0, 120.0)),
+        obstacles: vec![
+            wall(Vec2::ZERO, Vec2::new(width, WALL_THICKNESS)),
+            wall(
+                Vec2::new(0.0, COURSE_HEIGHT),
+                Vec2::new(width, WALL_THICKNESS),
+            ),
+            wall(
+                Vec2::new(-COURSE_HALF_WIDTH, COURSE_HEIGHT / 2.0),
+                Vec2::new(WALL_THICKNESS, COURSE_HEIGHT),
+            ),
+            wall(
+                Vec2::new(COURSE_HALF_WIDTH, COURSE_HEIGHT / 2.0),
+                Vec2::new(WALL_THICKNESS, COURSE_HEIGHT),
+            ),
+        ],
+        anchors: target_positions().collect(),
+        ..default()
+    }
+}
+
+/// Marks the lit anchor.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct CalibrationTarget;
+
+fn start_calibration(mut commands: Commands, mut calibration: ResMut<Calibration>) {
+    calibration.trials.clear();
+    calibration.state = TrialState::Waiting(WAIT.end);
+    commands.spawn((
+        widget::ui_root("Calibration Instructions"),
+        GlobalZIndex(1),
+        StateScoped(Screen::Gameplay),
+        children![(
+            widget::label("Hook each anchor as soon as it lights up"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Px(20.0),
+                ..default()
+            },
+        )],
+        Pickable::IGNORE,
+    ));
+}
+
+fn light_target(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut calibration: ResMut<Calibration>,
+) {
+    let TrialState::Waiting(remaining) = &mut calibration.state else {
+        return;
+    };
+    *remaining -= time.delta_secs();
+    if *remaining > 0.0 {
+        return;
+    }
+    let rng = stream.get(&game_rng, "calibration targets");
+    let target = target_positions().choose(rng).unwrap_or(Vec2::ZERO);
+    calibration.state = TrialState::Lit {
+        target,
+        elapsed: 0.0,
+        fired: None,
+    };
+    commands.spawn((
+        Name::new("Calibration Target"),
+        CalibrationTarget,
+        Sprite::from_color(TARGET_COLOR, TARGET_SIZE),
+        Transform::from_translation(target.extend(0.5)),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn time_trial(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut calibration: ResMut<Calibration>,
+    mut progress: ResMut<Progress>,
+    mut fired: EventReader<ChainFired>,
+    mut hooked: EventReader<ChainHooked>,
+    target_query: Query<Entity, With<CalibrationTarget>>,
+) {
+    let fired = fired.read().count() > 0;
+    let hook = hooked.read().last().map(|event| event.point);
+    let TrialState::Lit {
+        target,
+        elapsed,
+        fired: fired_after,
+    } = &mut calibration.state
+    else {
+        return;
+    };
+    *elapsed += time.delta_secs();
+    if fired && fired_after.is_none() {
+        *fired_after = Some(*elapsed);
+    }
+    let trial = match (hook, *fired_after) {
+        (Some(point), Some(reaction_time)) => Trial {
+            reaction_time,
+            miss_distance: point.distance(*target).min(MISS_DISTANCE),
+        },
+        _ if *elapsed >= TRIAL_TIMEOUT => Trial {
+            reaction_time: fired_after.unwrap_or(TRIAL_TIMEOUT),
+            miss_distance: MISS_DISTANCE,
+        },
+        _ => return,
+    };
+
+    for entity in &target_query {
+        commands.entity(entity).despawn();
+    }
+    calibration.trials.push(trial);
+    if calibration.trials.len() < TRIALS {
+        let rng = stream.get(&game_rng, "calibration waits");
+        calibration.state = TrialState::Waiting(rng.random_range(WAIT));
+        return;
+    }
+
+    calibration.state = TrialState::Done;
+    let result = CalibrationResult::from_trials(&calibration.trials);
+    progress.calibration = Some(result);
+    commands.spawn(results_panel(result));
+}
+
+fn results_panel(result: CalibrationResult) -> impl Bundle {
+    let hazards = if result.hazard_speed < 1.0 {
+        format!("Hazards at {:.0}% speed", result.hazard_speed * 100.0)
+    } else {
+        "Hazards at full speed".to_string()
+    };
+    let reel = if result.auto_reel {
+        "Auto reel on"
+    } else {
+        "Auto reel off"
+    };
+    (
+        widget::ui_root("Calibration Results"),
+        GlobalZIndex(2),
+        StateScoped(Screen::Gameplay),
+        children![
+            widget::header("All done"),
+            widget::label(format!(
+                "You reacted in {:.2}s and hooked {:.0}px off on average",
+                result.reaction_time, result.miss_distance
+            )),
+            widget::label(format!("Suggested: {hazards}, {reel}")),
+            widget::button(
+                "Use suggested assists",
+                move |_: Trigger<Pointer<Click>>,
+                      mut assists: ResMut<Assists>,
+                      mut next_screen: ResMut<NextState<Screen>>| {
+                    assists.hazard_speed = result.hazard_speed;
+                    assists.auto_reel = result.auto_reel;
+                    next_screen.set(Screen::Title);
+                }
+            ),
+            widget::button("Keep my assists", back_to_title),
+        ],
+    )
+}
+
+fn back_to_title(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn leave_calibration(mut calibration: ResMut<Calibration>) {
+    *calibration = Calibration::default();
+}
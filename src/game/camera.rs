@@ -0,0 +1,349 @@
+//! The camera following the player through the level.
+//!
+//! The camera eases toward the player rather than sticking to them, leans a
+//! little toward where they're aiming, and zooms out the faster they move. It
+//! never shows more than [`CameraConfig::margin`] past the edges of the
+//! [`LevelBounds`], and levels smaller than the view are kept centered. In
+//! levels with a [`CameraRail`], the camera stays on it. Camera anchors take
+//! the camera away for a moment while they show something, and a
+//! [`FreeCamera`] takes it away altogether.
+//!
+//! With [`Settings::screen_nudge`] on, hooks attaching and hard landings kick
+//! the camera a pixel or two in the direction of the jolt, scaled by the
+//! feedback intensity, and it eases straight back.
+
+use avian2d::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        aim::PlayerAim,
+        camera_anchor::CameraPullIn,
+        chain::{ChainHooked, Layer},
+        gameplay_error::{GameplayError, OrReport},
+        level::{LevelBounds, spawn_level},
+        path::PathShape,
+        player::Player,
+    },
+    persistence::Settings,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CameraConfig>();
+    app.init_resource::<CameraConfig>();
+    app.init_resource::<CameraRail>();
+    app.init_resource::<CameraNudge>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        snap_camera_to_player.after(spawn_level),
+    );
+    app.add_systems(
+        Update,
+        (nudge_on_hook, nudge_on_landing, follow_player)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(not(resource_exists::<CameraPullIn>))
+                    .and(not(resource_exists::<FreeCamera>)),
+            ),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), reset_camera);
+}
+
+/// While this exists, the camera is moved by hand rather than following the
+/// player, e.g. in the replay viewer.
+#[derive(Resource, Default, Debug)]
+pub struct FreeCamera;
+
+/// How far a hook attaching kicks the camera, in pixels.
+const HOOK_NUDGE: f32 = 1.0;
+/// How far a landing kicks the camera, in pixels.
+const LANDING_NUDGE: f32 = 2.0;
+/// How fast the player has to hit something for it to count as a landing, in
+/// pixels per second.
+const MIN_LANDING_SPEED: f32 = 300.0;
+/// How quickly the camera comes back from a nudge, per second.
+const NUDGE_RECOVERY: f32 = 20.0;
+
+/// How the camera follows the player.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct CameraConfig {
+    /// How quickly the camera catches up, per second. Higher is snappier.
+    pub smoothing: f32,
+    /// How much of the way toward the aim point the camera leans.
+    pub look_ahead: f32,
+    /// The furthest the camera leans toward the aim point, in pixels.
+    pub max_look_ahead: f32,
+    /// Whether moving fast zooms out.
+    pub speed_zoom: bool,
+    /// The speed at which the camera is zoomed out all the way, in pixels per
+    /// second.
+    pub full_zoom_speed: f32,
+    /// How far out the camera zooms at full speed, as a projection scale.
+    pub max_zoom: f32,
+    /// How quickly the zoom follows the player's speed, per second.
+    pub zoom_smoothing: f32,
+    /// How far past the level's edges the camera may show, in pixels.
+    pub margin: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            smoothing: 6.0,
+            look_ahead: 0.25,
+            max_look_ahead: 120.0,
+            speed_zoom: true,
+            full_zoom_speed: 1200.0,
+            max_zoom: 1.4,
+            zoom_smoothing: 2.0,
+            margin: 100.0,
+        }
+    }
+}
+
+/// The current level's camera track, if it has one.
+#[derive(Resource, Default, Debug)]
+pub struct CameraRail(pub Option<PathShape>);
+
+/// A small kick to the camera, on top of where it follows the player to.
+#[derive(Resource, Default, Debug)]
+struct CameraNudge {
+    /// How far the camera is kicked right now.
+    offset: Vec2,
+    /// How much of the kick is in the camera's transform, to take back out
+    /// before following the player.
+    applied: Vec2,
+}
+
+impl CameraNudge {
+    fn kick(&mut self, settings: &Settings, direction: Vec2, distance: f32) {
+        if settings.screen_nudge {
+            self.offset = direction.normalize_or_zero() * distance * settings.feedback_intensity;
+        }
+    }
+}
+
+/// Kick the camera toward where a hook attached, the way the player is about
+/// to be pulled.
+fn nudge_on_hook(
+    settings: Res<Settings>,
+    mut nudge: ResMut<CameraNudge>,
+    mut hooked: EventReader<ChainHooked>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if hooked.is_empty() {
+        return;
+    }
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "nudge_on_hook", "player")
+    else {
+        return;
+    };
+    let position = player.translation().truncate();
+    for event in hooked.read() {
+        nudge.kick(&settings, event.point - position, HOOK_NUDGE);
+    }
+}
+
+/// Kick the camera the way the player was going when they hit a wall or the
+/// ground hard. Only a swinging player is a body that can hit anything.
+fn nudge_on_landing(
+    settings: Res<Settings>,
+    collisions: Collisions,
+    mut nudge: ResMut<CameraNudge>,
+    player_query: Query<(Entity, Option<&LinearVelocity>), With<Player>>,
+    layer_query: Query<&CollisionLayers>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some((player, velocity)) =
+        player_query
+            .single()
+            .or_report(&mut errors, "nudge_on_landing", "player")
+    else {
+        return;
+    };
+    let Some(velocity) = velocity else {
+        return;
+    };
+    for pair in collisions
+        .collisions_with(player)
+        .filter(|pair| pair.collision_started())
+    {
+        let other = if pair.collider1 == player {
+            pair.collider2
+        } else {
+            pair.collider1
+        };
+        if !layer_query
+            .get(other)
+            .is_ok_and(|layers| layers.memberships.has_all(Layer::StaticObstacle))
+        {
+            continue;
+        }
+        let Some(manifold) = pair.manifolds.first() else {
+            continue;
+        };
+        // The normal points from the first collider into the second.
+        let into_surface = if pair.collider1 == player {
+            manifold.normal
+        } else {
+            -manifold.normal
+        };
+        if velocity.dot(into_surface) >= MIN_LANDING_SPEED {
+            nudge.kick(&settings, into_surface, LANDING_NUDGE);
+        }
+    }
+}
+
+fn snap_camera_to_player(
+    rail: Res<CameraRail>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    const SYSTEM: &str = "snap_camera_to_player";
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, SYSTEM, "player")
+    else {
+        return;
+    };
+    let Some(mut camera) = camera_query
+        .single_mut()
+        .or_report(&mut errors, SYSTEM, "camera")
+    else {
+        return;
+    };
+    let position = player.translation().truncate();
+    let position = rail
+        .0
+        .as_ref()
+        .and_then(|rail| rail.closest_point(position))
+        .unwrap_or(position);
+    let z = camera.translation.z;
+    camera.translation = position.extend(z);
+}
+
+fn follow_player(
+    time: Res<Time>,
+    config: Res<CameraConfig>,
+    mut nudge: ResMut<CameraNudge>,
+    aim: Res<PlayerAim>,
+    bounds: Res<LevelBounds>,
+    rail: Res<CameraRail>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<(&GlobalTransform, Option<&LinearVelocity>), With<Player>>,
+    mut camera_query: Query<
+        (&Camera, &mut Transform, &mut Projection),
+        (With<Camera2d>, Without<Player>),
+    >,
+    mut errors: EventWriter<GameplayError>,
+) {
+    const SYSTEM: &str = "follow_player";
+    let Some(window) = window_query
+        .single()
+        .or_report(&mut errors, SYSTEM, "primary window")
+    else {
+        return;
+    };
+    let Some((player_transform, velocity)) =
+        player_query
+            .single()
+            .or_report(&mut errors, SYSTEM, "player")
+    else {
+        return;
+    };
+    let Some((camera, mut camera_transform, mut projection)) =
+        camera_query
+            .single_mut()
+            .or_report(&mut errors, SYSTEM, "camera")
+    else {
+        return;
+    };
+    let Projection::Orthographic(projection) = &mut *projection else {
+        return;
+    };
+    let dt = time.delta_secs();
+
+    // Walking players aren't physics bodies, so only swinging zooms out.
+    let speed = velocity.map_or(0.0, |velocity| velocity.length());
+    let zoom = if config.speed_zoom {
+        1.0 + (config.max_zoom - 1.0) * (speed / config.full_zoom_speed).min(1.0)
+    } else {
+        1.0
+    };
+    projection
+        .scale
+        .smooth_nudge(&zoom, config.zoom_smoothing, dt);
+
+    let position = player_transform.translation().truncate();
+    let lean = aim.target.map_or(Vec2::ZERO, |target| {
+        ((target - position) * config.look_ahead).clamp_length_max(config.max_look_ahead)
+    });
+    let look_at = position + lean;
+    let look_at = rail
+        .0
+        .as_ref()
+        .and_then(|rail| rail.closest_point(look_at))
+        .unwrap_or(look_at);
+    let target = clamp_to_bounds(
+        look_at,
+        camera.logical_viewport_size().unwrap_or(window.size()) * projection.scale / 2.0,
+        bounds.0.inflate(config.margin),
+    );
+    let mut translation = camera_transform.translation.truncate() - nudge.applied;
+    translation.smooth_nudge(&target, config.smoothing, dt);
+    nudge.offset.smooth_nudge(&Vec2::ZERO, NUDGE_RECOVERY, dt);
+    nudge.applied = nudge.offset;
+    camera_transform.translation =
+        (translation + nudge.applied).extend(camera_transform.translation.z);
+}
+
+/// Keep a view of `half_size` around `center` inside `bounds`, or centered on
+/// them along any axis where the view is bigger.
+fn clamp_to_bounds(center: Vec2, half_size: Vec2, bounds: Rect) -> Vec2 {
+    let min = bounds.min + half_size;
+    let max = bounds.max - half_size;
+    let clamp = |value: f32, min: f32, max: f32, middle: f32| {
+        if min > max {
+            middle
+        } else {
+            value.clamp(min, max)
+        }
+    };
+    let middle = bounds.center();
+    Vec2::new(
+        clamp(center.x, min.x, max.x, middle.x),
+        clamp(center.y, min.y, max.y, middle.y),
+    )
+}
+
+/// Menus and the editor expect the camera where it started.
+fn reset_camera(
+    mut nudge: ResMut<CameraNudge>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    *nudge = CameraNudge::default();
+    let Some((mut transform, mut projection)) =
+        camera_query
+            .single_mut()
+            .or_report(&mut errors, "reset_camera", "camera")
+    else {
+        return;
+    };
+    transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+    transform.rotation = Quat::IDENTITY;
+    if let Projection::Orthographic(projection) = &mut *projection {
+        projection.scale = 1.0;
+    }
+}
@@ -0,0 +1,170 @@
+//! Anchors that show the player something when they're hooked.
+//!
+//! A camera anchor looks and hangs like any other anchor. The first time a
+//! chain hooks one in an attempt, the camera leaves the player and glides
+//! along a route through the anchor's `via` points to its focus, zooming to
+//! frame it, holds there for a few seconds, and then goes back to following
+//! the player. The route is a Catmull-Rom [`PathShape`], like a camera rail's,
+//! and the player keeps control all the while.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::ChainAttached,
+        gameplay_error::{GameplayError, OrReport},
+        level::anchor,
+        level_data::CameraAnchorData,
+        path::{PathCurve, PathEasing, PathShape},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CameraAnchor>();
+
+    app.add_systems(
+        Update,
+        (start_pull_in, pull_camera)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), end_pull_in);
+}
+
+/// How fast the camera travels to a focus, in pixels per second.
+const PULL_SPEED: f32 = 600.0;
+/// The shortest and longest the camera takes to get to a focus, in seconds.
+const PULL_TIME: (f32, f32) = (0.6, 2.0);
+
+/// Pulls the camera over to `focus` when hooked.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct CameraAnchor {
+    pub focus: Vec2,
+    pub via: Vec<Vec2>,
+    /// How long the camera stays on the focus, in seconds.
+    pub hold: f32,
+    /// The projection scale the focus is framed at.
+    pub zoom: f32,
+    /// Whether it has been hooked this attempt.
+    pub shown: bool,
+}
+
+pub fn camera_anchor(index: usize, data: &CameraAnchorData) -> impl Bundle {
+    (
+        Name::new(format!("Camera Anchor {index}")),
+        anchor(data.position),
+        CameraAnchor {
+            focus: data.focus,
+            via: data.via.clone(),
+            hold: data.hold,
+            zoom: data.zoom,
+            shown: false,
+        },
+    )
+}
+
+/// The camera on its way to, or resting on, a camera anchor's focus. The
+/// camera doesn't follow the player while this exists.
+#[derive(Resource, Debug)]
+pub struct CameraPullIn {
+    route: PathShape,
+    /// The projection scale the pull started at.
+    from_zoom: f32,
+    zoom: f32,
+    /// How long getting to the focus takes, in seconds.
+    travel: f32,
+    hold: f32,
+    elapsed: f32,
+}
+
+fn start_pull_in(
+    mut commands: Commands,
+    hooked_query: Query<&ChainAttached, Added<ChainAttached>>,
+    mut anchor_query: Query<&mut CameraAnchor>,
+    camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
+    pull: Option<Res<CameraPullIn>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    // One thing at a time. Anchors hooked meanwhile can show theirs later.
+    if pull.is_some() {
+        return;
+    }
+    let Some(target) = hooked_query
+        .iter()
+        .map(|attached| attached.target)
+        .find(|&target| anchor_query.get(target).is_ok_and(|anchor| !anchor.shown))
+    else {
+        return;
+    };
+    let Ok(mut anchor) = anchor_query.get_mut(target) else {
+        return;
+    };
+    let Some((transform, projection)) =
+        camera_query
+            .single()
+            .or_report(&mut errors, "start_pull_in", "camera")
+    else {
+        return;
+    };
+    anchor.shown = true;
+
+    let from = transform.translation.truncate();
+    let points: Vec<Vec2> = std::iter::once(from)
+        .chain(anchor.via.iter().copied())
+        .chain(std::iter::once(anchor.focus))
+        .collect();
+    let route = PathShape::new(&points, PathCurve::CatmullRom, false);
+    let from_zoom = match projection {
+        Projection::Orthographic(projection) => projection.scale,
+        _ => 1.0,
+    };
+    commands.insert_resource(CameraPullIn {
+        travel: (route.length() / PULL_SPEED).clamp(PULL_TIME.0, PULL_TIME.1),
+        route,
+        from_zoom,
+        zoom: anchor.zoom,
+        hold: anchor.hold,
+        elapsed: 0.0,
+    });
+}
+
+fn pull_camera(
+    mut commands: Commands,
+    time: Res<Time>,
+    pull: Option<ResMut<CameraPullIn>>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(mut pull) = pull else {
+        return;
+    };
+    pull.elapsed += time.delta_secs();
+    if pull.elapsed >= pull.travel + pull.hold {
+        commands.remove_resource::<CameraPullIn>();
+        return;
+    }
+
+    let t = PathEasing::InOut.ease((pull.elapsed / pull.travel).min(1.0));
+    let Some((mut transform, mut projection)) =
+        camera_query
+            .single_mut()
+            .or_report(&mut errors, "pull_camera", "camera")
+    else {
+        return;
+    };
+    if let Some(position) = pull.route.point_along(t * pull.route.length()) {
+        transform.translation = position.extend(transform.translation.z);
+    }
+    if let Projection::Orthographic(projection) = &mut *projection {
+        projection.scale = pull.from_zoom.lerp(pull.zoom, t);
+    }
+}
+
+fn end_pull_in(mut commands: Commands) {
+    commands.remove_resource::<CameraPullIn>();
+}
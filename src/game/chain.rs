@@ -0,0 +1,1696 @@
+//! Chain shooting mechanics with physics.
+
+use std::collections::HashSet;
+
+use avian2d::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        aim::PlayerAim,
+        assists::Assists,
+        chain_stress::ChainStress,
+        cheats::infinite_hooks,
+        cleanup::{GameplaySpawned, SpawnKind},
+        debris::Debris,
+        game_timer::{AddGameTimer, GameTimer},
+        gameplay_error::{GameplayError, OrReport},
+        hook_indicator::HookHead,
+        hook_types::{HookRegistry, HookType},
+        hookable::{Hooked, Reeled, Unhooked},
+        movement::MovementController,
+        mutators::{Mutator, mutator_active},
+        player::{Player, playing_hooker},
+        run_stats::RunStats,
+        sandbox::in_sandbox,
+        trigger_volume::TriggerActivator,
+    },
+    input::{ActionInput, InputAction},
+    screens::Screen,
+    theme::interaction::pointing_at_button,
+};
+
+/// Collision layers for physics objects
+#[derive(PhysicsLayer, Default)]
+pub enum Layer {
+    #[default]
+    ChainLink,
+    /// Level geometry. The far end of a chain hooks onto anything on this layer.
+    StaticObstacle,
+    /// The player, while hanging from a chain. Links can tangle around them.
+    Player,
+    /// Moving pieces that cut chains.
+    Hazard,
+    /// Loose things that chains grab and tow along.
+    DynamicProp,
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChainLink>();
+    app.register_type::<ChainRoot>();
+    app.register_type::<ChainOwner>();
+    app.register_type::<ChainFragment>();
+    app.register_type::<ChainLifetime>();
+    app.register_type::<FeedingLink>();
+    app.register_type::<ChainAttached>();
+    app.register_type::<HookLanding>();
+    app.register_type::<JointCollisionDisabled>();
+    app.add_game_timer::<ChainLifetime>();
+    app.add_game_timer::<FeedingLink>();
+    app.register_type::<ChainConfig>();
+    app.init_resource::<ChainConfig>();
+    app.init_resource::<ChainState>();
+    app.init_resource::<OwnedChains>();
+    app.init_resource::<ChainPool>();
+    app.init_resource::<JointedPairs>();
+    app.add_event::<ChainFired>();
+    app.add_event::<ChainHooked>();
+    app.add_observer(yank_owned_chain);
+    app.add_observer(drop_yanked_chain);
+
+    app.add_systems(OnEnter(Screen::Gameplay), prewarm_chain_pool);
+    app.add_systems(
+        Update,
+        (
+            (
+                handle_chain_input.run_if(not(pointing_at_button).and(playing_hooker)),
+                release_extra_chains.run_if(not(in_sandbox)),
+            )
+                .chain(),
+            (reel_newest_chain, feed_out_links).chain(),
+            (land_chain_tips, attach_chain_tips).chain(),
+            cleanup_expired_chains.run_if(
+                not(mutator_active(Mutator::EverlastingChains))
+                    .and(not(infinite_hooks))
+                    .and(not(in_sandbox)),
+            ),
+        )
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), despawn_chains);
+    app.add_systems(
+        FixedPostUpdate,
+        collect_jointed_pairs.before(PhysicsSet::StepSimulation),
+    );
+}
+
+/// Marker component for chain links
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChainLink {
+    pub link_index: usize,
+}
+
+/// Marker component for the root of a chain (connected to player)
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChainRoot;
+
+/// Tags the links of a chain in [`OwnedChains`] with who has it.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChainOwner(pub Entity);
+
+/// A link that has broken off its chain, left to fall as debris.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChainFragment;
+
+/// Component to track chain lifetime for automatic removal
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChainLifetime {
+    pub timer: Timer,
+}
+
+impl GameTimer for ChainLifetime {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+impl Default for ChainLifetime {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(5.0, TimerMode::Once),
+        }
+    }
+}
+
+/// A joint easing a newly fed-out link away from the chain's root, so the
+/// chain lengthens smoothly instead of gaining a whole link at once.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct FeedingLink {
+    pub timer: Timer,
+}
+
+impl GameTimer for FeedingLink {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+/// The far end of a chain, hooked onto `target`.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChainAttached {
+    pub target: Entity,
+}
+
+/// Sent when the player fires a chain.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct ChainFired {
+    /// Where the chain was fired from.
+    pub from: Vec2,
+}
+
+/// Sent when the far end of a chain hooks onto an obstacle or grabs a prop.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct ChainHooked {
+    /// The link that hooked on. It has a [`ChainAttached`] saying what to.
+    pub link: Entity,
+    pub point: Vec2,
+}
+
+/// Tuning for firing and reeling chains.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct ChainConfig {
+    /// Links per second taken in while reeling in.
+    pub reel_in_speed: f32,
+    /// Links per second fed out while reeling out.
+    pub reel_out_speed: f32,
+    /// Chains never have more links than this, whether fired or reeled out.
+    pub max_links: usize,
+    /// How many chains can be out at once. Past that, the oldest are
+    /// released.
+    pub max_chains: usize,
+    /// Chains with at least this many links freeze their middle while taut.
+    pub streaming_min_links: usize,
+    /// How many links at each end of a long chain are always simulated.
+    pub streaming_end_links: usize,
+    /// How far a joint stretches before it starts to wear, in links.
+    pub stress_tolerance: f32,
+    /// How much wear breaks a chain: stretch past the tolerance, in links,
+    /// times seconds.
+    pub breaking_stress: f32,
+    /// Chains with at least this many simulated links count as long.
+    pub long_chain_links: usize,
+    /// The fewest physics substeps while a long chain is out. Avian solves
+    /// each joint once per substep, so this is how many solver iterations
+    /// long chains get, whatever the physics quality.
+    pub long_chain_substeps: usize,
+    /// How a chain's root is held to the player until the chain hooks.
+    pub root_attachment: RootAttachment,
+    /// How hard a [`RootAttachment::Spring`] pulls the root toward the player,
+    /// per second squared.
+    pub root_stiffness: f32,
+    /// How much a [`RootAttachment::Spring`] slows the root down, per second.
+    pub root_damping: f32,
+    /// How much of the shooter's velocity a fired chain carries along, so
+    /// throws made on the move go further.
+    pub velocity_inheritance: f32,
+    /// The id of the [`HookType`] chains are fired as.
+    pub hook_type: String,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self {
+            reel_in_speed: 12.5,
+            reel_out_speed: 4.0,
+            max_links: 40,
+            max_chains: 8,
+            streaming_min_links: 100,
+            streaming_end_links: 20,
+            stress_tolerance: 0.1,
+            breaking_stress: 0.5,
+            long_chain_links: 60,
+            long_chain_substeps: 8,
+            root_attachment: RootAttachment::Spring,
+            root_stiffness: 300.0,
+            root_damping: 20.0,
+            velocity_inheritance: 0.8,
+            hook_type: "hook".to_string(),
+        }
+    }
+}
+
+/// How the root of a chain that hasn't hooked yet is held to the player.
+#[derive(Reflect, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RootAttachment {
+    /// Not at all, so the whole chain flies off.
+    None,
+    /// Pulled toward the player by a damped spring.
+    Spring,
+    /// Kept right at the player.
+    Rigid,
+}
+
+impl RootAttachment {
+    pub const ALL: [Self; 3] = [Self::None, Self::Spring, Self::Rigid];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&mode| mode == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+}
+
+impl ChainConfig {
+    /// How far a chain reaches at most.
+    pub fn max_length(&self) -> f32 {
+        self.max_links as f32 * LINK_SIZE
+    }
+
+    /// Whether `chain` is long enough to need [`Self::long_chain_substeps`].
+    /// Frozen links aren't simulated, so they don't count.
+    pub fn is_long(&self, chain: &Chain) -> bool {
+        let frozen = chain.frozen.as_ref().map_or(0, |frozen| frozen.links.len());
+        chain.links.len().saturating_sub(frozen) >= self.long_chain_links
+    }
+}
+
+/// Resource to track active chains
+#[derive(Resource, Default)]
+pub struct ChainState {
+    pub chains: Vec<Chain>,
+}
+
+/// Chains fired by something other than the player, along with who has them:
+/// what fired them, or the player once they've yanked one away. The
+/// player's own chain handling leaves these alone, but they are drawn like
+/// any other.
+#[derive(Resource, Default)]
+pub struct OwnedChains(pub Vec<(Entity, Chain)>);
+
+impl OwnedChains {
+    /// The chain `owner` fired, if it has one out.
+    pub fn get(&self, owner: Entity) -> Option<&Chain> {
+        self.0
+            .iter()
+            .find(|(entity, _)| *entity == owner)
+            .map(|(_, chain)| chain)
+    }
+
+    /// Take back the chain `owner` fired, if it has one out.
+    pub fn take(&mut self, owner: Entity) -> Option<Chain> {
+        let index = self.0.iter().position(|(entity, _)| *entity == owner)?;
+        Some(self.0.remove(index).1)
+    }
+
+    /// Give the chain `from` has out to `to`, breaking whatever held it to
+    /// `from`.
+    pub fn transfer(&mut self, commands: &mut Commands, from: Entity, to: Entity) {
+        let Some((owner, chain)) = self.0.iter_mut().find(|(owner, _)| *owner == from) else {
+            return;
+        };
+        for entity in chain.extra_joints() {
+            commands.entity(entity).despawn();
+        }
+        chain.rappel = None;
+        chain.hook = None;
+        for &link in &chain.links {
+            commands.entity(link).insert(ChainOwner(to));
+        }
+        *owner = to;
+    }
+}
+
+impl ChainState {
+    /// Release the oldest chain, if there is one.
+    pub fn release_oldest(&mut self, commands: &mut Commands, pool: &mut ChainPool) {
+        if !self.chains.is_empty() {
+            self.chains.remove(0).release(commands, pool);
+        }
+    }
+}
+
+/// Represents a single chain with its links
+#[derive(Debug)]
+pub struct Chain {
+    pub links: Vec<Entity>,
+    pub joints: Vec<Entity>,
+    /// The joint holding the chain rigid while rappelling, if any.
+    pub rappel: Option<Entity>,
+    /// The joint hooking the far end onto an obstacle, if it has caught on one.
+    pub hook: Option<Entity>,
+    /// The middle of a long chain, while it isn't simulated.
+    pub frozen: Option<FrozenMiddle>,
+    /// What the chain was fired as.
+    pub hook_type: HookType,
+    /// How long the chain has been decaying, in seconds, once it's started.
+    /// See [`chain_decay`](crate::game::chain_decay).
+    pub decay: Option<f32>,
+}
+
+/// The middle links of a long chain, taken out of the simulation while the
+/// chain is taut.
+#[derive(Debug)]
+pub struct FrozenMiddle {
+    pub links: Vec<Entity>,
+    /// The joints between the frozen links, and joining them to `start` and
+    /// `end`.
+    pub joints: Vec<Entity>,
+    /// The simulated links on either side.
+    pub start: Entity,
+    pub end: Entity,
+    /// Keeps `start` and `end` from pulling further apart than the frozen
+    /// links reach.
+    pub joint: Entity,
+}
+
+impl Chain {
+    /// Start the chain decaying, unless it already is.
+    pub fn start_decay(&mut self) {
+        self.decay.get_or_insert(0.0);
+    }
+
+    /// Hand a freshly fired chain over to `owner`. Its root stops following
+    /// the player, it doesn't run out, and its links are tagged with a
+    /// [`ChainOwner`]. Keep it in [`OwnedChains`] rather than [`ChainState`].
+    pub fn hand_to(&self, commands: &mut Commands, owner: Entity) {
+        for &link in &self.links {
+            commands.entity(link).insert(ChainOwner(owner));
+        }
+        if let Some(&root) = self.links.first() {
+            commands.entity(root).remove::<(ChainRoot, ChainLifetime)>();
+        }
+        if let Some(&tip) = self.links.last() {
+            commands
+                .entity(tip)
+                .remove::<(HookHead, TriggerActivator)>();
+        }
+    }
+
+    /// Every joint of the chain that isn't between two links.
+    fn extra_joints(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.rappel
+            .iter()
+            .chain(&self.hook)
+            .copied()
+            .chain(self.frozen.as_ref().map(|frozen| frozen.joint))
+    }
+
+    /// Put the chain's links and joints back in the pool.
+    pub fn release(&self, commands: &mut Commands, pool: &mut ChainPool) {
+        for &link in &self.links {
+            pool.release_link(commands, link);
+        }
+        for &joint in &self.joints {
+            pool.release_joint(commands, joint);
+        }
+        for entity in self.extra_joints() {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    /// Despawn every link and joint of the chain.
+    pub fn despawn(&self, commands: &mut Commands) {
+        for entity in self
+            .links
+            .iter()
+            .chain(&self.joints)
+            .copied()
+            .chain(self.extra_joints())
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    /// Break the chain apart, leaving its links behind as debris. Only the
+    /// joints go back in the pool.
+    pub fn snap(&self, commands: &mut Commands, pool: &mut ChainPool) {
+        for &joint in &self.joints {
+            pool.release_joint(commands, joint);
+        }
+        for entity in self.extra_joints() {
+            commands.entity(entity).despawn();
+        }
+        for &link in &self.links {
+            commands
+                .entity(link)
+                .remove::<(
+                    ChainLink,
+                    ChainRoot,
+                    ChainLifetime,
+                    ChainAttached,
+                    ChainStress,
+                    ChainOwner,
+                    // Frozen links fall with the rest
+                    RigidBodyDisabled,
+                    ColliderDisabled,
+                )>()
+                .insert((
+                    Name::new("Chain Fragment"),
+                    ChainFragment,
+                    fragment_sprite(),
+                    Debris::default(),
+                    StateScoped(Screen::Gameplay),
+                ));
+        }
+    }
+
+    /// Reel in: put the link nearest the player back in the pool, and make
+    /// the next one the root, with the old root's `lifetime`. Chains of one
+    /// link stay as they are, and `false` is returned.
+    pub fn reel_in(
+        &mut self,
+        commands: &mut Commands,
+        pool: &mut ChainPool,
+        link_query: &mut Query<&mut ChainLink>,
+        lifetime: ChainLifetime,
+    ) -> bool {
+        if self.links.len() < 2 {
+            return false;
+        }
+        pool.release_link(commands, self.links.remove(0));
+        pool.release_joint(commands, self.joints.remove(0));
+        commands.entity(self.links[0]).insert((ChainRoot, lifetime));
+        for &link in &self.links {
+            if let Ok(mut chain_link) = link_query.get_mut(link) {
+                chain_link.link_index -= 1;
+            }
+        }
+        true
+    }
+
+    /// Reel out: feed a new root out of the current one, at `root_transform`,
+    /// with the old root's `lifetime`. The joint to it grows to a full link
+    /// over `feed_time` seconds, see [`FeedingLink`].
+    pub fn reel_out(
+        &mut self,
+        commands: &mut Commands,
+        pool: &mut ChainPool,
+        link_query: &mut Query<&mut ChainLink>,
+        root_transform: &Transform,
+        lifetime: ChainLifetime,
+        feed_time: f32,
+    ) {
+        let Some(&root) = self.links.first() else {
+            return;
+        };
+        for &link in &self.links {
+            if let Ok(mut chain_link) = link_query.get_mut(link) {
+                chain_link.link_index += 1;
+            }
+        }
+        // The new link starts on top of the root and slides out as its joint
+        // grows, so nothing gets pushed apart.
+        let new_root = pool.take_link(
+            commands,
+            0,
+            root_transform.translation.truncate(),
+            root_transform.rotation,
+        );
+        commands.entity(new_root).insert((ChainRoot, lifetime));
+        commands.entity(root).remove::<(ChainRoot, ChainLifetime)>();
+        let joint = pool.take_joint(commands, new_root, root, 1);
+        commands.entity(joint).insert(FeedingLink {
+            timer: Timer::from_seconds(feed_time, TimerMode::Once),
+        });
+        self.links.insert(0, new_root);
+        self.joints.insert(0, joint);
+    }
+}
+
+/// Base link size for physics
+pub const LINK_SIZE: f32 = 20.0;
+/// Thickness of the chain links
+const LINK_THICKNESS: f32 = 5.0;
+/// How short auto-reel takes a hooked chain, in links.
+const AUTO_REEL_LINKS: usize = 4;
+
+/// Links and joints kept around between chains, so firing and reeling
+/// reuse entities instead of spawning new ones. Pooled links keep their
+/// physics body, disabled and hidden. Pooled joints are bare entities.
+#[derive(Resource, Default)]
+pub struct ChainPool {
+    links: Vec<Entity>,
+    joints: Vec<Entity>,
+}
+
+/// How many chains' worth of links the pool starts out with.
+const PREWARMED_CHAINS: usize = 4;
+
+impl ChainPool {
+    /// A link from the pool, or a new one if the pool is empty.
+    fn take_link(
+        &mut self,
+        commands: &mut Commands,
+        index: usize,
+        position: Vec2,
+        rotation: Quat,
+    ) -> Entity {
+        match self.links.pop() {
+            Some(link) => commands
+                .entity(link)
+                .remove::<(RigidBodyDisabled, ColliderDisabled)>()
+                .insert(chain_link(index, position, rotation))
+                .id(),
+            None => commands
+                .spawn((link_body(), chain_link(index, position, rotation)))
+                .id(),
+        }
+    }
+
+    /// A joint from the pool, or a new one if the pool is empty.
+    fn take_joint(
+        &mut self,
+        commands: &mut Commands,
+        previous: Entity,
+        current: Entity,
+        index: usize,
+    ) -> Entity {
+        let entity = self.take_joint_entity(commands);
+        commands
+            .entity(entity)
+            .insert(link_joint(previous, current, index));
+        entity
+    }
+
+    /// An entity for a joint, from the pool or newly spawned, still without
+    /// the joint itself.
+    fn take_joint_entity(&mut self, commands: &mut Commands) -> Entity {
+        self.joints
+            .pop()
+            .unwrap_or_else(|| commands.spawn(pooled_joint()).id())
+    }
+
+    fn release_link(&mut self, commands: &mut Commands, link: Entity) {
+        commands
+            .entity(link)
+            .remove::<(
+                ChainLink,
+                ChainRoot,
+                ChainLifetime,
+                ChainAttached,
+                ChainStress,
+                ChainOwner,
+                HookHead,
+                TriggerActivator,
+            )>()
+            .insert(pooled_link());
+        self.links.push(link);
+    }
+
+    fn release_joint(&mut self, commands: &mut Commands, joint: Entity) {
+        commands
+            .entity(joint)
+            .remove::<(
+                RevoluteJoint,
+                FeedingLink,
+                JointCollisionDisabled,
+                JointDisabled,
+            )>()
+            .insert(pooled_joint());
+        self.joints.push(joint);
+    }
+
+    /// Fire a chain of `hook_type` from `from` toward `target`, as long as
+    /// the distance between them or [`HookType::max_links`] allows. The chain
+    /// carries [`ChainConfig::velocity_inheritance`] of the shooter's
+    /// `velocity`.
+    pub fn fire_chain(
+        &mut self,
+        commands: &mut Commands,
+        config: &ChainConfig,
+        hook_type: &HookType,
+        from: Vec2,
+        target: Vec2,
+        velocity: Vec2,
+    ) -> Chain {
+        let chain_direction = (target - from).normalize();
+        let chain_length = (target - from).length();
+        let actual_link_spacing = LINK_SIZE; // Actual distance between link centers
+        let num_links = ((chain_length / actual_link_spacing).max(1.0) as usize)
+            .min(hook_type.max_links(config));
+        // The whole chain sets off together, rather than being dragged along
+        // by its first link.
+        let link_velocity =
+            chain_direction * hook_type.throw_speed + velocity * config.velocity_inheritance;
+
+        let mut previous_entity = None;
+        let mut links = Vec::new();
+        let mut joints = Vec::new();
+        // Every joint goes in with one command, which matters for long chains.
+        let mut joint_batch = Vec::new();
+
+        for i in 0..num_links {
+            // Spaced as far apart as the joints hold them, so the chain doesn't
+            // spring apart and tumble the moment it's fired.
+            let link_pos = from + chain_direction * actual_link_spacing * i as f32;
+
+            // Calculate rotation to align with chain direction
+            // Capsules are Y-axis oriented by default, sprites are X-axis oriented
+            // We need to rotate the entire entity so the capsule aligns with the chain direction
+            let chain_angle = chain_direction.y.atan2(chain_direction.x);
+            let entity_rotation = Quat::from_rotation_z(chain_angle - std::f32::consts::PI / 2.0);
+
+            let current_entity = self.take_link(commands, i, link_pos, entity_rotation);
+            commands
+                .entity(current_entity)
+                .insert(LinearVelocity(link_velocity));
+
+            // Add root marker and lifetime to first link only
+            if i == 0 {
+                commands.entity(current_entity).insert((
+                    ChainRoot,
+                    ChainLifetime {
+                        timer: Timer::from_seconds(hook_type.lifetime, TimerMode::Once),
+                    },
+                ));
+            }
+
+            links.push(current_entity);
+
+            // Create joint to previous link
+            if let Some(prev_entity) = previous_entity {
+                let joint_entity = self.take_joint_entity(commands);
+                joint_batch.push((joint_entity, link_joint(prev_entity, current_entity, i)));
+                joints.push(joint_entity);
+            }
+
+            previous_entity = Some(current_entity);
+        }
+        commands.insert_batch(joint_batch);
+
+        // Show how far the far end gets, and let it set off trigger volumes
+        if let Some(&tip) = links.last() {
+            commands
+                .entity(tip)
+                .insert((HookHead::default(), TriggerActivator));
+        }
+
+        Chain {
+            links,
+            joints,
+            rappel: None,
+            hook: None,
+            frozen: None,
+            decay: None,
+            hook_type: hook_type.clone(),
+        }
+    }
+
+    /// Despawn everything in the pool.
+    fn clear(&mut self, commands: &mut Commands) {
+        for entity in self.links.drain(..).chain(self.joints.drain(..)) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn prewarm_chain_pool(
+    mut commands: Commands,
+    config: Res<ChainConfig>,
+    mut pool: ResMut<ChainPool>,
+) {
+    let count = config.max_links * PREWARMED_CHAINS;
+    for _ in pool.links.len()..count {
+        let link = commands.spawn((link_body(), pooled_link())).id();
+        pool.links.push(link);
+    }
+    for _ in pool.joints.len()..count {
+        let joint = commands.spawn(pooled_joint()).id();
+        pool.joints.push(joint);
+    }
+}
+
+/// A link waiting in the pool.
+fn pooled_link() -> impl Bundle {
+    (
+        Name::new("Pooled Chain Link"),
+        RigidBodyDisabled,
+        ColliderDisabled,
+        Visibility::Hidden,
+    )
+}
+
+/// A joint waiting in the pool. Spawned joints start out as one, and keep
+/// their scope through reuse.
+fn pooled_joint() -> impl Bundle {
+    (
+        Name::new("Pooled Chain Joint"),
+        GameplaySpawned(SpawnKind::Joint),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// A single link of a chain, the `index`th counting from the player.
+fn chain_link(index: usize, position: Vec2, rotation: Quat) -> impl Bundle {
+    (
+        Name::new(format!("Chain Link {}", index)),
+        ChainLink { link_index: index },
+        ChainStress::default(),
+        // Drawn as part of the rope by `chain_render`, not on its own
+        Transform::from_translation(position.extend(0.0)).with_rotation(rotation),
+        Visibility::default(),
+        // Pooled links may still be moving from their last chain
+        LinearVelocity::ZERO,
+        AngularVelocity::ZERO,
+    )
+}
+
+/// The physics body of a link, which pooled links keep, along with their
+/// scope.
+fn link_body() -> impl Bundle {
+    (
+        GameplaySpawned(SpawnKind::ChainLink),
+        StateScoped(Screen::Gameplay),
+        // Physics components
+        RigidBody::Dynamic,
+        Collider::capsule(LINK_THICKNESS / 2.0, LINK_SIZE * 0.8), // Length, radius - smaller radius for tighter contact
+        Mass(2.0),             // Increased mass for better stability
+        LinearDamping(0.2),    // More air resistance for stability
+        AngularDamping(0.3),   // More rotational damping
+        SweptCcd::default(),   // Continuous Collision Detection to prevent tunneling
+        Restitution::new(0.1), // Less bounciness for smoother collisions
+        Friction::new(0.7),    // Higher friction for better interaction with obstacles
+        // Lets `ChainCollisionHooks` skip contacts with jointed neighbors
+        ActiveCollisionHooks::FILTER_PAIRS,
+        // Collision groups to ensure proper detection (including self-collision,
+        // though not between neighbors, see `link_joint`).
+        // Links far enough from the player collide with them too, see `tangle`.
+        CollisionLayers::new(
+            [Layer::ChainLink],
+            [
+                Layer::ChainLink,
+                Layer::StaticObstacle,
+                Layer::Hazard,
+                Layer::DynamicProp,
+            ],
+        ),
+    )
+}
+
+/// How a link looks once it has broken off the rope.
+fn fragment_sprite() -> Sprite {
+    Sprite {
+        color: Color::WHITE,
+        custom_size: Some(Vec2::new(3.0, LINK_SIZE * 0.9)), // Height is the long dimension, like the capsule
+        ..default()
+    }
+}
+
+/// The joint between link `index - 1` and link `index`.
+fn link_joint(
+    previous: Entity,
+    current: Entity,
+    index: usize,
+) -> (Name, RevoluteJoint, JointCollisionDisabled) {
+    let capsule_half_length = LINK_SIZE * 0.5; // Half-length of each capsule
+    (
+        Name::new(format!("Chain Joint {}-{}", index - 1, index)),
+        RevoluteJoint::new(previous, current)
+            .with_local_anchor_1(Vec2::new(0.0, capsule_half_length)) // Top end of previous link (capsule is now Y-oriented)
+            .with_local_anchor_2(Vec2::new(0.0, -capsule_half_length)) // Bottom end of current link
+            .with_compliance(0.00001) // Soft constraint for natural movement
+            .with_angular_velocity_damping(0.1), // Add some rotational damping
+        // Neighbors overlap at the joint, so their contacts would only fight it
+        JointCollisionDisabled,
+    )
+}
+
+/// Keeps the two bodies of a joint from colliding with each other. Only
+/// bodies with [`ActiveCollisionHooks::FILTER_PAIRS`] are checked.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct JointCollisionDisabled;
+
+/// The pairs of bodies joined by joints with [`JointCollisionDisabled`],
+/// smallest entity first.
+#[derive(Resource, Default)]
+struct JointedPairs(HashSet<(Entity, Entity)>);
+
+fn jointed_pair(a: Entity, b: Entity) -> (Entity, Entity) {
+    (a.min(b), a.max(b))
+}
+
+fn collect_jointed_pairs(
+    mut jointed: ResMut<JointedPairs>,
+    joint_query: Query<&RevoluteJoint, With<JointCollisionDisabled>>,
+) {
+    jointed.0.clear();
+    jointed.0.extend(
+        joint_query
+            .iter()
+            .map(|joint| jointed_pair(joint.entity1, joint.entity2)),
+    );
+}
+
+/// Collision hooks that skip contacts between jointed bodies.
+#[derive(SystemParam)]
+pub struct ChainCollisionHooks<'w> {
+    jointed: Res<'w, JointedPairs>,
+}
+
+impl CollisionHooks for ChainCollisionHooks<'_> {
+    fn filter_pairs(&self, collider1: Entity, collider2: Entity, _commands: &mut Commands) -> bool {
+        !self.jointed.0.contains(&jointed_pair(collider1, collider2))
+    }
+}
+
+/// System to handle chain input (fire to add, release to remove oldest)
+fn handle_chain_input(
+    mut commands: Commands,
+    input: ActionInput,
+    aim: Res<PlayerAim>,
+    config: Res<ChainConfig>,
+    hooks: Res<HookRegistry>,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    mut stats: ResMut<RunStats>,
+    mut fired: EventWriter<ChainFired>,
+    spatial_query: SpatialQuery,
+    player_query: Query<(&Transform, &MovementController, Option<&LinearVelocity>), With<Player>>,
+    target_query: Query<&GlobalTransform>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    // Fire: Add new chain toward wherever the player is aiming
+    let firing = input.just_pressed(InputAction::FireChain).then(|| {
+        player_query
+            .single()
+            .or_report(&mut errors, "handle_chain_input", "player")
+    });
+    if let (Some((player_transform, controller, swing)), Some(cursor_world_pos)) =
+        (firing.flatten(), aim.target)
+    {
+        let from = player_transform.translation.truncate();
+        // Swinging players move by physics, and walking ones by intent.
+        let velocity = swing.map_or(controller.max_speed * controller.intent, |velocity| {
+            velocity.0
+        });
+        let hook_type = hooks.active(&config);
+        let landing = predict_landing(&spatial_query, &config, hook_type, from, cursor_world_pos);
+        let aim_point = landing.map_or(cursor_world_pos, |(_, point)| point);
+        let chain = pool.fire_chain(&mut commands, &config, hook_type, from, aim_point, velocity);
+        // Misses fly free, and hook whatever they happen to touch.
+        let landing = landing
+            .and_then(|(target, point)| Some((target, point, target_query.get(target).ok()?)));
+        if let (Some((target, point, target_transform)), Some(&tip)) = (landing, chain.links.last())
+        {
+            commands.entity(tip).insert(HookLanding {
+                target,
+                local_point: target_transform
+                    .affine()
+                    .inverse()
+                    .transform_point3(point.extend(0.0))
+                    .truncate(),
+                duration: from.distance(point) / LANDING_SPEED,
+                elapsed: 0.0,
+            });
+        }
+        stats.record_hook(LINK_SIZE * chain.links.len().saturating_sub(1) as f32);
+        fired.write(ChainFired { from });
+        chain_state.chains.push(chain);
+    }
+
+    // Release - remove oldest chain
+    if input.just_pressed(InputAction::ReleaseChain) {
+        chain_state.release_oldest(&mut commands, &mut pool);
+    }
+}
+
+/// Start chains decaying while there are too many that aren't already.
+/// Chains not hooked onto anything go first, nearest the player first, then
+/// the oldest. The newest chain is the one just fired, so it's left be.
+fn release_extra_chains(
+    config: Res<ChainConfig>,
+    mut chain_state: ResMut<ChainState>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    link_query: Query<&GlobalTransform, With<ChainLink>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) =
+        player_query
+            .single()
+            .or_report(&mut errors, "release_extra_chains", "player")
+    else {
+        return;
+    };
+    let player = player.translation().truncate();
+    let distance = |chain: &Chain| {
+        chain
+            .links
+            .last()
+            .and_then(|&tip| link_query.get(tip).ok())
+            .map_or(f32::MAX, |tip| {
+                tip.translation().truncate().distance(player)
+            })
+    };
+    let lasting = chain_state
+        .chains
+        .iter()
+        .filter(|chain| chain.decay.is_none())
+        .count();
+    let extra = lasting.saturating_sub(config.max_chains.max(1));
+    if extra == 0 {
+        return;
+    }
+    let newest = chain_state.chains.len() - 1;
+    let mut candidates: Vec<(usize, bool, f32)> = chain_state.chains[..newest]
+        .iter()
+        .enumerate()
+        .filter(|(_, chain)| chain.decay.is_none())
+        .map(|(i, chain)| (i, chain.hook.is_some(), distance(chain)))
+        .collect();
+    // Sorting is stable, so hooked chains stay oldest first.
+    candidates.sort_by(|a, b| match (a.1, b.1) {
+        (false, false) => a.2.total_cmp(&b.2),
+        (hooked_a, hooked_b) => hooked_a.cmp(&hooked_b),
+    });
+    for &(i, _, _) in candidates.iter().take(extra) {
+        chain_state.chains[i].start_decay();
+    }
+}
+
+/// Shorten or lengthen the newest chain one link at a time at the player's end.
+fn reel_newest_chain(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: ActionInput,
+    config: Res<ChainConfig>,
+    assists: Res<Assists>,
+    mut since_last_link: Local<f32>,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    mut link_query: Query<&mut ChainLink>,
+    transform_query: Query<&Transform>,
+    lifetime_query: Query<&ChainLifetime>,
+    feeding_query: Query<(), With<FeedingLink>>,
+    attached_query: Query<&ChainAttached>,
+) {
+    let mut reel = input.axis(InputAction::ReelOut, InputAction::ReelIn);
+    let auto_reel = assists.auto_reel
+        && chain_state
+            .chains
+            .last()
+            .is_some_and(|chain| chain.hook.is_some() && chain.links.len() > AUTO_REEL_LINKS);
+    if reel == 0.0 && auto_reel {
+        reel = -1.0;
+    }
+    let reel_interval = 1.0 / config.reel_in_speed;
+    if reel == 0.0 {
+        // React to the next press (or scroll) right away.
+        *since_last_link = reel_interval;
+        return;
+    }
+
+    // While rappelling, the rappel sets the length instead.
+    let Some(chain) = chain_state
+        .chains
+        .last_mut()
+        .filter(|chain| chain.rappel.is_none())
+    else {
+        return;
+    };
+    let Some(&root) = chain.links.first() else {
+        return;
+    };
+    // The new root keeps the chain's remaining lifetime.
+    let lifetime = lifetime_query.get(root).map_or_else(
+        |_| ChainLifetime::default(),
+        |lifetime| ChainLifetime {
+            timer: lifetime.timer.clone(),
+        },
+    );
+
+    if reel < 0.0 {
+        *since_last_link += time.delta_secs();
+        if *since_last_link < reel_interval {
+            return;
+        }
+        *since_last_link = 0.0;
+
+        if !chain.reel_in(&mut commands, &mut pool, &mut link_query, lifetime) {
+            return;
+        }
+    } else {
+        // Feed links out one at a time.
+        let still_feeding = chain
+            .joints
+            .first()
+            .is_some_and(|&joint| feeding_query.contains(joint));
+        if still_feeding || chain.links.len() >= chain.hook_type.max_links(&config) {
+            return;
+        }
+        let Ok(root_transform) = transform_query.get(root) else {
+            return;
+        };
+        chain.reel_out(
+            &mut commands,
+            &mut pool,
+            &mut link_query,
+            root_transform,
+            lifetime,
+            1.0 / config.reel_out_speed,
+        );
+    }
+
+    let Some(&tip) = chain.links.last() else {
+        return;
+    };
+    if let Ok(attached) = attached_query.get(tip) {
+        commands.trigger_targets(
+            Reeled {
+                link: tip,
+                links: chain.links.len(),
+                reeled_in: reel < 0.0,
+            },
+            attached.target,
+        );
+    }
+}
+
+/// Grow the joints of newly fed-out links from nothing to a full link.
+fn feed_out_links(
+    mut commands: Commands,
+    mut joint_query: Query<(Entity, &mut RevoluteJoint, &FeedingLink)>,
+) {
+    let capsule_half_length = LINK_SIZE * 0.5;
+    for (entity, mut joint, feeding) in &mut joint_query {
+        let offset = capsule_half_length * feeding.timer.fraction();
+        joint.local_anchor1 = Vec2::new(0.0, offset);
+        joint.local_anchor2 = Vec2::new(0.0, -offset);
+        if feeding.timer.finished() {
+            commands.entity(entity).remove::<FeedingLink>();
+        }
+    }
+}
+
+/// How much a tow joint gives per unit of the towed prop's mass. Heavier
+/// props hang back on a springier joint instead of snapping to the chain.
+const TOW_COMPLIANCE_PER_MASS: f32 = 0.0005;
+
+/// How fast the far end of a chain flies to a predicted landing, in pixels
+/// per second of the whole throw.
+const LANDING_SPEED: f32 = 2000.0;
+
+/// The far end of a chain flying to the point it was predicted to hook, found
+/// by a raycast when the chain was fired. It hooks there when it arrives,
+/// rather than on whatever it touches first.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HookLanding {
+    target: Entity,
+    /// The point to hook, relative to the target, so moving props are still
+    /// hooked where they were aimed at.
+    local_point: Vec2,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// What a chain of `hook_type` fired from `from` toward `target` will hook,
+/// and where: the first thing it hooks in the way within the chain's reach.
+/// Anything else in the way is looked past.
+fn predict_landing(
+    spatial_query: &SpatialQuery,
+    config: &ChainConfig,
+    hook_type: &HookType,
+    from: Vec2,
+    target: Vec2,
+) -> Option<(Entity, Vec2)> {
+    let direction = Dir2::new(target - from).ok()?;
+    let mut mask = LayerMask::NONE;
+    if hook_type.hooks_obstacles {
+        mask |= Layer::StaticObstacle;
+    }
+    if hook_type.grabs_props {
+        mask |= Layer::DynamicProp;
+    }
+    let hit = spatial_query.cast_ray(
+        from,
+        direction,
+        hook_type.max_links(config) as f32 * LINK_SIZE,
+        true,
+        &SpatialQueryFilter::from_mask(mask),
+    )?;
+    Some((hit.entity, from + *direction * hit.distance))
+}
+
+/// How loosely a chain of `hook_type` holds on to what it hooks: not at all
+/// for obstacles, and more the heavier a prop is. `None` for things it
+/// doesn't hook.
+fn hook_compliance(
+    hook_type: &HookType,
+    layers: &CollisionLayers,
+    mass: Option<&Mass>,
+) -> Option<f32> {
+    if layers.memberships.has_all(Layer::StaticObstacle) {
+        hook_type.hooks_obstacles.then_some(0.0)
+    } else if layers.memberships.has_all(Layer::DynamicProp) && hook_type.grabs_props {
+        Some(mass.map_or(0.0, |mass| mass.0 * TOW_COMPLIANCE_PER_MASS))
+    } else {
+        None
+    }
+}
+
+/// Join the far end of `chain` to `target` at `point`.
+fn hook_tip(
+    commands: &mut Commands,
+    chain: &mut Chain,
+    hooked: &mut EventWriter<ChainHooked>,
+    (tip, tip_transform): (Entity, &GlobalTransform),
+    (target, target_transform): (Entity, &GlobalTransform),
+    compliance: f32,
+    point: Vec2,
+) {
+    let local_point = |transform: &GlobalTransform| {
+        transform
+            .affine()
+            .inverse()
+            .transform_point3(point.extend(0.0))
+            .truncate()
+    };
+    let joint = commands
+        .spawn((
+            Name::new("Chain Hook"),
+            RevoluteJoint::new(target, tip)
+                .with_local_anchor_1(local_point(target_transform))
+                .with_local_anchor_2(local_point(tip_transform))
+                .with_compliance(compliance),
+            GameplaySpawned(SpawnKind::Joint),
+            StateScoped(Screen::Gameplay),
+        ))
+        .id();
+    commands.entity(tip).insert(ChainAttached { target });
+    chain.hook = Some(joint);
+    hooked.write(ChainHooked { link: tip, point });
+    commands.trigger_targets(Hooked { link: tip, point }, target);
+}
+
+/// Fly the far ends of chains to their predicted landings on a ballistic arc,
+/// steering so they arrive right on time whatever the rest of the chain does,
+/// and hook them there.
+fn land_chain_tips(
+    mut commands: Commands,
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    mut chain_state: ResMut<ChainState>,
+    mut hooked: EventWriter<ChainHooked>,
+    mut tip_query: Query<(&mut HookLanding, &mut Position, &mut LinearVelocity)>,
+    target_query: Query<(&GlobalTransform, &CollisionLayers, Option<&Mass>), Without<ChainLink>>,
+) {
+    for chain in &mut chain_state.chains {
+        let Some(&tip) = chain.links.last() else {
+            continue;
+        };
+        let Ok((mut landing, mut position, mut velocity)) = tip_query.get_mut(tip) else {
+            continue;
+        };
+        // Whatever it was going to hook may be gone, or was never hookable.
+        let Some((target_transform, compliance)) =
+            target_query
+                .get(landing.target)
+                .ok()
+                .and_then(|(transform, layers, mass)| {
+                    Some((transform, hook_compliance(&chain.hook_type, layers, mass)?))
+                })
+        else {
+            commands.entity(tip).remove::<HookLanding>();
+            continue;
+        };
+        let point = target_transform
+            .transform_point(landing.local_point.extend(0.0))
+            .truncate();
+
+        landing.elapsed += time.delta_secs();
+        let remaining = landing.duration - landing.elapsed;
+        if remaining > 0.0 {
+            // The velocity that reaches the point in the time left, with
+            // gravity pulling on the way.
+            velocity.0 = (point - position.0) / remaining - 0.5 * gravity.0 * remaining;
+            continue;
+        }
+
+        position.0 = point;
+        velocity.0 = Vec2::ZERO;
+        commands.entity(tip).remove::<HookLanding>();
+        hook_tip(
+            &mut commands,
+            chain,
+            &mut hooked,
+            (tip, &GlobalTransform::from_translation(point.extend(0.0))),
+            (landing.target, target_transform),
+            compliance,
+            point,
+        );
+    }
+}
+
+/// Hook the far end of each chain onto the first obstacle or prop it touches,
+/// with a joint it can swing around. Props are towed along from the point
+/// they were hit, and so are chains fired by others, see
+/// [`yank_owned_chain`]. Chains flying to a predicted landing only hook there.
+fn attach_chain_tips(
+    mut commands: Commands,
+    collisions: Collisions,
+    mut chain_state: ResMut<ChainState>,
+    mut hooked: EventWriter<ChainHooked>,
+    tip_query: Query<
+        &GlobalTransform,
+        (
+            With<ChainLink>,
+            Without<ChainAttached>,
+            Without<HookLanding>,
+        ),
+    >,
+    obstacle_query: Query<
+        (
+            &GlobalTransform,
+            &CollisionLayers,
+            Option<&Mass>,
+            Has<ChainOwner>,
+        ),
+        Or<(Without<ChainLink>, With<ChainOwner>)>,
+    >,
+) {
+    for chain in &mut chain_state.chains {
+        let Some(&tip) = chain.links.last() else {
+            continue;
+        };
+        let Ok(tip_transform) = tip_query.get(tip) else {
+            continue;
+        };
+        let hit = collisions.collisions_with(tip).find_map(|pair| {
+            let target = if pair.collider1 == tip {
+                pair.collider2
+            } else {
+                pair.collider1
+            };
+            let (target_transform, layers, mass, owned) = obstacle_query.get(target).ok()?;
+            let compliance = if owned {
+                // Grabbed like a prop
+                chain
+                    .hook_type
+                    .grabs_props
+                    .then(|| mass.map_or(0.0, |mass| mass.0 * TOW_COMPLIANCE_PER_MASS))?
+            } else {
+                hook_compliance(&chain.hook_type, layers, mass)?
+            };
+            // Contact points are relative to each collider, so take the tip's.
+            let contact = pair.manifolds.first()?.points.first()?;
+            let local = if pair.collider1 == tip {
+                contact.local_point1
+            } else {
+                contact.local_point2
+            };
+            let point = tip_transform.transform_point(local.extend(0.0)).truncate();
+            Some((target, target_transform, compliance, point))
+        });
+        let Some((target, target_transform, compliance, point)) = hit else {
+            continue;
+        };
+        hook_tip(
+            &mut commands,
+            chain,
+            &mut hooked,
+            (tip, tip_transform),
+            (target, target_transform),
+            compliance,
+            point,
+        );
+    }
+}
+
+/// A chain someone else fired that the player hooks is yanked off them, and
+/// is the player's until they let go of it.
+fn yank_owned_chain(
+    trigger: Trigger<Hooked>,
+    mut commands: Commands,
+    mut owned_chains: ResMut<OwnedChains>,
+    owner_query: Query<&ChainOwner>,
+    player_query: Query<Entity, With<Player>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Ok(&ChainOwner(owner)) = owner_query.get(trigger.target()) else {
+        return;
+    };
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "yank_owned_chain", "player")
+    else {
+        return;
+    };
+    if owner != player {
+        owned_chains.transfer(&mut commands, owner, player);
+    }
+}
+
+/// Letting go of a yanked chain breaks it apart.
+fn drop_yanked_chain(
+    trigger: Trigger<Unhooked>,
+    mut commands: Commands,
+    mut owned_chains: ResMut<OwnedChains>,
+    mut pool: ResMut<ChainPool>,
+    player_query: Query<Entity, With<Player>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "drop_yanked_chain", "player")
+    else {
+        return;
+    };
+    let link = trigger.target();
+    let Some(index) = owned_chains
+        .0
+        .iter()
+        .position(|(owner, chain)| *owner == player && chain.links.contains(&link))
+    else {
+        return;
+    };
+    owned_chains
+        .0
+        .remove(index)
+        .1
+        .snap(&mut commands, &mut pool);
+}
+
+/// System to start chains decaying once their lifetime is up
+fn cleanup_expired_chains(
+    mut chain_state: ResMut<ChainState>,
+    lifetime_query: Query<(Entity, &ChainLifetime), With<ChainRoot>>,
+) {
+    for (entity, lifetime) in &lifetime_query {
+        if lifetime.timer.finished() {
+            // Find the chain containing this root entity
+            if let Some(chain) = chain_state
+                .chains
+                .iter_mut()
+                .find(|chain| chain.links.first() == Some(&entity))
+            {
+                chain.start_decay();
+            }
+        }
+    }
+}
+
+/// Chains and the pool aren't scoped to the screen, so they're despawned with
+/// the level here.
+fn despawn_chains(
+    mut commands: Commands,
+    mut chain_state: ResMut<ChainState>,
+    mut owned_chains: ResMut<OwnedChains>,
+    mut pool: ResMut<ChainPool>,
+) {
+    for chain in chain_state.chains.drain(..) {
+        chain.despawn(&mut commands);
+    }
+    for (_, chain) in owned_chains.0.drain(..) {
+        chain.despawn(&mut commands);
+    }
+    pool.clear(&mut commands);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use bevy::{ecs::system::SystemState, time::TimeUpdateStrategy};
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::game::chain_streaming::{freeze, thaw};
+
+    /// Something done to the chains. Which chain is picked modulo how many
+    /// there are.
+    #[derive(Clone, Debug)]
+    enum Step {
+        Fire(usize),
+        ReelIn(usize),
+        ReelOut(usize),
+        Freeze {
+            chain: usize,
+            first: usize,
+            len: usize,
+        },
+        Thaw(usize),
+        Release(usize),
+        Snap(usize),
+    }
+
+    fn step() -> impl Strategy<Value = Step> {
+        prop_oneof![
+            (1..=40usize).prop_map(Step::Fire),
+            any::<usize>().prop_map(Step::ReelIn),
+            any::<usize>().prop_map(Step::ReelOut),
+            any::<(usize, usize, usize)>().prop_map(|(chain, first, len)| Step::Freeze {
+                chain,
+                first,
+                len
+            }),
+            any::<usize>().prop_map(Step::Thaw),
+            any::<usize>().prop_map(Step::Release),
+            any::<usize>().prop_map(Step::Snap),
+        ]
+    }
+
+    type ChainParams = (
+        Commands<'static, 'static>,
+        ResMut<'static, ChainState>,
+        ResMut<'static, ChainPool>,
+        Query<'static, 'static, &'static mut ChainLink>,
+    );
+
+    fn pick(chains: &[Chain], i: usize) -> Option<usize> {
+        (!chains.is_empty()).then(|| i % chains.len())
+    }
+
+    fn run(world: &mut World, state: &mut SystemState<ChainParams>, step: &Step) {
+        let config = ChainConfig::default();
+        let (mut commands, mut chain_state, mut pool, mut link_query) = state.get_mut(world);
+        let chains = &mut chain_state.chains;
+        match *step {
+            Step::Fire(links) => {
+                let target = Vec2::X * links as f32 * LINK_SIZE;
+                let chain = pool.fire_chain(
+                    &mut commands,
+                    &config,
+                    &HookType::default(),
+                    Vec2::ZERO,
+                    target,
+                    Vec2::ZERO,
+                );
+                chains.push(chain);
+            }
+            Step::ReelIn(i) => {
+                if let Some(i) = pick(chains, i) {
+                    chains[i].reel_in(
+                        &mut commands,
+                        &mut pool,
+                        &mut link_query,
+                        ChainLifetime::default(),
+                    );
+                }
+            }
+            Step::ReelOut(i) => {
+                if let Some(i) =
+                    pick(chains, i).filter(|&i| chains[i].links.len() < config.max_links)
+                {
+                    chains[i].reel_out(
+                        &mut commands,
+                        &mut pool,
+                        &mut link_query,
+                        &Transform::default(),
+                        ChainLifetime::default(),
+                        1.0 / config.reel_out_speed,
+                    );
+                }
+            }
+            Step::Freeze { chain, first, len } => {
+                let Some(i) = pick(chains, chain) else {
+                    return;
+                };
+                let chain = &mut chains[i];
+                let count = chain.links.len();
+                if chain.frozen.is_none() && count >= 3 {
+                    let first = 1 + first % (count - 2);
+                    let last = first + len % (count - 1 - first);
+                    freeze(&mut commands, chain, first, last);
+                }
+            }
+            Step::Thaw(i) => {
+                if let Some(i) = pick(chains, i) {
+                    thaw(&mut commands, &mut chains[i]);
+                }
+            }
+            Step::Release(i) => {
+                if let Some(i) = pick(chains, i) {
+                    chains.remove(i).release(&mut commands, &mut pool);
+                }
+            }
+            Step::Snap(i) => {
+                if let Some(i) = pick(chains, i) {
+                    chains.remove(i).snap(&mut commands, &mut pool);
+                }
+            }
+        }
+        // Reeling in can take the start of a frozen middle away, which
+        // `stream_long_chains` thaws on the next frame.
+        for chain in chains.iter_mut() {
+            if chain
+                .frozen
+                .as_ref()
+                .is_some_and(|frozen| !chain.links.contains(&frozen.start))
+            {
+                thaw(&mut commands, chain);
+            }
+        }
+        state.apply(world);
+    }
+
+    /// Check that the chains and the pool match the world.
+    fn check(world: &mut World) -> Result<(), TestCaseError> {
+        let bodies = world
+            .query_filtered::<(), With<RigidBody>>()
+            .iter(world)
+            .count();
+        let fragments = world
+            .query_filtered::<(), With<ChainFragment>>()
+            .iter(world)
+            .count();
+        let chain_links: Vec<Entity> = world
+            .query_filtered::<Entity, With<ChainLink>>()
+            .iter(world)
+            .collect();
+        let chains = &world.resource::<ChainState>().chains;
+        let pool = world.resource::<ChainPool>();
+
+        let mut seen = HashSet::new();
+        for chain in chains {
+            prop_assert_eq!(chain.joints.len() + 1, chain.links.len());
+            let frozen_links = chain
+                .frozen
+                .as_ref()
+                .map_or(&[][..], |frozen| &frozen.links);
+            for (i, &link) in chain.links.iter().enumerate() {
+                prop_assert!(seen.insert(link), "link {} is in two places", link);
+                let index = world.get::<ChainLink>(link).map(|link| link.link_index);
+                prop_assert_eq!(index, Some(i));
+                let disabled = world.get::<RigidBodyDisabled>(link).is_some();
+                prop_assert_eq!(disabled, frozen_links.contains(&link));
+            }
+            for (i, &joint) in chain.joints.iter().enumerate() {
+                let joined = world
+                    .get::<RevoluteJoint>(joint)
+                    .map(|joint| [joint.entity1, joint.entity2]);
+                prop_assert_eq!(joined, Some([chain.links[i], chain.links[i + 1]]));
+                let frozen = chain
+                    .frozen
+                    .as_ref()
+                    .is_some_and(|frozen| frozen.joints.contains(&joint));
+                prop_assert_eq!(world.get::<JointDisabled>(joint).is_some(), frozen);
+            }
+            if let Some(frozen) = &chain.frozen {
+                let first = chain.links.iter().position(|&link| link == frozen.links[0]);
+                prop_assert!(first.is_some_and(|first| first > 0));
+                let (first, last) = (first.unwrap(), first.unwrap() + frozen.links.len() - 1);
+                prop_assert!(last + 1 < chain.links.len());
+                prop_assert_eq!(&chain.links[first..=last], &frozen.links[..]);
+                prop_assert_eq!(&chain.joints[first - 1..=last], &frozen.joints[..]);
+                prop_assert_eq!(chain.links[first - 1], frozen.start);
+                prop_assert_eq!(chain.links[last + 1], frozen.end);
+            }
+        }
+        for &link in &pool.links {
+            prop_assert!(seen.insert(link), "pooled link {} is also in use", link);
+            prop_assert!(world.get::<ChainLink>(link).is_none());
+        }
+        for &joint in &pool.joints {
+            prop_assert!(world.get::<RevoluteJoint>(joint).is_none());
+        }
+        for link in chain_links {
+            prop_assert!(seen.contains(&link), "link {} is in no chain", link);
+        }
+        // Links only ever move between chains and the pool, or break off.
+        prop_assert_eq!(bodies, seen.len() + fragments);
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn chains_keep_their_links_in_order(steps in prop::collection::vec(step(), 1..60)) {
+            let mut world = World::new();
+            world.init_resource::<ChainState>();
+            world.init_resource::<ChainPool>();
+            let mut state = SystemState::<ChainParams>::new(&mut world);
+            for step in &steps {
+                run(&mut world, &mut state, step);
+                check(&mut world)?;
+            }
+        }
+    }
+
+    /// How far the tip of a chain of `links` fired straight to the right
+    /// gets in a second, with the physics ticking `hz` times a second.
+    fn throw_distance(hz: f64, links: usize) -> f32 {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+            PhysicsPlugins::default()
+                .with_length_unit(100.0)
+                .build()
+                .disable::<ColliderHierarchyPlugin>(),
+        ))
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<ChainPool>()
+        .insert_resource(Gravity(Vec2::new(0.0, -980.0)))
+        .insert_resource(Time::<Fixed>::from_hz(hz))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            10,
+        )));
+        app.finish();
+        app.cleanup();
+
+        let world = app.world_mut();
+        let mut state = SystemState::<(Commands, ResMut<ChainPool>)>::new(world);
+        let (mut commands, mut pool) = state.get_mut(world);
+        let chain = pool.fire_chain(
+            &mut commands,
+            &ChainConfig::default(),
+            &HookType::default(),
+            Vec2::ZERO,
+            Vec2::X * links as f32 * LINK_SIZE,
+            Vec2::ZERO,
+        );
+        state.apply(world);
+        let tip = *chain.links.last().unwrap();
+        let start = world.get::<Transform>(tip).unwrap().translation.x;
+
+        while app.world().resource::<Time<Fixed>>().elapsed_secs() < 1.0 {
+            app.update();
+        }
+        app.world().get::<Transform>(tip).unwrap().translation.x - start
+    }
+
+    #[test]
+    fn throws_reach_as_far_at_any_tick_rate() {
+        let expected = throw_distance(100.0, 10);
+        assert!(expected > 50.0, "the chain barely moved: {expected}");
+        for (hz, links) in [
+            (50.0, 10),
+            (200.0, 10),
+            (50.0, 30),
+            (100.0, 30),
+            (200.0, 30),
+        ] {
+            let distance = throw_distance(hz, links);
+            assert!(
+                (distance - expected).abs() < expected * 0.03,
+                "{links} links at {hz} Hz went {distance}, not {expected}"
+            );
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Chains decaying into scrap instead of just vanishing.
+//!
+//! A chain starts decaying when its [`ChainLifetime`] runs out, or when
+//! there are more chains than [`ChainConfig::max_chains`] allows. It still
+//! holds for [`DECAY_TIME`] while it flashes, then breaks apart, leaving a
+//! piece of scrap every few links. Scrap is worth a few points to the player
+//! who runs into it before it rusts away.
+//!
+//! [`ChainLifetime`]: crate::game::chain::ChainLifetime
+//! [`ChainConfig::max_chains`]: crate::game::chain::ChainConfig::max_chains
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{ChainLink, ChainPool, ChainState},
+        game_timer::{AddGameTimer, GameTimer},
+        gameplay_error::{GameplayError, OrReport},
+        objectives::Score,
+        player::Player,
+        trigger_volume::{DetectTriggers, TriggerEntered, trigger_volume},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Scrap>();
+    app.add_game_timer::<Scrap>();
+
+    app.add_systems(
+        Update,
+        (
+            break_decayed_chains,
+            pick_up_scrap.after(DetectTriggers),
+            rust_scrap,
+        )
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long a chain flashes before it breaks apart, in seconds.
+pub const DECAY_TIME: f32 = 0.6;
+/// How many times a second decaying chains flash.
+const FLASH_RATE: f32 = 8.0;
+/// How many links go into a piece of scrap.
+const LINKS_PER_SCRAP: usize = 4;
+const SCRAP_POINTS: u32 = 5;
+/// How long scrap lies around before rusting away, in seconds.
+const SCRAP_LIFETIME: f32 = 8.0;
+const SCRAP_RADIUS: f32 = 24.0;
+const SCRAP_SIZE: Vec2 = Vec2::new(8.0, 5.0);
+const SCRAP_COLOR: Color = Color::srgb(0.7, 0.65, 0.6);
+
+/// Whether a chain that's been decaying for `decay` seconds is flashed on.
+pub fn flash_on(decay: f32) -> bool {
+    ((decay * FLASH_RATE * 2.0) as u32).is_multiple_of(2)
+}
+
+/// A piece of a broken chain, worth a few points.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Scrap {
+    lifetime: Timer,
+}
+
+impl GameTimer for Scrap {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.lifetime
+    }
+}
+
+fn scrap(position: Vec2) -> impl Bundle {
+    (
+        Name::new("Scrap"),
+        Scrap {
+            lifetime: Timer::from_seconds(SCRAP_LIFETIME, TimerMode::Once),
+        },
+        trigger_volume(Collider::circle(SCRAP_RADIUS)),
+        Sprite::from_color(SCRAP_COLOR, SCRAP_SIZE),
+        Transform::from_translation(position.extend(0.0)),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn break_decayed_chains(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    link_query: Query<&GlobalTransform, With<ChainLink>>,
+) {
+    let mut i = 0;
+    while i < chain_state.chains.len() {
+        let chain = &mut chain_state.chains[i];
+        let Some(decay) = &mut chain.decay else {
+            i += 1;
+            continue;
+        };
+        *decay += time.delta_secs();
+        if *decay < DECAY_TIME {
+            i += 1;
+            continue;
+        }
+        let chain = chain_state.chains.remove(i);
+        for &link in chain.links.iter().step_by(LINKS_PER_SCRAP) {
+            if let Ok(transform) = link_query.get(link) {
+                commands.spawn(scrap(transform.translation().truncate()));
+            }
+        }
+        chain.release(&mut commands, &mut pool);
+    }
+}
+
+fn pick_up_scrap(
+    mut commands: Commands,
+    mut entered: EventReader<TriggerEntered>,
+    player_query: Query<Entity, With<Player>>,
+    scrap_query: Query<(), With<Scrap>>,
+    mut score: ResMut<Score>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if entered.is_empty() {
+        return;
+    }
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "pick_up_scrap", "player")
+    else {
+        return;
+    };
+    for event in entered.read() {
+        if event.other == player && scrap_query.contains(event.volume) {
+            score.points += SCRAP_POINTS;
+            commands.entity(event.volume).despawn();
+        }
+    }
+}
+
+fn rust_scrap(mut commands: Commands, scrap_query: Query<(Entity, &Scrap)>) {
+    for (entity, scrap) in &scrap_query {
+        if scrap.lifetime.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,494 @@
+//! Sounds and particles for what chains do.
+//!
+//! Firing a chain, a link knocking into something, the far end hooking on and
+//! a chain breaking each play a short clank, pitched a little differently each
+//! time. Impacts also kick up dust, and the hardest ones throw sparks. Like the
+//! scrape, the clanks are generated rather than loaded, and are muffled by
+//! walls. A whole chain can hit a wall at once, so each kind of sound plays at
+//! most every [`ChainSoundKind::min_interval`], and only the hardest impact
+//! of a frame is heard.
+//!
+//! Everything here, along with the scraping, runs in [`ChainFeedbackSystems`].
+
+use std::{f32::consts::TAU, time::Duration};
+
+use avian2d::prelude::*;
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source, Volume},
+    prelude::*,
+};
+use rand::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    audio::SoundEffect,
+    game::{
+        chain::{ChainFired, ChainHooked, ChainLink},
+        chain_stress::ChainBroken,
+        cleanup::{GameplaySpawned, SpawnKind},
+        game_rng::{GameRng, RngStream},
+        game_timer::{AddGameTimer, GameTimer},
+        occlusion::OccludedSound,
+        scrape::spark,
+    },
+    persistence::Settings,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_audio_source::<ClankSound>();
+    app.register_type::<Dust>();
+    app.add_game_timer::<Dust>();
+    app.init_resource::<ChainSoundLimiter>();
+
+    app.configure_sets(
+        Update,
+        ChainFeedbackSystems
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        (
+            clank_on_fire,
+            clank_on_impact,
+            clank_on_hook,
+            clank_on_break,
+            update_dust,
+        )
+            .in_set(ChainFeedbackSystems),
+    );
+}
+
+/// The systems turning chain events into sounds and particles.
+#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ChainFeedbackSystems;
+
+/// Links hitting something slower than this, in pixels per second, make no
+/// sound or dust.
+const MIN_IMPACT_SPEED: f32 = 80.0;
+/// Impacts this fast are at full strength.
+const FULL_IMPACT_SPEED: f32 = 700.0;
+/// Impacts at least this strong throw sparks as well as dust.
+const SPARK_STRENGTH: f32 = 0.6;
+/// Dust puffs per frame at most, however many links hit something.
+const MAX_DUST_BURSTS: usize = 6;
+const DUST_PER_BURST: usize = 4;
+const DUST_SPEED: f32 = 40.0;
+const DUST_LIFETIME: f32 = 0.5;
+const DUST_SIZE: f32 = 4.0;
+/// How much bigger dust gets as it settles.
+const DUST_GROWTH: f32 = 2.0;
+const DUST_DRAG: f32 = 4.0;
+const DUST_COLOR: Color = Color::srgba(0.75, 0.7, 0.6, 0.6);
+/// Random pitch changes, as a fraction of the playback speed either way.
+const PITCH_VARIATION: f32 = 0.08;
+const SAMPLE_RATE: u32 = 44_100;
+
+/// The chain sounds that are rate limited separately.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum ChainSoundKind {
+    Fire,
+    Impact,
+    Hook,
+    Break,
+}
+
+impl ChainSoundKind {
+    /// The shortest time between two of these sounds, in seconds.
+    fn min_interval(self) -> f32 {
+        match self {
+            Self::Fire => 0.05,
+            Self::Impact => 0.08,
+            Self::Hook => 0.05,
+            Self::Break => 0.1,
+        }
+    }
+
+    fn volume(self) -> f32 {
+        match self {
+            Self::Fire => 0.25,
+            Self::Impact => 0.35,
+            Self::Hook => 0.45,
+            Self::Break => 0.5,
+        }
+    }
+
+    fn sound(self, seed: u64) -> ClankSound {
+        match self {
+            // A light rattle as the links pay out.
+            Self::Fire => ClankSound {
+                partials: &[1800.0, 2650.0],
+                decay: 30.0,
+                noise: 0.6,
+                duration: 0.15,
+                seed,
+            },
+            Self::Impact => ClankSound {
+                partials: &[620.0, 1430.0, 2210.0],
+                decay: 22.0,
+                noise: 0.3,
+                duration: 0.25,
+                seed,
+            },
+            // Rings on a little, so it reads as catching.
+            Self::Hook => ClankSound {
+                partials: &[880.0, 1320.0, 2470.0],
+                decay: 9.0,
+                noise: 0.15,
+                duration: 0.5,
+                seed,
+            },
+            Self::Break => ClankSound {
+                partials: &[410.0, 1170.0, 1940.0, 3050.0],
+                decay: 12.0,
+                noise: 0.5,
+                duration: 0.45,
+                seed,
+            },
+        }
+    }
+}
+
+/// When each kind of chain sound last played, in seconds of game time.
+#[derive(Resource, Default, Debug)]
+struct ChainSoundLimiter(Vec<(ChainSoundKind, f32)>);
+
+impl ChainSoundLimiter {
+    /// Whether a sound of `kind` may play now, recording it if so.
+    fn try_play(&mut self, kind: ChainSoundKind, now: f32) -> bool {
+        match self.0.iter_mut().find(|(played, _)| *played == kind) {
+            Some((_, last)) if now - *last < kind.min_interval() => false,
+            Some((_, last)) => {
+                *last = now;
+                true
+            }
+            None => {
+                self.0.push((kind, now));
+                true
+            }
+        }
+    }
+}
+
+/// Play a clank of `kind` at `position`, with `strength` between 0 and 1
+/// scaling its volume, unless one played too recently.
+fn play_clank(
+    commands: &mut Commands,
+    sounds: &mut Assets<ClankSound>,
+    limiter: &mut ChainSoundLimiter,
+    rng: &mut impl Rng,
+    time: &Time,
+    kind: ChainSoundKind,
+    position: Vec2,
+    strength: f32,
+) {
+    if !limiter.try_play(kind, time.elapsed_secs()) {
+        return;
+    }
+    let pitch = 1.0 + rng.random_range(-PITCH_VARIATION..PITCH_VARIATION);
+    commands.spawn((
+        Name::new(format!("{kind:?} Clank")),
+        AudioPlayer(sounds.add(kind.sound(rng.random()))),
+        PlaybackSettings::DESPAWN
+            .with_volume(Volume::Linear(kind.volume() * strength))
+            .with_speed(pitch),
+        SoundEffect,
+        OccludedSound::default(),
+        Transform::from_translation(position.extend(0.0)),
+        GameplaySpawned(SpawnKind::Sound),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn clank_on_fire(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut sounds: ResMut<Assets<ClankSound>>,
+    mut limiter: ResMut<ChainSoundLimiter>,
+    mut fired: EventReader<ChainFired>,
+) {
+    let rng = stream.get(&game_rng, "fire clanks");
+    for event in fired.read() {
+        play_clank(
+            &mut commands,
+            &mut sounds,
+            &mut limiter,
+            rng,
+            &time,
+            ChainSoundKind::Fire,
+            event.from,
+            1.0,
+        );
+    }
+}
+
+fn clank_on_impact(
+    mut commands: Commands,
+    time: Res<Time>,
+    collisions: Collisions,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut sounds: ResMut<Assets<ClankSound>>,
+    mut limiter: ResMut<ChainSoundLimiter>,
+    settings: Res<Settings>,
+    link_query: Query<&LinearVelocity, With<ChainLink>>,
+    body_query: Query<(&Position, &Rotation)>,
+) {
+    let rng = stream.get(&game_rng, "impact clanks");
+    let mut hardest: Option<(f32, Vec2)> = None;
+    let mut bursts = 0;
+
+    for pair in collisions.iter().filter(|pair| pair.collision_started()) {
+        let (velocity, flip) = match (
+            link_query.get(pair.collider1),
+            link_query.get(pair.collider2),
+        ) {
+            // Links knocking into each other are part of the chain settling.
+            (Ok(_), Ok(_)) | (Err(_), Err(_)) => continue,
+            (Ok(velocity), Err(_)) => (velocity.0, 1.0),
+            (Err(_), Ok(velocity)) => (velocity.0, -1.0),
+        };
+        // Contact points are kept relative to the first collider.
+        let Ok((position, rotation)) = body_query.get(pair.collider1) else {
+            continue;
+        };
+        for manifold in &pair.manifolds {
+            // The normal points from the first collider to the second, so
+            // this is how fast the link moves into whatever it hit.
+            let normal = manifold.normal * flip;
+            let speed = velocity.dot(normal);
+            if speed < MIN_IMPACT_SPEED {
+                continue;
+            }
+            let strength = (speed / FULL_IMPACT_SPEED).min(1.0);
+            let Some(point) = manifold
+                .points
+                .first()
+                .map(|point| point.global_point1(position, rotation))
+            else {
+                continue;
+            };
+            if hardest.is_none_or(|(hardest, _)| strength > hardest) {
+                hardest = Some((strength, point));
+            }
+
+            if bursts >= MAX_DUST_BURSTS {
+                continue;
+            }
+            bursts += 1;
+            for _ in 0..settings.particles(DUST_PER_BURST) {
+                // Dust puffs back off the surface.
+                let direction =
+                    (-normal + Vec2::from_angle(rng.random_range(0.0..TAU))).normalize_or_zero();
+                commands.spawn(dust(
+                    point,
+                    direction * DUST_SPEED * rng.random_range(0.5..1.5),
+                ));
+            }
+            if strength >= SPARK_STRENGTH {
+                let direction = (-normal + Vec2::from_angle(rng.random_range(0.0..TAU)) * 0.5)
+                    .normalize_or_zero();
+                commands.spawn(spark(point, direction * speed * 0.5));
+            }
+        }
+    }
+
+    if let Some((strength, point)) = hardest {
+        play_clank(
+            &mut commands,
+            &mut sounds,
+            &mut limiter,
+            rng,
+            &time,
+            ChainSoundKind::Impact,
+            point,
+            strength,
+        );
+    }
+}
+
+fn clank_on_hook(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut sounds: ResMut<Assets<ClankSound>>,
+    mut limiter: ResMut<ChainSoundLimiter>,
+    mut hooked: EventReader<ChainHooked>,
+) {
+    let rng = stream.get(&game_rng, "hook clanks");
+    for event in hooked.read() {
+        play_clank(
+            &mut commands,
+            &mut sounds,
+            &mut limiter,
+            rng,
+            &time,
+            ChainSoundKind::Hook,
+            event.point,
+            1.0,
+        );
+    }
+}
+
+fn clank_on_break(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut sounds: ResMut<Assets<ClankSound>>,
+    mut limiter: ResMut<ChainSoundLimiter>,
+    mut broken: EventReader<ChainBroken>,
+) {
+    let rng = stream.get(&game_rng, "break clanks");
+    for event in broken.read() {
+        play_clank(
+            &mut commands,
+            &mut sounds,
+            &mut limiter,
+            rng,
+            &time,
+            ChainSoundKind::Break,
+            event.point,
+            1.0,
+        );
+    }
+}
+
+/// A puff of dust knocked off an obstacle by a link.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Dust {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+impl GameTimer for Dust {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.lifetime
+    }
+}
+
+fn dust(position: Vec2, velocity: Vec2) -> impl Bundle {
+    (
+        Name::new("Dust"),
+        Dust {
+            velocity,
+            lifetime: Timer::from_seconds(DUST_LIFETIME, TimerMode::Once),
+        },
+        Sprite {
+            color: DUST_COLOR,
+            custom_size: Some(Vec2::splat(DUST_SIZE)),
+            ..default()
+        },
+        Transform::from_translation(position.extend(1.0)),
+        GameplaySpawned(SpawnKind::Particle),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn update_dust(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut dust_query: Query<(Entity, &mut Dust, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut dust, mut transform, mut sprite) in &mut dust_query {
+        if dust.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (dust.velocity * time.delta_secs()).extend(0.0);
+        dust.velocity *= (-DUST_DRAG * time.delta_secs()).exp();
+        let remaining = dust.lifetime.fraction_remaining();
+        transform.scale = Vec3::splat(1.0 + DUST_GROWTH * (1.0 - remaining));
+        sprite.color = DUST_COLOR.with_alpha(DUST_COLOR.alpha() * remaining);
+    }
+}
+
+/// A struck piece of metal: a few inharmonic partials ringing out, over a
+/// burst of noise for the hit itself.
+#[derive(Asset, TypePath, Clone)]
+struct ClankSound {
+    /// Frequencies in Hz.
+    partials: &'static [f32],
+    /// How quickly the partials die away, per second.
+    decay: f32,
+    /// How loud the hit is, compared to the ringing.
+    noise: f32,
+    duration: f32,
+    /// Seeds the noise of the hit.
+    seed: u64,
+}
+
+impl Decodable for ClankSound {
+    type DecoderItem = f32;
+    type Decoder = ClankDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        ClankDecoder {
+            sound: self.clone(),
+            position: 0,
+            total: (self.duration * SAMPLE_RATE as f32) as u32,
+            rng: SmallRng::seed_from_u64(self.seed),
+        }
+    }
+}
+
+struct ClankDecoder {
+    sound: ClankSound,
+    position: u32,
+    total: u32,
+    rng: SmallRng,
+}
+
+/// How quickly the noise of the hit dies away, per second.
+const HIT_DECAY: f32 = 120.0;
+
+impl Iterator for ClankDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.total {
+            return None;
+        }
+        let t = self.position as f32 / SAMPLE_RATE as f32;
+        self.position += 1;
+        let partials = &self.sound.partials;
+        let ring: f32 = partials
+            .iter()
+            .enumerate()
+            // Higher partials are quieter and die away sooner.
+            .map(|(i, frequency)| {
+                let envelope = (-t * self.sound.decay * (1.0 + i as f32 * 0.5)).exp();
+                (t * frequency * TAU).sin() * envelope / (1.0 + i as f32)
+            })
+            .sum::<f32>()
+            / partials.len().max(1) as f32;
+        let hit = self.rng.random_range(-1.0..1.0) * (-t * HIT_DECAY).exp() * self.sound.noise;
+        Some(ring + hit)
+    }
+}
+
+impl Source for ClankDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.total as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
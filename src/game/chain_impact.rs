@@ -0,0 +1,294 @@
+//! Fast chain links hurt the player.
+//!
+//! A link, or a fragment of a severed chain, that hits the player at
+//! [`IMPACT_SPEED`] or more relative to them takes a little of their
+//! [`PlayerHealth`], more the faster it was going. That goes for the player's
+//! own chains whipping back at them as much as anyone else's. Links are
+//! harmless for [`GRACE_TIME`] after they're spawned, so firing point blank
+//! doesn't hurt, and so are the few next to the player's hand, which move
+//! with them. After a hit the player can't be hurt again for a moment, and
+//! health comes back once they haven't been hit for a while. Running out of
+//! it ends the attempt, the same as a hazard, which takes it all at once.
+//!
+//! The [`Assists::chain_damage`] assist scales the damage, down to none. With
+//! [`Mutator::OneHitDeath`], any hit is fatal, whatever the assist is set to,
+//! since the mutator's score bonus is for taking that risk.
+
+use avian2d::prelude::*;
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    AppSystems, PausableSystems, Pause,
+    game::{
+        assists::Assists,
+        chain::{ChainFragment, ChainLink},
+        cheats::Cheats,
+        game_timer::{AddGameTimer, GameTimer},
+        gameplay_error::{GameplayError, OrReport},
+        mutators::{Mutator, RunMutators},
+        objectives::{Defeat, DefeatCause},
+        player::Player,
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::layout::HudAnchor,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ImpactGrace>();
+    app.register_type::<HealthBar>();
+    app.register_type::<PlayerHealth>();
+    app.init_resource::<PlayerHealth>();
+    app.add_game_timer::<ImpactGrace>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (reset_player_health, spawn_health_bar),
+    );
+    app.add_systems(
+        Update,
+        (
+            start_impact_grace,
+            (recover_health, hit_player).chain(),
+            update_health_bar,
+        )
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+pub const MAX_HEALTH: f32 = 100.0;
+/// How fast a link has to hit the player to hurt, in pixels per second.
+pub const IMPACT_SPEED: f32 = 450.0;
+/// How long new links are harmless for, in seconds.
+pub const GRACE_TIME: f32 = 0.4;
+/// Links this close to the start of a chain never hurt.
+const HELD_LINKS: usize = 3;
+/// How much health a hit at [`IMPACT_SPEED`] takes.
+const IMPACT_DAMAGE: f32 = 10.0;
+/// Faster hits take more, up to this many times [`IMPACT_DAMAGE`].
+const MAX_DAMAGE_SCALE: f32 = 2.0;
+/// How close a link's center has to come to the player's to hit them.
+const HIT_RADIUS: f32 = 18.0;
+/// How long after a hit the player can't be hurt, in seconds.
+const HIT_COOLDOWN: f32 = 0.75;
+/// How long after a hit health starts coming back, in seconds.
+const RECOVERY_DELAY: f32 = 3.0;
+/// How much health comes back per second.
+const RECOVERY_RATE: f32 = 10.0;
+const HEALTH_BAR_SIZE: Vec2 = Vec2::new(160.0, 6.0);
+const HEALTH_COLOR: Color = Color::srgb(0.9, 0.3, 0.3);
+const HEALTH_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+
+/// Counts down how long a link is harmless for.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ImpactGrace(pub Timer);
+
+impl GameTimer for ImpactGrace {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.0
+    }
+}
+
+/// How much more the player can take from chains.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct PlayerHealth {
+    pub current: f32,
+    /// Seconds since the last hit.
+    since_hit: f32,
+}
+
+impl Default for PlayerHealth {
+    fn default() -> Self {
+        Self {
+            current: MAX_HEALTH,
+            since_hit: RECOVERY_DELAY,
+        }
+    }
+}
+
+fn reset_player_health(mut health: ResMut<PlayerHealth>) {
+    *health = PlayerHealth::default();
+}
+
+fn start_impact_grace(mut commands: Commands, link_query: Query<Entity, Added<ChainLink>>) {
+    for link in &link_query {
+        commands
+            .entity(link)
+            .insert(ImpactGrace(Timer::from_seconds(
+                GRACE_TIME,
+                TimerMode::Once,
+            )));
+    }
+}
+
+fn recover_health(time: Res<Time>, mut health: ResMut<PlayerHealth>) {
+    health.since_hit += time.delta_secs();
+    if health.since_hit >= RECOVERY_DELAY && health.current < MAX_HEALTH {
+        health.current = (health.current + RECOVERY_RATE * time.delta_secs()).min(MAX_HEALTH);
+    }
+}
+
+fn hit_player(
+    player_query: Query<(&GlobalTransform, Option<&LinearVelocity>), With<Player>>,
+    link_query: Query<
+        (
+            &GlobalTransform,
+            &LinearVelocity,
+            Option<&ChainLink>,
+            Option<&ImpactGrace>,
+        ),
+        Or<(With<ChainLink>, With<ChainFragment>)>,
+    >,
+    assists: Res<Assists>,
+    cheats: Res<Cheats>,
+    mutators: Res<RunMutators>,
+    mut health: ResMut<PlayerHealth>,
+    mut defeat: ResMut<Defeat>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let one_hit_death = mutators.contains(Mutator::OneHitDeath);
+    if (assists.chain_damage <= 0.0 && !one_hit_death)
+        || cheats.god_mode
+        || health.since_hit < HIT_COOLDOWN
+    {
+        return;
+    }
+    let Some((player_transform, player_velocity)) =
+        player_query
+            .single()
+            .or_report(&mut errors, "hit_player", "player")
+    else {
+        return;
+    };
+    let player_position = player_transform.translation().truncate();
+    let player_velocity = player_velocity.map_or(Vec2::ZERO, |velocity| velocity.0);
+
+    let hardest_hit = link_query
+        .iter()
+        .filter(|(_, _, link, grace)| {
+            link.is_none_or(|link| link.link_index >= HELD_LINKS)
+                && grace.is_none_or(|grace| grace.0.finished())
+        })
+        .filter(|(transform, ..)| {
+            transform.translation().truncate().distance(player_position) < HIT_RADIUS
+        })
+        .map(|(_, velocity, ..)| (velocity.0 - player_velocity).length())
+        .filter(|&speed| speed >= IMPACT_SPEED)
+        .reduce(f32::max);
+    let Some(speed) = hardest_hit else {
+        return;
+    };
+
+    let scale = (speed / IMPACT_SPEED).min(MAX_DAMAGE_SCALE);
+    if one_hit_death {
+        health.current = 0.0;
+    } else {
+        health.current -= IMPACT_DAMAGE * scale * assists.chain_damage;
+    }
+    health.since_hit = 0.0;
+    if health.current <= 0.0 {
+        health.current = 0.0;
+        defeat.0 = Some(DefeatCause::ChainHit);
+        next_pause.set(Pause(true));
+        next_menu.set(Menu::Retry);
+    }
+}
+
+/// Shows what's left of the player's health while it isn't full.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HealthBar;
+
+fn spawn_health_bar(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Health Bar"),
+        Node {
+            width: Px(HEALTH_BAR_SIZE.x),
+            height: Px(HEALTH_BAR_SIZE.y),
+            ..default()
+        },
+        BackgroundColor(HEALTH_BACKGROUND),
+        HudAnchor::Top(40.0),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+        children![(
+            Name::new("Health"),
+            HealthBar,
+            Node {
+                width: Percent(100.0),
+                height: Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(HEALTH_COLOR),
+        )],
+    ));
+}
+
+fn update_health_bar(
+    health: Res<PlayerHealth>,
+    mut bar: Single<(&mut Node, &ChildOf), With<HealthBar>>,
+    mut visibility_query: Query<&mut Visibility>,
+) {
+    let (node, parent) = &mut *bar;
+    node.width = Percent(health.current / MAX_HEALTH * 100.0);
+    if let Ok(mut visibility) = visibility_query.get_mut(parent.parent()) {
+        visibility.set_if_neq(if health.current < MAX_HEALTH {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// The player's health after a fragment hits them as hard as it can,
+    /// with chain damage scaled by `chain_damage`.
+    fn health_after_hit(chain_damage: f32, mutators: RunMutators) -> f32 {
+        let mut world = World::new();
+        world.insert_resource(Assists {
+            chain_damage,
+            ..default()
+        });
+        world.insert_resource(mutators);
+        world.init_resource::<Cheats>();
+        world.init_resource::<PlayerHealth>();
+        world.init_resource::<Defeat>();
+        world.init_resource::<NextState<Menu>>();
+        world.init_resource::<NextState<Pause>>();
+        world.init_resource::<Events<GameplayError>>();
+        world.spawn((Player, GlobalTransform::IDENTITY));
+        world.spawn((
+            ChainFragment,
+            GlobalTransform::IDENTITY,
+            LinearVelocity(Vec2::X * IMPACT_SPEED * MAX_DAMAGE_SCALE),
+        ));
+
+        world.run_system_once(hit_player).unwrap();
+        world.resource::<PlayerHealth>().current
+    }
+
+    #[test]
+    fn no_chain_damage_means_no_hurt() {
+        assert_eq!(health_after_hit(0.0, RunMutators::default()), MAX_HEALTH);
+    }
+
+    #[test]
+    fn one_hit_death_ignores_the_chain_damage_assist() {
+        let mut mutators = RunMutators::default();
+        mutators.insert(Mutator::OneHitDeath);
+        assert_eq!(health_after_hit(1.0, mutators), 0.0);
+        assert_eq!(health_after_hit(0.0, mutators), 0.0);
+    }
+}
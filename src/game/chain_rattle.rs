@@ -0,0 +1,263 @@
+//! The rattle of chains being dragged and shaken.
+//!
+//! Every frame, each chain's links are checked for how much their spin
+//! changed, which is what a chain jangling about looks like to the physics.
+//! The more it changes, the louder and higher a looping rattle plays at the
+//! link shaken hardest. The rattle is made of short random clicks rather than
+//! a sound file, so every voice sounds a little different. Only the
+//! [`MAX_RATTLE_VOICES`] chains shaken hardest are heard, and a chain that
+//! goes still fades out rather than stopping dead.
+
+use std::{collections::HashMap, f32::consts::TAU, time::Duration};
+
+use avian2d::prelude::*;
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source, Volume},
+    prelude::*,
+};
+use rand::prelude::*;
+
+use crate::{
+    Pause,
+    audio::SoundEffect,
+    game::{
+        chain::{ChainLink, ChainState},
+        chain_feedback::ChainFeedbackSystems,
+        cleanup::{GameplaySpawned, SpawnKind},
+        game_rng::{GameRng, RngStream},
+        occlusion::OccludedSound,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_audio_source::<RattleSound>();
+    app.register_type::<ChainRattle>();
+
+    app.add_systems(Update, rattle_chains.in_set(ChainFeedbackSystems));
+    // Looping sounds would carry on through the pause menu.
+    app.add_systems(OnEnter(Pause(true)), pause_rattles);
+    app.add_systems(OnExit(Pause(true)), resume_rattles);
+}
+
+/// How many chains can rattle at once.
+const MAX_RATTLE_VOICES: usize = 3;
+/// How fast, on average, a chain's links have to change their spin to be
+/// heard, in radians per second per second.
+const MIN_SHAKE: f32 = 20.0;
+/// Shaking this hard rattles at full volume.
+const FULL_SHAKE: f32 = 400.0;
+const RATTLE_VOLUME: f32 = 0.3;
+/// How quickly a rattle follows its chain's shaking.
+const RATTLE_SMOOTHING: f32 = 12.0;
+/// Rattles fading out are stopped once they're this quiet.
+const SILENT_LEVEL: f32 = 0.01;
+/// The playback speed of a barely shaken chain, and how much faster a fully
+/// shaken one rattles.
+const MIN_PITCH: f32 = 0.8;
+const PITCH_RANGE: f32 = 0.5;
+/// Clicks per second in the rattle, before pitching.
+const CLICK_RATE: f32 = 35.0;
+const SAMPLE_RATE: u32 = 44_100;
+
+/// A looping rattle for the chain starting at `chain`.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct ChainRattle {
+    chain: Entity,
+    /// How loud the rattle is, from 0 to 1, before [`RATTLE_VOLUME`].
+    level: f32,
+}
+
+/// Endless link clicks: short, bright pings at random pitches, at random
+/// times.
+#[derive(Asset, TypePath, Clone)]
+struct RattleSound {
+    seed: u64,
+}
+
+impl Decodable for RattleSound {
+    type DecoderItem = f32;
+    type Decoder = RattleDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        RattleDecoder {
+            rng: SmallRng::seed_from_u64(self.seed),
+            clicks: Vec::new(),
+        }
+    }
+}
+
+/// A click ringing out: its phase and frequency in radians per sample, and
+/// how loud it still is.
+struct Click {
+    phase: f32,
+    step: f32,
+    amplitude: f32,
+}
+
+struct RattleDecoder {
+    rng: SmallRng,
+    clicks: Vec<Click>,
+}
+
+impl Iterator for RattleDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self
+            .rng
+            .random_bool(f64::from(CLICK_RATE / SAMPLE_RATE as f32))
+        {
+            let frequency = self.rng.random_range(1500.0..4200.0);
+            self.clicks.push(Click {
+                phase: 0.0,
+                step: TAU * frequency / SAMPLE_RATE as f32,
+                amplitude: self.rng.random_range(0.3..1.0),
+            });
+        }
+        let mut sample = 0.0;
+        for click in &mut self.clicks {
+            sample += click.phase.sin() * click.amplitude;
+            click.phase += click.step;
+            // Rings out over a few milliseconds.
+            click.amplitude *= 0.997;
+        }
+        self.clicks.retain(|click| click.amplitude > 0.01);
+        Some(sample * 0.5)
+    }
+}
+
+impl Source for RattleDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+fn rattle_chains(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    chain_state: Res<ChainState>,
+    mut sounds: ResMut<Assets<RattleSound>>,
+    mut last_spin: Local<HashMap<Entity, f32>>,
+    link_query: Query<(&AngularVelocity, &GlobalTransform), With<ChainLink>>,
+    mut voice_query: Query<(
+        Entity,
+        &mut ChainRattle,
+        &mut PlaybackSettings,
+        &mut Transform,
+        Option<&AudioSink>,
+    )>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    // How hard each chain is shaken, and where it's shaken hardest.
+    let mut spin = HashMap::with_capacity(last_spin.len());
+    let mut shaken = Vec::new();
+    for chain in &chain_state.chains {
+        let Some(&root) = chain.links.first() else {
+            continue;
+        };
+        let mut total = 0.0;
+        let mut count = 0;
+        let mut hardest = (0.0, Vec2::ZERO);
+        for &link in &chain.links {
+            let Ok((velocity, transform)) = link_query.get(link) else {
+                continue;
+            };
+            spin.insert(link, velocity.0);
+            let change = last_spin
+                .get(&link)
+                .map_or(0.0, |&previous| (velocity.0 - previous).abs());
+            total += change;
+            count += 1;
+            if change >= hardest.0 {
+                hardest = (change, transform.translation().truncate());
+            }
+        }
+        if count == 0 {
+            continue;
+        }
+        let shake = total / count as f32 / dt;
+        let strength = ((shake - MIN_SHAKE) / (FULL_SHAKE - MIN_SHAKE)).clamp(0.0, 1.0);
+        if strength > 0.0 {
+            shaken.push((root, strength, hardest.1));
+        }
+    }
+    *last_spin = spin;
+    shaken.sort_by(|(_, a, _), (_, b, _)| b.total_cmp(a));
+    shaken.truncate(MAX_RATTLE_VOICES);
+
+    let mut voices = Vec::new();
+    for (entity, mut rattle, mut playback, mut transform, sink) in &mut voice_query {
+        let heard = shaken.iter().find(|(root, ..)| *root == rattle.chain);
+        let target = heard.map_or(0.0, |&(_, strength, _)| strength);
+        rattle.level.smooth_nudge(&target, RATTLE_SMOOTHING, dt);
+        if let Some(&(_, _, position)) = heard {
+            transform.translation = position.extend(0.0);
+        } else if rattle.level < SILENT_LEVEL {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        voices.push(rattle.chain);
+        // Occlusion sets the sink's volume from this.
+        playback.volume = Volume::Linear(RATTLE_VOLUME * rattle.level);
+        if let Some(sink) = sink {
+            sink.set_speed(MIN_PITCH + PITCH_RANGE * rattle.level);
+        }
+    }
+
+    let rng = stream.get(&game_rng, "rattles");
+    for &(root, _, position) in &shaken {
+        if voices.len() >= MAX_RATTLE_VOICES {
+            break;
+        }
+        if voices.contains(&root) {
+            continue;
+        }
+        voices.push(root);
+        commands.spawn((
+            Name::new("Chain Rattle"),
+            ChainRattle {
+                chain: root,
+                level: 0.0,
+            },
+            AudioPlayer(sounds.add(RattleSound { seed: rng.random() })),
+            PlaybackSettings::LOOP.with_volume(Volume::SILENT),
+            SoundEffect,
+            OccludedSound::default(),
+            Transform::from_translation(position.extend(0.0)),
+            GameplaySpawned(SpawnKind::Sound),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+fn pause_rattles(sink_query: Query<&AudioSink, With<ChainRattle>>) {
+    for sink in &sink_query {
+        sink.pause();
+    }
+}
+
+fn resume_rattles(sink_query: Query<&AudioSink, With<ChainRattle>>) {
+    for sink in &sink_query {
+        sink.play();
+    }
+}
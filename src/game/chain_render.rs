@@ -0,0 +1,287 @@
+//! Drawing chains as one continuous rope.
+//!
+//! Chain links only exist for physics. Every frame, a Catmull-Rom spline is
+//! run through the points where neighboring links meet, and a strip mesh is
+//! built along it, so the rope stays smooth and unbroken however much the
+//! joints stretch. All chains share one mesh. How the rope looks is set by
+//! [`ChainRenderConfig`]. Each link's stretch of rope is tinted toward
+//! `stressed_color` as its [`ChainStress`] builds up, and decaying chains
+//! flash `decay_color`.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::{
+    AppSystems,
+    game::{
+        chain::{ChainConfig, ChainState, LINK_SIZE, OwnedChains},
+        chain_decay::flash_on,
+        chain_stress::ChainStress,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChainRenderConfig>();
+    app.init_resource::<ChainRenderConfig>();
+    app.register_type::<ChainRope>();
+
+    app.add_systems(
+        Update,
+        (
+            update_rope_material.run_if(resource_changed::<ChainRenderConfig>),
+            update_rope_mesh,
+        )
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Points added between each pair of link ends, to round off the bends.
+const SUBDIVISIONS: usize = 4;
+
+/// How chains are drawn.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct ChainRenderConfig {
+    /// The width of the rope, in pixels.
+    pub width: f32,
+    pub color: Color,
+    /// An image repeated along the rope, tinted by `color`. Its sampler has
+    /// to repeat for it to tile rather than stretch.
+    pub texture: Option<Handle<Image>>,
+    /// How long one repeat of the texture is along the rope, in pixels.
+    pub tile_length: f32,
+    /// The tint of links about to break.
+    pub stressed_color: Color,
+    /// The color decaying chains flash.
+    pub decay_color: Color,
+}
+
+impl Default for ChainRenderConfig {
+    fn default() -> Self {
+        Self {
+            width: 3.0,
+            color: Color::WHITE,
+            texture: None,
+            tile_length: LINK_SIZE,
+            stressed_color: Color::srgb(1.0, 0.15, 0.1),
+            decay_color: Color::srgb(1.0, 0.9, 0.5),
+        }
+    }
+}
+
+/// The mesh all chains are drawn with.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ChainRope;
+
+fn rope_material(config: &ChainRenderConfig) -> ColorMaterial {
+    ColorMaterial {
+        color: config.color,
+        texture: config.texture.clone(),
+        ..default()
+    }
+}
+
+fn update_rope_material(
+    config: Res<ChainRenderConfig>,
+    rope_query: Query<&MeshMaterial2d<ColorMaterial>, With<ChainRope>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for material in &rope_query {
+        if let Some(material) = materials.get_mut(material) {
+            *material = rope_material(&config);
+        }
+    }
+}
+
+/// Rebuild the rope along the links. It is spawned along with the first
+/// chain, and hidden while there are none.
+fn update_rope_mesh(
+    mut commands: Commands,
+    config: Res<ChainRenderConfig>,
+    chain_config: Res<ChainConfig>,
+    chain_state: Res<ChainState>,
+    owned_chains: Res<OwnedChains>,
+    link_query: Query<(&GlobalTransform, &ChainStress)>,
+    mut rope_query: Query<(&Mesh2d, &mut Visibility), With<ChainRope>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let strips: Vec<Strip> = chain_state
+        .chains
+        .iter()
+        .chain(owned_chains.0.iter().map(|(_, chain)| chain))
+        .map(|chain| {
+            let (links, tints): (Vec<&GlobalTransform>, Vec<f32>) = chain
+                .links
+                .iter()
+                .filter_map(|&link| link_query.get(link).ok())
+                .map(|(transform, stress)| {
+                    (
+                        transform,
+                        (stress.0 / chain_config.breaking_stress).min(1.0),
+                    )
+                })
+                .unzip();
+            Strip {
+                points: smooth(&link_ends(&links)),
+                color: chain.hook_type.color,
+                tints: smooth_tints(&tints),
+                flash: chain.decay.is_some_and(flash_on),
+            }
+        })
+        .filter(|strip| strip.points.len() >= 2)
+        .collect();
+
+    let Ok((mesh, mut visibility)) = rope_query.single_mut() else {
+        if !strips.is_empty() {
+            commands.spawn((
+                Name::new("Chain Rope"),
+                ChainRope,
+                Mesh2d(meshes.add(rope_mesh(&strips, &config))),
+                MeshMaterial2d(materials.add(rope_material(&config))),
+                Transform::default(),
+                Visibility::default(),
+                StateScoped(Screen::Gameplay),
+            ));
+        }
+        return;
+    };
+    if strips.is_empty() {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+    visibility.set_if_neq(Visibility::Inherited);
+    if let Some(mesh) = meshes.get_mut(mesh) {
+        *mesh = rope_mesh(&strips, &config);
+    }
+}
+
+/// Where the rope passes through: the bottom of the first link, halfway
+/// between the ends of each pair of neighboring links, and the top of the
+/// last link.
+fn link_ends(links: &[&GlobalTransform]) -> Vec<Vec2> {
+    let end = |link: &GlobalTransform, side: f32| {
+        link.transform_point(Vec3::new(0.0, side * LINK_SIZE * 0.5, 0.0))
+            .truncate()
+    };
+    let (Some(first), Some(last)) = (links.first(), links.last()) else {
+        return Vec::new();
+    };
+    let mut points = vec![end(first, -1.0)];
+    points.extend(
+        links
+            .windows(2)
+            .map(|pair| end(pair[0], 1.0).midpoint(end(pair[1], -1.0))),
+    );
+    points.push(end(last, 1.0));
+    points
+}
+
+/// A Catmull-Rom spline through `points`, with [`SUBDIVISIONS`] extra points
+/// between each pair.
+fn smooth(points: &[Vec2]) -> Vec<Vec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let at = |i: isize| points[i.clamp(0, points.len() as isize - 1) as usize];
+    let mut smoothed = Vec::with_capacity((points.len() - 1) * (SUBDIVISIONS + 1) + 1);
+    for i in 0..points.len() as isize - 1 {
+        let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+        for step in 0..=SUBDIVISIONS {
+            let t = step as f32 / (SUBDIVISIONS + 1) as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            smoothed.push(
+                0.5 * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3),
+            );
+        }
+    }
+    smoothed.push(points[points.len() - 1]);
+    smoothed
+}
+
+/// How much each point of a [`smooth`]ed rope is tinted, from how much each
+/// link is.
+fn smooth_tints(tints: &[f32]) -> Vec<f32> {
+    // A single link has just its two ends.
+    if tints.len() < 2 {
+        return tints.iter().chain(tints).copied().collect();
+    }
+    let mut smoothed: Vec<f32> = tints
+        .iter()
+        .flat_map(|&tint| [tint; SUBDIVISIONS + 1])
+        .collect();
+    smoothed.extend(tints.last());
+    smoothed
+}
+
+/// The points the rope of one chain passes through, and how stressed it is
+/// at each.
+struct Strip {
+    points: Vec<Vec2>,
+    /// The color of its hook type.
+    color: Color,
+    tints: Vec<f32>,
+    /// Whether the chain is decaying and flashed on this frame.
+    flash: bool,
+}
+
+/// A strip `width` wide along each of `strips`. The texture repeats every
+/// `tile_length` pixels along it.
+fn rope_mesh(strips: &[Strip], config: &ChainRenderConfig) -> Mesh {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut indices = Vec::new();
+    for Strip {
+        points,
+        color,
+        tints,
+        flash,
+    } in strips
+    {
+        let mut distance = 0.0;
+        for (i, &point) in points.iter().enumerate() {
+            let previous = points[i.saturating_sub(1)];
+            let next = points[(i + 1).min(points.len() - 1)];
+            let side = (next - previous).normalize_or_zero().perp() * config.width * 0.5;
+            distance += point.distance(previous);
+            let u = distance / config.tile_length;
+            let tint = tints.get(i).copied().unwrap_or_default();
+            let vertex_color = if *flash {
+                config.decay_color
+            } else {
+                color.mix(&config.stressed_color, tint)
+            };
+            let vertex_color = vertex_color.to_linear().to_f32_array();
+
+            let first = positions.len() as u32;
+            positions.push((point - side).extend(0.0).to_array());
+            positions.push((point + side).extend(0.0).to_array());
+            uvs.push([u, 1.0]);
+            uvs.push([u, 0.0]);
+            colors.extend([vertex_color, vertex_color]);
+            if i > 0 {
+                indices.extend([first - 2, first - 1, first, first, first - 1, first + 1]);
+            }
+        }
+    }
+
+    // Kept in the main world too, to be rebuilt every frame.
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
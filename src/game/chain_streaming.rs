@@ -0,0 +1,167 @@
+//! Streaming the middle of very long chains.
+//!
+//! Every link of a chain is a rigid body, so chains of hundreds of links get
+//! expensive to simulate. Once a chain has [`ChainConfig::streaming_min_links`]
+//! links and is pulled taut, its middle is frozen: only the
+//! [`ChainConfig::streaming_end_links`] links at each end stay in the
+//! simulation, held together by a distance joint, and the links in between
+//! are laid out in a straight line between them every frame. The middle
+//! thaws again as soon as the chain goes slack or is rappelled down.
+//!
+//! Frozen links don't collide, so a taut chain can't catch on corners with
+//! its middle.
+
+use std::f32::consts::FRAC_PI_2;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{Chain, ChainConfig, ChainState, FrozenMiddle, LINK_SIZE},
+        cleanup::{GameplaySpawned, SpawnKind},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (stream_long_chains, lay_out_frozen_links)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How stretched the middle has to be to freeze, as a fraction of its
+/// length.
+const FREEZE_TENSION: f32 = 0.95;
+/// How slack a frozen middle has to get to thaw. Lower than
+/// [`FREEZE_TENSION`], so chains don't flicker between the two.
+const THAW_TENSION: f32 = 0.8;
+
+/// Freeze the middle of long, taut chains, and thaw it when they go slack.
+fn stream_long_chains(
+    mut commands: Commands,
+    config: Res<ChainConfig>,
+    mut chain_state: ResMut<ChainState>,
+    transform_query: Query<&Transform>,
+) {
+    // How far apart `start` and `end` are, next to how far `links` reach.
+    let tension = |start: Entity, end: Entity, links: usize| {
+        let [start, end] = transform_query.get_many([start, end]).ok()?;
+        Some(link_end(start, 1.0).distance(link_end(end, -1.0)) / (links as f32 * LINK_SIZE))
+    };
+
+    for chain in &mut chain_state.chains {
+        if let Some(frozen) = &chain.frozen {
+            // Reeling in can take the start link away with the root.
+            let keep = chain.rappel.is_none()
+                && chain.links.contains(&frozen.start)
+                && tension(frozen.start, frozen.end, frozen.links.len())
+                    .is_some_and(|tension| tension >= THAW_TENSION);
+            if !keep {
+                thaw(&mut commands, chain);
+            }
+            continue;
+        }
+
+        let end_links = config.streaming_end_links.max(1);
+        if chain.rappel.is_some()
+            || chain.links.len() < config.streaming_min_links
+            || chain.links.len() < 2 * end_links + 2
+        {
+            continue;
+        }
+        let (first, last) = (end_links, chain.links.len() - 1 - end_links);
+        let (start, end) = (chain.links[first - 1], chain.links[last + 1]);
+        if tension(start, end, last + 1 - first).is_some_and(|tension| tension >= FREEZE_TENSION) {
+            freeze(&mut commands, chain, first, last);
+        }
+    }
+}
+
+/// Take links `first..=last` out of the simulation.
+pub(super) fn freeze(commands: &mut Commands, chain: &mut Chain, first: usize, last: usize) {
+    let links = chain.links[first..=last].to_vec();
+    let joints = chain.joints[first - 1..=last].to_vec();
+    let (start, end) = (chain.links[first - 1], chain.links[last + 1]);
+    for &link in &links {
+        commands
+            .entity(link)
+            .insert((RigidBodyDisabled, ColliderDisabled));
+    }
+    for &joint in &joints {
+        commands.entity(joint).insert(JointDisabled);
+    }
+    let length = links.len() as f32 * LINK_SIZE;
+    let joint = commands
+        .spawn((
+            Name::new("Frozen Chain Span"),
+            DistanceJoint::new(start, end)
+                .with_local_anchor_1(Vec2::new(0.0, LINK_SIZE * 0.5))
+                .with_local_anchor_2(Vec2::new(0.0, -LINK_SIZE * 0.5))
+                .with_rest_length(length)
+                .with_limits(0.0, length),
+            GameplaySpawned(SpawnKind::Joint),
+            StateScoped(Screen::Gameplay),
+        ))
+        .id();
+    chain.frozen = Some(FrozenMiddle {
+        links,
+        joints,
+        start,
+        end,
+        joint,
+    });
+}
+
+/// Put a frozen middle back into the simulation.
+pub(super) fn thaw(commands: &mut Commands, chain: &mut Chain) {
+    let Some(frozen) = chain.frozen.take() else {
+        return;
+    };
+    for link in frozen.links {
+        commands
+            .entity(link)
+            .remove::<(RigidBodyDisabled, ColliderDisabled)>();
+    }
+    for joint in frozen.joints {
+        commands.entity(joint).remove::<JointDisabled>();
+    }
+    commands.entity(frozen.joint).despawn();
+}
+
+/// Spread frozen links evenly along the line between the links on either
+/// side, so the rope is drawn through them.
+fn lay_out_frozen_links(chain_state: Res<ChainState>, mut transform_query: Query<&mut Transform>) {
+    for frozen in chain_state
+        .chains
+        .iter()
+        .filter_map(|chain| chain.frozen.as_ref())
+    {
+        let Ok([start, end]) = transform_query.get_many([frozen.start, frozen.end]) else {
+            continue;
+        };
+        let (start, end) = (link_end(start, 1.0), link_end(end, -1.0));
+        let rotation = Quat::from_rotation_z((end - start).to_angle() - FRAC_PI_2);
+        let count = frozen.links.len() as f32;
+        for (i, &link) in frozen.links.iter().enumerate() {
+            let Ok(mut transform) = transform_query.get_mut(link) else {
+                continue;
+            };
+            let center = start.lerp(end, (i as f32 + 0.5) / count);
+            transform.translation = center.extend(transform.translation.z);
+            transform.rotation = rotation;
+        }
+    }
+}
+
+/// The top (`side` 1) or bottom (`side` -1) end of a link.
+fn link_end(link: &Transform, side: f32) -> Vec2 {
+    link.transform_point(Vec3::new(0.0, side * LINK_SIZE * 0.5, 0.0))
+        .truncate()
+}
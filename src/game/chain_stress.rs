@@ -0,0 +1,93 @@
+//! Chains wearing out and breaking under load.
+//!
+//! Every frame, each joint of a chain is checked for how far its two links
+//! have been pulled apart. Stretch past [`ChainConfig::stress_tolerance`]
+//! builds up [`ChainStress`] on the link, and it wears off again while the
+//! joint is relaxed. Once any link reaches [`ChainConfig::breaking_stress`],
+//! the whole chain snaps and a [`ChainBroken`] event goes out. The rope is
+//! drawn redder the more stressed it is.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::chain::{ChainConfig, ChainPool, ChainState, FeedingLink, LINK_SIZE},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChainStress>();
+    app.add_event::<ChainBroken>();
+
+    app.add_systems(
+        Update,
+        stress_chains
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Wear on the joint from a link to the next one toward the far end.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct ChainStress(pub f32);
+
+/// Sent when a chain breaks, whether under stress or cut by a hazard.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct ChainBroken {
+    /// Where the joint that gave way was.
+    pub point: Vec2,
+}
+
+fn stress_chains(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<ChainConfig>,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    mut broken: EventWriter<ChainBroken>,
+    // Feeding joints are short on purpose, and disabled ones are in a frozen
+    // middle that doesn't stretch.
+    joint_query: Query<&RevoluteJoint, (Without<FeedingLink>, Without<JointDisabled>)>,
+    transform_query: Query<&Transform>,
+    mut stress_query: Query<&mut ChainStress>,
+) {
+    let mut i = 0;
+    while i < chain_state.chains.len() {
+        let mut break_point = None;
+        for &joint in &chain_state.chains[i].joints {
+            let Ok(joint) = joint_query.get(joint) else {
+                continue;
+            };
+            let Ok([previous, next]) = transform_query.get_many([joint.entity1, joint.entity2])
+            else {
+                continue;
+            };
+            let Ok(mut stress) = stress_query.get_mut(joint.entity1) else {
+                continue;
+            };
+            let anchor1 = previous
+                .transform_point(joint.local_anchor1.extend(0.0))
+                .truncate();
+            let anchor2 = next
+                .transform_point(joint.local_anchor2.extend(0.0))
+                .truncate();
+            let stretch = anchor1.distance(anchor2) / LINK_SIZE;
+            stress.0 =
+                (stress.0 + (stretch - config.stress_tolerance) * time.delta_secs()).max(0.0);
+            if stress.0 >= config.breaking_stress && break_point.is_none() {
+                break_point = Some(anchor1.midpoint(anchor2));
+            }
+        }
+
+        match break_point {
+            Some(point) => {
+                chain_state.chains.remove(i).snap(&mut commands, &mut pool);
+                broken.write(ChainBroken { point });
+            }
+            None => i += 1,
+        }
+    }
+}
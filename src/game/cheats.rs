@@ -0,0 +1,72 @@
+//! Developer cheats.
+//!
+//! Cheats can only be turned on in dev builds, from the debug console or the
+//! pause menu. Once any cheat has been used, a notice stays on screen for the
+//! rest of the session, and scores from it shouldn't be submitted anywhere.
+
+use bevy::prelude::*;
+
+use crate::{screens::Screen, theme::layout::HudAnchor};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Cheats>();
+    app.register_type::<CheatsNotice>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_cheats_notice);
+    app.add_systems(
+        Update,
+        update_cheats_notice.run_if(
+            in_state(Screen::Gameplay)
+                .and(resource_changed::<Cheats>.or(any_match_filter::<Added<CheatsNotice>>)),
+        ),
+    );
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Cheats {
+    /// The player can't be hurt.
+    pub god_mode: bool,
+    /// Chains never expire.
+    pub infinite_hooks: bool,
+    /// Whether any cheat has been turned on this session.
+    pub used: bool,
+}
+
+/// Run condition for systems that limit the player's chains.
+pub fn infinite_hooks(cheats: Res<Cheats>) -> bool {
+    cheats.infinite_hooks
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct CheatsNotice;
+
+fn spawn_cheats_notice(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Cheats Notice"),
+        Text::default(),
+        TextFont::from_font_size(16.0),
+        TextColor(Color::srgb(1.0, 0.4, 0.4)),
+        HudAnchor::TopRight(10.0),
+        CheatsNotice,
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn update_cheats_notice(cheats: Res<Cheats>, mut text: Single<&mut Text, With<CheatsNotice>>) {
+    if !cheats.used {
+        return;
+    }
+    let active: Vec<&str> = [
+        (cheats.god_mode, "God Mode"),
+        (cheats.infinite_hooks, "Infinite Hooks"),
+    ]
+    .into_iter()
+    .filter_map(|(on, name)| on.then_some(name))
+    .collect();
+    text.0 = if active.is_empty() {
+        "Cheats used - scores won't be submitted".to_string()
+    } else {
+        format!("Cheats: {} - scores won't be submitted", active.join(", "))
+    };
+}
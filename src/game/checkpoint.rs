@@ -0,0 +1,269 @@
+//! Checkpoints, and resuming a run from the last one reached.
+//!
+//! Touching a checkpoint autosaves the run through the [`ActivePlatform`]:
+//! which checkpoint it was, where the player stood, the run's mutators, its
+//! [`RunStats`] and its [`Score`] so far. The main menu offers to continue
+//! from the autosave, which puts the player back at the checkpoint with the
+//! run clock where it was and the collectibles already found still gone.
+//! Finishing the run clears the autosave.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        gameplay_error::{GameplayError, OrReport},
+        level::{MAIN_LEVEL_PATH, spawn_level},
+        mutators::RunMutators,
+        objectives::{Collectible, Score, reset_score},
+        player::Player,
+        run_stats::{RunStats, reset_run_stats},
+        secret::Secret,
+        stinger::{PlayStinger, Stinger},
+        trigger_volume::{DetectTriggers, TriggerEntered, trigger_volume},
+    },
+    menus::Menu,
+    packed::{self, PackedKind},
+    platform::ActivePlatform,
+    replay::{ReplayMode, replaying},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Checkpoint>();
+    app.init_resource::<ReachedCheckpoint>();
+    app.init_resource::<PendingResume>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        resume_from_checkpoint
+            .after(spawn_level)
+            .after(reset_run_stats)
+            .after(reset_score),
+    );
+    app.add_systems(
+        Update,
+        (
+            reach_checkpoints
+                .in_set(AppSystems::Update)
+                .in_set(PausableSystems)
+                .after(DetectTriggers),
+            update_checkpoint_colors.run_if(resource_changed::<ReachedCheckpoint>),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        OnEnter(Menu::RunSummary),
+        clear_autosave.run_if(not(replaying)),
+    );
+    app.add_systems(
+        OnEnter(Screen::Results),
+        clear_autosave.run_if(not(replaying)),
+    );
+}
+
+/// The save the run is kept in between checkpoints.
+const AUTOSAVE_FILE: &str = "autosave.sav";
+/// Where builds before the autosave was packed kept it. Read when there's no
+/// newer autosave, so an update doesn't lose the run.
+const LEGACY_AUTOSAVE_FILE: &str = "autosave.ron";
+/// Bumped whenever autosaves written by older builds can't be read.
+const AUTOSAVE_VERSION: u32 = 1;
+/// How close the player has to get to a checkpoint to reach it.
+const CHECKPOINT_RADIUS: f32 = 30.0;
+const CHECKPOINT_SIZE: Vec2 = Vec2::new(12.0, 40.0);
+const CHECKPOINT_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const REACHED_CHECKPOINT_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+
+/// The `index`th checkpoint of the level.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Checkpoint {
+    pub index: usize,
+}
+
+pub fn checkpoint(index: usize, position: Vec2) -> impl Bundle {
+    (
+        Name::new(format!("Checkpoint {index}")),
+        Checkpoint { index },
+        trigger_volume(Collider::circle(CHECKPOINT_RADIUS)),
+        Sprite {
+            color: CHECKPOINT_COLOR,
+            custom_size: Some(CHECKPOINT_SIZE),
+            ..default()
+        },
+        Transform::from_translation(position.extend(0.0)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// The checkpoint the player last reached this run, if any.
+#[derive(Resource, Default, Debug)]
+pub struct ReachedCheckpoint(pub Option<usize>);
+
+/// Everything needed to pick a run back up at a checkpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckpointSave {
+    /// The level file the checkpoint is in.
+    pub level: String,
+    pub checkpoint: usize,
+    pub position: Vec2,
+    pub mutators: u8,
+    pub stats: RunStats,
+    #[serde(default)]
+    pub score: Score,
+}
+
+/// A save to resume from when gameplay starts.
+#[derive(Resource, Default, Debug)]
+pub struct PendingResume(pub Option<CheckpointSave>);
+
+/// The autosave, if there is one to continue from.
+pub fn read_autosave(platform: &ActivePlatform) -> Option<CheckpointSave> {
+    let data = platform
+        .0
+        .read_save(AUTOSAVE_FILE)
+        .or_else(|_| platform.0.read_save(LEGACY_AUTOSAVE_FILE))
+        .ok()?;
+    // Finished runs leave an empty autosave behind.
+    if data.is_empty() {
+        return None;
+    }
+    let save = if packed::is_packed(&data) {
+        packed::unpack::<CheckpointSave>(PackedKind::Autosave, AUTOSAVE_VERSION, &data)
+            .map_err(|error| error.to_string())
+    } else {
+        ron::de::from_bytes::<CheckpointSave>(&data).map_err(|error| error.to_string())
+    };
+    match save {
+        Ok(save) if save.level == MAIN_LEVEL_PATH => Some(save),
+        Ok(_) => None,
+        Err(error) => {
+            warn!("Ignoring corrupt autosave: {error}");
+            None
+        }
+    }
+}
+
+fn reach_checkpoints(
+    mut entered: EventReader<TriggerEntered>,
+    player_query: Query<Entity, With<Player>>,
+    checkpoint_query: Query<(&Checkpoint, &GlobalTransform)>,
+    mut reached: ResMut<ReachedCheckpoint>,
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    mutators: Res<RunMutators>,
+    platform: Res<ActivePlatform>,
+    replay: Res<ReplayMode>,
+    mut stingers: EventWriter<PlayStinger>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if entered.is_empty() {
+        return;
+    }
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "reach_checkpoints", "player")
+    else {
+        return;
+    };
+    for event in entered.read() {
+        if event.other != player {
+            continue;
+        }
+        let Ok((checkpoint, transform)) = checkpoint_query.get(event.volume) else {
+            continue;
+        };
+        if reached.0 == Some(checkpoint.index) {
+            continue;
+        }
+        let checkpoint_position = transform.translation().truncate();
+        reached.0 = Some(checkpoint.index);
+        stingers.write(PlayStinger(Stinger::Checkpoint));
+        // The autosave is the player's own run.
+        if replay.is_replaying() {
+            continue;
+        }
+
+        let save = CheckpointSave {
+            level: MAIN_LEVEL_PATH.to_string(),
+            checkpoint: checkpoint.index,
+            position: checkpoint_position,
+            mutators: mutators.bits(),
+            stats: stats.clone(),
+            score: score.clone(),
+        };
+        let result = packed::pack(PackedKind::Autosave, AUTOSAVE_VERSION, &save)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| {
+                platform
+                    .0
+                    .write_save(AUTOSAVE_FILE, &bytes)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!(
+                "Couldn't autosave at checkpoint {}: {error}",
+                checkpoint.index
+            );
+        }
+    }
+}
+
+fn update_checkpoint_colors(
+    reached: Res<ReachedCheckpoint>,
+    mut checkpoint_query: Query<(&Checkpoint, &mut Sprite)>,
+) {
+    for (checkpoint, mut sprite) in &mut checkpoint_query {
+        sprite.color = if reached.0 == Some(checkpoint.index) {
+            REACHED_CHECKPOINT_COLOR
+        } else {
+            CHECKPOINT_COLOR
+        };
+    }
+}
+
+/// Put the player back at the checkpoint of a save being continued. Starting
+/// over, or any other start, begins from the level's spawn point.
+pub fn resume_from_checkpoint(
+    mut commands: Commands,
+    mut pending: ResMut<PendingResume>,
+    mut reached: ResMut<ReachedCheckpoint>,
+    mut stats: ResMut<RunStats>,
+    mut score: ResMut<Score>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    collectible_query: Query<(Entity, &Collectible)>,
+    mut secret_query: Query<(&mut Secret, &mut Sprite)>,
+) {
+    let Some(save) = pending.0.take() else {
+        reached.0 = None;
+        return;
+    };
+    for mut transform in &mut player_query {
+        transform.translation = save.position.extend(transform.translation.z);
+    }
+    for (entity, collectible) in &collectible_query {
+        if save.score.collected.contains(&collectible.index) {
+            commands.entity(entity).despawn();
+        }
+    }
+    // Secrets already found are open from the start.
+    for (mut secret, mut sprite) in &mut secret_query {
+        if save.score.secrets.contains(&secret.index) {
+            secret.found = true;
+            sprite.color.set_alpha(0.0);
+        }
+    }
+    reached.0 = Some(save.checkpoint);
+    *stats = save.stats;
+    *score = save.score;
+}
+
+fn clear_autosave(platform: Res<ActivePlatform>) {
+    if let Err(error) = platform.0.write_save(AUTOSAVE_FILE, &[]) {
+        warn!("Couldn't clear the autosave: {error}");
+    }
+}
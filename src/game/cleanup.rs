@@ -0,0 +1,68 @@
+//! Checking that leaving gameplay leaves nothing behind.
+//!
+//! Entities that gameplay spawns as it goes, rather than along with the level,
+//! are tagged with what they are in a [`GameplaySpawned`]. They should all be
+//! [`StateScoped`] to [`Screen::Gameplay`], or despawned on the way out by
+//! whatever keeps track of them. With the [`CleanupAudit`] on, the first frame
+//! after leaving gameplay looks for any that survived anyway, and logs and
+//! despawns them.
+
+use bevy::prelude::*;
+
+use crate::screens::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameplaySpawned>();
+    app.register_type::<CleanupAudit>();
+    app.init_resource::<CleanupAudit>();
+
+    app.add_systems(
+        Update,
+        audit_cleanup.run_if(
+            resource_equals(CleanupAudit { enabled: true })
+                .and(state_changed::<Screen>)
+                .and(not(in_state(Screen::Gameplay))),
+        ),
+    );
+}
+
+/// What kind of thing a [`GameplaySpawned`] entity is, for the audit's log.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+pub enum SpawnKind {
+    ChainLink,
+    Joint,
+    Particle,
+    Sound,
+}
+
+/// An entity spawned during gameplay that shouldn't outlive it.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct GameplaySpawned(pub SpawnKind);
+
+/// Whether to check for gameplay entities surviving the end of gameplay. On
+/// in dev builds.
+#[derive(Resource, Reflect, PartialEq, Debug)]
+#[reflect(Resource)]
+pub struct CleanupAudit {
+    pub enabled: bool,
+}
+
+impl Default for CleanupAudit {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(feature = "dev"),
+        }
+    }
+}
+
+fn audit_cleanup(
+    mut commands: Commands,
+    leftover_query: Query<(Entity, &GameplaySpawned, Option<&Name>)>,
+) {
+    for (entity, spawned, name) in &leftover_query {
+        let name = name.map_or_else(|| entity.to_string(), |name| name.to_string());
+        warn!("{:?} `{name}` outlived gameplay, despawning it", spawned.0);
+        commands.entity(entity).despawn();
+    }
+}
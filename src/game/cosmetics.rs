@@ -0,0 +1,251 @@
+//! How the chain and the player look, picked in the wardrobe.
+//!
+//! Each cosmetic other than the defaults is unlocked by an achievement. The
+//! achievements the player has, and the cosmetics they picked, are kept in
+//! [`Progress`], so they're saved along with everything else.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::ChainState,
+        chain_render::ChainRenderConfig,
+        player::{Character, InTow},
+    },
+    persistence::Progress,
+    platform::{ALL_SECRETS, FIRST_HOOK, FIRST_SECRET, FULL_LENGTH},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        apply_chain_color.run_if(resource_changed::<Progress>),
+    );
+    app.add_systems(
+        Update,
+        (apply_player_skin, draw_chain_trails.in_set(PausableSystems))
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long a trail lasts behind the far end of a chain, in seconds.
+const TRAIL_TIME: f32 = 0.3;
+const AFTERGLOW_COLOR: Color = Color::srgb(1.0, 0.75, 0.3);
+const SPARKLE_COLOR: Color = Color::srgb(0.7, 0.9, 1.0);
+
+/// Something to show off with that may have to be unlocked first.
+pub trait Cosmetic: Copy + Eq + Send + Sync + 'static {
+    /// Every choice, the default first.
+    const ALL: &'static [Self];
+
+    fn label(self) -> &'static str;
+
+    /// The achievement that unlocks it, if it needs one.
+    fn achievement(self) -> Option<&'static str>;
+
+    fn is_unlocked(self, progress: &Progress) -> bool {
+        self.achievement()
+            .is_none_or(|id| progress.achievements.contains(id))
+    }
+}
+
+/// The cosmetics the player picked.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[serde(default)]
+pub struct Cosmetics {
+    pub chain_color: ChainColor,
+    pub trail: ChainTrail,
+    pub skin: PlayerSkin,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum ChainColor {
+    #[default]
+    Classic,
+    Ember,
+    Gold,
+    Frost,
+}
+
+impl ChainColor {
+    pub fn color(self) -> Color {
+        match self {
+            Self::Classic => Color::WHITE,
+            Self::Ember => Color::srgb(1.0, 0.45, 0.2),
+            Self::Gold => Color::srgb(1.0, 0.8, 0.25),
+            Self::Frost => Color::srgb(0.55, 0.85, 1.0),
+        }
+    }
+}
+
+impl Cosmetic for ChainColor {
+    const ALL: &'static [Self] = &[Self::Classic, Self::Ember, Self::Gold, Self::Frost];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::Ember => "Ember",
+            Self::Gold => "Gold",
+            Self::Frost => "Frost",
+        }
+    }
+
+    fn achievement(self) -> Option<&'static str> {
+        match self {
+            Self::Classic => None,
+            Self::Ember => Some(FIRST_HOOK),
+            Self::Gold => Some(FULL_LENGTH),
+            Self::Frost => Some(ALL_SECRETS),
+        }
+    }
+}
+
+/// What the far end of a chain leaves behind as it flies.
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum ChainTrail {
+    #[default]
+    None,
+    Afterglow,
+    Sparkle,
+}
+
+impl Cosmetic for ChainTrail {
+    const ALL: &'static [Self] = &[Self::None, Self::Afterglow, Self::Sparkle];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Afterglow => "Afterglow",
+            Self::Sparkle => "Sparkle",
+        }
+    }
+
+    fn achievement(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Afterglow => Some(FULL_LENGTH),
+            Self::Sparkle => Some(FIRST_SECRET),
+        }
+    }
+}
+
+/// A tint for the player's sprite.
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum PlayerSkin {
+    #[default]
+    Ducky,
+    Shadow,
+    Golden,
+}
+
+impl PlayerSkin {
+    pub fn color(self) -> Color {
+        match self {
+            Self::Ducky => Color::WHITE,
+            Self::Shadow => Color::srgb(0.35, 0.35, 0.45),
+            Self::Golden => Color::srgb(1.0, 0.85, 0.3),
+        }
+    }
+}
+
+impl Cosmetic for PlayerSkin {
+    const ALL: &'static [Self] = &[Self::Ducky, Self::Shadow, Self::Golden];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Ducky => "Ducky",
+            Self::Shadow => "Shadow",
+            Self::Golden => "Golden",
+        }
+    }
+
+    fn achievement(self) -> Option<&'static str> {
+        match self {
+            Self::Ducky => None,
+            Self::Shadow => Some(FIRST_SECRET),
+            Self::Golden => Some(ALL_SECRETS),
+        }
+    }
+}
+
+fn apply_chain_color(progress: Res<Progress>, mut config: ResMut<ChainRenderConfig>) {
+    let color = progress.cosmetics.chain_color.color();
+    if config.color != color {
+        config.color = color;
+    }
+}
+
+/// Skins are for the hooking character, and give way to the tint while
+/// they're in tow.
+fn apply_player_skin(
+    progress: Res<Progress>,
+    mut character_query: Query<(&mut Sprite, &Character, &InTow)>,
+) {
+    let color = progress.cosmetics.skin.color();
+    for (mut sprite, &character, in_tow) in &mut character_query {
+        if character == Character::Hooker && !in_tow.towed() && sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+/// A point the far end of a chain passed through.
+struct TrailPoint {
+    tip: Entity,
+    position: Vec2,
+    age: f32,
+}
+
+fn draw_chain_trails(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    progress: Res<Progress>,
+    chain_state: Res<ChainState>,
+    transform_query: Query<&GlobalTransform>,
+    mut points: Local<Vec<TrailPoint>>,
+) {
+    let color = match progress.cosmetics.trail {
+        ChainTrail::None => {
+            points.clear();
+            return;
+        }
+        ChainTrail::Afterglow => AFTERGLOW_COLOR,
+        ChainTrail::Sparkle => SPARKLE_COLOR,
+    };
+
+    for point in points.iter_mut() {
+        point.age += time.delta_secs();
+    }
+    points.retain(|point| point.age < TRAIL_TIME);
+    for tip in chain_state
+        .chains
+        .iter()
+        .filter_map(|chain| chain.links.last())
+    {
+        if let Ok(transform) = transform_query.get(*tip) {
+            points.push(TrailPoint {
+                tip: *tip,
+                position: transform.translation().truncate(),
+                age: 0.0,
+            });
+        }
+    }
+
+    let fade = |point: &TrailPoint| 1.0 - point.age / TRAIL_TIME;
+    for (i, point) in points.iter().enumerate() {
+        // Sparkles are dots that shrink away.
+        if progress.cosmetics.trail == ChainTrail::Sparkle {
+            gizmos.circle_2d(point.position, 3.0 * fade(point), color);
+            continue;
+        }
+        // Points are pushed in order, so the next one of the same tip is the
+        // next point along its trail.
+        if let Some(next) = points[i + 1..].iter().find(|next| next.tip == point.tip) {
+            gizmos.line_2d(point.position, next.position, color.with_alpha(fade(point)));
+        }
+    }
+}
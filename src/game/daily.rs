@@ -0,0 +1,57 @@
+//! The daily challenge: one run per day with a seed and mutator combo derived
+//! from the date, so everyone plays the same rules on the same day.
+
+use bevy::prelude::*;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::mutators::RunMutators;
+
+const SECS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// A daily challenge, identified by its day number since the Unix epoch (UTC).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+pub struct DailyChallenge {
+    pub day: u64,
+}
+
+impl DailyChallenge {
+    /// Today's challenge, according to the system clock.
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        Self {
+            day: secs / SECS_PER_DAY,
+        }
+    }
+
+    /// The run seed for this day. Consecutive days get unrelated seeds.
+    pub fn seed(self) -> u64 {
+        // SplitMix64 finalizer, so day N and N+1 don't roll similar mutators.
+        let mut z = self.day.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// The mutator combo for this day.
+    pub fn mutators(self) -> RunMutators {
+        RunMutators::from_seed(self.seed())
+    }
+
+    /// The challenge date formatted as `YYYY-MM-DD`.
+    pub fn date(self) -> String {
+        // Civil-from-days, see <https://howardhinnant.github.io/date_algorithms.html>.
+        let z = self.day as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
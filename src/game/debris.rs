@@ -0,0 +1,103 @@
+//! Debris that stays around as set dressing once it settles.
+//!
+//! Anything given a [`Debris`] component, like the links of an expired chain,
+//! keeps simulating until it comes to rest. Then it loses its physics and is
+//! left as a plain sprite, so the level fills up with the player's mess
+//! without slowing down. Only so much debris simulates at once; past that,
+//! the oldest pieces settle first. The oldest settled pieces are cleared once
+//! the level's budget is used up.
+
+use std::collections::VecDeque;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Debris>();
+    app.init_resource::<DebrisPile>();
+
+    app.add_systems(
+        Update,
+        (track_new_debris, settle_debris)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), clear_debris_pile);
+}
+
+/// How many pieces of debris can simulate at once.
+const MAX_MOVING_DEBRIS: usize = 60;
+/// How many settled pieces a level keeps.
+const MAX_SETTLED_DEBRIS: usize = 400;
+/// Debris slower than this, in pixels per second, counts as resting.
+const REST_SPEED: f32 = 5.0;
+/// Debris spinning slower than this, in radians per second, counts as resting.
+const REST_ANGULAR_SPEED: f32 = 0.2;
+/// How long debris must rest before it settles for good.
+const SETTLE_TIME: f32 = 1.0;
+
+/// A physics body that becomes set dressing once it comes to rest.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Debris {
+    resting_for: f32,
+}
+
+/// The level's debris, oldest first.
+#[derive(Resource, Default)]
+struct DebrisPile {
+    moving: VecDeque<Entity>,
+    settled: VecDeque<Entity>,
+}
+
+fn track_new_debris(mut pile: ResMut<DebrisPile>, debris_query: Query<Entity, Added<Debris>>) {
+    pile.moving.extend(&debris_query);
+}
+
+fn settle_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pile: ResMut<DebrisPile>,
+    mut debris_query: Query<(&mut Debris, &LinearVelocity, &AngularVelocity)>,
+) {
+    let DebrisPile { moving, settled } = &mut *pile;
+    // Settle the oldest pieces when there are too many.
+    let mut over_budget = moving.len().saturating_sub(MAX_MOVING_DEBRIS);
+    moving.retain(|&entity| {
+        let Ok((mut debris, velocity, angular_velocity)) = debris_query.get_mut(entity) else {
+            // Despawned by something else.
+            return false;
+        };
+        let resting =
+            velocity.0.length() < REST_SPEED && angular_velocity.0.abs() < REST_ANGULAR_SPEED;
+        debris.resting_for = if resting {
+            debris.resting_for + time.delta_secs()
+        } else {
+            0.0
+        };
+        if debris.resting_for < SETTLE_TIME && over_budget == 0 {
+            return true;
+        }
+
+        over_budget = over_budget.saturating_sub(1);
+        commands
+            .entity(entity)
+            .remove::<(Debris, RigidBody, Collider, SweptCcd)>();
+        settled.push_back(entity);
+        false
+    });
+
+    while settled.len() > MAX_SETTLED_DEBRIS {
+        let oldest = settled.pop_front().unwrap();
+        commands.entity(oldest).despawn();
+    }
+}
+
+/// Debris is despawned with the level.
+fn clear_debris_pile(mut pile: ResMut<DebrisPile>) {
+    *pile = DebrisPile::default();
+}
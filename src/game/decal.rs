@@ -0,0 +1,197 @@
+//! Marks left behind on the level: scorch marks, skid marks and chips.
+//!
+//! Gameplay code writes [`SpawnDecal`] events. Decals fade out over time and
+//! are drawn beneath everything else. Only [`MAX_DECALS`] exist at once; past
+//! that, the oldest decal is reused for the new one.
+
+use std::{collections::VecDeque, str::FromStr};
+
+use bevy::prelude::*;
+
+#[cfg(feature = "dev")]
+use crate::dev_tools::console::ConsoleAppExt;
+use crate::{
+    AppSystems, PausableSystems,
+    game::game_timer::{AddGameTimer, GameTimer},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<SpawnDecal>();
+    app.register_type::<Decal>();
+    app.add_game_timer::<Decal>();
+    app.init_resource::<DecalPool>();
+
+    #[cfg(feature = "dev")]
+    app.add_console_command(
+        "decal",
+        "decal <scorch|skid|chip> <x> <y>: leave a decal at a position",
+        spawn_decal_command,
+    );
+
+    app.add_systems(
+        Update,
+        (spawn_decals, fade_decals)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), clear_decal_pool);
+}
+
+/// How many decals can exist at once.
+pub const MAX_DECALS: usize = 200;
+/// Decals are drawn behind everything at depth 0.
+const DECAL_Z: f32 = -0.5;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+pub enum DecalKind {
+    /// Left by explosions.
+    Scorch,
+    /// Left by chains sliding along a surface.
+    Skid,
+    /// Knocked out of something by a hard hit.
+    Chip,
+}
+
+impl DecalKind {
+    fn color(self) -> Color {
+        match self {
+            Self::Scorch => Color::srgba(0.05, 0.05, 0.05, 0.6),
+            Self::Skid => Color::srgba(0.2, 0.2, 0.2, 0.4),
+            Self::Chip => Color::srgba(0.35, 0.3, 0.25, 0.8),
+        }
+    }
+
+    fn size(self) -> Vec2 {
+        match self {
+            Self::Scorch => Vec2::splat(40.0),
+            Self::Skid => Vec2::new(14.0, 3.0),
+            Self::Chip => Vec2::splat(5.0),
+        }
+    }
+
+    /// Seconds until the decal has faded out.
+    fn lifetime(self) -> f32 {
+        match self {
+            Self::Scorch => 20.0,
+            Self::Skid => 8.0,
+            Self::Chip => 30.0,
+        }
+    }
+}
+
+impl FromStr for DecalKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "scorch" => Ok(Self::Scorch),
+            "skid" => Ok(Self::Skid),
+            "chip" => Ok(Self::Chip),
+            _ => Err(format!("unknown decal `{name}`")),
+        }
+    }
+}
+
+/// Leave a decal at `position`, turned `angle` radians.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct SpawnDecal {
+    pub kind: DecalKind,
+    pub position: Vec2,
+    pub angle: f32,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Decal {
+    kind: DecalKind,
+    lifetime: Timer,
+}
+
+impl GameTimer for Decal {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.lifetime
+    }
+}
+
+/// Every decal in the level, oldest first.
+#[derive(Resource, Default)]
+struct DecalPool(VecDeque<Entity>);
+
+fn decal(event: &SpawnDecal) -> impl Bundle {
+    (
+        Name::new(format!("{:?} Decal", event.kind)),
+        Decal {
+            kind: event.kind,
+            lifetime: Timer::from_seconds(event.kind.lifetime(), TimerMode::Once),
+        },
+        Sprite {
+            color: event.kind.color(),
+            custom_size: Some(event.kind.size()),
+            ..default()
+        },
+        Transform::from_translation(event.position.extend(DECAL_Z))
+            .with_rotation(Quat::from_rotation_z(event.angle)),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn spawn_decals(
+    mut commands: Commands,
+    mut events: EventReader<SpawnDecal>,
+    mut pool: ResMut<DecalPool>,
+) {
+    for event in events.read() {
+        let entity = if pool.0.len() >= MAX_DECALS {
+            let oldest = pool.0.pop_front().unwrap();
+            commands.entity(oldest).insert(decal(event));
+            oldest
+        } else {
+            commands.spawn(decal(event)).id()
+        };
+        pool.0.push_back(entity);
+    }
+}
+
+fn fade_decals(
+    mut commands: Commands,
+    mut pool: ResMut<DecalPool>,
+    mut decal_query: Query<(Entity, &Decal, &mut Sprite)>,
+) {
+    for (entity, decal, mut sprite) in &mut decal_query {
+        if decal.lifetime.finished() {
+            commands.entity(entity).despawn();
+            pool.0.retain(|&pooled| pooled != entity);
+            continue;
+        }
+        let color = decal.kind.color();
+        sprite.color = color.with_alpha(color.alpha() * decal.lifetime.fraction_remaining());
+    }
+}
+
+/// Decals are despawned with the level.
+fn clear_decal_pool(mut pool: ResMut<DecalPool>) {
+    pool.0.clear();
+}
+
+#[cfg(feature = "dev")]
+fn spawn_decal_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [kind, x, y] = args else {
+        return Err("usage: decal <scorch|skid|chip> <x> <y>".to_string());
+    };
+    let kind: DecalKind = kind.parse()?;
+    let position = Vec2::new(
+        x.parse()
+            .map_err(|_| format!("<x> must be a number, got `{x}`"))?,
+        y.parse()
+            .map_err(|_| format!("<y> must be a number, got `{y}`"))?,
+    );
+    world.send_event(SpawnDecal {
+        kind,
+        position,
+        angle: 0.0,
+    });
+    Ok(format!("left a {kind:?} decal at {position}"))
+}
@@ -0,0 +1,175 @@
+//! A rolling log of what just happened in gameplay, for the recap on the
+//! retry menu.
+//!
+//! Chains being fired, hooking on and breaking, checkpoints and the player
+//! starting to drop fast are each logged with the run time they happened at.
+//! Only the latest [`MAX_ENTRIES`] are kept, and the log starts over with
+//! every attempt. How the attempt ended is the [`Defeat`] itself, so it
+//! isn't logged here.
+//!
+//! [`Defeat`]: crate::game::objectives::Defeat
+
+use std::collections::VecDeque;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{ChainAttached, ChainFired, ChainHooked},
+        chain_stress::ChainBroken,
+        checkpoint::ReachedCheckpoint,
+        gameplay_error::{GameplayError, OrReport},
+        hazards::HazardKind,
+        highlight::HookTarget,
+        player::Player,
+        run_stats::RunStats,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GameplayLog>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), clear_log);
+    app.add_systems(
+        Update,
+        (
+            log_chain_events,
+            log_checkpoints.run_if(resource_changed::<ReachedCheckpoint>),
+            log_falls,
+        )
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How many entries the log keeps.
+pub const MAX_ENTRIES: usize = 24;
+/// How fast the player has to be dropping for it to count as a fall, in
+/// pixels per second.
+const FALL_SPEED: f32 = 900.0;
+
+/// Something that happened, and when.
+#[derive(Copy, Clone, Debug)]
+pub struct LogEntry {
+    /// The run time it happened at, in seconds.
+    pub time: f32,
+    pub event: LoggedEvent,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum LoggedEvent {
+    ChainFired,
+    Hooked,
+    Grabbed,
+    /// A chain was cut, by this kind of hazard if it's known.
+    ChainBroke(Option<HazardKind>),
+    Checkpoint(usize),
+    Falling,
+}
+
+impl LoggedEvent {
+    pub fn describe(self) -> String {
+        match self {
+            Self::ChainFired => "Fired a chain".to_string(),
+            Self::Hooked => "Hooked on".to_string(),
+            Self::Grabbed => "Grabbed something".to_string(),
+            Self::ChainBroke(Some(kind)) => format!("A {} cut the chain", kind.label()),
+            Self::ChainBroke(None) => "The chain snapped".to_string(),
+            Self::Checkpoint(index) => format!("Reached checkpoint {}", index + 1),
+            Self::Falling => "Started falling".to_string(),
+        }
+    }
+}
+
+/// The latest notable gameplay events, oldest first.
+#[derive(Resource, Default, Debug)]
+pub struct GameplayLog(pub VecDeque<LogEntry>);
+
+impl GameplayLog {
+    fn push(&mut self, time: f32, event: LoggedEvent) {
+        if self.0.len() == MAX_ENTRIES {
+            self.0.pop_front();
+        }
+        self.0.push_back(LogEntry { time, event });
+    }
+}
+
+fn clear_log(mut log: ResMut<GameplayLog>) {
+    log.0.clear();
+}
+
+/// How far from a chain break a hazard can be to have been what cut it.
+const CUT_DISTANCE: f32 = 60.0;
+
+fn log_chain_events(
+    stats: Res<RunStats>,
+    mut log: ResMut<GameplayLog>,
+    mut fired: EventReader<ChainFired>,
+    mut hooked: EventReader<ChainHooked>,
+    mut broken: EventReader<ChainBroken>,
+    attached_query: Query<&ChainAttached>,
+    target_query: Query<&HookTarget>,
+    hazard_query: Query<(&HazardKind, &GlobalTransform)>,
+) {
+    for _ in fired.read() {
+        log.push(stats.time, LoggedEvent::ChainFired);
+    }
+    for event in hooked.read() {
+        let grabbed = attached_query
+            .get(event.link)
+            .ok()
+            .and_then(|attached| target_query.get(attached.target).ok())
+            .is_some_and(|target| *target == HookTarget::Grabbable);
+        let event = if grabbed {
+            LoggedEvent::Grabbed
+        } else {
+            LoggedEvent::Hooked
+        };
+        log.push(stats.time, event);
+    }
+    for event in broken.read() {
+        let cutter = hazard_query
+            .iter()
+            .filter(|(kind, _)| kind.cuts_chains())
+            .find(|(_, transform)| {
+                transform.translation().truncate().distance(event.point) <= CUT_DISTANCE
+            })
+            .map(|(&kind, _)| kind);
+        log.push(stats.time, LoggedEvent::ChainBroke(cutter));
+    }
+}
+
+fn log_checkpoints(
+    stats: Res<RunStats>,
+    reached: Res<ReachedCheckpoint>,
+    mut log: ResMut<GameplayLog>,
+) {
+    if let Some(index) = reached.0 {
+        log.push(stats.time, LoggedEvent::Checkpoint(index));
+    }
+}
+
+/// Log when the player starts dropping fast, once per drop.
+fn log_falls(
+    stats: Res<RunStats>,
+    mut log: ResMut<GameplayLog>,
+    mut falling: Local<bool>,
+    player_query: Query<Option<&LinearVelocity>, With<Player>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "log_falls", "player")
+    else {
+        return;
+    };
+    let dropping = player.is_some_and(|velocity| velocity.y < -FALL_SPEED);
+    if dropping && !*falling {
+        log.push(stats.time, LoggedEvent::Falling);
+    }
+    *falling = dropping;
+}
@@ -0,0 +1,287 @@
+//! Birds, fish and butterflies that live in a level, purely for atmosphere.
+//!
+//! Levels list their [`FaunaData`], and each flock wanders around the area it
+//! was given. Creatures scatter away from the player, from chain links and
+//! from blasts, which are wherever a scorch mark gets left, then drift back
+//! home once it's safe. Nothing can touch them and they touch nothing.
+//!
+//! A level never has more than [`MAX_CREATURES`], however many its flocks ask
+//! for, and creatures out of view are hidden and left where they are until
+//! the camera comes back.
+
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::ChainLink,
+        decal::{DecalKind, SpawnDecal},
+        game_rng::{GameRng, RngStream},
+        gameplay_error::{GameplayError, OrReport},
+        level_data::FaunaData,
+        player::Player,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Creature>();
+
+    app.add_systems(
+        Update,
+        (cull_creatures, startle_creatures, steer_creatures)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The most creatures a level has at once.
+pub const MAX_CREATURES: usize = 48;
+/// How close something has to be for a creature to flee it, in pixels.
+const FLEE_RADIUS: f32 = 160.0;
+/// How far away a blast still scatters creatures, in pixels.
+const BLAST_RADIUS: f32 = 400.0;
+/// How long a creature keeps fleeing a blast, in seconds.
+const BLAST_FLEE_TIME: f32 = 1.5;
+/// How quickly a wandering creature turns, in radians per second.
+const WANDER_TURN: f32 = 3.0;
+/// How far past the edges of the view creatures keep moving, in pixels.
+const CULL_MARGIN: f32 = 100.0;
+const CREATURE_Z: f32 = 0.5;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum CreatureKind {
+    #[default]
+    Bird,
+    /// Stays in the water, which is the area of its flock.
+    Fish,
+    Butterfly,
+}
+
+impl CreatureKind {
+    /// How fast it wanders, in pixels per second.
+    fn cruise_speed(self) -> f32 {
+        match self {
+            Self::Bird => 80.0,
+            Self::Fish => 40.0,
+            Self::Butterfly => 30.0,
+        }
+    }
+
+    /// How fast it flees, in pixels per second.
+    fn flee_speed(self) -> f32 {
+        match self {
+            Self::Bird => 400.0,
+            Self::Fish => 200.0,
+            Self::Butterfly => 120.0,
+        }
+    }
+
+    /// How quickly it changes course, per second.
+    fn agility(self) -> f32 {
+        match self {
+            Self::Bird => 3.0,
+            Self::Fish => 5.0,
+            Self::Butterfly => 8.0,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Bird => Color::srgb(0.15, 0.15, 0.2),
+            Self::Fish => Color::srgb(0.9, 0.55, 0.2),
+            Self::Butterfly => Color::srgb(0.95, 0.85, 0.3),
+        }
+    }
+
+    fn size(self) -> Vec2 {
+        match self {
+            Self::Bird => Vec2::new(10.0, 4.0),
+            Self::Fish => Vec2::new(9.0, 5.0),
+            Self::Butterfly => Vec2::splat(5.0),
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct Creature {
+    kind: CreatureKind,
+    /// Where it lives.
+    home: Rect,
+    velocity: Vec2,
+    /// The way it's wandering, as an angle.
+    heading: f32,
+    /// Where the last blast near it went off, and how much longer it runs
+    /// from it.
+    blast: Option<(Vec2, f32)>,
+}
+
+/// Spawns the creatures of every flock, up to [`MAX_CREATURES`] in all.
+pub fn spawn_fauna(commands: &mut Commands, flocks: &[FaunaData]) {
+    let mut budget = MAX_CREATURES;
+    for (i, data) in flocks.iter().enumerate() {
+        let count = data.count.min(budget);
+        budget -= count;
+        for j in 0..count {
+            commands.spawn(creature(i, j, data));
+        }
+    }
+}
+
+fn creature(flock: usize, index: usize, data: &FaunaData) -> impl Bundle {
+    // Spread the flock over its area without needing any randomness yet.
+    let golden = 0.618_034;
+    let spread = Vec2::new(
+        (index as f32 * golden).fract(),
+        (index as f32 + 0.5) / data.count as f32,
+    );
+    let home = data.rect();
+    let position = home.min + spread * home.size();
+    let heading = index as f32 * golden * TAU;
+    (
+        Name::new(format!("Creature {flock}.{index}")),
+        Creature {
+            kind: data.kind,
+            home,
+            velocity: Vec2::from_angle(heading) * data.kind.cruise_speed(),
+            heading,
+            blast: None,
+        },
+        Sprite::from_color(data.kind.color(), data.kind.size()),
+        Transform::from_translation(position.extend(CREATURE_Z)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// Hide creatures out of view. Hidden creatures aren't moved.
+fn cull_creatures(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
+    mut creature_query: Query<(&Transform, &mut Visibility), (With<Creature>, Without<Camera2d>)>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    const SYSTEM: &str = "cull_creatures";
+    let Some(window) = window_query
+        .single()
+        .or_report(&mut errors, SYSTEM, "primary window")
+    else {
+        return;
+    };
+    let Some((camera_transform, projection)) =
+        camera_query
+            .single()
+            .or_report(&mut errors, SYSTEM, "camera")
+    else {
+        return;
+    };
+    let scale = match projection {
+        Projection::Orthographic(projection) => projection.scale,
+        _ => 1.0,
+    };
+    let view = Rect::from_center_size(
+        camera_transform.translation.truncate(),
+        window.size() * scale,
+    )
+    .inflate(CULL_MARGIN);
+    for (transform, mut visibility) in &mut creature_query {
+        let visible = if view.contains(transform.translation.truncate()) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(visible);
+    }
+}
+
+fn startle_creatures(
+    mut decals: EventReader<SpawnDecal>,
+    mut creature_query: Query<(&Transform, &mut Creature)>,
+) {
+    for decal in decals.read() {
+        if decal.kind != DecalKind::Scorch {
+            continue;
+        }
+        for (transform, mut creature) in &mut creature_query {
+            let position = transform.translation.truncate();
+            if position.distance(decal.position) <= BLAST_RADIUS {
+                creature.blast = Some((decal.position, BLAST_FLEE_TIME));
+            }
+        }
+    }
+}
+
+fn steer_creatures(
+    time: Res<Time>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    link_query: Query<&GlobalTransform, With<ChainLink>>,
+    mut creature_query: Query<(&mut Transform, &mut Creature, &Visibility)>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "steer_creatures", "player")
+    else {
+        return;
+    };
+    let rng = stream.get(&game_rng, "fauna");
+    let player = player.translation().truncate();
+
+    for (mut transform, mut creature, visibility) in &mut creature_query {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let kind = creature.kind;
+
+        if let Some((_, remaining)) = &mut creature.blast {
+            *remaining -= dt;
+        }
+        creature.blast = creature.blast.filter(|&(_, remaining)| remaining > 0.0);
+
+        let nearest_threat = std::iter::once(player)
+            .chain(
+                link_query
+                    .iter()
+                    .map(|transform| transform.translation().truncate()),
+            )
+            .filter(|threat| threat.distance(position) <= FLEE_RADIUS)
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+        let threat = creature.blast.map(|(blast, _)| blast).or(nearest_threat);
+
+        creature.heading += rng.random_range(-1.0..1.0) * WANDER_TURN * dt;
+        let desired = if let Some(threat) = threat {
+            (position - threat).normalize_or(Vec2::Y) * kind.flee_speed()
+        } else if !creature.home.contains(position) {
+            (creature.home.center() - position).normalize_or_zero() * kind.cruise_speed()
+        } else {
+            Vec2::from_angle(creature.heading) * kind.cruise_speed()
+        };
+        let mut velocity = creature.velocity;
+        velocity.smooth_nudge(&desired, kind.agility(), dt);
+        creature.velocity = velocity;
+
+        let mut position = position + velocity * dt;
+        if kind == CreatureKind::Fish {
+            // Fish don't leave the water, even to get away.
+            position = position.clamp(creature.home.min, creature.home.max);
+        }
+        transform.translation = position.extend(transform.translation.z);
+        if velocity.x != 0.0 {
+            transform.scale.x = velocity.x.signum();
+        }
+    }
+}
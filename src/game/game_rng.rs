@@ -0,0 +1,75 @@
+//! Randomness that plays out the same way every time from the same seed.
+//!
+//! Gameplay draws its random numbers from [`GameRng`] rather than from
+//! `rand::rng()`, so a replay with the same seed sees the same sparks, the
+//! same sounds and everything else the same. The seed is picked anew every
+//! time a level starts, unless something asked for a particular one with
+//! [`GameRng::seed_next_level`].
+//!
+//! Systems take their own [`RngStream`] off it, so what each of them draws
+//! doesn't depend on the order they happen to run in.
+
+use bevy::prelude::*;
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::screens::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GameRng>();
+    app.add_systems(OnEnter(Screen::Gameplay), reseed);
+}
+
+/// The seed gameplay randomness comes from.
+#[derive(Resource, Default, Debug)]
+pub struct GameRng {
+    seed: u64,
+    next_seed: Option<u64>,
+    /// Bumped on every reseed, so streams know to start over.
+    generation: u32,
+}
+
+impl GameRng {
+    /// The seed of the level being played.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Use `seed` for the next level that starts, instead of a random one.
+    pub fn seed_next_level(&mut self, seed: u64) {
+        self.next_seed = Some(seed);
+    }
+}
+
+/// Picks the seed for the level that's starting.
+pub(crate) fn reseed(mut rng: ResMut<GameRng>) {
+    rng.seed = rng.next_seed.take().unwrap_or_else(rand::random);
+    rng.generation = rng.generation.wrapping_add(1);
+}
+
+/// One system's share of the [`GameRng`], kept in a [`Local`] and named so
+/// no two systems draw the same numbers.
+#[derive(Default)]
+pub struct RngStream {
+    generation: u32,
+    rng: Option<StdRng>,
+}
+
+impl RngStream {
+    /// The stream called `name`, started over from the seed if the level has
+    /// been reseeded since it was last used.
+    pub fn get(&mut self, game_rng: &GameRng, name: &str) -> &mut StdRng {
+        if self.generation != game_rng.generation {
+            self.generation = game_rng.generation;
+            self.rng = None;
+        }
+        self.rng
+            .get_or_insert_with(|| StdRng::seed_from_u64(game_rng.seed ^ fnv1a(name)))
+    }
+}
+
+/// A hash of `name` that's the same on every machine and every build.
+fn fnv1a(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -0,0 +1,38 @@
+//! Timers that run on gameplay time.
+//!
+//! Components holding a [`Timer`] implement [`GameTimer`] and are registered
+//! with [`AddGameTimer::add_game_timer`] instead of ticking their own timers.
+//! They're then all ticked together in [`AppSystems::TickTimers`], only while
+//! the game isn't paused, and by [`Time<Virtual>`]: slowing that down slows
+//! every gameplay timer with it.
+
+use bevy::{ecs::component::Mutable, prelude::*};
+
+use crate::{AppSystems, PausableSystems};
+
+/// A component with a timer that runs on gameplay time.
+pub trait GameTimer: Component<Mutability = Mutable> {
+    fn timer_mut(&mut self) -> &mut Timer;
+}
+
+pub trait AddGameTimer {
+    /// Tick the timers of every `T` each frame the game isn't paused.
+    fn add_game_timer<T: GameTimer>(&mut self) -> &mut Self;
+}
+
+impl AddGameTimer for App {
+    fn add_game_timer<T: GameTimer>(&mut self) -> &mut Self {
+        self.add_systems(
+            Update,
+            tick_game_timers::<T>
+                .in_set(AppSystems::TickTimers)
+                .in_set(PausableSystems),
+        )
+    }
+}
+
+fn tick_game_timers<T: GameTimer>(time: Res<Time>, mut timer_query: Query<&mut T>) {
+    for mut timer in &mut timer_query {
+        timer.timer_mut().tick(time.delta());
+    }
+}
@@ -0,0 +1,175 @@
+//! Problems that stop a gameplay system from doing its job, like having no
+//! player to fire chains from. Systems report them with [`OrReport`] rather
+//! than quietly returning, so a broken setup shows up right away instead of
+//! as clicks that do nothing.
+//!
+//! Every gameplay system that needs the player, the camera or the window
+//! looks them up this way. HUD text and other widgets a module spawns for
+//! itself are still taken as a `Single`, since they can't go missing
+//! without the module being broken.
+//!
+//! Every distinct problem is logged once, every report counts towards the
+//! [`GAMEPLAY_ERRORS`] diagnostic, and dev builds also show a toast.
+
+use std::{collections::HashSet, fmt};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    ecs::query::QuerySingleError,
+    prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<GameplayError>();
+    app.init_resource::<ReportedErrors>();
+    app.register_diagnostic(Diagnostic::new(GAMEPLAY_ERRORS));
+
+    app.add_systems(PostUpdate, report_gameplay_errors);
+
+    #[cfg(feature = "dev")]
+    {
+        app.register_type::<ErrorToast>();
+        app.add_systems(PostUpdate, show_error_toasts.after(report_gameplay_errors));
+        app.add_systems(Update, fade_error_toasts);
+    }
+}
+
+/// How many gameplay errors have been reported so far.
+pub const GAMEPLAY_ERRORS: DiagnosticPath = DiagnosticPath::const_new("gameplay_errors");
+
+/// A gameplay system that couldn't run because something it needs is missing
+/// or ambiguous.
+#[derive(Event, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct GameplayError {
+    /// The system that gave up.
+    pub system: &'static str,
+    pub problem: Problem,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Problem {
+    /// There was none of something there should be exactly one of, e.g. the
+    /// player.
+    Missing(&'static str),
+    /// There were several of something there should be exactly one of.
+    Duplicate(&'static str),
+}
+
+impl fmt::Display for GameplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.problem {
+            Problem::Missing(what) => write!(f, "{}: there's no {what}", self.system),
+            Problem::Duplicate(what) => write!(f, "{}: there's more than one {what}", self.system),
+        }
+    }
+}
+
+/// Turns a failed lookup into a [`GameplayError`].
+pub trait OrReport<T> {
+    /// The value, or `None` after reporting why there isn't one. `what` names
+    /// the thing looked up, e.g. `"player"`.
+    fn or_report(
+        self,
+        errors: &mut EventWriter<GameplayError>,
+        system: &'static str,
+        what: &'static str,
+    ) -> Option<T>;
+}
+
+impl<T> OrReport<T> for Result<T, QuerySingleError> {
+    fn or_report(
+        self,
+        errors: &mut EventWriter<GameplayError>,
+        system: &'static str,
+        what: &'static str,
+    ) -> Option<T> {
+        self.map_err(|error| {
+            let problem = match error {
+                QuerySingleError::NoEntities(_) => Problem::Missing(what),
+                QuerySingleError::MultipleEntities(_) => Problem::Duplicate(what),
+            };
+            errors.write(GameplayError { system, problem });
+        })
+        .ok()
+    }
+}
+
+/// Which errors have been logged already, and how many have been reported in
+/// total.
+#[derive(Resource, Default, Debug)]
+struct ReportedErrors {
+    logged: HashSet<GameplayError>,
+    total: u64,
+}
+
+/// Log each distinct error the first time it's reported, and keep count.
+fn report_gameplay_errors(
+    mut errors: EventReader<GameplayError>,
+    mut reported: ResMut<ReportedErrors>,
+    mut diagnostics: Diagnostics,
+) {
+    for error in errors.read() {
+        reported.total += 1;
+        if reported.logged.insert(*error) {
+            warn!("{error}");
+        }
+    }
+    let total = reported.total;
+    diagnostics.add_measurement(&GAMEPLAY_ERRORS, || total as f64);
+}
+
+/// How long an error toast stays up, in seconds.
+#[cfg(feature = "dev")]
+const TOAST_TIME: f32 = 5.0;
+
+#[cfg(feature = "dev")]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ErrorToast(Timer);
+
+/// Put up a toast for each new error, stacked down from the top of the
+/// screen.
+#[cfg(feature = "dev")]
+fn show_error_toasts(
+    mut commands: Commands,
+    mut errors: EventReader<GameplayError>,
+    mut shown: Local<HashSet<GameplayError>>,
+    toast_query: Query<(), With<ErrorToast>>,
+) {
+    let mut count = toast_query.iter().count();
+    for error in errors.read() {
+        if !shown.insert(*error) {
+            continue;
+        }
+        commands.spawn((
+            Name::new("Error Toast"),
+            ErrorToast(Timer::from_seconds(TOAST_TIME, TimerMode::Once)),
+            Text::new(error.to_string()),
+            TextFont::from_font_size(16.0),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(12.0),
+                top: Val::Px(12.0 + 32.0 * count as f32),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.6, 0.1, 0.1, 0.9)),
+            GlobalZIndex(10),
+        ));
+        count += 1;
+    }
+}
+
+/// Toasts go by real time, so they still go away while paused.
+#[cfg(feature = "dev")]
+fn fade_error_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toast_query: Query<(Entity, &mut ErrorToast)>,
+) {
+    for (entity, mut toast) in &mut toast_query {
+        if toast.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
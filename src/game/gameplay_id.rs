@@ -0,0 +1,139 @@
+//! Stable gameplay IDs for entities that other sessions need to refer to.
+//!
+//! An [`Entity`] is only meaningful inside one running world: its index is
+//! reused after despawns and differs between sessions. Netcode, replays and
+//! save states instead refer to players, chains and dynamic props by their
+//! [`GameplayId`], and look up the current entity in [`GameplayIds`].
+//!
+//! IDs are handed out in spawn order and restart for every run, so the same
+//! level spawns the same IDs each time it is played.
+
+use std::collections::HashMap;
+
+use avian2d::prelude::*;
+use bevy::{
+    ecs::{component::HookContext, world::DeferredWorld},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems,
+    game::{chain::ChainLink, player::Player},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameplayId>();
+    app.init_resource::<GameplayIds>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_gameplay_ids);
+    app.add_systems(
+        Update,
+        assign_gameplay_ids
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// An entity's identity that survives entity reallocation and can be sent
+/// over the network or written to disk.
+#[derive(
+    Component,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Ord,
+    PartialOrd,
+    Debug,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+#[reflect(Component)]
+#[component(on_insert = remember_id, on_replace = forget_id)]
+pub struct GameplayId(pub u64);
+
+/// Maps between [`GameplayId`]s and the entities that currently have them.
+#[derive(Resource, Default, Debug)]
+pub struct GameplayIds {
+    next: u64,
+    entities: HashMap<GameplayId, Entity>,
+    ids: HashMap<Entity, GameplayId>,
+}
+
+impl GameplayIds {
+    /// Reserve a new ID. Insert it on an entity to register it.
+    pub fn allocate(&mut self) -> GameplayId {
+        let id = GameplayId(self.next);
+        self.next += 1;
+        id
+    }
+
+    pub fn entity(&self, id: GameplayId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    pub fn id(&self, entity: Entity) -> Option<GameplayId> {
+        self.ids.get(&entity).copied()
+    }
+}
+
+fn remember_id(mut world: DeferredWorld, context: HookContext) {
+    let Some(&id) = world.get::<GameplayId>(context.entity) else {
+        return;
+    };
+    let mut ids = world.resource_mut::<GameplayIds>();
+    if let Some(previous) = ids.entities.insert(id, context.entity) {
+        warn!(
+            "{id:?} was given to {} while {previous} still had it",
+            context.entity
+        );
+    }
+    ids.ids.insert(context.entity, id);
+    // Keep allocations ahead of IDs inserted from elsewhere, e.g. a save state.
+    ids.next = ids.next.max(id.0 + 1);
+}
+
+fn forget_id(mut world: DeferredWorld, context: HookContext) {
+    let Some(&id) = world.get::<GameplayId>(context.entity) else {
+        return;
+    };
+    let mut ids = world.resource_mut::<GameplayIds>();
+    if ids.entities.get(&id) == Some(&context.entity) {
+        ids.entities.remove(&id);
+    }
+    ids.ids.remove(&context.entity);
+}
+
+fn reset_gameplay_ids(mut ids: ResMut<GameplayIds>) {
+    ids.next = 0;
+}
+
+/// Give players, chain links and dynamic props an ID once they are spawned.
+fn assign_gameplay_ids(
+    mut commands: Commands,
+    mut ids: ResMut<GameplayIds>,
+    new_query: Query<
+        (Entity, Option<&RigidBody>, Has<Player>, Has<ChainLink>),
+        (
+            Without<GameplayId>,
+            Or<(Added<Player>, Added<ChainLink>, Added<RigidBody>)>,
+        ),
+    >,
+) {
+    let mut new: Vec<Entity> = new_query
+        .iter()
+        .filter(|&(_, body, is_player, is_link)| {
+            is_player || is_link || body == Some(&RigidBody::Dynamic)
+        })
+        .map(|(entity, ..)| entity)
+        .collect();
+    // Query order depends on archetypes, so sort to keep IDs repeatable.
+    new.sort_unstable_by_key(|entity| entity.index());
+    for entity in new {
+        commands.entity(entity).insert(ids.allocate());
+    }
+}
@@ -0,0 +1,213 @@
+//! Moving level pieces: spinning saws, crushers and platforms.
+//!
+//! All of them are kinematic bodies. Ones with a [`PathFollower`] travel
+//! along its path, and saws spin on the spot. Their velocities are set
+//! in the [`KinematicMotion`] set, which pauses with the rest of the game, so
+//! avian carries whatever rests on them along and stops them while paused.
+//! Saws and crushers cut through any chain they touch. Platforms are surfaces
+//! like any obstacle, so chains hook onto them and ride along.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems, Pause,
+    game::{
+        assists::Assists,
+        chain::{ChainLink, ChainPool, ChainState, Layer},
+        chain_stress::ChainBroken,
+        highlight::HookTarget,
+        level_data::HazardData,
+        path::PathFollower,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HazardKind>();
+
+    app.configure_sets(
+        Update,
+        KinematicMotion
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(Update, spin_saws.in_set(KinematicMotion));
+    app.add_systems(
+        Update,
+        cut_chains
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnEnter(Pause(true)), stop_kinematic_bodies);
+}
+
+/// How fast saws spin, in radians per second.
+const SAW_SPIN: f32 = 8.0;
+
+/// Systems that set the velocities of kinematic level pieces.
+#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct KinematicMotion;
+
+#[derive(
+    Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect,
+)]
+#[reflect(Component)]
+pub enum HazardKind {
+    /// A spinning blade.
+    #[default]
+    Saw,
+    /// A heavy block, usually moving back and forth.
+    Crusher,
+    /// A moving surface to hook onto and stand on.
+    Platform,
+}
+
+impl HazardKind {
+    /// All kinds, in the order the editor cycles through them.
+    pub const ALL: [Self; 3] = [Self::Saw, Self::Crusher, Self::Platform];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Saw => "saw",
+            Self::Crusher => "crusher",
+            Self::Platform => "platform",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&kind| kind == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+
+    /// Whether chains touching it are cut.
+    pub fn cuts_chains(self) -> bool {
+        matches!(self, Self::Saw | Self::Crusher)
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Saw => Color::srgb(0.9, 0.3, 0.2),
+            Self::Crusher => Color::srgb(0.5, 0.35, 0.3),
+            Self::Platform => Color::srgb(0.6, 0.75, 0.6),
+        }
+    }
+}
+
+pub fn hazard(data: &HazardData) -> impl Bundle {
+    let (collider, layers) = match data.kind {
+        HazardKind::Saw => (
+            Collider::circle(data.size.x / 2.0),
+            CollisionLayers::new(
+                [Layer::Hazard],
+                [Layer::ChainLink, Layer::Player, Layer::DynamicProp],
+            ),
+        ),
+        HazardKind::Crusher => (
+            Collider::rectangle(data.size.x, data.size.y),
+            CollisionLayers::new(
+                [Layer::Hazard],
+                [Layer::ChainLink, Layer::Player, Layer::DynamicProp],
+            ),
+        ),
+        HazardKind::Platform => (
+            Collider::rectangle(data.size.x, data.size.y),
+            CollisionLayers::new(
+                [Layer::StaticObstacle],
+                [Layer::ChainLink, Layer::Player, Layer::DynamicProp],
+            ),
+        ),
+    };
+    (
+        Name::new(format!("{:?}", data.kind)),
+        data.kind,
+        HookTarget::Surface,
+        RigidBody::Kinematic,
+        collider,
+        layers,
+        Friction::new(0.9),
+        PathFollower::new(data.shape(), data.speed, data.easing, data.loop_mode),
+        Sprite {
+            color: data.kind.color(),
+            custom_size: Some(data.size),
+            ..default()
+        },
+        Transform::from_translation(data.position.extend(0.0)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn spin_saws(assists: Res<Assists>, mut saw_query: Query<(&HazardKind, &mut AngularVelocity)>) {
+    for (kind, mut velocity) in &mut saw_query {
+        if *kind == HazardKind::Saw {
+            velocity.0 = SAW_SPIN * assists.hazard_speed;
+        }
+    }
+}
+
+/// Snap every chain that touches a saw or crusher.
+fn cut_chains(
+    mut commands: Commands,
+    collisions: Collisions,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    mut broken: EventWriter<ChainBroken>,
+    hazard_query: Query<(Entity, &HazardKind)>,
+    link_query: Query<(), With<ChainLink>>,
+    body_query: Query<(&Position, &Rotation)>,
+) {
+    for (hazard, kind) in &hazard_query {
+        if !kind.cuts_chains() {
+            continue;
+        }
+        for pair in collisions.collisions_with(hazard) {
+            let link = if pair.collider1 == hazard {
+                pair.collider2
+            } else {
+                pair.collider1
+            };
+            if !link_query.contains(link) {
+                continue;
+            }
+            let Some(index) = chain_state
+                .chains
+                .iter()
+                .position(|chain| chain.links.contains(&link))
+            else {
+                continue;
+            };
+            // Contact points are kept relative to the first collider.
+            let Ok((position, rotation)) = body_query.get(pair.collider1) else {
+                continue;
+            };
+            let Some(point) = pair
+                .manifolds
+                .first()
+                .and_then(|manifold| manifold.points.first())
+                .map(|contact| contact.global_point1(position, rotation))
+            else {
+                continue;
+            };
+            chain_state
+                .chains
+                .remove(index)
+                .snap(&mut commands, &mut pool);
+            broken.write(ChainBroken { point });
+        }
+    }
+}
+
+/// Physics keeps running while paused, so moving pieces are stopped until
+/// [`KinematicMotion`] picks up again.
+fn stop_kinematic_bodies(
+    mut body_query: Query<(&mut LinearVelocity, &mut AngularVelocity), With<HazardKind>>,
+) {
+    for (mut linear, mut angular) in &mut body_query {
+        linear.0 = Vec2::ZERO;
+        angular.0 = 0.0;
+    }
+}
@@ -0,0 +1,114 @@
+//! Outlining the hook target under the aim.
+//!
+//! A ray is cast from the player toward where they're aiming, and the first
+//! [`HookTarget`] it hits gets an outline in the color of what a chain would
+//! do with it. The outline is a slightly larger copy of the target's sprite
+//! drawn just behind it.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems, game::aim::PlayerAim, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HookTarget>();
+    app.register_type::<HookOutline>();
+    app.init_resource::<Highlighted>();
+
+    app.add_systems(
+        Update,
+        highlight_hook_target
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), clear_highlight);
+}
+
+/// How far around the target the outline reaches, in pixels.
+const OUTLINE_WIDTH: f32 = 3.0;
+
+/// Something a chain can hook onto, and what happens when it does.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Debug)]
+#[reflect(Component)]
+pub enum HookTarget {
+    /// A point made for hanging from.
+    Anchor,
+    /// A static surface the chain can catch on.
+    Surface,
+    /// Something loose the chain can drag around.
+    Grabbable,
+}
+
+impl HookTarget {
+    fn outline_color(self) -> Color {
+        match self {
+            Self::Anchor => Color::srgb(0.4, 0.9, 1.0),
+            Self::Surface => Color::srgb(1.0, 1.0, 1.0),
+            Self::Grabbable => Color::srgb(1.0, 0.7, 0.2),
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HookOutline;
+
+/// The target currently outlined, and its outline.
+#[derive(Resource, Default)]
+struct Highlighted(Option<(Entity, Entity)>);
+
+fn highlight_hook_target(
+    mut commands: Commands,
+    aim: Res<PlayerAim>,
+    spatial_query: SpatialQuery,
+    mut highlighted: ResMut<Highlighted>,
+    target_query: Query<(&HookTarget, &Sprite)>,
+) {
+    let hit = aim.target.and_then(|target| {
+        let direction = Dir2::new(target - aim.origin).ok()?;
+        spatial_query.cast_ray_predicate(
+            aim.origin,
+            direction,
+            aim.origin.distance(target),
+            true,
+            &SpatialQueryFilter::default(),
+            &|entity| target_query.contains(entity),
+        )
+    });
+    let target = hit.map(|hit| hit.entity);
+    if highlighted.0.map(|(entity, _)| entity) == target {
+        return;
+    }
+
+    if let Some((_, outline)) = highlighted.0.take() {
+        commands.entity(outline).try_despawn();
+    }
+    let Some((target, (&kind, sprite))) =
+        target.and_then(|target| Some((target, target_query.get(target).ok()?)))
+    else {
+        return;
+    };
+    let Some(size) = sprite.custom_size else {
+        return;
+    };
+    let outline = commands
+        .spawn((
+            Name::new("Hook Outline"),
+            HookOutline,
+            Sprite {
+                color: kind.outline_color(),
+                custom_size: Some(size + Vec2::splat(OUTLINE_WIDTH * 2.0)),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -0.1),
+            ChildOf(target),
+        ))
+        .id();
+    highlighted.0 = Some((target, outline));
+}
+
+/// The outline is despawned with its target.
+fn clear_highlight(mut highlighted: ResMut<Highlighted>) {
+    highlighted.0 = None;
+}
@@ -0,0 +1,199 @@
+//! Hints for players stuck on a section of a level.
+//!
+//! A level's sections are the stretches from its start to the first
+//! checkpoint, between consecutive checkpoints, and from the last checkpoint
+//! to the exit. Every loss counts against the section the player was in.
+//! After [`FAILURES_BEFORE_HINT`] losses in the same section, the next attempt
+//! at it opens with a hint and rings the hook point closest to the middle of
+//! the section, until the player moves on to the next one. Hints can be
+//! turned off in the settings.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        checkpoint::{Checkpoint, ReachedCheckpoint, resume_from_checkpoint},
+        gameplay_error::{GameplayError, OrReport},
+        highlight::HookTarget,
+        objectives::GoalZone,
+        player::Player,
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::layout::HudAnchor,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HintSettings>();
+    app.register_type::<HintPopup>();
+    app.init_resource::<HintSettings>();
+    app.init_resource::<SectionFailures>();
+    app.init_resource::<SuggestedHook>();
+
+    app.add_systems(OnEnter(Menu::Retry), count_failure);
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        offer_hint.after(resume_from_checkpoint),
+    );
+    app.add_systems(
+        Update,
+        (
+            (drop_hint_after_section, fade_hint_popups)
+                .in_set(AppSystems::Update)
+                .in_set(PausableSystems),
+            draw_suggested_hook.in_set(AppSystems::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnEnter(Screen::Results), forget_failures);
+}
+
+/// How many times a section has to be lost before hints show up for it.
+const FAILURES_BEFORE_HINT: u32 = 3;
+/// How long the hint text stays up, in seconds.
+const POPUP_TIME: f32 = 6.0;
+const HINT_RING_COLOR: Color = Color::srgb(1.0, 0.9, 0.3);
+
+/// Whether hints are shown at all.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct HintSettings {
+    pub enabled: bool,
+}
+
+impl Default for HintSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Losses per section, keyed by the checkpoint the section starts at.
+#[derive(Resource, Default, Debug)]
+struct SectionFailures(HashMap<Option<usize>, u32>);
+
+/// The hook point suggested for a section, while the player is in it.
+#[derive(Resource, Default, Debug)]
+struct SuggestedHook(Option<(Option<usize>, Vec2)>);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HintPopup(Timer);
+
+fn count_failure(reached: Res<ReachedCheckpoint>, mut failures: ResMut<SectionFailures>) {
+    *failures.0.entry(reached.0).or_default() += 1;
+}
+
+fn forget_failures(mut failures: ResMut<SectionFailures>) {
+    failures.0.clear();
+}
+
+/// Suggest a hook point for the section the attempt starts in, if it has
+/// been lost often enough.
+fn offer_hint(
+    mut commands: Commands,
+    settings: Res<HintSettings>,
+    failures: Res<SectionFailures>,
+    reached: Res<ReachedCheckpoint>,
+    mut suggested: ResMut<SuggestedHook>,
+    player_query: Query<&Transform, With<Player>>,
+    checkpoint_query: Query<(&Checkpoint, &Transform)>,
+    goal_query: Query<&Transform, With<GoalZone>>,
+    target_query: Query<(&HookTarget, &Transform)>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    suggested.0 = None;
+    let section = reached.0;
+    if !settings.enabled
+        || failures.0.get(&section).copied().unwrap_or_default() < FAILURES_BEFORE_HINT
+    {
+        return;
+    }
+
+    let next_index = section.map_or(0, |index| index + 1);
+    let Some(end) = checkpoint_query
+        .iter()
+        .find(|(checkpoint, _)| checkpoint.index == next_index)
+        .map(|(_, transform)| transform)
+        .or_else(|| goal_query.iter().next())
+        .map(|transform| transform.translation.truncate())
+    else {
+        return;
+    };
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "offer_hint", "player")
+    else {
+        return;
+    };
+    let middle = player.translation.truncate().midpoint(end);
+    // Anchors are made for hanging from, so they're suggested over surfaces.
+    let Some(hook) = target_query
+        .iter()
+        .filter(|(target, _)| **target != HookTarget::Grabbable)
+        .min_by(|(a_target, a), (b_target, b)| {
+            let a_anchor = **a_target == HookTarget::Anchor;
+            let b_anchor = **b_target == HookTarget::Anchor;
+            b_anchor.cmp(&a_anchor).then(
+                a.translation
+                    .truncate()
+                    .distance(middle)
+                    .total_cmp(&b.translation.truncate().distance(middle)),
+            )
+        })
+        .map(|(_, transform)| transform.translation.truncate())
+    else {
+        return;
+    };
+    suggested.0 = Some((section, hook));
+
+    commands.spawn((
+        Name::new("Hint Popup"),
+        HintPopup(Timer::from_seconds(POPUP_TIME, TimerMode::Once)),
+        Node {
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        HudAnchor::Bottom(40.0),
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+        children![(
+            Name::new("Hint Text"),
+            Text::new("Stuck? Try hooking the ringed spot."),
+            TextFont::from_font_size(20.0),
+            TextColor(HINT_RING_COLOR),
+        )],
+    ));
+}
+
+fn drop_hint_after_section(reached: Res<ReachedCheckpoint>, mut suggested: ResMut<SuggestedHook>) {
+    if suggested.0.is_some_and(|(section, _)| section != reached.0) {
+        suggested.0 = None;
+    }
+}
+
+fn fade_hint_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popup_query: Query<(Entity, &mut HintPopup)>,
+) {
+    for (entity, mut popup) in &mut popup_query {
+        if popup.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn draw_suggested_hook(time: Res<Time>, suggested: Res<SuggestedHook>, mut gizmos: Gizmos) {
+    let Some((_, hook)) = suggested.0 else {
+        return;
+    };
+    let pulse = 1.0 + 0.2 * (time.elapsed_secs() * 4.0).sin();
+    gizmos.circle_2d(
+        Isometry2d::from_translation(hook),
+        24.0 * pulse,
+        HINT_RING_COLOR,
+    );
+}
@@ -0,0 +1,120 @@
+//! Faint markers where hooks attached on an earlier attempt, for planning a
+//! faster route.
+//!
+//! With [`Settings::hook_ghosts`] on, each attempt at the level starts with a
+//! small numbered marker at every point a hook attached on either the last
+//! attempt or the fastest completion. The last attempt's come from its
+//! replay, and the fastest's are kept in the level's [`LevelProgress`].
+//! Nothing else of the attempt is shown, and the markers don't collide with
+//! anything.
+//!
+//! [`LevelProgress`]: crate::persistence::LevelProgress
+
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        calibration::Calibration,
+        level::{MAIN_LEVEL_PATH, spawn_level},
+        sandbox::Sandbox,
+    },
+    persistence::{Progress, Settings},
+    platform::ActivePlatform,
+    replay::{ReplayMode, read_last_replay},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HookGhost>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        spawn_hook_ghosts.after(spawn_level),
+    );
+}
+
+const GHOST_SIZE: f32 = 10.0;
+const GHOST_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.25);
+const GHOST_Z: f32 = 0.4;
+const GHOST_FONT_SIZE: f32 = 12.0;
+
+/// Whose hooks to show.
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+pub enum HookGhosts {
+    #[default]
+    Off,
+    LastAttempt,
+    BestTime,
+}
+
+impl HookGhosts {
+    pub const ALL: [Self; 3] = [Self::Off, Self::LastAttempt, Self::BestTime];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::LastAttempt => "Last Attempt",
+            Self::BestTime => "Best Time",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&ghosts| ghosts == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+}
+
+/// A point a hook attached on an earlier attempt.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HookGhost;
+
+fn spawn_hook_ghosts(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    progress: Res<Progress>,
+    platform: Res<ActivePlatform>,
+    mode: Res<ReplayMode>,
+    sandbox: Res<Sandbox>,
+    calibration: Res<Calibration>,
+) {
+    // Only attempts at the level have anything to compare with.
+    if mode.is_replaying() || sandbox.active || calibration.active {
+        return;
+    }
+    let hooks = match settings.hook_ghosts {
+        HookGhosts::Off => return,
+        HookGhosts::LastAttempt => read_last_replay(&platform)
+            .map(|replay| replay.hooks().to_vec())
+            .unwrap_or_default(),
+        HookGhosts::BestTime => progress
+            .levels
+            .get(MAIN_LEVEL_PATH)
+            .map(|level| level.best_hooks.clone())
+            .unwrap_or_default(),
+    };
+    for (i, point) in hooks.into_iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("Hook Ghost {i}")),
+            HookGhost,
+            Transform::from_translation(point.extend(GHOST_Z)),
+            Visibility::default(),
+            StateScoped(Screen::Gameplay),
+            children![
+                (
+                    Sprite::from_color(GHOST_COLOR, Vec2::splat(GHOST_SIZE)),
+                    Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_4)),
+                ),
+                (
+                    Text2d::new((i + 1).to_string()),
+                    TextFont::from_font_size(GHOST_FONT_SIZE),
+                    TextColor(GHOST_COLOR),
+                    Transform::from_xyz(0.0, GHOST_SIZE, 0.0),
+                ),
+            ],
+        ));
+    }
+}
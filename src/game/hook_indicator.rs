@@ -0,0 +1,143 @@
+//! A ring around the far end of a freshly fired chain.
+//!
+//! The ring shrinks as the hook head gets farther from the player, showing
+//! how much of the chain's reach is left. When the head catches on something
+//! the ring flashes green; if it doesn't within a moment, it flashes red.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        aim::PlayerAim,
+        chain::{ChainConfig, ChainLink, LINK_SIZE},
+        game_timer::{AddGameTimer, GameTimer},
+        hook_types::HookRegistry,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HookHead>();
+    app.register_type::<HookFlash>();
+    app.add_game_timer::<HookHead>();
+    app.add_game_timer::<HookFlash>();
+
+    app.add_systems(
+        Update,
+        (update_hook_heads, draw_hook_flashes)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long a hook head can fly before it counts as a miss.
+const FLIGHT_TIME: f32 = 0.6;
+/// The ring's radius with the whole reach left.
+const RING_RADIUS: f32 = 24.0;
+const RING_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.7);
+const HIT_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+const MISS_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+const FLASH_DURATION: f32 = 0.3;
+
+/// The far end of a chain that hasn't caught on anything yet.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HookHead {
+    flight: Timer,
+}
+
+impl GameTimer for HookHead {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.flight
+    }
+}
+
+impl Default for HookHead {
+    fn default() -> Self {
+        Self {
+            flight: Timer::from_seconds(FLIGHT_TIME, TimerMode::Once),
+        }
+    }
+}
+
+/// Shows where a hook head hit or missed.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HookFlash {
+    color: Color,
+    timer: Timer,
+}
+
+impl GameTimer for HookFlash {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+fn update_hook_heads(
+    mut commands: Commands,
+    aim: Res<PlayerAim>,
+    config: Res<ChainConfig>,
+    hooks: Res<HookRegistry>,
+    collisions: Collisions,
+    mut gizmos: Gizmos,
+    head_query: Query<(Entity, &HookHead, &GlobalTransform)>,
+    link_query: Query<(), With<ChainLink>>,
+) {
+    let reach = hooks.active(&config).max_links(&config) as f32 * LINK_SIZE;
+    for (entity, head, transform) in &head_query {
+        let position = transform.translation().truncate();
+        let hit = collisions.collisions_with(entity).any(|pair| {
+            let other = if pair.collider1 == entity {
+                pair.collider2
+            } else {
+                pair.collider1
+            };
+            !link_query.contains(other) && !pair.manifolds.is_empty()
+        });
+
+        if hit || head.flight.finished() {
+            commands.entity(entity).remove::<HookHead>();
+            commands.spawn((
+                Name::new("Hook Flash"),
+                HookFlash {
+                    color: if hit { HIT_COLOR } else { MISS_COLOR },
+                    timer: Timer::from_seconds(FLASH_DURATION, TimerMode::Once),
+                },
+                Transform::from_translation(position.extend(0.0)),
+                StateScoped(Screen::Gameplay),
+            ));
+            continue;
+        }
+
+        let reach_left = 1.0 - (position.distance(aim.origin) / reach).min(1.0);
+        gizmos.circle_2d(
+            Isometry2d::from_translation(position),
+            RING_RADIUS * reach_left,
+            RING_COLOR,
+        );
+    }
+}
+
+fn draw_hook_flashes(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    flash_query: Query<(Entity, &HookFlash, &Transform)>,
+) {
+    for (entity, flash, transform) in &flash_query {
+        if flash.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let fraction = flash.timer.fraction();
+        gizmos.circle_2d(
+            Isometry2d::from_translation(transform.translation.truncate()),
+            RING_RADIUS * (1.0 + fraction),
+            flash.color.with_alpha(1.0 - fraction),
+        );
+    }
+}
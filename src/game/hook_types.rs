@@ -0,0 +1,134 @@
+//! Hook types: what a fired chain is like, from how fast it flies to what it
+//! hooks onto.
+//!
+//! Every type is defined in [`HOOK_TYPES_PATH`], by the id
+//! [`ChainConfig::hook_type`] picks it with, so new ones don't need any code.
+//! The [`HookRegistry`] is kept up to date with the file, and chains take a
+//! copy of their type when they're fired. Ids missing from the file get
+//! [`HookType::default`], the plain hook.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{asset_tracking::LoadResource, game::chain::ChainConfig, ron_asset::RonAssetLoader};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HookAssets>();
+    app.init_asset::<HookTypes>();
+    app.register_asset_loader(RonAssetLoader::<HookTypes>::new(&["hooks.ron"]));
+    app.load_resource::<HookAssets>();
+    app.init_resource::<HookRegistry>();
+
+    app.add_systems(
+        Update,
+        update_hook_registry.run_if(on_event::<AssetEvent<HookTypes>>),
+    );
+}
+
+/// The hook type definitions, relative to the assets folder.
+pub const HOOK_TYPES_PATH: &str = "hooks/hook_types.hooks.ron";
+
+/// What chains of one type are like.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HookType {
+    /// What players know it as.
+    pub name: String,
+    /// How fast a fired chain sets off toward its target, in pixels per
+    /// second. Every link starts at this velocity rather than being pushed,
+    /// so it's the same however long or heavy the chain is and whatever the
+    /// physics tick rate.
+    pub throw_speed: f32,
+    /// The most links a chain of this type has, if fewer than
+    /// [`ChainConfig::max_links`].
+    pub max_links: Option<usize>,
+    /// Whether it hooks onto level geometry.
+    pub hooks_obstacles: bool,
+    /// Whether it grabs loose props and tows them along.
+    pub grabs_props: bool,
+    /// How long a chain lasts before it starts to decay, in seconds.
+    pub lifetime: f32,
+    /// What its rope is tinted, on top of the chain color.
+    pub color: Color,
+}
+
+impl Default for HookType {
+    fn default() -> Self {
+        Self {
+            name: "Hook".to_string(),
+            throw_speed: 100.0,
+            max_links: None,
+            hooks_obstacles: true,
+            grabs_props: true,
+            lifetime: 5.0,
+            color: Color::WHITE,
+        }
+    }
+}
+
+impl HookType {
+    /// The most links chains of this type have with `config`.
+    pub fn max_links(&self, config: &ChainConfig) -> usize {
+        self.max_links
+            .map_or(config.max_links, |max| max.min(config.max_links))
+    }
+}
+
+/// Every hook type by id, as it's written in the file.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct HookTypes(pub BTreeMap<String, HookType>);
+
+/// The hook types chains can be fired as.
+#[derive(Resource, Debug, Default)]
+pub struct HookRegistry {
+    types: BTreeMap<String, HookType>,
+    fallback: HookType,
+}
+
+impl HookRegistry {
+    /// The hook type `id`, or the plain hook if there isn't one.
+    pub fn get(&self, id: &str) -> &HookType {
+        self.types.get(id).unwrap_or(&self.fallback)
+    }
+
+    /// The hook type chains are fired as with `config`.
+    pub fn active(&self, config: &ChainConfig) -> &HookType {
+        self.get(&config.hook_type)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.types.keys().map(String::as_str)
+    }
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct HookAssets {
+    #[dependency]
+    types: Handle<HookTypes>,
+}
+
+impl FromWorld for HookAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            types: assets.load(HOOK_TYPES_PATH),
+        }
+    }
+}
+
+fn update_hook_registry(
+    hook_assets: Option<Res<HookAssets>>,
+    types: Res<Assets<HookTypes>>,
+    mut registry: ResMut<HookRegistry>,
+) {
+    let Some(hook_assets) = hook_assets else {
+        return;
+    };
+    if let Some(types) = types.get(&hook_assets.types) {
+        registry.types = types.0.clone();
+    }
+}
@@ -0,0 +1,111 @@
+//! Letting whatever a chain hooks react to it, without the chain knowing
+//! what it is.
+//!
+//! The chain sends entity events to what its far end hooks onto: [`Hooked`]
+//! as it catches, [`Reeled`] for every link taken in or fed out after, and
+//! [`Unhooked`] once it lets go, however that happens. Anything can observe
+//! them. Components that want callbacks instead implement [`Hookable`] and
+//! are registered with [`AddHookable::add_hookable`], the same way timers
+//! are registered with [`AddGameTimer`].
+//!
+//! [`AddGameTimer`]: crate::game::game_timer::AddGameTimer
+
+use bevy::{ecs::component::Mutable, prelude::*};
+
+use crate::game::chain::ChainAttached;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(send_unhooked);
+}
+
+/// Sent to an entity when the far end of a chain hooks onto it.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct Hooked {
+    /// The link that hooked on.
+    pub link: Entity,
+    pub point: Vec2,
+}
+
+/// Sent to an entity hooked by a chain when the chain is reeled in or out by
+/// a link.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct Reeled {
+    /// The link hooked on.
+    pub link: Entity,
+    /// How many links the chain has now.
+    pub links: usize,
+    /// Whether the chain got shorter rather than longer.
+    pub reeled_in: bool,
+}
+
+/// Sent to an entity a chain was hooked onto when it lets go, whether it was
+/// released, snapped or broke apart.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct Unhooked {
+    /// The link that was hooked on.
+    pub link: Entity,
+}
+
+/// A component with its own reaction to chains hooking its entity. Every
+/// callback does nothing unless implemented.
+pub trait Hookable: Component<Mutability = Mutable> {
+    fn on_hook(&mut self, _entity: Entity, _event: &Hooked, _commands: &mut Commands) {}
+    fn on_reel(&mut self, _entity: Entity, _event: &Reeled, _commands: &mut Commands) {}
+    fn on_release(&mut self, _entity: Entity, _event: &Unhooked, _commands: &mut Commands) {}
+}
+
+pub trait AddHookable {
+    /// Call the [`Hookable`] callbacks of every `T` whose entity is hooked.
+    fn add_hookable<T: Hookable>(&mut self) -> &mut Self;
+}
+
+impl AddHookable for App {
+    fn add_hookable<T: Hookable>(&mut self) -> &mut Self {
+        self.add_observer(on_hook::<T>)
+            .add_observer(on_reel::<T>)
+            .add_observer(on_release::<T>)
+    }
+}
+
+fn on_hook<T: Hookable>(
+    trigger: Trigger<Hooked>,
+    mut commands: Commands,
+    mut query: Query<&mut T>,
+) {
+    if let Ok(mut hookable) = query.get_mut(trigger.target()) {
+        hookable.on_hook(trigger.target(), trigger.event(), &mut commands);
+    }
+}
+
+fn on_reel<T: Hookable>(
+    trigger: Trigger<Reeled>,
+    mut commands: Commands,
+    mut query: Query<&mut T>,
+) {
+    if let Ok(mut hookable) = query.get_mut(trigger.target()) {
+        hookable.on_reel(trigger.target(), trigger.event(), &mut commands);
+    }
+}
+
+fn on_release<T: Hookable>(
+    trigger: Trigger<Unhooked>,
+    mut commands: Commands,
+    mut query: Query<&mut T>,
+) {
+    if let Ok(mut hookable) = query.get_mut(trigger.target()) {
+        hookable.on_release(trigger.target(), trigger.event(), &mut commands);
+    }
+}
+
+/// Every way a chain lets go ends with its far end losing its
+/// [`ChainAttached`], so that's where [`Unhooked`] is sent from.
+fn send_unhooked(
+    trigger: Trigger<OnRemove, ChainAttached>,
+    mut commands: Commands,
+    attached_query: Query<&ChainAttached>,
+) {
+    let link = trigger.target();
+    if let Ok(attached) = attached_query.get(link) {
+        commands.trigger_targets(Unhooked { link }, attached.target);
+    }
+}
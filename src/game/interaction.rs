@@ -0,0 +1,225 @@
+//! Prompts for things the player can use, and using them.
+//!
+//! Anything with an [`Interactable`] can be used once the player is within
+//! its range. Of everything in range, the one with the highest priority wins,
+//! and the closest among those. Only that one gets a prompt, naming the
+//! button for [`InputAction::Interact`], and pressing it sends an
+//! [`Interacted`] for that entity. Whatever the entity is (a lever, a sign, a
+//! character to talk to, something to pick up) reads those and checks whether
+//! it's the target.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        gameplay_error::{GameplayError, OrReport},
+        player::Player,
+    },
+    input::{
+        ActionInput, InputAction, InputMap,
+        gamepad::{ActiveController, ControllerProfiles, active_preset},
+    },
+    screens::Screen,
+    theme::layout::HudAnchor,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Interactable>();
+    app.register_type::<InteractionPrompt>();
+    app.register_type::<InteractionPromptIcon>();
+    app.register_type::<InteractionPromptText>();
+    app.init_resource::<FocusedInteractable>();
+    app.add_event::<Interacted>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_prompt);
+    app.add_systems(
+        Update,
+        (
+            (focus_interactable, interact)
+                .chain()
+                .in_set(AppSystems::Update)
+                .in_set(PausableSystems),
+            update_prompt.in_set(AppSystems::Update),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), clear_focus);
+}
+
+const PROMPT_COLOR: Color = Color::srgb(0.95, 0.95, 0.85);
+const PROMPT_ICON_SIZE: f32 = 24.0;
+
+/// Something the player can use when they're close enough.
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct Interactable {
+    /// What using it does, e.g. "Pull".
+    pub prompt: String,
+    /// Shown next to the prompt text.
+    pub icon: Option<Handle<Image>>,
+    /// Wins over lower priorities in range, however much closer they are.
+    pub priority: i32,
+    /// How close the player has to be, in pixels.
+    pub range: f32,
+}
+
+/// Sent when the player uses an [`Interactable`].
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Interacted {
+    pub target: Entity,
+    pub by: Entity,
+}
+
+/// The interactable the prompt is showing, if any.
+#[derive(Resource, Default, Debug)]
+pub struct FocusedInteractable(pub Option<Entity>);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct InteractionPrompt;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct InteractionPromptIcon;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct InteractionPromptText;
+
+fn spawn_prompt(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Interaction Prompt"),
+        Node {
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            column_gap: Px(8.0),
+            ..default()
+        },
+        HudAnchor::Bottom(90.0),
+        Visibility::Hidden,
+        InteractionPrompt,
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+        children![
+            (
+                Name::new("Interaction Prompt Icon"),
+                Node {
+                    width: Px(PROMPT_ICON_SIZE),
+                    height: Px(PROMPT_ICON_SIZE),
+                    display: Display::None,
+                    ..default()
+                },
+                ImageNode::default(),
+                InteractionPromptIcon,
+            ),
+            (
+                Name::new("Interaction Prompt Text"),
+                Text::default(),
+                TextFont::from_font_size(20.0),
+                TextColor(PROMPT_COLOR),
+                InteractionPromptText,
+            )
+        ],
+    ));
+}
+
+/// Pick the interactable in range with the highest priority, and the closest
+/// of those.
+fn focus_interactable(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    interactable_query: Query<(Entity, &Interactable, &GlobalTransform)>,
+    mut focused: ResMut<FocusedInteractable>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "focus_interactable", "player")
+    else {
+        return;
+    };
+    let position = player.translation().truncate();
+    let best = interactable_query
+        .iter()
+        .map(|(entity, interactable, transform)| {
+            let distance = transform.translation().truncate().distance(position);
+            (entity, interactable, distance)
+        })
+        .filter(|(_, interactable, distance)| *distance <= interactable.range)
+        .min_by(|(_, a, a_distance), (_, b, b_distance)| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a_distance.total_cmp(b_distance))
+        })
+        .map(|(entity, _, _)| entity);
+    if focused.0 != best {
+        focused.0 = best;
+    }
+}
+
+fn interact(
+    input: ActionInput,
+    player_query: Query<Entity, With<Player>>,
+    focused: Res<FocusedInteractable>,
+    mut interacted: EventWriter<Interacted>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(target) = focused
+        .0
+        .filter(|_| input.just_pressed(InputAction::Interact))
+    else {
+        return;
+    };
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "interact", "player")
+    else {
+        return;
+    };
+    interacted.write(Interacted { target, by: player });
+}
+
+fn update_prompt(
+    focused: Res<FocusedInteractable>,
+    interactable_query: Query<&Interactable>,
+    input_map: Res<InputMap>,
+    active: Res<ActiveController>,
+    profiles: Res<ControllerProfiles>,
+    name_query: Query<&Name, With<Gamepad>>,
+    mut visibility: Single<&mut Visibility, With<InteractionPrompt>>,
+    mut icon: Single<(&mut ImageNode, &mut Node), With<InteractionPromptIcon>>,
+    mut text: Single<&mut Text, With<InteractionPromptText>>,
+) {
+    let Some(interactable) = focused
+        .0
+        .and_then(|entity| interactable_query.get(entity).ok())
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Inherited);
+
+    let (image, node) = &mut *icon;
+    match &interactable.icon {
+        Some(handle) => {
+            image.image = handle.clone();
+            node.display = Display::Flex;
+        }
+        None => node.display = Display::None,
+    }
+
+    let preset = active_preset(&active, &profiles, &name_query);
+    let label = format!(
+        "[{}] {}",
+        input_map.prompt(InputAction::Interact, preset),
+        interactable.prompt
+    );
+    if text.0 != label {
+        text.0 = label;
+    }
+}
+
+fn clear_focus(mut focused: ResMut<FocusedInteractable>) {
+    focused.0 = None;
+}
@@ -0,0 +1,291 @@
+//! Spawn the main level from its level file.
+//!
+//! The layout lives in [`MAIN_LEVEL_PATH`] rather than in code. Native dev
+//! builds hot reload assets, so after editing the file (by hand or in the
+//! editor) restarting the level picks up the changes without recompiling.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    asset_tracking::LoadResource,
+    audio::music,
+    game::beat::{BeatDivision, BeatPulse, MusicTempo},
+    game::big_switch::spawn_big_switch,
+    game::boss::boss,
+    game::calibration::{Calibration, calibration_course},
+    game::camera::CameraRail,
+    game::camera_anchor::camera_anchor,
+    game::chain::Layer,
+    game::checkpoint::checkpoint,
+    game::fauna::spawn_fauna,
+    game::hazards::hazard,
+    game::highlight::HookTarget,
+    game::level_data::{CameraRailData, LevelData, ObstacleData, PropData},
+    game::level_physics::LevelPhysics,
+    game::objectives::{LevelObjectives, collectible, goal_zone},
+    game::player::{PlayerAssets, partner, player},
+    game::prefab::{PREFAB_LIBRARY_PATH, PartOverride, PrefabData, PrefabLibrary},
+    game::reverb::reverb_zone,
+    game::sandbox::{Sandbox, sandbox_arena},
+    game::secret::secret,
+    game::signpost::signpost,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LevelAssets>();
+    app.load_resource::<LevelAssets>();
+    app.init_resource::<LevelBounds>();
+}
+
+/// The beat of the gameplay music, for anything kept in time with it.
+const GAMEPLAY_MUSIC_TEMPO: MusicTempo = MusicTempo {
+    bpm: 120.0,
+    beats_per_bar: 4,
+    first_beat: 0.0,
+};
+
+/// The level played in a run.
+pub const MAIN_LEVEL_PATH: &str = "levels/level1.level.ron";
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct LevelAssets {
+    #[dependency]
+    music: Handle<AudioSource>,
+    #[dependency]
+    level: Handle<LevelData>,
+    /// The prefabs placed in the level.
+    #[dependency]
+    prefabs: Handle<PrefabLibrary>,
+}
+
+impl FromWorld for LevelAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            music: assets.load("audio/music/Fluffing A Duck.ogg"),
+            level: assets.load(MAIN_LEVEL_PATH),
+            prefabs: assets.load(PREFAB_LIBRARY_PATH),
+        }
+    }
+}
+
+/// The smallest rectangle containing everything in the current level.
+#[derive(Resource, Default, Debug)]
+pub struct LevelBounds(pub Rect);
+
+/// A system that spawns the main level, or the sandbox's arena or the
+/// calibration course.
+pub fn spawn_level(
+    mut commands: Commands,
+    sandbox: Res<Sandbox>,
+    calibration: Res<Calibration>,
+    level_assets: Res<LevelAssets>,
+    player_assets: Res<PlayerAssets>,
+    levels: Res<Assets<LevelData>>,
+    libraries: Res<Assets<PrefabLibrary>>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let arena = if sandbox.active {
+        Some(sandbox_arena(&sandbox))
+    } else {
+        calibration.active.then(calibration_course)
+    };
+    let Some(level) = arena.as_ref().or_else(|| levels.get(&level_assets.level)) else {
+        error!("The level at {MAIN_LEVEL_PATH} is not loaded");
+        return;
+    };
+    let empty_library = PrefabLibrary::default();
+    let library = libraries
+        .get(&level_assets.prefabs)
+        .unwrap_or(&empty_library);
+    // Prefab instances are spawned as the parts they expand to.
+    let (level, _) = level.flatten(library);
+    // Validation on load makes sure there is a spawn point.
+    let spawn = level.spawn.unwrap_or_default();
+    commands.insert_resource(LevelBounds(level.bounds()));
+    commands.insert_resource(level.safe_ground());
+    commands.insert_resource(CameraRail(
+        level.camera_rail.as_ref().map(CameraRailData::shape),
+    ));
+    commands.insert_resource(LevelPhysics(level.physics.unwrap_or_default()));
+    commands.insert_resource(LevelObjectives {
+        collectibles: level.collectibles.len(),
+        secrets: level.secrets.len(),
+        time_limit: level.time_limit,
+        par: level.par,
+    });
+
+    commands.spawn((
+        Name::new("Level"),
+        Transform::default(),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+        children![
+            player(spawn, 400.0, &player_assets, &mut texture_atlas_layouts),
+            (
+                Name::new("Gameplay Music"),
+                music(level_assets.music.clone()),
+                GAMEPLAY_MUSIC_TEMPO,
+            )
+        ],
+    ));
+
+    for (i, data) in level.obstacles.iter().enumerate() {
+        commands.spawn((Name::new(format!("Obstacle {i}")), obstacle(data)));
+    }
+    for (i, data) in level.props.iter().enumerate() {
+        commands.spawn((Name::new(format!("Prop {i}")), prop(data)));
+    }
+    for (i, &position) in level.anchors.iter().enumerate() {
+        commands.spawn((Name::new(format!("Anchor {i}")), anchor(position)));
+    }
+    for (i, data) in level.camera_anchors.iter().enumerate() {
+        commands.spawn(camera_anchor(i, data));
+    }
+    for (i, &position) in level.checkpoints.iter().enumerate() {
+        commands.spawn(checkpoint(i, position));
+    }
+    for data in &level.reverb_zones {
+        commands.spawn(reverb_zone(data));
+    }
+    for data in &level.hazards {
+        commands.spawn(hazard(data));
+    }
+    for (i, data) in level.collectibles.iter().enumerate() {
+        commands.spawn(collectible(i, data));
+    }
+    for (i, data) in level.secrets.iter().enumerate() {
+        commands.spawn(secret(i, data));
+    }
+    for (i, data) in level.signposts.iter().enumerate() {
+        commands.spawn(signpost(i, data));
+    }
+    if let Some(exit) = level.exit {
+        commands.spawn(goal_zone(exit));
+    }
+    if let Some(position) = level.partner {
+        commands.spawn(partner(position));
+    }
+    for (i, data) in level.big_switches.iter().enumerate() {
+        spawn_big_switch(&mut commands, i, data);
+    }
+    if let Some(data) = &level.boss {
+        commands.spawn(boss(data));
+    }
+    spawn_fauna(&mut commands, &level.fauna);
+}
+
+/// Spawns the parts of a prefab at `position`, e.g. for scripted set pieces.
+pub fn spawn_prefab(
+    commands: &mut Commands,
+    prefab: &PrefabData,
+    position: Vec2,
+    overrides: &[PartOverride],
+) {
+    let placed = prefab.instantiate(position, overrides);
+    for data in &placed.obstacles {
+        commands.spawn((Name::new("Prefab Obstacle"), obstacle(data)));
+    }
+    for data in &placed.props {
+        commands.spawn((Name::new("Prefab Prop"), prop(data)));
+    }
+    for &position in &placed.anchors {
+        commands.spawn((Name::new("Prefab Anchor"), anchor(position)));
+    }
+}
+
+/// A static box that chains can interact with.
+pub fn obstacle(data: &ObstacleData) -> impl Bundle {
+    (
+        // Physics components
+        HookTarget::Surface,
+        RigidBody::Static, // Static means it won't move
+        Collider::rectangle(data.size.x, data.size.y),
+        Restitution::new(0.1), // Low restitution for less bouncy collisions
+        Friction::new(0.9),    // Very high friction for better chain interaction
+        // Collision groups
+        CollisionLayers::new(
+            [Layer::StaticObstacle],
+            [Layer::ChainLink, Layer::Player, Layer::DynamicProp],
+        ),
+        // Visual components
+        Sprite {
+            color: OBSTACLE_COLOR,
+            custom_size: Some(data.size),
+            ..default()
+        },
+        Transform::from_translation(data.position.extend(0.0)),
+        Visibility::default(),
+        BeatPulse::new(BeatDivision::Bar, 0.3),
+        StateScoped(Screen::Gameplay), // Clean up when leaving gameplay
+    )
+}
+
+/// A dynamic box that reacts to physics. Chains that hit it grab it, so it
+/// can be dragged or slung around.
+pub fn prop(data: &PropData) -> impl Bundle {
+    (
+        HookTarget::Grabbable,
+        // Physics components - similar to chain links but as a box
+        RigidBody::Dynamic,
+        Collider::rectangle(data.size.x, data.size.y),
+        Mass(data.mass),
+        LinearDamping(0.1),
+        AngularDamping(0.2),
+        SweptCcd::default(), // Same CCD as chain links
+        Restitution::new(0.3),
+        Friction::new(0.5),
+        CollisionLayers::new(
+            [Layer::DynamicProp],
+            [
+                Layer::ChainLink,
+                Layer::StaticObstacle,
+                Layer::Player,
+                Layer::Hazard,
+                Layer::DynamicProp,
+            ],
+        ),
+        // Visual components
+        Sprite {
+            color: PROP_COLOR,
+            custom_size: Some(data.size),
+            ..default()
+        },
+        Transform::from_translation(data.position.extend(0.0)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// A small static point that chains can catch on.
+pub fn anchor(position: Vec2) -> impl Bundle {
+    (
+        HookTarget::Anchor,
+        RigidBody::Static,
+        Collider::circle(ANCHOR_RADIUS),
+        Friction::new(0.9),
+        CollisionLayers::new(
+            [Layer::StaticObstacle],
+            [Layer::ChainLink, Layer::Player, Layer::DynamicProp],
+        ),
+        Sprite {
+            color: ANCHOR_COLOR,
+            custom_size: Some(Vec2::splat(ANCHOR_RADIUS * 2.0)),
+            ..default()
+        },
+        Transform::from_translation(position.extend(0.0)),
+        Visibility::default(),
+        BeatPulse::new(BeatDivision::Beat, 0.6),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+pub const ANCHOR_RADIUS: f32 = 6.0;
+/// Light gray.
+pub const OBSTACLE_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+/// Light red, to tell props apart from static boxes.
+pub const PROP_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
+pub const ANCHOR_COLOR: Color = Color::srgb(0.4, 0.6, 1.0);
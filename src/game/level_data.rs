@@ -0,0 +1,959 @@
+//! The versioned level file format, its validation pass, and its asset loader.
+//!
+//! Levels are stored as RON in `assets/levels/*.level.ron`. Files with an
+//! unknown version or validation errors fail to load with an error that says
+//! what to fix, instead of panicking later while the level is spawned.
+//!
+//! Levels can also be shipped [compressed](crate::packed) as
+//! `*.level.pack`, which is the same RON, only smaller.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        fauna::CreatureKind,
+        hazards::HazardKind,
+        level_migrations,
+        path::{LoopMode, PathCurve, PathEasing, PathShape},
+        prefab::{PREFAB_LIBRARY_PATH, PrefabInstance, PrefabLibrary},
+        reverb::ReverbPreset,
+        signpost::SignIcon,
+        wiring::{ActionData, TriggerData, Wire},
+    },
+    packed::{self, PackError, PackedKind},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<LevelData>();
+    app.init_asset_loader::<LevelLoader>();
+}
+
+/// The level format version written by this build.
+pub const LEVEL_FORMAT_VERSION: u32 = 2;
+
+/// Upgrades a level file from one format version to the next: takes the
+/// older RON and gives back the newer, along with what it changed.
+pub type LevelMigration = fn(&str) -> Result<(String, Vec<String>), LevelLoadError>;
+
+/// The upgrades from older format versions, by the version each upgrades
+/// from. Changing the format means bumping [`LEVEL_FORMAT_VERSION`] and
+/// adding one here, with its own copy of whatever it needs of the old
+/// format to read it, so levels made for older builds keep loading.
+const LEVEL_MIGRATIONS: &[(u32, LevelMigration)] = &[(1, level_migrations::collectible_points)];
+
+/// How far apart two footholds may be for the exit reachability heuristic.
+/// Roughly the distance a chain can cover in one swing.
+const MAX_HOOK_REACH: f32 = 600.0;
+
+/// What level files end in, plain and [packed](LevelData::to_packed).
+pub const LEVEL_EXTENSIONS: [&str; 2] = [".level.ron", ".level.pack"];
+
+/// Where the thumbnail for a level file is stored: `foo.level.ron` and
+/// `foo.level.pack` both get `foo.thumb.png` next to them. Nothing shows
+/// thumbnails yet, since there's no level select and the editor always opens
+/// the same file.
+pub fn thumbnail_path(level_path: &Path) -> PathBuf {
+    let file_name = level_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let stem = LEVEL_EXTENSIONS
+        .iter()
+        .find_map(|extension| file_name.strip_suffix(extension))
+        .unwrap_or(file_name);
+    level_path.with_file_name(format!("{stem}.thumb.png"))
+}
+
+/// A level as stored on disk.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LevelData {
+    pub version: u32,
+    #[serde(default)]
+    pub name: String,
+    /// Where the player starts. Required, but optional here so that a missing
+    /// spawn point is reported by validation rather than as a parse error.
+    #[serde(default)]
+    pub spawn: Option<Vec2>,
+    #[serde(default)]
+    pub exit: Option<Vec2>,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleData>,
+    #[serde(default)]
+    pub props: Vec<PropData>,
+    #[serde(default)]
+    pub anchors: Vec<Vec2>,
+    #[serde(default)]
+    pub prefabs: Vec<PrefabInstance>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<TriggerData>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<ActionData>,
+    /// Which triggers fire which actions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wires: Vec<Wire>,
+    /// Areas where sound effects echo. Where they overlap, later zones win.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reverb_zones: Vec<ReverbZoneData>,
+    /// Where the run is autosaved, in the order they're meant to be reached.
+    /// The stretches between them are the level's sections.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<Vec2>,
+    /// Saws, crushers and moving platforms.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hazards: Vec<HazardData>,
+    /// A path the camera keeps to instead of roaming freely.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera_rail: Option<CameraRailData>,
+    /// Anchors that show the player something when they're hooked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub camera_anchors: Vec<CameraAnchorData>,
+    /// Things to pick up for points.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collectibles: Vec<CollectibleData>,
+    /// Hidden rooms, covered up until someone finds them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<SecretData>,
+    /// Seconds to reach the exit in. Untimed levels leave this out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_limit: Option<f32>,
+    /// What a good run of the level looks like, set by the level's author.
+    /// There are no online leaderboards yet to take a community par from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub par: Option<ParData>,
+    /// Physics that differ from the rest of the game in this level, like low
+    /// gravity on the moon or thick water to swing through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physics: Option<PhysicsData>,
+    /// Birds, fish and butterflies living in the level, just for looks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fauna: Vec<FaunaData>,
+    /// Where the heavy partner starts, in levels played with two characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partner: Option<Vec2>,
+    /// Switches only the heavy partner weighs enough to hold down.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub big_switches: Vec<BigSwitchData>,
+    /// Signs with a message, and maybe the way on from them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signposts: Vec<SignpostData>,
+    /// The boss waiting at the end of the level, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boss: Option<BossData>,
+}
+
+impl Default for LevelData {
+    fn default() -> Self {
+        Self {
+            version: LEVEL_FORMAT_VERSION,
+            name: String::new(),
+            spawn: Some(Vec2::ZERO),
+            exit: None,
+            obstacles: Vec::new(),
+            props: Vec::new(),
+            anchors: Vec::new(),
+            prefabs: Vec::new(),
+            triggers: Vec::new(),
+            actions: Vec::new(),
+            wires: Vec::new(),
+            reverb_zones: Vec::new(),
+            checkpoints: Vec::new(),
+            hazards: Vec::new(),
+            camera_rail: None,
+            camera_anchors: Vec::new(),
+            collectibles: Vec::new(),
+            secrets: Vec::new(),
+            time_limit: None,
+            par: None,
+            physics: None,
+            fauna: Vec::new(),
+            partner: None,
+            big_switches: Vec::new(),
+            signposts: Vec::new(),
+            boss: None,
+        }
+    }
+}
+
+/// A static box that chains can hit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ObstacleData {
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+/// A dynamic box that can be pushed around.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PropData {
+    pub position: Vec2,
+    pub size: Vec2,
+    #[serde(default = "default_prop_mass")]
+    pub mass: f32,
+}
+
+/// A switch on the floor, and the door it holds open while it's held down.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BigSwitchData {
+    pub position: Vec2,
+    pub door: ObstacleData,
+}
+
+/// An area with its own reverb.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReverbZoneData {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub preset: ReverbPreset,
+}
+
+/// A hidden room, the area its facade covers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SecretData {
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+/// A sign in the level. Its message can be left empty for just the icon.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignpostData {
+    pub position: Vec2,
+    #[serde(default)]
+    pub icon: SignIcon,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// The points the way on goes through, from the sign.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub route: Vec<Vec2>,
+}
+
+/// Where a boss sits, and how big it is.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BossData {
+    pub position: Vec2,
+    #[serde(default = "default_boss_size")]
+    pub size: Vec2,
+}
+
+impl BossData {
+    /// A boss of the usual size at `position`.
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            size: default_boss_size(),
+        }
+    }
+}
+
+fn default_boss_size() -> Vec2 {
+    Vec2::new(96.0, 128.0)
+}
+
+/// A flock of creatures roaming the area it covers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FaunaData {
+    pub kind: CreatureKind,
+    pub position: Vec2,
+    pub size: Vec2,
+    pub count: usize,
+}
+
+impl FaunaData {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.position, self.size)
+    }
+}
+
+/// A moving piece of the level. It starts at `position` and follows `path`
+/// from there, doing what `loop_mode` says once it gets to the end.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HazardData {
+    pub kind: HazardKind,
+    pub position: Vec2,
+    /// For saws, only the width counts, as the diameter.
+    pub size: Vec2,
+    /// The control points of the path after `position`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<Vec2>,
+    #[serde(default)]
+    pub curve: PathCurve,
+    #[serde(default)]
+    pub easing: PathEasing,
+    #[serde(default)]
+    pub loop_mode: LoopMode,
+    /// In pixels per second.
+    #[serde(default = "default_hazard_speed")]
+    pub speed: f32,
+}
+
+/// The camera's track. The camera stays at the point of it closest to where
+/// it would otherwise look.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CameraRailData {
+    pub points: Vec<Vec2>,
+    #[serde(default)]
+    pub curve: PathCurve,
+}
+
+/// An anchor that, the first time it's hooked, pulls the camera over to
+/// `focus` for a while.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CameraAnchorData {
+    pub position: Vec2,
+    /// What to show.
+    pub focus: Vec2,
+    /// Points the camera curves through on its way to `focus`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub via: Vec<Vec2>,
+    /// How long the camera stays on `focus`, in seconds.
+    #[serde(default = "default_camera_anchor_hold")]
+    pub hold: f32,
+    /// How far the camera zooms out to frame `focus`, as a projection scale.
+    #[serde(default = "default_camera_anchor_zoom")]
+    pub zoom: f32,
+}
+
+/// The hooks and time a good run of a level takes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ParData {
+    pub hooks: u32,
+    /// In seconds.
+    pub time: f32,
+}
+
+/// Overrides for the game's physics while a level is played. Anything left
+/// out stays as it is everywhere else.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct PhysicsData {
+    /// In pixels per second squared, pointing down by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gravity: Option<Vec2>,
+    /// Linear damping added to every moving body, as if the air was thicker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub air_damping: Option<f32>,
+    /// The most links a chain can have.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_chain_links: Option<usize>,
+}
+
+fn default_hazard_speed() -> f32 {
+    100.0
+}
+
+fn default_camera_anchor_hold() -> f32 {
+    3.0
+}
+
+fn default_camera_anchor_zoom() -> f32 {
+    1.0
+}
+
+fn default_prop_mass() -> f32 {
+    0.5
+}
+
+/// Something to pick up for points.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CollectibleData {
+    pub position: Vec2,
+    /// What picking it up is worth.
+    #[serde(default = "default_collectible_points")]
+    pub points: u32,
+}
+
+fn default_collectible_points() -> u32 {
+    100
+}
+
+impl ObstacleData {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.position, self.size)
+    }
+}
+
+impl PropData {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.position, self.size)
+    }
+}
+
+impl ReverbZoneData {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.position, self.size)
+    }
+}
+
+impl SecretData {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.position, self.size)
+    }
+}
+
+impl HazardData {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.position, self.size)
+    }
+
+    /// All control points of the path, starting with `position`.
+    pub fn path_points(&self) -> Vec<Vec2> {
+        std::iter::once(self.position)
+            .chain(self.path.iter().copied())
+            .collect()
+    }
+
+    pub fn shape(&self) -> PathShape {
+        PathShape::new(
+            &self.path_points(),
+            self.curve,
+            self.loop_mode == LoopMode::Loop,
+        )
+    }
+}
+
+impl CameraRailData {
+    pub fn shape(&self) -> PathShape {
+        PathShape::new(&self.points, self.curve, false)
+    }
+}
+
+/// A level file brought up to the current format version.
+#[derive(Debug)]
+pub struct MigratedLevel {
+    pub level: LevelData,
+    /// The version the file was written in.
+    pub from_version: u32,
+    /// What each upgrade changed, in the order they ran.
+    pub changes: Vec<String>,
+}
+
+impl MigratedLevel {
+    /// Whether the file was written in an older version than this build's.
+    pub fn upgraded(&self) -> bool {
+        self.from_version != LEVEL_FORMAT_VERSION
+    }
+}
+
+impl LevelData {
+    /// Parses a level file, checking its version before anything else.
+    /// Files in an older version are upgraded in memory.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, LevelLoadError> {
+        let migrated = Self::migrate(bytes)?;
+        if migrated.upgraded() {
+            warn!(
+                "Upgraded a level from format version {}, run with `--migrate-levels` to update the file",
+                migrated.from_version
+            );
+        }
+        Ok(migrated.level)
+    }
+
+    /// Parses a level file of any version there's an upgrade path from,
+    /// running the upgrades needed to bring it to [`LEVEL_FORMAT_VERSION`].
+    pub fn migrate(bytes: &[u8]) -> Result<MigratedLevel, LevelLoadError> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let header: Header = ron::de::from_bytes(bytes).map_err(LevelLoadError::Parse)?;
+        let from_version = header.version;
+        if from_version == LEVEL_FORMAT_VERSION {
+            return Ok(MigratedLevel {
+                level: ron::de::from_bytes(bytes).map_err(LevelLoadError::Parse)?,
+                from_version,
+                changes: Vec::new(),
+            });
+        }
+        if from_version > LEVEL_FORMAT_VERSION {
+            return Err(LevelLoadError::UnsupportedVersion(from_version));
+        }
+
+        let mut ron = std::str::from_utf8(bytes)
+            .map_err(|error| LevelLoadError::Migration(error.to_string()))?
+            .to_string();
+        let mut changes = Vec::new();
+        for version in from_version..LEVEL_FORMAT_VERSION {
+            let Some((_, migration)) = LEVEL_MIGRATIONS.iter().find(|(from, _)| *from == version)
+            else {
+                return Err(LevelLoadError::UnsupportedVersion(from_version));
+            };
+            let (upgraded, notes) = migration(&ron)?;
+            ron = upgraded;
+            changes.extend(
+                notes
+                    .into_iter()
+                    .map(|note| format!("version {version} to {}: {note}", version + 1)),
+            );
+        }
+        let mut level: LevelData = ron::de::from_str(&ron).map_err(LevelLoadError::Parse)?;
+        level.version = LEVEL_FORMAT_VERSION;
+        Ok(MigratedLevel {
+            level,
+            from_version,
+            changes,
+        })
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Read a level that's either plain RON or [packed](Self::to_packed).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LevelLoadError> {
+        Self::from_ron(&Self::unpack_ron(bytes)?)
+    }
+
+    /// The RON of a level that's either plain RON or [packed](Self::to_packed).
+    pub fn unpack_ron(bytes: &[u8]) -> Result<Cow<'_, [u8]>, LevelLoadError> {
+        if !packed::is_packed(bytes) {
+            return Ok(Cow::Borrowed(bytes));
+        }
+        // Older versions are upgraded once unpacked, like plain RON.
+        let version = packed::version(bytes).unwrap_or(LEVEL_FORMAT_VERSION);
+        if version > LEVEL_FORMAT_VERSION {
+            return Err(LevelLoadError::UnsupportedVersion(version));
+        }
+        packed::decompress(PackedKind::Level, version, bytes)
+            .map(Cow::Owned)
+            .map_err(LevelLoadError::Packed)
+    }
+
+    /// The level as compressed RON, for shipping as a `.level.pack`.
+    pub fn to_packed(&self) -> Result<Vec<u8>, ron::Error> {
+        let ron = self.to_ron()?;
+        Ok(packed::compress(
+            PackedKind::Level,
+            LEVEL_FORMAT_VERSION,
+            ron.as_bytes(),
+        ))
+    }
+
+    /// The smallest rectangle containing everything in the level.
+    pub fn bounds(&self) -> Rect {
+        let points = self
+            .spawn
+            .iter()
+            .chain(self.exit.iter())
+            .chain(self.partner.iter())
+            .chain(self.anchors.iter())
+            .chain(self.camera_anchors.iter().map(|anchor| &anchor.position))
+            .chain(self.checkpoints.iter())
+            .chain(
+                self.collectibles
+                    .iter()
+                    .map(|collectible| &collectible.position),
+            )
+            .map(|&point| Rect::from_center_size(point, Vec2::ZERO));
+        let boxes = self
+            .obstacles
+            .iter()
+            .map(ObstacleData::rect)
+            .chain(self.props.iter().map(PropData::rect))
+            .chain(self.hazards.iter().map(HazardData::rect))
+            .chain(self.big_switches.iter().map(|switch| switch.door.rect()));
+        points
+            .chain(boxes)
+            .reduce(|bounds, rect| bounds.union(rect))
+            .unwrap_or_default()
+    }
+
+    /// Checks the level for mistakes, with prefab instances expanded from
+    /// `library`.
+    pub fn validate_with_prefabs(&self, library: &PrefabLibrary) -> Vec<LevelIssue> {
+        let (flat, missing) = self.flatten(library);
+        let mut issues: Vec<_> = missing
+            .into_iter()
+            .map(|name| LevelIssue::UnknownPrefab { name })
+            .collect();
+        issues.extend(flat.validate());
+        issues
+    }
+
+    /// Checks the level for mistakes, ignoring prefab instances. Issues with [`Severity::Error`] make the
+    /// level unplayable, warnings are worth a look but don't block loading.
+    pub fn validate(&self) -> Vec<LevelIssue> {
+        let mut issues = Vec::new();
+
+        let Some(spawn) = self.spawn else {
+            issues.push(LevelIssue::MissingSpawn);
+            return issues;
+        };
+
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            if obstacle.size.min_element() <= 0.0 {
+                issues.push(LevelIssue::EmptyCollider {
+                    kind: "obstacle",
+                    index: i,
+                });
+            }
+            if obstacle.rect().contains(spawn) {
+                issues.push(LevelIssue::SpawnInsideObstacle { index: i });
+            }
+            for (j, other) in self.obstacles.iter().enumerate().skip(i + 1) {
+                if overlaps(obstacle.rect(), other.rect()) {
+                    issues.push(LevelIssue::OverlappingObstacles { a: i, b: j });
+                }
+            }
+        }
+
+        for (i, prop) in self.props.iter().enumerate() {
+            if prop.size.min_element() <= 0.0 || prop.mass <= 0.0 {
+                issues.push(LevelIssue::EmptyCollider {
+                    kind: "prop",
+                    index: i,
+                });
+            }
+            if let Some(obstacle) = self
+                .obstacles
+                .iter()
+                .position(|obstacle| overlaps(prop.rect(), obstacle.rect()))
+            {
+                issues.push(LevelIssue::PropInsideObstacle { prop: i, obstacle });
+            }
+        }
+
+        for (i, hazard) in self.hazards.iter().enumerate() {
+            if hazard.curve == PathCurve::Bezier && hazard.path.len() % 3 != 0 {
+                issues.push(LevelIssue::UnevenBezierPath { hazard: i });
+            }
+        }
+
+        for (i, &Wire(trigger, action)) in self.wires.iter().enumerate() {
+            if trigger >= self.triggers.len() || action >= self.actions.len() {
+                issues.push(LevelIssue::BrokenWire { index: i });
+            }
+        }
+        for i in 0..self.triggers.len() {
+            if !self.wires.iter().any(|wire| wire.0 == i) {
+                issues.push(LevelIssue::UnwiredTrigger { index: i });
+            }
+        }
+
+        if let Some(physics) = self.physics {
+            if physics.gravity.is_some_and(|gravity| !gravity.is_finite()) {
+                issues.push(LevelIssue::BadPhysics { what: "gravity" });
+            }
+            if physics
+                .air_damping
+                .is_some_and(|damping| !(damping >= 0.0 && damping.is_finite()))
+            {
+                issues.push(LevelIssue::BadPhysics {
+                    what: "air damping",
+                });
+            }
+            if physics.max_chain_links == Some(0) {
+                issues.push(LevelIssue::BadPhysics {
+                    what: "chain length",
+                });
+            }
+        }
+
+        match self.exit {
+            None => issues.push(LevelIssue::MissingExit),
+            Some(exit) if !self.is_reachable(spawn, exit) => {
+                issues.push(LevelIssue::UnreachableExit { exit })
+            }
+            Some(_) => {}
+        }
+
+        issues
+    }
+
+    /// A rough check that the exit can be reached by hopping between
+    /// obstacles and anchors that are each within [`MAX_HOOK_REACH`].
+    fn is_reachable(&self, from: Vec2, to: Vec2) -> bool {
+        let mut footholds: Vec<Vec2> = self
+            .obstacles
+            .iter()
+            .map(|obstacle| obstacle.position)
+            .chain(self.anchors.iter().copied())
+            .chain(self.camera_anchors.iter().map(|anchor| anchor.position))
+            .collect();
+        footholds.push(to);
+
+        let mut frontier = vec![from];
+        let mut visited = vec![false; footholds.len()];
+        while let Some(current) = frontier.pop() {
+            for (i, &foothold) in footholds.iter().enumerate() {
+                if !visited[i] && current.distance(foothold) <= MAX_HOOK_REACH {
+                    visited[i] = true;
+                    frontier.push(foothold);
+                }
+            }
+        }
+        visited[footholds.len() - 1]
+    }
+}
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    !a.intersect(b).is_empty()
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found by [`LevelData::validate`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum LevelIssue {
+    MissingSpawn,
+    SpawnInsideObstacle { index: usize },
+    EmptyCollider { kind: &'static str, index: usize },
+    OverlappingObstacles { a: usize, b: usize },
+    PropInsideObstacle { prop: usize, obstacle: usize },
+    MissingExit,
+    UnreachableExit { exit: Vec2 },
+    UnknownPrefab { name: String },
+    BrokenWire { index: usize },
+    UnwiredTrigger { index: usize },
+    UnevenBezierPath { hazard: usize },
+    BadPhysics { what: &'static str },
+}
+
+impl LevelIssue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            LevelIssue::MissingSpawn
+            | LevelIssue::SpawnInsideObstacle { .. }
+            | LevelIssue::EmptyCollider { .. }
+            | LevelIssue::PropInsideObstacle { .. }
+            | LevelIssue::UnknownPrefab { .. }
+            | LevelIssue::BrokenWire { .. }
+            | LevelIssue::BadPhysics { .. } => Severity::Error,
+            LevelIssue::OverlappingObstacles { .. }
+            | LevelIssue::UnwiredTrigger { .. }
+            | LevelIssue::UnevenBezierPath { .. }
+            | LevelIssue::MissingExit
+            | LevelIssue::UnreachableExit { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelIssue::MissingSpawn => write!(f, "No spawn point. Add a `spawn` position."),
+            LevelIssue::SpawnInsideObstacle { index } => write!(
+                f,
+                "The spawn point is inside obstacle #{index}. Move one of them."
+            ),
+            LevelIssue::EmptyCollider { kind, index } => write!(
+                f,
+                "The {kind} #{index} has no size or mass. Give it a positive size and mass."
+            ),
+            LevelIssue::OverlappingObstacles { a, b } => write!(
+                f,
+                "Obstacles #{a} and #{b} overlap. Merge them into one or move them apart."
+            ),
+            LevelIssue::PropInsideObstacle { prop, obstacle } => write!(
+                f,
+                "Prop #{prop} starts inside obstacle #{obstacle} and will be flung out. Move it."
+            ),
+            LevelIssue::MissingExit => write!(f, "No exit. Add an `exit` position."),
+            LevelIssue::UnreachableExit { exit } => write!(
+                f,
+                "The exit at ({:.0}, {:.0}) looks unreachable. Add obstacles or anchors within {MAX_HOOK_REACH:.0} px of each other leading to it.",
+                exit.x, exit.y
+            ),
+            LevelIssue::UnknownPrefab { name } => write!(
+                f,
+                "There is no prefab called `{name}`. Add it to {PREFAB_LIBRARY_PATH} or fix the name."
+            ),
+            LevelIssue::BrokenWire { index } => write!(
+                f,
+                "Wire #{index} points at a trigger or action that doesn't exist. Remove it."
+            ),
+            LevelIssue::UnwiredTrigger { index } => write!(
+                f,
+                "Trigger #{index} isn't wired to any action and does nothing. Wire it up or remove it."
+            ),
+            LevelIssue::UnevenBezierPath { hazard } => write!(
+                f,
+                "The Bézier path of hazard #{hazard} ends partway through a curve, which is left out. Give each curve two handles and an end point."
+            ),
+            LevelIssue::BadPhysics { what } => write!(
+                f,
+                "The level's {what} override is out of range. Fix it or leave it out to use the usual {what}."
+            ),
+        }
+    }
+}
+
+/// Why a level file could not be loaded.
+#[derive(Debug)]
+pub enum LevelLoadError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Packed(PackError),
+    UnsupportedVersion(u32),
+    /// An upgrade from an older version failed.
+    Migration(String),
+    Prefabs(String),
+    Invalid(Vec<LevelIssue>),
+}
+
+impl fmt::Display for LevelLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelLoadError::Io(error) => write!(f, "could not read level file: {error}"),
+            LevelLoadError::Parse(error) => write!(f, "level file is corrupt: {error}"),
+            LevelLoadError::Packed(error) => write!(f, "level file is corrupt: {error}"),
+            LevelLoadError::UnsupportedVersion(version) => write!(
+                f,
+                "level format version {version} is not supported, expected {LEVEL_FORMAT_VERSION}"
+            ),
+            LevelLoadError::Migration(error) => {
+                write!(f, "could not upgrade the level file: {error}")
+            }
+            LevelLoadError::Prefabs(error) => {
+                write!(f, "could not load prefabs used by the level: {error}")
+            }
+            LevelLoadError::Invalid(issues) => {
+                write!(f, "level has {} error(s):", issues.len())?;
+                for issue in issues {
+                    write!(f, "\n- {issue}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelLoadError {}
+
+impl From<std::io::Error> for LevelLoadError {
+    fn from(error: std::io::Error) -> Self {
+        LevelLoadError::Io(error)
+    }
+}
+
+/// Loads `.level.ron` and `.level.pack` files as [`LevelData`], rejecting
+/// files that fail validation.
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let level = LevelData::from_bytes(&bytes)?;
+
+        let issues = if level.prefabs.is_empty() {
+            level.validate()
+        } else {
+            let library = load_context
+                .loader()
+                .immediate()
+                .load::<PrefabLibrary>(PREFAB_LIBRARY_PATH)
+                .await
+                .map_err(|error| LevelLoadError::Prefabs(error.to_string()))?;
+            level.validate_with_prefabs(library.get())
+        };
+
+        let (errors, warnings): (Vec<_>, Vec<_>) = issues
+            .into_iter()
+            .partition(|issue| issue.severity() == Severity::Error);
+        for warning in warnings {
+            warn!("{}: {warning}", load_context.path().display());
+        }
+        if !errors.is_empty() {
+            return Err(LevelLoadError::Invalid(errors));
+        }
+
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron", "level.pack"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A level as version 1 wrote it, comments and all.
+    const V1_LEVEL: &str = r#"(
+    version: 1,
+    name: "Old (but gold), [level]",
+    spawn: Some((0.0, 0.0)),
+    exit: Some((-300.0, 200.0)),
+    // Collectibles were only positions.
+    collectibles: [
+        (150.0, 150.0), // The easy one.
+        (-250.0, -100.0),
+        /* Behind the saw. */ (350.0, 50.0),
+    ],
+    time_limit: Some(120.0),
+)"#;
+
+    #[test]
+    fn upgrades_version_1_levels() {
+        let migrated = LevelData::migrate(V1_LEVEL.as_bytes()).unwrap();
+        assert_eq!(migrated.from_version, 1);
+        assert_eq!(migrated.level.version, LEVEL_FORMAT_VERSION);
+        assert_eq!(migrated.changes.len(), 1);
+        assert_eq!(migrated.level.name, "Old (but gold), [level]");
+        assert_eq!(migrated.level.time_limit, Some(120.0));
+        let collectibles: Vec<_> = migrated
+            .level
+            .collectibles
+            .iter()
+            .map(|collectible| (collectible.position, collectible.points))
+            .collect();
+        assert_eq!(
+            collectibles,
+            [
+                (Vec2::new(150.0, 150.0), 100),
+                (Vec2::new(-250.0, -100.0), 100),
+                (Vec2::new(350.0, 50.0), 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn upgraded_levels_load_as_current() {
+        let migrated = LevelData::migrate(V1_LEVEL.as_bytes()).unwrap();
+        let saved = migrated.level.to_ron().unwrap();
+        let reloaded = LevelData::migrate(saved.as_bytes()).unwrap();
+        assert!(!reloaded.upgraded());
+        assert_eq!(reloaded.level, migrated.level);
+    }
+
+    #[test]
+    fn thumbnails_sit_next_to_plain_and_packed_levels() {
+        for level in ["levels/cave.level.ron", "levels/cave.level.pack"] {
+            assert_eq!(
+                thumbnail_path(Path::new(level)),
+                Path::new("levels/cave.thumb.png")
+            );
+        }
+    }
+
+    #[test]
+    fn version_1_levels_without_collectibles_upgrade() {
+        let migrated = LevelData::migrate(b"(version: 1, spawn: Some((0.0, 0.0)))").unwrap();
+        assert!(migrated.level.collectibles.is_empty());
+        assert!(migrated.changes.is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! Upgrades for level files written in older format versions.
+//!
+//! Each upgrade works on the RON text, touching only what changed between
+//! two versions and keeping everything else as it was written, comments
+//! included. That way they don't need a copy of every struct in the old
+//! format, only enough of its layout to find what to change.
+
+use std::ops::Range;
+
+use crate::game::level_data::LevelLoadError;
+
+/// Version 1 to 2: collectibles went from bare positions to
+/// `(position: ..)` entries, so each can say how many points it's worth.
+/// Upgraded ones are worth the default.
+pub fn collectible_points(ron: &str) -> Result<(String, Vec<String>), LevelLoadError> {
+    let marks = structure(ron);
+    let Some(value) = top_level_field(ron, &marks, "collectibles") else {
+        return Ok((ron.to_string(), Vec::new()));
+    };
+    let items = list_items(ron, &marks, value).ok_or_else(|| {
+        LevelLoadError::Migration("collectibles should be a list of positions".to_string())
+    })?;
+
+    let mut upgraded = String::with_capacity(ron.len() + items.len() * "(position: )".len());
+    let mut copied = 0;
+    for item in &items {
+        upgraded += &ron[copied..item.start];
+        upgraded += "(position: ";
+        upgraded += &ron[item.clone()];
+        upgraded += ")";
+        copied = item.end;
+    }
+    upgraded += &ron[copied..];
+    let note = format!("made {} collectibles worth the default points", items.len());
+    Ok((upgraded, vec![note]))
+}
+
+/// A bracket, comma or colon in RON text, outside strings and comments.
+#[derive(Copy, Clone, Debug)]
+struct Mark {
+    offset: usize,
+    byte: u8,
+    /// How many brackets it's inside of, not counting its own.
+    depth: usize,
+}
+
+/// Every [`Mark`] in `ron`, in order.
+fn structure(ron: &str) -> Vec<Mark> {
+    let bytes = ron.as_bytes();
+    let find = |from: usize, pattern: &str| {
+        ron[from..]
+            .find(pattern)
+            .map_or(bytes.len(), |at| from + at + pattern.len())
+    };
+    let mut marks = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match byte {
+            b'"' | b'\'' => {
+                // Skip to the matching quote, minding escapes.
+                i += 1;
+                while i < bytes.len() && bytes[i] != byte {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'r' if matches!(next, Some(b'"' | b'#'))
+                && !bytes[..i]
+                    .last()
+                    .is_some_and(|&before| before.is_ascii_alphanumeric() || before == b'_') =>
+            {
+                // A raw string ends at a quote followed by as many hashes as
+                // it starts with.
+                let hashes = bytes[i + 1..].iter().take_while(|&&b| b == b'#').count();
+                let end = format!("\"{}", "#".repeat(hashes));
+                i = find(i + 2 + hashes, &end) - 1;
+            }
+            b'/' if next == Some(b'/') => i = find(i, "\n") - 1,
+            b'/' if next == Some(b'*') => i = find(i + 2, "*/") - 1,
+            b'(' | b'[' | b'{' => {
+                marks.push(Mark {
+                    offset: i,
+                    byte,
+                    depth,
+                });
+                depth += 1;
+            }
+            b')' | b']' | b'}' => {
+                depth = depth.saturating_sub(1);
+                marks.push(Mark {
+                    offset: i,
+                    byte,
+                    depth,
+                });
+            }
+            b',' | b':' => marks.push(Mark {
+                offset: i,
+                byte,
+                depth,
+            }),
+            _ => {}
+        }
+        i += 1;
+    }
+    marks
+}
+
+/// Where the value of the field `name` of the outermost struct is.
+fn top_level_field(ron: &str, marks: &[Mark], name: &str) -> Option<Range<usize>> {
+    let (i, colon) = marks.iter().enumerate().find(|(_, mark)| {
+        mark.byte == b':' && mark.depth == 1 && {
+            let before = ron[..mark.offset].trim_end();
+            before.ends_with(name)
+                && !before[..before.len() - name.len()]
+                    .ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        }
+    })?;
+    let end = marks[i + 1..]
+        .iter()
+        .find(|mark| (mark.byte == b',' && mark.depth == 1) || mark.depth == 0)?;
+    Some(colon.offset + 1..end.offset)
+}
+
+/// Where each item of the list in `value` is, without the whitespace
+/// around it, or `None` if it isn't a list.
+fn list_items(ron: &str, marks: &[Mark], value: Range<usize>) -> Option<Vec<Range<usize>>> {
+    let start = value.start + ron[value.clone()].find(|c: char| !c.is_whitespace())?;
+    let open = marks.iter().position(|mark| mark.offset == start)?;
+    let Mark {
+        byte: b'[', depth, ..
+    } = marks[open]
+    else {
+        return None;
+    };
+
+    let mut items = Vec::new();
+    let mut item_start = start + 1;
+    for mark in &marks[open + 1..] {
+        let closed = mark.depth == depth;
+        let separator = mark.byte == b',' && mark.depth == depth + 1;
+        if !(closed || separator) {
+            continue;
+        }
+        let item = &ron[item_start..mark.offset];
+        let trimmed = item.trim();
+        if !trimmed.is_empty() {
+            let at = item_start + (item.len() - item.trim_start().len());
+            items.push(at..at + trimmed.len());
+        }
+        if closed {
+            return Some(items);
+        }
+        item_start = mark.offset + 1;
+    }
+    None
+}
@@ -0,0 +1,102 @@
+//! Physics overrides from the level being played.
+//!
+//! A level's [`PhysicsData`] takes effect when the level spawns: its gravity
+//! replaces the usual gravity, and mutators like low gravity scale whatever
+//! the level set. Its air damping slows every moving body at least that much,
+//! and its chain length caps chains. Everything is put back as it was when
+//! the level is left.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{chain::ChainConfig, level::spawn_level, level_data::PhysicsData},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelPhysics>();
+    app.init_resource::<ReplacedPhysics>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        apply_level_physics.after(spawn_level),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), revert_level_physics);
+    app.add_systems(
+        Update,
+        apply_air_damping
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay).and(has_air_damping)),
+    );
+}
+
+/// The physics overrides of the current level.
+#[derive(Resource, Default, Debug)]
+pub struct LevelPhysics(pub PhysicsData);
+
+/// What the current level's overrides replaced, to put back when it's left.
+#[derive(Resource, Default, Debug)]
+pub struct ReplacedPhysics {
+    gravity: Option<Vec2>,
+    max_chain_links: Option<usize>,
+}
+
+pub fn apply_level_physics(
+    physics: Res<LevelPhysics>,
+    mut replaced: ResMut<ReplacedPhysics>,
+    mut gravity: ResMut<Gravity>,
+    mut config: ResMut<ChainConfig>,
+) {
+    if let Some(level_gravity) = physics.0.gravity {
+        replaced.gravity = Some(gravity.0);
+        gravity.0 = level_gravity;
+    }
+    if let Some(max_links) = physics.0.max_chain_links {
+        replaced.max_chain_links = Some(config.max_links);
+        config.max_links = max_links;
+    }
+}
+
+fn revert_level_physics(
+    mut replaced: ResMut<ReplacedPhysics>,
+    mut gravity: ResMut<Gravity>,
+    mut config: ResMut<ChainConfig>,
+) {
+    if let Some(usual) = replaced.gravity.take() {
+        gravity.0 = usual;
+    }
+    if let Some(usual) = replaced.max_chain_links.take() {
+        config.max_links = usual;
+    }
+}
+
+fn has_air_damping(physics: Res<LevelPhysics>) -> bool {
+    physics.0.air_damping.is_some()
+}
+
+/// Bodies come and go, and some change their own damping for a while, so
+/// this keeps every dynamic body up to the level's damping as it goes.
+fn apply_air_damping(
+    mut commands: Commands,
+    physics: Res<LevelPhysics>,
+    mut body_query: Query<(Entity, &RigidBody, Option<&mut LinearDamping>)>,
+) {
+    let Some(air_damping) = physics.0.air_damping else {
+        return;
+    };
+    for (entity, body, damping) in &mut body_query {
+        if !body.is_dynamic() {
+            continue;
+        }
+        match damping {
+            Some(mut damping) if damping.0 < air_damping => damping.0 = air_damping,
+            Some(_) => {}
+            None => {
+                commands.entity(entity).insert(LinearDamping(air_damping));
+            }
+        }
+    }
+}
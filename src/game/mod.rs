@@ -0,0 +1,179 @@
+//! Gameplay: chains, the player, levels and everything in them.
+//!
+//! [`GamePlugin`] adds all of it. Tools working with levels, like the editor,
+//! should go through the [`prelude`] rather than reaching into the modules,
+//! so gameplay can be reorganized without breaking them.
+
+use bevy::prelude::*;
+
+pub mod aim;
+pub mod animation;
+pub mod assists;
+pub mod beat;
+pub mod big_switch;
+pub mod boss;
+pub mod calibration;
+pub mod camera;
+pub mod camera_anchor;
+pub mod chain;
+pub mod chain_decay;
+pub mod chain_feedback;
+pub mod chain_impact;
+pub mod chain_rattle;
+pub mod chain_render;
+pub mod chain_streaming;
+pub mod chain_stress;
+pub mod cheats;
+pub mod checkpoint;
+pub mod cleanup;
+pub mod cosmetics;
+pub mod daily;
+pub mod debris;
+pub mod decal;
+pub mod event_log;
+pub mod fauna;
+pub mod game_rng;
+pub mod game_timer;
+pub mod gameplay_error;
+pub mod gameplay_id;
+pub mod hazards;
+pub mod highlight;
+pub mod hints;
+pub mod hook_ghosts;
+pub mod hook_indicator;
+pub mod hook_types;
+pub mod hookable;
+pub mod interaction;
+pub mod level;
+pub mod level_data;
+pub mod level_migrations;
+pub mod level_physics;
+pub mod movement;
+pub mod mutators;
+pub mod objectives;
+pub mod occlusion;
+pub mod path;
+pub mod performance;
+pub mod physics_quality;
+pub mod player;
+pub mod prefab;
+pub mod pulley;
+pub mod rappel;
+pub mod reverb;
+pub mod run_code;
+pub mod run_stats;
+pub mod sandbox;
+pub mod scrape;
+pub mod secret;
+pub mod signpost;
+pub mod snag;
+pub mod stinger;
+pub mod tangle;
+pub mod taunt;
+pub mod tether;
+pub mod time_control;
+pub mod trigger_volume;
+pub mod unstick;
+pub mod victory;
+pub mod wall_run;
+pub mod wiring;
+
+/// The types making up a level and what's in it.
+pub mod prelude {
+    pub use super::{
+        hazards::HazardKind,
+        level_data::{
+            BossData, CameraAnchorData, CameraRailData, HazardData, LevelData, LevelIssue,
+            LevelLoadError, MigratedLevel, ObstacleData, PropData, ReverbZoneData, SecretData,
+            Severity, SignpostData, thumbnail_path,
+        },
+        path::{LoopMode, PathCurve, PathEasing},
+        prefab::{PREFAB_LIBRARY_PATH, PrefabInstance, PrefabLibrary},
+        reverb::ReverbPreset,
+        signpost::SignIcon,
+        wiring::{ActionData, ActionKind, TriggerData, TriggerKind, Wire},
+    };
+}
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        // Level assets first, as `level` loads them while it's being added.
+        app.add_plugins((level_data::plugin, prefab::plugin));
+        app.add_plugins((
+            aim::plugin,
+            animation::plugin,
+            assists::plugin,
+            beat::plugin,
+            big_switch::plugin,
+            boss::plugin,
+            calibration::plugin,
+            camera::plugin,
+            camera_anchor::plugin,
+            chain::plugin,
+            chain_decay::plugin,
+            chain_feedback::plugin,
+            chain_impact::plugin,
+            chain_rattle::plugin,
+            chain_render::plugin,
+        ));
+        app.add_plugins((
+            chain_streaming::plugin,
+            chain_stress::plugin,
+            cheats::plugin,
+            checkpoint::plugin,
+            cleanup::plugin,
+            cosmetics::plugin,
+            debris::plugin,
+            decal::plugin,
+            event_log::plugin,
+            fauna::plugin,
+            game_rng::plugin,
+            gameplay_error::plugin,
+            gameplay_id::plugin,
+            hazards::plugin,
+            highlight::plugin,
+        ));
+        app.add_plugins((
+            hints::plugin,
+            hook_ghosts::plugin,
+            hook_indicator::plugin,
+            hook_types::plugin,
+            hookable::plugin,
+            interaction::plugin,
+            level::plugin,
+            level_physics::plugin,
+            movement::plugin,
+            mutators::plugin,
+            objectives::plugin,
+            occlusion::plugin,
+            path::plugin,
+            performance::plugin,
+        ));
+        app.add_plugins((
+            physics_quality::plugin,
+            player::plugin,
+            pulley::plugin,
+            rappel::plugin,
+            reverb::plugin,
+            run_stats::plugin,
+            sandbox::plugin,
+            scrape::plugin,
+            secret::plugin,
+            signpost::plugin,
+            snag::plugin,
+            stinger::plugin,
+            tangle::plugin,
+            taunt::plugin,
+        ));
+        app.add_plugins((
+            tether::plugin,
+            time_control::plugin,
+            trigger_volume::plugin,
+            unstick::plugin,
+            victory::plugin,
+            wall_run::plugin,
+        ));
+    }
+}
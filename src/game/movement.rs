@@ -13,9 +13,13 @@
 //! purposes. If you want to move the player in a smoother way,
 //! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/main/examples/movement/physics_in_fixed_timestep.rs).
 
+use avian2d::prelude::*;
 use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::{AppSystems, PausableSystems};
+use crate::{
+    AppSystems, PausableSystems,
+    game::gameplay_error::{GameplayError, OrReport},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<MovementController>();
@@ -54,9 +58,11 @@ impl Default for MovementController {
     }
 }
 
+/// Characters with a [`RigidBody`] are moved by physics instead, e.g. the
+/// player while hanging from a chain.
 fn apply_movement(
     time: Res<Time>,
-    mut movement_query: Query<(&MovementController, &mut Transform)>,
+    mut movement_query: Query<(&MovementController, &mut Transform), Without<RigidBody>>,
 ) {
     for (controller, mut transform) in &mut movement_query {
         let velocity = controller.max_speed * controller.intent;
@@ -69,9 +75,20 @@ fn apply_movement(
 pub struct ScreenWrap;
 
 fn apply_screen_wrap(
-    window: Single<&Window, With<PrimaryWindow>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
     mut wrap_query: Query<&mut Transform, With<ScreenWrap>>,
+    mut errors: EventWriter<GameplayError>,
 ) {
+    if wrap_query.is_empty() {
+        return;
+    }
+    let Some(window) =
+        window_query
+            .single()
+            .or_report(&mut errors, "apply_screen_wrap", "primary window")
+    else {
+        return;
+    };
     let size = window.size() + 256.0;
     let half_size = size / 2.0;
     for mut transform in &mut wrap_query {
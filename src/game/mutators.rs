@@ -0,0 +1,204 @@
+//! Optional run mutators that twist the rules of a single run.
+//!
+//! Mutators are stored together as a bitset in [`RunMutators`] and picked on
+//! the run setup menu, either by hand or rolled from a [`RunSeed`].
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    AppSystems, GRAVITY,
+    game::{
+        gameplay_error::{GameplayError, OrReport},
+        level_physics::apply_level_physics,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RunMutators>();
+    app.register_type::<RunSeed>();
+    app.init_resource::<RunMutators>();
+    app.init_resource::<RunSeed>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (apply_gravity.after(apply_level_physics), apply_mirror),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), (reset_gravity, reset_mirror));
+    app.add_systems(
+        Update,
+        apply_bouncy
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay).and(mutator_active(Mutator::Bouncy))),
+    );
+}
+
+/// A single rule change that can be toggled for a run.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect)]
+pub enum Mutator {
+    LowGravity,
+    Bouncy,
+    EverlastingChains,
+    /// The first chain hit knocks the player out, however light.
+    OneHitDeath,
+    Mirror,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 5] = [
+        Mutator::LowGravity,
+        Mutator::Bouncy,
+        Mutator::EverlastingChains,
+        Mutator::OneHitDeath,
+        Mutator::Mirror,
+    ];
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Mutator::LowGravity => "Low gravity",
+            Mutator::Bouncy => "Bouncy everything",
+            Mutator::EverlastingChains => "Chains never expire",
+            Mutator::OneHitDeath => "One-hit death",
+            Mutator::Mirror => "Mirror mode",
+        }
+    }
+
+    /// How much this mutator scales the run's score. Mutators that make the
+    /// game harder are worth more, ones that make it easier are worth less.
+    pub fn score_multiplier(self) -> f32 {
+        match self {
+            Mutator::LowGravity => 0.9,
+            Mutator::Bouncy => 1.1,
+            Mutator::EverlastingChains => 0.75,
+            Mutator::OneHitDeath => 1.5,
+            Mutator::Mirror => 1.2,
+        }
+    }
+}
+
+/// The mutators enabled for the current run, as a bitset of [`Mutator`]s.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct RunMutators(u8);
+
+impl RunMutators {
+    pub fn contains(self, mutator: Mutator) -> bool {
+        self.0 & mutator.bit() != 0
+    }
+
+    pub fn insert(&mut self, mutator: Mutator) {
+        self.0 |= mutator.bit();
+    }
+
+    pub fn toggle(&mut self, mutator: Mutator) {
+        self.0 ^= mutator.bit();
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Builds a set from raw bits, ignoring bits that don't map to a [`Mutator`].
+    pub fn from_bits(bits: u8) -> Self {
+        let all = Mutator::ALL
+            .iter()
+            .fold(0, |acc, mutator| acc | mutator.bit());
+        Self(bits & all)
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Mutator> {
+        Mutator::ALL
+            .into_iter()
+            .filter(move |&mutator| self.contains(mutator))
+    }
+
+    /// Rolls a random combination of mutators. The same seed always gives the
+    /// same combination.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut mutators = Self::default();
+        for mutator in Mutator::ALL {
+            if rng.random_bool(0.4) {
+                mutators.insert(mutator);
+            }
+        }
+        mutators
+    }
+
+    /// The combined score multiplier of all enabled mutators.
+    pub fn score_multiplier(self) -> f32 {
+        self.iter().map(Mutator::score_multiplier).product()
+    }
+}
+
+/// The seed of the current run: its mutators are rolled from it, and its
+/// first level plays out from it.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct RunSeed(pub u64);
+
+/// A run condition that is true while the given mutator is enabled.
+pub fn mutator_active(mutator: Mutator) -> impl Fn(Res<RunMutators>) -> bool + Clone {
+    move |mutators: Res<RunMutators>| mutators.contains(mutator)
+}
+
+const LOW_GRAVITY_SCALE: f32 = 0.4;
+const BOUNCY_RESTITUTION: f32 = 0.9;
+
+fn apply_gravity(mutators: Res<RunMutators>, mut gravity: ResMut<Gravity>) {
+    if mutators.contains(Mutator::LowGravity) {
+        // Scale whatever gravity the level has.
+        gravity.0 *= LOW_GRAVITY_SCALE;
+    }
+}
+
+fn reset_gravity(mut gravity: ResMut<Gravity>) {
+    gravity.0 = GRAVITY;
+}
+
+/// Make every new body in the level bouncy, overriding its own restitution.
+fn apply_bouncy(mut commands: Commands, body_query: Query<Entity, Added<RigidBody>>) {
+    for entity in &body_query {
+        commands.entity(entity).insert(
+            Restitution::new(BOUNCY_RESTITUTION).with_combine_rule(CoefficientCombine::Max),
+        );
+    }
+}
+
+/// Mirror the view horizontally. Movement input is mirrored in the player module
+/// so controls stay relative to the screen.
+fn apply_mirror(
+    mutators: Res<RunMutators>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if !mutators.contains(Mutator::Mirror) {
+        return;
+    }
+    if let Some(mut camera) =
+        camera_query
+            .single_mut()
+            .or_report(&mut errors, "apply_mirror", "camera")
+    {
+        camera.scale.x = -camera.scale.x.abs();
+    }
+}
+
+fn reset_mirror(
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if let Some(mut camera) =
+        camera_query
+            .single_mut()
+            .or_report(&mut errors, "reset_mirror", "camera")
+    {
+        camera.scale.x = camera.scale.x.abs();
+    }
+}
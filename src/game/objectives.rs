@@ -0,0 +1,408 @@
+//! What a level asks of the player, and how well they did it.
+//!
+//! Levels have collectibles to pick up, a goal zone at their exit and
+//! optionally a time limit. Picking things up scores points, and reaching the
+//! goal scores more, plus a bonus for any time left. Reaching the goal sends
+//! a [`GoalReached`], and after a short celebration moves on to
+//! [`Screen::Results`]. Falling below the level (or letting a partner left
+//! behind fall), running into a saw or crusher, or running out of time opens
+//! [`Menu::Retry`] instead, as does losing to a boss or to chain impacts.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems, Pause,
+    game::{
+        chain_impact::PlayerHealth,
+        cheats::Cheats,
+        gameplay_error::{GameplayError, OrReport},
+        hazards::HazardKind,
+        level::LevelBounds,
+        level_data::{CollectibleData, ParData},
+        player::{Character, Player},
+        run_stats::{RunStats, format_time},
+        trigger_volume::{DetectTriggers, TriggerEntered, trigger_volume},
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::layout::HudAnchor,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Collectible>();
+    app.register_type::<GoalZone>();
+    app.register_type::<ObjectivesHud>();
+    app.init_resource::<Score>();
+    app.init_resource::<LevelObjectives>();
+    app.init_resource::<Defeat>();
+    app.add_event::<GoalReached>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), (reset_score, spawn_hud));
+    app.add_systems(
+        Update,
+        (
+            (pick_up_collectibles, reach_goal, check_defeat)
+                .chain()
+                .in_set(AppSystems::Update)
+                .in_set(PausableSystems)
+                .after(DetectTriggers),
+            update_hud.in_set(AppSystems::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Points for reaching the goal.
+const GOAL_POINTS: u32 = 500;
+/// Points for every second left on the clock when reaching the goal.
+const TIME_BONUS_POINTS: f32 = 10.0;
+/// How close the player has to get to pick something up.
+const PICKUP_RADIUS: f32 = 24.0;
+/// How close the player has to get to the goal to reach it.
+const GOAL_RADIUS: f32 = 30.0;
+/// How far below the level the player can fall before the run is lost.
+const FALL_MARGIN: f32 = 150.0;
+const COLLECTIBLE_SIZE: f32 = 14.0;
+const COLLECTIBLE_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const GOAL_COLOR: Color = Color::srgba(0.3, 1.0, 0.5, 0.5);
+
+/// The `index`th collectible of the level.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Collectible {
+    pub index: usize,
+    pub points: u32,
+}
+
+pub fn collectible(index: usize, data: &CollectibleData) -> impl Bundle {
+    (
+        Name::new(format!("Collectible {index}")),
+        Collectible {
+            index,
+            points: data.points,
+        },
+        trigger_volume(Collider::circle(PICKUP_RADIUS)),
+        Sprite {
+            color: COLLECTIBLE_COLOR,
+            custom_size: Some(Vec2::splat(COLLECTIBLE_SIZE)),
+            ..default()
+        },
+        // Turned to stand on a corner, so it reads as a gem.
+        Transform::from_translation(data.position.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// Where the level ends.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct GoalZone;
+
+pub fn goal_zone(position: Vec2) -> impl Bundle {
+    (
+        Name::new("Goal Zone"),
+        GoalZone,
+        trigger_volume(Collider::circle(GOAL_RADIUS)),
+        Sprite {
+            color: GOAL_COLOR,
+            custom_size: Some(Vec2::splat(GOAL_RADIUS * 2.0)),
+            ..default()
+        },
+        Transform::from_translation(position.extend(-1.0)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// What the current level asks of the player, set when it's spawned.
+#[derive(Resource, Default, Debug)]
+pub struct LevelObjectives {
+    /// How many collectibles there are to pick up.
+    pub collectibles: usize,
+    /// How many secrets there are to find.
+    pub secrets: usize,
+    /// Seconds to reach the goal in, if the level is timed.
+    pub time_limit: Option<f32>,
+    /// The hooks and time to beat, shown on the results screen.
+    pub par: Option<ParData>,
+}
+
+impl LevelObjectives {
+    /// Seconds left on the clock, if the level is timed.
+    pub fn time_left(&self, stats: &RunStats) -> Option<f32> {
+        self.time_limit.map(|limit| (limit - stats.time).max(0.0))
+    }
+
+    /// How many medals the level has to win. Only levels with a par have any.
+    pub fn medals(&self) -> usize {
+        if self.par.is_some() {
+            Medal::ALL.len()
+        } else {
+            0
+        }
+    }
+
+    /// The medals a finished run with `stats` won.
+    pub fn medals_won(&self, stats: &RunStats) -> Vec<Medal> {
+        let Some(par) = self.par else {
+            return Vec::new();
+        };
+        Medal::ALL
+            .into_iter()
+            .filter(|medal| match medal {
+                Medal::ParTime => stats.time <= par.time,
+                Medal::ParHooks => stats.hooks_fired <= par.hooks,
+            })
+            .collect()
+    }
+}
+
+/// Won by finishing a level at or under one of its pars.
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum Medal {
+    ParTime,
+    ParHooks,
+}
+
+impl Medal {
+    pub const ALL: [Self; 2] = [Self::ParTime, Self::ParHooks];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ParTime => "Par time",
+            Self::ParHooks => "Par hooks",
+        }
+    }
+}
+
+/// Points scored this run.
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Score {
+    pub points: u32,
+    /// The indices of the collectibles picked up so far.
+    pub collected: Vec<usize>,
+    /// The indices of the secrets found so far.
+    #[serde(default)]
+    pub secrets: Vec<usize>,
+    /// Whether any assist has been on this run.
+    #[serde(default)]
+    pub assisted: bool,
+}
+
+/// Why the player has to retry.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DefeatCause {
+    Fell,
+    /// The character left behind fell, in levels with two.
+    PartnerFell,
+    /// Caught in a hazard of this kind.
+    Hazard(HazardKind),
+    OutOfTime,
+    /// Lost the tug of war with a boss.
+    DraggedIn,
+    /// Hit by fast chain links too often.
+    ChainHit,
+}
+
+impl DefeatCause {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Fell => "You fell off the level",
+            Self::PartnerFell => "Your partner fell off the level",
+            Self::Hazard(HazardKind::Saw) => "You were cut by a saw",
+            Self::Hazard(HazardKind::Crusher) => "You were crushed",
+            Self::Hazard(_) => "You got caught in a hazard",
+            Self::OutOfTime => "You ran out of time",
+            Self::DraggedIn => "The boss dragged you in",
+            Self::ChainHit => "A chain knocked you out",
+        }
+    }
+}
+
+/// How the last attempt was lost, for the retry menu.
+#[derive(Resource, Default, Debug)]
+pub struct Defeat(pub Option<DefeatCause>);
+
+pub fn reset_score(mut score: ResMut<Score>, mut defeat: ResMut<Defeat>) {
+    *score = Score::default();
+    defeat.0 = None;
+}
+
+fn pick_up_collectibles(
+    mut commands: Commands,
+    mut entered: EventReader<TriggerEntered>,
+    player_query: Query<Entity, With<Player>>,
+    collectible_query: Query<&Collectible>,
+    mut score: ResMut<Score>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if entered.is_empty() {
+        return;
+    }
+    let Some(player) =
+        player_query
+            .single()
+            .or_report(&mut errors, "pick_up_collectibles", "player")
+    else {
+        return;
+    };
+    for event in entered.read() {
+        if event.other != player {
+            continue;
+        }
+        let Ok(collectible) = collectible_query.get(event.volume) else {
+            continue;
+        };
+        score.points += collectible.points;
+        score.collected.push(collectible.index);
+        commands.entity(event.volume).despawn();
+    }
+}
+
+/// Sent when the player reaches the goal, with where they and the goal are.
+#[derive(Event, Debug)]
+pub struct GoalReached {
+    pub player: Vec2,
+    pub goal: Vec2,
+}
+
+fn reach_goal(
+    mut entered: EventReader<TriggerEntered>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    goal_query: Query<&GlobalTransform, With<GoalZone>>,
+    objectives: Res<LevelObjectives>,
+    stats: Res<RunStats>,
+    mut score: ResMut<Score>,
+    mut goal_reached: EventWriter<GoalReached>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if entered.is_empty() {
+        return;
+    }
+    let Some((player, player_transform)) =
+        player_query
+            .single()
+            .or_report(&mut errors, "reach_goal", "player")
+    else {
+        return;
+    };
+    let Some(goal) = entered
+        .read()
+        .filter(|event| event.other == player)
+        .find_map(|event| goal_query.get(event.volume).ok())
+    else {
+        return;
+    };
+    let time_bonus = objectives.time_left(&stats).unwrap_or_default() * TIME_BONUS_POINTS;
+    score.points += GOAL_POINTS + time_bonus as u32;
+    goal_reached.write(GoalReached {
+        player: player_transform.translation().truncate(),
+        goal: goal.translation().truncate(),
+    });
+}
+
+fn check_defeat(
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    partner_query: Query<&GlobalTransform, (With<Character>, Without<Player>)>,
+    collisions: Collisions,
+    hazard_query: Query<&HazardKind>,
+    bounds: Res<LevelBounds>,
+    objectives: Res<LevelObjectives>,
+    stats: Res<RunStats>,
+    cheats: Res<Cheats>,
+    mut health: ResMut<PlayerHealth>,
+    mut defeat: ResMut<Defeat>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some((player, transform)) =
+        player_query
+            .single()
+            .or_report(&mut errors, "check_defeat", "player")
+    else {
+        return;
+    };
+    let hit_hazard = collisions.collisions_with(player).find_map(|pair| {
+        let other = if pair.collider1 == player {
+            pair.collider2
+        } else {
+            pair.collider1
+        };
+        hazard_query
+            .get(other)
+            .ok()
+            .filter(|kind| kind.cuts_chains())
+            .copied()
+    });
+    let hit_hazard = hit_hazard.filter(|_| !cheats.god_mode);
+    let fell =
+        |transform: &GlobalTransform| transform.translation().y < bounds.0.min.y - FALL_MARGIN;
+    let cause = if fell(transform) {
+        DefeatCause::Fell
+    } else if partner_query.iter().any(fell) {
+        DefeatCause::PartnerFell
+    } else if let Some(kind) = hit_hazard {
+        // Hazards take all the player's health in one go.
+        health.current = 0.0;
+        DefeatCause::Hazard(kind)
+    } else if objectives.time_left(&stats) == Some(0.0) {
+        DefeatCause::OutOfTime
+    } else {
+        return;
+    };
+    defeat.0 = Some(cause);
+    next_pause.set(Pause(true));
+    next_menu.set(Menu::Retry);
+}
+
+/// Shows the score, the collectibles found and the clock during gameplay.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ObjectivesHud;
+
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Objectives HUD"),
+        Node {
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        HudAnchor::Top(10.0),
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+        children![(
+            Name::new("Objectives Text"),
+            Text::default(),
+            TextFont::from_font_size(20.0),
+            ObjectivesHud,
+        )],
+    ));
+}
+
+fn update_hud(
+    score: Res<Score>,
+    objectives: Res<LevelObjectives>,
+    stats: Res<RunStats>,
+    mut text: Single<&mut Text, With<ObjectivesHud>>,
+) {
+    let clock = match objectives.time_left(&stats) {
+        Some(left) => format!("Time left {}", format_time(left)),
+        None => format!("Time {}", format_time(stats.time)),
+    };
+    // Levels without secrets don't mention them.
+    let secrets = match objectives.secrets {
+        0 => String::new(),
+        total => format!("   Secrets {}/{total}", score.secrets.len()),
+    };
+    text.0 = format!(
+        "Score {}   Found {}/{}{secrets}   {clock}",
+        score.points,
+        score.collected.len(),
+        objectives.collectibles
+    );
+}
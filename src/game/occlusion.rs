@@ -0,0 +1,98 @@
+//! Sounds muffled by level geometry.
+//!
+//! Sounds with an [`OccludedSound`] come from a point in the level. Every
+//! frame, a ray is cast from each of them to the player, who is the one
+//! listening. While static obstacles are in the way, the sound fades down to
+//! [`OCCLUDED_VOLUME`] of its normal volume. Bevy's audio has no filters, so
+//! occlusion only changes the volume.
+
+use avian2d::prelude::*;
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    AppSystems,
+    audio::{Music, category_volume},
+    game::{
+        chain::Layer,
+        gameplay_error::{GameplayError, OrReport},
+        player::Player,
+    },
+    persistence::Settings,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<OccludedSound>();
+
+    app.add_systems(
+        Update,
+        occlude_sounds
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How loud a sound is behind a wall, compared to in the open.
+const OCCLUDED_VOLUME: f32 = 0.3;
+/// How quickly sounds fade in and out of occlusion, per second.
+const OCCLUSION_FADE: f32 = 8.0;
+/// Hits closer to the sound than this are the surface making the sound.
+const SOURCE_MARGIN: f32 = 2.0;
+const MAX_HITS: u32 = 4;
+
+/// A sound coming from its [`Transform`], which can be muffled by walls
+/// between it and the player.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct OccludedSound {
+    /// How much of its volume the sound currently plays at. Starts out at
+    /// whatever the first check says, without fading.
+    factor: Option<f32>,
+}
+
+fn occlude_sounds(
+    time: Res<Time>,
+    global_volume: Res<GlobalVolume>,
+    settings: Res<Settings>,
+    spatial_query: SpatialQuery,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut sound_query: Query<(
+        &GlobalTransform,
+        &PlaybackSettings,
+        &mut AudioSink,
+        &mut OccludedSound,
+        Has<Music>,
+    )>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "occlude_sounds", "player")
+    else {
+        return;
+    };
+    let listener = player.translation().truncate();
+    let filter = SpatialQueryFilter::from_mask(Layer::StaticObstacle);
+    for (transform, playback, mut sink, mut sound, is_music) in &mut sound_query {
+        let origin = transform.translation().truncate();
+        let Ok((direction, distance)) = Dir2::new_and_length(listener - origin) else {
+            continue;
+        };
+        let blocked = spatial_query
+            .ray_hits(origin, direction, distance, MAX_HITS, true, &filter)
+            .iter()
+            .any(|hit| hit.distance > SOURCE_MARGIN);
+
+        let target = if blocked { OCCLUDED_VOLUME } else { 1.0 };
+        let factor = sound.factor.map_or(target, |factor| {
+            factor.lerp(target, (OCCLUSION_FADE * time.delta_secs()).min(1.0))
+        });
+        sound.factor = Some(factor);
+        sink.set_volume(
+            global_volume.volume
+                * playback.volume
+                * category_volume(&settings, is_music)
+                * Volume::Linear(factor),
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! Paths through the level, for moving pieces to follow and the camera to
+//! keep to.
+//!
+//! A path is a list of control points joined up by a [`PathCurve`]: straight
+//! lines, a Catmull-Rom spline through every point, or cubic Bézier curves.
+//! The points a path passes through are its stops. A [`PathFollower`] eases
+//! out of and into each stop according to its [`PathEasing`], and at the end
+//! of the path loops, turns around or stays put according to its
+//! [`LoopMode`].
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    assists::Assists,
+    hazards::{HazardKind, KinematicMotion},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PathFollower>();
+
+    app.add_systems(Update, follow_paths.in_set(KinematicMotion));
+}
+
+/// How many straight pieces each curved leg of a path is drawn and followed
+/// as.
+const LEG_SAMPLES: usize = 16;
+
+/// How the control points of a path are joined up.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum PathCurve {
+    /// Straight lines from point to point.
+    #[default]
+    Linear,
+    /// A smooth curve through every point.
+    CatmullRom,
+    /// Cubic Bézier curves. After the first point, every third point is a
+    /// stop, and the two points before it are the handles that bend the
+    /// curve leading there.
+    Bezier,
+}
+
+impl PathCurve {
+    /// All curves, in the order the editor cycles through them.
+    pub const ALL: [Self; 3] = [Self::Linear, Self::CatmullRom, Self::Bezier];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::CatmullRom => "catmull-rom",
+            Self::Bezier => "bezier",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&curve| curve == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+
+    /// Whether the control point at `index` is one the path passes through,
+    /// rather than a Bézier handle.
+    pub fn is_stop(self, index: usize) -> bool {
+        self != Self::Bezier || index.is_multiple_of(3)
+    }
+}
+
+/// How a follower speeds up and slows down between two stops.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum PathEasing {
+    /// The same speed all the way.
+    #[default]
+    Linear,
+    /// Starts slow and ends slow.
+    InOut,
+    /// Starts slow.
+    In,
+    /// Ends slow.
+    Out,
+}
+
+impl PathEasing {
+    /// All easings, in the order the editor cycles through them.
+    pub const ALL: [Self; 4] = [Self::Linear, Self::InOut, Self::In, Self::Out];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::InOut => "ease in and out",
+            Self::In => "ease in",
+            Self::Out => "ease out",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&easing| easing == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+
+    /// How far along a leg the follower is, from 0 to 1, after `t` of the
+    /// time it takes to travel it.
+    pub fn ease(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::InOut => t * t * (3.0 - 2.0 * t),
+            Self::In => t * t,
+            Self::Out => 1.0 - (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+/// What a follower does at the end of its path.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum LoopMode {
+    /// Head back to the first point, and start over.
+    #[default]
+    Loop,
+    /// Turn around and go back the way it came, over and over.
+    PingPong,
+    /// Stop at the last point.
+    Once,
+}
+
+impl LoopMode {
+    /// All loop modes, in the order the editor cycles through them.
+    pub const ALL: [Self; 3] = [Self::Loop, Self::PingPong, Self::Once];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Loop => "loop",
+            Self::PingPong => "ping-pong",
+            Self::Once => "once",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&mode| mode == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+}
+
+/// A path broken down into straight pieces, one run of them for each leg
+/// between two stops.
+#[derive(Reflect, Debug, Clone, Default)]
+pub struct PathShape {
+    legs: Vec<PathLeg>,
+}
+
+#[derive(Reflect, Debug, Clone)]
+struct PathLeg {
+    points: Vec<Vec2>,
+    length: f32,
+}
+
+impl PathLeg {
+    fn new(points: Vec<Vec2>) -> Self {
+        let length = points
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .sum();
+        Self { points, length }
+    }
+
+    /// The point `distance` along the leg.
+    fn point_at(&self, mut distance: f32) -> Vec2 {
+        for pair in self.points.windows(2) {
+            let piece = pair[0].distance(pair[1]);
+            if distance <= piece {
+                return pair[0].lerp(pair[1], distance / piece.max(f32::EPSILON));
+            }
+            distance -= piece;
+        }
+        self.points.last().copied().unwrap_or_default()
+    }
+}
+
+impl PathShape {
+    /// The path through `points` joined up by `curve`. Closed paths lead
+    /// from the last stop back to the first.
+    pub fn new(points: &[Vec2], curve: PathCurve, closed: bool) -> Self {
+        let mut legs = Vec::new();
+        match curve {
+            PathCurve::Linear => {
+                legs.extend(points.windows(2).map(|pair| PathLeg::new(pair.to_vec())));
+            }
+            PathCurve::CatmullRom => {
+                let n = points.len();
+                let count = if closed { n } else { n.saturating_sub(1) };
+                // Open paths repeat their end points to have something to
+                // curve toward.
+                let point = |i: isize| {
+                    if closed {
+                        points[i.rem_euclid(n as isize) as usize]
+                    } else {
+                        points[i.clamp(0, n as isize - 1) as usize]
+                    }
+                };
+                for i in 0..count as isize {
+                    let [p0, p1, p2, p3] = [point(i - 1), point(i), point(i + 1), point(i + 2)];
+                    legs.push(PathLeg::new(
+                        (0..=LEG_SAMPLES)
+                            .map(|s| catmull_rom(p0, p1, p2, p3, s as f32 / LEG_SAMPLES as f32))
+                            .collect(),
+                    ));
+                }
+            }
+            PathCurve::Bezier => {
+                // Points after the last full curve are left out.
+                for i in (0..points.len().saturating_sub(3)).step_by(3) {
+                    let [a, b, c, d] = [points[i], points[i + 1], points[i + 2], points[i + 3]];
+                    legs.push(PathLeg::new(
+                        (0..=LEG_SAMPLES)
+                            .map(|s| bezier(a, b, c, d, s as f32 / LEG_SAMPLES as f32))
+                            .collect(),
+                    ));
+                }
+            }
+        }
+        // Catmull-Rom paths already curve back to the start.
+        if closed && curve != PathCurve::CatmullRom {
+            let last = legs.last().and_then(|leg| leg.points.last().copied());
+            if let Some((first, last)) = points
+                .first()
+                .copied()
+                .zip(last)
+                .filter(|(first, last)| first != last)
+            {
+                legs.push(PathLeg::new(vec![last, first]));
+            }
+        }
+        Self { legs }
+    }
+
+    /// The straight pieces of each leg, e.g. for drawing the path.
+    pub fn legs(&self) -> impl Iterator<Item = &[Vec2]> {
+        self.legs.iter().map(|leg| leg.points.as_slice())
+    }
+
+    /// The point on the path closest to `point`, or `None` if the path has
+    /// no legs.
+    pub fn closest_point(&self, point: Vec2) -> Option<Vec2> {
+        self.legs
+            .iter()
+            .flat_map(|leg| leg.points.windows(2))
+            .map(|pair| {
+                let along = pair[1] - pair[0];
+                let t = ((point - pair[0]).dot(along) / along.length_squared().max(f32::EPSILON))
+                    .clamp(0.0, 1.0);
+                pair[0] + along * t
+            })
+            .min_by(|a, b| {
+                a.distance_squared(point)
+                    .total_cmp(&b.distance_squared(point))
+            })
+    }
+
+    /// How long the whole path is, in pixels.
+    pub fn length(&self) -> f32 {
+        self.legs.iter().map(|leg| leg.length).sum()
+    }
+
+    /// The point `distance` along the whole path, from its start.
+    pub fn point_along(&self, mut distance: f32) -> Option<Vec2> {
+        for leg in &self.legs {
+            if distance <= leg.length {
+                return Some(leg.point_at(distance));
+            }
+            distance -= leg.length;
+        }
+        self.legs.last().and_then(|leg| leg.points.last().copied())
+    }
+}
+
+fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn bezier(a: Vec2, b: Vec2, c: Vec2, d: Vec2, t: f32) -> Vec2 {
+    let u = 1.0 - t;
+    u * u * u * a + 3.0 * u * u * t * b + 3.0 * u * t * t * c + t * t * t * d
+}
+
+/// Moves a kinematic body along a path at `speed` pixels per second.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct PathFollower {
+    pub shape: PathShape,
+    pub speed: f32,
+    pub easing: PathEasing,
+    pub loop_mode: LoopMode,
+    /// The leg being travelled.
+    pub leg: usize,
+    /// How long the follower has been on the current leg, in seconds.
+    pub elapsed: f32,
+    /// Whether it's heading back toward the start, in [`LoopMode::PingPong`].
+    pub reversed: bool,
+}
+
+impl PathFollower {
+    pub fn new(shape: PathShape, speed: f32, easing: PathEasing, loop_mode: LoopMode) -> Self {
+        Self {
+            shape,
+            speed,
+            easing,
+            loop_mode,
+            leg: 0,
+            elapsed: 0.0,
+            reversed: false,
+        }
+    }
+
+    /// Where the follower is on its path, or `None` if there's no path.
+    pub fn position(&self) -> Option<Vec2> {
+        let leg = self.shape.legs.get(self.leg)?;
+        let duration = leg.length / self.speed;
+        let t = if duration > 0.0 {
+            self.easing.ease((self.elapsed / duration).clamp(0.0, 1.0))
+        } else {
+            1.0
+        };
+        let t = if self.reversed { 1.0 - t } else { t };
+        Some(leg.point_at(t * leg.length))
+    }
+
+    /// Travel for `dt` seconds, moving on to the next leg whenever one is
+    /// done.
+    fn advance(&mut self, dt: f32) {
+        let legs = self.shape.legs.len();
+        // Without any distance to cover, a follower would move on forever.
+        if legs == 0 || self.speed <= 0.0 || self.shape.length() <= 0.0 {
+            return;
+        }
+        self.elapsed += dt;
+        loop {
+            let duration = self.shape.legs[self.leg].length / self.speed;
+            if self.elapsed < duration {
+                return;
+            }
+            match self.loop_mode {
+                LoopMode::Loop => self.leg = (self.leg + 1) % legs,
+                LoopMode::Once if self.leg + 1 == legs => {
+                    self.elapsed = duration;
+                    return;
+                }
+                LoopMode::Once => self.leg += 1,
+                LoopMode::PingPong => match (self.reversed, self.leg) {
+                    (true, 0) => self.reversed = false,
+                    (true, _) => self.leg -= 1,
+                    (false, leg) if leg + 1 == legs => self.reversed = true,
+                    (false, _) => self.leg += 1,
+                },
+            }
+            self.elapsed -= duration;
+        }
+    }
+}
+
+fn follow_paths(
+    time: Res<Time>,
+    assists: Res<Assists>,
+    mut follower_query: Query<(
+        &mut PathFollower,
+        &Transform,
+        &mut LinearVelocity,
+        Has<HazardKind>,
+    )>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    for (mut follower, transform, mut velocity, is_hazard) in &mut follower_query {
+        // Hazards are slowed down by their assist, other moving pieces aren't.
+        follower.advance(if is_hazard {
+            dt * assists.hazard_speed
+        } else {
+            dt
+        });
+        let Some(target) = follower.position() else {
+            velocity.0 = Vec2::ZERO;
+            continue;
+        };
+        // Head for where the follower should be by the end of the frame, which
+        // also makes up for any drift from the physics steps.
+        velocity.0 = (target - transform.translation.truncate()) / dt;
+    }
+}
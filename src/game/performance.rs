@@ -0,0 +1,66 @@
+//! Settings for keeping the game smooth on slower machines, and the handheld
+//! preset that sets them all at once.
+//!
+//! [`Settings::handheld`] is turned on by default when the game starts on a
+//! Steam Deck. It drops the [`PhysicsQuality`](super::physics_quality::PhysicsQuality) to low, halves
+//! [`Settings::particle_density`], caps the frame rate at
+//! [`HANDHELD_FRAME_CAP`] to save battery, and shows gamepad glyphs in prompts
+//! from the start rather than after the first button press.
+
+use bevy::prelude::*;
+
+use crate::{input::gamepad::ActiveController, persistence::Settings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, prefer_gamepad_prompts.run_if(on_handheld));
+    #[cfg(not(target_family = "wasm"))]
+    app.add_systems(Last, cap_frame_rate);
+}
+
+/// The frame rate the handheld preset caps at. Steam Deck screens can run at
+/// 40 Hz, which keeps this smooth.
+pub const HANDHELD_FRAME_CAP: u32 = 40;
+/// The frame caps to pick from in the settings, after none at all.
+pub const FRAME_CAPS: [u32; 4] = [30, 40, 60, 120];
+
+/// Whether the game is running on a Steam Deck, which Steam marks with an
+/// environment variable.
+pub fn running_on_handheld() -> bool {
+    std::env::var("SteamDeck").is_ok_and(|value| value == "1")
+}
+
+fn on_handheld(settings: Res<Settings>) -> bool {
+    settings.handheld
+}
+
+/// Handhelds are played with their built-in controller, so prompts show its
+/// buttons until a key is pressed or the mouse clicked.
+fn prefer_gamepad_prompts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_query: Query<Entity, With<Gamepad>>,
+    mut active: ResMut<ActiveController>,
+    mut used_keyboard: Local<bool>,
+) {
+    if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some() {
+        *used_keyboard = true;
+    }
+    if *used_keyboard || active.0.is_some() {
+        return;
+    }
+    if let Some(gamepad) = gamepad_query.iter().next() {
+        active.0 = Some(gamepad);
+    }
+}
+
+/// Sleep off whatever is left of the frame's share of the cap.
+#[cfg(not(target_family = "wasm"))]
+fn cap_frame_rate(settings: Res<Settings>, mut frame_start: Local<Option<web_time::Instant>>) {
+    if let (Some(cap), Some(start)) = (settings.frame_cap, *frame_start) {
+        let frame_time = std::time::Duration::from_secs_f64(1.0 / f64::from(cap.max(1)));
+        if let Some(left) = frame_time.checked_sub(start.elapsed()) {
+            std::thread::sleep(left);
+        }
+    }
+    *frame_start = Some(web_time::Instant::now());
+}
@@ -0,0 +1,127 @@
+//! Trading physics fidelity for framerate.
+//!
+//! The [`PhysicsQuality`] in the [`Settings`] picks how many substeps the
+//! physics runs per frame, how many restitution iterations the solver makes,
+//! and whether debris keeps its continuous collision detection. Chains are by
+//! far the most expensive thing to simulate, so fewer substeps mostly means
+//! stretchier chains. Changes apply right away, even mid-swing.
+//!
+//! Long chains need more substeps to hold together than the quality may
+//! give, so while one is out the physics takes at least
+//! [`ChainConfig::long_chain_substeps`].
+
+use avian2d::{dynamics::solver::SolverConfig, prelude::*};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems,
+    game::{
+        chain::{ChainConfig, ChainState},
+        debris::Debris,
+    },
+    persistence::Settings,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            apply_physics_quality.run_if(
+                resource_changed::<Settings>
+                    .or(resource_changed::<ChainState>)
+                    .or(resource_changed::<ChainConfig>),
+            ),
+            apply_debris_ccd.in_set(AppSystems::Update).run_if(
+                in_state(Screen::Gameplay)
+                    .and(resource_changed::<Settings>.or(any_match_filter::<Added<Debris>>)),
+            ),
+        ),
+    );
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+pub enum PhysicsQuality {
+    /// For low-end machines. Chains stretch noticeably under load.
+    Low,
+    /// Avian's defaults.
+    #[default]
+    Medium,
+    /// Stiffer chains and bouncier bounces, for machines with time to spare.
+    High,
+}
+
+impl PhysicsQuality {
+    /// All qualities, lowest first.
+    pub const ALL: [Self; 3] = [Self::Low, Self::Medium, Self::High];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::High => "High",
+        }
+    }
+
+    pub fn substeps(self) -> u32 {
+        match self {
+            Self::Low => 3,
+            Self::Medium => 6,
+            Self::High => 12,
+        }
+    }
+
+    /// Avian's solver relaxes contacts once per substep, so restitution is the
+    /// only part of it with iterations of its own.
+    pub fn restitution_iterations(self) -> usize {
+        match self {
+            Self::Low | Self::Medium => 1,
+            Self::High => 2,
+        }
+    }
+
+    /// Whether debris keeps the [`SweptCcd`] it had. Without it, debris can
+    /// tunnel through thin walls when flung hard enough.
+    pub fn debris_ccd(self) -> bool {
+        self != Self::Low
+    }
+}
+
+fn apply_physics_quality(
+    settings: Res<Settings>,
+    config: Res<ChainConfig>,
+    chain_state: Res<ChainState>,
+    mut substeps: ResMut<SubstepCount>,
+    mut solver: ResMut<SolverConfig>,
+) {
+    let quality = settings.physics_quality;
+    let wanted = if chain_state.chains.iter().any(|chain| config.is_long(chain)) {
+        quality.substeps().max(config.long_chain_substeps as u32)
+    } else {
+        quality.substeps()
+    };
+    if substeps.0 != wanted {
+        substeps.0 = wanted;
+    }
+    if solver.restitution_iterations != quality.restitution_iterations() {
+        solver.restitution_iterations = quality.restitution_iterations();
+    }
+}
+
+/// Debris starts out with CCD, being broken-off chain links, so this only
+/// takes it away and gives it back.
+fn apply_debris_ccd(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    debris_query: Query<(Entity, Has<SweptCcd>), With<Debris>>,
+) {
+    let ccd = settings.physics_quality.debris_ccd();
+    for (entity, has_ccd) in &debris_query {
+        if ccd && !has_ccd {
+            commands.entity(entity).insert(SweptCcd::default());
+        } else if !ccd && has_ccd {
+            commands.entity(entity).remove::<SweptCcd>();
+        }
+    }
+}
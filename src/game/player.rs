@@ -0,0 +1,354 @@
+//! Player-specific behavior.
+//!
+//! Some levels have two characters: the usual one with the hooks, and a
+//! heavy partner who can't hook but weighs enough to hold down big
+//! switches. Whichever is being played has the [`Player`] marker, and
+//! swapping moves it to the other. The one left behind becomes a body like
+//! any prop, for the other to stand on, and unless
+//! [`Settings::partner_hooking`] is off, to hook and reel in: to pull them
+//! up before they fall, or to fling them.
+
+use avian2d::prelude::*;
+use bevy::{
+    image::{ImageLoaderSettings, ImageSampler},
+    prelude::*,
+};
+
+use crate::{
+    AppSystems, PausableSystems,
+    asset_tracking::LoadResource,
+    game::{
+        animation::PlayerAnimation,
+        chain::{ChainPool, ChainState, Layer},
+        highlight::HookTarget,
+        hookable::{AddHookable, Hookable, Hooked, Unhooked},
+        movement::MovementController,
+        mutators::{Mutator, RunMutators},
+        trigger_volume::TriggerActivator,
+    },
+    input::{ActionInput, InputAction},
+    persistence::Settings,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Player>();
+    app.register_type::<Character>();
+    app.register_type::<InTow>();
+    app.add_hookable::<InTow>();
+
+    app.register_type::<PlayerAssets>();
+    app.load_resource::<PlayerAssets>();
+
+    // Record directional input as movement controls.
+    app.add_systems(
+        Update,
+        record_player_directional_input
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(
+        Update,
+        (
+            swap_characters,
+            allow_partner_hooking,
+            walk_heavy,
+            tint_towed_characters,
+        )
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The heavy partner's size, in pixels.
+const HEAVY_SIZE: Vec2 = Vec2::splat(40.0);
+const HEAVY_MASS: f32 = 20.0;
+const HEAVY_SPEED: f32 = 220.0;
+const HEAVY_COLOR: Color = Color::srgb(0.55, 0.45, 0.75);
+/// What a character in tow is tinted toward.
+const TOW_COLOR: Color = Color::srgb(1.0, 0.8, 0.3);
+/// The hooking character's collider while left behind, before their scale
+/// is applied.
+const HOOKER_RADIUS: f32 = 10.0;
+const HOOKER_MASS: f32 = 4.0;
+
+/// The player character, starting at `position`.
+pub fn player(
+    position: Vec2,
+    max_speed: f32,
+    player_assets: &PlayerAssets,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+) -> impl Bundle {
+    // A texture atlas is a way to split a single image into a grid of related images.
+    // You can learn more in this example: https://github.com/bevyengine/bevy/blob/latest/examples/2d/texture_atlas.rs
+    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 6, 2, Some(UVec2::splat(1)), None);
+    let texture_atlas_layout = texture_atlas_layouts.add(layout);
+    let player_animation = PlayerAnimation::new();
+
+    (
+        Name::new("Player"),
+        Player,
+        Character::Hooker,
+        InTow::default(),
+        TriggerActivator,
+        Sprite {
+            image: player_assets.ducky.clone(),
+            texture_atlas: Some(TextureAtlas {
+                layout: texture_atlas_layout,
+                index: player_animation.get_atlas_index(),
+            }),
+            ..default()
+        },
+        Transform::from_translation(position.extend(0.0)).with_scale(Vec2::splat(2.0).extend(1.0)),
+        MovementController {
+            max_speed,
+            ..default()
+        },
+        player_animation,
+    )
+}
+
+/// The heavy partner, starting at `position` and left behind until swapped
+/// to.
+pub fn partner(position: Vec2) -> impl Bundle {
+    (
+        Name::new("Partner"),
+        Character::Heavy,
+        InTow::default(),
+        TriggerActivator,
+        Sprite::from_color(HEAVY_COLOR, HEAVY_SIZE),
+        Transform::from_translation(position.extend(0.0)),
+        MovementController {
+            max_speed: HEAVY_SPEED,
+            ..default()
+        },
+        Character::Heavy.left_behind(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// The character being played.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct Player;
+
+/// Which of a level's characters this is.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum Character {
+    /// Fires chains.
+    Hooker,
+    /// Can't fire chains, but presses big switches.
+    Heavy,
+}
+
+impl Character {
+    fn color(self) -> Color {
+        match self {
+            Self::Hooker => Color::WHITE,
+            Self::Heavy => HEAVY_COLOR,
+        }
+    }
+
+    fn collider(self) -> Collider {
+        match self {
+            Self::Hooker => Collider::circle(HOOKER_RADIUS),
+            Self::Heavy => Collider::rectangle(HEAVY_SIZE.x, HEAVY_SIZE.y),
+        }
+    }
+
+    fn mass(self) -> Mass {
+        match self {
+            Self::Hooker => Mass(HOOKER_MASS),
+            Self::Heavy => Mass(HEAVY_MASS),
+        }
+    }
+
+    /// The body of the character while the other one is played, like a
+    /// prop's. Chains pass through it until [`allow_partner_hooking`] says
+    /// otherwise.
+    fn left_behind(self) -> impl Bundle {
+        (
+            RigidBody::Dynamic,
+            self.collider(),
+            self.mass(),
+            LockedAxes::ROTATION_LOCKED,
+            CollisionLayers::new(
+                [Layer::DynamicProp],
+                [
+                    Layer::StaticObstacle,
+                    Layer::Player,
+                    Layer::Hazard,
+                    Layer::DynamicProp,
+                ],
+            ),
+        )
+    }
+}
+
+/// How many chains have hold of a character.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct InTow(usize);
+
+impl InTow {
+    pub fn towed(&self) -> bool {
+        self.0 > 0
+    }
+}
+
+impl Hookable for InTow {
+    fn on_hook(&mut self, _: Entity, _: &Hooked, _: &mut Commands) {
+        self.0 += 1;
+    }
+
+    fn on_release(&mut self, _: Entity, _: &Unhooked, _: &mut Commands) {
+        self.0 = self.0.saturating_sub(1);
+    }
+}
+
+/// Run condition for things only the hooking character does.
+pub fn playing_hooker(player_query: Query<&Character, With<Player>>) -> bool {
+    player_query
+        .iter()
+        .all(|&character| character == Character::Hooker)
+}
+
+/// Hand control to the other character. The hooking character lets go of
+/// their chains first.
+fn swap_characters(
+    mut commands: Commands,
+    input: ActionInput,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    mut character_query: Query<(Entity, &Character, &mut MovementController, Has<Player>)>,
+) {
+    if !input.just_pressed(InputAction::SwapCharacter) || character_query.iter().count() < 2 {
+        return;
+    }
+    for chain in chain_state.chains.drain(..) {
+        chain.release(&mut commands, &mut pool);
+    }
+    for (entity, &character, mut controller, playing) in &mut character_query {
+        let mut entity = commands.entity(entity);
+        if playing {
+            controller.intent = Vec2::ZERO;
+            entity.remove::<Player>().insert(character.left_behind());
+            continue;
+        }
+        entity.remove::<HookTarget>().insert(Player);
+        match character {
+            // Moves freely until hanging from a chain again.
+            Character::Hooker => {
+                entity.remove::<(RigidBody, Collider, Mass, LockedAxes, CollisionLayers)>();
+            }
+            // Walks, and falls under gravity.
+            Character::Heavy => {
+                entity.insert(CollisionLayers::new(
+                    [Layer::Player],
+                    [Layer::StaticObstacle, Layer::Hazard, Layer::DynamicProp],
+                ));
+            }
+        }
+    }
+}
+
+/// Let chains grab the character left behind, or not, as the settings say.
+fn allow_partner_hooking(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut partner_query: Query<
+        (Entity, &mut CollisionLayers, Has<HookTarget>),
+        (With<Character>, Without<Player>),
+    >,
+) {
+    for (entity, mut layers, hookable) in &mut partner_query {
+        if hookable == settings.partner_hooking {
+            continue;
+        }
+        if settings.partner_hooking {
+            layers.filters.add(Layer::ChainLink);
+            commands.entity(entity).insert(HookTarget::Grabbable);
+        } else {
+            layers.filters.remove(Layer::ChainLink);
+            commands.entity(entity).remove::<HookTarget>();
+        }
+    }
+}
+
+/// Tint characters while chains have hold of them.
+fn tint_towed_characters(
+    mut character_query: Query<(&Character, &InTow, &mut Sprite), Changed<InTow>>,
+) {
+    for (&character, in_tow, mut sprite) in &mut character_query {
+        sprite.color = if in_tow.towed() {
+            character.color().mix(&TOW_COLOR, 0.5)
+        } else {
+            character.color()
+        };
+    }
+}
+
+/// The heavy partner walks along the ground, where the hooking character
+/// flies.
+fn walk_heavy(
+    mut player_query: Query<(&Character, &MovementController, &mut LinearVelocity), With<Player>>,
+) {
+    for (&character, controller, mut velocity) in &mut player_query {
+        if character == Character::Heavy {
+            velocity.x = controller.intent.x * controller.max_speed;
+        }
+    }
+}
+
+fn record_player_directional_input(
+    input: ActionInput,
+    mutators: Res<RunMutators>,
+    mut controller_query: Query<&mut MovementController, With<Player>>,
+) {
+    // Collect directional input. Diagonal movement is already normalized to the
+    // same speed as horizontal / vertical.
+    let mut intent = input.movement();
+
+    // Keep controls relative to the screen when the view is mirrored.
+    if mutators.contains(Mutator::Mirror) {
+        intent.x = -intent.x;
+    }
+
+    // Apply movement intent to controllers.
+    for mut controller in &mut controller_query {
+        controller.intent = intent;
+    }
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct PlayerAssets {
+    #[dependency]
+    ducky: Handle<Image>,
+    #[dependency]
+    pub steps: Vec<Handle<AudioSource>>,
+}
+
+impl FromWorld for PlayerAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            ducky: assets.load_with_settings(
+                "images/ducky.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            steps: vec![
+                assets.load("audio/sound_effects/step1.ogg"),
+                assets.load("audio/sound_effects/step2.ogg"),
+                assets.load("audio/sound_effects/step3.ogg"),
+                assets.load("audio/sound_effects/step4.ogg"),
+            ],
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Prefabs: named groups of level parts that are placed as a unit.
+//!
+//! All prefabs live in a single library file, `assets/levels/prefabs.prefabs.ron`.
+//! Levels place them with a [`PrefabInstance`], which can tweak or remove
+//! individual parts of that one instance through [`PartOverride`]s.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::level_data::{LevelData, ObstacleData, PropData},
+    ron_asset::RonAssetLoader,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<PrefabLibrary>();
+    app.register_asset_loader(RonAssetLoader::<PrefabLibrary>::new(&["prefabs.ron"]));
+}
+
+/// The prefab library used by all levels, relative to the assets folder.
+pub const PREFAB_LIBRARY_PATH: &str = "levels/prefabs.prefabs.ron";
+
+/// All prefabs by name.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct PrefabLibrary(pub BTreeMap<String, PrefabData>);
+
+/// A group of level parts, positioned relative to the prefab's origin.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PrefabData {
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleData>,
+    #[serde(default)]
+    pub props: Vec<PropData>,
+    #[serde(default)]
+    pub anchors: Vec<Vec2>,
+}
+
+/// A prefab placed in a level.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PrefabInstance {
+    /// The prefab's name in the [`PrefabLibrary`].
+    pub prefab: String,
+    pub position: Vec2,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<PartOverride>,
+}
+
+/// Identifies one part of a prefab.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefabPart {
+    Obstacle(usize),
+    Prop(usize),
+    Anchor(usize),
+}
+
+/// A per-instance tweak to one part of a prefab.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PartOverride {
+    pub part: PrefabPart,
+    /// Moves the part relative to where the prefab puts it.
+    #[serde(default)]
+    pub offset: Vec2,
+    #[serde(default)]
+    pub size: Option<Vec2>,
+    #[serde(default)]
+    pub mass: Option<f32>,
+    /// Leaves the part out of this instance entirely.
+    #[serde(default)]
+    pub removed: bool,
+}
+
+impl PrefabData {
+    /// The parts of this prefab placed at `position`, with `overrides` applied.
+    pub fn instantiate(&self, position: Vec2, overrides: &[PartOverride]) -> PrefabData {
+        let find = |part| overrides.iter().find(|tweak| tweak.part == part);
+        let mut placed = PrefabData::default();
+
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            let mut obstacle = *obstacle;
+            obstacle.position += position;
+            if let Some(tweak) = find(PrefabPart::Obstacle(i)) {
+                if tweak.removed {
+                    continue;
+                }
+                obstacle.position += tweak.offset;
+                obstacle.size = tweak.size.unwrap_or(obstacle.size);
+            }
+            placed.obstacles.push(obstacle);
+        }
+
+        for (i, prop) in self.props.iter().enumerate() {
+            let mut prop = *prop;
+            prop.position += position;
+            if let Some(tweak) = find(PrefabPart::Prop(i)) {
+                if tweak.removed {
+                    continue;
+                }
+                prop.position += tweak.offset;
+                prop.size = tweak.size.unwrap_or(prop.size);
+                prop.mass = tweak.mass.unwrap_or(prop.mass);
+            }
+            placed.props.push(prop);
+        }
+
+        for (i, anchor) in self.anchors.iter().enumerate() {
+            let mut anchor = *anchor + position;
+            if let Some(tweak) = find(PrefabPart::Anchor(i)) {
+                if tweak.removed {
+                    continue;
+                }
+                anchor += tweak.offset;
+            }
+            placed.anchors.push(anchor);
+        }
+
+        placed
+    }
+
+    /// The bounds of this prefab's parts, relative to its origin.
+    pub fn bounds(&self) -> Rect {
+        LevelData {
+            spawn: None,
+            obstacles: self.obstacles.clone(),
+            props: self.props.clone(),
+            anchors: self.anchors.clone(),
+            ..default()
+        }
+        .bounds()
+    }
+}
+
+impl LevelData {
+    /// A copy of this level with all prefab instances expanded into plain
+    /// parts. Returns the names of prefabs missing from the library.
+    pub fn flatten(&self, library: &PrefabLibrary) -> (LevelData, Vec<String>) {
+        let mut flat = LevelData {
+            prefabs: Vec::new(),
+            ..self.clone()
+        };
+        let mut missing = Vec::new();
+
+        for instance in &self.prefabs {
+            let Some(prefab) = library.0.get(&instance.prefab) else {
+                missing.push(instance.prefab.clone());
+                continue;
+            };
+            let placed = prefab.instantiate(instance.position, &instance.overrides);
+            flat.obstacles.extend(placed.obstacles);
+            flat.props.extend(placed.props);
+            flat.anchors.extend(placed.anchors);
+        }
+
+        (flat, missing)
+    }
+}
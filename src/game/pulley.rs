@@ -0,0 +1,222 @@
+//! Hoisting things with a rope routed over two anchors.
+//!
+//! Hook two anchors with the two newest chains, then press pulley next to
+//! something loose, like a crate. The chains make way for a single rope that
+//! runs from the player up over the first anchor, across to the second, and
+//! down to the crate. The rope slides freely over both anchors and only its
+//! total length is kept, like an ideal pulley: walking away from the first
+//! anchor hoists the crate toward the second, and reeling in or out takes up
+//! or pays out rope. Press pulley again to let go.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{Chain, ChainAttached, ChainConfig, ChainPool, ChainState, LINK_SIZE},
+        gameplay_error::{GameplayError, OrReport},
+        highlight::HookTarget,
+        player::Player,
+    },
+    input::{ActionInput, InputAction},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Pulley>();
+
+    app.add_systems(
+        Update,
+        (
+            (toggle_pulley, hoist_with_pulley)
+                .chain()
+                .in_set(PausableSystems),
+            draw_pulleys,
+        )
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How close to the player something loose has to be to tie the rope to it.
+const GRAB_RADIUS: f32 = 60.0;
+/// How hard the rope pulls the load back in once it's stretched, per second.
+const ROPE_STIFFNESS: f32 = 20.0;
+const ROPE_COLOR: Color = Color::srgb(0.9, 0.85, 0.7);
+
+/// A rope from the player over `wheels` to `load`.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Pulley {
+    /// The anchors the rope runs over, the player's side first.
+    wheels: [Vec2; 2],
+    load: Entity,
+    /// How long the rope is, end to end.
+    length: f32,
+}
+
+impl Pulley {
+    /// How much rope there is on either side of the span between the wheels.
+    fn reach(&self) -> f32 {
+        self.length - self.wheels[0].distance(self.wheels[1])
+    }
+}
+
+/// Turn the two newest chains into a pulley, if both hooked an anchor and
+/// there is something to hoist, or let go of the pulley.
+fn toggle_pulley(
+    mut commands: Commands,
+    input: ActionInput,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    pulley_query: Query<Entity, With<Pulley>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    attached_query: Query<&ChainAttached>,
+    target_query: Query<(Entity, &HookTarget, &GlobalTransform)>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if !input.just_pressed(InputAction::Pulley) {
+        return;
+    }
+    if !pulley_query.is_empty() {
+        for pulley in &pulley_query {
+            commands.entity(pulley).despawn();
+        }
+        return;
+    }
+
+    let [.., older, newer] = chain_state.chains.as_slice() else {
+        return;
+    };
+    let wheel = |chain: &Chain| {
+        let attached = attached_query.get(*chain.links.last()?).ok()?;
+        let (_, &target, transform) = target_query.get(attached.target).ok()?;
+        (target == HookTarget::Anchor).then(|| transform.translation().truncate())
+    };
+    let (Some(first), Some(second)) = (wheel(older), wheel(newer)) else {
+        return;
+    };
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "toggle_pulley", "player")
+    else {
+        return;
+    };
+    let position = player.translation().truncate();
+    let Some((load, load_position)) = target_query
+        .iter()
+        .filter(|(_, target, _)| **target == HookTarget::Grabbable)
+        .map(|(entity, _, transform)| (entity, transform.translation().truncate()))
+        .filter(|(_, load_position)| load_position.distance(position) <= GRAB_RADIUS)
+        .min_by(|(_, a), (_, b)| a.distance(position).total_cmp(&b.distance(position)))
+    else {
+        return;
+    };
+
+    for _ in 0..2 {
+        if let Some(chain) = chain_state.chains.pop() {
+            chain.release(&mut commands, &mut pool);
+        }
+    }
+    commands.spawn((
+        Name::new("Pulley"),
+        Pulley {
+            wheels: [first, second],
+            load,
+            length: position.distance(first)
+                + first.distance(second)
+                + second.distance(load_position),
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+/// Reel the rope, keep the player within reach of the first anchor, and pull
+/// the load up to the second as the player's side takes up rope.
+fn hoist_with_pulley(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: ActionInput,
+    config: Res<ChainConfig>,
+    mut pulley_query: Query<(Entity, &mut Pulley)>,
+    mut player_query: Query<(&mut Transform, Has<RigidBody>), With<Player>>,
+    mut load_query: Query<(&GlobalTransform, &mut LinearVelocity), Without<Player>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if pulley_query.is_empty() {
+        return;
+    }
+    let Some((mut player_transform, player_is_body)) =
+        player_query
+            .single_mut()
+            .or_report(&mut errors, "hoist_with_pulley", "player")
+    else {
+        return;
+    };
+    for (entity, mut pulley) in &mut pulley_query {
+        let Ok((load_transform, mut velocity)) = load_query.get_mut(pulley.load) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let reel = input.axis(InputAction::ReelOut, InputAction::ReelIn);
+        let speed = if reel > 0.0 {
+            config.reel_out_speed
+        } else {
+            config.reel_in_speed
+        };
+        let span = pulley.wheels[0].distance(pulley.wheels[1]);
+        pulley.length = (pulley.length + reel * speed * LINK_SIZE * time.delta_secs()).max(span);
+
+        // Walking can't pull more rope than there is. A player swinging from
+        // a chain is left to the physics.
+        let reach = pulley.reach();
+        let offset = player_transform.translation.truncate() - pulley.wheels[0];
+        if !player_is_body && offset.length() > reach {
+            let clamped = pulley.wheels[0] + offset.clamp_length_max(reach);
+            player_transform.translation = clamped.extend(player_transform.translation.z);
+        }
+
+        let load_reach = (reach - offset.length().min(reach)).max(0.0);
+        let load_offset = load_transform.translation().truncate() - pulley.wheels[1];
+        let stretch = load_offset.length() - load_reach;
+        if stretch > 0.0 {
+            let outward = load_offset.normalize_or_zero();
+            let speed_out = velocity.dot(outward).max(0.0);
+            velocity.0 -= outward * (speed_out + stretch * ROPE_STIFFNESS);
+        }
+    }
+}
+
+fn draw_pulleys(
+    pulley_query: Query<&Pulley>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    load_query: Query<&GlobalTransform>,
+    mut gizmos: Gizmos,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if pulley_query.is_empty() {
+        return;
+    }
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "draw_pulleys", "player")
+    else {
+        return;
+    };
+    for pulley in &pulley_query {
+        let Ok(load) = load_query.get(pulley.load) else {
+            continue;
+        };
+        gizmos.linestrip_2d(
+            [
+                player.translation().truncate(),
+                pulley.wheels[0],
+                pulley.wheels[1],
+                load.translation().truncate(),
+            ],
+            ROPE_COLOR,
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Rappelling down a chain.
+//!
+//! When the newest chain hangs almost straight down from its far end, the
+//! rappel key holds it rigid with a prismatic joint, so the player end can't
+//! swing and only slides along the chain. Moving up and down then sets the
+//! length precisely instead of reeling whole links. Pressing the key again
+//! goes back to swinging freely.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{ChainState, LINK_SIZE},
+        cleanup::{GameplaySpawned, SpawnKind},
+    },
+    input::{ActionInput, InputAction},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (toggle_rappel, rappel_along_chain)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How far from straight down the chain can hang to start rappelling.
+const MAX_RAPPEL_ANGLE: f32 = 0.35;
+/// How fast the player end moves while rappelling, in pixels per second.
+const RAPPEL_SPEED: f32 = 80.0;
+
+fn toggle_rappel(
+    mut commands: Commands,
+    input: ActionInput,
+    mut chain_state: ResMut<ChainState>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    if !input.just_pressed(InputAction::Rappel) {
+        return;
+    }
+    let Some(chain) = chain_state.chains.last_mut() else {
+        return;
+    };
+    if let Some(joint) = chain.rappel.take() {
+        commands.entity(joint).despawn();
+        return;
+    }
+
+    let (Some(&bottom), Some(&top)) = (chain.links.first(), chain.links.last()) else {
+        return;
+    };
+    let Ok([bottom_transform, top_transform]) = transform_query.get_many([bottom, top]) else {
+        return;
+    };
+    let hanging =
+        top_transform.translation().truncate() - bottom_transform.translation().truncate();
+    if hanging == Vec2::ZERO || hanging.angle_to(Vec2::Y).abs() > MAX_RAPPEL_ANGLE {
+        return;
+    }
+
+    // The top link points along the chain, so its local Y axis is the one the
+    // bottom can slide along, on the negative side.
+    let length = hanging.length();
+    let joint = commands
+        .spawn((
+            Name::new("Rappel Joint"),
+            PrismaticJoint::new(top, bottom)
+                .with_free_axis(Vec2::Y)
+                .with_limits(-length, -length),
+            GameplaySpawned(SpawnKind::Joint),
+            StateScoped(Screen::Gameplay),
+        ))
+        .id();
+    chain.rappel = Some(joint);
+}
+
+/// Move the player end of a rappelling chain up and down.
+fn rappel_along_chain(
+    time: Res<Time>,
+    input: ActionInput,
+    chain_state: Res<ChainState>,
+    mut joint_query: Query<&mut PrismaticJoint>,
+) {
+    // Up shortens the chain.
+    let climb = input.axis(InputAction::MoveUp, InputAction::MoveDown);
+    if climb == 0.0 {
+        return;
+    }
+    let Some(chain) = chain_state.chains.last() else {
+        return;
+    };
+    let Some(mut joint) = chain
+        .rappel
+        .and_then(|joint| joint_query.get_mut(joint).ok())
+    else {
+        return;
+    };
+    let Some(limits) = joint.free_axis_limits else {
+        return;
+    };
+
+    // The chain can't get longer than its links.
+    let max_length = chain.links.len().saturating_sub(1) as f32 * LINK_SIZE;
+    let length = (-limits.max - climb * RAPPEL_SPEED * time.delta_secs())
+        .clamp(LINK_SIZE, max_length.max(LINK_SIZE));
+    joint.free_axis_limits = Some(DistanceLimit::new(-length, -length));
+}
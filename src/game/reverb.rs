@@ -0,0 +1,187 @@
+//! Reverb zones: areas of a level that change how sound effects ring out.
+//!
+//! Each zone uses a [`ReverbPreset`]. The reverb heard follows the zone the
+//! player is in, crossfading when they move between zones, and goes dry
+//! outside of them. Bevy's audio has no effect buses, so the reverb is built
+//! into the sounds that are generated in-game, such as chain scrapes.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        gameplay_error::{GameplayError, OrReport},
+        level_data::ReverbZoneData,
+        player::Player,
+        trigger_volume::{DetectTriggers, TriggerVolume, trigger_volume},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ReverbZone>();
+    app.init_resource::<ListenerReverb>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_listener_reverb);
+    app.add_systems(
+        Update,
+        fade_listener_reverb
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .after(DetectTriggers)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How quickly the reverb crossfades between zones, per second.
+const REVERB_FADE: f32 = 2.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum ReverbPreset {
+    /// Long, dark echoes.
+    #[default]
+    Cave,
+    /// A big, bright room.
+    Hall,
+    /// Hardly any reverb, just a faint slap back.
+    Outdoors,
+}
+
+impl ReverbPreset {
+    /// All presets, in the order the editor cycles through them.
+    pub const ALL: [Self; 3] = [Self::Cave, Self::Hall, Self::Outdoors];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Cave => "cave",
+            Self::Hall => "hall",
+            Self::Outdoors => "outdoors",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&preset| preset == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+
+    pub fn params(self) -> ReverbParams {
+        match self {
+            Self::Cave => ReverbParams {
+                delay: 0.09,
+                feedback: 0.6,
+                mix: 0.5,
+            },
+            Self::Hall => ReverbParams {
+                delay: 0.05,
+                feedback: 0.45,
+                mix: 0.35,
+            },
+            Self::Outdoors => ReverbParams {
+                delay: 0.12,
+                feedback: 0.1,
+                mix: 0.1,
+            },
+        }
+    }
+}
+
+/// Settings for the echo added to generated sounds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ReverbParams {
+    /// Seconds between echoes.
+    pub delay: f32,
+    /// How much of each echo comes back in the next one.
+    pub feedback: f32,
+    /// How loud the echoes are next to the dry sound.
+    pub mix: f32,
+}
+
+impl ReverbParams {
+    /// No reverb at all.
+    pub const DRY: Self = Self {
+        delay: 0.05,
+        feedback: 0.0,
+        mix: 0.0,
+    };
+
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            delay: self.delay.lerp(other.delay, t),
+            feedback: self.feedback.lerp(other.feedback, t),
+            mix: self.mix.lerp(other.mix, t),
+        }
+    }
+
+    /// How long the echoes take to die down, in seconds.
+    pub fn tail(self) -> f32 {
+        if self.mix <= 0.0 || self.feedback <= 0.0 {
+            return 0.0;
+        }
+        // Until the echoes are down to about 1% of the sound.
+        self.delay * (0.01f32.ln() / self.feedback.ln()).max(1.0)
+    }
+}
+
+/// A box in the level with its own reverb, centered on its [`Transform`].
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct ReverbZone {
+    pub preset: ReverbPreset,
+    pub size: Vec2,
+}
+
+pub fn reverb_zone(data: &ReverbZoneData) -> impl Bundle {
+    (
+        Name::new(format!("Reverb Zone ({})", data.preset.label())),
+        ReverbZone {
+            preset: data.preset,
+            size: data.size,
+        },
+        trigger_volume(Collider::rectangle(data.size.x, data.size.y)),
+        Transform::from_translation(data.position.extend(0.0)),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// The reverb the player hears right now.
+#[derive(Resource, Debug)]
+pub struct ListenerReverb(pub ReverbParams);
+
+impl Default for ListenerReverb {
+    fn default() -> Self {
+        Self(ReverbParams::DRY)
+    }
+}
+
+fn reset_listener_reverb(mut reverb: ResMut<ListenerReverb>) {
+    *reverb = ListenerReverb::default();
+}
+
+fn fade_listener_reverb(
+    time: Res<Time>,
+    player_query: Query<Entity, With<Player>>,
+    zone_query: Query<(&ReverbZone, &TriggerVolume)>,
+    mut reverb: ResMut<ListenerReverb>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(player) =
+        player_query
+            .single()
+            .or_report(&mut errors, "fade_listener_reverb", "player")
+    else {
+        return;
+    };
+    // Later zones win where zones overlap.
+    let target = zone_query
+        .iter()
+        .filter(|(_, volume)| volume.contains(player))
+        .last()
+        .map_or(ReverbParams::DRY, |(zone, _)| zone.preset.params());
+    if reverb.0 != target {
+        reverb.0 = reverb
+            .0
+            .lerp(target, (REVERB_FADE * time.delta_secs()).min(1.0));
+    }
+}
@@ -0,0 +1,68 @@
+//! Short codes for sharing runs.
+//!
+//! A run code packs a [`RunSeed`] and [`RunMutators`] into 16 characters of
+//! Crockford base32, like `0A3F-9KQZ-TT21-M8XC`, so a friend can type it in
+//! and play the same run. Codes end in a checksum that catches most typos.
+//! Decoding ignores case and dashes, and reads `O` as `0` and `I`/`L` as `1`.
+
+use crate::game::mutators::{RunMutators, RunSeed};
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CODE_LENGTH: usize = 16;
+const GROUP_LENGTH: usize = 4;
+
+/// The code for a run with `seed` and `mutators`.
+pub fn encode(seed: RunSeed, mutators: RunMutators) -> String {
+    let mut bytes = [0; 10];
+    bytes[0] = mutators.bits();
+    bytes[1..9].copy_from_slice(&seed.0.to_be_bytes());
+    bytes[9] = checksum(&bytes[..9]);
+    let bits = u128::from_be_bytes({
+        let mut padded = [0; 16];
+        padded[6..].copy_from_slice(&bytes);
+        padded
+    });
+
+    let mut code = String::new();
+    for i in 0..CODE_LENGTH {
+        if i > 0 && i % GROUP_LENGTH == 0 {
+            code.push('-');
+        }
+        let shift = 5 * (CODE_LENGTH - 1 - i);
+        code.push(ALPHABET[(bits >> shift) as usize & 31] as char);
+    }
+    code
+}
+
+/// The seed and mutators in `code`, or `None` if it isn't a valid code.
+pub fn decode(code: &str) -> Option<(RunSeed, RunMutators)> {
+    let mut bits = 0u128;
+    let mut length = 0;
+    for c in code.chars().filter(|&c| c != '-' && !c.is_whitespace()) {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        let value = ALPHABET.iter().position(|&a| a as char == c)?;
+        bits = bits << 5 | value as u128;
+        length += 1;
+    }
+    if length != CODE_LENGTH {
+        return None;
+    }
+
+    let padded = bits.to_be_bytes();
+    let bytes = &padded[6..];
+    if checksum(&bytes[..9]) != bytes[9] {
+        return None;
+    }
+    let seed = u64::from_be_bytes(bytes[1..9].try_into().ok()?);
+    Some((RunSeed(seed), RunMutators::from_bits(bytes[0])))
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0x5a, |sum: u8, &byte| sum.rotate_left(3) ^ byte)
+}
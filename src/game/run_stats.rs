@@ -0,0 +1,62 @@
+//! Statistics about the current run, for the run summary.
+//!
+//! Stats start over whenever gameplay starts, including restarts, unless the
+//! run is continued from a checkpoint. They only count while the game isn't
+//! paused.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppSystems, PausableSystems, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RunStats>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_run_stats);
+    app.add_systems(
+        Update,
+        track_run_time
+            .in_set(AppSystems::TickTimers)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How often the hook count is sampled for the summary graph, in seconds.
+pub const SAMPLE_INTERVAL: f32 = 5.0;
+
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct RunStats {
+    /// Seconds played this run.
+    pub time: f32,
+    pub hooks_fired: u32,
+    /// The longest chain fired, in pixels.
+    pub longest_chain: f32,
+    /// The number of hooks fired so far, every [`SAMPLE_INTERVAL`] seconds.
+    pub hook_samples: Vec<u32>,
+}
+
+impl RunStats {
+    pub fn record_hook(&mut self, length: f32) {
+        self.hooks_fired += 1;
+        self.longest_chain = self.longest_chain.max(length);
+    }
+}
+
+pub fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn track_run_time(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.time += time.delta_secs();
+    while stats.hook_samples.len() < (stats.time / SAMPLE_INTERVAL) as usize {
+        let hooks_fired = stats.hooks_fired;
+        stats.hook_samples.push(hooks_fired);
+    }
+}
+
+/// Format seconds as `m:ss.cc`.
+pub fn format_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.0) as u32;
+    format!("{minutes}:{:05.2}", seconds - minutes as f32 * 60.0)
+}
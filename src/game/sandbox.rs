@@ -0,0 +1,493 @@
+//! A playground for chains, reached from the title screen.
+//!
+//! The sandbox plays in an empty arena instead of the level, with a panel for
+//! spawning preset scenarios next to the player, turning gravity and wind up
+//! or down, and clearing everything away again. Chains fire as usual, but
+//! don't expire and aren't limited in number, so the 100-chain stress test
+//! stays up until it's cleared. Nothing done in the sandbox counts toward
+//! progress or is recorded as a replay.
+//!
+//! Dev builds can also restore an exported scene into the sandbox, in place
+//! of the arena.
+
+use std::f32::consts::PI;
+
+use avian2d::prelude::*;
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{
+    AppSystems, GRAVITY, PausableSystems,
+    game::{
+        chain::{ChainConfig, ChainPool, ChainState, JointCollisionDisabled},
+        hook_types::HookRegistry,
+        level::{anchor, obstacle, prop},
+        level_data::{LevelData, ObstacleData, PropData},
+        player::Player,
+    },
+    screens::Screen,
+    theme::{layout::HudAnchor, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Sandbox>();
+    app.register_type::<SandboxPiece>();
+    app.register_type::<SandboxLabel>();
+    app.init_resource::<Sandbox>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (
+            apply_sandbox_gravity,
+            spawn_sandbox_panel,
+            spawn_sandbox_scene,
+        )
+            .run_if(in_sandbox),
+    );
+    app.add_systems(
+        Update,
+        (
+            apply_sandbox_gravity.run_if(resource_changed::<Sandbox>),
+            update_sandbox_labels
+                .run_if(resource_changed::<Sandbox>.or(any_match_filter::<Added<SandboxLabel>>)),
+            blow_wind.in_set(PausableSystems),
+        )
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay).and(in_sandbox)),
+    );
+    app.add_systems(OnEnter(Screen::Title), leave_sandbox);
+}
+
+/// How far the arena reaches from its middle, left and right.
+const ARENA_HALF_WIDTH: f32 = 1600.0;
+const ARENA_HEIGHT: f32 = 1200.0;
+const WALL_THICKNESS: f32 = 100.0;
+/// The highest gravity goes, as a multiple of normal gravity.
+const MAX_GRAVITY_SCALE: f32 = 3.0;
+const GRAVITY_STEP: f32 = 0.25;
+/// The strongest wind, in pixels per second squared.
+const MAX_WIND: f32 = 1000.0;
+const WIND_STEP: f32 = 100.0;
+const STRESS_TEST_CHAINS: usize = 100;
+
+/// Whether the player is in the sandbox, and how it's set up.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Sandbox {
+    pub active: bool,
+    /// Gravity as a multiple of normal gravity.
+    pub gravity: f32,
+    /// Sideways acceleration of everything loose, in pixels per second
+    /// squared. Positive blows to the right.
+    pub wind: f32,
+    /// A scene to play in instead of the arena.
+    pub scene: Option<Handle<DynamicScene>>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            active: false,
+            gravity: 1.0,
+            wind: 0.0,
+            scene: None,
+        }
+    }
+}
+
+/// Run condition for things that only happen in, or never happen in, the
+/// sandbox.
+pub fn in_sandbox(sandbox: Res<Sandbox>) -> bool {
+    sandbox.active
+}
+
+/// The level the sandbox plays in: a closed box with anchors along the
+/// ceiling, and room to spawn presets in. A restored scene brings its own
+/// level, so only the spawn point is kept.
+pub fn sandbox_arena(sandbox: &Sandbox) -> LevelData {
+    let spawn = Some(Vec2::new(0.0, 120.0));
+    if sandbox.scene.is_some() {
+        return LevelData {
+            name: "Sandbox".to_string(),
+            spawn,
+            ..default()
+        };
+    }
+    let wall = |position: Vec2, size: Vec2| ObstacleData { position, size };
+    let width = ARENA_HALF_WIDTH * 2.0;
+    LevelData {
+        name: "Sandbox".to_string(),
+        spawn,
+        obstacles: vec![
+            wall(Vec2::ZERO, Vec2::new(width, WALL_THICKNESS)),
+            wall(
+                Vec2::new(0.0, ARENA_HEIGHT),
+                Vec2::new(width, WALL_THICKNESS),
+            ),
+            wall(
+                Vec2::new(-ARENA_HALF_WIDTH, ARENA_HEIGHT / 2.0),
+                Vec2::new(WALL_THICKNESS, ARENA_HEIGHT),
+            ),
+            wall(
+                Vec2::new(ARENA_HALF_WIDTH, ARENA_HEIGHT / 2.0),
+                Vec2::new(WALL_THICKNESS, ARENA_HEIGHT),
+            ),
+        ],
+        anchors: (-4..=4)
+            .map(|i| Vec2::new(i as f32 * 350.0, ARENA_HEIGHT - 250.0))
+            .collect(),
+        ..default()
+    }
+}
+
+/// Something spawned by a preset, despawned when the sandbox is cleared.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SandboxPiece;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Preset {
+    RopeBridge,
+    Pendulum,
+    Pulley,
+    StressTest,
+}
+
+impl Preset {
+    const ALL: [Self; 4] = [
+        Self::RopeBridge,
+        Self::Pendulum,
+        Self::Pulley,
+        Self::StressTest,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::RopeBridge => "Rope Bridge",
+            Self::Pendulum => "Pendulum",
+            Self::Pulley => "Pulley",
+            Self::StressTest => "Stress Test",
+        }
+    }
+
+    /// Spawn the preset around `origin`, a point on the floor.
+    fn spawn(self, world: &mut World, origin: Vec2) {
+        match self {
+            Self::RopeBridge => spawn_rope_bridge(world, origin),
+            Self::Pendulum => spawn_pendulum(world, origin),
+            Self::Pulley => spawn_pulley(world, origin),
+            Self::StressTest => fire_stress_test(world, origin),
+        }
+    }
+}
+
+/// Planks hanging between two posts, joined end to end.
+fn spawn_rope_bridge(world: &mut World, origin: Vec2) {
+    const SPAN: f32 = 600.0;
+    const POST_SIZE: Vec2 = Vec2::new(40.0, 250.0);
+    const PLANKS: usize = 12;
+
+    let post_top = origin.y + WALL_THICKNESS / 2.0 + POST_SIZE.y;
+    let mut post = |x: f32| {
+        world
+            .spawn((
+                Name::new("Bridge Post"),
+                obstacle(&ObstacleData {
+                    position: Vec2::new(x, post_top - POST_SIZE.y / 2.0),
+                    size: POST_SIZE,
+                }),
+                SandboxPiece,
+            ))
+            .id()
+    };
+    let left = post(origin.x - SPAN / 2.0);
+    let right = post(origin.x + SPAN / 2.0);
+
+    let gap = SPAN - POST_SIZE.x;
+    let plank_size = Vec2::new(gap / PLANKS as f32, 10.0);
+    let half_plank = Vec2::new(plank_size.x / 2.0, 0.0);
+    let post_edge = Vec2::new(POST_SIZE.x / 2.0, POST_SIZE.y / 2.0);
+    let mut previous = (left, post_edge);
+    for i in 0..PLANKS {
+        let x = origin.x - gap / 2.0 + plank_size.x * (i as f32 + 0.5);
+        let plank = world
+            .spawn((
+                Name::new(format!("Bridge Plank {i}")),
+                prop(&PropData {
+                    position: Vec2::new(x, post_top),
+                    size: plank_size,
+                    mass: 0.5,
+                }),
+                // Neighboring planks overlap at their joints
+                ActiveCollisionHooks::FILTER_PAIRS,
+                SandboxPiece,
+            ))
+            .id();
+        world.spawn(bridge_joint(previous, (plank, -half_plank)));
+        previous = (plank, half_plank);
+    }
+    world.spawn(bridge_joint(
+        previous,
+        (right, post_edge * Vec2::new(-1.0, 1.0)),
+    ));
+}
+
+fn bridge_joint(from: (Entity, Vec2), to: (Entity, Vec2)) -> impl Bundle {
+    (
+        Name::new("Bridge Joint"),
+        RevoluteJoint::new(from.0, to.0)
+            .with_local_anchor_1(from.1)
+            .with_local_anchor_2(to.1)
+            .with_compliance(0.00001),
+        JointCollisionDisabled,
+        SandboxPiece,
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// A heavy bob on a rope, let go from level with its pivot.
+fn spawn_pendulum(world: &mut World, origin: Vec2) {
+    const LENGTH: f32 = 300.0;
+
+    let pivot_position = origin + Vec2::new(0.0, 650.0);
+    let pivot = world
+        .spawn((
+            Name::new("Pendulum Pivot"),
+            anchor(pivot_position),
+            SandboxPiece,
+        ))
+        .id();
+    let bob = world
+        .spawn((
+            Name::new("Pendulum Bob"),
+            prop(&PropData {
+                position: pivot_position + Vec2::new(LENGTH, 0.0),
+                size: Vec2::splat(50.0),
+                mass: 5.0,
+            }),
+            SandboxPiece,
+        ))
+        .id();
+    world.spawn((
+        Name::new("Pendulum Rope"),
+        DistanceJoint::new(pivot, bob)
+            .with_rest_length(LENGTH)
+            .with_limits(LENGTH, LENGTH),
+        SandboxPiece,
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+/// Two anchors up high and a crate under each, ready to be hoisted with the
+/// pulley.
+fn spawn_pulley(world: &mut World, origin: Vec2) {
+    const SPREAD: f32 = 200.0;
+
+    for side in [-1.0, 1.0] {
+        let x = origin.x + side * SPREAD;
+        world.spawn((
+            Name::new("Pulley Anchor"),
+            anchor(Vec2::new(x, origin.y + 700.0)),
+            SandboxPiece,
+        ));
+        world.spawn((
+            Name::new("Pulley Crate"),
+            prop(&PropData {
+                position: Vec2::new(x, origin.y + WALL_THICKNESS / 2.0 + 40.0),
+                size: Vec2::splat(80.0),
+                mass: 3.0,
+            }),
+            SandboxPiece,
+        ));
+    }
+}
+
+/// Fire a fan of chains up at the ceiling all at once.
+fn fire_stress_test(world: &mut World, origin: Vec2) {
+    let config = world.resource::<ChainConfig>().clone();
+    let hook_type = world.resource::<HookRegistry>().active(&config).clone();
+    let from = origin + Vec2::new(0.0, 200.0);
+    world.resource_scope(|world, mut chain_state: Mut<ChainState>| {
+        world.resource_scope(|world, mut pool: Mut<ChainPool>| {
+            let mut commands = world.commands();
+            for i in 0..STRESS_TEST_CHAINS {
+                let angle = PI * (i as f32 + 0.5) / STRESS_TEST_CHAINS as f32;
+                let target = from + Vec2::from_angle(angle) * config.max_length();
+                let chain =
+                    pool.fire_chain(&mut commands, &config, &hook_type, from, target, Vec2::ZERO);
+                chain_state.chains.push(chain);
+            }
+        });
+    });
+    world.flush();
+}
+
+/// Release every chain, and despawn everything the presets spawned.
+fn clear_sandbox(world: &mut World) {
+    world.resource_scope(|world, mut chain_state: Mut<ChainState>| {
+        world.resource_scope(|world, mut pool: Mut<ChainPool>| {
+            let mut commands = world.commands();
+            while !chain_state.chains.is_empty() {
+                chain_state.release_oldest(&mut commands, &mut pool);
+            }
+        });
+    });
+    world.flush();
+
+    let pieces: Vec<Entity> = world
+        .query_filtered::<Entity, With<SandboxPiece>>()
+        .iter(world)
+        .collect();
+    for piece in pieces {
+        world.despawn(piece);
+    }
+}
+
+/// Where presets are spawned: on the floor under the player.
+fn preset_origin(world: &mut World) -> Option<Vec2> {
+    let position = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .ok()?
+        .translation
+        .truncate();
+    Some(Vec2::new(
+        position
+            .x
+            .clamp(-ARENA_HALF_WIDTH / 2.0, ARENA_HALF_WIDTH / 2.0),
+        0.0,
+    ))
+}
+
+fn apply_sandbox_gravity(sandbox: Res<Sandbox>, mut gravity: ResMut<Gravity>) {
+    gravity.0 = GRAVITY * sandbox.gravity;
+}
+
+fn blow_wind(
+    time: Res<Time>,
+    sandbox: Res<Sandbox>,
+    mut body_query: Query<(&RigidBody, &mut LinearVelocity)>,
+) {
+    if sandbox.wind == 0.0 {
+        return;
+    }
+    for (body, mut velocity) in &mut body_query {
+        if body.is_dynamic() {
+            velocity.x += sandbox.wind * time.delta_secs();
+        }
+    }
+}
+
+fn spawn_sandbox_scene(mut commands: Commands, sandbox: Res<Sandbox>) {
+    if let Some(scene) = &sandbox.scene {
+        commands.spawn((
+            Name::new("Sandbox Scene"),
+            DynamicSceneRoot(scene.clone()),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+fn leave_sandbox(mut sandbox: ResMut<Sandbox>) {
+    *sandbox = Sandbox::default();
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+enum SandboxSetting {
+    Gravity,
+    Wind,
+}
+
+impl SandboxSetting {
+    fn value(self, sandbox: &Sandbox) -> String {
+        match self {
+            Self::Gravity => format!("Gravity {:.2}x", sandbox.gravity),
+            Self::Wind => format!("Wind {:+.0}", sandbox.wind),
+        }
+    }
+
+    fn step(self, sandbox: &mut Sandbox, step: f32) {
+        match self {
+            Self::Gravity => {
+                sandbox.gravity =
+                    (sandbox.gravity + step * GRAVITY_STEP).clamp(0.0, MAX_GRAVITY_SCALE);
+            }
+            Self::Wind => {
+                sandbox.wind = (sandbox.wind + step * WIND_STEP).clamp(-MAX_WIND, MAX_WIND);
+            }
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SandboxLabel(SandboxSetting);
+
+fn spawn_sandbox_panel(mut commands: Commands) {
+    let mut panel = commands.spawn((
+        Name::new("Sandbox Panel"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::End,
+            row_gap: Px(6.0),
+            padding: UiRect::all(Px(10.0)),
+            ..default()
+        },
+        HudAnchor::BottomRight(10.0),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        StateScoped(Screen::Gameplay),
+    ));
+    for preset in Preset::ALL {
+        panel.with_child(widget::button(
+            preset.label(),
+            move |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                commands.queue(move |world: &mut World| {
+                    if let Some(origin) = preset_origin(world) {
+                        preset.spawn(world, origin);
+                    }
+                });
+            },
+        ));
+    }
+    panel.with_child(widget::button(
+        "Clear",
+        |_: Trigger<Pointer<Click>>, mut commands: Commands| commands.queue(clear_sandbox),
+    ));
+    for setting in [SandboxSetting::Gravity, SandboxSetting::Wind] {
+        panel.with_child(setting_widget(setting));
+    }
+}
+
+fn setting_widget(setting: SandboxSetting) -> impl Bundle {
+    (
+        Name::new(format!("{setting:?} Widget")),
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: Px(10.0),
+            ..default()
+        },
+        children![
+            widget::button_small(
+                "-",
+                move |_: Trigger<Pointer<Click>>, mut sandbox: ResMut<Sandbox>| {
+                    setting.step(&mut sandbox, -1.0);
+                },
+            ),
+            (widget::label(""), SandboxLabel(setting)),
+            widget::button_small(
+                "+",
+                move |_: Trigger<Pointer<Click>>, mut sandbox: ResMut<Sandbox>| {
+                    setting.step(&mut sandbox, 1.0);
+                },
+            ),
+        ],
+    )
+}
+
+fn update_sandbox_labels(
+    sandbox: Res<Sandbox>,
+    mut label_query: Query<(&SandboxLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut label_query {
+        text.0 = label.0.value(&sandbox);
+    }
+}
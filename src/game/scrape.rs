@@ -0,0 +1,377 @@
+//! Sparks and scraping sounds where chains slide along obstacles.
+//!
+//! Every frame, the contacts between chain links and static obstacles are
+//! checked for how fast the link slides along the surface. Faster slides throw
+//! more sparks and scrape louder, and leave skid marks. Hard hits chip the
+//! obstacle, and so does a hook biting into it. Chains breaking under stress
+//! burst into sparks. The scrape is generated noise rather than a sound file,
+//! so it can be pitched to the sliding speed. It is heard from the fastest
+//! contact, muffled when walls are between it and the player, and echoes with
+//! the reverb of the zone the player is in.
+//!
+//! The rest of the chain sounds and particles are in `chain_feedback`, and
+//! this runs along with them in [`ChainFeedbackSystems`].
+
+use std::{f32::consts::TAU, time::Duration};
+
+use avian2d::prelude::*;
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source, Volume},
+    prelude::*,
+};
+use rand::prelude::*;
+
+use crate::{
+    audio::SoundEffect,
+    game::{
+        chain::{ChainHooked, ChainLink},
+        chain_feedback::ChainFeedbackSystems,
+        chain_stress::ChainBroken,
+        cleanup::{GameplaySpawned, SpawnKind},
+        decal::{DecalKind, SpawnDecal},
+        game_rng::{GameRng, RngStream},
+        game_timer::{AddGameTimer, GameTimer},
+        occlusion::OccludedSound,
+        reverb::{ListenerReverb, ReverbParams},
+    },
+    persistence::Settings,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_audio_source::<ScrapeSound>();
+    app.register_type::<Spark>();
+    app.add_game_timer::<Spark>();
+
+    app.add_systems(
+        Update,
+        (
+            scrape_chain_contacts,
+            spark_on_hook,
+            spark_on_break,
+            update_sparks,
+        )
+            .in_set(ChainFeedbackSystems),
+    );
+}
+
+/// Sliding slower than this, in pixels per second, makes no sparks or sound.
+const MIN_SLIDE_SPEED: f32 = 60.0;
+/// Sliding this fast throws sparks and scrapes at full strength.
+const FULL_SLIDE_SPEED: f32 = 600.0;
+/// Sparks per second from a contact sliding at full speed.
+const SPARK_RATE: f32 = 40.0;
+/// Skid marks per second from a contact sliding at full speed.
+const SKID_RATE: f32 = 20.0;
+/// Contacts pushing apart harder than this chip the obstacle.
+const CHIP_IMPULSE: f32 = 400.0;
+/// Sparks thrown when a hook bites into an obstacle.
+const HOOK_SPARKS: usize = 8;
+const HOOK_SPARK_SPEED: f32 = 150.0;
+/// Sparks thrown when a chain snaps under stress.
+const BREAK_SPARKS: usize = 16;
+const BREAK_SPARK_SPEED: f32 = 250.0;
+const SPARK_LIFETIME: f32 = 0.35;
+/// How quickly sparks slow down.
+const SPARK_DRAG: f32 = 6.0;
+const SPARK_COLOR: Color = Color::srgb(1.0, 0.8, 0.3);
+/// How far scrapes are heard from off the surface, toward the link, so the
+/// surface itself doesn't muffle them.
+const SCRAPE_SOURCE_OFFSET: f32 = 4.0;
+/// Seconds between scrapes, so overlapping contacts don't stack up sounds.
+const SCRAPE_INTERVAL: f32 = 0.12;
+const SCRAPE_DURATION: f32 = 0.15;
+const SCRAPE_VOLUME: f32 = 0.4;
+
+/// A short burst of noise, for scraping metal, echoing with the reverb of
+/// wherever it was heard.
+#[derive(Asset, TypePath, Clone)]
+struct ScrapeSound {
+    duration: f32,
+    reverb: ReverbParams,
+    /// Seeds the noise.
+    seed: u64,
+}
+
+impl Decodable for ScrapeSound {
+    type DecoderItem = f32;
+    type Decoder = ScrapeDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let dry = (self.duration * SAMPLE_RATE as f32) as u32;
+        let tail = (self.reverb.tail() * SAMPLE_RATE as f32) as u32;
+        let delay = ((self.reverb.delay * SAMPLE_RATE as f32) as usize).max(1);
+        ScrapeDecoder {
+            position: 0,
+            dry,
+            total: dry + tail,
+            filtered: 0.0,
+            rng: SmallRng::seed_from_u64(self.seed),
+            echo: vec![0.0; delay],
+            cursor: 0,
+            reverb: self.reverb,
+        }
+    }
+}
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Low-passed white noise that fades out, through a feedback delay for the
+/// reverb.
+struct ScrapeDecoder {
+    position: u32,
+    /// How many samples of noise there are before only echoes are left.
+    dry: u32,
+    total: u32,
+    filtered: f32,
+    rng: SmallRng,
+    echo: Vec<f32>,
+    cursor: usize,
+    reverb: ReverbParams,
+}
+
+impl Iterator for ScrapeDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.total {
+            return None;
+        }
+        self.position += 1;
+        let dry = if self.position <= self.dry {
+            let noise: f32 = self.rng.random_range(-1.0..1.0);
+            self.filtered += (noise - self.filtered) * 0.3;
+            self.filtered * (self.dry - self.position) as f32 / self.dry as f32
+        } else {
+            0.0
+        };
+        let delayed = self.echo[self.cursor];
+        self.echo[self.cursor] = dry + delayed * self.reverb.feedback;
+        self.cursor = (self.cursor + 1) % self.echo.len();
+        Some(dry + delayed * self.reverb.mix)
+    }
+}
+
+impl Source for ScrapeDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.total as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
+
+/// A spark flying off a scraping chain.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Spark {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+impl GameTimer for Spark {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.lifetime
+    }
+}
+
+fn scrape_chain_contacts(
+    mut commands: Commands,
+    time: Res<Time>,
+    collisions: Collisions,
+    reverb: Res<ListenerReverb>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut sounds: ResMut<Assets<ScrapeSound>>,
+    mut decals: EventWriter<SpawnDecal>,
+    mut since_scrape: Local<f32>,
+    settings: Res<Settings>,
+    link_query: Query<&LinearVelocity, With<ChainLink>>,
+    body_query: Query<(&Position, &Rotation)>,
+) {
+    *since_scrape += time.delta_secs();
+    let rng = stream.get(&game_rng, "scrape");
+    let mut fastest = 0.0f32;
+    let mut loudest_point = Vec2::ZERO;
+
+    for pair in collisions.iter() {
+        let (velocity, flip) = match (
+            link_query.get(pair.collider1),
+            link_query.get(pair.collider2),
+        ) {
+            // Links scraping each other don't count.
+            (Ok(_), Ok(_)) | (Err(_), Err(_)) => continue,
+            (Ok(velocity), Err(_)) => (velocity.0, 1.0),
+            (Err(_), Ok(velocity)) => (velocity.0, -1.0),
+        };
+        // Contact points are kept relative to the first collider.
+        let Ok((position, rotation)) = body_query.get(pair.collider1) else {
+            continue;
+        };
+        for manifold in &pair.manifolds {
+            // The normal points from the first collider to the second.
+            let normal = manifold.normal * flip;
+            for point in &manifold.points {
+                if point.normal_impulse > CHIP_IMPULSE {
+                    decals.write(SpawnDecal {
+                        kind: DecalKind::Chip,
+                        position: point.global_point1(position, rotation),
+                        angle: rng.random_range(0.0..TAU),
+                    });
+                }
+            }
+
+            let slide = velocity - normal * velocity.dot(normal);
+            let speed = slide.length();
+            if speed < MIN_SLIDE_SPEED {
+                continue;
+            }
+            if speed > fastest {
+                fastest = speed;
+                loudest_point = manifold.points.first().map_or(loudest_point, |point| {
+                    point.global_point1(position, rotation) - normal * SCRAPE_SOURCE_OFFSET
+                });
+            }
+
+            let strength = (speed / FULL_SLIDE_SPEED).min(1.0);
+            for point in &manifold.points {
+                let contact = point.global_point1(position, rotation);
+                if rng.random_bool((SKID_RATE * strength * time.delta_secs()).min(1.0) as f64) {
+                    decals.write(SpawnDecal {
+                        kind: DecalKind::Skid,
+                        position: contact,
+                        angle: slide.to_angle(),
+                    });
+                }
+                let rate = SPARK_RATE * settings.particle_density;
+                if !rng.random_bool((rate * strength * time.delta_secs()).min(1.0) as f64) {
+                    continue;
+                }
+                // Sparks fly back off the surface, trailing the slide.
+                let direction =
+                    (-normal + -slide.normalize() * rng.random_range(0.0..1.5)).normalize_or_zero();
+                commands.spawn(spark(contact, direction * speed * 0.5));
+            }
+        }
+    }
+
+    if fastest > 0.0 && *since_scrape >= SCRAPE_INTERVAL {
+        *since_scrape = 0.0;
+        let strength = (fastest / FULL_SLIDE_SPEED).min(1.0);
+        commands.spawn((
+            Name::new("Chain Scrape"),
+            // A sound per scrape, for the reverb where it happens. It is
+            // dropped once it has played.
+            AudioPlayer(sounds.add(ScrapeSound {
+                duration: SCRAPE_DURATION,
+                reverb: reverb.0,
+                seed: rng.random(),
+            })),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::Linear(SCRAPE_VOLUME * strength))
+                .with_speed(0.8 + strength * 0.6),
+            SoundEffect,
+            OccludedSound::default(),
+            Transform::from_translation(loudest_point.extend(0.0)),
+            GameplaySpawned(SpawnKind::Sound),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+fn spark_on_hook(
+    mut commands: Commands,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut hooked: EventReader<ChainHooked>,
+    mut decals: EventWriter<SpawnDecal>,
+    settings: Res<Settings>,
+    link_query: Query<&LinearVelocity>,
+) {
+    let rng = stream.get(&game_rng, "hook sparks");
+    for event in hooked.read() {
+        decals.write(SpawnDecal {
+            kind: DecalKind::Chip,
+            position: event.point,
+            angle: rng.random_range(0.0..TAU),
+        });
+        // Sparks bounce back the way the hook came from.
+        let back = link_query
+            .get(event.link)
+            .map_or(Vec2::ZERO, |velocity| -velocity.0.normalize_or_zero());
+        for _ in 0..settings.particles(HOOK_SPARKS) {
+            let direction =
+                (back + Vec2::from_angle(rng.random_range(0.0..TAU))).normalize_or_zero();
+            commands.spawn(spark(
+                event.point,
+                direction * HOOK_SPARK_SPEED * rng.random_range(0.5..1.5),
+            ));
+        }
+    }
+}
+
+fn spark_on_break(
+    mut commands: Commands,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    mut broken: EventReader<ChainBroken>,
+    settings: Res<Settings>,
+) {
+    let rng = stream.get(&game_rng, "break sparks");
+    for event in broken.read() {
+        for _ in 0..settings.particles(BREAK_SPARKS) {
+            commands.spawn(spark(
+                event.point,
+                Vec2::from_angle(rng.random_range(0.0..TAU))
+                    * BREAK_SPARK_SPEED
+                    * rng.random_range(0.5..1.5),
+            ));
+        }
+    }
+}
+
+/// A spark flying off from `position`, fading as it slows down.
+pub fn spark(position: Vec2, velocity: Vec2) -> impl Bundle {
+    (
+        Name::new("Spark"),
+        Spark {
+            velocity,
+            lifetime: Timer::from_seconds(SPARK_LIFETIME, TimerMode::Once),
+        },
+        Sprite {
+            color: SPARK_COLOR,
+            custom_size: Some(Vec2::splat(3.0)),
+            ..default()
+        },
+        Transform::from_translation(position.extend(1.0)),
+        GameplaySpawned(SpawnKind::Particle),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn update_sparks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut spark_query: Query<(Entity, &mut Spark, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut spark, mut transform, mut sprite) in &mut spark_query {
+        if spark.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (spark.velocity * time.delta_secs()).extend(0.0);
+        spark.velocity *= (-SPARK_DRAG * time.delta_secs()).exp();
+        sprite.color = SPARK_COLOR.with_alpha(spark.lifetime.fraction_remaining());
+    }
+}
@@ -0,0 +1,108 @@
+//! Hidden rooms, covered up until someone finds them.
+//!
+//! A secret is an area of the level hidden behind a facade that looks like
+//! the walls around it. The facade is a [`TriggerVolume`], and as soon as the
+//! player or the head of a chain gets inside, it fades away to show what's
+//! behind it. Each secret counts once per run, in [`Score::secrets`], and is
+//! remembered in the level's progress when the level is completed.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::ChainLink,
+        level_data::SecretData,
+        objectives::Score,
+        player::Player,
+        trigger_volume::{DetectTriggers, TriggerEntered, trigger_volume},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Secret>();
+    app.add_event::<SecretFound>();
+
+    app.add_systems(
+        Update,
+        (find_secrets.after(DetectTriggers), fade_facades)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The color of a facade, the same as the obstacles it blends in with.
+const FACADE_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+/// In front of everything in the room, and the player going into it.
+const FACADE_Z: f32 = 5.0;
+/// How much of a facade fades away per second.
+const FACADE_FADE: f32 = 2.5;
+
+/// The `index`th secret of the level.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Secret {
+    pub index: usize,
+    pub found: bool,
+}
+
+/// Sent the first time a run finds a secret.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SecretFound {
+    pub index: usize,
+}
+
+pub fn secret(index: usize, data: &SecretData) -> impl Bundle {
+    (
+        Name::new(format!("Secret {index}")),
+        Secret {
+            index,
+            found: false,
+        },
+        trigger_volume(Collider::rectangle(data.size.x, data.size.y)),
+        Sprite::from_color(FACADE_COLOR, data.size),
+        Transform::from_translation(data.position.extend(FACADE_Z)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn find_secrets(
+    mut entered: EventReader<TriggerEntered>,
+    finder_query: Query<(), Or<(With<Player>, With<ChainLink>)>>,
+    mut secret_query: Query<&mut Secret>,
+    mut score: ResMut<Score>,
+    mut found: EventWriter<SecretFound>,
+) {
+    for event in entered.read() {
+        if !finder_query.contains(event.other) {
+            continue;
+        }
+        let Ok(mut secret) = secret_query.get_mut(event.volume) else {
+            continue;
+        };
+        if secret.found {
+            continue;
+        }
+        secret.found = true;
+        score.secrets.push(secret.index);
+        found.write(SecretFound {
+            index: secret.index,
+        });
+    }
+}
+
+fn fade_facades(time: Res<Time>, mut facade_query: Query<(&Secret, &mut Sprite)>) {
+    for (secret, mut sprite) in &mut facade_query {
+        let alpha = sprite.color.alpha();
+        if secret.found && alpha > 0.0 {
+            sprite
+                .color
+                .set_alpha((alpha - FACADE_FADE * time.delta_secs()).max(0.0));
+        }
+    }
+}
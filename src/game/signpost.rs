@@ -0,0 +1,164 @@
+//! Signposts: signs in the level with an icon and a short message.
+//!
+//! A sign can also know the way on from it, as a route of points. With the
+//! [`Assists::show_the_way`] assist on, arrows march along every route, from
+//! its sign through each of its points in turn.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems,
+    game::{assists::Assists, level_data::SignpostData},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Signpost>();
+    app.register_type::<SignIcon>();
+
+    app.add_systems(
+        Update,
+        draw_route_arrows
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay).and(showing_the_way)),
+    );
+}
+
+/// Behind the player, in front of the level.
+const SIGN_Z: f32 = -0.5;
+const BOARD_SIZE: Vec2 = Vec2::new(36.0, 28.0);
+const POST_SIZE: Vec2 = Vec2::new(5.0, 40.0);
+const POST_COLOR: Color = Color::srgb(0.45, 0.32, 0.2);
+const ICON_FONT_SIZE: f32 = 20.0;
+const TEXT_FONT_SIZE: f32 = 16.0;
+const TEXT_COLOR: Color = Color::srgb(0.95, 0.92, 0.85);
+const ARROW_COLOR: Color = Color::srgba(1.0, 0.85, 0.3, 0.8);
+/// How far apart route arrows are, in pixels.
+const ARROW_SPACING: f32 = 60.0;
+/// How fast route arrows move along, in pixels per second.
+const ARROW_SPEED: f32 = 40.0;
+/// How long each side of an arrow's head is, in pixels.
+const ARROW_SIZE: f32 = 10.0;
+
+/// What a sign shows above its message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum SignIcon {
+    #[default]
+    Info,
+    /// Something dangerous ahead.
+    Danger,
+    /// A good place to hook.
+    Hook,
+    /// The way to the exit.
+    Exit,
+}
+
+impl SignIcon {
+    /// All icons, in the order the editor cycles through them.
+    pub const ALL: [Self; 4] = [Self::Info, Self::Danger, Self::Hook, Self::Exit];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Danger => "danger",
+            Self::Hook => "hook",
+            Self::Exit => "exit",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&icon| icon == self);
+        Self::ALL[i.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+
+    fn glyph(self) -> &'static str {
+        match self {
+            Self::Info => "i",
+            Self::Danger => "!",
+            Self::Hook => "^",
+            Self::Exit => ">",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Info => Color::srgb(0.35, 0.6, 0.9),
+            Self::Danger => Color::srgb(0.9, 0.35, 0.25),
+            Self::Hook => Color::srgb(0.4, 0.8, 0.45),
+            Self::Exit => Color::srgb(1.0, 0.85, 0.2),
+        }
+    }
+}
+
+/// A sign, and the route on from it.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Signpost {
+    pub route: Vec<Vec2>,
+}
+
+/// The `index`th signpost of the level. Its board is at the sign's position,
+/// on top of a post.
+pub fn signpost(index: usize, data: &SignpostData) -> impl Bundle {
+    (
+        Name::new(format!("Signpost {index}")),
+        Signpost {
+            route: data.route.clone(),
+        },
+        Transform::from_translation(data.position.extend(SIGN_Z)),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+        children![
+            (
+                Sprite::from_color(POST_COLOR, POST_SIZE),
+                Transform::from_xyz(0.0, -POST_SIZE.y / 2.0, -0.01),
+            ),
+            Sprite::from_color(data.icon.color(), BOARD_SIZE),
+            (
+                Text2d::new(data.icon.glyph()),
+                TextFont::from_font_size(ICON_FONT_SIZE),
+                TextColor(Color::BLACK),
+                Transform::from_xyz(0.0, 0.0, 0.01),
+            ),
+            (
+                Text2d::new(data.text.clone()),
+                TextFont::from_font_size(TEXT_FONT_SIZE),
+                TextColor(TEXT_COLOR),
+                Transform::from_xyz(0.0, BOARD_SIZE.y, 0.01),
+            ),
+        ],
+    )
+}
+
+fn showing_the_way(assists: Res<Assists>) -> bool {
+    assists.show_the_way
+}
+
+/// Arrows every [`ARROW_SPACING`] along each route, moving along it.
+fn draw_route_arrows(
+    time: Res<Time>,
+    sign_query: Query<(&Signpost, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    let offset = (time.elapsed_secs() * ARROW_SPEED) % ARROW_SPACING;
+    for (sign, transform) in &sign_query {
+        let mut from = transform.translation().truncate();
+        // How far into the current leg the next arrow goes.
+        let mut along = offset;
+        for &to in &sign.route {
+            let leg = to - from;
+            let length = leg.length();
+            let direction = leg.normalize_or_zero();
+            while along < length {
+                let tip = from + direction * along;
+                let back = -direction * ARROW_SIZE;
+                gizmos.line_2d(tip, tip + Vec2::from_angle(0.6).rotate(back), ARROW_COLOR);
+                gizmos.line_2d(tip, tip + Vec2::from_angle(-0.6).rotate(back), ARROW_COLOR);
+                along += ARROW_SPACING;
+            }
+            along -= length;
+            from = to;
+        }
+    }
+}
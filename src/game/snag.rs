@@ -0,0 +1,121 @@
+//! Releasing chains that have snagged.
+//!
+//! A chain wrapped around or caught on something stops getting shorter when
+//! it is reeled in, which could leave the player stuck. If reeling in makes
+//! no progress for a while, the chain is released and a hint says why.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::chain::{ChainPool, ChainState},
+    input::{ActionInput, InputAction},
+    screens::Screen,
+    theme::layout::HudAnchor,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SnagHint>();
+
+    app.add_systems(
+        Update,
+        (
+            release_snagged_chain
+                .in_set(AppSystems::Update)
+                .in_set(PausableSystems),
+            fade_snag_hint.in_set(AppSystems::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How many frames of reeling to measure progress over.
+const SNAG_FRAMES: u32 = 45;
+/// Reeling in must bring the ends of the chain at least this close, in
+/// pixels, every [`SNAG_FRAMES`] frames.
+const SNAG_EPSILON: f32 = 4.0;
+const HINT_DURATION: f32 = 2.5;
+
+/// Progress of reeling in the newest chain.
+#[derive(Default)]
+struct ReelProgress {
+    frames: u32,
+    start_length: f32,
+}
+
+fn release_snagged_chain(
+    mut commands: Commands,
+    input: ActionInput,
+    mut progress: Local<ReelProgress>,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    let reeling_in = chain_state.chains.last().is_some_and(|chain| {
+        if chain.rappel.is_some() {
+            input.pressed(InputAction::MoveUp)
+        } else {
+            // A single link can't be reeled in any further.
+            input.pressed(InputAction::ReelIn) && chain.links.len() > 1
+        }
+    });
+    let ends = chain_state.chains.last().and_then(|chain| {
+        transform_query
+            .get_many([*chain.links.first()?, *chain.links.last()?])
+            .ok()
+    });
+    let (true, Some([bottom, top])) = (reeling_in, ends) else {
+        *progress = ReelProgress::default();
+        return;
+    };
+
+    let length = bottom.translation().distance(top.translation());
+    if progress.frames == 0 {
+        progress.start_length = length;
+    }
+    progress.frames += 1;
+    if progress.frames < SNAG_FRAMES {
+        return;
+    }
+    let snagged = progress.start_length - length < SNAG_EPSILON;
+    *progress = ReelProgress::default();
+    if !snagged {
+        return;
+    }
+
+    if let Some(chain) = chain_state.chains.pop() {
+        chain.release(&mut commands, &mut pool);
+    }
+    commands.spawn(snag_hint());
+}
+
+/// Tells the player why their chain let go.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SnagHint(Timer);
+
+fn snag_hint() -> impl Bundle {
+    (
+        Name::new("Snag Hint"),
+        SnagHint(Timer::from_seconds(HINT_DURATION, TimerMode::Once)),
+        Text::new("Chain snagged - released"),
+        TextFont::from_font_size(20.0),
+        TextLayout::new_with_justify(JustifyText::Center),
+        HudAnchor::Top(10.0),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+fn fade_snag_hint(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hint_query: Query<(Entity, &mut SnagHint, &mut TextColor)>,
+) {
+    for (entity, mut hint, mut color) in &mut hint_query {
+        hint.0.tick(time.delta());
+        color.0.set_alpha(hint.0.fraction_remaining());
+        if hint.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Short musical stingers layered over the soundtrack on key events.
+//!
+//! Send a [`PlayStinger`] event to play one. Stingers wait for the next beat
+//! of the music on the [`MusicClock`], and their notes are spaced on its beat
+//! too. Without a beat to follow they play right away. Like the chain scrape,
+//! stingers are generated tones rather than sound files, so they fit any
+//! tempo.
+//!
+//! Stingers play when the first hook of a level lands, at checkpoints, and
+//! when a run ends. Levels have no bosses yet.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source, Volume},
+    prelude::*,
+};
+
+use crate::{
+    audio::SoundEffect,
+    game::{beat::MusicClock, chain::ChainHooked},
+    menus::Menu,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_audio_source::<StingerSound>();
+    app.add_event::<PlayStinger>();
+    app.init_resource::<PendingStingers>();
+    app.init_resource::<FirstHookHeard>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_first_hook);
+    app.add_systems(OnEnter(Menu::RunSummary), play_run_complete_stinger);
+    app.add_systems(OnEnter(Screen::Results), play_run_complete_stinger);
+    app.add_systems(
+        Update,
+        (
+            play_first_hook_stinger.run_if(in_state(Screen::Gameplay)),
+            schedule_stingers,
+            play_pending_stingers,
+        )
+            .chain(),
+    );
+}
+
+/// How long each note lasts when there is no tempo to follow, in seconds.
+const DEFAULT_NOTE_LENGTH: f32 = 0.15;
+const STINGER_VOLUME: f32 = 0.3;
+/// How quickly each note dies down.
+const NOTE_DECAY: f32 = 8.0;
+const SAMPLE_RATE: u32 = 44_100;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Stinger {
+    /// The first hook of a level bit into something.
+    FirstHook,
+    /// The player reached a checkpoint.
+    Checkpoint,
+    /// The run is over.
+    RunComplete,
+}
+
+impl Stinger {
+    /// The notes to play, in Hz.
+    fn notes(self) -> &'static [f32] {
+        match self {
+            // C5, G5
+            Self::FirstHook => &[523.25, 783.99],
+            // E5, G5, E6
+            Self::Checkpoint => &[659.25, 783.99, 1318.51],
+            // C5, E5, G5, C6
+            Self::RunComplete => &[523.25, 659.25, 783.99, 1046.5],
+        }
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct PlayStinger(pub Stinger);
+
+/// Stingers waiting for their beat.
+#[derive(Resource, Default)]
+struct PendingStingers(Vec<PendingStinger>);
+
+struct PendingStinger {
+    stinger: Stinger,
+    /// Real seconds until the stinger starts.
+    delay: f32,
+    note_length: f32,
+}
+
+#[derive(Resource, Default)]
+struct FirstHookHeard(bool);
+
+fn reset_first_hook(mut heard: ResMut<FirstHookHeard>) {
+    heard.0 = false;
+}
+
+fn play_first_hook_stinger(
+    mut hooked_events: EventReader<ChainHooked>,
+    mut heard: ResMut<FirstHookHeard>,
+    mut stingers: EventWriter<PlayStinger>,
+) {
+    if hooked_events.read().last().is_some() && !heard.0 {
+        heard.0 = true;
+        stingers.write(PlayStinger(Stinger::FirstHook));
+    }
+}
+
+fn play_run_complete_stinger(mut stingers: EventWriter<PlayStinger>) {
+    stingers.write(PlayStinger(Stinger::RunComplete));
+}
+
+fn schedule_stingers(
+    mut stinger_events: EventReader<PlayStinger>,
+    clock: Res<MusicClock>,
+    mut pending: ResMut<PendingStingers>,
+) {
+    for PlayStinger(stinger) in stinger_events.read() {
+        let (delay, note_length) = match (clock.until_next_beat(), clock.tempo) {
+            // Eighth notes, starting on the next beat.
+            (Some(delay), Some(tempo)) => (delay, tempo.beat_length() / 2.0),
+            _ => (0.0, DEFAULT_NOTE_LENGTH),
+        };
+        pending.0.push(PendingStinger {
+            stinger: *stinger,
+            delay,
+            note_length,
+        });
+    }
+}
+
+/// Play stingers once their beat comes up. Real time is used, since the
+/// music keeps playing while the game is paused.
+fn play_pending_stingers(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut pending: ResMut<PendingStingers>,
+    mut sounds: ResMut<Assets<StingerSound>>,
+) {
+    if pending.0.is_empty() {
+        return;
+    }
+    for stinger in &mut pending.0 {
+        stinger.delay -= time.delta_secs();
+    }
+    let (ready, waiting) = pending
+        .0
+        .drain(..)
+        .partition::<Vec<_>, _>(|stinger| stinger.delay <= 0.0);
+    pending.0 = waiting;
+    for stinger in ready {
+        commands.spawn((
+            Name::new("Stinger"),
+            AudioPlayer(sounds.add(StingerSound {
+                notes: stinger.stinger.notes(),
+                note_length: stinger.note_length,
+            })),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(STINGER_VOLUME)),
+            SoundEffect,
+        ));
+    }
+}
+
+/// A run of plucked notes.
+#[derive(Asset, TypePath, Clone)]
+struct StingerSound {
+    notes: &'static [f32],
+    note_length: f32,
+}
+
+impl Decodable for StingerSound {
+    type DecoderItem = f32;
+    type Decoder = StingerDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        StingerDecoder {
+            notes: self.notes,
+            note_samples: ((self.note_length * SAMPLE_RATE as f32) as u32).max(1),
+            position: 0,
+        }
+    }
+}
+
+struct StingerDecoder {
+    notes: &'static [f32],
+    note_samples: u32,
+    position: u32,
+}
+
+impl StingerDecoder {
+    /// The length in samples. The last note rings out for twice as long.
+    fn total(&self) -> u32 {
+        (self.notes.len() as u32 + 1) * self.note_samples
+    }
+}
+
+impl Iterator for StingerDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.notes.is_empty() || self.position >= self.total() {
+            return None;
+        }
+        // The last note rings on until the end.
+        let note = ((self.position / self.note_samples) as usize).min(self.notes.len() - 1);
+        let since_note =
+            (self.position - note as u32 * self.note_samples) as f32 / SAMPLE_RATE as f32;
+        self.position += 1;
+        let envelope = (-since_note * NOTE_DECAY).exp();
+        Some((since_note * self.notes[note] * TAU).sin() * envelope)
+    }
+}
+
+impl Source for StingerDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.total() as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
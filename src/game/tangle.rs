@@ -0,0 +1,200 @@
+//! Getting tangled up in your own chains.
+//!
+//! While the player hangs from a chain, chain links bump into them, except for
+//! the first few links of each chain, which are always right next to the
+//! player. A chain fired carelessly can then wrap around the player, and once
+//! enough links press on them at once they're tangled: their swing is damped
+//! until they shake themselves free by pumping left and right, or the tangle
+//! works itself loose. Getting free lets the links slip off for a moment, so
+//! the player doesn't get caught again right away.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{ChainLink, Layer},
+        game_timer::{AddGameTimer, GameTimer},
+        movement::MovementController,
+        player::Player,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Tangled>();
+    app.register_type::<SlippingFree>();
+    app.add_game_timer::<Tangled>();
+    app.add_game_timer::<SlippingFree>();
+
+    app.add_systems(
+        Update,
+        (
+            keep_root_links_clear,
+            detect_tangle,
+            shake_free,
+            end_slipping_free,
+            untangle_when_untethered,
+        )
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How many links at the player end of each chain never touch the player.
+const CLEAR_LINKS: usize = 3;
+/// How many links have to press on the player at once to tangle them.
+const TANGLE_LINKS: usize = 3;
+/// How long a tangle lasts if the player doesn't shake it off, in seconds.
+const TANGLE_TIME: f32 = 1.5;
+/// Changes of direction it takes to shake free.
+const SHAKES_TO_FREE: u32 = 4;
+/// How much a tangle damps the player's swing.
+const TANGLED_DAMPING: f32 = 4.0;
+/// How long links slip past the player after a tangle, in seconds.
+const SLIP_TIME: f32 = 0.5;
+
+/// The player wrapped up in chain links.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Tangled {
+    timer: Timer,
+    shakes: u32,
+    /// The side the player last pushed toward, -1 or 1, or 0 before they
+    /// have.
+    last_push: f32,
+}
+
+impl GameTimer for Tangled {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+/// The player just got free of a tangle, and links pass through them.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SlippingFree {
+    timer: Timer,
+}
+
+impl GameTimer for SlippingFree {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+/// Links collide with the player only once they're far enough along their
+/// chain. Feeding out and reeling in renumber the links, so this is kept up
+/// as they do.
+fn keep_root_links_clear(mut link_query: Query<(&ChainLink, &mut CollisionLayers)>) {
+    for (link, mut layers) in &mut link_query {
+        let touches_player = link.link_index >= CLEAR_LINKS;
+        if layers.filters.has_all(Layer::Player) != touches_player {
+            if touches_player {
+                layers.filters.add(Layer::Player);
+            } else {
+                layers.filters.remove(Layer::Player);
+            }
+        }
+    }
+}
+
+fn detect_tangle(
+    mut commands: Commands,
+    collisions: Collisions,
+    player_query: Query<
+        Entity,
+        (
+            With<Player>,
+            With<RigidBody>,
+            Without<Tangled>,
+            Without<SlippingFree>,
+        ),
+    >,
+    link_query: Query<(), With<ChainLink>>,
+) {
+    let Ok(player) = player_query.single() else {
+        return;
+    };
+    let touching = collisions
+        .collisions_with(player)
+        .filter(|pair| {
+            let other = if pair.collider1 == player {
+                pair.collider2
+            } else {
+                pair.collider1
+            };
+            link_query.contains(other)
+        })
+        .count();
+    if touching >= TANGLE_LINKS {
+        commands.entity(player).insert((
+            Tangled {
+                timer: Timer::from_seconds(TANGLE_TIME, TimerMode::Once),
+                shakes: 0,
+                last_push: 0.0,
+            },
+            LinearDamping(TANGLED_DAMPING),
+        ));
+    }
+}
+
+/// Count each change between pushing left and right, and let the player go
+/// once they've shaken enough or the tangle has run its course.
+fn shake_free(
+    mut commands: Commands,
+    mut player_query: Query<(
+        Entity,
+        &MovementController,
+        &mut Tangled,
+        &mut CollisionLayers,
+    )>,
+) {
+    for (player, controller, mut tangled, mut layers) in &mut player_query {
+        let push = controller.intent.x;
+        if push != 0.0 && push.signum() != tangled.last_push {
+            if tangled.last_push != 0.0 {
+                tangled.shakes += 1;
+            }
+            tangled.last_push = push.signum();
+        }
+        if tangled.shakes < SHAKES_TO_FREE && !tangled.timer.finished() {
+            continue;
+        }
+        layers.filters.remove(Layer::ChainLink);
+        commands
+            .entity(player)
+            .remove::<(Tangled, LinearDamping)>()
+            .insert(SlippingFree {
+                timer: Timer::from_seconds(SLIP_TIME, TimerMode::Once),
+            });
+    }
+}
+
+fn end_slipping_free(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &SlippingFree, &mut CollisionLayers)>,
+) {
+    for (player, slipping, mut layers) in &mut player_query {
+        if slipping.timer.finished() {
+            layers.filters.add(Layer::ChainLink);
+            commands.entity(player).remove::<SlippingFree>();
+        }
+    }
+}
+
+/// Letting go of the chain lets go of the tangle too.
+fn untangle_when_untethered(
+    mut commands: Commands,
+    player_query: Query<Entity, (Or<(With<Tangled>, With<SlippingFree>)>, Without<RigidBody>)>,
+) {
+    for player in &player_query {
+        commands
+            .entity(player)
+            .remove::<(Tangled, SlippingFree, LinearDamping)>();
+    }
+}
@@ -0,0 +1,158 @@
+//! Taunts the player can show off with.
+//!
+//! Each [`Taunt`] has its own key and plays as a [`TauntLayer`] over
+//! whatever the player's base animation is doing, whether they're standing
+//! still, walking or swinging. The duck is a single sprite, so the layer
+//! can't keep to the upper body. It rocks, squashes or bobs the whole sprite
+//! instead, and leaves the base animation's frames alone. Taunts other than
+//! the first are unlocked by achievements, like cosmetics.
+//!
+//! Playing one sends a [`PlayTaunt`], which online sessions pass on to the
+//! other players.
+
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        cosmetics::Cosmetic,
+        game_timer::{AddGameTimer, GameTimer},
+        gameplay_error::{GameplayError, OrReport},
+        player::Player,
+    },
+    input::{ActionInput, InputAction},
+    persistence::Progress,
+    platform::{FIRST_HOOK, FULL_LENGTH},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TauntLayer>();
+    app.add_game_timer::<TauntLayer>();
+    app.add_event::<PlayTaunt>();
+
+    app.add_systems(
+        Update,
+        (start_taunt, play_taunts)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long a taunt lasts, in seconds.
+const TAUNT_TIME: f32 = 1.2;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+pub enum Taunt {
+    Wave,
+    Flex,
+    Hop,
+}
+
+impl Taunt {
+    /// The action that plays it.
+    pub fn action(self) -> InputAction {
+        match self {
+            Self::Wave => InputAction::Taunt1,
+            Self::Flex => InputAction::Taunt2,
+            Self::Hop => InputAction::Taunt3,
+        }
+    }
+
+    /// How far into the taunt `t` (from 0 to 1) moves the sprite, as an offset
+    /// of its anchor, and how much it squashes it.
+    fn pose(self, t: f32) -> (Vec2, f32) {
+        match self {
+            Self::Wave => (Vec2::new(0.08 * (2.0 * TAU * t).sin(), 0.0), 0.0),
+            Self::Flex => (Vec2::ZERO, 0.15 * (2.0 * TAU * t).sin().abs()),
+            // Anchoring below the middle draws the sprite higher.
+            Self::Hop => (Vec2::new(0.0, -0.3 * (TAU * t).sin().abs()), 0.0),
+        }
+    }
+}
+
+impl Cosmetic for Taunt {
+    const ALL: &'static [Self] = &[Self::Wave, Self::Flex, Self::Hop];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Wave => "Wave",
+            Self::Flex => "Flex",
+            Self::Hop => "Hop",
+        }
+    }
+
+    fn achievement(self) -> Option<&'static str> {
+        match self {
+            Self::Wave => None,
+            Self::Flex => Some(FIRST_HOOK),
+            Self::Hop => Some(FULL_LENGTH),
+        }
+    }
+}
+
+/// Sent when the local player taunts.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayTaunt(pub Taunt);
+
+/// A taunt playing on top of the player's base animation.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct TauntLayer {
+    pub taunt: Taunt,
+    pub timer: Timer,
+}
+
+impl TauntLayer {
+    /// Where the sprite is anchored, and its size as a multiple of a frame's.
+    pub fn pose(&self) -> (Anchor, Vec2) {
+        let (offset, squash) = self.taunt.pose(self.timer.fraction());
+        (
+            Anchor::Custom(offset),
+            Vec2::new(1.0 + squash, 1.0 - squash),
+        )
+    }
+}
+
+impl GameTimer for TauntLayer {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+fn start_taunt(input: ActionInput, progress: Res<Progress>, mut taunts: EventWriter<PlayTaunt>) {
+    let pressed = Taunt::ALL
+        .iter()
+        .copied()
+        .find(|taunt| input.just_pressed(taunt.action()));
+    if let Some(taunt) = pressed.filter(|taunt| taunt.is_unlocked(&progress)) {
+        taunts.write(PlayTaunt(taunt));
+    }
+}
+
+/// Start taunts over from the beginning, even if one is already playing.
+fn play_taunts(
+    mut commands: Commands,
+    mut taunts: EventReader<PlayTaunt>,
+    player_query: Query<Entity, With<Player>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(PlayTaunt(taunt)) = taunts.read().last() else {
+        return;
+    };
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "play_taunts", "player")
+    else {
+        return;
+    };
+    commands.entity(player).insert(TauntLayer {
+        taunt: *taunt,
+        timer: Timer::from_seconds(TAUNT_TIME, TimerMode::Once),
+    });
+}
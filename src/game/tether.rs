@@ -0,0 +1,218 @@
+//! Holding on to chains: by their root while they fly, and hanging from them
+//! once they hook.
+//!
+//! Until a chain hooks, its root is held to the player as set by
+//! [`ChainConfig::root_attachment`], either pulled along by a damped spring
+//! or kept right at the player, so a fired chain trails from the shooter
+//! rather than flying off whole.
+//!
+//! Once the newest chain has hooked onto something, the player hangs from its
+//! root link and swings under gravity, pumping the swing with left and right.
+//! Any slack between the player and the root is taken up at the reel-in
+//! speed, and reeling works as usual: the player follows the root as links
+//! are taken in or fed out. When the chain is released or snaps, the player
+//! lets go and moves freely again.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{ChainConfig, ChainState, LINK_SIZE, Layer, RootAttachment},
+        gameplay_error::{GameplayError, OrReport},
+        movement::MovementController,
+        player::Player,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Tethered>();
+    app.add_observer(let_go_when_swapped);
+
+    app.add_systems(
+        Update,
+        (hold_chain_roots, tether_player, swing_player)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The player's collider, before the player's scale is applied.
+const PLAYER_RADIUS: f32 = 10.0;
+const PLAYER_MASS: f32 = 4.0;
+/// Sideways acceleration from left and right while swinging, in pixels per
+/// second squared.
+const SWING_ACCELERATION: f32 = 600.0;
+
+/// The player hanging from `root`, the player end of a hooked chain.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Tethered {
+    root: Entity,
+    joint: Entity,
+}
+
+/// A character swapped out lets go of the chain they hung from. Their body is
+/// left to whoever swapped them.
+fn let_go_when_swapped(
+    trigger: Trigger<OnRemove, Player>,
+    mut commands: Commands,
+    tethered_query: Query<&Tethered>,
+) {
+    let Ok(tethered) = tethered_query.get(trigger.target()) else {
+        return;
+    };
+    commands.entity(tethered.joint).try_despawn();
+    commands.entity(trigger.target()).try_remove::<Tethered>();
+}
+
+/// Keep the roots of chains that haven't hooked yet with the player.
+fn hold_chain_roots(
+    time: Res<Time>,
+    config: Res<ChainConfig>,
+    chain_state: Res<ChainState>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut root_query: Query<(&mut Position, &mut LinearVelocity)>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    if config.root_attachment == RootAttachment::None {
+        return;
+    }
+    let Some(player) = player_query
+        .single()
+        .or_report(&mut errors, "hold_chain_roots", "player")
+    else {
+        return;
+    };
+    let target = player.translation().truncate();
+    let dt = time.delta_secs();
+    for chain in chain_state
+        .chains
+        .iter()
+        .filter(|chain| chain.hook.is_none())
+    {
+        let Some(&root) = chain.links.first() else {
+            continue;
+        };
+        let Ok((mut position, mut velocity)) = root_query.get_mut(root) else {
+            continue;
+        };
+        match config.root_attachment {
+            RootAttachment::None => {}
+            RootAttachment::Spring => {
+                let pull = config.root_stiffness * (target - position.0)
+                    - config.root_damping * velocity.0;
+                velocity.0 += pull * dt;
+            }
+            RootAttachment::Rigid => {
+                position.0 = target;
+                velocity.0 = Vec2::ZERO;
+            }
+        }
+    }
+}
+
+/// Hang the player from the newest hooked chain, follow its root as it is
+/// reeled, and let go once it's gone.
+fn tether_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<ChainConfig>,
+    chain_state: Res<ChainState>,
+    player_query: Query<(Entity, &GlobalTransform, Option<&Tethered>), With<Player>>,
+    transform_query: Query<&GlobalTransform>,
+    mut joint_query: Query<&mut DistanceJoint>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some((player, player_transform, tethered)) =
+        player_query
+            .single()
+            .or_report(&mut errors, "tether_player", "player")
+    else {
+        return;
+    };
+    let root = chain_state
+        .chains
+        .last()
+        .filter(|chain| chain.hook.is_some())
+        .and_then(|chain| chain.links.first().copied());
+
+    match (root, tethered) {
+        (Some(root), Some(tethered)) if tethered.root == root => {
+            // Take up the slack, down to the root's bottom end.
+            if let Ok(mut joint) = joint_query.get_mut(tethered.joint) {
+                let Some(limits) = joint.length_limits else {
+                    return;
+                };
+                let slack =
+                    (limits.max - config.reel_in_speed * LINK_SIZE * time.delta_secs()).max(0.0);
+                joint.length_limits = Some(DistanceLimit::new(0.0, slack));
+            }
+        }
+        (Some(root), tethered) => {
+            let Ok(root_transform) = transform_query.get(root) else {
+                return;
+            };
+            // Reeling changes the root, so the player keeps however much
+            // slack they had to the old one.
+            let distance = player_transform
+                .translation()
+                .truncate()
+                .distance(root_transform.translation().truncate());
+            if let Some(tethered) = tethered {
+                commands.entity(tethered.joint).despawn();
+            }
+            let joint = commands
+                .spawn((
+                    Name::new("Tether Joint"),
+                    DistanceJoint::new(player, root)
+                        .with_local_anchor_2(Vec2::new(0.0, -LINK_SIZE * 0.5))
+                        .with_limits(0.0, distance),
+                    StateScoped(Screen::Gameplay),
+                ))
+                .id();
+            commands.entity(player).insert((
+                Tethered { root, joint },
+                RigidBody::Dynamic,
+                Collider::circle(PLAYER_RADIUS),
+                Mass(PLAYER_MASS),
+                LockedAxes::ROTATION_LOCKED,
+                CollisionLayers::new(
+                    [Layer::Player],
+                    [
+                        Layer::StaticObstacle,
+                        Layer::Hazard,
+                        Layer::DynamicProp,
+                        Layer::ChainLink,
+                    ],
+                ),
+            ));
+        }
+        (None, Some(tethered)) => {
+            commands.entity(tethered.joint).despawn();
+            commands.entity(player).remove::<(
+                Tethered,
+                RigidBody,
+                Collider,
+                Mass,
+                LockedAxes,
+                CollisionLayers,
+            )>();
+        }
+        (None, None) => {}
+    }
+}
+
+/// Pump the swing with left and right.
+fn swing_player(
+    time: Res<Time>,
+    mut player_query: Query<(&MovementController, &mut LinearVelocity), With<Tethered>>,
+) {
+    for (controller, mut velocity) in &mut player_query {
+        velocity.x += controller.intent.x * SWING_ACCELERATION * time.delta_secs();
+    }
+}
@@ -0,0 +1,95 @@
+//! Slowing the game down for aiming.
+//!
+//! Holding [`InputAction::SlowMotion`] while there's something to aim at eases
+//! the [`GameSpeed`] down to [`GameSpeed::slow_motion`], and letting go eases
+//! it back up. The speed is applied to [`Time<Virtual>`], which everything in
+//! gameplay runs on: physics steps on the fixed timestep that follows it, and
+//! chain lifetimes and other [`GameTimer`]s tick by it. The easing itself goes
+//! by real time, so it takes just as long slowing down as speeding up.
+//!
+//! [`GameTimer`]: crate::game::game_timer::GameTimer
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::aim::PlayerAim,
+    input::{ActionInput, InputAction},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameSpeed>();
+    app.init_resource::<GameSpeed>();
+
+    app.add_systems(
+        Update,
+        (hold_slow_motion, ease_game_speed)
+            .chain()
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), reset_game_speed);
+}
+
+/// How fast the game runs, as a factor of normal speed.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct GameSpeed {
+    /// The speed being eased toward.
+    pub target: f32,
+    /// The speed the game runs at right now.
+    pub current: f32,
+    /// The speed while slow motion is held.
+    pub slow_motion: f32,
+    /// How quickly the speed eases toward the target, per real second.
+    pub easing: f32,
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self {
+            target: 1.0,
+            current: 1.0,
+            slow_motion: 0.3,
+            easing: 8.0,
+        }
+    }
+}
+
+fn hold_slow_motion(input: ActionInput, aim: Res<PlayerAim>, mut speed: ResMut<GameSpeed>) {
+    let slowed = input.pressed(InputAction::SlowMotion) && aim.target.is_some();
+    let target = if slowed { speed.slow_motion } else { 1.0 };
+    if speed.target != target {
+        speed.target = target;
+    }
+}
+
+fn ease_game_speed(
+    real_time: Res<Time<Real>>,
+    mut speed: ResMut<GameSpeed>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if speed.current == speed.target {
+        return;
+    }
+    let GameSpeed {
+        target,
+        current,
+        easing,
+        ..
+    } = &mut *speed;
+    current.smooth_nudge(target, *easing, real_time.delta_secs());
+    // Easing never quite gets there on its own.
+    if (*current - *target).abs() < 0.01 {
+        *current = *target;
+    }
+    time.set_relative_speed(*current);
+}
+
+fn reset_game_speed(mut speed: ResMut<GameSpeed>, mut time: ResMut<Time<Virtual>>) {
+    speed.target = 1.0;
+    speed.current = 1.0;
+    time.set_relative_speed(1.0);
+}
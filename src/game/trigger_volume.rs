@@ -0,0 +1,124 @@
+//! Areas that notice things going in and out of them.
+//!
+//! A [`TriggerVolume`] is a [`Sensor`] collider, so it never pushes anything
+//! and its shape is what counts as inside. Every frame, each entity with a
+//! [`TriggerActivator`] is checked against each volume, and going in or out
+//! sends a [`TriggerEntered`] or [`TriggerExited`] naming the volume and the
+//! entity. Whatever the volume is for (a checkpoint, a collectible, the goal)
+//! reads those and looks the volume up among its own entities.
+//!
+//! Activators count by the point at their center rather than by contacts, as
+//! the player only has a collider of their own while hanging from a chain.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TriggerVolume>();
+    app.register_type::<TriggerActivator>();
+    app.add_event::<TriggerEntered>();
+    app.add_event::<TriggerExited>();
+
+    app.configure_sets(
+        Update,
+        DetectTriggers
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(Update, detect_triggers.in_set(DetectTriggers));
+}
+
+/// Sends [`TriggerEntered`] and [`TriggerExited`]. Systems reading them run
+/// after it to hear about them the same frame.
+#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DetectTriggers;
+
+/// An area that sends events as activators go in and out of its collider.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct TriggerVolume {
+    /// The activators inside.
+    inside: Vec<Entity>,
+}
+
+impl TriggerVolume {
+    /// Whether `entity` is inside the volume.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.inside.contains(&entity)
+    }
+}
+
+/// Something that trigger volumes notice.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct TriggerActivator;
+
+/// Sent when an activator goes into a trigger volume.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TriggerEntered {
+    pub volume: Entity,
+    pub other: Entity,
+}
+
+/// Sent when an activator leaves a trigger volume, or is despawned inside it.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TriggerExited {
+    pub volume: Entity,
+    pub other: Entity,
+}
+
+/// A trigger volume with the shape of `collider`.
+pub fn trigger_volume(collider: Collider) -> impl Bundle {
+    (
+        TriggerVolume::default(),
+        collider,
+        Sensor,
+        // Volumes are checked by shape, so physics can leave them out.
+        CollisionLayers::NONE,
+    )
+}
+
+fn detect_triggers(
+    mut volume_query: Query<(Entity, &mut TriggerVolume, &Collider, Ref<GlobalTransform>)>,
+    activator_query: Query<(Entity, Ref<GlobalTransform>), With<TriggerActivator>>,
+    mut entered: EventWriter<TriggerEntered>,
+    mut exited: EventWriter<TriggerExited>,
+) {
+    for (volume, mut trigger, collider, transform) in &mut volume_query {
+        // Things spawned this frame haven't been moved into place yet.
+        if transform.is_added() {
+            continue;
+        }
+        let position = Position::new(transform.translation().truncate());
+        let (_, _, angle) = transform.rotation().to_euler(EulerRot::XYZ);
+        let rotation = Rotation::radians(angle);
+
+        let mut still_inside = Vec::new();
+        for (other, other_transform) in &activator_query {
+            if other_transform.is_added()
+                || !collider.contains_point(
+                    position,
+                    rotation,
+                    other_transform.translation().truncate(),
+                )
+            {
+                continue;
+            }
+            if !trigger.contains(other) {
+                entered.write(TriggerEntered { volume, other });
+            }
+            still_inside.push(other);
+        }
+        for &other in &trigger.inside {
+            if !still_inside.contains(&other) {
+                exited.write(TriggerExited { volume, other });
+            }
+        }
+        if trigger.inside != still_inside {
+            trigger.inside = still_inside;
+        }
+    }
+}
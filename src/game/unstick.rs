@@ -0,0 +1,171 @@
+//! Getting players out of spots they can't move out of.
+//!
+//! Pushing a direction for [`STUCK_TIME`] without getting [`STUCK_DISTANCE`]
+//! from where the push started, like when wedged between obstacles or pinned
+//! under settled chains, counts as stuck. The interaction prompt then offers
+//! a way out, which lets go of every chain and puts the player on the
+//! nearest [`SafeGround`]. Moving away on their own takes the offer back.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{ChainPool, ChainState},
+        gameplay_error::{GameplayError, OrReport},
+        interaction::{Interactable, Interacted},
+        level_data::LevelData,
+        movement::MovementController,
+        player::Player,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<UnstickOffer>();
+    app.init_resource::<SafeGround>();
+
+    app.add_systems(
+        Update,
+        (detect_stuck_player, unstick_player)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long pushing without getting anywhere counts as stuck, in seconds.
+pub const STUCK_TIME: f32 = 4.0;
+/// How far the player has to get for a push to count as going somewhere, in
+/// pixels.
+pub const STUCK_DISTANCE: f32 = 24.0;
+/// How far above the ground it puts the player, enough to clear any
+/// character.
+const STANDING_CLEARANCE: f32 = 30.0;
+
+/// Places the player can stand: the top of every obstacle, the spawn point
+/// and the checkpoints. Worked out from the level when it's spawned.
+#[derive(Resource, Default, Debug)]
+pub struct SafeGround(pub Vec<Vec2>);
+
+impl LevelData {
+    /// Where in this level a player can be put down safely.
+    pub fn safe_ground(&self) -> SafeGround {
+        SafeGround(
+            self.obstacles
+                .iter()
+                .map(|obstacle| {
+                    obstacle.position + Vec2::Y * (obstacle.size.y / 2.0 + STANDING_CLEARANCE)
+                })
+                .chain(self.spawn)
+                .chain(self.checkpoints.iter().copied())
+                .collect(),
+        )
+    }
+}
+
+impl SafeGround {
+    /// The safe place nearest `position`, if the level has any.
+    pub fn nearest(&self, position: Vec2) -> Option<Vec2> {
+        self.0.iter().copied().min_by(|a, b| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+    }
+}
+
+/// Offers a stuck player a way out, through the interaction prompt. It goes
+/// along with the player, so it's always in range.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct UnstickOffer;
+
+/// Where the player started pushing without getting anywhere, and for how
+/// long they have.
+#[derive(Default)]
+struct Push {
+    from: Option<Vec2>,
+    duration: f32,
+}
+
+fn detect_stuck_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut push: Local<Push>,
+    player_query: Query<(Entity, &GlobalTransform, &MovementController), With<Player>>,
+    offer_query: Query<Entity, With<UnstickOffer>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some((player, transform, controller)) =
+        player_query
+            .single()
+            .or_report(&mut errors, "detect_stuck_player", "player")
+    else {
+        return;
+    };
+    let position = transform.translation().truncate();
+    let moved = push
+        .from
+        .is_some_and(|from| from.distance(position) > STUCK_DISTANCE);
+    if moved {
+        for offer in &offer_query {
+            commands.entity(offer).despawn();
+        }
+    }
+    if controller.intent == Vec2::ZERO || moved || push.from.is_none() {
+        *push = Push {
+            from: (controller.intent != Vec2::ZERO).then_some(position),
+            duration: 0.0,
+        };
+        return;
+    }
+
+    push.duration += time.delta_secs();
+    if push.duration >= STUCK_TIME && offer_query.is_empty() {
+        commands.spawn((
+            Name::new("Unstick Offer"),
+            UnstickOffer,
+            Interactable {
+                prompt: "Get Unstuck".to_string(),
+                icon: None,
+                // Getting out matters more than anything else in reach.
+                priority: i32::MAX,
+                range: f32::INFINITY,
+            },
+            Transform::default(),
+            ChildOf(player),
+        ));
+    }
+}
+
+fn unstick_player(
+    mut commands: Commands,
+    mut interacted: EventReader<Interacted>,
+    safe_ground: Res<SafeGround>,
+    mut chain_state: ResMut<ChainState>,
+    mut pool: ResMut<ChainPool>,
+    offer_query: Query<(), With<UnstickOffer>>,
+    mut player_query: Query<(&mut Transform, Option<&mut LinearVelocity>), With<Player>>,
+) {
+    for event in interacted.read() {
+        if !offer_query.contains(event.target) {
+            continue;
+        }
+        commands.entity(event.target).despawn();
+        let Ok((mut transform, velocity)) = player_query.get_mut(event.by) else {
+            continue;
+        };
+        let Some(ground) = safe_ground.nearest(transform.translation.truncate()) else {
+            continue;
+        };
+        while !chain_state.chains.is_empty() {
+            chain_state.release_oldest(&mut commands, &mut pool);
+        }
+        transform.translation = ground.extend(transform.translation.z);
+        if let Some(mut velocity) = velocity {
+            velocity.0 = Vec2::ZERO;
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! A short celebration between reaching the goal and the results screen.
+//!
+//! When the player reaches the goal, the game freezes where it is and the
+//! camera spirals in on them, turning once around as it zooms in, while
+//! confetti bursts out of the goal and the score counts up on screen. Then
+//! the results screen takes over. Gameplay is paused all the while, so the
+//! camera is left to the [`VictoryOrbit`].
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{
+    Pause,
+    game::{
+        game_rng::{GameRng, RngStream},
+        gameplay_error::{GameplayError, OrReport},
+        mutators::RunMutators,
+        objectives::{GoalReached, Score},
+        path::PathEasing,
+    },
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Confetti>();
+    app.register_type::<ScoreTally>();
+
+    app.add_systems(
+        Update,
+        (
+            start_victory.run_if(on_event::<GoalReached>),
+            (orbit_camera, fall_confetti, tally_score).run_if(resource_exists::<VictoryOrbit>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), end_victory);
+}
+
+/// How long the celebration lasts, in seconds.
+const ORBIT_TIME: f32 = 2.5;
+/// How long the score takes to count up, in seconds.
+const TALLY_TIME: f32 = 1.5;
+/// How far out the camera starts its spiral, in pixels.
+const ORBIT_RADIUS: f32 = 80.0;
+/// The projection scale the camera ends up at.
+const ORBIT_ZOOM: f32 = 0.6;
+const CONFETTI_COUNT: usize = 80;
+/// How fast confetti bursts out, in pixels per second.
+const CONFETTI_SPEED: std::ops::Range<f32> = 200.0..600.0;
+const CONFETTI_GRAVITY: f32 = 600.0;
+/// How much confetti slows in the air, per second.
+const CONFETTI_DRAG: f32 = 1.5;
+const CONFETTI_SIZE: Vec2 = Vec2::new(6.0, 3.0);
+const CONFETTI_COLORS: [Color; 5] = [
+    Color::srgb(0.95, 0.3, 0.35),
+    Color::srgb(1.0, 0.8, 0.2),
+    Color::srgb(0.3, 0.85, 0.45),
+    Color::srgb(0.3, 0.6, 1.0),
+    Color::srgb(0.8, 0.4, 0.95),
+];
+const CONFETTI_Z: f32 = 5.0;
+
+/// The camera spiraling in on the player after they reach the goal.
+#[derive(Resource, Debug)]
+pub struct VictoryOrbit {
+    center: Vec2,
+    /// Where the camera was, relative to `center`, when the goal was reached.
+    from_offset: Vec2,
+    /// The projection scale the orbit started at.
+    from_zoom: f32,
+    /// The final score, to count up to.
+    points: u32,
+    /// Real seconds since the goal was reached. Game time is stopped.
+    elapsed: f32,
+}
+
+/// A piece of confetti, moving by real time since game time is stopped.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct Confetti {
+    velocity: Vec2,
+    spin: f32,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ScoreTally;
+
+fn start_victory(
+    mut commands: Commands,
+    mut goal: EventReader<GoalReached>,
+    score: Res<Score>,
+    mutators: Res<RunMutators>,
+    game_rng: Res<GameRng>,
+    mut stream: Local<RngStream>,
+    camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    let Some(goal) = goal.read().last() else {
+        return;
+    };
+    let Some((transform, projection)) =
+        camera_query
+            .single()
+            .or_report(&mut errors, "start_victory", "camera")
+    else {
+        return;
+    };
+    time.pause();
+    next_pause.set(Pause(true));
+
+    let from_zoom = match projection {
+        Projection::Orthographic(projection) => projection.scale,
+        _ => 1.0,
+    };
+    let offset = transform.translation.truncate() - goal.player;
+    commands.insert_resource(VictoryOrbit {
+        center: goal.player,
+        // Start from the side the camera was on, however close it was.
+        from_offset: offset.normalize_or(Vec2::Y) * ORBIT_RADIUS.max(offset.length()),
+        from_zoom,
+        points: (score.points as f32 * mutators.score_multiplier()).round() as u32,
+        elapsed: 0.0,
+    });
+
+    let rng = stream.get(&game_rng, "confetti");
+    for i in 0..CONFETTI_COUNT {
+        // Mostly upward, spreading out to the sides.
+        let angle = rng.random_range(0.15..0.85) * std::f32::consts::PI;
+        let speed = rng.random_range(CONFETTI_SPEED);
+        commands.spawn((
+            Name::new("Confetti"),
+            Confetti {
+                velocity: Vec2::from_angle(angle) * speed,
+                spin: rng.random_range(-10.0..10.0),
+            },
+            Sprite::from_color(CONFETTI_COLORS[i % CONFETTI_COLORS.len()], CONFETTI_SIZE),
+            Transform::from_translation(goal.goal.extend(CONFETTI_Z))
+                .with_rotation(Quat::from_rotation_z(rng.random_range(0.0..TAU))),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+
+    commands.spawn((
+        widget::ui_root("Victory"),
+        GlobalZIndex(1),
+        StateScoped(Screen::Gameplay),
+        Pickable::IGNORE,
+        children![
+            widget::header("Level complete!"),
+            (widget::label("Score 0"), ScoreTally),
+        ],
+    ));
+}
+
+fn orbit_camera(
+    time: Res<Time<Real>>,
+    mut orbit: ResMut<VictoryOrbit>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut errors: EventWriter<GameplayError>,
+) {
+    orbit.elapsed += time.delta_secs();
+    if orbit.elapsed >= ORBIT_TIME {
+        next_screen.set(Screen::Results);
+        return;
+    }
+
+    let t = PathEasing::InOut.ease(orbit.elapsed / ORBIT_TIME);
+    let turn = t * TAU;
+    let offset = Vec2::from_angle(turn).rotate(orbit.from_offset) * (1.0 - t);
+    let Some((mut transform, mut projection)) =
+        camera_query
+            .single_mut()
+            .or_report(&mut errors, "orbit_camera", "camera")
+    else {
+        return;
+    };
+    transform.translation = (orbit.center + offset).extend(transform.translation.z);
+    transform.rotation = Quat::from_rotation_z(turn);
+    if let Projection::Orthographic(projection) = &mut *projection {
+        projection.scale = orbit.from_zoom.lerp(ORBIT_ZOOM, t);
+    }
+}
+
+fn fall_confetti(
+    time: Res<Time<Real>>,
+    mut confetti_query: Query<(&mut Transform, &mut Confetti)>,
+) {
+    let dt = time.delta_secs();
+    for (mut transform, mut confetti) in &mut confetti_query {
+        confetti.velocity.y -= CONFETTI_GRAVITY * dt;
+        let mut velocity = confetti.velocity;
+        velocity.smooth_nudge(&Vec2::ZERO, CONFETTI_DRAG, dt);
+        confetti.velocity = velocity;
+        transform.translation += (velocity * dt).extend(0.0);
+        transform.rotate_z(confetti.spin * dt);
+    }
+}
+
+fn tally_score(orbit: Res<VictoryOrbit>, mut tally: Single<&mut Text, With<ScoreTally>>) {
+    let shown = (orbit.points as f32 * (orbit.elapsed / TALLY_TIME).min(1.0)).round() as u32;
+    tally.0 = format!("Score {shown}");
+}
+
+/// Game time was stopped for the celebration.
+fn end_victory(mut commands: Commands, mut time: ResMut<Time<Virtual>>) {
+    commands.remove_resource::<VictoryOrbit>();
+    time.unpause();
+}
@@ -0,0 +1,159 @@
+//! Running along walls while hanging from a chain.
+//!
+//! A player swinging into a wall fast enough keeps running along it. The
+//! chain supplies the pull toward the middle of the swing, and the player is
+//! pressed against the wall partly along its normal and partly along the
+//! chain, so the run follows the arc of the swing. While running, gravity is
+//! partly held off, less so the slower the player goes, and not at all once
+//! the run's time is up. The player then has to leave the wall before they
+//! can run along it again.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    game::{
+        chain::{ChainState, Layer},
+        game_timer::{AddGameTimer, GameTimer},
+        movement::MovementController,
+        player::Player,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WallRun>();
+    app.add_game_timer::<WallRun>();
+
+    app.add_systems(
+        Update,
+        (wall_run, end_wall_run_when_untethered)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Surfaces whose normal points further up or down than this aren't walls.
+const MAX_WALL_NORMAL_Y: f32 = 0.3;
+/// Running slower than this along a wall, in pixels per second, ends the run.
+const MIN_RUN_SPEED: f32 = 150.0;
+/// Running this fast holds off gravity fully.
+const FULL_RUN_SPEED: f32 = 450.0;
+/// The most a run can hold off gravity.
+const MAX_SUPPORT: f32 = 0.85;
+/// How long a run can last, in seconds.
+const RUN_TIME: f32 = 1.5;
+/// How hard the player is pressed into the wall, in pixels per second squared.
+const GRIP_ACCELERATION: f32 = 400.0;
+/// How much of the press is along the chain instead of into the wall.
+const CHAIN_BLEND: f32 = 0.5;
+/// Acceleration along the wall from moving along it.
+const RUN_ACCELERATION: f32 = 300.0;
+
+/// The player running along a wall, for as long as `timer` allows.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct WallRun {
+    timer: Timer,
+}
+
+impl GameTimer for WallRun {
+    fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+fn wall_run(
+    mut commands: Commands,
+    time: Res<Time>,
+    collisions: Collisions,
+    chain_state: Res<ChainState>,
+    mut player_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &MovementController,
+            &mut LinearVelocity,
+            Option<&WallRun>,
+        ),
+        (With<Player>, With<RigidBody>),
+    >,
+    layer_query: Query<&CollisionLayers>,
+    transform_query: Query<&GlobalTransform>,
+) {
+    let Ok((player, player_transform, controller, mut velocity, wall_run)) =
+        player_query.single_mut()
+    else {
+        return;
+    };
+
+    let wall_normal = collisions.collisions_with(player).find_map(|pair| {
+        let (other, normal) = if pair.collider1 == player {
+            (pair.collider2, -pair.manifolds.first()?.normal)
+        } else {
+            (pair.collider1, pair.manifolds.first()?.normal)
+        };
+        let layers = layer_query.get(other).ok()?;
+        (layers.memberships.has_all(Layer::StaticObstacle) && normal.y.abs() < MAX_WALL_NORMAL_Y)
+            .then_some(normal)
+    });
+    let Some(normal) = wall_normal else {
+        if wall_run.is_some() {
+            commands.entity(player).remove::<(WallRun, GravityScale)>();
+        }
+        return;
+    };
+
+    let tangent = normal.perp();
+    let speed = velocity.dot(tangent).abs();
+    let wall_run = match wall_run {
+        Some(wall_run) => wall_run,
+        None if speed >= MIN_RUN_SPEED => {
+            commands.entity(player).insert(WallRun {
+                timer: Timer::from_seconds(RUN_TIME, TimerMode::Once),
+            });
+            return;
+        }
+        None => return,
+    };
+
+    let falloff = ((speed - MIN_RUN_SPEED) / (FULL_RUN_SPEED - MIN_RUN_SPEED)).clamp(0.0, 1.0);
+    let support = if wall_run.timer.finished() {
+        0.0
+    } else {
+        MAX_SUPPORT * falloff * (1.0 - wall_run.timer.fraction())
+    };
+    commands.entity(player).insert(GravityScale(1.0 - support));
+    if support == 0.0 {
+        return;
+    }
+
+    // Press into the wall, leaning toward the chain's root.
+    let position = player_transform.translation().truncate();
+    let toward_chain = chain_state
+        .chains
+        .last()
+        .filter(|chain| chain.hook.is_some())
+        .and_then(|chain| transform_query.get(*chain.links.first()?).ok())
+        .map_or(Vec2::ZERO, |root| {
+            (root.translation().truncate() - position).normalize_or_zero()
+        });
+    let press = (-normal)
+        .lerp(toward_chain, CHAIN_BLEND)
+        .normalize_or(-normal);
+    let run = tangent * controller.intent.dot(tangent) * RUN_ACCELERATION;
+    velocity.0 += (press * GRIP_ACCELERATION + run) * support * time.delta_secs();
+}
+
+/// Letting go of the chain ends the run.
+fn end_wall_run_when_untethered(
+    mut commands: Commands,
+    player_query: Query<Entity, (With<WallRun>, Without<RigidBody>)>,
+) {
+    for player in &player_query {
+        commands.entity(player).remove::<(WallRun, GravityScale)>();
+    }
+}
@@ -0,0 +1,142 @@
+//! Triggers, actions and the wires between them, as stored in level files.
+//!
+//! A trigger (a zone, a switch or a timer) fires every action it is wired
+//! to. Wires are stored as compact `(trigger, action)` index pairs, e.g.
+//! `wires: [(0, 1), (0, 2)]`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::level_data::LevelData;
+
+/// Something in a level that fires when the player interacts with it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TriggerData {
+    pub position: Vec2,
+    pub kind: TriggerKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TriggerKind {
+    /// Fires when the player enters an area.
+    Zone { size: Vec2 },
+    /// Fires when the player's chain hits it.
+    Switch,
+    /// Fires a number of seconds after the level starts.
+    Timer { seconds: f32 },
+}
+
+/// Something that happens when a trigger wired to it fires.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ActionData {
+    pub position: Vec2,
+    pub kind: ActionKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActionKind {
+    /// Removes the door-sized obstacle at the action's position.
+    OpenDoor {
+        size: Vec2,
+    },
+    SpawnEnemies {
+        count: u32,
+    },
+    StartCutscene {
+        name: String,
+    },
+    ChangeGravity {
+        gravity: Vec2,
+    },
+}
+
+/// Connects the trigger and action at these indices.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wire(pub usize, pub usize);
+
+impl TriggerKind {
+    /// All trigger kinds with default settings, in the order the editor
+    /// cycles through them.
+    pub fn presets() -> [TriggerKind; 3] {
+        [
+            TriggerKind::Zone {
+                size: Vec2::splat(80.0),
+            },
+            TriggerKind::Switch,
+            TriggerKind::Timer { seconds: 5.0 },
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TriggerKind::Zone { .. } => "zone",
+            TriggerKind::Switch => "switch",
+            TriggerKind::Timer { .. } => "timer",
+        }
+    }
+}
+
+impl ActionKind {
+    /// All action kinds with default settings, in the order the editor
+    /// cycles through them.
+    pub fn presets() -> [ActionKind; 4] {
+        [
+            ActionKind::OpenDoor {
+                size: Vec2::new(20.0, 100.0),
+            },
+            ActionKind::SpawnEnemies { count: 3 },
+            ActionKind::StartCutscene {
+                name: "intro".to_string(),
+            },
+            ActionKind::ChangeGravity {
+                gravity: Vec2::new(0.0, -400.0),
+            },
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActionKind::OpenDoor { .. } => "open door",
+            ActionKind::SpawnEnemies { .. } => "spawn enemies",
+            ActionKind::StartCutscene { .. } => "start cutscene",
+            ActionKind::ChangeGravity { .. } => "change gravity",
+        }
+    }
+}
+
+impl LevelData {
+    /// Connect a trigger to an action, or disconnect them if they already are.
+    pub fn toggle_wire(&mut self, trigger: usize, action: usize) {
+        let wire = Wire(trigger, action);
+        match self.wires.iter().position(|&other| other == wire) {
+            Some(i) => {
+                self.wires.remove(i);
+            }
+            None => self.wires.push(wire),
+        }
+    }
+
+    /// Remove a trigger along with its wires, keeping other wires pointing
+    /// at the right triggers.
+    pub fn remove_trigger(&mut self, index: usize) {
+        self.triggers.remove(index);
+        self.wires.retain(|wire| wire.0 != index);
+        for wire in &mut self.wires {
+            if wire.0 > index {
+                wire.0 -= 1;
+            }
+        }
+    }
+
+    /// Remove an action along with its wires, keeping other wires pointing
+    /// at the right actions.
+    pub fn remove_action(&mut self, index: usize) {
+        self.actions.remove(index);
+        self.wires.retain(|wire| wire.1 != index);
+        for wire in &mut self.wires {
+            if wire.1 > index {
+                wire.1 -= 1;
+            }
+        }
+    }
+}
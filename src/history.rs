@@ -0,0 +1,174 @@
+//! Undo/redo for dev tooling. Only enabled in dev builds.
+//!
+//! Anything that edits the world from a tool (the level editor, the debug
+//! console) does so through an [`EditCommand`], which knows how to apply and
+//! revert itself. Executed commands are recorded in a bounded
+//! [`CommandHistory`], and can be grouped so that e.g. a whole drag is undone
+//! in one step.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(CommandHistory::with_capacity(DEFAULT_HISTORY_CAPACITY));
+}
+
+const DEFAULT_HISTORY_CAPACITY: usize = 100;
+
+/// A reversible change to the world.
+pub trait EditCommand: Send + Sync + 'static {
+    fn apply(&mut self, world: &mut World);
+    fn revert(&mut self, world: &mut World);
+    /// A short description for history listings, e.g. "Move obstacle #3".
+    fn describe(&self) -> String;
+}
+
+/// A single undo step: one command, or several grouped together.
+#[derive(Default)]
+struct Step(Vec<Box<dyn EditCommand>>);
+
+impl Step {
+    fn describe(&self) -> String {
+        match self.0.as_slice() {
+            [] => "Nothing".to_string(),
+            [command] => command.describe(),
+            [first, ..] => format!("{} (+{} more)", first.describe(), self.0.len() - 1),
+        }
+    }
+}
+
+/// Executed commands that can be undone, and undone commands that can be redone.
+#[derive(Resource)]
+pub struct CommandHistory {
+    done: VecDeque<Step>,
+    undone: Vec<Step>,
+    group: Option<Step>,
+    capacity: usize,
+}
+
+impl CommandHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            done: VecDeque::with_capacity(capacity),
+            undone: Vec::new(),
+            group: None,
+            capacity,
+        }
+    }
+
+    /// Forget all history, e.g. when the thing being edited is replaced.
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+        self.group = None;
+    }
+
+    /// Start collecting commands into a single undo step.
+    pub fn begin_group(&mut self) {
+        self.end_group();
+        self.group = Some(Step::default());
+    }
+
+    /// Finish the current group. Empty groups are dropped.
+    pub fn end_group(&mut self) {
+        if let Some(group) = self.group.take().filter(|group| !group.0.is_empty()) {
+            self.push_step(group);
+        }
+    }
+
+    /// Record a command that has already been applied.
+    pub fn record(&mut self, command: Box<dyn EditCommand>) {
+        self.undone.clear();
+        match &mut self.group {
+            Some(group) => group.0.push(command),
+            None => self.push_step(Step(vec![command])),
+        }
+    }
+
+    fn push_step(&mut self, step: Step) {
+        if self.done.len() == self.capacity {
+            self.done.pop_front();
+        }
+        self.done.push_back(step);
+    }
+
+    /// Descriptions of the undoable steps, oldest first.
+    pub fn descriptions(&self) -> impl Iterator<Item = String> + '_ {
+        self.done.iter().map(Step::describe)
+    }
+}
+
+/// Apply a command and record it in the history.
+pub fn execute(world: &mut World, mut command: impl EditCommand) {
+    command.apply(world);
+    world
+        .resource_mut::<CommandHistory>()
+        .record(Box::new(command));
+}
+
+/// Revert the most recent step. Returns its description, if there was one.
+pub fn undo(world: &mut World) -> Option<String> {
+    world.resource_scope(|world, mut history: Mut<CommandHistory>| {
+        history.end_group();
+        let mut step = history.done.pop_back()?;
+        for command in step.0.iter_mut().rev() {
+            command.revert(world);
+        }
+        let description = step.describe();
+        history.undone.push(step);
+        Some(description)
+    })
+}
+
+/// Re-apply the most recently undone step. Returns its description, if there was one.
+pub fn redo(world: &mut World) -> Option<String> {
+    world.resource_scope(|world, mut history: Mut<CommandHistory>| {
+        let mut step = history.undone.pop()?;
+        for command in &mut step.0 {
+            command.apply(world);
+        }
+        let description = step.describe();
+        history.push_step(step);
+        Some(description)
+    })
+}
+
+/// Queue history operations from systems, in order with other commands.
+pub trait HistoryCommandsExt {
+    fn execute(&mut self, command: impl EditCommand);
+    fn begin_group(&mut self);
+    fn end_group(&mut self);
+    fn undo(&mut self);
+    fn redo(&mut self);
+}
+
+impl HistoryCommandsExt for Commands<'_, '_> {
+    fn execute(&mut self, command: impl EditCommand) {
+        self.queue(move |world: &mut World| execute(world, command));
+    }
+
+    fn begin_group(&mut self) {
+        self.queue(|world: &mut World| world.resource_mut::<CommandHistory>().begin_group());
+    }
+
+    fn end_group(&mut self) {
+        self.queue(|world: &mut World| world.resource_mut::<CommandHistory>().end_group());
+    }
+
+    fn undo(&mut self) {
+        self.queue(|world: &mut World| {
+            if let Some(description) = undo(world) {
+                info!("Undo: {description}");
+            }
+        });
+    }
+
+    fn redo(&mut self) {
+        self.queue(|world: &mut World| {
+            if let Some(description) = redo(world) {
+                info!("Redo: {description}");
+            }
+        });
+    }
+}
@@ -0,0 +1,523 @@
+//! Gamepads: recognizing controllers, their button labels and stick settings.
+//!
+//! Controllers are looked up in an SDL-style mapping database,
+//! `assets/input/gamecontrollerdb.txt`, which uses the same format as
+//! <https://github.com/mdqinc/SDL_GameControllerDB>, so entries can be copied
+//! from there. The database tells us what a controller really is, which picks
+//! the [`ControllerPreset`] used for button labels and default deadzones.
+//! Players can override both per device in the settings menu.
+//!
+//! Controllers can come and go at any time, including mid-game.
+
+use std::{collections::BTreeMap, fmt};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    input::gamepad::{AxisSettings, GamepadSettings},
+    prelude::*,
+};
+
+use crate::{asset_tracking::LoadResource, theme::layout::HudAnchor};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<ControllerDatabase>();
+    app.init_asset_loader::<ControllerDatabaseLoader>();
+    app.register_type::<ControllerAssets>();
+    app.load_resource::<ControllerAssets>();
+
+    app.init_resource::<ControllerProfiles>();
+    app.init_resource::<ActiveController>();
+    app.init_resource::<ControllerNotice>();
+    app.add_event::<ActiveControllerLost>();
+    app.register_type::<ControllerNoticeText>();
+
+    app.add_systems(Startup, spawn_notice_text);
+    app.add_systems(
+        PreUpdate,
+        (
+            identify_controllers.run_if(resource_exists::<ControllerAssets>),
+            notice_disconnects,
+            track_active_controller,
+            apply_deadzones.run_if(resource_changed::<ControllerProfiles>),
+        )
+            .chain()
+            .after(bevy::input::InputSystem),
+    );
+    app.add_systems(Update, update_notice_text);
+}
+
+/// The built-in controller families, which decide how buttons are labeled.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+pub enum ControllerPreset {
+    #[default]
+    Xbox,
+    DualSense,
+    SwitchPro,
+}
+
+impl ControllerPreset {
+    pub const ALL: [Self; 3] = [Self::Xbox, Self::DualSense, Self::SwitchPro];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Xbox => "Xbox",
+            Self::DualSense => "DualSense",
+            Self::SwitchPro => "Switch Pro",
+        }
+    }
+
+    /// Guess the family from a controller's name.
+    pub fn detect(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+        if has(&["xbox", "xinput", "x-box"]) {
+            Some(Self::Xbox)
+        } else if has(&["dualsense", "dualshock", "playstation", "ps3", "ps4", "ps5"]) {
+            Some(Self::DualSense)
+        } else if has(&["switch", "nintendo", "joy-con"]) {
+            Some(Self::SwitchPro)
+        } else {
+            None
+        }
+    }
+
+    /// Older pads drift more, so they get a bigger deadzone.
+    pub fn default_deadzone(self) -> f32 {
+        match self {
+            Self::Xbox => 0.15,
+            Self::DualSense => 0.08,
+            Self::SwitchPro => 0.12,
+        }
+    }
+
+    /// What's printed on `button` on this kind of controller. Buttons are
+    /// identified by position, so the bottom face button is "B" on a Switch
+    /// Pro controller but "A" on an Xbox one.
+    pub fn glyph(self, button: GamepadButton) -> &'static str {
+        use GamepadButton::*;
+        match (self, button) {
+            (Self::Xbox, South) => "A",
+            (Self::Xbox, East) => "B",
+            (Self::Xbox, West) => "X",
+            (Self::Xbox, North) => "Y",
+            (Self::Xbox, LeftTrigger) => "LB",
+            (Self::Xbox, RightTrigger) => "RB",
+            (Self::Xbox, LeftTrigger2) => "LT",
+            (Self::Xbox, RightTrigger2) => "RT",
+            (Self::Xbox, Select) => "View",
+            (Self::Xbox, Start) => "Menu",
+            (Self::Xbox, Mode) => "Xbox",
+            (Self::DualSense, South) => "Cross",
+            (Self::DualSense, East) => "Circle",
+            (Self::DualSense, West) => "Square",
+            (Self::DualSense, North) => "Triangle",
+            (Self::DualSense, LeftTrigger) => "L1",
+            (Self::DualSense, RightTrigger) => "R1",
+            (Self::DualSense, LeftTrigger2) => "L2",
+            (Self::DualSense, RightTrigger2) => "R2",
+            (Self::DualSense, Select) => "Create",
+            (Self::DualSense, Start) => "Options",
+            (Self::DualSense, Mode) => "PS",
+            (Self::DualSense, LeftThumb) => "L3",
+            (Self::DualSense, RightThumb) => "R3",
+            (Self::SwitchPro, South) => "B",
+            (Self::SwitchPro, East) => "A",
+            (Self::SwitchPro, West) => "Y",
+            (Self::SwitchPro, North) => "X",
+            (Self::SwitchPro, LeftTrigger) => "L",
+            (Self::SwitchPro, RightTrigger) => "R",
+            (Self::SwitchPro, LeftTrigger2) => "ZL",
+            (Self::SwitchPro, RightTrigger2) => "ZR",
+            (Self::SwitchPro, Select) => "-",
+            (Self::SwitchPro, Start) => "+",
+            (Self::SwitchPro, Mode) => "Home",
+            (_, LeftThumb) => "LS",
+            (_, RightThumb) => "RS",
+            (_, DPadUp) => "D-Pad Up",
+            (_, DPadDown) => "D-Pad Down",
+            (_, DPadLeft) => "D-Pad Left",
+            (_, DPadRight) => "D-Pad Right",
+            _ => "?",
+        }
+    }
+}
+
+/// One line of the mapping database.
+#[derive(Clone, Debug)]
+pub struct ControllerMapping {
+    pub name: String,
+    /// Which SDL inputs the controller has, e.g. `a` or `righttrigger`.
+    pub inputs: Vec<String>,
+}
+
+/// Inputs the game needs. Controllers missing any of them still work, but
+/// can't do everything.
+const REQUIRED_INPUTS: [&str; 6] = ["a", "leftx", "lefty", "rightx", "righty", "righttrigger"];
+
+impl ControllerMapping {
+    pub fn missing_inputs(&self) -> Vec<&'static str> {
+        REQUIRED_INPUTS
+            .into_iter()
+            .filter(|&input| !self.inputs.iter().any(|known| known == input))
+            .collect()
+    }
+}
+
+/// Controller mappings for this platform by USB vendor and product ID.
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct ControllerDatabase(pub BTreeMap<(u16, u16), ControllerMapping>);
+
+impl ControllerDatabase {
+    /// Parse a database in SDL's format, keeping the entries for `platform`
+    /// (`"Linux"`, `"Windows"` or `"Mac OS X"`). Lines that aren't mappings
+    /// are skipped.
+    pub fn parse(text: &str, platform: &str) -> Self {
+        let mut mappings = BTreeMap::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split(',').filter(|field| !field.is_empty());
+            let (Some(guid), Some(name)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let Some(ids) = vendor_and_product(guid) else {
+                continue;
+            };
+            let mut inputs = Vec::new();
+            let mut line_platform = None;
+            for (key, value) in fields.filter_map(|field| field.split_once(':')) {
+                if key == "platform" {
+                    line_platform = Some(value);
+                } else {
+                    inputs.push(key.trim_start_matches(['+', '-']).to_string());
+                }
+            }
+            if line_platform.is_none_or(|line_platform| line_platform == platform) {
+                mappings.insert(
+                    ids,
+                    ControllerMapping {
+                        name: name.to_string(),
+                        inputs,
+                    },
+                );
+            }
+        }
+        Self(mappings)
+    }
+
+    pub fn find(&self, vendor_id: u16, product_id: u16) -> Option<&ControllerMapping> {
+        self.0.get(&(vendor_id, product_id))
+    }
+}
+
+/// SDL GUIDs of USB devices store the vendor and product IDs as
+/// little-endian hex at bytes 4 and 8.
+fn vendor_and_product(guid: &str) -> Option<(u16, u16)> {
+    let id_at = |byte: usize| {
+        let hex = guid.get(byte * 2..byte * 2 + 4)?;
+        u16::from_str_radix(hex, 16).ok().map(u16::swap_bytes)
+    };
+    if guid.len() != 32 {
+        return None;
+    }
+    Some((id_at(4)?, id_at(8)?))
+}
+
+/// The SDL name of the platform we're running on.
+fn sdl_platform() -> &'static str {
+    match std::env::consts::OS {
+        "windows" => "Windows",
+        "macos" => "Mac OS X",
+        "android" => "Android",
+        "ios" => "iOS",
+        _ => "Linux",
+    }
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct ControllerAssets {
+    #[dependency]
+    pub database: Handle<ControllerDatabase>,
+}
+
+impl FromWorld for ControllerAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            database: assets.load("input/gamecontrollerdb.txt"),
+        }
+    }
+}
+
+/// Per-device settings, chosen by the player in the settings menu.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ControllerProfile {
+    pub preset: ControllerPreset,
+    /// Stick input closer to the center than this is ignored.
+    pub deadzone: f32,
+}
+
+impl ControllerProfile {
+    pub const MIN_DEADZONE: f32 = 0.0;
+    pub const MAX_DEADZONE: f32 = 0.5;
+
+    fn new(preset: ControllerPreset) -> Self {
+        Self {
+            preset,
+            deadzone: preset.default_deadzone(),
+        }
+    }
+}
+
+/// Settings of every controller seen this session, by device name. Names are
+/// the most stable way to recognize a controller when it is plugged back in.
+#[derive(Resource, Default, Debug)]
+pub struct ControllerProfiles(pub BTreeMap<String, ControllerProfile>);
+
+/// The gamepad the player last used, if they are using one at all. Prompts
+/// show its buttons instead of keys.
+#[derive(Resource, Default, Debug)]
+pub struct ActiveController(pub Option<Entity>);
+
+/// Sent when the controller being played with disconnects.
+#[derive(Event, Debug)]
+pub struct ActiveControllerLost;
+
+/// A short message about controllers coming and going.
+#[derive(Resource, Debug)]
+pub struct ControllerNotice {
+    text: String,
+    timer: Timer,
+}
+
+impl Default for ControllerNotice {
+    fn default() -> Self {
+        // Nothing to show yet, so it starts out finished.
+        let mut timer = Timer::from_seconds(NOTICE_DURATION, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self {
+            text: String::new(),
+            timer,
+        }
+    }
+}
+
+impl ControllerNotice {
+    pub fn show(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.timer.reset();
+    }
+}
+
+const NOTICE_DURATION: f32 = 3.0;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ControllerNoticeText;
+
+fn spawn_notice_text(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Controller Notice"),
+        Text::default(),
+        TextFont::from_font_size(20.0),
+        TextLayout::new_with_justify(JustifyText::Center),
+        HudAnchor::Bottom(10.0),
+        GlobalZIndex(3),
+        ControllerNoticeText,
+    ));
+}
+
+fn update_notice_text(
+    time: Res<Time>,
+    mut notice: ResMut<ControllerNotice>,
+    mut text: Single<&mut Text, With<ControllerNoticeText>>,
+) {
+    notice.timer.tick(time.delta());
+    let shown = if notice.timer.finished() {
+        ""
+    } else {
+        notice.text.as_str()
+    };
+    if text.0 != shown {
+        text.0 = shown.to_string();
+    }
+}
+
+/// Give newly connected controllers a profile and tell the player about them.
+fn identify_controllers(
+    controller_assets: Res<ControllerAssets>,
+    databases: Res<Assets<ControllerDatabase>>,
+    gamepad_query: Query<(Entity, &Name, &Gamepad), Added<Gamepad>>,
+    mut profiles: ResMut<ControllerProfiles>,
+    mut active: ResMut<ActiveController>,
+    mut notice: ResMut<ControllerNotice>,
+) {
+    let database = databases.get(&controller_assets.database);
+    for (entity, name, gamepad) in &gamepad_query {
+        let mapping = gamepad
+            .vendor_id()
+            .zip(gamepad.product_id())
+            .and_then(|(vendor, product)| database?.find(vendor, product));
+        let known_name = mapping.map_or(name.as_str(), |mapping| mapping.name.as_str());
+        let detected = ControllerPreset::detect(known_name);
+        let profile = *profiles
+            .0
+            .entry(name.to_string())
+            .or_insert_with(|| ControllerProfile::new(detected.unwrap_or_default()));
+        active.0 = Some(entity);
+
+        let mut message = format!("{known_name} connected ({}).", profile.preset.label());
+        if detected.is_none() {
+            message.push_str(" Change its button layout in Settings.");
+        }
+        if let Some(missing) = mapping
+            .map(ControllerMapping::missing_inputs)
+            .filter(|missing| !missing.is_empty())
+        {
+            message.push_str(&format!(" Missing: {}.", missing.join(", ")));
+        }
+        notice.show(message);
+    }
+}
+
+fn notice_disconnects(
+    mut removed: RemovedComponents<Gamepad>,
+    name_query: Query<&Name>,
+    mut active: ResMut<ActiveController>,
+    mut notice: ResMut<ControllerNotice>,
+    mut lost: EventWriter<ActiveControllerLost>,
+) {
+    for entity in removed.read() {
+        if active.0 == Some(entity) {
+            active.0 = None;
+            lost.write(ActiveControllerLost);
+        }
+        let name = name_query.get(entity).map_or("Controller", Name::as_str);
+        notice.show(format!("{name} disconnected."));
+    }
+}
+
+/// Switch prompts between keyboard and gamepad, depending on what the player
+/// touched last.
+fn track_active_controller(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_query: Query<(Entity, &Gamepad)>,
+    mut active: ResMut<ActiveController>,
+) {
+    let used_gamepad = gamepad_query.iter().find(|(_, gamepad)| {
+        gamepad.get_just_pressed().next().is_some()
+            || gamepad.left_stick().length() > 0.5
+            || gamepad.right_stick().length() > 0.5
+    });
+    let used = if let Some((entity, _)) = used_gamepad {
+        Some(entity)
+    } else if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some()
+    {
+        None
+    } else {
+        return;
+    };
+    if active.0 != used {
+        active.0 = used;
+    }
+}
+
+/// Apply each controller's deadzone to its sticks.
+fn apply_deadzones(
+    profiles: Res<ControllerProfiles>,
+    mut gamepad_query: Query<(&Name, &mut GamepadSettings), With<Gamepad>>,
+) {
+    for (name, mut settings) in &mut gamepad_query {
+        let Some(profile) = profiles.0.get(name.as_str()) else {
+            continue;
+        };
+        let Ok(axis_settings) = AxisSettings::new(
+            -1.0,
+            -profile.deadzone,
+            profile.deadzone,
+            1.0,
+            AxisSettings::default().threshold(),
+        ) else {
+            continue;
+        };
+        for axis in [
+            GamepadAxis::LeftStickX,
+            GamepadAxis::LeftStickY,
+            GamepadAxis::RightStickX,
+            GamepadAxis::RightStickY,
+        ] {
+            settings.axis_settings.insert(axis, axis_settings.clone());
+        }
+    }
+}
+
+/// The preset of the controller the player is using, if any.
+pub fn active_preset(
+    active: &ActiveController,
+    profiles: &ControllerProfiles,
+    name_query: &Query<&Name, With<Gamepad>>,
+) -> Option<ControllerPreset> {
+    let name = name_query.get(active.0?).ok()?;
+    Some(
+        profiles
+            .0
+            .get(name.as_str())
+            .map(|profile| profile.preset)
+            .unwrap_or_default(),
+    )
+}
+
+#[derive(Debug)]
+pub enum ControllerDatabaseLoadError {
+    Io(std::io::Error),
+    Utf8(std::string::FromUtf8Error),
+}
+
+impl fmt::Display for ControllerDatabaseLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControllerDatabaseLoadError::Io(error) => {
+                write!(f, "could not read controller database: {error}")
+            }
+            ControllerDatabaseLoadError::Utf8(error) => {
+                write!(f, "controller database is not text: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ControllerDatabaseLoadError {}
+
+impl From<std::io::Error> for ControllerDatabaseLoadError {
+    fn from(error: std::io::Error) -> Self {
+        ControllerDatabaseLoadError::Io(error)
+    }
+}
+
+#[derive(Default)]
+struct ControllerDatabaseLoader;
+
+impl AssetLoader for ControllerDatabaseLoader {
+    type Asset = ControllerDatabase;
+    type Settings = ();
+    type Error = ControllerDatabaseLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = String::from_utf8(bytes).map_err(ControllerDatabaseLoadError::Utf8)?;
+        Ok(ControllerDatabase::parse(&text, sdl_platform()))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["txt"]
+    }
+}
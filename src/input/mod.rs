@@ -0,0 +1,587 @@
+//! Turning keyboard, mouse and gamepad input into game actions.
+//!
+//! Gameplay code asks [`ActionInput`] whether an [`InputAction`] is pressed
+//! instead of reading devices directly, so every action works with whatever
+//! the [`InputMap`] binds it to.
+//!
+//! For players who can't keep a button held down, [`HoldToggles`] turns every
+//! held action into one that a press switches on and the next press switches
+//! off. [`ActionInput`] applies it, so gameplay code doesn't have to know.
+//!
+//! Tools that drive the game themselves, like TAS playback, insert a
+//! [`ScriptedInput`], which [`ActionInput`] then reads instead of the devices.
+
+pub mod gamepad;
+pub mod text;
+
+use bevy::{
+    ecs::system::SystemParam,
+    input::{InputSystem, mouse::AccumulatedMouseScroll},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{input::gamepad::ControllerPreset, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<InputMap>();
+    app.init_resource::<HoldToggles>();
+
+    app.add_plugins((gamepad::plugin, text::plugin));
+
+    app.add_systems(PreUpdate, toggle_held_actions.after(InputSystem));
+    app.add_systems(OnExit(Screen::Gameplay), release_held_actions);
+}
+
+/// Something the player can do.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    FireChain,
+    ReleaseChain,
+    /// Keyboard aiming, for playing without a mouse.
+    AimUp,
+    AimDown,
+    AimLeft,
+    AimRight,
+    /// Held to turn the keyboard aim slowly instead of snapping it.
+    FineAim,
+    /// Shorten the newest chain.
+    ReelIn,
+    /// Lengthen the newest chain.
+    ReelOut,
+    /// Switch between swinging and rappelling down the newest chain.
+    Rappel,
+    /// Route a rope over the anchors the two newest chains hooked, or let go
+    /// of it.
+    Pulley,
+    /// Start the level over right away.
+    Restart,
+    /// Held to slow the game down while aiming.
+    SlowMotion,
+    /// Use whatever the interaction prompt shows.
+    Interact,
+    Taunt1,
+    Taunt2,
+    Taunt3,
+    /// Take control of the other character, in levels with two.
+    SwapCharacter,
+}
+
+impl InputAction {
+    pub const ALL: [Self; 22] = [
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::FireChain,
+        Self::ReleaseChain,
+        Self::AimUp,
+        Self::AimDown,
+        Self::AimLeft,
+        Self::AimRight,
+        Self::FineAim,
+        Self::ReelIn,
+        Self::ReelOut,
+        Self::Rappel,
+        Self::Pulley,
+        Self::Restart,
+        Self::SlowMotion,
+        Self::Interact,
+        Self::Taunt1,
+        Self::Taunt2,
+        Self::Taunt3,
+        // Replays index actions by their place here, so new ones go last.
+        Self::SwapCharacter,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MoveUp => "Move Up",
+            Self::MoveDown => "Move Down",
+            Self::MoveLeft => "Move Left",
+            Self::MoveRight => "Move Right",
+            Self::FireChain => "Fire Chain",
+            Self::ReleaseChain => "Release Chain",
+            Self::AimUp => "Aim Up",
+            Self::AimDown => "Aim Down",
+            Self::AimLeft => "Aim Left",
+            Self::AimRight => "Aim Right",
+            Self::FineAim => "Fine Aim",
+            Self::ReelIn => "Reel In",
+            Self::ReelOut => "Reel Out",
+            Self::Rappel => "Rappel",
+            Self::Pulley => "Pulley",
+            Self::Restart => "Restart",
+            Self::SlowMotion => "Slow Motion",
+            Self::Interact => "Interact",
+            Self::Taunt1 => "Taunt 1",
+            Self::Taunt2 => "Taunt 2",
+            Self::Taunt3 => "Taunt 3",
+            Self::SwapCharacter => "Swap Character",
+        }
+    }
+
+    /// Whether the action lasts for as long as it's held, rather than
+    /// happening once per press.
+    pub fn is_held(self) -> bool {
+        matches!(
+            self,
+            Self::FineAim | Self::ReelIn | Self::ReelOut | Self::SlowMotion
+        )
+    }
+
+    /// The held action that undoes this one, if any.
+    fn opposite(self) -> Option<Self> {
+        match self {
+            Self::ReelIn => Some(Self::ReelOut),
+            Self::ReelOut => Some(Self::ReelIn),
+            _ => None,
+        }
+    }
+}
+
+/// Ready-made sets of bindings.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Reflect, Serialize, Deserialize)]
+pub enum InputPreset {
+    /// Keyboard to move, mouse to aim and fire.
+    #[default]
+    Standard,
+    /// Mouse in the left hand: fire and release swap buttons, and the
+    /// arrow keys move.
+    LeftHanded,
+    /// Everything on the keyboard, for laptops and trackpads.
+    KeyboardOnly,
+    /// Rebound by the player. Starts out as [`InputPreset::Standard`].
+    Custom,
+}
+
+impl InputPreset {
+    /// The presets players can pick. [`InputPreset::Custom`] is only reached
+    /// by rebinding.
+    pub const ALL: [Self; 3] = [Self::Standard, Self::LeftHanded, Self::KeyboardOnly];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::LeftHanded => "Left-Handed",
+            Self::KeyboardOnly => "Keyboard Only",
+            Self::Custom => "Custom",
+        }
+    }
+
+    pub fn bindings(self) -> Vec<(InputAction, Binding)> {
+        use Binding::*;
+        use InputAction::*;
+        let mut bindings = Vec::new();
+        if self != Self::LeftHanded {
+            bindings.extend([
+                (MoveUp, Key(KeyCode::KeyW)),
+                (MoveDown, Key(KeyCode::KeyS)),
+                (MoveLeft, Key(KeyCode::KeyA)),
+                (MoveRight, Key(KeyCode::KeyD)),
+            ]);
+        }
+        // M4 and M5 are the thumb buttons, and the wheel reels one link per notch.
+        let mouse_reel = [
+            (ReelIn, Mouse(MouseButton::Back)),
+            (ReelIn, MouseWheel(WheelDirection::Up)),
+            (ReelOut, Mouse(MouseButton::Forward)),
+            (ReelOut, MouseWheel(WheelDirection::Down)),
+        ];
+        bindings.extend(match self {
+            Self::Standard | Self::Custom => vec![
+                (MoveUp, Key(KeyCode::ArrowUp)),
+                (MoveDown, Key(KeyCode::ArrowDown)),
+                (MoveLeft, Key(KeyCode::ArrowLeft)),
+                (MoveRight, Key(KeyCode::ArrowRight)),
+                (FireChain, Mouse(MouseButton::Left)),
+                (ReleaseChain, Mouse(MouseButton::Right)),
+                (Rappel, Key(KeyCode::ControlLeft)),
+                (Pulley, Key(KeyCode::KeyF)),
+                (Restart, Key(KeyCode::KeyR)),
+                (SlowMotion, Key(KeyCode::ShiftLeft)),
+                (Interact, Key(KeyCode::KeyE)),
+            ],
+            Self::LeftHanded => vec![
+                (MoveUp, Key(KeyCode::ArrowUp)),
+                (MoveUp, Key(KeyCode::KeyI)),
+                (MoveDown, Key(KeyCode::ArrowDown)),
+                (MoveDown, Key(KeyCode::KeyK)),
+                (MoveLeft, Key(KeyCode::ArrowLeft)),
+                (MoveLeft, Key(KeyCode::KeyJ)),
+                (MoveRight, Key(KeyCode::ArrowRight)),
+                (MoveRight, Key(KeyCode::KeyL)),
+                (FireChain, Mouse(MouseButton::Right)),
+                (ReleaseChain, Mouse(MouseButton::Left)),
+                (Rappel, Key(KeyCode::ControlRight)),
+                (Pulley, Key(KeyCode::KeyH)),
+                (Restart, Key(KeyCode::KeyR)),
+                (SlowMotion, Key(KeyCode::ShiftRight)),
+                (Interact, Key(KeyCode::KeyU)),
+            ],
+            Self::KeyboardOnly => vec![
+                (AimUp, Key(KeyCode::ArrowUp)),
+                (AimUp, Key(KeyCode::KeyI)),
+                (AimDown, Key(KeyCode::ArrowDown)),
+                (AimDown, Key(KeyCode::KeyK)),
+                (AimLeft, Key(KeyCode::ArrowLeft)),
+                (AimLeft, Key(KeyCode::KeyJ)),
+                (AimRight, Key(KeyCode::ArrowRight)),
+                (AimRight, Key(KeyCode::KeyL)),
+                (FineAim, Key(KeyCode::ShiftLeft)),
+                (FineAim, Key(KeyCode::ShiftRight)),
+                (FireChain, Key(KeyCode::Space)),
+                (ReleaseChain, Key(KeyCode::KeyE)),
+                (ReelIn, Key(KeyCode::KeyU)),
+                (ReelOut, Key(KeyCode::KeyO)),
+                (Rappel, Key(KeyCode::KeyR)),
+                (Pulley, Key(KeyCode::KeyY)),
+                (Restart, Key(KeyCode::Backspace)),
+                (SlowMotion, Key(KeyCode::KeyT)),
+                (Interact, Key(KeyCode::KeyF)),
+            ],
+        });
+        if self != Self::KeyboardOnly {
+            bindings.extend(mouse_reel);
+        }
+        // Tab and the number keys are free in every preset.
+        bindings.extend([
+            (SwapCharacter, Key(KeyCode::Tab)),
+            (Taunt1, Key(KeyCode::Digit1)),
+            (Taunt2, Key(KeyCode::Digit2)),
+            (Taunt3, Key(KeyCode::Digit3)),
+        ]);
+        // Gamepads work the same with every preset.
+        bindings.extend([
+            (MoveUp, Gamepad(GamepadButton::DPadUp)),
+            (MoveDown, Gamepad(GamepadButton::DPadDown)),
+            (MoveLeft, Gamepad(GamepadButton::DPadLeft)),
+            (MoveRight, Gamepad(GamepadButton::DPadRight)),
+            (FireChain, Gamepad(GamepadButton::RightTrigger2)),
+            (ReleaseChain, Gamepad(GamepadButton::LeftTrigger2)),
+            (ReelIn, Gamepad(GamepadButton::RightTrigger)),
+            (ReelOut, Gamepad(GamepadButton::LeftTrigger)),
+            (Rappel, Gamepad(GamepadButton::LeftThumb)),
+            (Pulley, Gamepad(GamepadButton::North)),
+            (Restart, Gamepad(GamepadButton::Select)),
+            (SlowMotion, Gamepad(GamepadButton::RightThumb)),
+            (Interact, Gamepad(GamepadButton::West)),
+            (SwapCharacter, Gamepad(GamepadButton::East)),
+        ]);
+        bindings
+    }
+}
+
+/// A button on some input device.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Counts as pressed for the one frame the wheel is scrolled.
+    MouseWheel(WheelDirection),
+    Gamepad(GamepadButton),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+pub enum WheelDirection {
+    Up,
+    Down,
+}
+
+impl Binding {
+    pub fn is_gamepad(self) -> bool {
+        matches!(self, Binding::Gamepad(_))
+    }
+
+    /// A short name for showing the binding on screen. Gamepad buttons are
+    /// named after the labels on `preset`.
+    pub fn label(self, preset: ControllerPreset) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                name.strip_prefix("Key").unwrap_or(&name).to_string()
+            }
+            Binding::Mouse(MouseButton::Left) => "LMB".to_string(),
+            Binding::Mouse(MouseButton::Right) => "RMB".to_string(),
+            Binding::Mouse(MouseButton::Middle) => "MMB".to_string(),
+            Binding::Mouse(MouseButton::Back) => "M4".to_string(),
+            Binding::Mouse(MouseButton::Forward) => "M5".to_string(),
+            Binding::Mouse(button) => format!("{button:?}"),
+            Binding::MouseWheel(WheelDirection::Up) => "Wheel Up".to_string(),
+            Binding::MouseWheel(WheelDirection::Down) => "Wheel Down".to_string(),
+            Binding::Gamepad(button) => preset.glyph(button).to_string(),
+        }
+    }
+}
+
+/// Which bindings trigger which actions. An action can have any number of
+/// bindings, and is pressed when any of them is.
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct InputMap {
+    /// The preset the bindings came from.
+    pub preset: InputPreset,
+    pub bindings: Vec<(InputAction, Binding)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::from_preset(InputPreset::default())
+    }
+}
+
+impl InputMap {
+    pub fn from_preset(preset: InputPreset) -> Self {
+        Self {
+            preset,
+            bindings: preset.bindings(),
+        }
+    }
+
+    pub fn bindings(&self, action: InputAction) -> impl Iterator<Item = Binding> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(bound, _)| *bound == action)
+            .map(|&(_, binding)| binding)
+    }
+
+    /// Bind `binding` to `action` as well. Returns the other actions that
+    /// already use it, which the player should probably rebind.
+    pub fn bind(&mut self, action: InputAction, binding: Binding) -> Vec<InputAction> {
+        self.preset = InputPreset::Custom;
+        if !self.bindings.contains(&(action, binding)) {
+            self.bindings.push((action, binding));
+        }
+        self.conflicts(binding)
+            .filter(|&other| other != action)
+            .collect()
+    }
+
+    /// Remove the most recently added binding of `action`.
+    pub fn unbind_last(&mut self, action: InputAction) {
+        let last = self
+            .bindings
+            .iter()
+            .rposition(|&(bound, _)| bound == action);
+        if let Some(i) = last {
+            self.preset = InputPreset::Custom;
+            self.bindings.remove(i);
+        }
+    }
+
+    /// Every action `binding` is bound to.
+    pub fn conflicts(&self, binding: Binding) -> impl Iterator<Item = InputAction> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |&&(_, bound)| bound == binding)
+            .map(|&(action, _)| action)
+    }
+
+    /// Whether any of `action`'s bindings also triggers another action.
+    pub fn has_conflict(&self, action: InputAction) -> bool {
+        self.bindings(action)
+            .any(|binding| self.conflicts(binding).any(|other| other != action))
+    }
+
+    /// How to show `action` in prompts: its first gamepad binding if the
+    /// player is using a controller, otherwise its first keyboard or mouse
+    /// binding.
+    pub fn prompt(&self, action: InputAction, controller: Option<ControllerPreset>) -> String {
+        let mut bindings = self.bindings(action);
+        let binding = match controller {
+            Some(_) => bindings.find(|binding| binding.is_gamepad()),
+            None => bindings.find(|binding| !binding.is_gamepad()),
+        };
+        binding.map_or("-".to_string(), |binding| {
+            binding.label(controller.unwrap_or_default())
+        })
+    }
+}
+
+/// Turns held actions into toggles, as an accessibility option.
+#[derive(Resource, Default, Debug)]
+pub struct HoldToggles {
+    pub enabled: bool,
+    /// The held actions currently switched on.
+    active: Vec<InputAction>,
+}
+
+/// Switch held actions on and off as their bindings are pressed. The mouse
+/// wheel is left alone, since each notch is already a single step.
+fn toggle_held_actions(map: Res<InputMap>, devices: DeviceInput, mut toggles: ResMut<HoldToggles>) {
+    if !toggles.enabled {
+        if !toggles.active.is_empty() {
+            toggles.active.clear();
+        }
+        return;
+    }
+    for action in InputAction::ALL
+        .into_iter()
+        .filter(|action| action.is_held())
+    {
+        let pressed = map
+            .bindings(action)
+            .filter(|binding| !matches!(binding, Binding::MouseWheel(_)))
+            .any(|binding| devices.just_pressed(binding));
+        if !pressed {
+            continue;
+        }
+        if let Some(i) = toggles.active.iter().position(|&active| active == action) {
+            toggles.active.remove(i);
+        } else {
+            toggles
+                .active
+                .retain(|&active| Some(active) != action.opposite());
+            toggles.active.push(action);
+        }
+    }
+}
+
+/// Nothing stays switched on once gameplay is over.
+fn release_held_actions(mut toggles: ResMut<HoldToggles>) {
+    toggles.active.clear();
+}
+
+/// Stick input smaller than this doesn't count as aiming.
+const STICK_AIM_THRESHOLD: f32 = 0.5;
+
+/// The state of every connected input device, by [`Binding`].
+#[derive(SystemParam)]
+pub struct DeviceInput<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    scroll: Res<'w, AccumulatedMouseScroll>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl DeviceInput<'_, '_> {
+    pub fn pressed(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys.pressed(key),
+            Binding::Mouse(button) => self.mouse.pressed(button),
+            Binding::MouseWheel(direction) => self.scrolled(direction),
+            Binding::Gamepad(button) => self.gamepads.iter().any(|pad| pad.pressed(button)),
+        }
+    }
+
+    pub fn just_pressed(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys.just_pressed(key),
+            Binding::Mouse(button) => self.mouse.just_pressed(button),
+            Binding::MouseWheel(direction) => self.scrolled(direction),
+            Binding::Gamepad(button) => self.gamepads.iter().any(|pad| pad.just_pressed(button)),
+        }
+    }
+
+    fn scrolled(&self, direction: WheelDirection) -> bool {
+        match direction {
+            WheelDirection::Up => self.scroll.delta.y > 0.0,
+            WheelDirection::Down => self.scroll.delta.y < 0.0,
+        }
+    }
+}
+
+/// The actions pressed this frame and the one before, set by a script
+/// instead of coming from the devices.
+#[derive(Resource, Default, Debug)]
+pub struct ScriptedInput {
+    pub pressed: Vec<InputAction>,
+    pub previous: Vec<InputAction>,
+}
+
+impl ScriptedInput {
+    /// Move on to the next frame, with `pressed` held down.
+    pub fn advance(&mut self, pressed: Vec<InputAction>) {
+        self.previous = std::mem::replace(&mut self.pressed, pressed);
+    }
+}
+
+/// Reads actions from every connected input device, or from the
+/// [`ScriptedInput`] if there is one.
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    map: Res<'w, InputMap>,
+    toggles: Res<'w, HoldToggles>,
+    devices: DeviceInput<'w, 's>,
+    scripted: Option<Res<'w, ScriptedInput>>,
+}
+
+impl ActionInput<'_, '_> {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        if let Some(scripted) = &self.scripted {
+            return scripted.pressed.contains(&action);
+        }
+        if self.toggles.enabled && action.is_held() {
+            return self.toggles.active.contains(&action)
+                || self.map.bindings(action).any(|binding| {
+                    matches!(binding, Binding::MouseWheel(_)) && self.devices.pressed(binding)
+                });
+        }
+        self.map
+            .bindings(action)
+            .any(|binding| self.devices.pressed(binding))
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        if let Some(scripted) = &self.scripted {
+            return scripted.pressed.contains(&action) && !scripted.previous.contains(&action);
+        }
+        self.map
+            .bindings(action)
+            .any(|binding| self.devices.just_pressed(binding))
+    }
+
+    /// 1 if only `positive` is pressed, -1 if only `negative` is, else 0.
+    pub fn axis(&self, positive: InputAction, negative: InputAction) -> f32 {
+        (self.pressed(positive) as i8 - self.pressed(negative) as i8) as f32
+    }
+
+    /// The direction the player wants to move in, at most one long. Digital
+    /// input is normalized so diagonals aren't faster, while analog sticks
+    /// keep their magnitude.
+    pub fn movement(&self) -> Vec2 {
+        let digital = Vec2::new(
+            self.axis(InputAction::MoveRight, InputAction::MoveLeft),
+            self.axis(InputAction::MoveUp, InputAction::MoveDown),
+        );
+        if digital != Vec2::ZERO || self.scripted.is_some() {
+            return digital.normalize_or_zero();
+        }
+        self.devices
+            .gamepads
+            .iter()
+            .map(|pad| pad.left_stick())
+            .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .unwrap_or_default()
+            .clamp_length_max(1.0)
+    }
+
+    /// Whether the player aims with the keyboard instead of the mouse.
+    pub fn aims_with_keys(&self) -> bool {
+        self.map.bindings.iter().any(|(action, _)| {
+            matches!(
+                action,
+                InputAction::AimUp
+                    | InputAction::AimDown
+                    | InputAction::AimLeft
+                    | InputAction::AimRight
+            )
+        })
+    }
+
+    /// The direction a gamepad's right stick is aiming in, if any.
+    pub fn stick_aim(&self) -> Option<Vec2> {
+        if self.scripted.is_some() {
+            return None;
+        }
+        self.devices
+            .gamepads
+            .iter()
+            .map(|pad| pad.right_stick())
+            .find(|stick| stick.length() >= STICK_AIM_THRESHOLD)
+            .map(Vec2::normalize)
+    }
+}
@@ -0,0 +1,347 @@
+//! Text entry that works with a gamepad as well as a keyboard.
+//!
+//! Anything that takes text reads it through [`TextInput`] instead of reading
+//! keyboard events, and sets [`OnScreenKeyboard::wanted`] while it does.
+//! When the player is on a gamepad, an on-screen keyboard then shows up at
+//! the bottom of the screen. The D-pad or left stick moves between keys,
+//! South types the focused key, East deletes, West types a space, North
+//! switches case and Start is Enter. Keys can also be clicked.
+
+use bevy::{
+    ecs::{spawn::SpawnIter, system::SystemParam},
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    input::gamepad::ActiveController,
+    theme::{interaction::InteractionPalette, palette::*},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<OnScreenKey>();
+    app.init_resource::<OnScreenKeyboard>();
+    app.register_type::<KeyboardRoot>();
+    app.register_type::<KeyboardButton>();
+    app.add_observer(click_keyboard_button);
+
+    app.add_systems(
+        Update,
+        (show_keyboard, navigate_keyboard, update_keyboard_buttons).chain(),
+    );
+}
+
+/// The character rows. A row of [`SPECIAL_KEYS`] goes below them.
+const ROWS: [&str; 4] = ["1234567890", "QWERTYUIOP", "ASDFGHJKL:", "ZXCVBNM.-_"];
+const SPECIAL_KEYS: [KeyboardKey; 4] = [
+    KeyboardKey::Shift,
+    KeyboardKey::Space,
+    KeyboardKey::Delete,
+    KeyboardKey::Done,
+];
+const KEY_SIZE: f32 = 48.0;
+const KEY_GAP: f32 = 6.0;
+/// How far the left stick has to be pushed to move the focus.
+const STICK_THRESHOLD: f32 = 0.6;
+const FOCUS_OUTLINE: f32 = 3.0;
+
+/// A key typed on the on-screen keyboard.
+#[derive(Event, Debug)]
+pub struct OnScreenKey(pub Key);
+
+/// Keys typed on the keyboard or the on-screen keyboard, in the order they
+/// were typed. Releases are left out.
+#[derive(SystemParam)]
+pub struct TextInput<'w, 's> {
+    keyboard_events: EventReader<'w, 's, KeyboardInput>,
+    on_screen_keys: EventReader<'w, 's, OnScreenKey>,
+}
+
+impl TextInput<'_, '_> {
+    pub fn read(&mut self) -> Vec<Key> {
+        self.keyboard_events
+            .read()
+            .filter(|event| event.state == ButtonState::Pressed)
+            .map(|event| event.logical_key.clone())
+            .chain(self.on_screen_keys.read().map(|key| key.0.clone()))
+            .collect()
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct OnScreenKeyboard {
+    /// Set while something is taking text. The keyboard only shows while the
+    /// player is on a gamepad.
+    pub wanted: bool,
+    /// Row and column of the focused key.
+    focus: (usize, usize),
+    uppercase: bool,
+    /// Whether the left stick was pushed last frame, so holding it only
+    /// moves one key.
+    stick_held: bool,
+}
+
+impl Default for OnScreenKeyboard {
+    fn default() -> Self {
+        Self {
+            wanted: false,
+            focus: (0, 0),
+            uppercase: true,
+            stick_held: false,
+        }
+    }
+}
+
+impl OnScreenKeyboard {
+    fn press(&mut self, key: KeyboardKey, keys: &mut EventWriter<OnScreenKey>) {
+        let key = match key {
+            KeyboardKey::Char(c) if self.uppercase => Key::Character(c.to_string().into()),
+            KeyboardKey::Char(c) => Key::Character(c.to_ascii_lowercase().to_string().into()),
+            KeyboardKey::Shift => {
+                self.uppercase = !self.uppercase;
+                return;
+            }
+            KeyboardKey::Space => Key::Space,
+            KeyboardKey::Delete => Key::Backspace,
+            KeyboardKey::Done => Key::Enter,
+        };
+        keys.write(OnScreenKey(key));
+    }
+
+    /// Move the focus, wrapping around the edges. Moving between rows of
+    /// different lengths keeps the focus at about the same place.
+    fn move_focus(&mut self, step: IVec2) {
+        let (row, column) = self.focus;
+        let rows = ROWS.len() + 1;
+        let new_row = (row as i32 - step.y).rem_euclid(rows as i32) as usize;
+        let column = if new_row == row {
+            column
+        } else {
+            column * row_length(new_row) / row_length(row)
+        };
+        let length = row_length(new_row) as i32;
+        self.focus = (
+            new_row,
+            (column as i32 + step.x).rem_euclid(length) as usize,
+        );
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+enum KeyboardKey {
+    Char(char),
+    Shift,
+    Space,
+    Delete,
+    Done,
+}
+
+impl KeyboardKey {
+    fn label(self, uppercase: bool) -> String {
+        match self {
+            Self::Char(c) if uppercase => c.to_string(),
+            Self::Char(c) => c.to_ascii_lowercase().to_string(),
+            Self::Shift if uppercase => "abc".to_string(),
+            Self::Shift => "ABC".to_string(),
+            Self::Space => "Space".to_string(),
+            Self::Delete => "Del".to_string(),
+            Self::Done => "Done".to_string(),
+        }
+    }
+}
+
+fn row_length(row: usize) -> usize {
+    ROWS.get(row).map_or(SPECIAL_KEYS.len(), |keys| keys.len())
+}
+
+fn key_at(row: usize, column: usize) -> KeyboardKey {
+    match ROWS.get(row) {
+        Some(keys) => KeyboardKey::Char(keys.as_bytes()[column] as char),
+        None => SPECIAL_KEYS[column],
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct KeyboardRoot;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct KeyboardButton {
+    row: usize,
+    column: usize,
+    key: KeyboardKey,
+}
+
+fn show_keyboard(
+    mut commands: Commands,
+    keyboard: Res<OnScreenKeyboard>,
+    active: Res<ActiveController>,
+    root_query: Query<Entity, With<KeyboardRoot>>,
+) {
+    let shown = keyboard.wanted && active.0.is_some();
+    match (shown, root_query.single()) {
+        (true, Err(_)) => {
+            commands.spawn(keyboard_root());
+        }
+        (false, Ok(root)) => commands.entity(root).despawn(),
+        _ => {}
+    }
+}
+
+fn keyboard_root() -> impl Bundle {
+    (
+        Name::new("On-Screen Keyboard"),
+        KeyboardRoot,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Px(40.0),
+            justify_self: JustifySelf::Center,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Px(KEY_GAP),
+            padding: UiRect::all(Px(12.0)),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        // Above every menu.
+        GlobalZIndex(4),
+        Children::spawn(SpawnIter((0..=ROWS.len()).map(keyboard_row))),
+    )
+}
+
+fn keyboard_row(row: usize) -> impl Bundle {
+    (
+        Name::new("Keyboard Row"),
+        Node {
+            column_gap: Px(KEY_GAP),
+            ..default()
+        },
+        Children::spawn(SpawnIter(
+            (0..row_length(row)).map(move |column| keyboard_button(row, column)),
+        )),
+    )
+}
+
+fn keyboard_button(row: usize, column: usize) -> impl Bundle {
+    let key = key_at(row, column);
+    let width = if matches!(key, KeyboardKey::Char(_)) {
+        KEY_SIZE
+    } else {
+        2.0 * KEY_SIZE + KEY_GAP
+    };
+    (
+        Name::new("Keyboard Button"),
+        KeyboardButton { row, column, key },
+        Button,
+        Node {
+            width: Px(width),
+            height: Px(KEY_SIZE),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(BUTTON_BACKGROUND),
+        InteractionPalette {
+            none: BUTTON_BACKGROUND,
+            hovered: BUTTON_HOVERED_BACKGROUND,
+            pressed: BUTTON_PRESSED_BACKGROUND,
+        },
+        Outline::default(),
+        Text(key.label(true)),
+        TextFont::from_font_size(24.0),
+        TextColor(BUTTON_TEXT),
+        TextLayout::new_with_justify(JustifyText::Center),
+    )
+}
+
+fn click_keyboard_button(
+    trigger: Trigger<Pointer<Click>>,
+    button_query: Query<&KeyboardButton>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+    mut keys: EventWriter<OnScreenKey>,
+) {
+    if let Ok(button) = button_query.get(trigger.target()) {
+        keyboard.focus = (button.row, button.column);
+        keyboard.press(button.key, &mut keys);
+    }
+}
+
+/// Move around and type with the active gamepad.
+fn navigate_keyboard(
+    active: Res<ActiveController>,
+    gamepad_query: Query<&Gamepad>,
+    root_query: Query<(), With<KeyboardRoot>>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+    mut keys: EventWriter<OnScreenKey>,
+) {
+    if root_query.is_empty() {
+        return;
+    }
+    let Some(gamepad) = active.0.and_then(|entity| gamepad_query.get(entity).ok()) else {
+        return;
+    };
+
+    let mut step = IVec2::ZERO;
+    for (button, direction) in [
+        (GamepadButton::DPadUp, IVec2::Y),
+        (GamepadButton::DPadDown, IVec2::NEG_Y),
+        (GamepadButton::DPadLeft, IVec2::NEG_X),
+        (GamepadButton::DPadRight, IVec2::X),
+    ] {
+        if gamepad.just_pressed(button) {
+            step += direction;
+        }
+    }
+    let stick = gamepad.left_stick();
+    let stick_pushed = stick.length() > STICK_THRESHOLD;
+    if stick_pushed && !keyboard.stick_held {
+        step += if stick.x.abs() > stick.y.abs() {
+            IVec2::new(stick.x.signum() as i32, 0)
+        } else {
+            IVec2::new(0, stick.y.signum() as i32)
+        };
+    }
+    if keyboard.stick_held != stick_pushed {
+        keyboard.stick_held = stick_pushed;
+    }
+    if step != IVec2::ZERO {
+        keyboard.move_focus(step);
+    }
+
+    let (row, column) = keyboard.focus;
+    for (button, key) in [
+        (GamepadButton::South, key_at(row, column)),
+        (GamepadButton::East, KeyboardKey::Delete),
+        (GamepadButton::West, KeyboardKey::Space),
+        (GamepadButton::North, KeyboardKey::Shift),
+        (GamepadButton::Start, KeyboardKey::Done),
+    ] {
+        if gamepad.just_pressed(button) {
+            keyboard.press(key, &mut keys);
+        }
+    }
+}
+
+/// Outline the focused key and show the current case.
+fn update_keyboard_buttons(
+    keyboard: Res<OnScreenKeyboard>,
+    mut button_query: Query<(Ref<KeyboardButton>, &mut Outline, &mut Text)>,
+) {
+    for (button, mut outline, mut text) in &mut button_query {
+        if !keyboard.is_changed() && !button.is_added() {
+            continue;
+        }
+        let focused = keyboard.focus == (button.row, button.column);
+        *outline = if focused {
+            Outline::new(Px(FOCUS_OUTLINE), Px(0.0), HEADER_TEXT)
+        } else {
+            Outline::default()
+        };
+        text.0 = button.key.label(keyboard.uppercase);
+    }
+}
@@ -0,0 +1,330 @@
+//! Hooked, as a library. [`AppPlugin`] is the whole game, as the `hooked`
+//! binary runs it, and [`AppConfig`] is the same with a say in which parts get
+//! added. Other binaries, examples and tests link against this
+//! instead of repeating the setup: [`GamePlugin`] is the gameplay on its own,
+//! [`game::prelude`] has the level format, and chains are fired with
+//! [`ChainPool::fire_chain`](game::chain::ChainPool::fire_chain).
+
+// Support configuring Bevy lints within code.
+#![cfg_attr(bevy_lint, feature(register_tool), register_tool(bevy))]
+
+pub mod asset_tracking;
+pub mod audio;
+#[cfg(feature = "dev")]
+pub mod dev_tools;
+#[cfg(feature = "dev")]
+pub mod editor;
+pub mod game;
+#[cfg(feature = "dev")]
+pub mod history;
+pub mod input;
+pub mod menus;
+#[cfg(not(target_family = "wasm"))]
+pub mod migrate_levels;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod packed;
+pub mod persistence;
+pub mod platform;
+pub mod render_scale;
+pub mod replay;
+pub mod replay_viewer;
+pub mod ron_asset;
+pub mod screens;
+pub mod theme;
+
+use std::time::Duration;
+
+use avian2d::prelude::*;
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    asset::AssetMetaCheck,
+    audio::{AudioPlugin, Volume},
+    prelude::*,
+    render::{
+        RenderPlugin,
+        settings::{WgpuFeatures, WgpuSettings},
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+
+pub use game::GamePlugin;
+
+/// The whole game, set up from the command line and environment with
+/// [`AppConfig::from_env`].
+pub struct AppPlugin;
+
+impl Plugin for AppPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(AppConfig::from_env());
+    }
+}
+
+/// Which groups of plugins make up the app. Flags that aren't compiled in
+/// (dev tools without the `dev` feature, networking without `net`) are
+/// ignored.
+///
+/// Each part can be turned on or off with a flag, e.g. `--no-audio` or
+/// `--net`, or an environment variable, e.g. `HOOKED_AUDIO=0`. Flags win over
+/// the environment.
+#[derive(Resource, Clone, Debug)]
+pub struct AppConfig {
+    /// Play sound. Without it, everything is still loaded and spawned, just
+    /// silent.
+    pub audio: bool,
+    /// The console, overlays and debug checks.
+    pub dev_tools: bool,
+    /// Online sessions, and the menu to join them.
+    pub net: bool,
+    /// Run without a window, e.g. for tests. Nothing is rendered unless
+    /// `offscreen` is on too.
+    pub headless: bool,
+    /// Keep rendering when headless, into images instead of a window.
+    pub offscreen: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            audio: true,
+            dev_tools: cfg!(feature = "dev"),
+            net: cfg!(feature = "net"),
+            headless: false,
+            offscreen: false,
+        }
+    }
+}
+
+impl AppConfig {
+    /// The default config, changed by environment variables and then by
+    /// command line flags.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        for (name, part) in [
+            ("HOOKED_AUDIO", &mut config.audio),
+            ("HOOKED_DEV_TOOLS", &mut config.dev_tools),
+            ("HOOKED_NET", &mut config.net),
+            ("HOOKED_HEADLESS", &mut config.headless),
+            ("HOOKED_OFFSCREEN", &mut config.offscreen),
+        ] {
+            if let Ok(value) = std::env::var(name) {
+                *part = !matches!(value.as_str(), "" | "0" | "false" | "off");
+            }
+        }
+        #[cfg(not(target_family = "wasm"))]
+        for arg in std::env::args().skip(1) {
+            match arg.as_str() {
+                "--audio" => config.audio = true,
+                "--no-audio" => config.audio = false,
+                "--dev-tools" => config.dev_tools = true,
+                "--no-dev-tools" => config.dev_tools = false,
+                "--net" => config.net = true,
+                "--no-net" => config.net = false,
+                "--headless" => config.headless = true,
+                "--offscreen" => config.offscreen = true,
+                _ => {}
+            }
+        }
+        config
+    }
+
+    pub fn audio(mut self, audio: bool) -> Self {
+        self.audio = audio;
+        self
+    }
+
+    pub fn dev_tools(mut self, dev_tools: bool) -> Self {
+        self.dev_tools = dev_tools;
+        self
+    }
+
+    pub fn net(mut self, net: bool) -> Self {
+        self.net = net;
+        self
+    }
+
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    pub fn offscreen(mut self, offscreen: bool) -> Self {
+        self.offscreen = offscreen;
+        self
+    }
+}
+
+impl Plugin for AppConfig {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clone());
+
+        // Add Bevy plugins.
+        let default_plugins = DefaultPlugins
+            .set(AssetPlugin {
+                // Wasm builds will check for meta files (that don't exist) if this isn't set.
+                // This causes errors and even panics on web build on itch.
+                // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            })
+            .set(WindowPlugin {
+                primary_window: Window {
+                    title: "Hooked".to_string(),
+                    fit_canvas_to_parent: true,
+                    ..default()
+                }
+                .into(),
+                ..default()
+            });
+        // Dev builds keep the latest log lines around for bug reports.
+        #[cfg(feature = "dev")]
+        let default_plugins = if self.dev_tools {
+            default_plugins.set(bevy::log::LogPlugin {
+                custom_layer: dev_tools::bug_report::recent_log_layer,
+                ..default()
+            })
+        } else {
+            default_plugins
+        };
+        let default_plugins = if self.audio {
+            default_plugins
+        } else {
+            default_plugins.set(AudioPlugin {
+                global_volume: GlobalVolume::new(Volume::SILENT),
+                ..default()
+            })
+        };
+        if self.headless {
+            let default_plugins = default_plugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .disable::<WinitPlugin>();
+            let default_plugins = if self.offscreen {
+                // GPU occlusion culling is only for 3D, and its shaders don't
+                // build for OpenGL, which software rendering goes through.
+                default_plugins.set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        disabled_features: Some(WgpuFeatures::MULTI_DRAW_INDIRECT),
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+            } else {
+                default_plugins.set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+            };
+            app.add_plugins((
+                default_plugins,
+                ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / 60.0)),
+            ));
+        } else {
+            app.add_plugins(default_plugins);
+        }
+
+        // Add Avian physics plugin with pixel-based length unit
+        app.add_plugins((
+            PhysicsPlugins::default()
+                .with_length_unit(100.0)
+                .with_collision_hooks::<game::chain::ChainCollisionHooks>(),
+            // Dev builds add `PhysicsDebugPlugin`, toggled with F5.
+        )); // 100 pixels = 1 meter
+
+        // Configure gravity
+        app.insert_resource(Gravity(GRAVITY));
+
+        // Add other plugins.
+        app.add_plugins((
+            asset_tracking::plugin,
+            #[cfg(feature = "dev")]
+            editor::plugin,
+            game::GamePlugin,
+            #[cfg(feature = "dev")]
+            history::plugin,
+            input::plugin,
+            menus::plugin,
+            persistence::plugin,
+            platform::plugin,
+            replay::plugin,
+            replay_viewer::plugin,
+            screens::plugin,
+            theme::plugin,
+        ));
+        // Audio only keeps volumes in line with the settings, so it's safe to
+        // leave out.
+        if self.audio {
+            app.add_plugins(audio::plugin);
+        }
+        #[cfg(feature = "dev")]
+        if self.dev_tools {
+            app.add_plugins(dev_tools::plugin);
+        }
+        #[cfg(feature = "net")]
+        if self.net {
+            app.add_plugins(net::plugin);
+        }
+        // Nothing is drawn without a window.
+        if !self.headless {
+            app.add_plugins(render_scale::plugin);
+        }
+
+        // Order new `AppSystems` variants by adding them here:
+        app.configure_sets(
+            Update,
+            (
+                AppSystems::TickTimers,
+                AppSystems::RecordInput,
+                AppSystems::Update,
+            )
+                .chain(),
+        );
+
+        // Set up the `Pause` state.
+        app.init_state::<Pause>();
+        app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
+
+        // Spawn the main camera.
+        app.add_systems(Startup, spawn_camera);
+    }
+}
+
+/// Standard gravity (9.8 m/s² * 100 pixels/meter).
+const GRAVITY: Vec2 = Vec2::new(0.0, -980.0);
+
+/// High-level groupings of systems for the app in the `Update` schedule.
+/// When adding a new variant, make sure to order it in the `configure_sets`
+/// call above.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum AppSystems {
+    /// Tick timers.
+    TickTimers,
+    /// Record player input.
+    RecordInput,
+    /// Do everything else (consider splitting this into further variants).
+    Update,
+}
+
+/// Whether or not the game is paused.
+#[derive(States, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[states(scoped_entities)]
+pub struct Pause(pub bool);
+
+/// A system set for systems that shouldn't run while the game is paused.
+#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PausableSystems;
+
+fn spawn_camera(mut commands: Commands) {
+    // `render_scale` points this at an offscreen image, and draws the UI with
+    // a camera of its own.
+    commands.spawn((Name::new("Camera"), Camera2d));
+}
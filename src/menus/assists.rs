@@ -0,0 +1,279 @@
+//! The assists menu, reached from the settings.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+
+use crate::{game::assists::Assists, menus::Menu, theme::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Assists), spawn_assists_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Assists).and(input_just_pressed(KeyCode::Escape))),
+    );
+
+    app.register_type::<HazardSpeedLabel>();
+    app.register_type::<AutoReelLabel>();
+    app.register_type::<ShowTheWayLabel>();
+    app.register_type::<ChainDamageLabel>();
+    app.add_systems(
+        Update,
+        (
+            update_hazard_speed_label,
+            update_auto_reel_label,
+            update_show_the_way_label,
+            update_chain_damage_label,
+        )
+            .run_if(in_state(Menu::Assists)),
+    );
+}
+
+fn spawn_assists_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Assists Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Assists),
+        children![
+            widget::header("Assists"),
+            assists_grid(),
+            widget::label("Runs with any assist on are marked as assisted."),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn assists_grid() -> impl Bundle {
+    (
+        Name::new("Assists Grid"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(10.0),
+            column_gap: Px(30.0),
+            grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+            ..default()
+        },
+        children![
+            (
+                widget::label("Hazard Speed"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            hazard_speed_widget(),
+            (
+                widget::label("Auto Reel"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            auto_reel_widget(),
+            (
+                widget::label("Show the Way"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            show_the_way_widget(),
+            (
+                widget::label("Chain Damage"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            chain_damage_widget(),
+        ],
+    )
+}
+
+fn hazard_speed_widget() -> impl Bundle {
+    (
+        Name::new("Hazard Speed Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", faster_hazards),
+            (
+                Name::new("Current Hazard Speed"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), HazardSpeedLabel)],
+            ),
+            widget::button_small(">", slower_hazards),
+        ],
+    )
+}
+
+fn step_hazard_speed(assists: &mut Assists, step: usize) {
+    let speeds = Assists::HAZARD_SPEEDS;
+    let i = speeds
+        .iter()
+        .position(|&speed| speed == assists.hazard_speed)
+        .unwrap_or(0);
+    assists.hazard_speed = speeds[(i + step) % speeds.len()];
+}
+
+fn faster_hazards(_: Trigger<Pointer<Click>>, mut assists: ResMut<Assists>) {
+    step_hazard_speed(&mut assists, Assists::HAZARD_SPEEDS.len() - 1);
+}
+
+fn slower_hazards(_: Trigger<Pointer<Click>>, mut assists: ResMut<Assists>) {
+    step_hazard_speed(&mut assists, 1);
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HazardSpeedLabel;
+
+fn update_hazard_speed_label(
+    assists: Res<Assists>,
+    mut label: Single<&mut Text, With<HazardSpeedLabel>>,
+) {
+    label.0 = format!("{:.0}%", assists.hazard_speed * 100.0);
+}
+
+fn auto_reel_widget() -> impl Bundle {
+    (
+        Name::new("Auto Reel Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_auto_reel),
+            (
+                Name::new("Current Auto Reel"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), AutoReelLabel)],
+            ),
+            widget::button_small(">", toggle_auto_reel),
+        ],
+    )
+}
+
+fn toggle_auto_reel(_: Trigger<Pointer<Click>>, mut assists: ResMut<Assists>) {
+    assists.auto_reel = !assists.auto_reel;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct AutoReelLabel;
+
+fn update_auto_reel_label(
+    assists: Res<Assists>,
+    mut label: Single<&mut Text, With<AutoReelLabel>>,
+) {
+    label.0 = if assists.auto_reel { "On" } else { "Off" }.to_string();
+}
+
+fn show_the_way_widget() -> impl Bundle {
+    (
+        Name::new("Show the Way Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_show_the_way),
+            (
+                Name::new("Current Show the Way"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ShowTheWayLabel)],
+            ),
+            widget::button_small(">", toggle_show_the_way),
+        ],
+    )
+}
+
+fn toggle_show_the_way(_: Trigger<Pointer<Click>>, mut assists: ResMut<Assists>) {
+    assists.show_the_way = !assists.show_the_way;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ShowTheWayLabel;
+
+fn update_show_the_way_label(
+    assists: Res<Assists>,
+    mut label: Single<&mut Text, With<ShowTheWayLabel>>,
+) {
+    label.0 = if assists.show_the_way { "On" } else { "Off" }.to_string();
+}
+
+fn chain_damage_widget() -> impl Bundle {
+    (
+        Name::new("Chain Damage Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", more_chain_damage),
+            (
+                Name::new("Current Chain Damage"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ChainDamageLabel)],
+            ),
+            widget::button_small(">", less_chain_damage),
+        ],
+    )
+}
+
+fn step_chain_damage(assists: &mut Assists, step: usize) {
+    let damages = Assists::CHAIN_DAMAGES;
+    let i = damages
+        .iter()
+        .position(|&damage| damage == assists.chain_damage)
+        .unwrap_or(0);
+    assists.chain_damage = damages[(i + step) % damages.len()];
+}
+
+fn more_chain_damage(_: Trigger<Pointer<Click>>, mut assists: ResMut<Assists>) {
+    step_chain_damage(&mut assists, Assists::CHAIN_DAMAGES.len() - 1);
+}
+
+fn less_chain_damage(_: Trigger<Pointer<Click>>, mut assists: ResMut<Assists>) {
+    step_chain_damage(&mut assists, 1);
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ChainDamageLabel;
+
+fn update_chain_damage_label(
+    assists: Res<Assists>,
+    mut label: Single<&mut Text, With<ChainDamageLabel>>,
+) {
+    label.0 = if assists.chain_damage > 0.0 {
+        format!("{:.0}%", assists.chain_damage * 100.0)
+    } else {
+        "Off".to_string()
+    };
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
@@ -0,0 +1,90 @@
+//! The cheats menu, only in dev builds. Reached from the pause menu.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+
+use crate::{game::cheats::Cheats, menus::Menu, theme::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CheatLabel>();
+
+    app.add_systems(OnEnter(Menu::Cheats), spawn_cheats_menu);
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(input_just_pressed(KeyCode::Escape)),
+            update_cheat_labels,
+        )
+            .run_if(in_state(Menu::Cheats)),
+    );
+}
+
+#[derive(Component, Reflect, Copy, Clone)]
+#[reflect(Component)]
+enum CheatLabel {
+    GodMode,
+    InfiniteHooks,
+}
+
+fn spawn_cheats_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Cheats Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Cheats),
+        children![
+            widget::header("Cheats"),
+            widget::label("Using cheats stops scores from being submitted."),
+            cheat_row(CheatLabel::GodMode),
+            cheat_row(CheatLabel::InfiniteHooks),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn cheat_row(cheat: CheatLabel) -> impl Bundle {
+    (
+        Name::new("Cheat Row"),
+        Node {
+            column_gap: Px(10.0),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            (
+                widget::label(""),
+                cheat,
+                Node {
+                    width: Px(300.0),
+                    ..default()
+                },
+            ),
+            widget::button_small(
+                "~",
+                move |_: Trigger<Pointer<Click>>, mut cheats: ResMut<Cheats>| {
+                    match cheat {
+                        CheatLabel::GodMode => cheats.god_mode = !cheats.god_mode,
+                        CheatLabel::InfiniteHooks => cheats.infinite_hooks = !cheats.infinite_hooks,
+                    }
+                    cheats.used = true;
+                }
+            ),
+        ],
+    )
+}
+
+fn update_cheat_labels(cheats: Res<Cheats>, mut label_query: Query<(&CheatLabel, &mut Text)>) {
+    for (&cheat, mut text) in &mut label_query {
+        let (name, on) = match cheat {
+            CheatLabel::GodMode => ("God Mode", cheats.god_mode),
+            CheatLabel::InfiniteHooks => ("Infinite Hooks", cheats.infinite_hooks),
+        };
+        text.0 = format!("{name}: {}", if on { "On" } else { "Off" });
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
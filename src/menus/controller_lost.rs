@@ -0,0 +1,96 @@
+//! The modal shown when the controller being played with disconnects.
+//!
+//! Losing the controller mid-run pauses the game behind this, the same way
+//! the pause menu does. It says whether a controller is connected again, and
+//! the game picks back up as soon as anything is pressed, on a controller,
+//! the keyboard or the mouse.
+
+use bevy::prelude::*;
+
+use crate::{
+    Pause,
+    game::victory::VictoryOrbit,
+    input::gamepad::ActiveControllerLost,
+    menus::Menu,
+    replay::replaying,
+    screens::{Screen, gameplay::spawn_pause_overlay},
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ReconnectLabel>();
+
+    app.add_systems(
+        Update,
+        (pause_game, spawn_pause_overlay, open_menu).run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Menu::None))
+                .and(not(replaying))
+                .and(not(resource_exists::<VictoryOrbit>))
+                .and(on_event::<ActiveControllerLost>),
+        ),
+    );
+    app.add_systems(OnEnter(Menu::ControllerLost), spawn_controller_lost_menu);
+    app.add_systems(
+        Update,
+        (update_reconnect_label, resume_on_input)
+            .chain()
+            .run_if(in_state(Menu::ControllerLost)),
+    );
+}
+
+fn pause_game(mut next_pause: ResMut<NextState<Pause>>) {
+    next_pause.set(Pause(true));
+}
+
+fn open_menu(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::ControllerLost);
+}
+
+/// Says whether there's a controller to pick up again.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ReconnectLabel;
+
+fn spawn_controller_lost_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Controller Lost Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::ControllerLost),
+        children![
+            widget::header("Controller disconnected"),
+            (widget::label(""), ReconnectLabel),
+        ],
+    ));
+}
+
+fn update_reconnect_label(
+    gamepad_query: Query<(), With<Gamepad>>,
+    mut label: Single<&mut Text, With<ReconnectLabel>>,
+) {
+    let text = if gamepad_query.is_empty() {
+        "Reconnect it, or press any key to carry on with the keyboard"
+    } else {
+        "Controller connected, press any button to carry on"
+    };
+    if label.0 != text {
+        label.0 = text.to_string();
+    }
+}
+
+/// Back to the game once the player presses anything.
+fn resume_on_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_query: Query<&Gamepad>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    let pressed = keys.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || gamepad_query
+            .iter()
+            .any(|gamepad| gamepad.get_just_pressed().next().is_some());
+    if pressed {
+        next_menu.set(Menu::None);
+    }
+}
@@ -0,0 +1,231 @@
+//! The controls menu, for rebinding keys, mouse buttons and gamepad buttons.
+//!
+//! Click `+` next to an action and press a key, mouse button or gamepad
+//! button, or scroll the wheel, to add a binding, or `-` to remove the last
+//! one. Bindings shared between actions are flagged, since both actions would
+//! trigger at once. Gamepad buttons are labeled for the controller in use.
+
+use bevy::{
+    ecs::spawn::SpawnIter,
+    input::{common_conditions::input_just_pressed, mouse::AccumulatedMouseScroll},
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    input::{
+        Binding, InputAction, InputMap, WheelDirection,
+        gamepad::{ActiveController, ControllerPreset, ControllerProfiles, active_preset},
+    },
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Rebinding>();
+    app.register_type::<BindingsLabel>();
+    app.register_type::<ControlsStatus>();
+
+    app.add_systems(OnEnter(Menu::Controls), spawn_controls_menu);
+    app.add_systems(OnExit(Menu::Controls), stop_rebinding);
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(rebinding_idle.and(input_just_pressed(KeyCode::Escape))),
+            capture_binding.run_if(not(rebinding_idle)),
+            update_controls_labels,
+        )
+            .chain()
+            .run_if(in_state(Menu::Controls)),
+    );
+}
+
+/// The action waiting for a new binding, and what happened last.
+#[derive(Resource, Default, Debug)]
+struct Rebinding {
+    action: Option<InputAction>,
+    message: String,
+}
+
+fn rebinding_idle(rebinding: Res<Rebinding>) -> bool {
+    rebinding.action.is_none()
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct BindingsLabel(InputAction);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ControlsStatus;
+
+fn spawn_controls_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Controls Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Controls),
+        children![
+            widget::header("Controls"),
+            (widget::label(""), ControlsStatus),
+            (
+                Name::new("Bindings"),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Px(4.0),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(InputAction::ALL.into_iter().map(bindings_row))),
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn bindings_row(action: InputAction) -> impl Bundle {
+    (
+        Name::new(format!("{} Row", action.label())),
+        Node {
+            column_gap: Px(10.0),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            (
+                widget::label(action.label()),
+                Node {
+                    width: Px(200.0),
+                    ..default()
+                },
+            ),
+            (
+                widget::label(""),
+                BindingsLabel(action),
+                Node {
+                    width: Px(420.0),
+                    ..default()
+                },
+            ),
+            widget::button_small(
+                "+",
+                move |_: Trigger<Pointer<Click>>, mut rebinding: ResMut<Rebinding>| {
+                    rebinding.action = Some(action);
+                    rebinding.message.clear();
+                }
+            ),
+            widget::button_small(
+                "-",
+                move |_: Trigger<Pointer<Click>>, mut input_map: ResMut<InputMap>| {
+                    input_map.unbind_last(action);
+                }
+            ),
+        ],
+    )
+}
+
+/// Bind whatever the player presses next to the waiting action.
+fn capture_binding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    scroll: Res<AccumulatedMouseScroll>,
+    gamepad_query: Query<&Gamepad>,
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.action = None;
+        return;
+    }
+
+    let wheel = if scroll.delta.y > 0.0 {
+        Some(WheelDirection::Up)
+    } else if scroll.delta.y < 0.0 {
+        Some(WheelDirection::Down)
+    } else {
+        None
+    };
+    let Some(binding) = keys
+        .get_just_pressed()
+        .next()
+        .map(|&key| Binding::Key(key))
+        .or_else(|| {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|&button| Binding::Mouse(button))
+        })
+        .or(wheel.map(Binding::MouseWheel))
+        .or_else(|| {
+            gamepad_query
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+                .map(|&button| Binding::Gamepad(button))
+        })
+    else {
+        return;
+    };
+
+    let conflicts = input_map.bind(action, binding);
+    rebinding.action = None;
+    rebinding.message = if conflicts.is_empty() {
+        String::new()
+    } else {
+        let others: Vec<_> = conflicts.iter().map(|other| other.label()).collect();
+        format!(
+            "Conflict: {} also triggers {}.",
+            binding.label(ControllerPreset::default()),
+            others.join(", ")
+        )
+    };
+}
+
+fn update_controls_labels(
+    input_map: Res<InputMap>,
+    rebinding: Res<Rebinding>,
+    active: Res<ActiveController>,
+    profiles: Res<ControllerProfiles>,
+    name_query: Query<&Name, With<Gamepad>>,
+    mut status: Single<&mut Text, (With<ControlsStatus>, Without<BindingsLabel>)>,
+    mut label_query: Query<(&BindingsLabel, &mut Text, &mut TextColor)>,
+) {
+    status.0 = match rebinding.action {
+        Some(action) => format!(
+            "Press a key or button for {} (Escape to cancel)",
+            action.label()
+        ),
+        None if !rebinding.message.is_empty() => rebinding.message.clone(),
+        None => format!("Preset: {}", input_map.preset.label()),
+    };
+
+    let preset = active_preset(&active, &profiles, &name_query).unwrap_or_default();
+    for (BindingsLabel(action), mut text, mut color) in &mut label_query {
+        let bindings: Vec<String> = input_map
+            .bindings(*action)
+            .map(|binding| binding.label(preset))
+            .collect();
+        text.0 = if bindings.is_empty() {
+            "-".to_string()
+        } else {
+            bindings.join(", ")
+        };
+        color.0 = if input_map.has_conflict(*action) {
+            ui_palette::CONFLICT_TEXT
+        } else {
+            ui_palette::LABEL_TEXT
+        };
+    }
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    *rebinding = Rebinding::default();
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
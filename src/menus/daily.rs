@@ -0,0 +1,91 @@
+//! The daily challenge menu, showing today's mutator combo before starting.
+
+use bevy::{
+    ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    game::{
+        daily::DailyChallenge,
+        game_rng::GameRng,
+        mutators::{RunMutators, RunSeed},
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Daily), spawn_daily_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Daily).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+fn spawn_daily_menu(mut commands: Commands) {
+    let challenge = DailyChallenge::today();
+    let mutators = challenge.mutators();
+
+    commands.spawn((
+        widget::ui_root("Daily Challenge Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Daily),
+        children![
+            widget::header(format!("Daily challenge {}", challenge.date())),
+            mutator_list(mutators),
+            widget::label(format!("Score x{:.2}", mutators.score_multiplier())),
+            widget::button("Start", start_daily_run),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn mutator_list(mutators: RunMutators) -> impl Bundle {
+    let labels: Vec<_> = if mutators == RunMutators::default() {
+        vec!["No mutators today"]
+    } else {
+        mutators.iter().map(|mutator| mutator.label()).collect()
+    };
+
+    (
+        Name::new("Mutator List"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Px(10.0),
+            ..default()
+        },
+        Children::spawn(SpawnIter(labels.into_iter().map(widget::label))),
+    )
+}
+
+fn start_daily_run(
+    _: Trigger<Pointer<Click>>,
+    resource_handles: Res<ResourceHandles>,
+    mut seed: ResMut<RunSeed>,
+    mut mutators: ResMut<RunMutators>,
+    mut rng: ResMut<GameRng>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    // Roll again on start in case the date changed while the menu was open.
+    let challenge = DailyChallenge::today();
+    seed.0 = challenge.seed();
+    *mutators = challenge.mutators();
+    rng.seed_next_level(seed.0);
+
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
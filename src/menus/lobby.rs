@@ -0,0 +1,274 @@
+//! The online lobby menu: host or join a game, then ready up and vote for a
+//! level. Only built with the `net` feature.
+//!
+//! Type to edit the focused field, Tab or Enter to switch between the name
+//! and address fields, and Up/Down to pick one of the games found on the LAN.
+//! On a gamepad, the fields are typed into with the on-screen keyboard.
+
+use bevy::{
+    input::{common_conditions::input_just_pressed, keyboard::Key},
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    input::text::{OnScreenKeyboard, TextInput},
+    menus::Menu,
+    net::{
+        NetMessage, NetSession, SendMessage,
+        lobby::{
+            ChosenLevel, DiscoveredGames, LOBBY_LEVELS, Lobby, LobbyStatus, host_game, join_game,
+            leave_game,
+        },
+        transport::{NetSocket, parse_address},
+    },
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LobbyForm>();
+    app.register_type::<LobbyText>();
+
+    app.add_systems(
+        OnEnter(Menu::Lobby),
+        (spawn_lobby_menu, want_keyboard(true)),
+    );
+    app.add_systems(OnExit(Menu::Lobby), want_keyboard(false));
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(input_just_pressed(KeyCode::Escape)),
+            (edit_form, update_lobby_text).chain(),
+        )
+            .run_if(in_state(Menu::Lobby)),
+    );
+}
+
+const MAX_NAME_LENGTH: usize = 16;
+/// Long enough for `255.255.255.255:65535`.
+const MAX_ADDRESS_LENGTH: usize = 21;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+enum Field {
+    #[default]
+    Name,
+    Address,
+}
+
+/// What the player has typed into the lobby menu.
+#[derive(Resource)]
+struct LobbyForm {
+    name: String,
+    address: String,
+    focus: Field,
+    /// Index into the discovered games.
+    selected_game: usize,
+}
+
+impl Default for LobbyForm {
+    fn default() -> Self {
+        Self {
+            name: "Player".to_string(),
+            address: String::new(),
+            focus: Field::Name,
+            selected_game: 0,
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct LobbyText;
+
+fn spawn_lobby_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Lobby Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Lobby),
+        children![
+            widget::header("Online"),
+            (widget::label(""), LobbyText),
+            button_row(children![
+                widget::button("Host", host),
+                widget::button("Join", join),
+            ]),
+            button_row(children![
+                widget::button("Ready", toggle_ready),
+                widget::button("Vote", cycle_vote),
+            ]),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn want_keyboard(wanted: bool) -> impl Fn(ResMut<OnScreenKeyboard>) {
+    move |mut keyboard: ResMut<OnScreenKeyboard>| keyboard.wanted = wanted
+}
+
+fn button_row(children: impl Bundle) -> impl Bundle {
+    (
+        Name::new("Button Row"),
+        Node {
+            column_gap: Px(20.0),
+            ..default()
+        },
+        children,
+    )
+}
+
+/// Type into the focused field and pick discovered games.
+fn edit_form(mut text_input: TextInput, games: Res<DiscoveredGames>, mut form: ResMut<LobbyForm>) {
+    for key in text_input.read() {
+        let (field, max_length) = match form.focus {
+            Field::Name => (&mut form.name, MAX_NAME_LENGTH),
+            Field::Address => (&mut form.address, MAX_ADDRESS_LENGTH),
+        };
+        match key {
+            Key::Character(text) if field.len() + text.len() <= max_length => {
+                field.push_str(&text);
+            }
+            Key::Backspace => {
+                field.pop();
+            }
+            Key::Tab | Key::Enter => {
+                form.focus = match form.focus {
+                    Field::Name => Field::Address,
+                    Field::Address => Field::Name,
+                };
+            }
+            Key::ArrowDown if !games.0.is_empty() => {
+                form.selected_game = (form.selected_game + 1) % games.0.len();
+            }
+            Key::ArrowUp if !games.0.is_empty() => {
+                form.selected_game = (form.selected_game + games.0.len() - 1) % games.0.len();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_lobby_text(
+    form: Res<LobbyForm>,
+    status: Res<LobbyStatus>,
+    games: Res<DiscoveredGames>,
+    session: Res<NetSession>,
+    lobby: Res<Lobby>,
+    chosen_level: Res<ChosenLevel>,
+    mut text: Single<&mut Text, With<LobbyText>>,
+) {
+    let cursor = |field| if form.focus == field { "_" } else { "" };
+    let mut lines = vec![
+        status.0.clone(),
+        format!("Name: {}{}", form.name, cursor(Field::Name)),
+        format!("Join address: {}{}", form.address, cursor(Field::Address)),
+        String::new(),
+    ];
+
+    if lobby.players.is_empty() {
+        lines.push("Games on the LAN (Up/Down to pick):".to_string());
+        if games.0.is_empty() {
+            lines.push("  None found yet".to_string());
+        }
+        for (i, game) in games.0.iter().enumerate() {
+            let marker = if i == form.selected_game { ">" } else { " " };
+            lines.push(format!(
+                "{marker} {}'s game - {} player(s) - {}",
+                game.name, game.players, game.address
+            ));
+        }
+    } else {
+        lines.push(format!("Players ({} hosting):", session.name(session.host)));
+        for player in &lobby.players {
+            lines.push(format!(
+                "  {} - {} - votes {}",
+                player.name,
+                if player.ready { "ready" } else { "not ready" },
+                player.vote.as_deref().unwrap_or("nothing"),
+            ));
+        }
+        if let Some(level) = &chosen_level.0 {
+            lines.push(format!("Starting {level}..."));
+        }
+    }
+    text.0 = lines.join("\n");
+}
+
+fn player_name(form: &LobbyForm) -> String {
+    let name = form.name.trim();
+    if name.is_empty() {
+        "Player".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn host(_: Trigger<Pointer<Click>>, mut commands: Commands, form: Res<LobbyForm>) {
+    let name = player_name(&form);
+    commands.queue(move |world: &mut World| host_game(world, &name));
+}
+
+/// Join the typed address, or the picked LAN game if no address was typed.
+fn join(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    form: Res<LobbyForm>,
+    games: Res<DiscoveredGames>,
+    mut status: ResMut<LobbyStatus>,
+) {
+    let address = if form.address.trim().is_empty() {
+        games.0.get(form.selected_game).map(|game| game.address)
+    } else {
+        parse_address(&form.address)
+    };
+    let Some(address) = address else {
+        status.0 = "Type an address like 192.168.1.20 or pick a game first.".to_string();
+        return;
+    };
+    let name = player_name(&form);
+    commands.queue(move |world: &mut World| join_game(world, address, &name));
+}
+
+fn toggle_ready(
+    _: Trigger<Pointer<Click>>,
+    session: Res<NetSession>,
+    lobby: Res<Lobby>,
+    mut sent: EventWriter<SendMessage>,
+) {
+    if let Some(player) = lobby.player(session.local) {
+        sent.write(SendMessage(NetMessage::Ready(!player.ready)));
+    }
+}
+
+fn cycle_vote(
+    _: Trigger<Pointer<Click>>,
+    session: Res<NetSession>,
+    lobby: Res<Lobby>,
+    mut sent: EventWriter<SendMessage>,
+) {
+    let Some(player) = lobby.player(session.local) else {
+        return;
+    };
+    let next = player
+        .vote
+        .as_deref()
+        .and_then(|vote| LOBBY_LEVELS.iter().position(|&level| level == vote))
+        .map_or(0, |i| (i + 1) % LOBBY_LEVELS.len());
+    sent.write(SendMessage(NetMessage::Vote(
+        LOBBY_LEVELS[next].to_string(),
+    )));
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.queue(leave_lobby);
+}
+
+fn go_back(mut commands: Commands) {
+    commands.queue(leave_lobby);
+}
+
+fn leave_lobby(world: &mut World) {
+    if world.contains_resource::<NetSocket>() {
+        leave_game(world);
+    }
+    world.resource_mut::<NextState<Menu>>().set(Menu::Main);
+}
@@ -2,38 +2,139 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::ResourceHandles, menus::Menu, screens::Screen, theme::widget};
+use crate::{
+    asset_tracking::ResourceHandles,
+    game::{
+        calibration::Calibration,
+        checkpoint::{PendingResume, read_autosave},
+        mutators::RunMutators,
+        sandbox::Sandbox,
+    },
+    menus::Menu,
+    persistence::Progress,
+    platform::ActivePlatform,
+    replay::{read_last_replay, start_playback},
+    screens::Screen,
+    theme::widget,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Main), spawn_main_menu);
 }
 
-fn spawn_main_menu(mut commands: Commands) {
-    commands.spawn((
+fn spawn_main_menu(
+    mut commands: Commands,
+    platform: Res<ActivePlatform>,
+    progress: Res<Progress>,
+    #[cfg(feature = "net")] config: Res<crate::AppConfig>,
+) {
+    let continue_button = read_autosave(&platform).is_some().then(|| {
+        commands
+            .spawn(widget::button("Continue", continue_run))
+            .id()
+    });
+    let replay_button = read_last_replay(&platform)
+        .is_some()
+        .then(|| commands.spawn(widget::button("Replay", watch_replay)).id());
+    let mut menu = commands.spawn((
         widget::ui_root("Main Menu"),
         GlobalZIndex(2),
         StateScoped(Menu::Main),
         #[cfg(not(target_family = "wasm"))]
         children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
+            widget::button("Play", open_run_setup_menu),
+            widget::button("Daily", open_daily_menu),
+            widget::button("Sandbox", enter_sandbox),
+            widget::button("Calibrate", enter_calibration),
+            widget::button("Wardrobe", open_wardrobe_menu),
             widget::button("Settings", open_settings_menu),
+            widget::button("What's New", open_whats_new_menu),
             widget::button("Credits", open_credits_menu),
             widget::button("Exit", exit_app),
         ],
         #[cfg(target_family = "wasm")]
         children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
+            widget::button("Play", open_run_setup_menu),
+            widget::button("Daily", open_daily_menu),
+            widget::button("Sandbox", enter_sandbox),
+            widget::button("Calibrate", enter_calibration),
+            widget::button("Wardrobe", open_wardrobe_menu),
             widget::button("Settings", open_settings_menu),
+            widget::button("What's New", open_whats_new_menu),
             widget::button("Credits", open_credits_menu),
         ],
     ));
+    // After Play and Daily.
+    if let Some(button) = replay_button {
+        menu.insert_children(2, &[button]);
+    }
+    if let Some(button) = continue_button {
+        menu.insert_children(0, &[button]);
+    }
+    #[cfg(feature = "net")]
+    if config.net {
+        menu.with_child(widget::button("Online", open_lobby_menu));
+    }
+    menu.with_child(widget::label(format!(
+        "{:.0}% complete",
+        progress.completion() * 100.0
+    )));
+    // Point first-time players at the calibration course.
+    if progress.calibration.is_none() && progress.levels.is_empty() {
+        menu.with_child(widget::label(
+            "New here? Calibrate to find the assists that suit you",
+        ));
+    }
+}
+
+/// Pick the autosaved run back up at its checkpoint.
+fn continue_run(
+    _: Trigger<Pointer<Click>>,
+    platform: Res<ActivePlatform>,
+    resource_handles: Res<ResourceHandles>,
+    mut mutators: ResMut<RunMutators>,
+    mut pending: ResMut<PendingResume>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(save) = read_autosave(&platform) else {
+        return;
+    };
+    *mutators = RunMutators::from_bits(save.mutators);
+    pending.0 = Some(save);
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+/// Play around with chains in an empty arena.
+fn enter_sandbox(
+    _: Trigger<Pointer<Click>>,
+    resource_handles: Res<ResourceHandles>,
+    mut sandbox: ResMut<Sandbox>,
+    mut mutators: ResMut<RunMutators>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    sandbox.active = true;
+    *mutators = RunMutators::default();
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
 }
 
-fn enter_loading_or_gameplay_screen(
+/// Find out how quickly and accurately the player hooks, to suggest assists.
+fn enter_calibration(
     _: Trigger<Pointer<Click>>,
     resource_handles: Res<ResourceHandles>,
+    mut calibration: ResMut<Calibration>,
+    mut mutators: ResMut<RunMutators>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
+    calibration.active = true;
+    *mutators = RunMutators::default();
     if resource_handles.is_all_done() {
         next_screen.set(Screen::Gameplay);
     } else {
@@ -41,10 +142,38 @@ fn enter_loading_or_gameplay_screen(
     }
 }
 
+/// Watch the last attempt at the level again.
+fn watch_replay(_: Trigger<Pointer<Click>>, mut commands: Commands, platform: Res<ActivePlatform>) {
+    if let Some(replay) = read_last_replay(&platform) {
+        commands.queue(move |world: &mut World| start_playback(world, replay));
+    }
+}
+
+fn open_run_setup_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::RunSetup);
+}
+
+fn open_daily_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Daily);
+}
+
+#[cfg(feature = "net")]
+fn open_lobby_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Lobby);
+}
+
 fn open_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Settings);
 }
 
+fn open_wardrobe_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Wardrobe);
+}
+
+fn open_whats_new_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::WhatsNew);
+}
+
 fn open_credits_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Credits);
 }
@@ -1,9 +1,22 @@
 //! The game's menus and transitions between them.
 
+mod assists;
+#[cfg(feature = "dev")]
+mod cheats;
+mod controller_lost;
+mod controls;
 mod credits;
+mod daily;
+#[cfg(feature = "net")]
+mod lobby;
 mod main;
 mod pause;
+mod retry;
+mod run_setup;
+mod run_summary;
 mod settings;
+mod wardrobe;
+mod whats_new;
 
 use bevy::prelude::*;
 
@@ -11,10 +24,23 @@ pub(super) fn plugin(app: &mut App) {
     app.init_state::<Menu>();
 
     app.add_plugins((
+        assists::plugin,
+        controller_lost::plugin,
+        #[cfg(feature = "dev")]
+        cheats::plugin,
+        controls::plugin,
         credits::plugin,
+        daily::plugin,
+        #[cfg(feature = "net")]
+        lobby::plugin,
         main::plugin,
         settings::plugin,
         pause::plugin,
+        retry::plugin,
+        run_setup::plugin,
+        run_summary::plugin,
+        wardrobe::plugin,
+        whats_new::plugin,
     ));
 }
 
@@ -26,5 +52,23 @@ pub enum Menu {
     Main,
     Credits,
     Settings,
+    Controls,
+    Assists,
     Pause,
+    RunSetup,
+    Daily,
+    RunSummary,
+    Wardrobe,
+    /// What changed in this version.
+    WhatsNew,
+    /// Shown when the player loses, to try the level again.
+    Retry,
+    /// Shown while the game waits for a disconnected controller.
+    ControllerLost,
+    /// Developer cheats, only in dev builds.
+    #[cfg(feature = "dev")]
+    Cheats,
+    /// Finding and setting up online games, only with the `net` feature.
+    #[cfg(feature = "net")]
+    Lobby,
 }
@@ -2,7 +2,11 @@
 
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
-use crate::{menus::Menu, screens::Screen, theme::widget};
+use crate::{
+    menus::Menu,
+    screens::{Screen, gameplay::restart_level},
+    theme::widget,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Pause), spawn_pause_menu);
@@ -13,17 +17,39 @@ pub(super) fn plugin(app: &mut App) {
 }
 
 fn spawn_pause_menu(mut commands: Commands) {
-    commands.spawn((
-        widget::ui_root("Pause Menu"),
-        GlobalZIndex(2),
-        StateScoped(Menu::Pause),
-        children![
-            widget::header("Game paused"),
-            widget::button("Continue", close_menu),
-            widget::button("Settings", open_settings_menu),
-            widget::button("Quit to title", quit_to_title),
-        ],
-    ));
+    // Only used for the dev-only cheats button.
+    let _menu = commands
+        .spawn((
+            widget::ui_root("Pause Menu"),
+            GlobalZIndex(2),
+            StateScoped(Menu::Pause),
+            children![
+                widget::header("Game paused"),
+                widget::button("Continue", close_menu),
+                widget::button("Restart", restart),
+                widget::button("Settings", open_settings_menu),
+                widget::button("End run", end_run),
+                widget::button("Quit to title", quit_to_title),
+            ],
+        ))
+        .id();
+    #[cfg(feature = "dev")]
+    commands
+        .entity(_menu)
+        .with_child(widget::button("Cheats", open_cheats_menu));
+}
+
+#[cfg(feature = "dev")]
+fn open_cheats_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Cheats);
+}
+
+fn restart(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.queue(restart_level);
+}
+
+fn end_run(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::RunSummary);
 }
 
 fn open_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
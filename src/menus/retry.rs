@@ -0,0 +1,89 @@
+//! The retry menu, shown when the player falls, gets caught in a hazard or
+//! runs out of time.
+//!
+//! Under what went wrong, a short timeline from the [`GameplayLog`] shows the
+//! last few things that happened leading up to it.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        checkpoint::{PendingResume, ReachedCheckpoint, read_autosave},
+        event_log::GameplayLog,
+        objectives::Defeat,
+        run_stats::RunStats,
+    },
+    menus::Menu,
+    platform::ActivePlatform,
+    screens::{Screen, gameplay::restart_level},
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Retry), spawn_retry_menu);
+}
+
+/// How far back the recap goes, in seconds.
+const RECAP_SECONDS: f32 = 10.0;
+const MAX_RECAP_LINES: usize = 6;
+
+fn spawn_retry_menu(
+    mut commands: Commands,
+    defeat: Res<Defeat>,
+    reached: Res<ReachedCheckpoint>,
+    log: Res<GameplayLog>,
+    stats: Res<RunStats>,
+) {
+    let menu = commands
+        .spawn((
+            widget::ui_root("Retry Menu"),
+            GlobalZIndex(2),
+            StateScoped(Menu::Retry),
+            children![widget::header(
+                defeat.0.map_or("Try again", |cause| cause.message())
+            )],
+        ))
+        .id();
+    let recap: Vec<_> = log
+        .0
+        .iter()
+        .rev()
+        .take_while(|entry| stats.time - entry.time <= RECAP_SECONDS)
+        .take(MAX_RECAP_LINES)
+        .collect();
+    for entry in recap.into_iter().rev() {
+        commands.entity(menu).with_child(widget::label(format!(
+            "{:>5.1}s  {}",
+            entry.time - stats.time,
+            entry.event.describe()
+        )));
+    }
+    if reached.0.is_some() {
+        commands.entity(menu).with_child(widget::button(
+            "Retry from checkpoint",
+            retry_from_checkpoint,
+        ));
+    }
+    commands.entity(menu).with_children(|menu| {
+        menu.spawn(widget::button("Restart level", restart));
+        menu.spawn(widget::button("Quit to title", quit_to_title));
+    });
+}
+
+fn retry_from_checkpoint(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    platform: Res<ActivePlatform>,
+    mut pending: ResMut<PendingResume>,
+) {
+    pending.0 = read_autosave(&platform);
+    commands.queue(restart_level);
+}
+
+fn restart(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.queue(restart_level);
+}
+
+fn quit_to_title(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -0,0 +1,338 @@
+//! The run setup menu, where mutators are picked before starting a run.
+
+use bevy::{
+    ecs::spawn::SpawnIter,
+    input::{common_conditions::input_just_pressed, keyboard::Key},
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    game::{
+        game_rng::GameRng,
+        level::MAIN_LEVEL_PATH,
+        mutators::{Mutator, RunMutators, RunSeed},
+        run_code,
+    },
+    input::text::{OnScreenKeyboard, TextInput},
+    menus::Menu,
+    persistence::{LevelProgress, Progress},
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CodeEntry>();
+    app.add_systems(OnEnter(Menu::RunSetup), spawn_run_setup_menu);
+    app.add_systems(OnExit(Menu::RunSetup), stop_code_entry);
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(not(entering_code).and(input_just_pressed(KeyCode::Escape))),
+            type_run_code.run_if(entering_code),
+        )
+            .chain()
+            .run_if(in_state(Menu::RunSetup)),
+    );
+
+    app.register_type::<MutatorStatusLabel>();
+    app.register_type::<RunSummaryLabel>();
+    app.register_type::<RunCodeLabel>();
+    app.add_systems(
+        Update,
+        (
+            update_mutator_status_labels,
+            update_run_summary_label,
+            update_run_code_label,
+        )
+            .run_if(in_state(Menu::RunSetup)),
+    );
+}
+
+/// The longest text a run code can be typed as, dashes included.
+const MAX_CODE_TEXT: usize = 19;
+
+/// A run code being typed in.
+#[derive(Resource, Default, Debug)]
+struct CodeEntry {
+    typing: bool,
+    text: String,
+    invalid: bool,
+}
+
+fn entering_code(entry: Res<CodeEntry>) -> bool {
+    entry.typing
+}
+
+fn spawn_run_setup_menu(mut commands: Commands, progress: Res<Progress>) {
+    let completion = progress
+        .levels
+        .get(MAIN_LEVEL_PATH)
+        .map_or(0.0, LevelProgress::completion);
+    commands.spawn((
+        widget::ui_root("Run Setup Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::RunSetup),
+        children![
+            widget::label(format!("Level {:.0}% complete", completion * 100.0)),
+            widget::header("Mutators"),
+            mutator_grid(),
+            (widget::label(""), RunSummaryLabel),
+            (widget::label(""), RunCodeLabel),
+            widget::button("Chaos", roll_chaos_mutators),
+            widget::button("Enter code", start_code_entry),
+            widget::button("Start", start_run),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn mutator_grid() -> impl Bundle {
+    (
+        Name::new("Mutator Grid"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Px(10.0),
+            ..default()
+        },
+        Children::spawn(SpawnIter(Mutator::ALL.into_iter().map(mutator_row))),
+    )
+}
+
+fn mutator_row(mutator: Mutator) -> impl Bundle {
+    (
+        Name::new("Mutator Row"),
+        Node {
+            display: Display::Grid,
+            column_gap: Px(30.0),
+            grid_template_columns: vec![
+                GridTrack::px(300.0),
+                GridTrack::auto(),
+                GridTrack::px(60.0),
+            ],
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            (
+                widget::label(mutator.label()),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            widget::button_small(
+                "~",
+                move |_: Trigger<Pointer<Click>>, mut mutators: ResMut<RunMutators>| {
+                    mutators.toggle(mutator);
+                },
+            ),
+            (widget::label(""), MutatorStatusLabel(mutator)),
+        ],
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct MutatorStatusLabel(Mutator);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct RunSummaryLabel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct RunCodeLabel;
+
+fn update_mutator_status_labels(
+    mutators: Res<RunMutators>,
+    mut label_query: Query<(&MutatorStatusLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut label_query {
+        text.0 = if mutators.contains(label.0) {
+            "On"
+        } else {
+            "Off"
+        }
+        .to_string();
+    }
+}
+
+fn update_run_summary_label(
+    mutators: Res<RunMutators>,
+    seed: Res<RunSeed>,
+    mut label: Single<&mut Text, With<RunSummaryLabel>>,
+) {
+    label.0 = format!(
+        "Seed {:016x} - score x{:.2}",
+        seed.0,
+        mutators.score_multiplier()
+    );
+}
+
+fn update_run_code_label(
+    mutators: Res<RunMutators>,
+    seed: Res<RunSeed>,
+    entry: Res<CodeEntry>,
+    mut label: Single<&mut Text, With<RunCodeLabel>>,
+) {
+    label.0 = if entry.typing {
+        format!(
+            "Type a code: {}_ (Enter to play it, Escape to cancel)",
+            entry.text
+        )
+    } else {
+        let code = run_code::encode(*seed, *mutators);
+        if entry.invalid {
+            format!("That's not a valid run code. Run code {code}")
+        } else {
+            format!("Run code {code}")
+        }
+    };
+}
+
+fn start_code_entry(
+    _: Trigger<Pointer<Click>>,
+    mut entry: ResMut<CodeEntry>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+) {
+    *entry = CodeEntry {
+        typing: true,
+        ..default()
+    };
+    keyboard.wanted = true;
+}
+
+fn stop_code_entry(mut entry: ResMut<CodeEntry>, mut keyboard: ResMut<OnScreenKeyboard>) {
+    *entry = CodeEntry::default();
+    keyboard.wanted = false;
+}
+
+fn type_run_code(
+    mut text_input: TextInput,
+    mut entry: ResMut<CodeEntry>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+    mut seed: ResMut<RunSeed>,
+    mut mutators: ResMut<RunMutators>,
+) {
+    for key in text_input.read() {
+        match key {
+            Key::Character(text) if entry.text.len() < MAX_CODE_TEXT => {
+                entry.text.push_str(&text);
+            }
+            Key::Backspace => {
+                entry.text.pop();
+            }
+            Key::Escape => {
+                entry.typing = false;
+                keyboard.wanted = false;
+            }
+            Key::Enter => {
+                entry.typing = false;
+                keyboard.wanted = false;
+                match run_code::decode(&entry.text) {
+                    Some((code_seed, code_mutators)) => {
+                        *seed = code_seed;
+                        *mutators = code_mutators;
+                    }
+                    None => entry.invalid = true,
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn roll_chaos_mutators(
+    _: Trigger<Pointer<Click>>,
+    mut seed: ResMut<RunSeed>,
+    mut mutators: ResMut<RunMutators>,
+) {
+    seed.0 = rand::random();
+    *mutators = RunMutators::from_seed(seed.0);
+}
+
+fn start_run(
+    _: Trigger<Pointer<Click>>,
+    resource_handles: Res<ResourceHandles>,
+    seed: Res<RunSeed>,
+    mut rng: ResMut<GameRng>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    // The same run code should play out the same, not just roll the same
+    // mutators.
+    rng.seed_next_level(seed.0);
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        picking::{
+            backend::HitData,
+            pointer::{Location, PointerId},
+        },
+        render::camera::{ManualTextureViewHandle, NormalizedRenderTarget},
+    };
+    use rand::Rng;
+
+    use super::*;
+    use crate::game::game_rng::{self, RngStream};
+
+    fn click() -> Pointer<Click> {
+        Pointer::new(
+            PointerId::Mouse,
+            Location {
+                target: NormalizedRenderTarget::TextureView(ManualTextureViewHandle(0)),
+                position: Vec2::ZERO,
+            },
+            Entity::PLACEHOLDER,
+            Click {
+                button: PointerButton::Primary,
+                hit: HitData::new(Entity::PLACEHOLDER, 0.0, None, None),
+                duration: Duration::ZERO,
+            },
+        )
+    }
+
+    /// The first numbers drawn in the first level of the run `code`.
+    fn first_draws(code: &str) -> Vec<u64> {
+        let mut world = World::new();
+        world.init_resource::<GameRng>();
+        world.init_resource::<ResourceHandles>();
+        world.init_resource::<NextState<Screen>>();
+        let (seed, mutators) = run_code::decode(code).unwrap();
+        world.insert_resource(seed);
+        world.insert_resource(mutators);
+
+        world.add_observer(start_run);
+        world.trigger(click());
+        world.run_system_once(game_rng::reseed).unwrap();
+
+        let mut stream = RngStream::default();
+        let rng = stream.get(world.resource::<GameRng>(), "test");
+        (0..4).map(|_| rng.random()).collect()
+    }
+
+    #[test]
+    fn same_run_code_plays_out_the_same() {
+        let code = run_code::encode(RunSeed(0x0123_4567_89ab_cdef), RunMutators::default());
+        assert_eq!(first_draws(&code), first_draws(&code));
+    }
+}
@@ -0,0 +1,155 @@
+//! The run summary, shown when a run is ended from the pause menu.
+//!
+//! The summary can be saved as a PNG card for sharing: a screenshot of the
+//! summary itself, taken with the buttons hidden.
+
+use bevy::{
+    ecs::spawn::SpawnIter,
+    prelude::*,
+    render::view::screenshot::{Screenshot, ScreenshotCaptured, save_to_disk},
+    ui::Val::*,
+};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    game::{
+        mutators::{RunMutators, RunSeed},
+        run_code,
+        run_stats::{RunStats, SAMPLE_INTERVAL, format_time},
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SummaryButtons>();
+
+    app.add_systems(OnEnter(Menu::RunSummary), spawn_run_summary);
+}
+
+/// Height of the tallest bar in the hooks graph.
+const GRAPH_HEIGHT: f32 = 120.0;
+const GRAPH_WIDTH: f32 = 480.0;
+const GRAPH_COLOR: Color = Color::srgb(0.384, 0.600, 0.820);
+
+/// Hidden while saving the card.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SummaryButtons;
+
+fn spawn_run_summary(
+    mut commands: Commands,
+    stats: Res<RunStats>,
+    seed: Res<RunSeed>,
+    mutators: Res<RunMutators>,
+) {
+    commands.spawn((
+        widget::ui_root("Run Summary"),
+        GlobalZIndex(2),
+        StateScoped(Menu::RunSummary),
+        children![
+            widget::header("Run summary"),
+            widget::label(format!("Time {}", format_time(stats.time))),
+            widget::label(format!("Hooks fired {}", stats.hooks_fired)),
+            widget::label(format!("Longest chain {:.0} px", stats.longest_chain)),
+            widget::label(format!(
+                "Seed {:016x} - score x{:.2}",
+                seed.0,
+                mutators.score_multiplier()
+            )),
+            widget::label(format!("Run code {}", run_code::encode(*seed, *mutators))),
+            hooks_graph(&stats),
+            (
+                Name::new("Summary Buttons"),
+                SummaryButtons,
+                Node {
+                    column_gap: Px(20.0),
+                    ..default()
+                },
+                children![
+                    widget::button("Save card", save_card),
+                    widget::button("Quit to title", quit_to_title),
+                ],
+            ),
+        ],
+    ));
+}
+
+/// Hooks fired over time, one bar per sample.
+fn hooks_graph(stats: &RunStats) -> impl Bundle {
+    let most = stats
+        .hook_samples
+        .last()
+        .copied()
+        .unwrap_or_default()
+        .max(1);
+    let bar_width = GRAPH_WIDTH / stats.hook_samples.len().max(1) as f32;
+    let bars: Vec<_> = stats
+        .hook_samples
+        .iter()
+        .map(|&hooks| {
+            (
+                Name::new("Graph Bar"),
+                Node {
+                    width: Px(bar_width),
+                    height: Px(GRAPH_HEIGHT * hooks as f32 / most as f32),
+                    ..default()
+                },
+                BackgroundColor(GRAPH_COLOR),
+            )
+        })
+        .collect();
+
+    (
+        Name::new("Hooks Graph"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Px(4.0),
+            ..default()
+        },
+        children![
+            (
+                Name::new("Graph Bars"),
+                Node {
+                    width: Px(GRAPH_WIDTH),
+                    height: Px(GRAPH_HEIGHT),
+                    align_items: AlignItems::End,
+                    ..default()
+                },
+                Children::spawn(SpawnIter(bars.into_iter())),
+            ),
+            widget::label(format!("Hooks fired, every {SAMPLE_INTERVAL:.0} seconds")),
+        ],
+    )
+}
+
+fn save_card(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut buttons: Single<&mut Visibility, With<SummaryButtons>>,
+) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    **buttons = Visibility::Hidden;
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(format!("run-summary-{secs}.png")))
+        .observe(show_buttons);
+}
+
+fn show_buttons(
+    _: Trigger<ScreenshotCaptured>,
+    mut buttons: Query<&mut Visibility, With<SummaryButtons>>,
+) {
+    for mut visibility in &mut buttons {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn quit_to_title(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -2,9 +2,25 @@
 //!
 //! Additional settings and accessibility options should go here.
 
-use bevy::{audio::Volume, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
 
-use crate::{menus::Menu, screens::Screen, theme::prelude::*};
+use crate::{
+    game::{
+        aim::AimAssist, hints::HintSettings, performance::FRAME_CAPS,
+        physics_quality::PhysicsQuality,
+    },
+    input::{
+        HoldToggles, InputAction, InputMap, InputPreset,
+        gamepad::{
+            ActiveController, ControllerPreset, ControllerProfile, ControllerProfiles,
+            active_preset,
+        },
+    },
+    menus::Menu,
+    persistence::Settings,
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
@@ -13,10 +29,47 @@ pub(super) fn plugin(app: &mut App) {
         go_back.run_if(in_state(Menu::Settings).and(input_just_pressed(KeyCode::Escape))),
     );
 
-    app.register_type::<GlobalVolumeLabel>();
+    app.register_type::<VolumeLabel>();
+    app.register_type::<ScreenShakeLabel>();
+    app.register_type::<ScreenNudgeLabel>();
+    app.register_type::<FeedbackIntensityLabel>();
+    app.register_type::<LetterboxLabel>();
+    app.register_type::<SafeAreaLabel>();
+    app.register_type::<PhysicsQualityLabel>();
+    app.register_type::<HandheldLabel>();
+    app.register_type::<ParticlesLabel>();
+    app.register_type::<FrameCapLabel>();
+    app.register_type::<DynamicResolutionLabel>();
+    app.register_type::<InputPresetLabel>();
+    app.register_type::<AimAssistLabel>();
+    app.register_type::<HintsLabel>();
+    app.register_type::<HookGhostsLabel>();
+    app.register_type::<PartnerHookingLabel>();
+    app.register_type::<HoldTogglesLabel>();
+    app.register_type::<ControllerLabel>();
+    app.register_type::<DeadzoneLabel>();
+    app.register_type::<ChainControlsLabel>();
     app.add_systems(
         Update,
-        update_global_volume_label.run_if(in_state(Menu::Settings)),
+        (
+            update_volume_labels,
+            update_screen_shake_label,
+            update_screen_nudge_label,
+            update_feedback_intensity_label,
+            update_letterbox_label,
+            update_safe_area_label,
+            update_physics_quality_label,
+            update_performance_labels,
+            update_dynamic_resolution_label,
+            update_input_labels,
+            update_hints_label,
+            update_hook_ghosts_label,
+            update_partner_hooking_label,
+            update_hold_toggles_label,
+            update_controller_labels,
+            update_chain_controls_label,
+        )
+            .run_if(in_state(Menu::Settings)),
     );
 }
 
@@ -28,6 +81,8 @@ fn spawn_settings_menu(mut commands: Commands) {
         children![
             widget::header("Settings"),
             settings_grid(),
+            widget::button("Controls", open_controls_menu),
+            widget::button("Assists", open_assists_menu),
             widget::button("Back", go_back_on_click),
         ],
     ));
@@ -43,65 +98,997 @@ fn settings_grid() -> impl Bundle {
             grid_template_columns: RepeatedGridTrack::px(2, 400.0),
             ..default()
         },
+        // Grouped, since a list of children can only be so long.
+        Children::spawn((
+            (
+                setting_row("Master Volume", volume_widget(VolumeChannel::Master)),
+                setting_row("Music Volume", volume_widget(VolumeChannel::Music)),
+                setting_row("Effects Volume", volume_widget(VolumeChannel::Effects)),
+                setting_row("Screen Shake", screen_shake_widget()),
+                setting_row("Screen Nudge", screen_nudge_widget()),
+                setting_row("Feedback Intensity", feedback_intensity_widget()),
+                setting_row("Letterbox", letterbox_widget()),
+                setting_row("Safe Area", safe_area_widget()),
+            ),
+            (
+                setting_row("Physics Quality", physics_quality_widget()),
+                setting_row("Handheld Mode", handheld_widget()),
+                setting_row("Particles", particles_widget()),
+                setting_row("Frame Cap", frame_cap_widget()),
+                setting_row("Dynamic Resolution", dynamic_resolution_widget()),
+            ),
+            (
+                setting_row("Input Preset", input_preset_widget()),
+                setting_row("Aim Assist", aim_assist_widget()),
+                setting_row("Hints", hints_widget()),
+                setting_row("Hook Ghosts", hook_ghosts_widget()),
+                setting_row("Partner Hooking", partner_hooking_widget()),
+                setting_row("Held Actions", hold_toggles_widget()),
+                setting_row("Controller", controller_preset_widget()),
+                setting_row("Stick Deadzone", deadzone_widget()),
+                setting_row(
+                    "Fire / Release",
+                    (
+                        widget::label(""),
+                        ChainControlsLabel,
+                        Node {
+                            justify_self: JustifySelf::Start,
+                            ..default()
+                        },
+                    ),
+                ),
+            ),
+        )),
+    )
+}
+
+/// A setting's name in the left column of the grid, and its widget in the
+/// right.
+fn setting_row<B: Bundle>(label: &'static str, control: B) -> (Spawn<impl Bundle>, Spawn<B>) {
+    (
+        Spawn((
+            widget::label(label),
+            Node {
+                justify_self: JustifySelf::End,
+                ..default()
+            },
+        )),
+        Spawn(control),
+    )
+}
+
+fn volume_widget(channel: VolumeChannel) -> impl Bundle {
+    (
+        Name::new(format!("{channel:?} Volume Widget")),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
         children![
+            widget::button_small(
+                "-",
+                move |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    let volume = channel.volume_mut(&mut settings);
+                    *volume = (*volume - 0.1).max(MIN_VOLUME);
+                }
+            ),
             (
-                widget::label("Master Volume"),
+                Name::new("Current Volume"),
                 Node {
-                    justify_self: JustifySelf::End,
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
                     ..default()
+                },
+                children![(widget::label(""), VolumeLabel(channel))],
+            ),
+            widget::button_small(
+                "+",
+                move |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    let volume = channel.volume_mut(&mut settings);
+                    *volume = (*volume + 0.1).min(MAX_VOLUME);
                 }
             ),
-            global_volume_widget(),
         ],
     )
 }
 
-fn global_volume_widget() -> impl Bundle {
+const MIN_VOLUME: f32 = 0.0;
+const MAX_VOLUME: f32 = 3.0;
+
+/// Which of the [`Settings`] volumes a widget changes.
+#[derive(Copy, Clone, Debug, Reflect)]
+enum VolumeChannel {
+    Master,
+    Music,
+    Effects,
+}
+
+impl VolumeChannel {
+    fn volume(self, settings: &Settings) -> f32 {
+        match self {
+            Self::Master => settings.master_volume,
+            Self::Music => settings.music_volume,
+            Self::Effects => settings.sfx_volume,
+        }
+    }
+
+    fn volume_mut(self, settings: &mut Settings) -> &mut f32 {
+        match self {
+            Self::Master => &mut settings.master_volume,
+            Self::Music => &mut settings.music_volume,
+            Self::Effects => &mut settings.sfx_volume,
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct VolumeLabel(VolumeChannel);
+
+fn update_volume_labels(
+    settings: Res<Settings>,
+    mut label_query: Query<(&VolumeLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut label_query {
+        let percent = 100.0 * label.0.volume(&settings);
+        text.0 = format!("{percent:3.0}%");
+    }
+}
+
+fn screen_shake_widget() -> impl Bundle {
     (
-        Name::new("Global Volume Widget"),
+        Name::new("Screen Shake Widget"),
         Node {
             justify_self: JustifySelf::Start,
             ..default()
         },
         children![
-            widget::button_small("-", lower_global_volume),
+            widget::button_small("<", toggle_screen_shake),
             (
-                Name::new("Current Volume"),
+                Name::new("Current Screen Shake"),
                 Node {
                     padding: UiRect::horizontal(Px(10.0)),
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
-                children![(widget::label(""), GlobalVolumeLabel)],
+                children![(widget::label(""), ScreenShakeLabel)],
             ),
-            widget::button_small("+", raise_global_volume),
+            widget::button_small(">", toggle_screen_shake),
         ],
     )
 }
 
-const MIN_VOLUME: f32 = 0.0;
-const MAX_VOLUME: f32 = 3.0;
+fn toggle_screen_shake(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.screen_shake = !settings.screen_shake;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ScreenShakeLabel;
+
+fn update_screen_shake_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<ScreenShakeLabel>>,
+) {
+    label.0 = if settings.screen_shake { "On" } else { "Off" }.to_string();
+}
+
+fn screen_nudge_widget() -> impl Bundle {
+    (
+        Name::new("Screen Nudge Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_screen_nudge),
+            (
+                Name::new("Current Screen Nudge"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ScreenNudgeLabel)],
+            ),
+            widget::button_small(">", toggle_screen_nudge),
+        ],
+    )
+}
+
+fn toggle_screen_nudge(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.screen_nudge = !settings.screen_nudge;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ScreenNudgeLabel;
+
+fn update_screen_nudge_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<ScreenNudgeLabel>>,
+) {
+    label.0 = if settings.screen_nudge { "On" } else { "Off" }.to_string();
+}
+
+fn feedback_intensity_widget() -> impl Bundle {
+    (
+        Name::new("Feedback Intensity Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small(
+                "-",
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    settings.feedback_intensity = (settings.feedback_intensity - 0.25).max(0.0);
+                }
+            ),
+            (
+                Name::new("Current Feedback Intensity"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), FeedbackIntensityLabel)],
+            ),
+            widget::button_small(
+                "+",
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    settings.feedback_intensity = (settings.feedback_intensity + 0.25).min(1.0);
+                }
+            ),
+        ],
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct FeedbackIntensityLabel;
+
+fn update_feedback_intensity_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<FeedbackIntensityLabel>>,
+) {
+    label.0 = format!("{:3.0}%", settings.feedback_intensity * 100.0);
+}
+
+fn letterbox_widget() -> impl Bundle {
+    (
+        Name::new("Letterbox Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_letterbox),
+            (
+                Name::new("Current Letterbox"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), LetterboxLabel)],
+            ),
+            widget::button_small(">", toggle_letterbox),
+        ],
+    )
+}
+
+fn toggle_letterbox(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.letterbox = !settings.letterbox;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct LetterboxLabel;
+
+fn update_letterbox_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<LetterboxLabel>>,
+) {
+    label.0 = if settings.letterbox { "On" } else { "Off" }.to_string();
+}
 
-fn lower_global_volume(_: Trigger<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() - 0.1).max(MIN_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
+/// The safe area goes up in steps of this, as a fraction of the screen.
+const SAFE_AREA_STEP: f32 = 0.02;
+const MAX_SAFE_AREA: f32 = 0.1;
+
+fn safe_area_widget() -> impl Bundle {
+    (
+        Name::new("Safe Area Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small(
+                "-",
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    settings.safe_area = (settings.safe_area - SAFE_AREA_STEP).max(0.0);
+                }
+            ),
+            (
+                Name::new("Current Safe Area"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), SafeAreaLabel)],
+            ),
+            widget::button_small(
+                "+",
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    settings.safe_area = (settings.safe_area + SAFE_AREA_STEP).min(MAX_SAFE_AREA);
+                }
+            ),
+        ],
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SafeAreaLabel;
+
+fn update_safe_area_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<SafeAreaLabel>>,
+) {
+    label.0 = format!("{:3.0}%", settings.safe_area * 100.0);
+}
+
+fn physics_quality_widget() -> impl Bundle {
+    (
+        Name::new("Physics Quality Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("-", lower_physics_quality),
+            (
+                Name::new("Current Physics Quality"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), PhysicsQualityLabel)],
+            ),
+            widget::button_small("+", raise_physics_quality),
+        ],
+    )
+}
+
+fn step_physics_quality(settings: &mut Settings, step: isize) {
+    let qualities = PhysicsQuality::ALL;
+    let i = qualities
+        .iter()
+        .position(|&quality| quality == settings.physics_quality)
+        .unwrap_or(0);
+    let i = i.saturating_add_signed(step).min(qualities.len() - 1);
+    settings.physics_quality = qualities[i];
+}
+
+fn lower_physics_quality(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    step_physics_quality(&mut settings, -1);
 }
 
-fn raise_global_volume(_: Trigger<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() + 0.1).min(MAX_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
+fn raise_physics_quality(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    step_physics_quality(&mut settings, 1);
 }
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct GlobalVolumeLabel;
+struct PhysicsQualityLabel;
 
-fn update_global_volume_label(
-    global_volume: Res<GlobalVolume>,
-    mut label: Single<&mut Text, With<GlobalVolumeLabel>>,
+fn update_physics_quality_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<PhysicsQualityLabel>>,
 ) {
-    let percent = 100.0 * global_volume.volume.to_linear();
-    label.0 = format!("{percent:3.0}%");
+    label.0 = settings.physics_quality.label().to_string();
+}
+
+fn handheld_widget() -> impl Bundle {
+    (
+        Name::new("Handheld Mode Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_handheld),
+            (
+                Name::new("Current Handheld Mode"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), HandheldLabel)],
+            ),
+            widget::button_small(">", toggle_handheld),
+        ],
+    )
+}
+
+fn toggle_handheld(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    let handheld = !settings.handheld;
+    settings.set_handheld(handheld);
+}
+
+fn particles_widget() -> impl Bundle {
+    (
+        Name::new("Particles Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small(
+                "-",
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    settings.particle_density = (settings.particle_density - 0.25).max(0.25);
+                }
+            ),
+            (
+                Name::new("Current Particles"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ParticlesLabel)],
+            ),
+            widget::button_small(
+                "+",
+                |_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    settings.particle_density = (settings.particle_density + 0.25).min(1.0);
+                }
+            ),
+        ],
+    )
+}
+
+fn frame_cap_widget() -> impl Bundle {
+    (
+        Name::new("Frame Cap Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", previous_frame_cap),
+            (
+                Name::new("Current Frame Cap"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), FrameCapLabel)],
+            ),
+            widget::button_small(">", next_frame_cap),
+        ],
+    )
+}
+
+/// No cap, then each of [`FRAME_CAPS`], round and round.
+fn cycle_frame_cap(settings: &mut Settings, step: usize) {
+    let caps: Vec<_> = std::iter::once(None).chain(FRAME_CAPS.map(Some)).collect();
+    let i = caps
+        .iter()
+        .position(|&cap| cap == settings.frame_cap)
+        .unwrap_or(0);
+    settings.frame_cap = caps[(i + step) % caps.len()];
+}
+
+fn previous_frame_cap(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    cycle_frame_cap(&mut settings, FRAME_CAPS.len());
+}
+
+fn next_frame_cap(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    cycle_frame_cap(&mut settings, 1);
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HandheldLabel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ParticlesLabel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct FrameCapLabel;
+
+fn update_performance_labels(
+    settings: Res<Settings>,
+    mut handheld_label: Single<
+        &mut Text,
+        (
+            With<HandheldLabel>,
+            Without<ParticlesLabel>,
+            Without<FrameCapLabel>,
+        ),
+    >,
+    mut particles_label: Single<&mut Text, (With<ParticlesLabel>, Without<FrameCapLabel>)>,
+    mut frame_cap_label: Single<&mut Text, With<FrameCapLabel>>,
+) {
+    handheld_label.0 = if settings.handheld { "On" } else { "Off" }.to_string();
+    particles_label.0 = format!("{:3.0}%", settings.particle_density * 100.0);
+    frame_cap_label.0 = settings
+        .frame_cap
+        .map_or("Off".to_string(), |cap| format!("{cap} FPS"));
+}
+
+fn dynamic_resolution_widget() -> impl Bundle {
+    (
+        Name::new("Dynamic Resolution Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_dynamic_resolution),
+            (
+                Name::new("Current Dynamic Resolution"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), DynamicResolutionLabel)],
+            ),
+            widget::button_small(">", toggle_dynamic_resolution),
+        ],
+    )
+}
+
+fn toggle_dynamic_resolution(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.dynamic_resolution = !settings.dynamic_resolution;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DynamicResolutionLabel;
+
+fn update_dynamic_resolution_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<DynamicResolutionLabel>>,
+) {
+    label.0 = if settings.dynamic_resolution {
+        "On"
+    } else {
+        "Off"
+    }
+    .to_string();
+}
+
+fn input_preset_widget() -> impl Bundle {
+    (
+        Name::new("Input Preset Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", previous_input_preset),
+            (
+                Name::new("Current Input Preset"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), InputPresetLabel)],
+            ),
+            widget::button_small(">", next_input_preset),
+        ],
+    )
+}
+
+fn aim_assist_widget() -> impl Bundle {
+    (
+        Name::new("Aim Assist Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_aim_assist),
+            (
+                Name::new("Current Aim Assist"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), AimAssistLabel)],
+            ),
+            widget::button_small(">", toggle_aim_assist),
+        ],
+    )
+}
+
+fn hints_widget() -> impl Bundle {
+    (
+        Name::new("Hints Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_hints),
+            (
+                Name::new("Current Hints"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), HintsLabel)],
+            ),
+            widget::button_small(">", toggle_hints),
+        ],
+    )
+}
+
+fn hook_ghosts_widget() -> impl Bundle {
+    (
+        Name::new("Hook Ghosts Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", cycle_hook_ghosts),
+            (
+                Name::new("Current Hook Ghosts"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), HookGhostsLabel)],
+            ),
+            widget::button_small(">", cycle_hook_ghosts),
+        ],
+    )
+}
+
+fn partner_hooking_widget() -> impl Bundle {
+    (
+        Name::new("Partner Hooking Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_partner_hooking),
+            (
+                Name::new("Current Partner Hooking"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), PartnerHookingLabel)],
+            ),
+            widget::button_small(">", toggle_partner_hooking),
+        ],
+    )
+}
+
+fn hold_toggles_widget() -> impl Bundle {
+    (
+        Name::new("Held Actions Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", toggle_hold_toggles),
+            (
+                Name::new("Current Held Actions"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), HoldTogglesLabel)],
+            ),
+            widget::button_small(">", toggle_hold_toggles),
+        ],
+    )
+}
+
+fn cycle_input_preset(input_map: &mut InputMap, step: usize) {
+    let presets = InputPreset::ALL;
+    let i = presets
+        .iter()
+        .position(|&preset| preset == input_map.preset)
+        .unwrap_or(0);
+    *input_map = InputMap::from_preset(presets[(i + step) % presets.len()]);
+}
+
+fn previous_input_preset(_: Trigger<Pointer<Click>>, mut input_map: ResMut<InputMap>) {
+    cycle_input_preset(&mut input_map, InputPreset::ALL.len() - 1);
+}
+
+fn next_input_preset(_: Trigger<Pointer<Click>>, mut input_map: ResMut<InputMap>) {
+    cycle_input_preset(&mut input_map, 1);
+}
+
+fn toggle_aim_assist(_: Trigger<Pointer<Click>>, mut assist: ResMut<AimAssist>) {
+    assist.enabled = !assist.enabled;
+}
+
+fn toggle_hints(_: Trigger<Pointer<Click>>, mut hints: ResMut<HintSettings>) {
+    hints.enabled = !hints.enabled;
+}
+
+fn cycle_hook_ghosts(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.hook_ghosts = settings.hook_ghosts.next();
+}
+
+fn toggle_partner_hooking(_: Trigger<Pointer<Click>>, mut settings: ResMut<Settings>) {
+    settings.partner_hooking = !settings.partner_hooking;
+}
+
+fn toggle_hold_toggles(_: Trigger<Pointer<Click>>, mut toggles: ResMut<HoldToggles>) {
+    toggles.enabled = !toggles.enabled;
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct InputPresetLabel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct AimAssistLabel;
+
+fn update_input_labels(
+    input_map: Res<InputMap>,
+    assist: Res<AimAssist>,
+    mut preset_label: Single<&mut Text, (With<InputPresetLabel>, Without<AimAssistLabel>)>,
+    mut assist_label: Single<&mut Text, With<AimAssistLabel>>,
+) {
+    preset_label.0 = input_map.preset.label().to_string();
+    assist_label.0 = if assist.enabled { "On" } else { "Off" }.to_string();
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HintsLabel;
+
+fn update_hints_label(hints: Res<HintSettings>, mut label: Single<&mut Text, With<HintsLabel>>) {
+    label.0 = if hints.enabled { "On" } else { "Off" }.to_string();
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HookGhostsLabel;
+
+fn update_hook_ghosts_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<HookGhostsLabel>>,
+) {
+    label.0 = settings.hook_ghosts.label().to_string();
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct PartnerHookingLabel;
+
+fn update_partner_hooking_label(
+    settings: Res<Settings>,
+    mut label: Single<&mut Text, With<PartnerHookingLabel>>,
+) {
+    label.0 = if settings.partner_hooking {
+        "On"
+    } else {
+        "Off"
+    }
+    .to_string();
+}
+
+/// Reel and fine aim either last while held, or a press toggles them.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct HoldTogglesLabel;
+
+fn update_hold_toggles_label(
+    toggles: Res<HoldToggles>,
+    mut label: Single<&mut Text, With<HoldTogglesLabel>>,
+) {
+    label.0 = if toggles.enabled { "Toggle" } else { "Hold" }.to_string();
+}
+
+fn controller_preset_widget() -> impl Bundle {
+    (
+        Name::new("Controller Preset Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("<", previous_controller_preset),
+            (
+                Name::new("Current Preset"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ControllerLabel)],
+            ),
+            widget::button_small(">", next_controller_preset),
+        ],
+    )
+}
+
+fn deadzone_widget() -> impl Bundle {
+    (
+        Name::new("Deadzone Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("-", lower_deadzone),
+            (
+                Name::new("Current Deadzone"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), DeadzoneLabel)],
+            ),
+            widget::button_small("+", raise_deadzone),
+        ],
+    )
+}
+
+/// The controller being set up: the one in use, or else any connected one.
+fn settings_controller(
+    active: &ActiveController,
+    gamepad_query: &Query<(Entity, &Name), With<Gamepad>>,
+) -> Option<String> {
+    active
+        .0
+        .and_then(|entity| gamepad_query.get(entity).ok())
+        .or_else(|| gamepad_query.iter().next())
+        .map(|(_, name)| name.to_string())
+}
+
+fn change_profile(
+    active: &ActiveController,
+    gamepad_query: &Query<(Entity, &Name), With<Gamepad>>,
+    profiles: &mut ControllerProfiles,
+    change: impl FnOnce(&mut ControllerProfile),
+) {
+    let Some(name) = settings_controller(active, gamepad_query) else {
+        return;
+    };
+    if let Some(profile) = profiles.0.get_mut(&name) {
+        change(profile);
+    }
+}
+
+fn cycle_preset(profile: &mut ControllerProfile, step: usize) {
+    let presets = ControllerPreset::ALL;
+    let i = presets
+        .iter()
+        .position(|&preset| preset == profile.preset)
+        .unwrap_or(0);
+    profile.preset = presets[(i + step) % presets.len()];
+}
+
+fn previous_controller_preset(
+    _: Trigger<Pointer<Click>>,
+    active: Res<ActiveController>,
+    gamepad_query: Query<(Entity, &Name), With<Gamepad>>,
+    mut profiles: ResMut<ControllerProfiles>,
+) {
+    change_profile(&active, &gamepad_query, &mut profiles, |profile| {
+        cycle_preset(profile, ControllerPreset::ALL.len() - 1);
+    });
+}
+
+fn next_controller_preset(
+    _: Trigger<Pointer<Click>>,
+    active: Res<ActiveController>,
+    gamepad_query: Query<(Entity, &Name), With<Gamepad>>,
+    mut profiles: ResMut<ControllerProfiles>,
+) {
+    change_profile(&active, &gamepad_query, &mut profiles, |profile| {
+        cycle_preset(profile, 1);
+    });
+}
+
+fn lower_deadzone(
+    _: Trigger<Pointer<Click>>,
+    active: Res<ActiveController>,
+    gamepad_query: Query<(Entity, &Name), With<Gamepad>>,
+    mut profiles: ResMut<ControllerProfiles>,
+) {
+    change_profile(&active, &gamepad_query, &mut profiles, |profile| {
+        profile.deadzone = (profile.deadzone - 0.01).max(ControllerProfile::MIN_DEADZONE);
+    });
+}
+
+fn raise_deadzone(
+    _: Trigger<Pointer<Click>>,
+    active: Res<ActiveController>,
+    gamepad_query: Query<(Entity, &Name), With<Gamepad>>,
+    mut profiles: ResMut<ControllerProfiles>,
+) {
+    change_profile(&active, &gamepad_query, &mut profiles, |profile| {
+        profile.deadzone = (profile.deadzone + 0.01).min(ControllerProfile::MAX_DEADZONE);
+    });
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ControllerLabel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DeadzoneLabel;
+
+fn update_controller_labels(
+    active: Res<ActiveController>,
+    gamepad_query: Query<(Entity, &Name), With<Gamepad>>,
+    profiles: Res<ControllerProfiles>,
+    mut controller_label: Single<&mut Text, (With<ControllerLabel>, Without<DeadzoneLabel>)>,
+    mut deadzone_label: Single<&mut Text, With<DeadzoneLabel>>,
+) {
+    let profile = settings_controller(&active, &gamepad_query)
+        .and_then(|name| profiles.0.get(&name).copied());
+    match profile {
+        Some(profile) => {
+            controller_label.0 = profile.preset.label().to_string();
+            deadzone_label.0 = format!("{:3.0}%", 100.0 * profile.deadzone);
+        }
+        None => {
+            controller_label.0 = "None connected".to_string();
+            deadzone_label.0 = "-".to_string();
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ChainControlsLabel;
+
+fn update_chain_controls_label(
+    input_map: Res<InputMap>,
+    active: Res<ActiveController>,
+    profiles: Res<ControllerProfiles>,
+    name_query: Query<&Name, With<Gamepad>>,
+    mut label: Single<&mut Text, With<ChainControlsLabel>>,
+) {
+    let preset = active_preset(&active, &profiles, &name_query);
+    label.0 = format!(
+        "{} / {}",
+        input_map.prompt(InputAction::FireChain, preset),
+        input_map.prompt(InputAction::ReleaseChain, preset),
+    );
+}
+
+fn open_controls_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Controls);
+}
+
+fn open_assists_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Assists);
 }
 
 fn go_back_on_click(
@@ -0,0 +1,191 @@
+//! The wardrobe, where unlocked cosmetics are picked.
+
+use bevy::{
+    ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+
+use crate::{game::cosmetics::Cosmetic, menus::Menu, persistence::Progress, theme::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Wardrobe), spawn_wardrobe_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Wardrobe).and(input_just_pressed(KeyCode::Escape))),
+    );
+
+    app.register_type::<WardrobeLabel>();
+    app.add_systems(
+        Update,
+        update_wardrobe_labels.run_if(in_state(Menu::Wardrobe)),
+    );
+}
+
+/// One kind of cosmetic, picked on its own row.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Reflect)]
+enum WardrobeSlot {
+    ChainColor,
+    Trail,
+    Skin,
+}
+
+impl WardrobeSlot {
+    const ALL: [Self; 3] = [Self::ChainColor, Self::Trail, Self::Skin];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::ChainColor => "Chain Color",
+            Self::Trail => "Chain Trail",
+            Self::Skin => "Skin",
+        }
+    }
+
+    /// What's picked, and how many of the choices are unlocked.
+    fn status(self, progress: &Progress) -> String {
+        let cosmetics = &progress.cosmetics;
+        match self {
+            Self::ChainColor => status(progress, cosmetics.chain_color),
+            Self::Trail => status(progress, cosmetics.trail),
+            Self::Skin => status(progress, cosmetics.skin),
+        }
+    }
+
+    /// Pick the next unlocked choice, or the previous one if `step` is
+    /// negative.
+    fn step(self, progress: &mut Progress, step: isize) {
+        match self {
+            Self::ChainColor => {
+                progress.cosmetics.chain_color =
+                    next_unlocked(progress, progress.cosmetics.chain_color, step);
+            }
+            Self::Trail => {
+                progress.cosmetics.trail = next_unlocked(progress, progress.cosmetics.trail, step);
+            }
+            Self::Skin => {
+                progress.cosmetics.skin = next_unlocked(progress, progress.cosmetics.skin, step);
+            }
+        }
+    }
+}
+
+fn status<T: Cosmetic>(progress: &Progress, current: T) -> String {
+    let unlocked = T::ALL
+        .iter()
+        .filter(|cosmetic| cosmetic.is_unlocked(progress))
+        .count();
+    format!("{} ({unlocked}/{})", current.label(), T::ALL.len())
+}
+
+fn next_unlocked<T: Cosmetic>(progress: &Progress, current: T, step: isize) -> T {
+    let unlocked: Vec<T> = T::ALL
+        .iter()
+        .copied()
+        .filter(|cosmetic| cosmetic.is_unlocked(progress))
+        .collect();
+    let i = unlocked
+        .iter()
+        .position(|&cosmetic| cosmetic == current)
+        .unwrap_or(0);
+    unlocked[(i as isize + step).rem_euclid(unlocked.len() as isize) as usize]
+}
+
+fn spawn_wardrobe_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Wardrobe Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Wardrobe),
+        children![
+            widget::header("Wardrobe"),
+            wardrobe_grid(),
+            widget::label("Unlock more with achievements."),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn wardrobe_grid() -> impl Bundle {
+    (
+        Name::new("Wardrobe Grid"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Px(10.0),
+            ..default()
+        },
+        Children::spawn(SpawnIter(WardrobeSlot::ALL.into_iter().map(wardrobe_row))),
+    )
+}
+
+fn wardrobe_row(slot: WardrobeSlot) -> impl Bundle {
+    (
+        Name::new("Wardrobe Row"),
+        Node {
+            display: Display::Grid,
+            column_gap: Px(30.0),
+            grid_template_columns: vec![GridTrack::px(300.0), GridTrack::auto()],
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            (
+                widget::label(slot.label()),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            (
+                Name::new("Wardrobe Picker"),
+                Node {
+                    justify_self: JustifySelf::Start,
+                    ..default()
+                },
+                children![
+                    widget::button_small(
+                        "<",
+                        move |_: Trigger<Pointer<Click>>, mut progress: ResMut<Progress>| {
+                            slot.step(&mut progress, -1);
+                        },
+                    ),
+                    (
+                        Name::new("Current Cosmetic"),
+                        Node {
+                            padding: UiRect::horizontal(Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        children![(widget::label(""), WardrobeLabel(slot))],
+                    ),
+                    widget::button_small(
+                        ">",
+                        move |_: Trigger<Pointer<Click>>, mut progress: ResMut<Progress>| {
+                            slot.step(&mut progress, 1);
+                        },
+                    ),
+                ],
+            ),
+        ],
+    )
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct WardrobeLabel(WardrobeSlot);
+
+fn update_wardrobe_labels(
+    progress: Res<Progress>,
+    mut label_query: Query<(&WardrobeLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut label_query {
+        let status = label.0.status(&progress);
+        if text.0 != status {
+            text.0 = status;
+        }
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
@@ -0,0 +1,235 @@
+//! The "What's new" menu, listing what changed in this version.
+//!
+//! What's in it comes from [`CHANGELOG_PATH`], one release per version. The
+//! release matching the game's version is shown by itself the first time the
+//! title screen is reached after an update, and the version is kept in
+//! [`Progress::seen_changelog`] so it isn't shown again. The main menu has a
+//! button to bring it back.
+
+use bevy::{
+    asset::LoadContext, ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed,
+    prelude::*, ui::Val::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    asset_tracking::LoadResource, menus::Menu, persistence::Progress, ron_asset::RonAssetLoader,
+    screens::Screen, theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Changelog>();
+    app.register_asset_loader(
+        RonAssetLoader::<Changelog>::new(&["changelog.ron"]).after_load(load_images),
+    );
+    app.register_type::<ChangelogAssets>();
+    app.load_resource::<ChangelogAssets>();
+
+    app.add_systems(
+        Update,
+        show_once
+            .run_if(in_state(Screen::Title).and(in_state(Menu::Main)))
+            .run_if(resource_exists::<ChangelogAssets>),
+    );
+    app.add_systems(OnEnter(Menu::WhatsNew), spawn_whats_new_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::WhatsNew).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+/// Every version's changes, relative to the assets folder.
+pub const CHANGELOG_PATH: &str = "changelog/changelog.changelog.ron";
+/// The version the changelog is looked up by.
+const GAME_VERSION: &str = env!("CARGO_PKG_VERSION");
+const IMAGE_SIZE: f32 = 96.0;
+const ENTRY_WIDTH: f32 = 720.0;
+
+/// What sort of change an entry is, for the tag shown with it.
+#[derive(Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+enum ChangeKind {
+    Hook,
+    Level,
+    Setting,
+    Change,
+}
+
+impl ChangeKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Hook => "New hook",
+            Self::Level => "New level",
+            Self::Setting => "New setting",
+            Self::Change => "Changed",
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ChangelogEntry {
+    kind: ChangeKind,
+    title: String,
+    text: String,
+    /// A picture of it, relative to the assets folder.
+    #[serde(default)]
+    image: Option<String>,
+    /// The picture, once the loader has started loading it.
+    #[serde(skip)]
+    image_handle: Option<Handle<Image>>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct Release {
+    version: String,
+    entries: Vec<ChangelogEntry>,
+}
+
+/// What changed in each version, as it's written in the file.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+#[serde(transparent)]
+struct Changelog(Vec<Release>);
+
+impl Changelog {
+    fn release(&self, version: &str) -> Option<&Release> {
+        self.0.iter().find(|release| release.version == version)
+    }
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+struct ChangelogAssets {
+    #[dependency]
+    changelog: Handle<Changelog>,
+}
+
+impl FromWorld for ChangelogAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            changelog: assets.load(CHANGELOG_PATH),
+        }
+    }
+}
+
+/// Open the menu the first time the title screen is reached in a version
+/// that has something in the changelog.
+fn show_once(
+    changelog_assets: Res<ChangelogAssets>,
+    changelogs: Res<Assets<Changelog>>,
+    mut progress: ResMut<Progress>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    if progress.seen_changelog.as_deref() == Some(GAME_VERSION) {
+        return;
+    }
+    let Some(changelog) = changelogs.get(&changelog_assets.changelog) else {
+        return;
+    };
+    progress.seen_changelog = Some(GAME_VERSION.to_string());
+    if changelog.release(GAME_VERSION).is_some() {
+        next_menu.set(Menu::WhatsNew);
+    }
+}
+
+fn spawn_whats_new_menu(
+    mut commands: Commands,
+    changelog_assets: Option<Res<ChangelogAssets>>,
+    changelogs: Res<Assets<Changelog>>,
+) {
+    let release = changelog_assets
+        .and_then(|changelog_assets| changelogs.get(&changelog_assets.changelog))
+        .and_then(|changelog| changelog.release(GAME_VERSION))
+        .cloned();
+    let entries = release.map(|release| release.entries).unwrap_or_default();
+    let empty = entries.is_empty();
+
+    let mut menu = commands.spawn((
+        widget::ui_root("What's New Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::WhatsNew),
+    ));
+    menu.with_child(widget::header(format!("What's new in {GAME_VERSION}")));
+    if empty {
+        menu.with_child(widget::label("Nothing new this time"));
+    }
+    menu.with_child((
+        Name::new("Entries"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Px(16.0),
+            ..default()
+        },
+        Children::spawn(SpawnIter(entries.into_iter().map(entry_row))),
+    ));
+    menu.with_child(widget::button("Back", go_back_on_click));
+}
+
+/// One change: its picture if it has one, and what it is beside it.
+fn entry_row(entry: ChangelogEntry) -> impl Bundle {
+    let image = entry.image_handle.map(|image| {
+        (
+            Name::new("Image"),
+            ImageNode::new(image),
+            Node {
+                width: Px(IMAGE_SIZE),
+                height: Px(IMAGE_SIZE),
+                flex_shrink: 0.0,
+                ..default()
+            },
+        )
+    });
+    (
+        Name::new(format!("Entry {}", entry.title)),
+        Node {
+            width: Px(ENTRY_WIDTH),
+            column_gap: Px(20.0),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Children::spawn((
+            SpawnIter(image.into_iter()),
+            Spawn((
+                Name::new("Description"),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Px(4.0),
+                    ..default()
+                },
+                children![
+                    small_text(entry.kind.label(), 16.0),
+                    widget::label(entry.title),
+                    small_text(entry.text, 18.0),
+                ],
+            )),
+        )),
+    )
+}
+
+/// Like [`widget::label`], at a smaller size.
+fn small_text(text: impl Into<String>, size: f32) -> impl Bundle {
+    (
+        Name::new("Label"),
+        Text(text.into()),
+        TextFont::from_font_size(size),
+        TextColor(ui_palette::LABEL_TEXT),
+    )
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+/// Start loading the pictures the changelog points to, along with it.
+fn load_images(changelog: &mut Changelog, load_context: &mut LoadContext) {
+    for entry in changelog
+        .0
+        .iter_mut()
+        .flat_map(|release| &mut release.entries)
+    {
+        entry.image_handle = entry.image.clone().map(|path| load_context.load(path));
+    }
+}
@@ -0,0 +1,145 @@
+//! `--migrate-levels`: upgrade level files made for older builds to the
+//! current format, in place, instead of starting the game.
+//!
+//! ```sh
+//! cargo run -- --migrate-levels                       # everything in assets/levels
+//! cargo run -- --migrate-levels my_levels a.level.ron  # these folders and files
+//! cargo run -- --migrate-levels --dry-run              # only say what would change
+//! ```
+//!
+//! Each file gets a line saying what version it was and what the upgrades
+//! changed in it, along with anything validation has to say about the
+//! result. Upgraded files keep their old contents next to them as
+//! `<file>.bak`, and packed levels stay packed. Files already in the current
+//! version are left alone.
+//!
+//! Levels in an older version load in the game too, upgraded as they're
+//! read. This is for updating the files themselves, e.g. a folder of
+//! community levels after a format change. Native only.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    game::level_data::{LEVEL_EXTENSIONS, LEVEL_FORMAT_VERSION, LevelData, LevelLoadError},
+    packed,
+};
+
+const LEVEL_DIR: &str = "assets/levels";
+
+/// Run the migration if the command line asks for it, giving back how it
+/// went. `None` means the game should start as usual.
+pub fn run_from_args() -> Option<AppExit> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.iter().any(|arg| arg == "--migrate-levels") {
+        return None;
+    }
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let mut roots: Vec<PathBuf> = args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .map(PathBuf::from)
+        .collect();
+    if roots.is_empty() {
+        roots.push(PathBuf::from(LEVEL_DIR));
+    }
+
+    let mut files = Vec::new();
+    for root in &roots {
+        if let Err(error) = collect_level_files(root, &mut files) {
+            eprintln!("Couldn't list {}: {error}", root.display());
+            return Some(AppExit::error());
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    let (mut upgraded, mut current, mut failed) = (0, 0, 0);
+    for path in &files {
+        match migrate_file(path, dry_run) {
+            Ok(Some(report)) => {
+                upgraded += 1;
+                println!("{}: {report}", path.display());
+            }
+            Ok(None) => {
+                current += 1;
+                println!("{}: already version {LEVEL_FORMAT_VERSION}", path.display());
+            }
+            Err(error) => {
+                failed += 1;
+                eprintln!("{}: {error}", path.display());
+            }
+        }
+    }
+
+    let verb = if dry_run {
+        "would be upgraded"
+    } else {
+        "upgraded"
+    };
+    println!(
+        "{} level files: {upgraded} {verb}, {current} already current, {failed} failed",
+        files.len()
+    );
+    Some(if failed == 0 {
+        AppExit::Success
+    } else {
+        AppExit::error()
+    })
+}
+
+/// Level files at `path`, or in it and its subfolders if it's a folder.
+fn collect_level_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+        let is_level = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| LEVEL_EXTENSIONS.iter().any(|ext| name.ends_with(ext)));
+        if path.is_dir() {
+            collect_level_files(&path, files)?;
+        } else if is_level {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Upgrade one file, saying what changed, or `None` if it was current.
+fn migrate_file(path: &Path, dry_run: bool) -> Result<Option<String>, LevelLoadError> {
+    let bytes = std::fs::read(path)?;
+    let migrated = LevelData::migrate(&LevelData::unpack_ron(&bytes)?)?;
+    if !migrated.upgraded() {
+        return Ok(None);
+    }
+
+    let mut report = format!(
+        "version {} to {LEVEL_FORMAT_VERSION}",
+        migrated.from_version
+    );
+    for change in &migrated.changes {
+        report += &format!("\n  - {change}");
+    }
+    for issue in migrated.level.validate() {
+        report += &format!("\n  {:?}: {issue}", issue.severity());
+    }
+
+    if !dry_run {
+        let upgraded = if packed::is_packed(&bytes) {
+            migrated.level.to_packed()
+        } else {
+            migrated.level.to_ron().map(String::into_bytes)
+        }
+        .map_err(|error| LevelLoadError::Migration(error.to_string()))?;
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        std::fs::write(&backup, &bytes)?;
+        std::fs::write(path, upgraded)?;
+    }
+    Ok(Some(report))
+}
@@ -0,0 +1,408 @@
+//! Text chat and an emote wheel for online sessions.
+//!
+//! Press Enter to type a message and Enter again to send it, or Escape to
+//! cancel. `/mute <name>` and `/unmute <name>` manage the mute list. Hold Q
+//! and point the mouse at an emote to send it on release. Taunts are sent
+//! along too, and show up in the chat the same way.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    f32::consts::TAU,
+};
+
+use bevy::{
+    ecs::spawn::SpawnIter,
+    input::{
+        InputSystem,
+        common_conditions::{input_just_pressed, input_just_released},
+        keyboard::Key,
+    },
+    prelude::*,
+    ui::Val::*,
+    window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{cosmetics::Cosmetic, taunt::PlayTaunt},
+    input::text::{OnScreenKeyboard, TextInput},
+    net::{NetMessage, NetSession, NetSystems, PeerId, ReceivedMessage, SendMessage},
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ChatLog>();
+    app.init_resource::<ChatInput>();
+    app.init_resource::<MuteList>();
+    app.init_resource::<ChatFilter>();
+    app.register_type::<ChatText>();
+    app.register_type::<EmoteWheel>();
+    app.register_type::<EmoteSlot>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_chat_overlay);
+    // Runs right after input is collected, so keys typed into the chat can be
+    // hidden from gameplay.
+    app.add_systems(
+        PreUpdate,
+        read_chat_input
+            .after(InputSystem)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        (
+            (
+                open_emote_wheel.run_if(input_just_pressed(EMOTE_KEY)),
+                highlight_emote,
+                send_emote.run_if(input_just_released(EMOTE_KEY)),
+            )
+                .chain()
+                .in_set(NetSystems::Send),
+            share_taunts.in_set(NetSystems::Send),
+            receive_chat.in_set(NetSystems::Receive),
+            update_chat_text.after(NetSystems::Receive),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+const EMOTE_KEY: KeyCode = KeyCode::KeyQ;
+const MAX_MESSAGE_LENGTH: usize = 200;
+const HISTORY_CAPACITY: usize = 50;
+const VISIBLE_LINES: usize = 8;
+const WHEEL_RADIUS: f32 = 120.0;
+/// How far the cursor has to move from the center to pick an emote.
+const WHEEL_DEADZONE: f32 = 30.0;
+const EMOTE_COLOR: Color = Color::srgb(0.85, 0.85, 0.85);
+const SELECTED_EMOTE_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// Short canned messages that can be sent without typing.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum Emote {
+    Wave,
+    Laugh,
+    GoodGame,
+    Oops,
+    Nice,
+    Angry,
+}
+
+impl Emote {
+    /// In wheel order, counterclockwise starting from the right.
+    pub const ALL: [Emote; 6] = [
+        Emote::Wave,
+        Emote::Laugh,
+        Emote::GoodGame,
+        Emote::Oops,
+        Emote::Nice,
+        Emote::Angry,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Emote::Wave => "Hi!",
+            Emote::Laugh => "Haha",
+            Emote::GoodGame => "GG",
+            Emote::Oops => "Oops",
+            Emote::Nice => "Nice!",
+            Emote::Angry => "Grr",
+        }
+    }
+
+    /// The emote the wheel points at for a direction, if it's far enough
+    /// from the center.
+    fn from_direction(direction: Vec2) -> Option<Emote> {
+        if direction.length() < WHEEL_DEADZONE {
+            return None;
+        }
+        let step = TAU / Self::ALL.len() as f32;
+        let angle = direction.to_angle().rem_euclid(TAU);
+        let i = (angle / step).round() as usize % Self::ALL.len();
+        Some(Self::ALL[i])
+    }
+}
+
+/// A line in the chat. Lines without a sender are notices from the game.
+#[derive(Clone, Debug)]
+pub struct ChatLine {
+    pub from: Option<PeerId>,
+    pub text: String,
+}
+
+/// Recent chat messages, oldest first.
+#[derive(Resource, Default, Debug)]
+pub struct ChatLog(VecDeque<ChatLine>);
+
+impl ChatLog {
+    pub fn push(&mut self, line: ChatLine) {
+        if self.0.len() == HISTORY_CAPACITY {
+            self.0.pop_front();
+        }
+        self.0.push_back(line);
+    }
+
+    pub fn notice(&mut self, text: impl Into<String>) {
+        self.push(ChatLine {
+            from: None,
+            text: text.into(),
+        });
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &ChatLine> {
+        self.0.iter()
+    }
+}
+
+/// Players whose chat and emotes are hidden.
+#[derive(Resource, Default, Debug)]
+pub struct MuteList(pub HashSet<PeerId>);
+
+/// Runs over every received chat message before it is shown. Replace it to
+/// plug in a profanity filter, e.g. one required by a platform.
+#[derive(Resource)]
+pub struct ChatFilter(pub fn(&str) -> String);
+
+impl Default for ChatFilter {
+    fn default() -> Self {
+        Self(|text| text.to_string())
+    }
+}
+
+/// The message being typed, if the chat is open.
+#[derive(Resource, Default)]
+struct ChatInput {
+    typing: bool,
+    text: String,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ChatText;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct EmoteWheel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct EmoteSlot(usize);
+
+fn spawn_chat_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Chat"),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Px(10.0),
+            bottom: Px(10.0),
+            max_width: Px(500.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+        children![(
+            Name::new("Chat Text"),
+            Text::default(),
+            TextFont::from_font_size(16.0),
+            ChatText,
+        )],
+    ));
+}
+
+/// Type into the chat while it is open, hiding the keys from everything else.
+fn read_chat_input(
+    mut text_input: TextInput,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut chat: ResMut<ChatInput>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+    session: Res<NetSession>,
+    mut mutes: ResMut<MuteList>,
+    mut log: ResMut<ChatLog>,
+    mut sent: EventWriter<SendMessage>,
+) {
+    let pressed = text_input.read();
+
+    if !chat.typing {
+        if pressed.contains(&Key::Enter) {
+            chat.typing = true;
+            keyboard.wanted = true;
+            keys.reset_all();
+        }
+        return;
+    }
+
+    for key in pressed {
+        match key {
+            Key::Character(text) if chat.text.len() < MAX_MESSAGE_LENGTH => {
+                chat.text.push_str(&text)
+            }
+            Key::Space => chat.text.push(' '),
+            Key::Backspace => {
+                chat.text.pop();
+            }
+            Key::Escape => {
+                chat.typing = false;
+                keyboard.wanted = false;
+                chat.text.clear();
+            }
+            Key::Enter => {
+                chat.typing = false;
+                keyboard.wanted = false;
+                let text = std::mem::take(&mut chat.text);
+                let text = text.trim();
+                if let Some(command) = text.strip_prefix('/') {
+                    let notice = run_chat_command(command, &session, &mut mutes);
+                    log.notice(notice);
+                } else if !text.is_empty() {
+                    sent.write(SendMessage(NetMessage::Chat(text.to_string())));
+                }
+            }
+            _ => {}
+        }
+    }
+    keys.reset_all();
+}
+
+fn run_chat_command(command: &str, session: &NetSession, mutes: &mut MuteList) -> String {
+    let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+    let peer = session.find(argument.trim());
+    match (name, peer) {
+        ("mute", Some(peer)) if peer == session.local => "You can't mute yourself.".to_string(),
+        ("mute", Some(peer)) => {
+            mutes.0.insert(peer);
+            format!("Muted {}.", session.name(peer))
+        }
+        ("unmute", Some(peer)) => {
+            mutes.0.remove(&peer);
+            format!("Unmuted {}.", session.name(peer))
+        }
+        ("mute" | "unmute", None) => format!("Nobody called \"{}\" is here.", argument.trim()),
+        _ => format!("Unknown command /{name}. Try /mute or /unmute."),
+    }
+}
+
+fn receive_chat(
+    mut received: EventReader<ReceivedMessage>,
+    mutes: Res<MuteList>,
+    filter: Res<ChatFilter>,
+    mut log: ResMut<ChatLog>,
+) {
+    for ReceivedMessage { from, message } in received.read() {
+        if mutes.0.contains(from) {
+            continue;
+        }
+        let text = match message {
+            NetMessage::Chat(text) => (filter.0)(text),
+            NetMessage::Emote(emote) => format!("*{}*", emote.label()),
+            NetMessage::Taunt(taunt) => format!("*{}*", taunt.label()),
+            _ => continue,
+        };
+        log.push(ChatLine {
+            from: Some(*from),
+            text,
+        });
+    }
+}
+
+fn update_chat_text(
+    log: Res<ChatLog>,
+    chat: Res<ChatInput>,
+    session: Res<NetSession>,
+    mut text: Single<&mut Text, With<ChatText>>,
+) {
+    if !log.is_changed() && !chat.is_changed() {
+        return;
+    }
+    let skip = log.0.len().saturating_sub(VISIBLE_LINES);
+    let mut lines: Vec<String> = log
+        .lines()
+        .skip(skip)
+        .map(|line| match line.from {
+            Some(peer) => format!("{}: {}", session.name(peer), line.text),
+            None => format!("* {}", line.text),
+        })
+        .collect();
+    if chat.typing {
+        lines.push(format!("Say: {}_", chat.text));
+    }
+    text.0 = lines.join("\n");
+}
+
+fn open_emote_wheel(mut commands: Commands) {
+    let size = 2.0 * WHEEL_RADIUS + 100.0;
+    let center = size / 2.0;
+    let slots = Emote::ALL.iter().enumerate().map(move |(i, emote)| {
+        let direction = Vec2::from_angle(i as f32 * TAU / Emote::ALL.len() as f32);
+        (
+            widget::label(emote.label()),
+            TextColor(EMOTE_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Px(center + direction.x * WHEEL_RADIUS - 30.0),
+                // UI coordinates grow downwards.
+                top: Px(center - direction.y * WHEEL_RADIUS - 15.0),
+                ..default()
+            },
+            EmoteSlot(i),
+        )
+    });
+    commands.spawn((
+        widget::ui_root("Emote Wheel"),
+        EmoteWheel,
+        GlobalZIndex(2),
+        StateScoped(Screen::Gameplay),
+        children![(
+            Name::new("Emote Wheel Slots"),
+            Node {
+                width: Px(size),
+                height: Px(size),
+                ..default()
+            },
+            Pickable::IGNORE,
+            Children::spawn(SpawnIter(slots)),
+        )],
+    ));
+}
+
+/// Where the cursor points relative to the middle of the window, y up.
+fn wheel_direction(window: &Window) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let offset = cursor - window.size() / 2.0;
+    Some(Vec2::new(offset.x, -offset.y))
+}
+
+fn highlight_emote(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut slot_query: Query<(&EmoteSlot, &mut TextColor)>,
+) {
+    let selected = wheel_direction(&window).and_then(Emote::from_direction);
+    for (slot, mut color) in &mut slot_query {
+        color.0 = if Some(Emote::ALL[slot.0]) == selected {
+            SELECTED_EMOTE_COLOR
+        } else {
+            EMOTE_COLOR
+        };
+    }
+}
+
+fn send_emote(
+    mut commands: Commands,
+    window: Single<&Window, With<PrimaryWindow>>,
+    wheel_query: Query<Entity, With<EmoteWheel>>,
+    mut sent: EventWriter<SendMessage>,
+) {
+    for entity in &wheel_query {
+        commands.entity(entity).despawn();
+    }
+    if let Some(emote) = wheel_direction(&window).and_then(Emote::from_direction) {
+        sent.write(SendMessage(NetMessage::Emote(emote)));
+    }
+}
+
+/// Other players don't have a duck on screen to taunt with, so taunts show up
+/// in the chat like emotes.
+fn share_taunts(mut taunts: EventReader<PlayTaunt>, mut sent: EventWriter<SendMessage>) {
+    for PlayTaunt(taunt) in taunts.read() {
+        sent.write(SendMessage(NetMessage::Taunt(*taunt)));
+    }
+}
@@ -0,0 +1,319 @@
+//! Finding, joining and setting up online games on the LAN.
+//!
+//! The host owns the [`Lobby`]: players send it their name, readiness and
+//! level vote, and it sends the whole lobby back to everyone once a second
+//! and whenever it changes. Once everyone is ready, the host picks the level
+//! with the most votes and starts the game for all players.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    time::Duration,
+};
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    menus::Menu,
+    net::{
+        NetMessage, NetSession, NetSystems, PeerId, ReceivedMessage, SendMessage,
+        transport::{DISCOVERY_PORT, GAME_PORT, NetSocket, Packet},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Lobby>();
+    app.init_resource::<DiscoveredGames>();
+    app.init_resource::<LobbyStatus>();
+    app.init_resource::<ChosenLevel>();
+
+    app.add_systems(OnEnter(Menu::Lobby), start_discovery);
+    app.add_systems(OnExit(Menu::Lobby), stop_discovery);
+    app.add_systems(
+        Update,
+        (
+            (
+                keep_in_touch.run_if(on_timer(SYNC_INTERVAL)),
+                find_games.run_if(resource_exists::<DiscoverySocket>),
+            )
+                .in_set(NetSystems::Send),
+            handle_lobby_messages.in_set(NetSystems::Receive),
+        )
+            .run_if(in_state(Menu::Lobby)),
+    );
+}
+
+/// The levels players can vote for.
+pub const LOBBY_LEVELS: [&str; 1] = ["level1"];
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+/// Games that haven't announced themselves for this long are forgotten.
+const DISCOVERY_TIMEOUT: f32 = 3.0;
+
+/// The players waiting to start a game.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lobby {
+    pub players: Vec<LobbyPlayer>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LobbyPlayer {
+    pub peer: PeerId,
+    pub name: String,
+    pub ready: bool,
+    pub vote: Option<String>,
+}
+
+impl Lobby {
+    pub fn player(&self, peer: PeerId) -> Option<&LobbyPlayer> {
+        self.players.iter().find(|player| player.peer == peer)
+    }
+
+    fn player_mut(&mut self, peer: PeerId) -> Option<&mut LobbyPlayer> {
+        self.players.iter_mut().find(|player| player.peer == peer)
+    }
+
+    pub fn all_ready(&self) -> bool {
+        !self.players.is_empty() && self.players.iter().all(|player| player.ready)
+    }
+
+    /// The level with the most votes. Ties go to the level listed first.
+    pub fn winning_level(&self) -> &'static str {
+        LOBBY_LEVELS
+            .iter()
+            .rev()
+            .max_by_key(|&&level| {
+                self.players
+                    .iter()
+                    .filter(|player| player.vote.as_deref() == Some(level))
+                    .count()
+            })
+            .copied()
+            .unwrap_or(LOBBY_LEVELS[0])
+    }
+}
+
+/// A game found on the LAN.
+#[derive(Clone, Debug)]
+pub struct DiscoveredGame {
+    pub address: SocketAddr,
+    pub name: String,
+    pub players: usize,
+    last_seen: f32,
+}
+
+/// Games currently announcing themselves on the LAN.
+#[derive(Resource, Default, Debug)]
+pub struct DiscoveredGames(pub Vec<DiscoveredGame>);
+
+/// What the lobby is doing, or what went wrong, for the lobby menu.
+#[derive(Resource, Default, Debug)]
+pub struct LobbyStatus(pub String);
+
+/// The level picked by the lobby vote for the current online game.
+#[derive(Resource, Default, Debug)]
+pub struct ChosenLevel(pub Option<String>);
+
+/// Listens for games announced on the LAN.
+#[derive(Resource)]
+struct DiscoverySocket(UdpSocket);
+
+fn start_discovery(mut commands: Commands, mut status: ResMut<LobbyStatus>) {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))
+        .and_then(|socket| socket.set_nonblocking(true).map(|()| socket));
+    match socket {
+        Ok(socket) => {
+            commands.insert_resource(DiscoverySocket(socket));
+            status.0 = "Looking for games on the LAN...".to_string();
+        }
+        Err(error) => {
+            status.0 = format!("Can't browse LAN games ({error}). You can still join by address.")
+        }
+    }
+}
+
+fn stop_discovery(mut commands: Commands, mut games: ResMut<DiscoveredGames>) {
+    commands.remove_resource::<DiscoverySocket>();
+    games.0.clear();
+}
+
+fn new_session(world: &mut World, name: &str) -> PeerId {
+    let local = PeerId(rand::random());
+    let mut session = world.resource_mut::<NetSession>();
+    session.local = local;
+    session.host = local;
+    session.names.clear();
+    session.names.insert(local, name.to_string());
+    local
+}
+
+/// Start hosting a game that others can find and join.
+pub fn host_game(world: &mut World, name: &str) {
+    leave_game(world);
+    let socket = match NetSocket::host(GAME_PORT) {
+        Ok(socket) => socket,
+        Err(error) => {
+            world.resource_mut::<LobbyStatus>().0 =
+                format!("Could not host on port {GAME_PORT}: {error}");
+            return;
+        }
+    };
+    let local = new_session(world, name);
+    world.insert_resource(socket);
+    world.resource_mut::<Lobby>().players = vec![LobbyPlayer {
+        peer: local,
+        name: name.to_string(),
+        ready: false,
+        vote: None,
+    }];
+    world.resource_mut::<LobbyStatus>().0 = format!("Hosting on port {GAME_PORT}.");
+}
+
+/// Join the game hosted at `address`.
+pub fn join_game(world: &mut World, address: SocketAddr, name: &str) {
+    leave_game(world);
+    let socket = match NetSocket::join(address) {
+        Ok(socket) => socket,
+        Err(error) => {
+            world.resource_mut::<LobbyStatus>().0 = format!("Could not join {address}: {error}");
+            return;
+        }
+    };
+    new_session(world, name);
+    world.insert_resource(socket);
+    world.resource_mut::<LobbyStatus>().0 = format!("Joining {address}...");
+    world.send_event(SendMessage(NetMessage::Hello {
+        name: name.to_string(),
+    }));
+}
+
+/// Stop hosting or leave the joined game.
+pub fn leave_game(world: &mut World) {
+    world.remove_resource::<NetSocket>();
+    *world.resource_mut::<Lobby>() = Lobby::default();
+    *world.resource_mut::<NetSession>() = NetSession::default();
+    world.resource_mut::<ChosenLevel>().0 = None;
+}
+
+/// Hosts announce their game and send the lobby to everyone. Players that
+/// haven't shown up in the lobby yet say hello again, in case it got lost.
+fn keep_in_touch(
+    socket: Option<Res<NetSocket>>,
+    session: Res<NetSession>,
+    lobby: Res<Lobby>,
+    mut sent: EventWriter<SendMessage>,
+) {
+    let Some(socket) = socket else {
+        return;
+    };
+    // Until the host's lobby arrives, a joining player's session still names
+    // them as the host, so ask the socket.
+    if socket.is_hosting() {
+        socket.announce(session.name(session.local), lobby.players.len());
+        sent.write(SendMessage(NetMessage::Lobby(lobby.clone())));
+    } else if lobby.player(session.local).is_none() {
+        sent.write(SendMessage(NetMessage::Hello {
+            name: session.name(session.local).to_string(),
+        }));
+    }
+}
+
+fn find_games(time: Res<Time>, socket: Res<DiscoverySocket>, mut games: ResMut<DiscoveredGames>) {
+    let now = time.elapsed_secs();
+    let mut buffer = [0; 1024];
+    while let Ok((length, from)) = socket.0.recv_from(&mut buffer) {
+        let Some(Packet::Announce {
+            name,
+            players,
+            port,
+        }) = Packet::decode(&buffer[..length])
+        else {
+            continue;
+        };
+        let address = SocketAddr::new(from.ip(), port);
+        let game = DiscoveredGame {
+            address,
+            name,
+            players,
+            last_seen: now,
+        };
+        match games.0.iter_mut().find(|game| game.address == address) {
+            Some(known) => *known = game,
+            None => games.0.push(game),
+        }
+    }
+    games
+        .0
+        .retain(|game| now - game.last_seen < DISCOVERY_TIMEOUT);
+}
+
+fn handle_lobby_messages(
+    socket: Option<Res<NetSocket>>,
+    mut received: EventReader<ReceivedMessage>,
+    mut session: ResMut<NetSession>,
+    mut lobby: ResMut<Lobby>,
+    mut status: ResMut<LobbyStatus>,
+    mut chosen_level: ResMut<ChosenLevel>,
+    mut sent: EventWriter<SendMessage>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let is_host = socket.is_none_or(|socket| socket.is_hosting());
+    let mut changed = false;
+    for ReceivedMessage { from, message } in received.read() {
+        match message {
+            NetMessage::Hello { name } if is_host && lobby.player(*from).is_none() => {
+                lobby.players.push(LobbyPlayer {
+                    peer: *from,
+                    name: name.clone(),
+                    ready: false,
+                    vote: None,
+                });
+                session.names.insert(*from, name.clone());
+                changed = true;
+            }
+            NetMessage::Ready(ready) if is_host => {
+                if let Some(player) = lobby.player_mut(*from) {
+                    player.ready = *ready;
+                    changed = true;
+                }
+            }
+            NetMessage::Vote(level) if is_host => {
+                if let Some(player) = lobby.player_mut(*from) {
+                    player.vote = Some(level.clone());
+                    changed = true;
+                }
+            }
+            NetMessage::Lobby(state) if !is_host => {
+                *lobby = state.clone();
+                session.host = *from;
+                session.names = lobby
+                    .players
+                    .iter()
+                    .map(|player| (player.peer, player.name.clone()))
+                    .collect();
+                status.0 = format!("In {}'s game.", session.name(*from));
+            }
+            NetMessage::Start { level } if *from == session.host => {
+                chosen_level.0 = Some(level.clone());
+                if resource_handles.is_all_done() {
+                    next_screen.set(Screen::Gameplay);
+                } else {
+                    next_screen.set(Screen::Loading);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !changed {
+        return;
+    }
+    sent.write(SendMessage(NetMessage::Lobby(lobby.clone())));
+    if lobby.all_ready() {
+        sent.write(SendMessage(NetMessage::Start {
+            level: lobby.winning_level().to_string(),
+        }));
+    }
+}
@@ -0,0 +1,149 @@
+//! Online sessions. Only compiled with the `net` feature.
+//!
+//! Gameplay code talks to other players by writing [`SendMessage`] events and
+//! reading [`ReceivedMessage`] events. Sent messages are also delivered back
+//! to the local player, so everything works the same offline.
+
+pub mod chat;
+pub mod lobby;
+pub mod session;
+pub mod transport;
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::taunt::Taunt;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PeerId>();
+    app.init_resource::<NetSession>();
+    app.add_event::<SendMessage>();
+    app.add_event::<ReceivedMessage>();
+
+    app.add_plugins((
+        chat::plugin,
+        lobby::plugin,
+        session::plugin,
+        transport::plugin,
+    ));
+
+    app.add_systems(
+        Update,
+        loopback.after(NetSystems::Send).before(NetSystems::Receive),
+    );
+    app.configure_sets(Update, (NetSystems::Send, NetSystems::Receive).chain());
+}
+
+/// Messages are sent before received messages are handled, so loopback
+/// messages arrive in the same frame.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum NetSystems {
+    Send,
+    Receive,
+}
+
+/// Identifies a player in an online session.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug, Reflect, Serialize, Deserialize,
+)]
+pub struct PeerId(pub u64);
+
+/// Who is in the current session.
+#[derive(Resource, Debug)]
+pub struct NetSession {
+    pub local: PeerId,
+    /// The player whose game state is authoritative.
+    pub host: PeerId,
+    /// Display names of every player, including the local one.
+    pub names: BTreeMap<PeerId, String>,
+}
+
+impl Default for NetSession {
+    fn default() -> Self {
+        let local = PeerId(0);
+        Self {
+            local,
+            host: local,
+            names: BTreeMap::from([(local, "Player".to_string())]),
+        }
+    }
+}
+
+impl NetSession {
+    pub fn is_host(&self) -> bool {
+        self.host == self.local
+    }
+
+    pub fn name(&self, peer: PeerId) -> &str {
+        self.names.get(&peer).map_or("Unknown", String::as_str)
+    }
+
+    /// Look up a player by display name, ignoring case.
+    pub fn find(&self, name: &str) -> Option<PeerId> {
+        self.names
+            .iter()
+            .find(|(_, peer_name)| peer_name.eq_ignore_ascii_case(name))
+            .map(|(&peer, _)| peer)
+    }
+}
+
+/// Everything players can send each other.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NetMessage {
+    Chat(String),
+    Emote(chat::Emote),
+    Taunt(Taunt),
+    /// Sent regularly so others can tell we're still connected.
+    Heartbeat,
+    /// The host's latest state, sent when a player reconnects.
+    Resync(session::Snapshot),
+    /// Asks the host to be let into its lobby.
+    Hello {
+        name: String,
+    },
+    /// The host's lobby, sent whenever it changes.
+    Lobby(lobby::Lobby),
+    Ready(bool),
+    Vote(String),
+    /// Sent by the host once everyone is ready.
+    Start {
+        level: String,
+    },
+}
+
+impl NetMessage {
+    /// Whether only the host may send this. Players never pass these on.
+    pub fn host_only(&self) -> bool {
+        matches!(
+            self,
+            NetMessage::Resync(_) | NetMessage::Lobby(_) | NetMessage::Start { .. }
+        )
+    }
+}
+
+/// Send a message to every other player in the session.
+#[derive(Event, Clone, Debug)]
+pub struct SendMessage(pub NetMessage);
+
+/// A message that arrived from a player.
+#[derive(Event, Clone, Debug)]
+pub struct ReceivedMessage {
+    pub from: PeerId,
+    pub message: NetMessage,
+}
+
+/// Deliver sent messages back to the local player.
+fn loopback(
+    session: Res<NetSession>,
+    mut sent: EventReader<SendMessage>,
+    mut received: EventWriter<ReceivedMessage>,
+) {
+    for SendMessage(message) in sent.read() {
+        received.write(ReceivedMessage {
+            from: session.local,
+            message: message.clone(),
+        });
+    }
+}
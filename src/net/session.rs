@@ -0,0 +1,417 @@
+//! Surviving disconnects in online sessions.
+//!
+//! Every player sends a heartbeat a few times per second. When a player goes
+//! quiet, the session pauses and waits for them to come back. If they do, the
+//! host resends the most recent state from its snapshot history so everyone
+//! agrees again. If they don't, they are dropped from the session and play
+//! continues (single-player if nobody is left).
+//!
+//! Players only hear each other through the host, so when the host goes quiet
+//! everyone else does too. Then a new host is picked straight away, and the
+//! old one is waited for like any other player.
+
+use std::collections::{HashMap, VecDeque};
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Pause,
+    game::gameplay_id::{GameplayId, GameplayIds},
+    net::{
+        NetMessage, NetSession, NetSystems, PeerId, ReceivedMessage, SendMessage, chat::ChatLog,
+        transport::NetSocket,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SessionSettings>();
+    app.init_resource::<PeerLinks>();
+    app.init_resource::<SnapshotHistory>();
+    app.register_type::<ReconnectText>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_reconnect_text);
+    app.add_systems(OnExit(Screen::Gameplay), clear_session_state);
+    app.add_systems(
+        FixedPostUpdate,
+        record_snapshot.run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        (
+            send_heartbeat.in_set(NetSystems::Send),
+            (
+                track_peers,
+                apply_resync,
+                check_timeouts,
+                update_reconnect_text,
+            )
+                .chain()
+                .in_set(NetSystems::Receive),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Timings for noticing and recovering from disconnects.
+#[derive(Resource, Debug)]
+pub struct SessionSettings {
+    pub heartbeat_interval: f32,
+    /// How long a player can stay quiet before they count as disconnected.
+    pub heartbeat_timeout: f32,
+    /// How long the session waits for a disconnected player, in seconds.
+    pub reconnect_window: f32,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: 0.25,
+            heartbeat_timeout: 3.0,
+            reconnect_window: 30.0,
+        }
+    }
+}
+
+/// When each other player was last heard from, and since when they've been
+/// gone if they have disconnected. Times are in seconds since startup.
+#[derive(Resource, Default, Debug)]
+pub struct PeerLinks(HashMap<PeerId, PeerLink>);
+
+#[derive(Copy, Clone, Debug)]
+struct PeerLink {
+    last_seen: f32,
+    disconnected_at: Option<f32>,
+}
+
+impl PeerLinks {
+    /// Players we are currently waiting on.
+    pub fn waiting(&self) -> impl Iterator<Item = (PeerId, f32)> + '_ {
+        self.0
+            .iter()
+            .filter_map(|(&peer, link)| link.disconnected_at.map(|at| (peer, at)))
+    }
+}
+
+/// The state of every entity with a [`GameplayId`] at one physics step.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub bodies: Vec<BodyState>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BodyState {
+    pub id: GameplayId,
+    pub position: Vec2,
+    pub rotation: f32,
+    pub velocity: Vec2,
+}
+
+/// Recent snapshots, oldest first. The host resyncs reconnecting players
+/// from the newest one.
+#[derive(Resource, Default, Debug)]
+pub struct SnapshotHistory(VecDeque<Snapshot>);
+
+/// About two seconds of physics steps.
+const SNAPSHOT_HISTORY_CAPACITY: usize = 128;
+
+impl SnapshotHistory {
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.0.back()
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ReconnectText;
+
+fn spawn_reconnect_text(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Reconnect Text"),
+        Text::default(),
+        TextFont::from_font_size(24.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            justify_self: JustifySelf::Center,
+            ..default()
+        },
+        GlobalZIndex(2),
+        ReconnectText,
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn clear_session_state(mut links: ResMut<PeerLinks>, mut history: ResMut<SnapshotHistory>) {
+    links.0.clear();
+    history.0.clear();
+}
+
+fn record_snapshot(
+    mut history: ResMut<SnapshotHistory>,
+    body_query: Query<(&GameplayId, &Transform, Option<&LinearVelocity>)>,
+) {
+    let mut bodies: Vec<BodyState> = body_query
+        .iter()
+        .map(|(&id, transform, velocity)| BodyState {
+            id,
+            position: transform.translation.truncate(),
+            rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+            velocity: velocity.map_or(Vec2::ZERO, |velocity| velocity.0),
+        })
+        .collect();
+    bodies.sort_unstable_by_key(|body| body.id);
+
+    if history.0.len() == SNAPSHOT_HISTORY_CAPACITY {
+        history.0.pop_front();
+    }
+    history.0.push_back(Snapshot { bodies });
+}
+
+fn send_heartbeat(
+    time: Res<Time>,
+    settings: Res<SessionSettings>,
+    mut since_last: Local<f32>,
+    mut sent: EventWriter<SendMessage>,
+) {
+    *since_last += time.delta_secs();
+    if *since_last >= settings.heartbeat_interval {
+        *since_last = 0.0;
+        sent.write(SendMessage(NetMessage::Heartbeat));
+    }
+}
+
+/// Note who we've heard from. Players coming back get resynced by the host.
+fn track_peers(
+    time: Res<Time>,
+    session: Res<NetSession>,
+    history: Res<SnapshotHistory>,
+    mut links: ResMut<PeerLinks>,
+    mut received: EventReader<ReceivedMessage>,
+    mut sent: EventWriter<SendMessage>,
+    mut log: ResMut<ChatLog>,
+    mut next_pause: ResMut<NextState<Pause>>,
+) {
+    let now = time.elapsed_secs();
+    for ReceivedMessage { from, .. } in received.read() {
+        if *from == session.local {
+            continue;
+        }
+        let link = links.0.entry(*from).or_insert(PeerLink {
+            last_seen: now,
+            disconnected_at: None,
+        });
+        link.last_seen = now;
+        if link.disconnected_at.take().is_none() {
+            continue;
+        }
+
+        log.notice(format!("{} reconnected.", session.name(*from)));
+        if let Some(snapshot) = history.latest().filter(|_| session.is_host()) {
+            sent.write(SendMessage(NetMessage::Resync(snapshot.clone())));
+        }
+        if links.waiting().next().is_none() {
+            next_pause.set(Pause(false));
+        }
+    }
+}
+
+/// Put everything back where the host says it is.
+fn apply_resync(
+    session: Res<NetSession>,
+    ids: Res<GameplayIds>,
+    mut received: EventReader<ReceivedMessage>,
+    mut body_query: Query<(&mut Transform, Option<&mut LinearVelocity>)>,
+) {
+    for ReceivedMessage { from, message } in received.read() {
+        let NetMessage::Resync(snapshot) = message else {
+            continue;
+        };
+        if *from != session.host || session.is_host() {
+            continue;
+        }
+        for body in &snapshot.bodies {
+            let Some((mut transform, velocity)) = ids
+                .entity(body.id)
+                .and_then(|entity| body_query.get_mut(entity).ok())
+            else {
+                continue;
+            };
+            transform.translation = body.position.extend(transform.translation.z);
+            transform.rotation = Quat::from_rotation_z(body.rotation);
+            if let Some(mut velocity) = velocity {
+                velocity.0 = body.velocity;
+            }
+        }
+    }
+}
+
+/// Pause while anyone is disconnected, and give up on players who don't come
+/// back in time.
+fn check_timeouts(
+    time: Res<Time>,
+    settings: Res<SessionSettings>,
+    mut session: ResMut<NetSession>,
+    mut links: ResMut<PeerLinks>,
+    mut socket: Option<ResMut<NetSocket>>,
+    mut log: ResMut<ChatLog>,
+    mut next_pause: ResMut<NextState<Pause>>,
+) {
+    let now = time.elapsed_secs();
+    let timed_out = |link: &PeerLink| {
+        link.disconnected_at.is_none() && now - link.last_seen > settings.heartbeat_timeout
+    };
+    let old_host = session.host;
+    if !session.is_host() && links.0.get(&old_host).is_some_and(timed_out) {
+        // Everyone else went quiet with the host, so don't count that
+        // against them. They'll be heard again through the new host.
+        session.host = session
+            .names
+            .keys()
+            .copied()
+            .find(|&peer| peer != old_host)
+            .unwrap_or(session.local);
+        if let Some(socket) = socket.as_mut() {
+            socket.follow_host(session.host, session.local);
+        }
+        log.notice(format!("{} is now hosting.", session.name(session.host)));
+        for (&peer, link) in &mut links.0 {
+            if peer != old_host {
+                link.last_seen = now;
+            }
+        }
+    }
+
+    let mut dropped = Vec::new();
+    for (&peer, link) in &mut links.0 {
+        match link.disconnected_at {
+            None if timed_out(link) => {
+                link.disconnected_at = Some(now);
+                log.notice(format!(
+                    "{} lost connection. Waiting for them to come back...",
+                    session.name(peer)
+                ));
+                next_pause.set(Pause(true));
+            }
+            Some(at) if now - at > settings.reconnect_window => dropped.push(peer),
+            _ => {}
+        }
+    }
+
+    for peer in dropped {
+        log.notice(format!("{} left the session.", session.name(peer)));
+        links.0.remove(&peer);
+        session.names.remove(&peer);
+        if let Some(socket) = socket.as_mut() {
+            socket.remove_peer(peer);
+        }
+        if session.names.len() == 1 {
+            log.notice("Everyone else left. Continuing in single-player.");
+        }
+        if links.waiting().next().is_none() {
+            next_pause.set(Pause(false));
+        }
+    }
+}
+
+fn update_reconnect_text(
+    time: Res<Time>,
+    settings: Res<SessionSettings>,
+    session: Res<NetSession>,
+    links: Res<PeerLinks>,
+    mut text: Single<&mut Text, With<ReconnectText>>,
+) {
+    let now = time.elapsed_secs();
+    let lines: Vec<String> = links
+        .waiting()
+        .map(|(peer, at)| {
+            let left = (settings.reconnect_window - (now - at)).max(0.0);
+            format!(
+                "Waiting for {} to reconnect ({left:.0}s)",
+                session.name(peer)
+            )
+        })
+        .collect();
+    text.0 = lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// A player in a session of three, hosted by someone else.
+    fn session_of_three() -> World {
+        let mut world = World::new();
+        world.insert_resource(Time::<()>::default());
+        world.init_resource::<SessionSettings>();
+        world.init_resource::<ChatLog>();
+        world.init_resource::<NextState<Pause>>();
+        world.insert_resource(NetSession {
+            local: PeerId(3),
+            host: PeerId(1),
+            names: [(1, "Host"), (2, "Ada"), (3, "Me")]
+                .map(|(peer, name)| (PeerId(peer), name.to_string()))
+                .into(),
+        });
+        let link = PeerLink {
+            last_seen: 0.0,
+            disconnected_at: None,
+        };
+        world.insert_resource(PeerLinks(HashMap::from([
+            (PeerId(1), link),
+            (PeerId(2), link),
+        ])));
+        world
+    }
+
+    fn wait(world: &mut World, seconds: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world.run_system_once(check_timeouts).unwrap();
+    }
+
+    #[test]
+    fn host_timing_out_hands_hosting_to_the_next_player() {
+        let mut world = session_of_three();
+        let settings = SessionSettings::default();
+
+        // Nobody's heard from anyone, since the host passes everything on.
+        wait(&mut world, settings.heartbeat_timeout + 0.1);
+        let session = world.resource::<NetSession>();
+        assert_eq!(session.host, PeerId(2));
+        let waiting: Vec<_> = world.resource::<PeerLinks>().waiting().collect();
+        assert_eq!(waiting.len(), 1);
+        assert_eq!(waiting[0].0, PeerId(1));
+
+        // The other player keeps talking through the new host, while the old
+        // one never comes back.
+        let mut time_left = settings.reconnect_window + 0.1;
+        while time_left > 0.0 {
+            let step = settings.heartbeat_interval;
+            let now = world.resource::<Time>().elapsed_secs() + step;
+            world
+                .resource_mut::<PeerLinks>()
+                .0
+                .get_mut(&PeerId(2))
+                .unwrap()
+                .last_seen = now;
+            wait(&mut world, step);
+            time_left -= step;
+        }
+
+        let session = world.resource::<NetSession>();
+        assert_eq!(session.host, PeerId(2));
+        assert_eq!(
+            session.names.keys().copied().collect::<Vec<_>>(),
+            [PeerId(2), PeerId(3)]
+        );
+        assert!(world.resource::<PeerLinks>().waiting().next().is_none());
+    }
+}
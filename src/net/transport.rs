@@ -0,0 +1,271 @@
+//! UDP transport for LAN sessions.
+//!
+//! Sessions are a star: the host talks to every player, and players only talk
+//! to the host, which forwards their messages to everyone else. Packets are
+//! small RON documents, which is plenty for chat, lobby and heartbeat traffic.
+//!
+//! Nothing in a packet can be trusted about who sent it, so the address it
+//! came from is checked instead. Players only listen to the host's address.
+//! The host ties each [`PeerId`] to the address it was first heard from,
+//! ignores it from anywhere else, and never passes on messages only the host
+//! may send.
+//!
+//! The host also shares where every player is, so that if it leaves, the
+//! players know where to find whoever hosts next.
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    time::Duration,
+};
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use serde::{Deserialize, Serialize};
+
+use crate::net::{NetMessage, NetSession, NetSystems, PeerId, ReceivedMessage, SendMessage};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (send_packets, receive_packets)
+            .chain()
+            .after(NetSystems::Send)
+            .before(NetSystems::Receive)
+            .run_if(resource_exists::<NetSocket>),
+    );
+    app.add_systems(
+        Update,
+        share_peers
+            .in_set(NetSystems::Send)
+            .run_if(resource_exists::<NetSocket>.and(on_timer(SHARE_INTERVAL))),
+    );
+}
+
+/// The port hosts listen on, unless a player asks for another one.
+pub const GAME_PORT: u16 = 7777;
+/// The port hosts announce their games on, for players browsing the LAN.
+pub const DISCOVERY_PORT: u16 = 7778;
+/// Larger than any packet we send.
+const MAX_PACKET_SIZE: usize = 64 * 1024;
+/// How often the host tells players where everyone is.
+const SHARE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Everything sent over the wire.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Packet {
+    /// Broadcast by hosts so players on the LAN can find them.
+    Announce {
+        name: String,
+        players: usize,
+        port: u16,
+    },
+    Message {
+        from: PeerId,
+        message: NetMessage,
+    },
+    /// Sent by the host so players can reach each other if it leaves.
+    Peers(Vec<(PeerId, SocketAddr)>),
+}
+
+impl Packet {
+    pub fn encode(&self) -> Option<Vec<u8>> {
+        ron::to_string(self).ok().map(String::into_bytes)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Packet> {
+        ron::de::from_bytes(bytes).ok()
+    }
+}
+
+/// The socket of an online session. Only exists while hosting or joined.
+#[derive(Resource, Debug)]
+pub struct NetSocket {
+    socket: UdpSocket,
+    /// Where the host is, if we joined someone else's game.
+    host: Option<SocketAddr>,
+    /// Where every player is. The host learns this from their packets, and
+    /// players from the host.
+    peers: HashMap<PeerId, SocketAddr>,
+}
+
+impl NetSocket {
+    /// Listen for players on `port`.
+    pub fn host(port: u16) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        socket.set_nonblocking(true)?;
+        socket.set_broadcast(true)?;
+        Ok(Self {
+            socket,
+            host: None,
+            peers: HashMap::new(),
+        })
+    }
+
+    /// Connect to the host at `address`.
+    pub fn join(address: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            host: Some(address),
+            peers: HashMap::new(),
+        })
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.socket.local_addr().ok().map(|address| address.port())
+    }
+
+    /// Tell players on the LAN about this game.
+    pub fn announce(&self, name: &str, players: usize) {
+        let Some(port) = self.port() else {
+            return;
+        };
+        let packet = Packet::Announce {
+            name: name.to_string(),
+            players,
+            port,
+        };
+        if let Some(bytes) = packet.encode() {
+            // Broadcasts fail on some networks. Direct joins still work then.
+            let _ = self
+                .socket
+                .send_to(&bytes, (Ipv4Addr::BROADCAST, DISCOVERY_PORT));
+        }
+    }
+
+    /// Whether this is the host's socket rather than a joined player's.
+    pub fn is_hosting(&self) -> bool {
+        self.host.is_none()
+    }
+
+    /// Forget a player, e.g. when they leave the session.
+    pub fn remove_peer(&mut self, peer: PeerId) {
+        self.peers.remove(&peer);
+    }
+
+    /// Talk to `new_host` from now on, after the old host left. If that's us,
+    /// start hosting for the players the old host told us about.
+    pub fn follow_host(&mut self, new_host: PeerId, local: PeerId) {
+        if new_host == local {
+            self.host = None;
+            self.peers.remove(&local);
+            return;
+        }
+        match self.peers.get(&new_host) {
+            Some(&address) => self.host = Some(address),
+            None => warn!("Don't know where the new host {new_host:?} is"),
+        }
+    }
+
+    fn send(&self, bytes: &[u8], to: SocketAddr) {
+        if let Err(error) = self.socket.send_to(bytes, to) {
+            warn!("Could not send packet to {to}: {error}");
+        }
+    }
+}
+
+/// Parse `ip` or `ip:port`, defaulting to [`GAME_PORT`].
+pub fn parse_address(text: &str) -> Option<SocketAddr> {
+    let text = text.trim();
+    text.parse()
+        .ok()
+        .or_else(|| format!("{text}:{GAME_PORT}").parse().ok())
+}
+
+fn send_packets(
+    socket: Res<NetSocket>,
+    session: Res<NetSession>,
+    mut sent: EventReader<SendMessage>,
+) {
+    for SendMessage(message) in sent.read() {
+        let packet = Packet::Message {
+            from: session.local,
+            message: message.clone(),
+        };
+        let Some(bytes) = packet.encode() else {
+            continue;
+        };
+        match socket.host {
+            Some(host) => socket.send(&bytes, host),
+            None => {
+                for &address in socket.peers.values() {
+                    socket.send(&bytes, address);
+                }
+            }
+        }
+    }
+}
+
+fn share_peers(socket: Res<NetSocket>) {
+    if !socket.is_hosting() || socket.peers.is_empty() {
+        return;
+    }
+    let packet = Packet::Peers(
+        socket
+            .peers
+            .iter()
+            .map(|(&peer, &address)| (peer, address))
+            .collect(),
+    );
+    let Some(bytes) = packet.encode() else {
+        return;
+    };
+    for &address in socket.peers.values() {
+        socket.send(&bytes, address);
+    }
+}
+
+fn receive_packets(
+    mut socket: ResMut<NetSocket>,
+    session: Res<NetSession>,
+    mut received: EventWriter<ReceivedMessage>,
+) {
+    let mut buffer = vec![0; MAX_PACKET_SIZE];
+    loop {
+        let (length, address) = match socket.socket.recv_from(&mut buffer) {
+            Ok(result) => result,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+            Err(error) => {
+                warn!("Could not receive packet: {error}");
+                break;
+            }
+        };
+        let (from, message) = match Packet::decode(&buffer[..length]) {
+            Some(Packet::Message { from, message }) => (from, message),
+            Some(Packet::Peers(peers)) if socket.host == Some(address) => {
+                socket.peers = peers.into_iter().collect();
+                continue;
+            }
+            _ => continue,
+        };
+
+        // Players only take messages from the host.
+        if let Some(host) = socket.host {
+            if address == host {
+                received.write(ReceivedMessage { from, message });
+            }
+            continue;
+        }
+
+        // As the host, remember where players are and pass their messages on.
+        // Nobody else gets to speak as us, as the host, or as another player.
+        if from == session.local || message.host_only() {
+            continue;
+        }
+        match socket.peers.get(&from) {
+            Some(&known) if known != address => continue,
+            Some(_) => {}
+            None => {
+                socket.peers.insert(from, address);
+            }
+        }
+        for (&peer, &peer_address) in &socket.peers {
+            if peer != from {
+                socket.send(&buffer[..length], peer_address);
+            }
+        }
+        received.write(ReceivedMessage { from, message });
+    }
+}
@@ -0,0 +1,273 @@
+//! A compact binary format for files where size matters.
+//!
+//! A packed file starts with a header: the [`MAGIC`] bytes, a byte saying
+//! what [`PackedKind`] of file it is and the format version of what's inside,
+//! as a little-endian `u32`. The rest is LZ4-compressed, with the size it
+//! unpacks to in front.
+//!
+//! Replays and the autosave are [`pack`]ed: encoded with postcard first, which
+//! is many times smaller than RON for the long lists of numbers they're made
+//! of. Levels are hand-edited and leave out whatever is at its default, which
+//! postcard can't express, so they keep their RON and only get it
+//! [`compress`]ed.
+//!
+//! Readers of these files tell packed files apart from the plain RON older
+//! builds wrote with [`is_packed`], and keep reading those.
+
+use std::fmt;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// What every packed file starts with.
+pub const MAGIC: [u8; 4] = *b"HKPK";
+/// The magic, the kind and the version.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4;
+/// The most a packed file may unpack to. Keeps a corrupt or hostile size
+/// from allocating the machine's memory away.
+const MAX_UNPACKED_LEN: usize = 64 * 1024 * 1024;
+
+/// What a packed file holds, so one kind isn't mistaken for another.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[repr(u8)]
+pub enum PackedKind {
+    Replay = 1,
+    Autosave = 2,
+    Level = 3,
+}
+
+impl PackedKind {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Self::Replay),
+            2 => Some(Self::Autosave),
+            3 => Some(Self::Level),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PackError {
+    /// The bytes don't start with the packed header.
+    NotPacked,
+    WrongKind {
+        expected: PackedKind,
+        found: Option<PackedKind>,
+    },
+    UnsupportedVersion {
+        found: u32,
+        expected: u32,
+    },
+    TooLarge(usize),
+    Decompress(lz4_flex::block::DecompressError),
+    Encoding(postcard::Error),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::NotPacked => write!(f, "not a packed file"),
+            PackError::WrongKind { expected, found } => match found {
+                Some(found) => write!(f, "expected a packed {expected:?}, found a {found:?}"),
+                None => write!(f, "expected a packed {expected:?}, found an unknown kind"),
+            },
+            PackError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "packed format version {found} is not supported, expected {expected}"
+            ),
+            PackError::TooLarge(len) => {
+                write!(f, "packed file claims to unpack to {len} bytes")
+            }
+            PackError::Decompress(error) => write!(f, "packed file is corrupt: {error}"),
+            PackError::Encoding(error) => write!(f, "packed contents are corrupt: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+impl From<lz4_flex::block::DecompressError> for PackError {
+    fn from(error: lz4_flex::block::DecompressError) -> Self {
+        PackError::Decompress(error)
+    }
+}
+
+impl From<postcard::Error> for PackError {
+    fn from(error: postcard::Error) -> Self {
+        PackError::Encoding(error)
+    }
+}
+
+/// Whether `bytes` are a packed file, as opposed to e.g. plain RON.
+pub fn is_packed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// The format version in a packed file's header.
+pub fn version(bytes: &[u8]) -> Option<u32> {
+    if !is_packed(bytes) {
+        return None;
+    }
+    let version_bytes = bytes.get(MAGIC.len() + 1..HEADER_LEN)?;
+    Some(u32::from_le_bytes(version_bytes.try_into().ok()?))
+}
+
+/// Compress `payload` behind a header for the `kind` and `version`.
+pub fn compress(kind: PackedKind, version: u32, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len() / 2);
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(kind as u8);
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend_from_slice(&lz4_flex::compress_prepend_size(payload));
+    bytes
+}
+
+/// The payload [`compress`]ed into `bytes`, as long as they're the `kind`
+/// and `version` expected.
+pub fn decompress(kind: PackedKind, version: u32, bytes: &[u8]) -> Result<Vec<u8>, PackError> {
+    if !is_packed(bytes) || bytes.len() < HEADER_LEN + 4 {
+        return Err(PackError::NotPacked);
+    }
+    let found = PackedKind::from_byte(bytes[MAGIC.len()]);
+    if found != Some(kind) {
+        return Err(PackError::WrongKind {
+            expected: kind,
+            found,
+        });
+    }
+    let found = self::version(bytes).unwrap_or_default();
+    if found != version {
+        return Err(PackError::UnsupportedVersion {
+            found,
+            expected: version,
+        });
+    }
+
+    let compressed = &bytes[HEADER_LEN..];
+    let mut len_bytes = [0; 4];
+    len_bytes.copy_from_slice(&compressed[..4]);
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    if len > MAX_UNPACKED_LEN {
+        return Err(PackError::TooLarge(len));
+    }
+    Ok(lz4_flex::decompress_size_prepended(compressed)?)
+}
+
+/// Encode `value` with postcard and [`compress`] it.
+pub fn pack<T: Serialize>(kind: PackedKind, version: u32, value: &T) -> Result<Vec<u8>, PackError> {
+    let payload = postcard::to_allocvec(value)?;
+    Ok(compress(kind, version, &payload))
+}
+
+/// The value [`pack`]ed into `bytes`.
+pub fn unpack<T: DeserializeOwned>(
+    kind: PackedKind,
+    version: u32,
+    bytes: &[u8],
+) -> Result<T, PackError> {
+    let payload = decompress(kind, version, bytes)?;
+    Ok(postcard::from_bytes(&payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Sample {
+        seed: u64,
+        name: String,
+        frames: Vec<(f32, f32)>,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            seed: 0xdead_beef,
+            name: "level one".to_string(),
+            frames: (0..500).map(|i| (i as f32, -(i as f32) * 0.5)).collect(),
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let bytes = pack(PackedKind::Replay, 3, &sample()).unwrap();
+        assert!(is_packed(&bytes));
+        assert_eq!(version(&bytes), Some(3));
+        let unpacked: Sample = unpack(PackedKind::Replay, 3, &bytes).unwrap();
+        assert_eq!(unpacked, sample());
+
+        let payload = b"(name: \"level one\")";
+        let bytes = compress(PackedKind::Level, 1, payload);
+        assert_eq!(decompress(PackedKind::Level, 1, &bytes).unwrap(), payload);
+    }
+
+    #[test]
+    fn rejects_the_wrong_kind() {
+        let mut bytes = pack(PackedKind::Replay, 1, &sample()).unwrap();
+        assert!(matches!(
+            unpack::<Sample>(PackedKind::Autosave, 1, &bytes),
+            Err(PackError::WrongKind {
+                expected: PackedKind::Autosave,
+                found: Some(PackedKind::Replay),
+            })
+        ));
+        bytes[MAGIC.len()] = 200;
+        assert!(matches!(
+            unpack::<Sample>(PackedKind::Replay, 1, &bytes),
+            Err(PackError::WrongKind { found: None, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let bytes = pack(PackedKind::Autosave, 2, &sample()).unwrap();
+        for expected in [1, 3] {
+            assert!(matches!(
+                unpack::<Sample>(PackedKind::Autosave, expected, &bytes),
+                Err(PackError::UnsupportedVersion { found: 2, expected: e }) if e == expected
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupt_files() {
+        let bytes = pack(PackedKind::Replay, 1, &sample()).unwrap();
+        assert!(matches!(
+            unpack::<Sample>(PackedKind::Replay, 1, b"(seed: 1)"),
+            Err(PackError::NotPacked)
+        ));
+        assert!(matches!(
+            unpack::<Sample>(PackedKind::Replay, 1, &bytes[..HEADER_LEN + 2]),
+            Err(PackError::NotPacked)
+        ));
+        assert!(matches!(
+            unpack::<Sample>(PackedKind::Replay, 1, &bytes[..bytes.len() / 2]),
+            Err(PackError::Decompress(_))
+        ));
+        // Compressed fine, but not a `Sample` inside.
+        let garbage = compress(PackedKind::Replay, 1, &[0xff; 3]);
+        assert!(matches!(
+            unpack::<Sample>(PackedKind::Replay, 1, &garbage),
+            Err(PackError::Encoding(_))
+        ));
+        // Flipping bits anywhere past the header never panics.
+        for i in HEADER_LEN..bytes.len() {
+            let mut corrupt = bytes.clone();
+            corrupt[i] ^= 0x5a;
+            let _ = unpack::<Sample>(PackedKind::Replay, 1, &corrupt);
+        }
+    }
+
+    #[test]
+    fn rejects_sizes_over_the_limit() {
+        let mut bytes = compress(PackedKind::Level, 1, b"small");
+        let too_large = (MAX_UNPACKED_LEN as u32 + 1).to_le_bytes();
+        bytes[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&too_large);
+        assert!(matches!(
+            decompress(PackedKind::Level, 1, &bytes),
+            Err(PackError::TooLarge(len)) if len == MAX_UNPACKED_LEN + 1
+        ));
+    }
+}
@@ -0,0 +1,319 @@
+//! Settings and level progress kept between sessions.
+//!
+//! Both are stored as RON through the [`ActivePlatform`], so they follow the
+//! player between machines where the platform syncs saves. They're loaded
+//! before the title screen, and saved again whenever they change.
+//! A missing save starts from the defaults, and so does a corrupt one, with a
+//! warning.
+//!
+//! [`Settings`] is where the audio module gets its volumes and the input
+//! module its bindings. Rebinding in the menus changes the [`InputMap`], which
+//! is copied back here to be saved.
+//!
+//! [`Progress`] keeps what was done in each completed run of a level (the
+//! collectibles, secrets and medals), which adds up to a completion
+//! percentage for the level and for the whole profile. It also has the
+//! achievements unlocked, the cosmetics picked with them, and the last
+//! version whose "What's new" the player has seen.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    game::{
+        calibration::CalibrationResult,
+        cosmetics::Cosmetics,
+        hook_ghosts::HookGhosts,
+        level::MAIN_LEVEL_PATH,
+        objectives::{LevelObjectives, Medal, Score},
+        performance::{HANDHELD_FRAME_CAP, running_on_handheld},
+        physics_quality::PhysicsQuality,
+        run_stats::RunStats,
+    },
+    input::{InputMap, InputPreset},
+    platform::ActivePlatform,
+    replay::{AttemptHooks, replaying},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Settings>();
+    app.init_resource::<Progress>();
+
+    // The initial state transition runs before `Startup`, so this is what
+    // gets the saves in before the title screen.
+    app.add_systems(PreStartup, load_saves);
+    app.add_systems(
+        Update,
+        (
+            store_bindings.run_if(resource_changed::<InputMap>),
+            save::<Settings>(SETTINGS_FILE)
+                .run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+            save::<Progress>(PROGRESS_FILE)
+                .run_if(resource_changed::<Progress>.and(not(resource_added::<Progress>))),
+        )
+            .chain(),
+    );
+    app.add_systems(
+        OnEnter(Screen::Results),
+        record_completion.run_if(not(replaying)),
+    );
+}
+
+const SETTINGS_FILE: &str = "settings.ron";
+const PROGRESS_FILE: &str = "progress.ron";
+
+/// The player's preferences.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Scales every sound, linearly.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// Whether the screen may shake. Nothing shakes it yet, but effects that
+    /// do should check this.
+    pub screen_shake: bool,
+    /// Whether the camera is kicked a pixel or two when a hook attaches or
+    /// the player lands, for players without rumble.
+    pub screen_nudge: bool,
+    /// How strong felt feedback is, from 0 to 1. Scales the screen nudge,
+    /// and is meant for rumble too once there is any.
+    pub feedback_intensity: f32,
+    pub physics_quality: PhysicsQuality,
+    /// Whose hooks to mark in the level, for planning a route.
+    pub hook_ghosts: HookGhosts,
+    /// Whether chains can grab the partner left behind, in levels with two
+    /// characters. Off, the partner stays put wherever they're left.
+    pub partner_hooking: bool,
+    /// Whether the view is kept to the designed aspect ratio, with bars
+    /// around it.
+    pub letterbox: bool,
+    /// How far HUD elements are kept in from the edges of the screen, as a
+    /// fraction of its size, for TVs that cut the edges off.
+    pub safe_area: f32,
+    /// Whether the handheld preset is on. Set with [`Settings::set_handheld`],
+    /// which changes the options it's made of too.
+    pub handheld: bool,
+    /// How many particles effects throw, as a fraction of the full amount.
+    pub particle_density: f32,
+    /// The most frames drawn per second, if capped.
+    pub frame_cap: Option<u32>,
+    /// Whether the level is drawn at a lower resolution while frames take
+    /// too long to keep up.
+    pub dynamic_resolution: bool,
+    pub bindings: InputMap,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let mut settings = Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            screen_shake: true,
+            screen_nudge: false,
+            feedback_intensity: 1.0,
+            physics_quality: PhysicsQuality::default(),
+            hook_ghosts: HookGhosts::default(),
+            partner_hooking: true,
+            letterbox: false,
+            safe_area: 0.0,
+            handheld: false,
+            particle_density: 1.0,
+            frame_cap: None,
+            dynamic_resolution: true,
+            bindings: InputMap::default(),
+        };
+        if running_on_handheld() {
+            settings.set_handheld(true);
+        }
+        settings
+    }
+}
+
+impl Settings {
+    /// Turn the handheld preset on or off, setting the options it's made of
+    /// to suit. They can still be changed one by one afterwards.
+    pub fn set_handheld(&mut self, handheld: bool) {
+        self.handheld = handheld;
+        if handheld {
+            self.physics_quality = PhysicsQuality::Low;
+            self.particle_density = 0.5;
+            self.frame_cap = Some(HANDHELD_FRAME_CAP);
+        } else {
+            self.physics_quality = PhysicsQuality::default();
+            self.particle_density = 1.0;
+            self.frame_cap = None;
+        }
+    }
+
+    /// How many of `count` particles to throw at the current density. Bursts
+    /// keep at least one, so nothing goes by without any.
+    pub fn particles(&self, count: usize) -> usize {
+        ((count as f32 * self.particle_density).round() as usize).max(1)
+    }
+}
+
+/// How far the player has got in each level, by level path.
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct Progress {
+    pub levels: BTreeMap<String, LevelProgress>,
+    /// The IDs of the achievements unlocked, whether or not a storefront
+    /// heard about them.
+    pub achievements: BTreeSet<String>,
+    pub cosmetics: Cosmetics,
+    /// How the player did on the calibration course, if they've done it.
+    pub calibration: Option<CalibrationResult>,
+    /// The last version whose "What's new" was shown.
+    pub seen_changelog: Option<String>,
+}
+
+impl Progress {
+    /// How much of every level played so far has been done, from 0 to 1.
+    /// Levels count by how much there is to do in them.
+    pub fn completion(&self) -> f32 {
+        let (done, total) = self.levels.values().fold((0, 0), |(done, total), level| {
+            (done + level.done(), total + level.totals.total())
+        });
+        if total == 0 {
+            0.0
+        } else {
+            done as f32 / total as f32
+        }
+    }
+}
+
+/// What the player has done in a level. Everything is kept from the best of
+/// every completed run, so it never goes down.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct LevelProgress {
+    pub completed: bool,
+    /// The fastest completion, in seconds.
+    pub best_time: Option<f32>,
+    /// Whether assists were on for the fastest completion.
+    pub best_time_assisted: bool,
+    /// Where hooks attached on the fastest completion, in order. Empty if it
+    /// wasn't recorded, like a run continued from a checkpoint.
+    pub best_hooks: Vec<Vec2>,
+    /// The indices of the collectibles picked up in any completed run.
+    pub collectibles: BTreeSet<usize>,
+    /// The indices of the secrets found in any completed run.
+    pub secrets: BTreeSet<usize>,
+    pub medals: BTreeSet<Medal>,
+    /// How much there is to do in the level, as of the last completed run.
+    pub totals: LevelTotals,
+}
+
+impl LevelProgress {
+    /// How much of the level has been done, from 0 to 1.
+    pub fn completion(&self) -> f32 {
+        self.done() as f32 / self.totals.total() as f32
+    }
+
+    /// How many things to do in the level have been done. Things since taken
+    /// out of the level don't count.
+    fn done(&self) -> usize {
+        let found = |indices: &BTreeSet<usize>, total: usize| indices.range(..total).count();
+        usize::from(self.completed)
+            + found(&self.collectibles, self.totals.collectibles)
+            + found(&self.secrets, self.totals.secrets)
+            + self.medals.len().min(self.totals.medals)
+    }
+}
+
+/// How many collectibles, secrets and medals a level has.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug)]
+#[serde(default)]
+pub struct LevelTotals {
+    pub collectibles: usize,
+    pub secrets: usize,
+    pub medals: usize,
+}
+
+impl LevelTotals {
+    /// How many things there are to do, counting reaching the goal.
+    fn total(&self) -> usize {
+        1 + self.collectibles + self.secrets + self.medals
+    }
+}
+
+fn load_saves(
+    mut commands: Commands,
+    platform: Res<ActivePlatform>,
+    mut input_map: ResMut<InputMap>,
+) {
+    let mut settings: Settings = load(&platform, SETTINGS_FILE);
+    // Bindings saved from a preset pick up actions added since.
+    if settings.bindings.preset != InputPreset::Custom {
+        settings.bindings = InputMap::from_preset(settings.bindings.preset);
+    }
+    *input_map = settings.bindings.clone();
+    commands.insert_resource(settings);
+    commands.insert_resource(load::<Progress>(&platform, PROGRESS_FILE));
+}
+
+/// Read `name` from the platform's saves, or the default if there's nothing
+/// usable there.
+fn load<T: DeserializeOwned + Default>(platform: &ActivePlatform, name: &str) -> T {
+    let Ok(data) = platform.0.read_save(name) else {
+        return T::default();
+    };
+    ron::de::from_bytes(&data).unwrap_or_else(|error| {
+        warn!("Ignoring corrupt save {name}: {error}");
+        T::default()
+    })
+}
+
+fn save<T: Resource + Serialize>(name: &'static str) -> impl Fn(Res<T>, Res<ActivePlatform>) {
+    move |value: Res<T>, platform: Res<ActivePlatform>| {
+        let result = ron::ser::to_string_pretty(&*value, default())
+            .map_err(|error| error.to_string())
+            .and_then(|ron| {
+                platform
+                    .0
+                    .write_save(name, ron.as_bytes())
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!("Couldn't save {name}: {error}");
+        }
+    }
+}
+
+fn store_bindings(input_map: Res<InputMap>, mut settings: ResMut<Settings>) {
+    if settings.bindings != *input_map {
+        settings.bindings = input_map.clone();
+    }
+}
+
+fn record_completion(
+    stats: Res<RunStats>,
+    hooks: Res<AttemptHooks>,
+    score: Res<Score>,
+    objectives: Res<LevelObjectives>,
+    mut progress: ResMut<Progress>,
+) {
+    let level = progress
+        .levels
+        .entry(MAIN_LEVEL_PATH.to_string())
+        .or_default();
+    level.completed = true;
+    if level.best_time.is_none_or(|best| stats.time < best) {
+        level.best_time = Some(stats.time);
+        level.best_time_assisted = score.assisted;
+        level.best_hooks = hooks.0.clone();
+    }
+    level.collectibles.extend(&score.collected);
+    level.secrets.extend(&score.secrets);
+    level.medals.extend(objectives.medals_won(&stats));
+    level.totals = LevelTotals {
+        collectibles: objectives.collectibles,
+        secrets: objectives.secrets,
+        medals: objectives.medals(),
+    };
+}
@@ -0,0 +1,139 @@
+//! Storefront integrations: achievements, cloud saves and rich presence.
+//!
+//! Game code talks to whichever [`Platform`] is active through the
+//! [`ActivePlatform`] resource. Without a storefront, [`Offline`] keeps saves
+//! in a local folder and ignores the rest, so the game builds and runs the
+//! same everywhere. With the `steam` feature, Steam is used when the game was
+//! started through it.
+
+#[cfg(feature = "steam")]
+pub mod steam;
+
+use std::{fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        chain::{ChainConfig, LINK_SIZE},
+        objectives::{LevelObjectives, Score},
+        run_stats::RunStats,
+        secret::SecretFound,
+    },
+    persistence::Progress,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(ActivePlatform(Box::new(Offline)));
+    #[cfg(feature = "steam")]
+    app.add_plugins(steam::plugin);
+
+    app.add_systems(OnEnter(Screen::Title), set_presence("In the menus"));
+    app.add_systems(OnEnter(Screen::Gameplay), set_presence("Swinging around"));
+    app.add_systems(
+        Update,
+        (unlock_run_achievements, unlock_secret_achievements).run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Achievement IDs, as set up in the storefront.
+pub const FIRST_HOOK: &str = "FIRST_HOOK";
+pub const FULL_LENGTH: &str = "FULL_LENGTH";
+pub const FIRST_SECRET: &str = "FIRST_SECRET";
+pub const ALL_SECRETS: &str = "ALL_SECRETS";
+
+/// A storefront the game can report to.
+pub trait Platform: Send + Sync {
+    /// Unlock an achievement. Unlocking one twice does nothing.
+    fn unlock_achievement(&self, id: &str);
+    /// Save `data` as `name`, synced between machines where supported.
+    fn write_save(&self, name: &str, data: &[u8]) -> io::Result<()>;
+    fn read_save(&self, name: &str) -> io::Result<Vec<u8>>;
+    /// Show what the player is doing to their friends.
+    fn set_presence(&self, status: &str);
+}
+
+#[derive(Resource)]
+pub struct ActivePlatform(pub Box<dyn Platform>);
+
+/// No storefront: saves go to a local folder.
+pub struct Offline;
+
+impl Offline {
+    fn save_path(name: &str) -> PathBuf {
+        PathBuf::from("saves").join(name)
+    }
+}
+
+impl Platform for Offline {
+    fn unlock_achievement(&self, id: &str) {
+        info!("Achievement unlocked: {id}");
+    }
+
+    fn write_save(&self, name: &str, data: &[u8]) -> io::Result<()> {
+        let path = Self::save_path(name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, data)
+    }
+
+    fn read_save(&self, name: &str) -> io::Result<Vec<u8>> {
+        fs::read(Self::save_path(name))
+    }
+
+    fn set_presence(&self, _status: &str) {}
+}
+
+fn set_presence(status: &'static str) -> impl Fn(Res<ActivePlatform>) {
+    move |platform: Res<ActivePlatform>| platform.0.set_presence(status)
+}
+
+fn unlock_run_achievements(
+    stats: Res<RunStats>,
+    config: Res<ChainConfig>,
+    platform: Res<ActivePlatform>,
+    mut progress: ResMut<Progress>,
+    mut hooks_seen: Local<u32>,
+) {
+    // Only firing a hook can unlock anything.
+    if stats.hooks_fired == *hooks_seen {
+        return;
+    }
+    *hooks_seen = stats.hooks_fired;
+
+    if stats.hooks_fired == 1 {
+        unlock(&platform, &mut progress, FIRST_HOOK);
+    }
+    // A full-length chain spans one link less than its length.
+    if stats.longest_chain >= config.max_length() - LINK_SIZE {
+        unlock(&platform, &mut progress, FULL_LENGTH);
+    }
+}
+
+fn unlock_secret_achievements(
+    mut found: EventReader<SecretFound>,
+    score: Res<Score>,
+    objectives: Res<LevelObjectives>,
+    platform: Res<ActivePlatform>,
+    mut progress: ResMut<Progress>,
+) {
+    if found.read().count() == 0 {
+        return;
+    }
+    unlock(&platform, &mut progress, FIRST_SECRET);
+    if score.secrets.len() == objectives.secrets {
+        unlock(&platform, &mut progress, ALL_SECRETS);
+    }
+}
+
+/// Unlock an achievement on the platform, and keep it in the progress, where
+/// the wardrobe looks for it.
+fn unlock(platform: &ActivePlatform, progress: &mut ResMut<Progress>, id: &str) {
+    platform.0.unlock_achievement(id);
+    // Only changed progress gets saved.
+    if !progress.achievements.contains(id) {
+        progress.achievements.insert(id.to_string());
+    }
+}
@@ -0,0 +1,59 @@
+//! Steam, through the `steamworks` bindings. Only compiled with the `steam`
+//! feature.
+//!
+//! If Steam isn't running, or the game wasn't started through it, the game
+//! carries on with the [`Offline`](super::Offline) platform.
+
+use std::io::{self, Read, Write};
+
+use bevy::prelude::*;
+use steamworks::{Client, SingleClient};
+
+use crate::platform::{ActivePlatform, Platform};
+
+pub(super) fn plugin(app: &mut App) {
+    let (client, single) = match Client::init() {
+        Ok(clients) => clients,
+        Err(error) => {
+            info!("Steam isn't available, playing offline: {error}");
+            return;
+        }
+    };
+    app.insert_resource(ActivePlatform(Box::new(Steam(client))));
+    // Steam callbacks have to be run from the thread that created them.
+    app.insert_non_send_resource(single);
+    app.add_systems(PreUpdate, run_steam_callbacks);
+}
+
+pub struct Steam(Client);
+
+impl Platform for Steam {
+    fn unlock_achievement(&self, id: &str) {
+        let stats = self.0.user_stats();
+        if stats.achievement(id).set().is_ok() {
+            let _ = stats.store_stats();
+        }
+    }
+
+    fn write_save(&self, name: &str, data: &[u8]) -> io::Result<()> {
+        self.0.remote_storage().file(name).write().write_all(data)
+    }
+
+    fn read_save(&self, name: &str) -> io::Result<Vec<u8>> {
+        let file = self.0.remote_storage().file(name);
+        if !file.exists() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let mut data = Vec::new();
+        file.read().read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn set_presence(&self, status: &str) {
+        self.0.friends().set_rich_presence("status", Some(status));
+    }
+}
+
+fn run_steam_callbacks(single: NonSend<SingleClient>) {
+    single.run_callbacks();
+}
@@ -0,0 +1,224 @@
+//! Drawing the level at less than the window's resolution when frames take
+//! too long.
+//!
+//! The main camera draws into an offscreen image, which a second camera
+//! stretches over the window before drawing the UI on top at the window's own
+//! resolution. So only the level gets blurrier, and text stays sharp.
+//!
+//! With [`Settings::dynamic_resolution`] on, frame times are compared against
+//! the budget for the frame cap, or for [`DEFAULT_TARGET_FPS`] without one.
+//! After [`DOWNSCALE_AFTER`] seconds over it, the [`RenderScale`] steps down,
+//! as far as [`MIN_SCALE`]. After a while under it, the scale steps back up,
+//! waiting twice as long each time it had to come back down, so a scene that
+//! only just fits doesn't flicker between resolutions.
+
+use bevy::{
+    math::FloatOrd,
+    prelude::*,
+    render::{
+        camera::{ImageRenderTarget, RenderTarget},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        view::RenderLayers,
+    },
+    window::{PrimaryWindow, WindowRef},
+};
+
+use crate::{persistence::Settings, theme::layout::letterbox};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PresentCamera>();
+    app.init_resource::<RenderScale>();
+
+    app.add_systems(Startup, spawn_present_camera);
+    app.add_systems(
+        Update,
+        (draw_offscreen, adjust_render_scale, size_offscreen_image).chain(),
+    );
+}
+
+/// The frame rate aimed for without a frame cap.
+pub const DEFAULT_TARGET_FPS: u32 = 60;
+/// The lowest fraction of the window's resolution the level is drawn at.
+pub const MIN_SCALE: f32 = 0.5;
+const SCALE_STEP: f32 = 0.1;
+/// Seconds over budget before the scale steps down.
+pub const DOWNSCALE_AFTER: f32 = 0.5;
+/// Seconds under budget before the scale first steps back up.
+const UPSCALE_AFTER: f32 = 3.0;
+const MAX_UPSCALE_WAIT: f32 = 30.0;
+/// Frames this much over budget count as over it. Some slack keeps
+/// occasional hitches from counting.
+const OVER_BUDGET: f32 = 1.15;
+/// How much each frame moves the average frame time.
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+/// The layer the present camera sees, which nothing in the level is on.
+const PRESENT_LAYER: usize = 31;
+
+/// The fraction of the window's resolution the level is drawn at.
+#[derive(Resource, Debug)]
+pub struct RenderScale(pub f32);
+
+impl Default for RenderScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// The camera drawing the offscreen image of the level, and the UI, into the
+/// window. It's a 3D camera so that queries for the 2D camera still only
+/// find the main one.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct PresentCamera;
+
+/// What the main camera draws into.
+#[derive(Resource)]
+struct OffscreenImage(Handle<Image>);
+
+fn spawn_present_camera(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let mut image = Image::new_fill(
+        Extent3d::default(),
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+
+    commands.spawn((
+        Name::new("Present Camera"),
+        PresentCamera,
+        Camera3d::default(),
+        Camera {
+            order: 1,
+            clear_color: ClearColorConfig::Custom(Color::BLACK),
+            ..default()
+        },
+        // The UI is drawn by this camera, at full resolution.
+        IsDefaultUiCamera,
+        RenderLayers::layer(PRESENT_LAYER),
+    ));
+    commands.spawn((
+        Name::new("Offscreen Image"),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        ImageNode::new(image.clone()),
+        // Behind every other bit of UI.
+        GlobalZIndex(i32::MIN),
+        Pickable::IGNORE,
+    ));
+    commands.insert_resource(OffscreenImage(image));
+}
+
+/// Send the main camera's drawing to the offscreen image instead of the
+/// window.
+fn draw_offscreen(
+    image: Res<OffscreenImage>,
+    mut camera_query: Query<&mut Camera, (With<Camera2d>, Without<PresentCamera>)>,
+) {
+    for mut camera in &mut camera_query {
+        if matches!(camera.target, RenderTarget::Window(WindowRef::Primary)) {
+            camera.target = RenderTarget::Image(image.0.clone().into());
+        }
+    }
+}
+
+fn adjust_render_scale(
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    mut scale: ResMut<RenderScale>,
+    mut average: Local<Option<f32>>,
+    mut over: Local<f32>,
+    mut under: Local<f32>,
+    mut upscale_wait: Local<Option<f32>>,
+) {
+    if !settings.dynamic_resolution {
+        if scale.0 != 1.0 {
+            scale.0 = 1.0;
+        }
+        return;
+    }
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    let frame_time = average.map_or(dt, |average| average.lerp(dt, FRAME_TIME_SMOOTHING));
+    *average = Some(frame_time);
+    let budget = 1.0 / settings.frame_cap.unwrap_or(DEFAULT_TARGET_FPS) as f32;
+    let wait = upscale_wait.get_or_insert(UPSCALE_AFTER);
+
+    if frame_time > budget * OVER_BUDGET {
+        *over += dt;
+        *under = 0.0;
+        if *over >= DOWNSCALE_AFTER && scale.0 > MIN_SCALE {
+            scale.0 = (scale.0 - SCALE_STEP).max(MIN_SCALE);
+            *over = 0.0;
+            *wait = (*wait * 2.0).min(MAX_UPSCALE_WAIT);
+        }
+    } else {
+        *under += dt;
+        *over = 0.0;
+        if *under >= *wait && scale.0 < 1.0 {
+            scale.0 = (scale.0 + SCALE_STEP).min(1.0);
+            *under = 0.0;
+            if scale.0 >= 1.0 {
+                *wait = UPSCALE_AFTER;
+            }
+        }
+    }
+}
+
+/// Keep the offscreen image at the render scale's share of the area the game
+/// is drawn in. Its scale factor makes up the difference, so to everything
+/// else the main camera still seems to be the size of that area.
+fn size_offscreen_image(
+    settings: Res<Settings>,
+    scale: Res<RenderScale>,
+    image: Res<OffscreenImage>,
+    mut images: ResMut<Assets<Image>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<&mut Camera, (With<Camera2d>, Without<PresentCamera>)>,
+) {
+    let area = if settings.letterbox {
+        letterbox(window.physical_size().as_vec2()).size()
+    } else {
+        window.physical_size().as_vec2()
+    };
+    let size = (area * scale.0).round().as_uvec2().max(UVec2::ONE);
+    // Only borrowed mutably when it has to change, so it isn't re-uploaded.
+    let stale = images
+        .get(&image.0)
+        .is_some_and(|image| image.size() != size);
+    let target = if stale {
+        images.get_mut(&image.0)
+    } else {
+        None
+    };
+    if let Some(image) = target {
+        image.resize(Extent3d {
+            width: size.x,
+            height: size.y,
+            ..default()
+        });
+    }
+
+    let scale_factor = FloatOrd(window.scale_factor() * size.x as f32 / area.x.max(1.0));
+    for mut camera in &mut camera_query {
+        let RenderTarget::Image(target) = &camera.target else {
+            continue;
+        };
+        if target.handle == image.0 && target.scale_factor != scale_factor {
+            camera.target = RenderTarget::Image(ImageRenderTarget {
+                handle: image.0.clone(),
+                scale_factor,
+            });
+        }
+    }
+}
@@ -0,0 +1,692 @@
+//! Recording runs and playing them back.
+//!
+//! Every attempt at the level is recorded: the actions held and the point
+//! aimed at on each frame, whether the game was paused, and how long the
+//! frame really took, along with the [`GameRng`] seed, the mutators, the
+//! physics quality and the [`Assists`] the attempt started with. When the
+//! attempt ends it's saved as [`LAST_REPLAY_FILE`]. Runs continued from a
+//! checkpoint aren't recorded, since they don't start from the level's start,
+//! and neither is the sandbox. Changing assists partway through stops the
+//! recording, since the replay would play out differently.
+//!
+//! A replay is played back from the title screen, or on native builds by
+//! starting the game with `--replay <file>`. Playback feeds the recorded
+//! actions through a [`ScriptedInput`], and makes every frame last exactly as
+//! long as the recorded one did. Physics runs on Avian's fixed timestep, and
+//! whatever was left over of it from before the level is dropped as the level
+//! starts, so the same frames make for the same physics steps. Escape stops
+//! playback early. What happens in a replay doesn't count toward progress or
+//! touch the autosave.
+//!
+//! Replays also keep where each hook attached, for showing the route taken
+//! without playing the whole attempt back, and the frames checkpoints were
+//! reached on, for the [replay viewer](crate::replay_viewer) to jump to.
+//!
+//! Playback can be paused, stepped a frame at a time, slowed down and sought
+//! through [`PlaybackControl`]. Recorded frames are only ever played in
+//! order and at their recorded length, so slowing down holds each frame for
+//! a few, and seeking back starts the level over and plays up to the frame
+//! at full speed, the same way the TAS tools load a state.
+//!
+//! Aim is recorded to the nearest pixel, and rounded the same way while
+//! recording so the run plays out as it's replayed. Frames with the same
+//! input are stored together, which keeps replays small.
+
+use std::time::Duration;
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSystems, PausableSystems, Pause,
+    asset_tracking::ResourceHandles,
+    game::{
+        aim::{PlayerAim, update_player_aim},
+        assists::Assists,
+        calibration::Calibration,
+        chain::ChainHooked,
+        checkpoint::{PendingResume, ReachedCheckpoint, resume_from_checkpoint},
+        game_rng::GameRng,
+        mutators::RunMutators,
+        physics_quality::PhysicsQuality,
+        sandbox::Sandbox,
+    },
+    input::{ActionInput, InputAction, ScriptedInput},
+    packed::{self, PackedKind},
+    persistence::Settings,
+    platform::ActivePlatform,
+    screens::{Screen, gameplay::restart_level},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ReplayMode>();
+    app.init_resource::<AttemptHooks>();
+    app.init_resource::<AttemptCheckpoints>();
+    app.init_resource::<AttemptAssists>();
+    app.init_resource::<PlaybackControl>();
+    app.configure_sets(Update, PausableSystems.run_if(playback_runs));
+
+    #[cfg(not(target_family = "wasm"))]
+    app.add_systems(Startup, read_replay_argument);
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        start_recording.before(resume_from_checkpoint),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), finish_attempt);
+    app.add_systems(
+        First,
+        (step_playback, replay_frame_timing)
+            .chain()
+            .run_if(playing)
+            .before(TimeSystem),
+    );
+    app.add_systems(
+        Update,
+        (
+            feed_replay_input
+                .run_if(playing)
+                .before(AppSystems::RecordInput),
+            (
+                aim_from_replay.run_if(playing),
+                record_frame.run_if(recording),
+            )
+                .in_set(AppSystems::RecordInput)
+                .after(update_player_aim),
+            (record_hooks, record_checkpoints)
+                .in_set(AppSystems::Update)
+                .run_if(recording),
+            stop_playback.run_if(playing.and(input_just_pressed(KeyCode::Escape))),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Last,
+        advance_playback.run_if(in_state(Screen::Gameplay).and(playing)),
+    );
+}
+
+/// Where the last attempt at the level is saved.
+pub const LAST_REPLAY_FILE: &str = "last_replay.replay";
+/// Bumped whenever replays saved by older builds would play back wrong.
+const REPLAY_VERSION: u32 = 2;
+
+/// One frame of input.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ReplayFrame {
+    /// How long the frame really took, in nanoseconds.
+    nanos: u32,
+    /// The actions held, as a set of bits indexed by [`InputAction::ALL`].
+    press: u32,
+    aim: Option<IVec2>,
+    paused: bool,
+}
+
+/// The same input for a number of frames in a row. The frames' lengths are
+/// kept apart from it, since they hardly ever repeat.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ReplaySegment {
+    frames: u32,
+    press: u32,
+    aim: Option<IVec2>,
+    paused: bool,
+}
+
+/// A recorded attempt, as it's saved.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    version: u32,
+    seed: u64,
+    mutators: u8,
+    physics_quality: PhysicsQuality,
+    assists: Assists,
+    /// Each frame's length, in nanoseconds.
+    nanos: Vec<u32>,
+    segments: Vec<ReplaySegment>,
+    /// Where hooks attached, in order. Only for showing, playback doesn't
+    /// need them.
+    #[serde(default)]
+    hooks: Vec<Vec2>,
+    /// The frames checkpoints were reached on, in order.
+    #[serde(default)]
+    checkpoints: Vec<u32>,
+}
+
+impl Replay {
+    fn new(
+        seed: u64,
+        mutators: RunMutators,
+        physics_quality: PhysicsQuality,
+        assists: Assists,
+        frames: &[ReplayFrame],
+        hooks: &[Vec2],
+        checkpoints: &[u32],
+    ) -> Self {
+        let mut segments: Vec<ReplaySegment> = Vec::new();
+        for frame in frames {
+            match segments.last_mut() {
+                Some(segment)
+                    if segment.press == frame.press
+                        && segment.aim == frame.aim
+                        && segment.paused == frame.paused =>
+                {
+                    segment.frames += 1;
+                }
+                _ => segments.push(ReplaySegment {
+                    frames: 1,
+                    press: frame.press,
+                    aim: frame.aim,
+                    paused: frame.paused,
+                }),
+            }
+        }
+        Self {
+            version: REPLAY_VERSION,
+            seed,
+            mutators: mutators.bits(),
+            physics_quality,
+            assists,
+            nanos: frames.iter().map(|frame| frame.nanos).collect(),
+            segments,
+            hooks: hooks.to_vec(),
+            checkpoints: checkpoints.to_vec(),
+        }
+    }
+
+    /// Where hooks attached during the attempt, in order.
+    pub fn hooks(&self) -> &[Vec2] {
+        &self.hooks
+    }
+
+    fn frames(&self) -> Vec<ReplayFrame> {
+        self.segments
+            .iter()
+            .flat_map(|segment| {
+                std::iter::repeat_n(
+                    (segment.press, segment.aim, segment.paused),
+                    segment.frames as usize,
+                )
+            })
+            .zip(&self.nanos)
+            .map(|((press, aim, paused), &nanos)| ReplayFrame {
+                nanos,
+                press,
+                aim,
+                paused,
+            })
+            .collect()
+    }
+
+    /// The replay [packed](crate::packed) the way it's saved.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        packed::pack(PackedKind::Replay, REPLAY_VERSION, self).map_err(|error| error.to_string())
+    }
+
+    /// Parse a saved replay, refusing ones this build can't play back.
+    /// Replays saved as RON by older builds are still read.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let replay: Self = if packed::is_packed(bytes) {
+            packed::unpack(PackedKind::Replay, REPLAY_VERSION, bytes)
+                .map_err(|error| error.to_string())?
+        } else {
+            ron::de::from_bytes(bytes).map_err(|error| error.to_string())?
+        };
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "the replay is version {}, this build plays version {REPLAY_VERSION}",
+                replay.version
+            ));
+        }
+        Ok(replay)
+    }
+}
+
+/// Read the last attempt's replay, if there is a usable one.
+pub fn read_last_replay(platform: &ActivePlatform) -> Option<Replay> {
+    let data = platform.0.read_save(LAST_REPLAY_FILE).ok()?;
+    Replay::from_bytes(&data)
+        .inspect_err(|error| warn!("Ignoring the last replay: {error}"))
+        .ok()
+}
+
+fn press_bits(input: &ActionInput) -> u32 {
+    InputAction::ALL
+        .into_iter()
+        .enumerate()
+        // A restart starts a new attempt, so it's never part of one.
+        .filter(|&(_, action)| action != InputAction::Restart && input.pressed(action))
+        .fold(0, |bits, (i, _)| bits | (1 << i))
+}
+
+fn pressed_actions(bits: u32) -> Vec<InputAction> {
+    InputAction::ALL
+        .into_iter()
+        .enumerate()
+        .filter(|&(i, _)| bits & (1 << i) != 0)
+        .map(|(_, action)| action)
+        .collect()
+}
+
+/// What's being recorded or played back.
+#[derive(Resource, Default)]
+pub enum ReplayMode {
+    /// Not recording, e.g. during a run continued from a checkpoint.
+    #[default]
+    Idle,
+    Recording(Vec<ReplayFrame>),
+    Playing {
+        frames: Vec<ReplayFrame>,
+        /// The next frame to play.
+        cursor: usize,
+        /// The seed the attempt started with, for starting it over.
+        seed: u64,
+        /// The frames checkpoints were reached on.
+        checkpoints: Vec<u32>,
+        /// The frame timing to go back to afterward.
+        strategy: TimeUpdateStrategy,
+        /// The player's own physics quality, to go back to afterward.
+        physics_quality: PhysicsQuality,
+        /// The player's own assists, to go back to afterward.
+        assists: Assists,
+    },
+    /// A replay played until the level ended, so whatever comes after it
+    /// wasn't the player's doing.
+    Watched,
+}
+
+impl ReplayMode {
+    /// Whether what's happening in the game is a replay.
+    pub fn is_replaying(&self) -> bool {
+        matches!(self, Self::Playing { .. } | Self::Watched)
+    }
+}
+
+/// A run condition for while a replay plays or has just played.
+pub fn replaying(mode: Res<ReplayMode>) -> bool {
+    mode.is_replaying()
+}
+
+pub fn playing(mode: Res<ReplayMode>) -> bool {
+    matches!(*mode, ReplayMode::Playing { .. })
+}
+
+fn recording(mode: Res<ReplayMode>) -> bool {
+    matches!(*mode, ReplayMode::Recording(_))
+}
+
+/// Where hooks have attached so far in the attempt being recorded.
+#[derive(Resource, Default, Debug)]
+pub struct AttemptHooks(pub Vec<Vec2>);
+
+/// The frames checkpoints have been reached on so far in the attempt being
+/// recorded.
+#[derive(Resource, Default, Debug)]
+struct AttemptCheckpoints(Vec<u32>);
+
+/// The assists the attempt being recorded started with.
+#[derive(Resource, Default, Debug)]
+struct AttemptAssists(Assists);
+
+/// Slow playback plays a recorded frame every this many app frames.
+pub const SLOWDOWNS: [u32; 4] = [1, 2, 4, 8];
+
+/// How playback is being steered, from the replay viewer.
+#[derive(Resource, Default, Debug)]
+pub struct PlaybackControl {
+    pub paused: bool,
+    /// Play just one frame, then stay paused.
+    pub step: bool,
+    /// Index into [`SLOWDOWNS`].
+    pub slowdown: usize,
+    /// Play at full speed up to this frame, then pause.
+    pub seek: Option<usize>,
+    /// App frames since the last recorded frame played.
+    waited: u32,
+    /// Whether a recorded frame plays this app frame.
+    running: bool,
+}
+
+/// Gameplay only moves on while a recorded frame plays.
+fn playback_runs(mode: Res<ReplayMode>, control: Res<PlaybackControl>) -> bool {
+    !matches!(*mode, ReplayMode::Playing { .. }) || control.running
+}
+
+/// Go to `frame` of the replay that's playing, and pause there. Going back
+/// starts the level over and plays up to it.
+pub fn seek_playback(world: &mut World, frame: usize) {
+    let ReplayMode::Playing {
+        frames,
+        cursor,
+        seed,
+        ..
+    } = world.resource::<ReplayMode>()
+    else {
+        return;
+    };
+    let (frame, cursor, seed) = (frame.min(frames.len() - 1), *cursor, *seed);
+    if frame >= cursor {
+        let mut control = world.resource_mut::<PlaybackControl>();
+        control.seek = (frame > cursor).then_some(frame);
+        control.paused = true;
+        return;
+    }
+
+    // Starting over runs the exit systems, which would wrap the replay up.
+    let mut mode = std::mem::take(&mut *world.resource_mut::<ReplayMode>());
+    world.resource_mut::<GameRng>().seed_next_level(seed);
+    restart_level(world);
+    world.remove_resource::<ScriptedInput>();
+    if let ReplayMode::Playing { cursor, .. } = &mut mode {
+        *cursor = 0;
+    }
+    world.insert_resource(mode);
+    let mut control = world.resource_mut::<PlaybackControl>();
+    control.seek = (frame > 0).then_some(frame);
+    control.paused = true;
+}
+
+/// Start playing `replay` back from the start of the level.
+pub fn start_playback(world: &mut World, replay: Replay) {
+    let frames = replay.frames();
+    if frames.is_empty() {
+        warn!("The replay has no frames");
+        return;
+    }
+    world.resource_mut::<GameRng>().seed_next_level(replay.seed);
+    *world.resource_mut::<RunMutators>() = RunMutators::from_bits(replay.mutators);
+    world.resource_mut::<PendingResume>().0 = None;
+    let physics_quality = std::mem::replace(
+        &mut world.resource_mut::<Settings>().physics_quality,
+        replay.physics_quality,
+    );
+    let assists = std::mem::replace(&mut *world.resource_mut::<Assists>(), replay.assists);
+    let strategy = world
+        .remove_resource::<TimeUpdateStrategy>()
+        .unwrap_or_default();
+    world.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_nanos(
+        frames[0].nanos.into(),
+    )));
+    world.insert_resource(ReplayMode::Playing {
+        frames,
+        cursor: 0,
+        seed: replay.seed,
+        checkpoints: replay.checkpoints,
+        strategy,
+        physics_quality,
+        assists,
+    });
+    world.insert_resource(PlaybackControl::default());
+    let next = if world.resource::<ResourceHandles>().is_all_done() {
+        Screen::Gameplay
+    } else {
+        Screen::Loading
+    };
+    world.resource_mut::<NextState<Screen>>().set(next);
+}
+
+/// Play the replay passed with `--replay <file>` right away.
+#[cfg(not(target_family = "wasm"))]
+fn read_replay_argument(mut commands: Commands) {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(path) = args
+        .iter()
+        .position(|arg| arg == "--replay")
+        .and_then(|i| args.get(i + 1))
+    else {
+        return;
+    };
+    let replay = std::fs::read(path)
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| Replay::from_bytes(&bytes));
+    match replay {
+        Ok(replay) => {
+            info!("Playing back {path}");
+            commands.queue(move |world: &mut World| start_playback(world, replay));
+        }
+        Err(error) => error!("Couldn't play back {path}: {error}"),
+    }
+}
+
+/// Record the attempt that's starting, and line physics up with the start of
+/// the level.
+fn start_recording(
+    mut mode: ResMut<ReplayMode>,
+    mut hooks: ResMut<AttemptHooks>,
+    mut checkpoints: ResMut<AttemptCheckpoints>,
+    mut attempt_assists: ResMut<AttemptAssists>,
+    assists: Res<Assists>,
+    pending: Res<PendingResume>,
+    sandbox: Res<Sandbox>,
+    calibration: Res<Calibration>,
+    mut fixed: ResMut<Time<Fixed>>,
+) {
+    let overstep = fixed.overstep();
+    fixed.discard_overstep(overstep);
+    hooks.0.clear();
+    checkpoints.0.clear();
+    attempt_assists.0 = assists.clone();
+    if matches!(*mode, ReplayMode::Playing { .. }) {
+        return;
+    }
+    *mode = if pending.0.is_some() || sandbox.active || calibration.active {
+        ReplayMode::Idle
+    } else {
+        ReplayMode::Recording(Vec::new())
+    };
+}
+
+fn record_frame(
+    mut mode: ResMut<ReplayMode>,
+    time: Res<Time<Real>>,
+    pause: Res<State<Pause>>,
+    input: ActionInput,
+    assists: Res<Assists>,
+    attempt_assists: Res<AttemptAssists>,
+    mut aim: ResMut<PlayerAim>,
+) {
+    if *assists != attempt_assists.0 {
+        info!("Assists changed, so the attempt won't be recorded");
+        *mode = ReplayMode::Idle;
+        return;
+    }
+    let ReplayMode::Recording(frames) = &mut *mode else {
+        return;
+    };
+    let rounded = aim.target.map(|target| target.round().as_ivec2());
+    if let Some(target) = rounded {
+        aim.target = Some(target.as_vec2());
+    }
+    frames.push(ReplayFrame {
+        nanos: time.delta().as_nanos().min(u32::MAX.into()) as u32,
+        press: press_bits(&input),
+        aim: rounded,
+        paused: pause.get().0,
+    });
+}
+
+fn record_hooks(mut hooked: EventReader<ChainHooked>, mut hooks: ResMut<AttemptHooks>) {
+    hooks.0.extend(hooked.read().map(|event| event.point));
+}
+
+fn record_checkpoints(
+    mode: Res<ReplayMode>,
+    reached: Res<ReachedCheckpoint>,
+    mut checkpoints: ResMut<AttemptCheckpoints>,
+) {
+    let ReplayMode::Recording(frames) = &*mode else {
+        return;
+    };
+    if reached.is_changed() && reached.0.is_some() {
+        // Recorded after the frame it was reached on
+        checkpoints.0.push(frames.len().saturating_sub(1) as u32);
+    }
+}
+
+/// Save the attempt that just ended, or wrap up the replay that played.
+fn finish_attempt(
+    mut commands: Commands,
+    mut mode: ResMut<ReplayMode>,
+    mut settings: ResMut<Settings>,
+    mut assists: ResMut<Assists>,
+    attempt_assists: Res<AttemptAssists>,
+    game_rng: Res<GameRng>,
+    mutators: Res<RunMutators>,
+    hooks: Res<AttemptHooks>,
+    checkpoints: Res<AttemptCheckpoints>,
+    mut control: ResMut<PlaybackControl>,
+    mut time: ResMut<Time<Virtual>>,
+    platform: Res<ActivePlatform>,
+) {
+    match std::mem::take(&mut *mode) {
+        ReplayMode::Recording(frames) if !frames.is_empty() => {
+            let replay = Replay::new(
+                game_rng.seed(),
+                *mutators,
+                settings.physics_quality,
+                attempt_assists.0.clone(),
+                &frames,
+                &hooks.0,
+                &checkpoints.0,
+            );
+            let result = replay.to_bytes().and_then(|bytes| {
+                platform
+                    .0
+                    .write_save(LAST_REPLAY_FILE, &bytes)
+                    .map_err(|error| error.to_string())
+            });
+            if let Err(error) = result {
+                warn!("Couldn't save the replay: {error}");
+            }
+        }
+        ReplayMode::Playing {
+            strategy,
+            physics_quality,
+            assists: own_assists,
+            ..
+        } => {
+            commands.insert_resource(strategy);
+            commands.remove_resource::<ScriptedInput>();
+            settings.physics_quality = physics_quality;
+            *assists = own_assists;
+            *control = PlaybackControl::default();
+            time.unpause();
+            *mode = ReplayMode::Watched;
+        }
+        _ => {}
+    }
+}
+
+/// Decide whether a recorded frame plays this app frame, and stop game time
+/// if it doesn't.
+fn step_playback(mut control: ResMut<PlaybackControl>, mut time: ResMut<Time<Virtual>>) {
+    let was_running = control.running;
+    control.running = if control.seek.is_some() {
+        true
+    } else if control.paused {
+        std::mem::take(&mut control.step)
+    } else {
+        control.waited += 1;
+        if control.waited >= SLOWDOWNS[control.slowdown] {
+            control.waited = 0;
+            true
+        } else {
+            false
+        }
+    };
+    if control.running != was_running {
+        if control.running {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+}
+
+/// Make the frame last as long as the recorded one, and be paused if it was.
+fn replay_frame_timing(
+    control: Res<PlaybackControl>,
+    mode: Res<ReplayMode>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    pause: Res<State<Pause>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+) {
+    let ReplayMode::Playing { frames, cursor, .. } = &*mode else {
+        return;
+    };
+    let Some(frame) = frames.get(*cursor).filter(|_| control.running) else {
+        return;
+    };
+    *strategy = TimeUpdateStrategy::ManualDuration(Duration::from_nanos(frame.nanos.into()));
+    if pause.get().0 != frame.paused {
+        next_pause.set(Pause(frame.paused));
+    }
+}
+
+fn feed_replay_input(
+    mut commands: Commands,
+    mode: Res<ReplayMode>,
+    control: Res<PlaybackControl>,
+    scripted: Option<ResMut<ScriptedInput>>,
+) {
+    let ReplayMode::Playing { frames, cursor, .. } = &*mode else {
+        return;
+    };
+    if !control.running {
+        // Held where it is, so nothing counts as just pressed again.
+        let Some(mut scripted) = scripted else {
+            return;
+        };
+        let pressed = scripted.pressed.clone();
+        scripted.advance(pressed);
+        return;
+    }
+    let pressed = frames
+        .get(*cursor)
+        .map(|frame| pressed_actions(frame.press))
+        .unwrap_or_default();
+    match scripted {
+        Some(mut scripted) => scripted.advance(pressed),
+        None => commands.insert_resource(ScriptedInput {
+            pressed,
+            previous: Vec::new(),
+        }),
+    }
+}
+
+fn aim_from_replay(mode: Res<ReplayMode>, mut aim: ResMut<PlayerAim>) {
+    let ReplayMode::Playing { frames, cursor, .. } = &*mode else {
+        return;
+    };
+    if let Some(frame) = frames.get(*cursor) {
+        aim.target = frame.aim.map(|target| target.as_vec2());
+    }
+}
+
+/// Move on to the next frame, and leave once the replay is over.
+fn advance_playback(
+    mut mode: ResMut<ReplayMode>,
+    mut control: ResMut<PlaybackControl>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let ReplayMode::Playing { frames, cursor, .. } = &mut *mode else {
+        return;
+    };
+    if !control.running {
+        return;
+    }
+    *cursor += 1;
+    if control.seek.is_some_and(|frame| *cursor >= frame) {
+        control.seek = None;
+    }
+    if *cursor == frames.len() {
+        info!("The replay ended after {cursor} frames");
+        next_screen.set(Screen::Title);
+    }
+}
+
+fn stop_playback(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
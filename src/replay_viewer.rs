@@ -0,0 +1,261 @@
+//! The replay viewer: a timeline and controls shown while a replay plays.
+//!
+//! The timeline along the bottom shows how far playback has got, with the
+//! frames checkpoints were reached on marked. Clicking anywhere on it seeks
+//! there. From the keyboard:
+//!
+//! - Space pauses and resumes, `.` steps a frame forward and `,` a frame back.
+//! - S cycles through slower playback speeds.
+//! - `[` and `]` jump to the previous and next checkpoint.
+//! - F frees the camera from the player, to be moved with the arrow keys.
+//!
+//! Playback itself is steered through [`PlaybackControl`], see
+//! [`replay`](crate::replay) for how.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::camera::FreeCamera,
+    replay::{PlaybackControl, ReplayMode, SLOWDOWNS, playing, seek_playback},
+    screens::Screen,
+    theme::{layout::HudAnchor, palette::LABEL_TEXT},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ReplayViewer>();
+    app.register_type::<TimelineCell>();
+    app.register_type::<ViewerStatus>();
+
+    app.add_systems(
+        Update,
+        (
+            spawn_viewer,
+            handle_viewer_keys,
+            pan_free_camera.run_if(resource_exists::<FreeCamera>),
+            update_timeline,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(playing)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), free_camera_off);
+}
+
+/// How many cells the timeline is split into. Each is a place to seek to.
+const TIMELINE_CELLS: usize = 60;
+const TIMELINE_HEIGHT: f32 = 12.0;
+const PLAYED_COLOR: Color = Color::srgb(0.4, 0.6, 0.95);
+const UNPLAYED_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+const CHECKPOINT_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+/// How fast the free camera moves, in pixels per second.
+const FREE_CAMERA_SPEED: f32 = 600.0;
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct ReplayViewer;
+
+/// The `index`th cell of the timeline.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct TimelineCell {
+    index: usize,
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct ViewerStatus;
+
+/// The frame the `index`th timeline cell starts on, out of `frames`.
+fn cell_start(index: usize, frames: usize) -> usize {
+    index * frames / TIMELINE_CELLS
+}
+
+/// Starting the level over for a seek takes the viewer with it, so it's
+/// spawned whenever it's missing.
+fn spawn_viewer(mut commands: Commands, viewer_query: Query<(), With<ReplayViewer>>) {
+    if !viewer_query.is_empty() {
+        return;
+    }
+    commands
+        .spawn((
+            Name::new("Replay Viewer"),
+            ReplayViewer,
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            HudAnchor::Bottom(10.0),
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Replay Status"),
+                ViewerStatus,
+                Text::default(),
+                TextFont::from_font_size(18.0),
+                TextColor(LABEL_TEXT),
+                Pickable::IGNORE,
+            ));
+            parent
+                .spawn((
+                    Name::new("Timeline"),
+                    Node {
+                        width: Val::Percent(80.0),
+                        height: Val::Px(TIMELINE_HEIGHT),
+                        column_gap: Val::Px(1.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    for index in 0..TIMELINE_CELLS {
+                        parent
+                            .spawn((
+                                Name::new(format!("Timeline Cell {index}")),
+                                TimelineCell { index },
+                                Node {
+                                    flex_grow: 1.0,
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                BackgroundColor(UNPLAYED_COLOR),
+                            ))
+                            .observe(seek_to_cell);
+                    }
+                });
+        });
+}
+
+fn seek_to_cell(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mode: Res<ReplayMode>,
+    cell_query: Query<&TimelineCell>,
+) {
+    let ReplayMode::Playing { frames, .. } = &*mode else {
+        return;
+    };
+    let Ok(cell) = cell_query.get(trigger.target()) else {
+        return;
+    };
+    let frame = cell_start(cell.index, frames.len());
+    commands.queue(move |world: &mut World| seek_playback(world, frame));
+}
+
+fn handle_viewer_keys(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mode: Res<ReplayMode>,
+    mut control: ResMut<PlaybackControl>,
+    free_camera: Option<Res<FreeCamera>>,
+) {
+    let ReplayMode::Playing {
+        cursor,
+        checkpoints,
+        ..
+    } = &*mode
+    else {
+        return;
+    };
+    let cursor = *cursor;
+    if input.just_pressed(KeyCode::Space) {
+        control.paused = !control.paused;
+    }
+    if input.just_pressed(KeyCode::Period) {
+        control.paused = true;
+        control.step = true;
+    }
+    if input.just_pressed(KeyCode::KeyS) {
+        control.slowdown = (control.slowdown + 1) % SLOWDOWNS.len();
+    }
+    if input.just_pressed(KeyCode::KeyF) {
+        if free_camera.is_some() {
+            commands.remove_resource::<FreeCamera>();
+        } else {
+            commands.init_resource::<FreeCamera>();
+        }
+    }
+
+    // Seeking to a checkpoint stops right after the frame it was reached on.
+    let mut marks = checkpoints.iter().map(|&frame| frame as usize + 1);
+    let seek = if input.just_pressed(KeyCode::Comma) {
+        Some(cursor.saturating_sub(1))
+    } else if input.just_pressed(KeyCode::BracketLeft) {
+        Some(marks.rfind(|&mark| mark < cursor).unwrap_or_default())
+    } else if input.just_pressed(KeyCode::BracketRight) {
+        marks.find(|&mark| mark > cursor)
+    } else {
+        None
+    };
+    if let Some(frame) = seek {
+        commands.queue(move |world: &mut World| seek_playback(world, frame));
+    }
+}
+
+fn pan_free_camera(
+    time: Res<Time<Real>>,
+    input: Res<ButtonInput<KeyCode>>,
+    mut camera: Single<&mut Transform, With<Camera2d>>,
+) {
+    let direction = Vec2::new(
+        input.pressed(KeyCode::ArrowRight) as i32 as f32
+            - input.pressed(KeyCode::ArrowLeft) as i32 as f32,
+        input.pressed(KeyCode::ArrowUp) as i32 as f32
+            - input.pressed(KeyCode::ArrowDown) as i32 as f32,
+    );
+    camera.translation += (direction * FREE_CAMERA_SPEED * time.delta_secs()).extend(0.0);
+}
+
+fn update_timeline(
+    mode: Res<ReplayMode>,
+    control: Res<PlaybackControl>,
+    free_camera: Option<Res<FreeCamera>>,
+    mut cell_query: Query<(&TimelineCell, &mut BackgroundColor)>,
+    mut status: Single<&mut Text, With<ViewerStatus>>,
+) {
+    let ReplayMode::Playing {
+        frames,
+        cursor,
+        checkpoints,
+        ..
+    } = &*mode
+    else {
+        return;
+    };
+    let frames = frames.len();
+    for (cell, mut background) in &mut cell_query {
+        let start = cell_start(cell.index, frames);
+        let end = cell_start(cell.index + 1, frames);
+        let color = if checkpoints
+            .iter()
+            .any(|&frame| (start..end).contains(&(frame as usize)))
+        {
+            CHECKPOINT_COLOR
+        } else if start < *cursor {
+            PLAYED_COLOR
+        } else {
+            UNPLAYED_COLOR
+        };
+        background.set_if_neq(BackgroundColor(color));
+    }
+
+    let state = if control.seek.is_some() {
+        "Seeking"
+    } else if control.paused {
+        "Paused"
+    } else {
+        "Playing"
+    };
+    let mut text = format!("{state}  frame {cursor} of {frames}");
+    if control.slowdown > 0 {
+        text += &format!("  1/{} speed", SLOWDOWNS[control.slowdown]);
+    }
+    if free_camera.is_some() {
+        text += "  free camera";
+    }
+    status.0 = text;
+}
+
+fn free_camera_off(mut commands: Commands) {
+    commands.remove_resource::<FreeCamera>();
+}
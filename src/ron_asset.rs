@@ -0,0 +1,90 @@
+//! Loading assets written as RON.
+//!
+//! Most of the game's data files are a single RON value, read straight into
+//! an asset. [`RonAssetLoader`] does that for any asset that can be
+//! deserialized, so each of them only needs registering once, with the
+//! extensions it's stored under. Assets that point at other files start
+//! loading them in [`RonAssetLoader::after_load`].
+//!
+//! Levels and the controller database have loaders of their own: levels are
+//! migrated, unpacked and checked before they're used, and the controller
+//! database isn't RON.
+
+use std::fmt;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::de::DeserializeOwned;
+
+/// Something done to an asset after it's read, with the file's load context.
+pub type AfterLoad<T> = fn(&mut T, &mut LoadContext<'_>);
+
+/// Reads one RON file into a `T`.
+pub struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    after_load: AfterLoad<T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    /// A loader for files ending in any of `extensions`, without the dot.
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            after_load: |_, _| {},
+        }
+    }
+
+    /// Calls `after_load` on every asset once it's read, e.g. to load the
+    /// files it refers to as its dependencies.
+    pub fn after_load(self, after_load: AfterLoad<T>) -> Self {
+        Self { after_load, ..self }
+    }
+}
+
+#[derive(Debug)]
+pub enum RonLoadError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl fmt::Display for RonLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RonLoadError::Io(error) => write!(f, "could not read the file: {error}"),
+            RonLoadError::Parse(error) => write!(f, "the file is corrupt: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RonLoadError {}
+
+impl From<std::io::Error> for RonLoadError {
+    fn from(error: std::io::Error) -> Self {
+        RonLoadError::Io(error)
+    }
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = RonLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut asset = ron::de::from_bytes(&bytes).map_err(RonLoadError::Parse)?;
+        (self.after_load)(&mut asset, load_context);
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
@@ -1,26 +1,48 @@
 //! The screen state for the main gameplay.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+use bevy::{
+    input::common_conditions::input_just_pressed, prelude::*, ui::Val::*, window::WindowFocused,
+};
 
-use crate::{Pause, demo::level::spawn_level, menus::Menu, screens::Screen};
+use crate::{
+    Pause,
+    game::{level::spawn_level, victory::VictoryOrbit},
+    input::{ActionInput, InputAction},
+    menus::Menu,
+    replay::replaying,
+    screens::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Gameplay), spawn_level);
 
-    // Toggle pause on key press.
+    // Toggle pause on key press, and pause when the window loses focus.
     app.add_systems(
         Update,
         (
+            // Replays pause where they were paused, and Escape stops them.
+            // There's nothing to pause once the goal is reached.
             (pause, spawn_pause_overlay, open_pause_menu).run_if(
                 in_state(Screen::Gameplay)
                     .and(in_state(Menu::None))
-                    .and(input_just_pressed(KeyCode::KeyP).or(input_just_pressed(KeyCode::Escape))),
+                    .and(not(replaying))
+                    .and(not(resource_exists::<VictoryOrbit>))
+                    .and(
+                        input_just_pressed(KeyCode::KeyP)
+                            .or(input_just_pressed(KeyCode::Escape))
+                            .or(focus_lost),
+                    ),
             ),
             close_menu.run_if(
                 in_state(Screen::Gameplay)
                     .and(not(in_state(Menu::None)))
                     .and(input_just_pressed(KeyCode::KeyP)),
             ),
+            restart_level.run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Menu::None))
+                    .and(restart_pressed),
+            ),
         ),
     );
     app.add_systems(OnExit(Screen::Gameplay), (close_menu, unpause));
@@ -38,7 +60,12 @@ fn pause(mut next_pause: ResMut<NextState<Pause>>) {
     next_pause.set(Pause(true));
 }
 
-fn spawn_pause_overlay(mut commands: Commands) {
+fn focus_lost(mut focused: EventReader<WindowFocused>) -> bool {
+    focused.read().any(|event| !event.focused)
+}
+
+/// Darken the level while the game is paused.
+pub fn spawn_pause_overlay(mut commands: Commands) {
     commands.spawn((
         Name::new("Pause Overlay"),
         Node {
@@ -59,3 +86,27 @@ fn open_pause_menu(mut next_menu: ResMut<NextState<Menu>>) {
 fn close_menu(mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::None);
 }
+
+fn restart_pressed(input: ActionInput) -> bool {
+    input.just_pressed(InputAction::Restart)
+}
+
+/// Start the level over without leaving [`Screen::Gameplay`], so nothing has
+/// to load again. Runs the same exit and enter systems as a real transition,
+/// and despawns everything scoped to the screen in between.
+pub fn restart_level(world: &mut World) {
+    world.run_schedule(OnExit(Screen::Gameplay));
+    let scoped: Vec<Entity> = world
+        .query::<(Entity, &StateScoped<Screen>)>()
+        .iter(world)
+        .filter(|(_, scope)| scope.0 == Screen::Gameplay)
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in scoped {
+        // Children of other scoped entities are already gone.
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn();
+        }
+    }
+    world.run_schedule(OnEnter(Screen::Gameplay));
+}
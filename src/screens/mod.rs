@@ -1,7 +1,8 @@
 //! The game's main screen states and transitions between them.
 
-mod gameplay;
+pub mod gameplay;
 mod loading;
+mod results;
 mod splash;
 mod title;
 
@@ -13,6 +14,7 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         gameplay::plugin,
         loading::plugin,
+        results::plugin,
         splash::plugin,
         title::plugin,
     ));
@@ -27,4 +29,8 @@ pub enum Screen {
     Title,
     Loading,
     Gameplay,
+    /// How the player did, after reaching the end of the level.
+    Results,
+    /// The level editor, only reachable in dev builds.
+    Editor,
 }
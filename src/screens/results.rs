@@ -0,0 +1,91 @@
+//! The results screen, shown when the player reaches the end of the level.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        mutators::RunMutators,
+        objectives::{LevelObjectives, Score},
+        run_stats::{RunStats, format_time},
+    },
+    platform::ActivePlatform,
+    replay::{read_last_replay, start_playback},
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Results), spawn_results);
+}
+
+fn spawn_results(
+    mut commands: Commands,
+    score: Res<Score>,
+    objectives: Res<LevelObjectives>,
+    stats: Res<RunStats>,
+    mutators: Res<RunMutators>,
+) {
+    let multiplier = mutators.score_multiplier();
+    let medals = objectives.medals_won(&stats);
+    let medals = if medals.is_empty() {
+        "none".to_string()
+    } else {
+        medals
+            .iter()
+            .map(|medal| medal.label())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let par = |value: String, par: Option<String>| match par {
+        Some(par) => format!("{value} (par {par})"),
+        None => value,
+    };
+    commands.spawn((
+        widget::ui_root("Results"),
+        StateScoped(Screen::Results),
+        children![
+            widget::header("Level complete"),
+            widget::label(format!(
+                "Score {} (x{multiplier:.2}){}",
+                (score.points as f32 * multiplier).round() as u32,
+                if score.assisted { ", assisted" } else { "" }
+            )),
+            widget::label(par(
+                format!("Time {}", format_time(stats.time)),
+                objectives.par.map(|par| format_time(par.time)),
+            )),
+            widget::label(par(
+                format!("Hooks fired {}", stats.hooks_fired),
+                objectives.par.map(|par| par.hooks.to_string()),
+            )),
+            widget::label(format!(
+                "Found {} of {}",
+                score.collected.len(),
+                objectives.collectibles
+            )),
+            widget::label(format!(
+                "Secrets {} of {}",
+                score.secrets.len(),
+                objectives.secrets
+            )),
+            widget::label(format!("Medals {medals}")),
+            widget::button("Play again", play_again),
+            widget::button("Watch replay", watch_replay),
+            widget::button("Quit to title", quit_to_title),
+        ],
+    ));
+}
+
+fn play_again(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Gameplay);
+}
+
+fn watch_replay(_: Trigger<Pointer<Click>>, mut commands: Commands, platform: Res<ActivePlatform>) {
+    if let Some(replay) = read_last_replay(&platform) {
+        commands.queue(move |world: &mut World| start_playback(world, replay));
+    }
+}
+
+fn quit_to_title(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -23,6 +23,14 @@ pub struct InteractionPalette {
     pub pressed: Color,
 }
 
+/// Run condition for clicks that shouldn't also count when they're on a
+/// button, like firing a chain.
+pub fn pointing_at_button(interaction_query: Query<&Interaction>) -> bool {
+    interaction_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+}
+
 fn apply_interaction_palette(
     mut palette_query: Query<
         (&Interaction, &InteractionPalette, &mut BackgroundColor),
@@ -0,0 +1,156 @@
+//! Keeping the UI the same size and in view on any window.
+//!
+//! Everything is laid out for [`DESIGN_SIZE`], and [`UiScale`] grows or
+//! shrinks it to fit the window, so 16:9, 16:10 and 21:9 screens all see the
+//! same layout. HUD elements pinned to the screen edges get a [`HudAnchor`],
+//! which keeps them inside the safe area: [`Settings::safe_area`] in from
+//! every edge, and no wider than the designed aspect on ultrawide screens.
+//!
+//! With [`Settings::letterbox`] on, the camera only draws a centered
+//! [`DESIGN_ASPECT`] part of the window, with bars around it, and the UI is
+//! laid out within that part.
+
+use bevy::{
+    prelude::*,
+    render::camera::Viewport,
+    ui::Val::*,
+    window::{PrimaryWindow, WindowResized},
+};
+
+use crate::persistence::Settings;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HudAnchor>();
+
+    app.add_systems(
+        Update,
+        (letterbox_camera, scale_ui, place_hud_anchors)
+            .chain()
+            .run_if(
+                on_event::<WindowResized>
+                    .or(resource_changed::<Settings>)
+                    .or(any_match_filter::<Added<HudAnchor>>),
+            ),
+    );
+}
+
+/// The window size everything is laid out for, in logical pixels.
+pub const DESIGN_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+/// The aspect ratio the game is designed for, and letterboxed to.
+pub const DESIGN_ASPECT: f32 = DESIGN_SIZE.x / DESIGN_SIZE.y;
+
+/// Pins a HUD element to an edge or corner of the safe area, this many
+/// pixels in from it. Elements pinned to the top or bottom span the width of
+/// the screen, and should center their content.
+#[derive(Component, Reflect, Copy, Clone, Debug)]
+#[reflect(Component)]
+pub enum HudAnchor {
+    Top(f32),
+    Bottom(f32),
+    TopLeft(f32),
+    TopRight(f32),
+    BottomLeft(f32),
+    BottomRight(f32),
+}
+
+/// The part of a window of `size` that's drawn to when letterboxed: as big
+/// as fits at [`DESIGN_ASPECT`], centered.
+pub fn letterbox(size: Vec2) -> Rect {
+    let fitted = if size.x / size.y > DESIGN_ASPECT {
+        Vec2::new(size.y * DESIGN_ASPECT, size.y)
+    } else {
+        Vec2::new(size.x, size.x / DESIGN_ASPECT)
+    };
+    Rect::from_center_size(size / 2.0, fitted)
+}
+
+/// The cursor's position in the part of the window the level is drawn in,
+/// for [`Camera::viewport_to_world_2d`] on the main camera.
+pub fn view_cursor(window: &Window, settings: &Settings) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    if settings.letterbox {
+        Some(cursor - letterbox(window.size()).min)
+    } else {
+        Some(cursor)
+    }
+}
+
+/// The UI, and the image of the level behind it, go wherever the UI camera
+/// draws, so letterboxing it boxes both in.
+fn letterbox_camera(
+    settings: Res<Settings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera: Single<&mut Camera, With<IsDefaultUiCamera>>,
+) {
+    let viewport = settings.letterbox.then(|| {
+        let area = letterbox(window.physical_size().as_vec2());
+        Viewport {
+            physical_position: area.min.as_uvec2(),
+            physical_size: area.size().as_uvec2().max(UVec2::ONE),
+            ..default()
+        }
+    });
+    camera.viewport = viewport;
+}
+
+/// The size of the area the UI is laid out in, in logical pixels.
+fn ui_area(window: &Window, settings: &Settings) -> Vec2 {
+    if settings.letterbox {
+        letterbox(window.size()).size()
+    } else {
+        window.size()
+    }
+}
+
+fn scale_ui(
+    settings: Res<Settings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let scale = (ui_area(&window, &settings) / DESIGN_SIZE).min_element();
+    if scale > 0.0 && ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+}
+
+fn place_hud_anchors(
+    settings: Res<Settings>,
+    ui_scale: Res<UiScale>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut anchor_query: Query<(&HudAnchor, &mut Node)>,
+) {
+    // Node sizes are multiplied by the UI scale, so the margins are divided
+    // by it.
+    let size = ui_area(&window, &settings) / ui_scale.0;
+    let ultrawide = (size.x - size.y * DESIGN_ASPECT).max(0.0) / 2.0;
+    let margin = Vec2::new(
+        size.x * settings.safe_area + ultrawide,
+        size.y * settings.safe_area,
+    );
+
+    for (&anchor, mut node) in &mut anchor_query {
+        let (inset, top, left, right) = match anchor {
+            HudAnchor::Top(inset) => (inset, true, true, true),
+            HudAnchor::Bottom(inset) => (inset, false, true, true),
+            HudAnchor::TopLeft(inset) => (inset, true, true, false),
+            HudAnchor::TopRight(inset) => (inset, true, false, true),
+            HudAnchor::BottomLeft(inset) => (inset, false, true, false),
+            HudAnchor::BottomRight(inset) => (inset, false, false, true),
+        };
+        // Top and bottom elements span the safe area rather than being inset
+        // from its sides.
+        let spans = left && right;
+        let side = if spans { margin.x } else { margin.x + inset };
+        node.position_type = PositionType::Absolute;
+        (node.top, node.bottom) = if top {
+            (Px(margin.y + inset), Auto)
+        } else {
+            (Auto, Px(margin.y + inset))
+        };
+        node.left = if left { Px(side) } else { Auto };
+        node.right = if right { Px(side) } else { Auto };
+        if spans {
+            node.width = Auto;
+        }
+    }
+}
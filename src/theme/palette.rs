@@ -3,6 +3,9 @@ use bevy::prelude::*;
 /// #ddd369
 pub const LABEL_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 
+/// #e0605a
+pub const CONFLICT_TEXT: Color = Color::srgb(0.878, 0.376, 0.353);
+
 /// #fcfbcc
 pub const HEADER_TEXT: Color = Color::srgb(0.988, 0.984, 0.800);
 